@@ -5,6 +5,11 @@ use cargo_metadata::camino::Utf8PathBuf;
 
 use crate::{
     command::{docker::create_docker_command, local::create_local_command, utils::execute_command},
+    features::validate_features,
+    layout::validate_memory_layout,
+    provenance::{source_sha256, write_provenance, PROVENANCE_EXTENSION},
+    report::check_elf_sizes,
+    shared::validate_shared_crates,
     utils::{cargo_rerun_if_changed, current_datetime},
     BuildArgs, WarningLevel, BUILD_TARGET, HELPER_TARGET_SUBDIR,
 };
@@ -32,16 +37,22 @@ pub fn execute_build_program(
     let program_dir: Utf8PathBuf =
         program_dir.try_into().expect("Failed to convert PathBuf to Utf8PathBuf");
 
+    validate_memory_layout(args)?;
+
     // Get the program metadata.
     let program_metadata_file = program_dir.join("Cargo.toml");
     let mut program_metadata_cmd = cargo_metadata::MetadataCommand::new();
     let program_metadata = program_metadata_cmd.manifest_path(program_metadata_file).exec()?;
 
+    validate_features(args, &program_metadata)?;
+    validate_shared_crates(args, &program_metadata)?;
+
     // Get the command corresponding to Docker or local build.
-    let cmd = if args.docker {
-        create_docker_command(args, &program_dir, &program_metadata)?
+    let (cmd, docker_image) = if args.docker {
+        let (cmd, image) = create_docker_command(args, &program_dir, &program_metadata)?;
+        (cmd, Some(image))
     } else {
-        create_local_command(args, &program_dir, &program_metadata)
+        (create_local_command(args, &program_dir, &program_metadata), None)
     };
 
     let target_elf_paths = generate_elf_paths(&program_metadata, Some(args))?;
@@ -52,33 +63,204 @@ pub fn execute_build_program(
 
     execute_command(cmd, args.docker)?;
 
-    if let Some(output_directory) = &args.output_directory {
-        // The path to the output directory, maybe relative or absolute.
-        let output_directory = PathBuf::from(output_directory);
+    check_elf_sizes(args, &target_elf_paths)?;
 
-        // Ensure the output directory is a directory. If it doesnt exist, this is false.
-        if output_directory.is_file() {
-            anyhow::bail!("--output-directory is a file.");
+    // Record how each ELF was built, so that the build can be reproduced on another machine.
+    let provenance_paths = match &docker_image {
+        Some(image) => {
+            let source_sha256 = source_sha256(&program_metadata)?;
+            let targets = target_elf_paths
+                .iter()
+                .map(|(target, path)| (target.clone(), path.clone(), source_sha256.clone()))
+                .collect::<Vec<_>>();
+            write_provenance(args, image, &targets)?
         }
+        None => vec![],
+    };
+
+    copy_to_output_directory(args, &target_elf_paths, &provenance_paths)?;
+
+    print_elf_paths_cargo_directives(&target_elf_paths);
+
+    Ok(target_elf_paths)
+}
+
+/// A group of programs that belong to the same cargo workspace, built in one cargo invocation.
+struct ProgramGroup {
+    /// The directory of the first program of the group, from which cargo is invoked.
+    program_dir: Utf8PathBuf,
+    /// The metadata of the workspace.
+    metadata: cargo_metadata::Metadata,
+    /// The build arguments, with [`BuildArgs::packages`] set to the programs of the group.
+    args: BuildArgs,
+    /// The cargo target directory the group is built in.
+    target_directory: Utf8PathBuf,
+}
+
+/// Groups the programs by cargo workspace.
+///
+/// Local builds of all groups share the target directory of the first program, so that the
+/// dependencies common to several programs are only compiled once. Docker builds can only write to
+/// the mounted workspace, so each group keeps its own target directory.
+fn group_programs(program_dirs: &[PathBuf], args: &BuildArgs) -> Result<Vec<ProgramGroup>> {
+    if !args.packages.is_empty() {
+        anyhow::bail!("--packages is not supported when building multiple programs.");
+    }
+
+    let mut groups: Vec<ProgramGroup> = vec![];
+    let mut shared_target_directory = None;
+    for program_dir in program_dirs {
+        let program_dir: Utf8PathBuf = program_dir
+            .canonicalize()?
+            .try_into()
+            .expect("Failed to convert PathBuf to Utf8PathBuf");
+        let metadata = cargo_metadata::MetadataCommand::new()
+            .manifest_path(program_dir.join("Cargo.toml"))
+            .exec()?;
+        let package = metadata
+            .root_package()
+            .ok_or_else(|| anyhow::anyhow!("{program_dir} is not a package"))?
+            .name
+            .clone();
+
+        let shared_target_directory =
+            shared_target_directory.get_or_insert_with(|| metadata.target_directory.clone());
+
+        match groups.iter_mut().find(|g| g.metadata.workspace_root == metadata.workspace_root) {
+            Some(group) => group.args.packages.push(package),
+            None => {
+                let target_directory = if args.docker {
+                    metadata.target_directory.clone()
+                } else {
+                    shared_target_directory.clone()
+                };
+                groups.push(ProgramGroup {
+                    program_dir,
+                    metadata,
+                    args: BuildArgs { packages: vec![package], ..args.clone() },
+                    target_directory,
+                });
+            }
+        }
+    }
+
+    Ok(groups)
+}
 
-        // Ensure the output directory exists.
-        std::fs::create_dir_all(&output_directory)?;
+/// Build several programs with the same [`BuildArgs`].
+///
+/// Programs of the same cargo workspace are built in a single cargo invocation, and local builds
+/// share one target directory across workspaces, so dependencies are compiled once rather than
+/// once per program.
+///
+/// # Returns
+///
+/// * `Result<Vec<(String, Utf8PathBuf)>>` - A list of mapping from bin target names to the paths to
+///   the built programs, for all the programs.
+pub fn execute_build_programs(
+    args: &BuildArgs,
+    program_dirs: &[PathBuf],
+) -> Result<Vec<(String, Utf8PathBuf)>> {
+    validate_memory_layout(args)?;
+
+    let groups = group_programs(program_dirs, args)?;
+
+    let mut target_elf_paths = vec![];
+    let mut provenance_targets = vec![];
+    let mut docker_image = None;
+    for group in &groups {
+        validate_features(&group.args, &group.metadata)?;
+        validate_shared_crates(&group.args, &group.metadata)?;
+
+        let (cmd, image) = if group.args.docker {
+            let (cmd, image) =
+                create_docker_command(&group.args, &group.program_dir, &group.metadata)?;
+            (cmd, Some(image))
+        } else {
+            (create_local_command(&group.args, &group.program_dir, &group.target_directory), None)
+        };
 
-        // Copy the ELF files to the output directory.
-        for (_, elf_path) in target_elf_paths.iter() {
-            let elf_path = elf_path.to_path_buf();
-            let elf_name = elf_path.file_name().expect("ELF path has a file name");
-            let output_path = output_directory.join(args.elf_name.as_deref().unwrap_or(elf_name));
+        let group_elf_paths =
+            generate_elf_paths_in(&group.metadata, Some(&group.args), &group.target_directory)?;
 
-            std::fs::copy(&elf_path, &output_path)?;
+        execute_command(cmd, group.args.docker)?;
+
+        check_elf_sizes(&group.args, &group_elf_paths)?;
+
+        if image.is_some() {
+            let source_sha256 = source_sha256(&group.metadata)?;
+            provenance_targets.extend(
+                group_elf_paths
+                    .iter()
+                    .map(|(target, path)| (target.clone(), path.clone(), source_sha256.clone())),
+            );
         }
+        docker_image = docker_image.or(image);
+        target_elf_paths.extend(group_elf_paths);
+    }
+
+    if target_elf_paths.len() > 1 && args.elf_name.is_some() {
+        anyhow::bail!("--elf-name is not supported when --output-directory is used and multiple ELFs are built.");
     }
 
+    // Record how each ELF was built, so that the build can be reproduced on another machine.
+    let provenance_paths = match &docker_image {
+        Some(image) => write_provenance(args, image, &provenance_targets)?,
+        None => vec![],
+    };
+
+    copy_to_output_directory(args, &target_elf_paths, &provenance_paths)?;
+
     print_elf_paths_cargo_directives(&target_elf_paths);
 
     Ok(target_elf_paths)
 }
 
+/// Copies the built ELFs, and their provenance files, to [`BuildArgs::output_directory`] if set.
+fn copy_to_output_directory(
+    args: &BuildArgs,
+    target_elf_paths: &[(String, Utf8PathBuf)],
+    provenance_paths: &[(String, Utf8PathBuf)],
+) -> Result<()> {
+    let Some(output_directory) = &args.output_directory else {
+        return Ok(());
+    };
+
+    // The path to the output directory, maybe relative or absolute.
+    let output_directory = PathBuf::from(output_directory);
+
+    // Ensure the output directory is a directory. If it doesnt exist, this is false.
+    if output_directory.is_file() {
+        anyhow::bail!("--output-directory is a file.");
+    }
+
+    // Ensure the output directory exists.
+    std::fs::create_dir_all(&output_directory)?;
+
+    // Copy the ELF files to the output directory.
+    for (_, elf_path) in target_elf_paths.iter() {
+        let elf_path = elf_path.to_path_buf();
+        let elf_name = elf_path.file_name().expect("ELF path has a file name");
+        let output_path = output_directory.join(args.elf_name.as_deref().unwrap_or(elf_name));
+
+        std::fs::copy(&elf_path, &output_path)?;
+    }
+
+    // Copy the provenance files alongside the ELF files.
+    for (_, provenance_path) in provenance_paths.iter() {
+        let provenance_path = provenance_path.to_path_buf();
+        let output_path = match &args.elf_name {
+            Some(elf_name) => output_directory.join(format!("{elf_name}.{PROVENANCE_EXTENSION}")),
+            None => output_directory
+                .join(provenance_path.file_name().expect("provenance path has a file name")),
+        };
+
+        std::fs::copy(&provenance_path, &output_path)?;
+    }
+
+    Ok(())
+}
+
 /// Internal helper function to build the program with or without arguments.
 pub(crate) fn build_program_internal(path: &str, args: Option<BuildArgs>) {
     // Get the root package name and metadata.
@@ -89,7 +271,8 @@ pub(crate) fn build_program_internal(path: &str, args: Option<BuildArgs>) {
     let root_package = metadata.root_package();
     let root_package_name = root_package.as_ref().map(|p| p.name.as_str()).unwrap_or("Program");
 
-    // Skip the program build if the MONEROCHAN_SKIP_PROGRAM_BUILD environment variable is set to true.
+    // Skip the program build if the MONEROCHAN_SKIP_PROGRAM_BUILD environment variable is set to
+    // true.
     let skip_program_build = std::env::var("MONEROCHAN_SKIP_PROGRAM_BUILD")
         .map(|v| v.eq_ignore_ascii_case("true"))
         .unwrap_or(false);
@@ -143,10 +326,68 @@ pub(crate) fn build_program_internal(path: &str, args: Option<BuildArgs>) {
     }
 }
 
+/// Internal helper function to build several programs with shared arguments.
+pub(crate) fn build_programs_internal(paths: &[&str], args: BuildArgs) {
+    let program_dirs: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+
+    // Skip the build if MONEROCHAN_SKIP_PROGRAM_BUILD is set, or if `cargo clippy` is the current
+    // compiler, see `build_program_internal`.
+    let skip_program_build = std::env::var("MONEROCHAN_SKIP_PROGRAM_BUILD")
+        .map(|v| v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+    let is_clippy_driver = std::env::var("RUSTC_WORKSPACE_WRAPPER")
+        .map(|val| val.contains("clippy-driver"))
+        .unwrap_or(false);
+    if skip_program_build || is_clippy_driver {
+        // Still need to set ELF env vars even if build is skipped.
+        let groups = group_programs(&program_dirs, &args).expect("failed to load programs");
+        for group in &groups {
+            let target_elf_paths =
+                generate_elf_paths_in(&group.metadata, Some(&group.args), &group.target_directory)
+                    .expect("failed to collect target ELF paths");
+            print_elf_paths_cargo_directives(&target_elf_paths);
+        }
+
+        println!(
+            "cargo:warning=Build skipped for {} programs at {}",
+            paths.len(),
+            current_datetime()
+        );
+        return;
+    }
+
+    // Activate the build command if any of the programs, or their dependencies, change.
+    for program_dir in &program_dirs {
+        let metadata = cargo_metadata::MetadataCommand::new()
+            .manifest_path(program_dir.join("Cargo.toml"))
+            .exec()
+            .unwrap();
+        cargo_rerun_if_changed(&metadata, program_dir);
+    }
+
+    if let Err(err) = execute_build_programs(&args, &program_dirs) {
+        panic!("Failed to build MONEROCHAN programs: {err}.");
+    }
+
+    if matches!(args.warning_level, WarningLevel::All) {
+        println!("cargo:warning={} programs built at {}", paths.len(), current_datetime());
+    }
+}
+
 /// Collects the list of targets that would be built and their output ELF file paths.
 pub fn generate_elf_paths(
     metadata: &cargo_metadata::Metadata,
     args: Option<&BuildArgs>,
+) -> Result<Vec<(String, Utf8PathBuf)>> {
+    generate_elf_paths_in(metadata, args, &metadata.target_directory)
+}
+
+/// Collects the list of targets that would be built and their output ELF file paths, when the
+/// programs are built in the given cargo target directory.
+fn generate_elf_paths_in(
+    metadata: &cargo_metadata::Metadata,
+    args: Option<&BuildArgs>,
+    target_directory: &Utf8PathBuf,
 ) -> Result<Vec<(String, Utf8PathBuf)>> {
     let mut target_elf_paths = vec![];
     let packages_to_iterate = if let Some(args) = args {
@@ -188,7 +429,7 @@ pub fn generate_elf_paths(
                 }
             }
 
-            let elf_path = metadata.target_directory.join(HELPER_TARGET_SUBDIR);
+            let elf_path = target_directory.join(HELPER_TARGET_SUBDIR);
             let elf_path = match args {
                 Some(args) if args.docker => elf_path.join("docker"),
                 _ => elf_path,
@@ -19,13 +19,57 @@ fn get_docker_image(tag: &str) -> String {
     })
 }
 
+/// The Docker image a program was built with.
+#[derive(Debug, Clone)]
+pub(crate) struct DockerImage {
+    /// The image reference passed to `docker run`.
+    pub(crate) reference: String,
+    /// The content digest of the image (`sha256:...`).
+    pub(crate) digest: String,
+    /// The output of `rustc --version` inside the image.
+    pub(crate) rustc_version: String,
+}
+
+/// Returns the content digest of a Docker image, pulling the image if it is not available locally.
+fn resolve_image_digest(image: &str) -> Result<String> {
+    let inspect = || {
+        Command::new("docker")
+            .args(["image", "inspect", "--format", "{{index .RepoDigests 0}}", image])
+            .stderr(Stdio::null())
+            .output()
+            .context("failed to run docker image inspect")
+    };
+
+    let mut output = inspect()?;
+    if !output.status.success() {
+        let pull = Command::new("docker")
+            .args(["pull", "--platform", "linux/amd64", image])
+            .status()
+            .context("failed to run docker pull")?;
+        if !pull.success() {
+            anyhow::bail!("failed to pull docker image {image}");
+        }
+        output = inspect()?;
+    }
+
+    let repo_digest = String::from_utf8(output.stdout)?;
+    repo_digest
+        .trim()
+        .split_once('@')
+        .map(|(_, digest)| digest.to_string())
+        .ok_or_else(|| anyhow::anyhow!("docker image {image} has no content digest"))
+}
+
 /// Creates a Docker command to build the program.
 pub(crate) fn create_docker_command(
     args: &BuildArgs,
     program_dir: &Utf8PathBuf,
     program_metadata: &cargo_metadata::Metadata,
-) -> Result<Command> {
-    let image = get_docker_image(&args.tag);
+) -> Result<(Command, DockerImage)> {
+    let image = match &args.docker_digest {
+        Some(digest) => format!("{}@{digest}", get_docker_image(&args.tag)),
+        None => get_docker_image(&args.tag),
+    };
     let canonicalized_program_dir: Utf8PathBuf = program_dir
         .canonicalize()
         .expect("Failed to canonicalize program directory")
@@ -66,6 +110,15 @@ pub(crate) fn create_docker_command(
         exit(1);
     }
 
+    // Resolve the digest of the image, so that the build can be reproduced from the provenance
+    // file even if the tag is later moved.
+    let digest = resolve_image_digest(&image)?;
+    if let Some(expected) = &args.docker_digest {
+        if *expected != digest {
+            anyhow::bail!("docker image {image} has digest {digest}, expected {expected}");
+        }
+    }
+
     // Mount the entire workspace, and set the working directory to the program dir. Note: If the
     // program dir has local dependencies outside of the workspace, building with Docker will fail.
     let workspace_root_path = format!("{workspace_root}:/root/program");
@@ -90,7 +143,7 @@ pub(crate) fn create_docker_command(
         "docker"
     );
 
-    let parsed_version = {
+    let (rustc_version, parsed_version) = {
         let mut cmd = run_command_in_docker(&image);
         cmd.args(["rustc", "--version"]);
 
@@ -111,7 +164,8 @@ pub(crate) fn create_docker_command(
             println!("cargo:warning=docker: rustc +monerochan --version: {stdout_string:?}");
         }
 
-        super::utils::parse_rustc_version(&stdout_string)
+        let parsed_version = super::utils::parse_rustc_version(&stdout_string);
+        (stdout_string.trim().to_string(), parsed_version)
     };
 
     std::thread::sleep(std::time::Duration::from_secs(2));
@@ -157,9 +211,11 @@ pub(crate) fn create_docker_command(
         format!("RUSTC={}", rustc_bin.display()),
         "-e".to_string(),
         "CFLAGS_riscv32im_succinct_zkvm_elf=-D__ILP32__".to_string(),
+        "-e".to_string(),
+        "CXXFLAGS_riscv32im_succinct_zkvm_elf=-D__ILP32__".to_string(),
         "--entrypoint".to_string(),
         "".to_string(),
-        image,
+        image.clone(),
         "cargo".to_string(),
     ];
 
@@ -168,7 +224,7 @@ pub(crate) fn create_docker_command(
 
     let mut command = Command::new("docker");
     command.current_dir(canonicalized_program_dir.clone()).args(&docker_args);
-    Ok(command)
+    Ok((command, DockerImage { reference: image, digest, rustc_version }))
 }
 
 /// Setups a command to be run in the docker image.
@@ -10,19 +10,25 @@ use super::utils::{get_program_build_args, get_rust_compiler_flags};
 pub(crate) fn create_local_command(
     args: &BuildArgs,
     program_dir: &Utf8PathBuf,
-    program_metadata: &cargo_metadata::Metadata,
+    target_directory: &Utf8PathBuf,
 ) -> Command {
     let mut command = Command::new("cargo");
     let canonicalized_program_dir =
         program_dir.canonicalize().expect("Failed to canonicalize program directory");
 
-    // If CC_riscv32im_succinct_zkvm_elf is not set, set it to the default C++ toolchain
-    // downloaded by 'monerochanup --c-toolchain'.
-    if env::var("CC_riscv32im_succinct_zkvm_elf").is_err() {
-        if let Some(home_dir) = home_dir() {
-            let cc_path = home_dir.join(".monerochan").join("bin").join("riscv32-unknown-elf-gcc");
-            if cc_path.exists() {
-                command.env("CC_riscv32im_succinct_zkvm_elf", cc_path);
+    // If CC_riscv32im_succinct_zkvm_elf or CXX_riscv32im_succinct_zkvm_elf is not set, set it to
+    // the default C/C++ toolchain downloaded by 'monerochanup --c-toolchain', so that the C and C++
+    // sources of the program and its dependencies are cross-compiled with the `cc` crate.
+    for (var, compiler) in [
+        ("CC_riscv32im_succinct_zkvm_elf", "riscv32-unknown-elf-gcc"),
+        ("CXX_riscv32im_succinct_zkvm_elf", "riscv32-unknown-elf-g++"),
+    ] {
+        if env::var(var).is_err() {
+            if let Some(home_dir) = home_dir() {
+                let path = home_dir.join(".monerochan").join("bin").join(compiler);
+                if path.exists() {
+                    command.env(var, path);
+                }
             }
         }
     }
@@ -30,6 +36,7 @@ pub(crate) fn create_local_command(
     // the following flag is added to avoid build failure on ring:
     // https://github.com/briansmith/ring/blob/bcf68dd27a071ff1947b6327d4c6bde526e24b60/include/ring-core/target.h#L47
     command.env("CFLAGS_riscv32im_succinct_zkvm_elf", "-D__ILP32__");
+    command.env("CXXFLAGS_riscv32im_succinct_zkvm_elf", "-D__ILP32__");
 
     let parsed_version = {
         let output = Command::new("rustc")
@@ -86,7 +93,7 @@ pub(crate) fn create_local_command(
         .env("CARGO_ENCODED_RUSTFLAGS", get_rust_compiler_flags(args, &parsed_version))
         .env_remove("RUSTC")
         .env("RUSTC", rustc_bin.display().to_string())
-        .env("CARGO_TARGET_DIR", program_metadata.target_directory.join(HELPER_TARGET_SUBDIR))
+        .env("CARGO_TARGET_DIR", target_directory.join(HELPER_TARGET_SUBDIR))
         // TODO: remove once trim-paths is supported - https://github.com/rust-lang/rust/issues/111540
         .env("RUSTC_BOOTSTRAP", "1") // allows trim-paths.
         .args(get_program_build_args(args));
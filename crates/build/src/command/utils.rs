@@ -6,7 +6,7 @@ use std::{
     thread,
 };
 
-use crate::{BuildArgs, BUILD_TARGET};
+use crate::{features::cargo_features, layout::memory_layout_flags, BuildArgs, BUILD_TARGET};
 
 /// Get the arguments to build the program with the arguments from the [`BuildArgs`] struct.
 pub(crate) fn get_program_build_args(args: &BuildArgs) -> Vec<String> {
@@ -33,9 +33,9 @@ pub(crate) fn get_program_build_args(args: &BuildArgs) -> Vec<String> {
         build_args.push(b.to_string());
     }
 
-    if !args.features.is_empty() {
+    if let Some(features) = cargo_features(args) {
         build_args.push("--features".to_string());
-        build_args.push(args.features.join(","));
+        build_args.push(features);
     }
 
     if args.no_default_features {
@@ -59,14 +59,13 @@ pub(crate) fn get_rust_compiler_flags(args: &BuildArgs, version: &semver::Versio
         "passes=loweratomic"
     };
 
+    let layout_flags = memory_layout_flags(args);
+    let layout_flags = layout_flags.iter().flat_map(|flag| ["-C", flag.as_str()]);
+
     let rust_flags = [
         "-C",
         atomic_lower_pass,
         "-C",
-        "link-arg=-Ttext=0x00201000",
-        "-C",
-        "link-arg=--image-base=0x00200800",
-        "-C",
         "panic=abort",
         "--cfg",
         "getrandom_backend=\"custom\"",
@@ -75,8 +74,15 @@ pub(crate) fn get_rust_compiler_flags(args: &BuildArgs, version: &semver::Versio
         "-C",
         "llvm-args=-misched-postra-direction=bottomup",
     ];
-    let rust_flags: Vec<_> =
-        rust_flags.into_iter().chain(args.rustflags.iter().map(String::as_str)).collect();
+    // The Zbb bit manipulation instructions are opt-in, since the provers only execute them with
+    // the `zbb` feature.
+    let zbb_flags = args.zbb.then_some(["-C", "target-feature=+zbb"]).into_iter().flatten();
+    let rust_flags: Vec<_> = rust_flags
+        .into_iter()
+        .chain(zbb_flags)
+        .chain(layout_flags)
+        .chain(args.rustflags.iter().map(String::as_str))
+        .collect();
     rust_flags.join("\x1f")
 }
 
@@ -0,0 +1,127 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+use anyhow::Result;
+use cargo_metadata::{Metadata, Package};
+
+use crate::BuildArgs;
+
+/// Crates that only build for the host, and must never be enabled in a program through a feature.
+const HOST_ONLY_CRATES: &[&str] = &[
+    "monerochan",
+    "monerochan-prover",
+    "monerochan-core-machine",
+    "monerochan-cuda",
+    "monerochan-recursion-gnark-ffi",
+];
+
+/// Returns the value of the `--features` flag passed to cargo, combining the program features and
+/// the per-dependency features.
+pub(crate) fn cargo_features(args: &BuildArgs) -> Option<String> {
+    let features: Vec<&str> =
+        args.features.iter().chain(&args.dependency_features).map(String::as_str).collect();
+    (!features.is_empty()).then(|| features.join(","))
+}
+
+/// Checks that the features in the [`BuildArgs`] exist, and that they don't enable a host-only
+/// crate in the programs being built.
+///
+/// Features are unified by cargo across a workspace, so a host feature (for example one enabling
+/// the SDK for proving) passed to a program build would otherwise fail deep in the compilation of
+/// the dependency for the zkVM target.
+pub(crate) fn validate_features(args: &BuildArgs, metadata: &Metadata) -> Result<()> {
+    let packages = selected_packages(args, metadata);
+
+    for feature in args.features.iter().filter(|f| !f.contains('/')) {
+        if !packages.iter().any(|p| p.features.contains_key(feature)) {
+            anyhow::bail!("none of the programs being built has a feature `{feature}`");
+        }
+    }
+
+    for package in packages {
+        for feature in &args.dependency_features {
+            let Some((dependency, _)) = feature.split_once('/') else {
+                anyhow::bail!(
+                    "dependency feature `{feature}` must be of the form `DEPENDENCY/FEATURE`"
+                );
+            };
+            if !dependencies(package).contains_key(dependency.trim_end_matches('?')) {
+                anyhow::bail!("package `{}` has no dependency `{dependency}`", package.name);
+            }
+        }
+
+        let mut enabled_features = args.features.clone();
+        if !args.no_default_features && package.features.contains_key("default") {
+            enabled_features.push("default".to_string());
+        }
+        for dependency in enabled_dependencies(package, &enabled_features) {
+            if HOST_ONLY_CRATES.contains(&dependency.as_str()) {
+                anyhow::bail!(
+                    "the features {enabled_features:?} of package `{}` enable the host-only crate \
+                     `{dependency}`, which cannot be built for the zkVM",
+                    package.name
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the programs being built, which are [`BuildArgs::packages`] or the default members of
+/// the workspace.
+pub(crate) fn selected_packages<'a>(args: &BuildArgs, metadata: &'a Metadata) -> Vec<&'a Package> {
+    if args.packages.is_empty() {
+        metadata
+            .workspace_default_members
+            .iter()
+            .filter_map(|id| metadata.packages.iter().find(|p| p.id == *id))
+            .collect()
+    } else {
+        metadata.packages.iter().filter(|p| args.packages.contains(&p.name)).collect()
+    }
+}
+
+/// Returns the dependencies of a package, by the name they are referred to in features.
+fn dependencies(package: &Package) -> HashMap<&str, &str> {
+    package
+        .dependencies
+        .iter()
+        .map(|d| (d.rename.as_deref().unwrap_or(&d.name), d.name.as_str()))
+        .collect()
+}
+
+/// Returns the package names of the optional dependencies enabled by the given features.
+fn enabled_dependencies(package: &Package, features: &[String]) -> BTreeSet<String> {
+    let dependencies = dependencies(package);
+    let mut enabled = BTreeSet::new();
+    let mut visited = BTreeSet::new();
+    let mut queue: VecDeque<&str> = features.iter().map(String::as_str).collect();
+
+    while let Some(feature) = queue.pop_front() {
+        if !visited.insert(feature) {
+            continue;
+        }
+
+        // `dep:name`, `name/feature` and `name?/feature` refer to a dependency.
+        let dependency = match feature.split_once('/') {
+            Some((dependency, _)) if !dependency.ends_with('?') => Some(dependency),
+            Some(_) => None,
+            None => feature.strip_prefix("dep:"),
+        };
+        if let Some(name) = dependency.and_then(|d| dependencies.get(d)) {
+            enabled.insert(name.to_string());
+        }
+
+        match package.features.get(feature) {
+            Some(implied) => queue.extend(implied.iter().map(String::as_str)),
+            // An optional dependency without an explicit `dep:` is also an implicit feature.
+            None => {
+                if let Some(name) = dependencies.get(feature) {
+                    enabled.insert(name.to_string());
+                }
+            }
+        }
+    }
+
+    enabled
+}
@@ -0,0 +1,121 @@
+use anyhow::Result;
+
+use crate::BuildArgs;
+
+/// The default address of the `.text` section, where the entrypoint is placed.
+pub(crate) const DEFAULT_TEXT_START: u32 = 0x0020_1000;
+
+/// The distance between the image base and the start of the `.text` section.
+const IMAGE_BASE_OFFSET: u32 = 0x800;
+
+/// The lowest address the image base can be placed at.
+const MIN_IMAGE_BASE: u32 = 0x0020_0800;
+
+/// Memory addresses must be lower than the BabyBear prime.
+const MAX_MEMORY: u32 = 0x7800_0000;
+
+/// The alignment of the stack pointer required by the RISC-V calling convention.
+const STACK_ALIGN: u32 = 16;
+
+/// The size of the guard region placed below a stack, which the executor traps on.
+///
+/// Must match `monerochan_primitives::consts::STACK_GUARD_SIZE`.
+const STACK_GUARD_SIZE: u32 = 0x1000;
+
+/// Parses an address or a size, either in decimal or in hex with a `0x` prefix.
+pub(crate) fn parse_u32(value: &str) -> Result<u32, String> {
+    let parsed = match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(&hex.replace('_', ""), 16),
+        None => value.replace('_', "").parse(),
+    };
+    parsed.map_err(|e| format!("invalid value `{value}`: {e}"))
+}
+
+/// Checks that the memory layout configured in the [`BuildArgs`] fits in the zkVM memory.
+pub(crate) fn validate_memory_layout(args: &BuildArgs) -> Result<()> {
+    if let Some(text_start) = args.text_start {
+        if text_start % 4 != 0 {
+            anyhow::bail!("--text-start {text_start:#x} must be word aligned");
+        }
+        if text_start < MIN_IMAGE_BASE + IMAGE_BASE_OFFSET {
+            anyhow::bail!(
+                "--text-start {text_start:#x} must be at least {:#x}",
+                MIN_IMAGE_BASE + IMAGE_BASE_OFFSET
+            );
+        }
+    }
+
+    if let Some(stack_size) = args.stack_size {
+        if stack_size == 0 || stack_size % STACK_ALIGN != 0 {
+            anyhow::bail!("--stack-size {stack_size} must be a non-zero multiple of {STACK_ALIGN}");
+        }
+        if stack_size > MAX_MEMORY - STACK_GUARD_SIZE {
+            anyhow::bail!(
+                "--stack-size {stack_size:#x} exceeds the maximum memory address {MAX_MEMORY:#x}"
+            );
+        }
+    }
+
+    let text_start = args.text_start.unwrap_or(DEFAULT_TEXT_START);
+    if let Some(heap_start) = args.heap_start {
+        if heap_start <= text_start {
+            anyhow::bail!(
+                "--heap-start {heap_start:#x} must be above the program ({text_start:#x})"
+            );
+        }
+    }
+
+    if let Some(heap_size) = args.heap_size {
+        let Some(heap_start) = args.heap_start else {
+            anyhow::bail!("--heap-size requires --heap-start");
+        };
+        if heap_start.checked_add(heap_size).map_or(true, |end| end > MAX_MEMORY) {
+            anyhow::bail!(
+                "the heap at {heap_start:#x} with size {heap_size:#x} exceeds the maximum memory \
+                 address {MAX_MEMORY:#x}"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the linker flags that place the program and define the memory layout symbols read by
+/// the runtime (`_monerochan_stack_top`, `_monerochan_heap_start` and `_monerochan_heap_end`).
+///
+/// Symbols that are not configured keep the defaults defined by the runtime.
+pub(crate) fn memory_layout_flags(args: &BuildArgs) -> Vec<String> {
+    let text_start = args.text_start.unwrap_or(DEFAULT_TEXT_START);
+    let mut flags = vec![
+        format!("link-arg=-Ttext={text_start:#010x}"),
+        format!("link-arg=--image-base={:#010x}", text_start - IMAGE_BASE_OFFSET),
+    ];
+
+    // With a stack size, the stack is placed right after the program, above a guard region that
+    // catches overflows before they reach the data of the program, and the heap after the stack
+    // unless it is placed explicitly.
+    if let Some(stack_size) = args.stack_size {
+        flags.push(format!("link-arg=--defsym=_monerochan_stack_guard=ALIGN(_end,{STACK_ALIGN})"));
+        flags.push(format!(
+            "link-arg=--defsym=_monerochan_stack_top=_monerochan_stack_guard+{:#x}",
+            STACK_GUARD_SIZE + stack_size
+        ));
+        if args.heap_start.is_none() {
+            flags
+                .push("link-arg=--defsym=_monerochan_heap_start=_monerochan_stack_top".to_string());
+        }
+    }
+
+    if let Some(heap_start) = args.heap_start {
+        flags.push(format!("link-arg=--defsym=_monerochan_heap_start={heap_start:#010x}"));
+    }
+
+    if let (Some(heap_start), Some(heap_size)) = (args.heap_start, args.heap_size) {
+        flags.push(format!(
+            "link-arg=--defsym=_monerochan_heap_end={:#010x}",
+            heap_start + heap_size
+        ));
+    }
+
+    flags
+}
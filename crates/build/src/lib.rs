@@ -1,11 +1,20 @@
 mod build;
 mod command;
+mod features;
+mod layout;
+mod provenance;
+mod report;
+mod shared;
 mod utils;
+mod watch;
 use std::{collections::HashMap, fs::File, io::Read};
 
-use build::build_program_internal;
-pub use build::{execute_build_program, generate_elf_paths};
+use build::{build_program_internal, build_programs_internal};
+pub use build::{execute_build_program, execute_build_programs, generate_elf_paths};
 pub use command::TOOLCHAIN_NAME;
+pub use provenance::{elf_sha256, source_sha256, BuildProvenance, PROVENANCE_EXTENSION};
+pub use report::ElfReport;
+pub use watch::watch_program;
 
 use clap::{Parser, ValueEnum};
 use monerochan_prover::{components::CpuProverComponents, HashableKey, MONEROCHANProver};
@@ -43,6 +52,19 @@ pub struct BuildArgs {
         default_value = DEFAULT_DOCKER_TAG
     )]
     pub tag: String,
+    #[arg(
+        long,
+        requires = "docker",
+        help = "Pin the Docker image to this content digest (sha256:...). The build fails if the \
+                image does not match."
+    )]
+    pub docker_digest: Option<String>,
+    #[arg(
+        long,
+        requires = "docker",
+        help = "Fail the build if the ELF hash differs from the one recorded in this provenance file."
+    )]
+    pub verify_provenance: Option<String>,
     #[arg(
         long,
         action,
@@ -50,6 +72,14 @@ pub struct BuildArgs {
         help = "Space or comma separated list of features to activate"
     )]
     pub features: Vec<String>,
+    #[arg(
+        long,
+        action,
+        value_delimiter = ',',
+        value_name = "DEPENDENCY/FEATURE",
+        help = "Space or comma separated list of features to activate on dependencies of the program"
+    )]
+    pub dependency_features: Vec<String>,
     #[arg(
         long,
         action,
@@ -94,6 +124,58 @@ pub struct BuildArgs {
 
     #[arg(long, value_enum, default_value = "all", help = "Control warning message verbosity")]
     pub warning_level: WarningLevel,
+
+    #[arg(long, action, help = "Print the size, sections and largest symbols of each built ELF")]
+    pub elf_report: bool,
+
+    #[arg(
+        long,
+        value_name = "BYTES",
+        help = "Fail the build if an ELF is larger than this many bytes"
+    )]
+    pub max_elf_size: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "BYTES",
+        value_parser = layout::parse_u32,
+        help = "Place a stack of this size right after the program, instead of below it, with a \
+                guard page that traps on stack overflows"
+    )]
+    pub stack_size: Option<u32>,
+
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        value_parser = layout::parse_u32,
+        help = "The start address of the heap. Defaults to the end of the program (or the stack)"
+    )]
+    pub heap_start: Option<u32>,
+
+    #[arg(
+        long,
+        value_name = "BYTES",
+        value_parser = layout::parse_u32,
+        requires = "heap_start",
+        help = "The size of the heap. Defaults to the rest of the memory"
+    )]
+    pub heap_size: Option<u32>,
+
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        value_parser = layout::parse_u32,
+        help = "The address of the .text section, which holds the entrypoint [default: 0x00201000]"
+    )]
+    pub text_start: Option<u32>,
+
+    #[arg(
+        long,
+        action,
+        help = "Emit the Zbb bit manipulation instructions, which only the provers built with the \
+                `zbb` feature support"
+    )]
+    pub zbb: bool,
 }
 
 // Implement default args to match clap defaults.
@@ -102,7 +184,10 @@ impl Default for BuildArgs {
         Self {
             docker: false,
             tag: DEFAULT_DOCKER_TAG.to_string(),
+            docker_digest: None,
+            verify_provenance: None,
             features: vec![],
+            dependency_features: vec![],
             rustflags: vec![],
             ignore_rust_version: false,
             packages: vec![],
@@ -113,6 +198,13 @@ impl Default for BuildArgs {
             no_default_features: false,
             workspace_directory: None,
             warning_level: WarningLevel::All,
+            elf_report: false,
+            max_elf_size: None,
+            stack_size: None,
+            heap_start: None,
+            heap_size: None,
+            text_start: None,
+            zbb: false,
         }
     }
 }
@@ -129,7 +221,8 @@ impl Default for BuildArgs {
 /// This function is useful for automatically rebuilding the program during development
 /// when changes are made to the source code or its dependencies.
 ///
-/// Set the `MONEROCHAN_SKIP_PROGRAM_BUILD` environment variable to `true` to skip building the program.
+/// Set the `MONEROCHAN_SKIP_PROGRAM_BUILD` environment variable to `true` to skip building the
+/// program.
 pub fn build_program(path: &str) {
     build_program_internal(path, None)
 }
@@ -142,11 +235,63 @@ pub fn build_program(path: &str) {
 /// * `path` - A string slice that holds the path to the program directory.
 /// * `args` - A [`BuildArgs`] struct that contains various build configuration options.
 ///
-/// Set the `MONEROCHAN_SKIP_PROGRAM_BUILD` environment variable to `true` to skip building the program.
+/// Set the `MONEROCHAN_SKIP_PROGRAM_BUILD` environment variable to `true` to skip building the
+/// program.
 pub fn build_program_with_args(path: &str, args: BuildArgs) {
     build_program_internal(path, Some(args))
 }
 
+/// Builds the program once for each set of features, with otherwise the same arguments.
+///
+/// Useful to build a program against several versions of a dependency, each selected by a feature,
+/// where each set of features enables different binary targets.
+///
+/// # Arguments
+///
+/// * `path` - A string slice that holds the path to the program directory.
+/// * `args` - A [`BuildArgs`] struct that contains various build configuration options. Its
+///   features are activated in every build, in addition to the features of the set.
+/// * `feature_sets` - The features to activate in each build.
+///
+/// # Example
+///
+/// ```no_run
+/// use monerochan_build::{build_program_with_feature_matrix, BuildArgs};
+///
+/// build_program_with_feature_matrix(
+///     "./program",
+///     BuildArgs::default(),
+///     &[&["v0-9-9"], &["v0-10-6"], &["v0-10-8"]],
+/// );
+/// ```
+pub fn build_program_with_feature_matrix(path: &str, args: BuildArgs, feature_sets: &[&[&str]]) {
+    for feature_set in feature_sets {
+        let mut features = args.features.clone();
+        features.extend(feature_set.iter().map(ToString::to_string));
+        build_program_internal(path, Some(BuildArgs { features, ..args.clone() }));
+    }
+}
+
+/// Builds several programs with the same arguments if any of them, or one of their dependencies,
+/// changes.
+///
+/// Programs in the same cargo workspace are built in a single cargo invocation, and local builds
+/// share one target directory across workspaces so that common dependencies are compiled once.
+/// Prefer this over calling [`build_program_with_args`] once per program when a project has many
+/// programs.
+///
+/// # Arguments
+///
+/// * `paths` - The paths to the program directories.
+/// * `args` - A [`BuildArgs`] struct that contains various build configuration options.
+///   [`BuildArgs::packages`] must be empty, the packages are derived from `paths`.
+///
+/// Set the `MONEROCHAN_SKIP_PROGRAM_BUILD` environment variable to `true` to skip building the
+/// programs.
+pub fn build_programs(paths: &[&str], args: BuildArgs) {
+    build_programs_internal(paths, args)
+}
+
 /// Returns the verification key for the provided program.
 ///
 /// # Arguments
@@ -170,7 +315,7 @@ pub fn vkey(path: &str, target_name: &str) -> String {
     let mut elf = Vec::new();
 
     file.read_to_end(&mut elf).unwrap();
-    let (_, _, _, vk) = prover.setup(&elf);
+    let (_, _, _, vk) = prover.setup(&elf).expect("failed to set up the program");
     vk.bytes32()
 }
 
@@ -200,7 +345,7 @@ pub fn vkeys(path: &str, args: BuildArgs) -> HashMap<String, String> {
             let mut elf = Vec::new();
             file.read_to_end(&mut elf).unwrap();
 
-            let (_, _, _, vk) = prover.setup(&elf);
+            let (_, _, _, vk) = prover.setup(&elf).expect("failed to set up the program");
             let vk = vk.bytes32();
 
             (target_name, vk)
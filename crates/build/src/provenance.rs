@@ -0,0 +1,209 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use cargo_metadata::camino::Utf8PathBuf;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{command::docker::DockerImage, BuildArgs};
+
+/// The file extension appended to an ELF path to get the path of its provenance file.
+pub const PROVENANCE_EXTENSION: &str = "provenance.json";
+
+/// Describes how an ELF was produced by a Docker build.
+///
+/// Two builds of the same sources with the same provenance inputs (image digest, features and
+/// flags) are expected to produce byte-identical ELFs, so the `elf_sha256` can be compared across
+/// machines before publishing a verification key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildProvenance {
+    /// The name of the binary target.
+    pub target: String,
+    /// The hex encoded SHA-256 hash of the ELF.
+    pub elf_sha256: String,
+    /// The hex encoded SHA-256 hash of the sources of the workspace, see [`source_sha256`].
+    #[serde(default)]
+    pub source_sha256: String,
+    /// The verification key hash of the ELF, as returned by `HashableKey::bytes32`.
+    ///
+    /// The build does not set up the program, so this is only set once the provenance is attested
+    /// by the SDK or the CLI (`cargo monerochan vkey --provenance`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vk_hash: Option<String>,
+    /// The Docker image reference used for the build.
+    pub image: String,
+    /// The content digest of the Docker image (`sha256:...`).
+    pub image_digest: String,
+    /// The output of `rustc --version` inside the image.
+    pub rustc_version: String,
+    /// The version of `monerochan-build` that drove the build.
+    pub monerochan_build_version: String,
+    /// The features activated for the build.
+    pub features: Vec<String>,
+    /// Whether the `default` feature was disabled.
+    pub no_default_features: bool,
+    /// The extra flags passed to `rustc`.
+    pub rustflags: Vec<String>,
+    /// Whether the build asserted that `Cargo.lock` was unchanged.
+    pub locked: bool,
+}
+
+impl BuildProvenance {
+    /// Loads a provenance file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("failed to open provenance file {}", path.display()))?;
+        serde_json::from_reader(file)
+            .with_context(|| format!("failed to parse provenance file {}", path.display()))
+    }
+
+    /// Saves the provenance file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let file = std::fs::File::create(path)
+            .with_context(|| format!("failed to create provenance file {}", path.display()))?;
+        serde_json::to_writer_pretty(file, self).map_err(Into::into)
+    }
+
+    /// Checks that the given ELF is the one described by this provenance.
+    pub fn verify_elf(&self, elf: &[u8]) -> Result<()> {
+        let actual = elf_sha256(elf);
+        if actual != self.elf_sha256 {
+            anyhow::bail!(
+                "ELF hash mismatch for target `{}`: expected {}, got {}. \
+                 The build is not reproducible with image {}",
+                self.target,
+                self.elf_sha256,
+                actual,
+                self.image_digest
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Returns the hex encoded SHA-256 hash of an ELF.
+pub fn elf_sha256(elf: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(elf))
+}
+
+/// Returns the hex encoded SHA-256 hash of the sources of a workspace.
+///
+/// The sources are the `Cargo.lock` of the workspace and the files of its local packages, except
+/// for hidden files and `target` directories. Each file is hashed with its path relative to the
+/// workspace root, so the hash does not depend on where the workspace is checked out. An
+/// `--output-directory` inside a package is hashed too, so it should be placed outside of them.
+pub fn source_sha256(metadata: &cargo_metadata::Metadata) -> Result<String> {
+    let root = metadata.workspace_root.as_std_path();
+    let mut stack = vec![root.join("Cargo.lock")];
+    stack.extend(
+        metadata
+            .packages
+            .iter()
+            .filter(|package| package.source.is_none())
+            .filter_map(|package| package.manifest_path.parent())
+            .map(|dir| dir.as_std_path().to_path_buf()),
+    );
+
+    // The files, by their path relative to the root. Packages may be nested, so a file can be
+    // found several times.
+    let mut files = BTreeMap::<PathBuf, PathBuf>::new();
+    while let Some(path) = stack.pop() {
+        if path.is_dir() {
+            for entry in std::fs::read_dir(&path)
+                .with_context(|| format!("failed to read directory {}", path.display()))?
+            {
+                let entry = entry?;
+                let name = entry.file_name();
+                if name.to_string_lossy().starts_with('.') || name == "target" {
+                    continue;
+                }
+                stack.push(entry.path());
+            }
+        } else if path.is_file() {
+            let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+            files.insert(relative, path);
+        }
+    }
+
+    let mut hasher = Sha256::new();
+    for (relative, path) in &files {
+        let contents = std::fs::read(path)
+            .with_context(|| format!("failed to read source file {}", path.display()))?;
+        hasher.update(relative.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher.update((contents.len() as u64).to_le_bytes());
+        hasher.update(&contents);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Returns the path of the provenance file for an ELF.
+pub(crate) fn provenance_path(elf_path: &Utf8PathBuf) -> Utf8PathBuf {
+    Utf8PathBuf::from(format!("{elf_path}.{PROVENANCE_EXTENSION}"))
+}
+
+/// Writes a provenance file next to each built ELF, and checks the ELFs against the provenance
+/// file given in [`BuildArgs::verify_provenance`], if any.
+///
+/// Each ELF is given with the target it was built for and the [`source_sha256`] of its workspace.
+pub(crate) fn write_provenance(
+    args: &BuildArgs,
+    image: &DockerImage,
+    target_elf_paths: &[(String, Utf8PathBuf, String)],
+) -> Result<Vec<(String, Utf8PathBuf)>> {
+    let expected = args.verify_provenance.as_ref().map(BuildProvenance::load).transpose()?;
+    let mut verified = expected.is_none();
+
+    let mut provenance_paths = Vec::with_capacity(target_elf_paths.len());
+    for (target, elf_path, source_sha256) in target_elf_paths {
+        let elf = std::fs::read(elf_path)
+            .with_context(|| format!("failed to read built ELF {elf_path}"))?;
+        let mut provenance = BuildProvenance {
+            target: target.clone(),
+            elf_sha256: elf_sha256(&elf),
+            source_sha256: source_sha256.clone(),
+            vk_hash: None,
+            image: image.reference.clone(),
+            image_digest: image.digest.clone(),
+            rustc_version: image.rustc_version.clone(),
+            monerochan_build_version: env!("CARGO_PKG_VERSION").to_string(),
+            features: args.features.clone(),
+            no_default_features: args.no_default_features,
+            rustflags: args.rustflags.clone(),
+            locked: args.locked,
+        };
+
+        if let Some(expected) = expected.as_ref().filter(|e| e.target == *target) {
+            if !expected.source_sha256.is_empty() && expected.source_sha256 != *source_sha256 {
+                anyhow::bail!(
+                    "source hash mismatch for target `{target}`: expected {}, got {}. The sources \
+                     differ from the ones the provenance file was recorded for",
+                    expected.source_sha256,
+                    source_sha256
+                );
+            }
+            expected.verify_elf(&elf)?;
+            // The ELF is the same, so its verification key is too.
+            provenance.vk_hash.clone_from(&expected.vk_hash);
+            verified = true;
+        }
+
+        let path = provenance_path(elf_path);
+        provenance.save(&path)?;
+        provenance_paths.push((target.clone(), path));
+    }
+
+    if !verified {
+        anyhow::bail!(
+            "the provenance file {} does not describe any of the built targets",
+            args.verify_provenance.as_deref().unwrap_or_default()
+        );
+    }
+
+    Ok(provenance_paths)
+}
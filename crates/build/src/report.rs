@@ -0,0 +1,151 @@
+use std::collections::BTreeSet;
+
+use anyhow::{Context, Result};
+use cargo_metadata::camino::Utf8PathBuf;
+use elf::{
+    abi::{PT_LOAD, STT_FUNC, STT_OBJECT},
+    endian::LittleEndian,
+    ElfBytes,
+};
+
+use crate::BuildArgs;
+
+/// The number of symbols listed in the report.
+const LARGEST_SYMBOLS: usize = 10;
+
+/// The page size used to estimate the number of memory image pages.
+const PAGE_SIZE: u64 = 4096;
+
+/// A summary of the size of a built ELF.
+#[derive(Debug, Clone, Default)]
+pub struct ElfReport {
+    /// The size of the ELF file in bytes.
+    pub file_size: u64,
+    /// The size of the executable code (`.text*` sections) in bytes.
+    pub text_size: u64,
+    /// The size of the read-only data (`.rodata*` sections) in bytes.
+    pub rodata_size: u64,
+    /// The size of the initialized data (`.data*` and `.sdata*` sections) in bytes.
+    pub data_size: u64,
+    /// The size of the zero-initialized data (`.bss*` and `.sbss*` sections) in bytes.
+    pub bss_size: u64,
+    /// The largest function and object symbols, as (demangled name, size in bytes).
+    pub largest_symbols: Vec<(String, u64)>,
+    /// The estimated number of pages of the initial memory image.
+    pub memory_image_pages: u64,
+}
+
+impl ElfReport {
+    /// Computes the report of an ELF.
+    pub fn from_elf(elf: &[u8]) -> Result<Self> {
+        let file = ElfBytes::<LittleEndian>::minimal_parse(elf)?;
+        let mut report = Self { file_size: elf.len() as u64, ..Default::default() };
+
+        let (sections, strtab) = file.section_headers_with_strtab()?;
+        if let (Some(sections), Some(strtab)) = (sections, strtab) {
+            for section in sections.iter() {
+                let name = strtab.get(section.sh_name as usize).unwrap_or_default();
+                let size = match name {
+                    n if n.starts_with(".text") => &mut report.text_size,
+                    n if n.starts_with(".rodata") || n.starts_with(".srodata") => {
+                        &mut report.rodata_size
+                    }
+                    n if n.starts_with(".data") || n.starts_with(".sdata") => &mut report.data_size,
+                    n if n.starts_with(".bss") || n.starts_with(".sbss") => &mut report.bss_size,
+                    _ => continue,
+                };
+                *size += section.sh_size;
+            }
+        }
+
+        if let Some((symbols, strtab)) = file.symbol_table()? {
+            let mut largest: Vec<(String, u64)> = symbols
+                .iter()
+                .filter(|sym| matches!(sym.st_symtype(), STT_FUNC | STT_OBJECT) && sym.st_size > 0)
+                .filter_map(|sym| {
+                    let name = strtab.get(sym.st_name as usize).ok()?;
+                    Some((rustc_demangle::demangle(name).to_string(), sym.st_size))
+                })
+                .collect();
+            largest.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            largest.truncate(LARGEST_SYMBOLS);
+            report.largest_symbols = largest;
+        }
+
+        // Count the distinct pages touched by the loadable segments.
+        let mut pages = BTreeSet::new();
+        for segment in file.segments().iter().flat_map(|s| s.iter()) {
+            if segment.p_type != PT_LOAD || segment.p_memsz == 0 {
+                continue;
+            }
+            let first = segment.p_vaddr / PAGE_SIZE;
+            let last = (segment.p_vaddr + segment.p_memsz - 1) / PAGE_SIZE;
+            pages.extend(first..=last);
+        }
+        report.memory_image_pages = pages.len() as u64;
+
+        Ok(report)
+    }
+
+    /// Returns the lines of the report, for printing.
+    pub fn lines(&self, target: &str) -> Vec<String> {
+        let sections = [
+            (".text", self.text_size),
+            (".rodata", self.rodata_size),
+            (".data", self.data_size),
+            (".bss", self.bss_size),
+        ];
+
+        let mut lines = vec![format!("{target}: {} bytes", self.file_size)];
+        for (name, size) in sections {
+            lines.push(format!("  {name:<8} {size:>10} bytes"));
+        }
+        lines.push(format!(
+            "  memory image: ~{} pages of {PAGE_SIZE} bytes",
+            self.memory_image_pages
+        ));
+        if !self.largest_symbols.is_empty() {
+            lines.push("  largest symbols:".to_string());
+            for (name, size) in &self.largest_symbols {
+                lines.push(format!("    {size:>10} {name}"));
+            }
+        }
+        lines
+    }
+}
+
+/// Prints a size report for each built ELF if [`BuildArgs::elf_report`] is set, and fails if an
+/// ELF exceeds [`BuildArgs::max_elf_size`].
+pub(crate) fn check_elf_sizes(
+    args: &BuildArgs,
+    target_elf_paths: &[(String, Utf8PathBuf)],
+) -> Result<()> {
+    if !args.elf_report && args.max_elf_size.is_none() {
+        return Ok(());
+    }
+
+    for (target, elf_path) in target_elf_paths {
+        let elf = std::fs::read(elf_path)
+            .with_context(|| format!("failed to read built ELF {elf_path}"))?;
+        let report = ElfReport::from_elf(&elf)
+            .with_context(|| format!("failed to parse built ELF {elf_path}"))?;
+
+        if args.elf_report {
+            for line in report.lines(target) {
+                println!("cargo:warning={line}");
+            }
+        }
+
+        if let Some(max_elf_size) = args.max_elf_size {
+            if report.file_size > max_elf_size {
+                anyhow::bail!(
+                    "ELF for target `{target}` is {} bytes, which exceeds --max-elf-size of {} bytes",
+                    report.file_size,
+                    max_elf_size
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
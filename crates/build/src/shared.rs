@@ -0,0 +1,72 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use anyhow::Result;
+use cargo_metadata::{Metadata, MetadataCommand, Package, PackageId};
+
+use crate::{features::selected_packages, BuildArgs};
+
+/// Checks that the local crates which the programs being built share with the host package are
+/// the same crate at the same version.
+///
+/// A crate of input and output types shared by a program and the script that runs it must be
+/// identical on both sides, otherwise the stdin and the public values are encoded with different
+/// schemas and the mismatch only shows up as a decoding failure at runtime. The host package is
+/// the one whose build script builds the programs, so the check is skipped outside of a build
+/// script, as in `cargo monerochan build`.
+pub(crate) fn validate_shared_crates(args: &BuildArgs, metadata: &Metadata) -> Result<()> {
+    let Ok(host_dir) = std::env::var("CARGO_MANIFEST_DIR") else {
+        return Ok(());
+    };
+    let host_manifest = PathBuf::from(host_dir).join("Cargo.toml");
+    let host_metadata = MetadataCommand::new().manifest_path(&host_manifest).exec()?;
+    let Some(host) =
+        host_metadata.packages.iter().find(|p| p.manifest_path.as_std_path() == host_manifest)
+    else {
+        return Ok(());
+    };
+    let host_crates = local_dependencies(&host_metadata, &host.id);
+
+    for program in selected_packages(args, metadata) {
+        for (name, program_crate) in local_dependencies(metadata, &program.id) {
+            let Some(host_crate) = host_crates.get(name) else {
+                continue;
+            };
+            if program_crate.version != host_crate.version ||
+                program_crate.manifest_path != host_crate.manifest_path
+            {
+                anyhow::bail!(
+                    "the program `{}` uses `{name}` {} at {}, but the host `{}` uses `{name}` {} \
+                     at {}; the program and the host must use the same crate so that their inputs \
+                     and outputs are encoded identically",
+                    program.name,
+                    program_crate.version,
+                    program_crate.manifest_path,
+                    host.name,
+                    host_crate.version,
+                    host_crate.manifest_path,
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the resolved local dependencies of a package, which are the crates that are neither
+/// from a registry nor from a git repository, by name.
+fn local_dependencies<'a>(
+    metadata: &'a Metadata,
+    id: &PackageId,
+) -> BTreeMap<&'a str, &'a Package> {
+    let Some(node) = metadata.resolve.as_ref().and_then(|r| r.nodes.iter().find(|n| n.id == *id))
+    else {
+        return BTreeMap::new();
+    };
+
+    node.deps
+        .iter()
+        .filter_map(|dep| metadata.packages.iter().find(|p| p.id == dep.pkg))
+        .filter(|package| package.source.is_none())
+        .map(|package| (package.name.as_str(), package))
+        .collect()
+}
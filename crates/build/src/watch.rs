@@ -0,0 +1,118 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Result;
+use cargo_metadata::camino::Utf8PathBuf;
+
+use crate::{build::execute_build_program, BuildArgs};
+
+/// How often the program sources are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Builds the program, then rebuilds it every time one of its sources changes.
+///
+/// The watched files are the same as the ones that trigger a rebuild from a build script: the
+/// `src` and `bin` directories, `build.rs` and `Cargo.toml` of the program, the `Cargo.lock` of its
+/// workspace, and its local path dependencies.
+///
+/// After each successful build, `on_build` is called with the built ELF paths. Build errors and
+/// errors returned by `on_build` are printed, and the program keeps being watched. This function
+/// only returns if the program metadata cannot be loaded.
+pub fn watch_program<F>(
+    args: &BuildArgs,
+    program_dir: Option<PathBuf>,
+    mut on_build: F,
+) -> Result<()>
+where
+    F: FnMut(&[(String, Utf8PathBuf)]) -> Result<()>,
+{
+    let program_dir = match program_dir {
+        Some(program_dir) => program_dir,
+        None => std::env::current_dir()?,
+    };
+
+    loop {
+        // Take the snapshot before building, so that changes made during the build trigger a new
+        // one. The local dependencies may change between builds, so they are listed every time.
+        let watched = watched_paths(&program_dir)?;
+        let mut snapshot = modification_times(&watched);
+
+        match execute_build_program(args, Some(program_dir.clone())) {
+            Ok(target_elf_paths) => {
+                if let Err(err) = on_build(&target_elf_paths) {
+                    eprintln!("[monerochan] {err:#}");
+                }
+            }
+            Err(err) => eprintln!("[monerochan] build failed: {err:#}"),
+        }
+        eprintln!("[monerochan] watching {} for changes", program_dir.display());
+
+        // Wait for a change, then for the files to settle so that an editor saving several files
+        // only triggers one build.
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let current = modification_times(&watched);
+            if current != snapshot {
+                snapshot = current;
+                break;
+            }
+        }
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let current = modification_times(&watched);
+            if current == snapshot {
+                break;
+            }
+            snapshot = current;
+        }
+    }
+}
+
+/// Returns the files and directories whose changes trigger a rebuild of the program.
+fn watched_paths(program_dir: &Path) -> Result<Vec<PathBuf>> {
+    let metadata = cargo_metadata::MetadataCommand::new()
+        .manifest_path(program_dir.join("Cargo.toml"))
+        .exec()?;
+
+    let mut paths = vec![
+        program_dir.join("src"),
+        program_dir.join("bin"),
+        program_dir.join("build.rs"),
+        program_dir.join("Cargo.toml"),
+        metadata.workspace_root.join("Cargo.lock").into(),
+    ];
+    for package in &metadata.packages {
+        for dependency in &package.dependencies {
+            if let Some(path) = &dependency.path {
+                paths.push(path.join("src").into());
+                paths.push(path.join("Cargo.toml").into());
+            }
+        }
+    }
+    paths.sort();
+    paths.dedup();
+
+    Ok(paths)
+}
+
+/// Returns the modification time of every file under the given paths.
+fn modification_times(paths: &[PathBuf]) -> BTreeMap<PathBuf, SystemTime> {
+    let mut times = BTreeMap::new();
+    let mut stack: Vec<PathBuf> = paths.to_vec();
+    while let Some(path) = stack.pop() {
+        let Ok(metadata) = std::fs::metadata(&path) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            if let Ok(entries) = std::fs::read_dir(&path) {
+                stack.extend(entries.filter_map(|entry| entry.ok().map(|entry| entry.path())));
+            }
+        } else if let Ok(modified) = metadata.modified() {
+            times.insert(path, modified);
+        }
+    }
+    times
+}
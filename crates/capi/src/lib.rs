@@ -0,0 +1,560 @@
+//! C bindings for the MONEROCHAN SDK.
+//!
+//! The bindings let services written in C, C++ or Go set up, execute, prove and verify programs
+//! without a custom Rust binary. The header is generated at `include/monerochan.h` when the crate
+//! is built.
+//!
+//! # Conventions
+//! - Fallible functions return a [`MonerochanStatus`]. On failure, [`monerochan_last_error`]
+//!   describes the error of the last failed call on the current thread.
+//! - Objects returned through out pointers are owned by the caller, and must be released with the
+//!   matching `_free` function.
+//! - [`MonerochanBuffer`]s returned by the library must be released with
+//!   [`monerochan_buffer_free`].
+//! - Panics are caught at the boundary and reported as [`MonerochanStatus::Panic`].
+
+#![allow(clippy::missing_safety_doc)]
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CStr, CString},
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr, slice,
+};
+
+use anyhow::Result;
+use monerochan::{
+    CpuProver, EnvProver, HashableKey, MONEROCHANProofMode, MONEROCHANProofWithPublicValues,
+    MONEROCHANProvingKey, MONEROCHANStdin, MONEROCHANVerifyingKey, Prover,
+};
+use monerochan_prover::components::CpuProverComponents;
+
+/// The result of a fallible call.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonerochanStatus {
+    /// The call succeeded.
+    Ok = 0,
+    /// A pointer argument was null or a string was not valid UTF-8.
+    InvalidArgument = 1,
+    /// The call failed, see [`monerochan_last_error`].
+    Error = 2,
+    /// The call panicked, see [`monerochan_last_error`].
+    Panic = 3,
+}
+
+/// The proof modes, see [`MONEROCHANProofMode`].
+///
+/// Functions take the mode as a `uint32_t` holding one of these values, so that an out of range
+/// value from C is rejected rather than undefined behavior.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonerochanProofMode {
+    /// A proof made of one STARK proof per shard.
+    Core = 0,
+    /// A single recursive STARK proof of constant size.
+    Compressed = 1,
+    /// A PLONK proof over BN254, verifiable onchain.
+    Plonk = 2,
+    /// A Groth16 proof over BN254, verifiable onchain.
+    Groth16 = 3,
+}
+
+impl TryFrom<u32> for MonerochanProofMode {
+    type Error = InvalidArgument;
+
+    fn try_from(mode: u32) -> Result<Self, InvalidArgument> {
+        match mode {
+            0 => Ok(Self::Core),
+            1 => Ok(Self::Compressed),
+            2 => Ok(Self::Plonk),
+            3 => Ok(Self::Groth16),
+            _ => Err(InvalidArgument("mode")),
+        }
+    }
+}
+
+impl From<MonerochanProofMode> for MONEROCHANProofMode {
+    fn from(mode: MonerochanProofMode) -> Self {
+        match mode {
+            MonerochanProofMode::Core => MONEROCHANProofMode::Core,
+            MonerochanProofMode::Compressed => MONEROCHANProofMode::Compressed,
+            MonerochanProofMode::Plonk => MONEROCHANProofMode::Plonk,
+            MonerochanProofMode::Groth16 => MONEROCHANProofMode::Groth16,
+        }
+    }
+}
+
+/// A byte buffer owned by the library.
+#[repr(C)]
+#[derive(Debug)]
+pub struct MonerochanBuffer {
+    /// The bytes of the buffer.
+    pub data: *mut u8,
+    /// The number of bytes.
+    pub len: usize,
+}
+
+impl MonerochanBuffer {
+    fn new(bytes: Vec<u8>) -> Self {
+        let len = bytes.len();
+        Self { data: Box::into_raw(bytes.into_boxed_slice()).cast(), len }
+    }
+}
+
+/// A prover.
+pub struct MonerochanProver(Box<dyn Prover<CpuProverComponents>>);
+
+/// The proving key of a program.
+pub struct MonerochanProvingKey(MONEROCHANProvingKey);
+
+/// The verifying key of a program.
+pub struct MonerochanVerifyingKey(MONEROCHANVerifyingKey);
+
+/// The input of a program.
+pub struct MonerochanStdin(MONEROCHANStdin);
+
+/// A proof bundled with its public values.
+pub struct MonerochanProof(MONEROCHANProofWithPublicValues);
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// An invalid argument, reported as [`MonerochanStatus::InvalidArgument`].
+#[derive(Debug)]
+struct InvalidArgument(&'static str);
+
+impl std::fmt::Display for InvalidArgument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid argument: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidArgument {}
+
+/// Runs `f`, turning errors and panics into a status and the last error.
+fn ffi_call(f: impl FnOnce() -> Result<()>) -> MonerochanStatus {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => MonerochanStatus::Ok,
+        Ok(Err(err)) => {
+            set_last_error(format!("{err:#}"));
+            if err.is::<InvalidArgument>() {
+                MonerochanStatus::InvalidArgument
+            } else {
+                MonerochanStatus::Error
+            }
+        }
+        Err(panic) => {
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(ToString::to_string)
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            set_last_error(format!("panicked: {message}"));
+            MonerochanStatus::Panic
+        }
+    }
+}
+
+unsafe fn arg<'a, T>(ptr: *const T, name: &'static str) -> Result<&'a T> {
+    ptr.as_ref().ok_or(InvalidArgument(name).into())
+}
+
+unsafe fn arg_mut<'a, T>(ptr: *mut T, name: &'static str) -> Result<&'a mut T> {
+    ptr.as_mut().ok_or(InvalidArgument(name).into())
+}
+
+unsafe fn bytes_arg<'a>(data: *const u8, len: usize, name: &'static str) -> Result<&'a [u8]> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+    if data.is_null() {
+        return Err(InvalidArgument(name).into());
+    }
+    Ok(slice::from_raw_parts(data, len))
+}
+
+unsafe fn str_arg<'a>(s: *const c_char, name: &'static str) -> Result<&'a str> {
+    if s.is_null() {
+        return Err(InvalidArgument(name).into());
+    }
+    CStr::from_ptr(s).to_str().map_err(|_| InvalidArgument(name).into())
+}
+
+/// Writes `value` to the out pointer `out`.
+unsafe fn write_out<T>(out: *mut T, value: T, name: &'static str) -> Result<()> {
+    if out.is_null() {
+        return Err(InvalidArgument(name).into());
+    }
+    out.write(value);
+    Ok(())
+}
+
+/// The message of the last failed call on the current thread, or null if no call failed.
+///
+/// The string is owned by the library and is valid until the next failed call on the thread.
+#[no_mangle]
+pub extern "C" fn monerochan_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+/// The version of the MONEROCHAN circuit, as a static string.
+#[no_mangle]
+pub extern "C" fn monerochan_circuit_version() -> *const c_char {
+    static VERSION: std::sync::OnceLock<CString> = std::sync::OnceLock::new();
+    VERSION.get_or_init(|| CString::new(monerochan::MONEROCHAN_CIRCUIT_VERSION).unwrap()).as_ptr()
+}
+
+/// Releases a buffer returned by the library.
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_buffer_free(buffer: MonerochanBuffer) {
+    if !buffer.data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(buffer.data, buffer.len)));
+    }
+}
+
+/// Creates a prover configured by the `MONEROCHAN_PROVER` environment variable and its companions,
+/// see `EnvProver::try_new`.
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_prover_from_env(
+    out: *mut *mut MonerochanProver,
+) -> MonerochanStatus {
+    ffi_call(|| {
+        let prover = EnvProver::try_new()?;
+        write_out(out, Box::into_raw(Box::new(MonerochanProver(Box::new(prover)))), "out")
+    })
+}
+
+/// Creates a prover that proves on the CPU.
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_prover_cpu(
+    out: *mut *mut MonerochanProver,
+) -> MonerochanStatus {
+    ffi_call(|| {
+        write_out(out, Box::into_raw(Box::new(MonerochanProver(Box::new(CpuProver::new())))), "out")
+    })
+}
+
+/// Creates a prover that generates mock proofs, for testing.
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_prover_mock(
+    out: *mut *mut MonerochanProver,
+) -> MonerochanStatus {
+    ffi_call(|| {
+        write_out(
+            out,
+            Box::into_raw(Box::new(MonerochanProver(Box::new(CpuProver::mock())))),
+            "out",
+        )
+    })
+}
+
+/// Releases a prover.
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_prover_free(prover: *mut MonerochanProver) {
+    if !prover.is_null() {
+        drop(Box::from_raw(prover));
+    }
+}
+
+/// Computes the proving and verifying keys of a program.
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_setup(
+    prover: *const MonerochanProver,
+    elf: *const u8,
+    elf_len: usize,
+    out_pk: *mut *mut MonerochanProvingKey,
+    out_vk: *mut *mut MonerochanVerifyingKey,
+) -> MonerochanStatus {
+    ffi_call(|| {
+        let prover = arg(prover, "prover")?;
+        let elf = bytes_arg(elf, elf_len, "elf")?;
+        if out_pk.is_null() || out_vk.is_null() {
+            return Err(InvalidArgument("out_pk/out_vk").into());
+        }
+        let (pk, vk) = prover.0.setup(elf);
+        write_out(out_pk, Box::into_raw(Box::new(MonerochanProvingKey(pk))), "out_pk")?;
+        write_out(out_vk, Box::into_raw(Box::new(MonerochanVerifyingKey(vk))), "out_vk")
+    })
+}
+
+/// Releases a proving key.
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_proving_key_free(pk: *mut MonerochanProvingKey) {
+    if !pk.is_null() {
+        drop(Box::from_raw(pk));
+    }
+}
+
+/// Releases a verifying key.
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_verifying_key_free(vk: *mut MonerochanVerifyingKey) {
+    if !vk.is_null() {
+        drop(Box::from_raw(vk));
+    }
+}
+
+/// Writes the hash of a verifying key, as a `0x`-prefixed hex string, to `out`.
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_verifying_key_hash(
+    vk: *const MonerochanVerifyingKey,
+    out: *mut MonerochanBuffer,
+) -> MonerochanStatus {
+    ffi_call(|| {
+        let vk = arg(vk, "vk")?;
+        write_out(out, MonerochanBuffer::new(vk.0.bytes32().into_bytes()), "out")
+    })
+}
+
+/// Serializes a verifying key, e.g. to verify proofs in another process.
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_verifying_key_serialize(
+    vk: *const MonerochanVerifyingKey,
+    out: *mut MonerochanBuffer,
+) -> MonerochanStatus {
+    ffi_call(|| {
+        let vk = arg(vk, "vk")?;
+        write_out(out, MonerochanBuffer::new(bincode::serialize(&vk.0)?), "out")
+    })
+}
+
+/// Deserializes a verifying key serialized by [`monerochan_verifying_key_serialize`].
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_verifying_key_deserialize(
+    data: *const u8,
+    len: usize,
+    out: *mut *mut MonerochanVerifyingKey,
+) -> MonerochanStatus {
+    ffi_call(|| {
+        let vk = bincode::deserialize(bytes_arg(data, len, "data")?)?;
+        write_out(out, Box::into_raw(Box::new(MonerochanVerifyingKey(vk))), "out")
+    })
+}
+
+/// Creates an empty input.
+#[no_mangle]
+pub extern "C" fn monerochan_stdin_new() -> *mut MonerochanStdin {
+    Box::into_raw(Box::new(MonerochanStdin(MONEROCHANStdin::new())))
+}
+
+/// Appends a byte buffer to the input, read in the program with `io::read_vec()`.
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_stdin_write_vec(
+    stdin: *mut MonerochanStdin,
+    data: *const u8,
+    len: usize,
+) -> MonerochanStatus {
+    ffi_call(|| {
+        let stdin = arg_mut(stdin, "stdin")?;
+        stdin.0.write_vec(bytes_arg(data, len, "data")?.to_vec());
+        Ok(())
+    })
+}
+
+/// Releases an input.
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_stdin_free(stdin: *mut MonerochanStdin) {
+    if !stdin.is_null() {
+        drop(Box::from_raw(stdin));
+    }
+}
+
+/// Executes a program without proving it.
+///
+/// Writes the public values to `out_public_values` and the number of executed instructions to
+/// `out_cycles`, which may be null.
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_execute(
+    prover: *const MonerochanProver,
+    elf: *const u8,
+    elf_len: usize,
+    stdin: *const MonerochanStdin,
+    out_public_values: *mut MonerochanBuffer,
+    out_cycles: *mut u64,
+) -> MonerochanStatus {
+    ffi_call(|| {
+        let prover = arg(prover, "prover")?;
+        let elf = bytes_arg(elf, elf_len, "elf")?;
+        let stdin = arg(stdin, "stdin")?;
+        if out_public_values.is_null() {
+            return Err(InvalidArgument("out_public_values").into());
+        }
+        let (public_values, report) = prover.0.execute(elf, &stdin.0)?;
+        write_out(out_public_values, MonerochanBuffer::new(public_values.to_vec()), "out")?;
+        if !out_cycles.is_null() {
+            out_cycles.write(report.total_instruction_count());
+        }
+        Ok(())
+    })
+}
+
+/// Proves a program in the given mode, one of the [`MonerochanProofMode`] values.
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_prove(
+    prover: *const MonerochanProver,
+    pk: *const MonerochanProvingKey,
+    stdin: *const MonerochanStdin,
+    mode: u32,
+    out: *mut *mut MonerochanProof,
+) -> MonerochanStatus {
+    ffi_call(|| {
+        let mode = MonerochanProofMode::try_from(mode)?;
+        let prover = arg(prover, "prover")?;
+        let pk = arg(pk, "pk")?;
+        let stdin = arg(stdin, "stdin")?;
+        if out.is_null() {
+            return Err(InvalidArgument("out").into());
+        }
+        let proof = prover.0.prove(&pk.0, &stdin.0, mode.into())?;
+        write_out(out, Box::into_raw(Box::new(MonerochanProof(proof))), "out")
+    })
+}
+
+/// Verifies a proof against the verifying key of a program.
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_verify(
+    prover: *const MonerochanProver,
+    proof: *const MonerochanProof,
+    vk: *const MonerochanVerifyingKey,
+) -> MonerochanStatus {
+    ffi_call(|| {
+        let prover = arg(prover, "prover")?;
+        let proof = arg(proof, "proof")?;
+        let vk = arg(vk, "vk")?;
+        prover.0.verify(&proof.0, &vk.0).map_err(|err| anyhow::anyhow!("{err}"))
+    })
+}
+
+/// Writes the public values of a proof to `out`.
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_proof_public_values(
+    proof: *const MonerochanProof,
+    out: *mut MonerochanBuffer,
+) -> MonerochanStatus {
+    ffi_call(|| {
+        let proof = arg(proof, "proof")?;
+        write_out(out, MonerochanBuffer::new(proof.0.public_values.to_vec()), "out")
+    })
+}
+
+/// Writes the encoding of a PLONK or Groth16 proof accepted by the onchain verifiers to `out`.
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_proof_onchain_bytes(
+    proof: *const MonerochanProof,
+    out: *mut MonerochanBuffer,
+) -> MonerochanStatus {
+    ffi_call(|| {
+        let proof = arg(proof, "proof")?;
+        write_out(out, MonerochanBuffer::new(proof.0.bytes()), "out")
+    })
+}
+
+/// Serializes a proof in the format of `MONEROCHANProofWithPublicValues::save`.
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_proof_serialize(
+    proof: *const MonerochanProof,
+    out: *mut MonerochanBuffer,
+) -> MonerochanStatus {
+    ffi_call(|| {
+        let proof = arg(proof, "proof")?;
+        write_out(out, MonerochanBuffer::new(bincode::serialize(&proof.0)?), "out")
+    })
+}
+
+/// Deserializes a proof serialized by [`monerochan_proof_serialize`].
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_proof_deserialize(
+    data: *const u8,
+    len: usize,
+    out: *mut *mut MonerochanProof,
+) -> MonerochanStatus {
+    ffi_call(|| {
+        let proof = bincode::deserialize(bytes_arg(data, len, "data")?)?;
+        write_out(out, Box::into_raw(Box::new(MonerochanProof(proof))), "out")
+    })
+}
+
+/// Saves a proof to a file.
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_proof_save(
+    proof: *const MonerochanProof,
+    path: *const c_char,
+) -> MonerochanStatus {
+    ffi_call(|| {
+        let proof = arg(proof, "proof")?;
+        proof.0.save(str_arg(path, "path")?)?;
+        Ok(())
+    })
+}
+
+/// Loads a proof from a file.
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_proof_load(
+    path: *const c_char,
+    out: *mut *mut MonerochanProof,
+) -> MonerochanStatus {
+    ffi_call(|| {
+        let proof = MONEROCHANProofWithPublicValues::load(str_arg(path, "path")?)?;
+        write_out(out, Box::into_raw(Box::new(MonerochanProof(proof))), "out")
+    })
+}
+
+/// Releases a proof.
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_proof_free(proof: *mut MonerochanProof) {
+    if !proof.is_null() {
+        drop(Box::from_raw(proof));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_null_arguments_set_the_last_error() {
+        let status = unsafe { monerochan_stdin_write_vec(ptr::null_mut(), ptr::null(), 0) };
+        assert_eq!(status, MonerochanStatus::InvalidArgument);
+        let error = unsafe { CStr::from_ptr(monerochan_last_error()) };
+        assert_eq!(error.to_str().unwrap(), "invalid argument: stdin");
+    }
+
+    #[test]
+    fn test_invalid_proof_mode() {
+        for mode in [
+            MonerochanProofMode::Core,
+            MonerochanProofMode::Compressed,
+            MonerochanProofMode::Plonk,
+            MonerochanProofMode::Groth16,
+        ] {
+            assert_eq!(MonerochanProofMode::try_from(mode as u32).unwrap(), mode);
+        }
+
+        let out = ptr::null_mut();
+        let status = unsafe { monerochan_prove(ptr::null(), ptr::null(), ptr::null(), 4, out) };
+        assert_eq!(status, MonerochanStatus::InvalidArgument);
+        let error = unsafe { CStr::from_ptr(monerochan_last_error()) };
+        assert_eq!(error.to_str().unwrap(), "invalid argument: mode");
+    }
+
+    #[test]
+    fn test_buffer_roundtrip() {
+        let stdin = monerochan_stdin_new();
+        let data = [1u8, 2, 3];
+        let status = unsafe { monerochan_stdin_write_vec(stdin, data.as_ptr(), data.len()) };
+        assert_eq!(status, MonerochanStatus::Ok);
+        assert_eq!(unsafe { &(*stdin).0.buffer }, &vec![data.to_vec()]);
+        unsafe { monerochan_stdin_free(stdin) };
+
+        let buffer = MonerochanBuffer::new(vec![4, 5]);
+        assert_eq!(unsafe { slice::from_raw_parts(buffer.data, buffer.len) }, &[4, 5]);
+        unsafe { monerochan_buffer_free(buffer) };
+        unsafe { monerochan_buffer_free(MonerochanBuffer { data: ptr::null_mut(), len: 0 }) };
+    }
+}
@@ -1,9 +1,11 @@
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
 use monerochan_cli::{
     commands::{
-        build::BuildCmd, build_toolchain::BuildToolchainCmd,
-        install_toolchain::InstallToolchainCmd, new::NewCmd, vkey::VkeyCmd,
+        api_key::ApiKeyCmd, build::BuildCmd, build_toolchain::BuildToolchainCmd, doctor::DoctorCmd,
+        fixtures::FixturesCmd, install_toolchain::InstallToolchainCmd, network::NetworkCmd,
+        new::NewCmd, report::ReportCmd, serve::ServeCmd, stdin::StdinCmd, upgrade::UpgradeCmd,
+        vkey::VkeyCmd, worker::WorkerCmd,
     },
     MONEROCHAN_VERSION_MESSAGE,
 };
@@ -24,20 +26,45 @@ pub struct ProveCli {
 #[derive(Subcommand)]
 pub enum ProveCliCommands {
     New(NewCmd),
+    ApiKey(ApiKeyCmd),
     Build(BuildCmd),
     BuildToolchain(BuildToolchainCmd),
+    Doctor(DoctorCmd),
+    Fixtures(FixturesCmd),
     InstallToolchain(InstallToolchainCmd),
     Vkey(VkeyCmd),
+    Network(NetworkCmd),
+    Stdin(StdinCmd),
+    Report(ReportCmd),
+    Serve(ServeCmd),
+    Upgrade(UpgradeCmd),
+    Worker(WorkerCmd),
 }
 
 fn main() -> Result<()> {
-    let Cargo::Monerochan(args) = Cargo::parse();
+    let matches = Cargo::command().get_matches();
+    let Cargo::Monerochan(args) = Cargo::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
 
     match args.command {
         ProveCliCommands::New(cmd) => cmd.run(),
+        ProveCliCommands::ApiKey(cmd) => cmd.run(),
         ProveCliCommands::Build(cmd) => cmd.run(),
         ProveCliCommands::BuildToolchain(cmd) => cmd.run(),
+        ProveCliCommands::Doctor(cmd) => cmd.run(),
+        ProveCliCommands::Fixtures(cmd) => cmd.run(),
         ProveCliCommands::InstallToolchain(cmd) => cmd.run(),
         ProveCliCommands::Vkey(cmd) => cmd.run(),
+        ProveCliCommands::Network(cmd) => cmd.run(),
+        ProveCliCommands::Stdin(cmd) => {
+            let matches = matches
+                .subcommand_matches("monerochan")
+                .and_then(|matches| matches.subcommand_matches("stdin"))
+                .expect("the stdin subcommand was parsed");
+            cmd.run(matches)
+        }
+        ProveCliCommands::Report(cmd) => cmd.run(),
+        ProveCliCommands::Serve(cmd) => cmd.run(),
+        ProveCliCommands::Upgrade(cmd) => cmd.run(),
+        ProveCliCommands::Worker(cmd) => cmd.run(),
     }
 }
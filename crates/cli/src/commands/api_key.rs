@@ -0,0 +1,125 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use monerochan::network::server::{api_key, ApiKeys, Scope};
+use prettytable::{format, row, Table};
+
+#[derive(Parser)]
+#[command(
+    name = "api-key",
+    about = "Mint and revoke the API keys of a self-hosted proving service.",
+    long_about = "Mint and revoke the API keys of a self-hosted proving service.\n\n\
+    The keys file is read by `cargo monerochan serve --api-keys`, and only holds the digests of \
+    the keys. The service reads it when it starts, so restart it to apply the changes."
+)]
+pub struct ApiKeyCmd {
+    /// The keys file, created if it does not exist.
+    #[arg(long, env = "MONEROCHAN_API_KEYS", default_value = "api-keys.json")]
+    keys: PathBuf,
+
+    #[command(subcommand)]
+    command: ApiKeySubcommand,
+}
+
+#[derive(Subcommand)]
+enum ApiKeySubcommand {
+    /// Mint a key and print it. The key cannot be recovered later.
+    Mint {
+        /// The client the key authenticates as, under which its requests are listed and
+        /// accounted.
+        #[arg(long)]
+        client: String,
+        /// What the key may do.
+        #[arg(long = "scope", value_enum, required = true)]
+        scopes: Vec<ScopeArg>,
+    },
+    /// Revoke a key.
+    Revoke {
+        /// The digest of the key, as listed.
+        digest: String,
+    },
+    /// List the keys.
+    List,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ScopeArg {
+    Prove,
+    Read,
+    Cancel,
+    Pin,
+}
+
+impl From<ScopeArg> for Scope {
+    fn from(scope: ScopeArg) -> Self {
+        match scope {
+            ScopeArg::Prove => Self::Prove,
+            ScopeArg::Read => Self::Read,
+            ScopeArg::Cancel => Self::Cancel,
+            ScopeArg::Pin => Self::Pin,
+        }
+    }
+}
+
+impl ApiKeyCmd {
+    pub fn run(&self) -> Result<()> {
+        let mut keys =
+            if self.keys.exists() { ApiKeys::load(&self.keys)? } else { ApiKeys::default() };
+
+        match &self.command {
+            ApiKeySubcommand::Mint { client, scopes } => {
+                let key = keys.mint(client, scopes.iter().copied().map(Scope::from).collect());
+                keys.save(&self.keys)?;
+                println!("Minted a key for {client}, with digest {}:", api_key::digest(&key));
+                println!("{key}");
+            }
+            ApiKeySubcommand::Revoke { digest } => {
+                if !keys.revoke(digest) {
+                    bail!("no key with digest {digest} in {}", self.keys.display());
+                }
+                keys.save(&self.keys)?;
+                println!("Revoked {digest}, restart the service to apply it");
+            }
+            ApiKeySubcommand::List => {
+                let mut table = Table::new();
+                table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+                table.set_titles(row!["Digest", "Client", "Scopes", "Revoked"]);
+                let mut grants: Vec<_> = keys.keys.iter().collect();
+                grants.sort_by(|(a, _), (b, _)| a.cmp(b));
+                for (digest, grant) in grants {
+                    let scopes: Vec<_> = grant.scopes.iter().map(|s| format!("{s:?}")).collect();
+                    table.add_row(row![
+                        digest,
+                        grant.client,
+                        scopes.join(", ").to_lowercase(),
+                        if grant.revoked { "yes" } else { "no" },
+                    ]);
+                }
+                table.printstd();
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_api_key_cmd() {
+        let cmd = ApiKeyCmd::parse_from([
+            "api-key", "--keys", "keys.json", "mint", "--client", "ci", "--scope", "prove",
+            "--scope", "read",
+        ]);
+        assert_eq!(cmd.keys, PathBuf::from("keys.json"));
+        let ApiKeySubcommand::Mint { client, scopes } = cmd.command else {
+            panic!("expected the mint subcommand");
+        };
+        assert_eq!(client, "ci");
+        let scopes: Vec<Scope> = scopes.into_iter().map(Scope::from).collect();
+        assert_eq!(scopes, vec![Scope::Prove, Scope::Read]);
+    }
+}
@@ -1,17 +1,48 @@
-use anyhow::Result;
+use std::process::Command;
+
+use anyhow::{Context, Result};
 use clap::Parser;
-use monerochan_build::{execute_build_program, BuildArgs};
+use monerochan_build::{execute_build_program, watch_program, BuildArgs};
 
 #[derive(Parser)]
 #[command(name = "build", about = "Compile an MONEROCHAN program")]
 pub struct BuildCmd {
     #[command(flatten)]
     build_args: BuildArgs,
+
+    /// Rebuild the program every time its sources change.
+    #[arg(long)]
+    watch: bool,
+
+    /// A shell command to run after each successful build, e.g. `cargo run --release`.
+    #[arg(long, value_name = "COMMAND")]
+    exec: Option<String>,
 }
 
 impl BuildCmd {
     pub fn run(&self) -> Result<()> {
+        if self.watch {
+            return watch_program(&self.build_args, None, |_| self.run_exec());
+        }
+
         execute_build_program(&self.build_args, None)?;
+        self.run_exec()
+    }
+
+    fn run_exec(&self) -> Result<()> {
+        let Some(exec) = &self.exec else {
+            return Ok(());
+        };
+
+        let status = if cfg!(windows) {
+            Command::new("cmd").args(["/C", exec]).status()
+        } else {
+            Command::new("sh").args(["-c", exec]).status()
+        }
+        .with_context(|| format!("failed to run `{exec}`"))?;
+        if !status.success() {
+            anyhow::bail!("`{exec}` exited with {status}");
+        }
 
         Ok(())
     }
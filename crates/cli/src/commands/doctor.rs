@@ -0,0 +1,220 @@
+use std::{
+    env, fs,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use anyhow::{bail, Result};
+use clap::Parser;
+use monerochan::{
+    install::{groth16_circuit_artifacts_dir, plonk_circuit_artifacts_dir},
+    EnvProver, MONEROCHAN_CIRCUIT_VERSION,
+};
+use yansi::Paint;
+
+use crate::RUSTUP_TOOLCHAIN_NAME;
+
+#[derive(Parser)]
+#[command(name = "doctor", about = "Diagnose the MONEROCHAN development environment.")]
+pub struct DoctorCmd {}
+
+/// The outcome of a check.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// A diagnosed part of the environment.
+struct Check {
+    name: &'static str,
+    status: Status,
+    detail: String,
+    /// How to fix the environment, if the check did not pass.
+    fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Self { name, status: Status::Ok, detail: detail.into(), fix: None }
+    }
+
+    fn warning(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { name, status: Status::Warning, detail: detail.into(), fix: Some(fix.into()) }
+    }
+
+    fn error(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { name, status: Status::Error, detail: detail.into(), fix: Some(fix.into()) }
+    }
+}
+
+/// The files of the Plonk and Groth16 circuit artifacts.
+const PLONK_ARTIFACTS: &[&str] =
+    &["plonk_circuit.bin", "plonk_pk.bin", "plonk_vk.bin", "PlonkVerifier.sol"];
+const GROTH16_ARTIFACTS: &[&str] =
+    &["groth16_circuit.bin", "groth16_pk.bin", "groth16_vk.bin", "Groth16Verifier.sol"];
+
+impl DoctorCmd {
+    pub fn run(&self) -> Result<()> {
+        let prover = env::var("MONEROCHAN_PROVER").unwrap_or_else(|_| "cpu".to_string());
+
+        let checks = [
+            check_toolchain(),
+            check_docker(),
+            check_cuda(&prover),
+            check_env(&prover),
+            check_artifacts("plonk", &plonk_circuit_artifacts_dir(), PLONK_ARTIFACTS),
+            check_artifacts("groth16", &groth16_circuit_artifacts_dir(), GROTH16_ARTIFACTS),
+        ];
+
+        for check in &checks {
+            let status = match check.status {
+                Status::Ok => Paint::green("ok").to_string(),
+                Status::Warning => Paint::yellow("warning").to_string(),
+                Status::Error => Paint::red("error").to_string(),
+            };
+            println!("[{status}] {}: {}", check.name, check.detail);
+            if let Some(fix) = &check.fix {
+                println!("    fix: {fix}");
+            }
+        }
+
+        let errors = checks.iter().filter(|check| check.status == Status::Error).count();
+        if errors > 0 {
+            bail!("{errors} check(s) failed");
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs a command, returning its standard output if it succeeds.
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).stderr(Stdio::null()).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn check_toolchain() -> Check {
+    const NAME: &str = "toolchain";
+
+    let Some(toolchains) = command_output("rustup", &["toolchain", "list"]) else {
+        return Check::error(NAME, "rustup is not installed", "install Rust from https://rustup.rs");
+    };
+    if !toolchains.lines().any(|line| line.starts_with(RUSTUP_TOOLCHAIN_NAME)) {
+        return Check::error(
+            NAME,
+            format!("the {RUSTUP_TOOLCHAIN_NAME} toolchain is not installed"),
+            "run `cargo monerochan install-toolchain`",
+        );
+    }
+
+    let toolchain = format!("+{RUSTUP_TOOLCHAIN_NAME}");
+    match command_output("rustc", &[&toolchain, "--version"]) {
+        Some(version) => Check::ok(NAME, version),
+        None => Check::error(
+            NAME,
+            format!("the {RUSTUP_TOOLCHAIN_NAME} toolchain is installed, but rustc does not run"),
+            "reinstall it with `cargo monerochan install-toolchain`",
+        ),
+    }
+}
+
+fn check_docker() -> Check {
+    const NAME: &str = "docker";
+
+    if command_output("docker", &["--version"]).is_none() {
+        return Check::warning(
+            NAME,
+            "docker is not installed, so Plonk and Groth16 proofs cannot be generated",
+            "install Docker from https://docs.docker.com/get-docker, or enable the `native-gnark` \
+             feature of the SDK",
+        );
+    }
+    match command_output("docker", &["info", "--format", "{{.ServerVersion}}"]) {
+        Some(version) => Check::ok(NAME, format!("docker daemon {version}")),
+        None => Check::warning(
+            NAME,
+            "the docker daemon is not running or not accessible",
+            "start Docker, and make sure that your user can access it (e.g. is in the `docker` \
+             group)",
+        ),
+    }
+}
+
+fn check_cuda(prover: &str) -> Check {
+    const NAME: &str = "cuda";
+
+    let gpus = command_output("nvidia-smi", &["--list-gpus"]).unwrap_or_default();
+    let count = gpus.lines().filter(|line| !line.trim().is_empty()).count();
+    match (count, prover) {
+        (0, "cuda") => Check::error(
+            NAME,
+            "MONEROCHAN_PROVER=cuda, but no GPU is visible",
+            "install the NVIDIA driver and the NVIDIA Container Toolkit, and check `nvidia-smi`",
+        ),
+        (0, _) => Check::ok(NAME, "no GPU visible, only needed with MONEROCHAN_PROVER=cuda"),
+        (count, _) => Check::ok(NAME, format!("{count} GPU(s) visible")),
+    }
+}
+
+fn check_env(prover: &str) -> Check {
+    const NAME: &str = "environment";
+
+    if let Err(err) = EnvProver::validate_env() {
+        return Check::error(NAME, err.to_string(), "fix or unset the environment variable");
+    }
+
+    // Never print the keys, only whether they are set.
+    let key = ["MONEROCHAN_NETWORK_PRIVATE_KEY", "BASE_PRIVATE_KEY"]
+        .into_iter()
+        .find(|name| env::var(name).is_ok_and(|value| !value.is_empty()));
+    let rpc = env::var("NETWORK_RPC_URL").unwrap_or_else(|_| "default".to_string());
+    let detail = format!(
+        "MONEROCHAN_PROVER={prover}, NETWORK_RPC_URL={rpc}, network key {}",
+        key.map_or("not set".to_string(), |name| format!("set in {name}"))
+    );
+
+    if prover == "network" && key.is_none() {
+        return Check::error(
+            NAME,
+            detail,
+            "set MONEROCHAN_NETWORK_PRIVATE_KEY to the private key of your network account",
+        );
+    }
+    if env::var("MONEROCHAN_PROVER").is_err() {
+        return Check::warning(
+            NAME,
+            detail,
+            "set MONEROCHAN_PROVER to one of mock, cpu, cuda or network (defaults to cpu)",
+        );
+    }
+    Check::ok(NAME, detail)
+}
+
+fn check_artifacts(name: &'static str, dir: &Path, files: &[&str]) -> Check {
+    if !dir.exists() {
+        return Check::ok(
+            name,
+            format!(
+                "{MONEROCHAN_CIRCUIT_VERSION} artifacts not installed, they are downloaded on \
+                 first use"
+            ),
+        );
+    }
+
+    let corrupted = files
+        .iter()
+        .filter(|file| fs::metadata(dir.join(file)).map_or(true, |metadata| metadata.len() == 0))
+        .copied()
+        .collect::<Vec<_>>();
+    if corrupted.is_empty() {
+        Check::ok(name, format!("{MONEROCHAN_CIRCUIT_VERSION} artifacts at {}", dir.display()))
+    } else {
+        Check::error(
+            name,
+            format!("{} missing or empty in {}", corrupted.join(", "), dir.display()),
+            format!("delete {} so that the artifacts are downloaded again", dir.display()),
+        )
+    }
+}
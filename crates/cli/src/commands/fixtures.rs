@@ -0,0 +1,121 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{bail, Context, Result};
+use clap::{Parser, ValueEnum};
+use monerochan::{utils::setup_logger, HashableKey, MONEROCHANStdin, ProveConfig, ProverClient};
+
+use super::vkey::Elf;
+
+#[derive(Parser)]
+#[command(
+    name = "fixtures",
+    about = "Generate the JSON proof fixture of a program for contract tests.",
+    long_about = "Generate the JSON proof fixture of a program for contract tests.\n\n\
+    The fixture holds the verification key hash, the public values and the proof bytes of the \
+    program, as read by the Foundry tests of the EVM template, and is written to \
+    <out>/<mode>-fixture.json."
+)]
+pub struct FixturesCmd {
+    /// The ELF of the program.
+    #[command(flatten)]
+    elf: Elf,
+
+    /// The kind of proof to generate.
+    #[arg(long, value_enum, default_value_t = FixtureMode::Groth16)]
+    mode: FixtureMode,
+
+    /// The serialized stdin of the program, as written by `cargo monerochan stdin`.
+    #[arg(long)]
+    stdin: Option<PathBuf>,
+
+    /// The directory to write the fixture to.
+    #[arg(long, default_value = "fixtures")]
+    out: PathBuf,
+
+    /// A prove config to apply to the request, such as the cycle limit. Its mode is overridden by
+    /// --mode.
+    #[arg(long, env = "MONEROCHAN_PROVE_CONFIG")]
+    prove_config: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum FixtureMode {
+    Groth16,
+    Plonk,
+}
+
+impl FixtureMode {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Groth16 => "groth16",
+            Self::Plonk => "plonk",
+        }
+    }
+}
+
+impl FixturesCmd {
+    pub fn run(&self) -> Result<()> {
+        setup_logger();
+
+        let mut elf_paths = self.elf.paths()?;
+        if elf_paths.len() != 1 {
+            bail!("{} programs were found, select one with --elf", elf_paths.len());
+        }
+        let (_, elf_path) = elf_paths.remove(0);
+        let elf = fs::read(&elf_path).with_context(|| format!("failed to read {elf_path}"))?;
+        let stdin = match &self.stdin {
+            Some(path) => {
+                let bytes = fs::read(path)
+                    .with_context(|| format!("failed to read stdin from {}", path.display()))?;
+                bincode::deserialize(&bytes).with_context(|| {
+                    format!("{} is not a serialized MONEROCHANStdin", path.display())
+                })?
+            }
+            None => MONEROCHANStdin::new(),
+        };
+
+        let config = match &self.prove_config {
+            Some(path) => ProveConfig::load(path)?,
+            None => ProveConfig::default(),
+        };
+
+        let client = ProverClient::from_env();
+        let (pk, vk) = client.setup(&elf);
+        let prove = client.prove(&pk, &stdin).with_config(&config);
+        let proof = match self.mode {
+            FixtureMode::Groth16 => prove.groth16().run()?,
+            FixtureMode::Plonk => prove.plonk().run()?,
+        };
+        client.verify(&proof, &vk).context("the generated proof is invalid")?;
+
+        let fixture = serde_json::json!({
+            "vkey": vk.bytes32(),
+            "publicValues": format!("0x{}", hex::encode(proof.public_values.as_slice())),
+            "proof": format!("0x{}", hex::encode(proof.bytes())),
+        });
+
+        fs::create_dir_all(&self.out)
+            .with_context(|| format!("failed to create {}", self.out.display()))?;
+        let path = self.out.join(format!("{}-fixture.json", self.mode.name()));
+        fs::write(&path, serde_json::to_string_pretty(&fixture)?)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        println!("Wrote the {} fixture to {}", self.mode.name(), path.display());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fixtures_cmd() {
+        let cmd = FixturesCmd::parse_from(["fixtures", "--elf", "program.elf", "--mode", "plonk"]);
+        assert_eq!(cmd.mode.name(), "plonk");
+        assert_eq!(cmd.out, PathBuf::from("fixtures"));
+        assert!(cmd.stdin.is_none());
+        assert!(cmd.prove_config.is_none());
+        assert_eq!(cmd.elf.paths().unwrap(), vec![(None, "program.elf".to_string())]);
+    }
+}
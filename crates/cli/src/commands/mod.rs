@@ -1,5 +1,14 @@
+pub mod api_key;
 pub mod build;
 pub mod build_toolchain;
+pub mod doctor;
+pub mod fixtures;
 pub mod install_toolchain;
+pub mod network;
 pub mod new;
+pub mod report;
+pub mod serve;
+pub mod stdin;
+pub mod upgrade;
 pub mod vkey;
+pub mod worker;
@@ -0,0 +1,226 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Result};
+use clap::{Parser, Subcommand};
+use monerochan::{
+    network::{
+        proto::api::{JobStatus, ProofMode},
+        prover::RequestFilter,
+    },
+    NetworkProver, ProverClient,
+};
+use prettytable::{format, row, Table};
+
+#[derive(Parser)]
+#[command(name = "network", about = "Manage proof requests on the monero-chan network.")]
+pub struct NetworkCmd {
+    /// The RPC URL of the network API. Defaults to the production network.
+    #[arg(long, env = "NETWORK_RPC_URL")]
+    rpc_url: Option<String>,
+
+    #[command(subcommand)]
+    command: NetworkSubcommand,
+}
+
+#[derive(Subcommand)]
+enum NetworkSubcommand {
+    /// Show the status of a proof request.
+    Status {
+        /// The ID of the proof request.
+        request_id: String,
+    },
+    /// List proof requests.
+    List {
+        /// Only list requests submitted with MONEROCHAN_NETWORK_PRIVATE_KEY or API key.
+        #[arg(long)]
+        mine: bool,
+        /// Only list requests in this status: pending, running, succeeded or failed.
+        #[arg(long)]
+        status: Option<String>,
+        /// Only list requests for this program.
+        #[arg(long)]
+        program: Option<String>,
+    },
+    /// Cancel a pending or running proof request.
+    Cancel {
+        /// The ID of the proof request.
+        request_id: String,
+    },
+    /// Download the proof of a fulfilled request.
+    Download {
+        /// The ID of the proof request.
+        request_id: String,
+        /// Where to write the proof.
+        #[arg(long, default_value = "proof.bin")]
+        out: PathBuf,
+    },
+    /// List the programs requested with MONEROCHAN_NETWORK_PRIVATE_KEY or API key.
+    Programs,
+    /// Show the metadata of a program.
+    Program {
+        /// The vk hash of the program.
+        program_id: String,
+    },
+    /// Pin a program, so the network retains it.
+    Pin {
+        /// The vk hash of the program.
+        program_id: String,
+    },
+    /// Unpin a program.
+    Unpin {
+        /// The vk hash of the program.
+        program_id: String,
+    },
+}
+
+impl NetworkCmd {
+    pub fn run(&self) -> Result<()> {
+        let mut builder = ProverClient::builder().network();
+        if let Some(rpc_url) = &self.rpc_url {
+            builder = builder.rpc_url(rpc_url);
+        }
+        let prover = builder.build();
+
+        tokio::runtime::Runtime::new()?.block_on(self.run_async(&prover))
+    }
+
+    async fn run_async(&self, prover: &NetworkProver) -> Result<()> {
+        match &self.command {
+            NetworkSubcommand::Status { request_id } => {
+                let status = prover.get_proof_status(request_id).await?;
+                println!("Request: {request_id}");
+                println!("Status:  {}", job_status_name(status.status));
+                if !status.error_message.is_empty() {
+                    println!("Error:   {}", status.error_message);
+                }
+                if let Some(execution) = status.execution {
+                    println!("Cycles:  {}", execution.cycles);
+                    if let Some(gas) = execution.gas {
+                        println!("Gas:     {gas}");
+                    }
+                    println!("Public values: 0x{}", hex::encode(execution.public_values));
+                }
+            }
+            NetworkSubcommand::List { mine, status, program } => {
+                if !mine {
+                    bail!("the network only lists requests of the authenticated client, pass --mine");
+                }
+
+                let statuses = match status {
+                    Some(status) => vec![parse_job_status(status)?],
+                    None => Vec::new(),
+                };
+                let filter =
+                    RequestFilter { statuses, program_id: program.clone(), ..Default::default() };
+                let requests = prover.list_requests_filtered(&filter).await?;
+                if requests.is_empty() {
+                    println!("No proof requests found.");
+                    return Ok(());
+                }
+
+                let mut table = Table::new();
+                table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+                table.set_titles(row!["Request", "Program", "Mode", "Status", "Created"]);
+                for request in requests {
+                    table.add_row(row![
+                        request.request_id,
+                        request.program_id,
+                        proof_mode_name(request.proof_mode),
+                        job_status_name(request.status),
+                        request.created_at,
+                    ]);
+                }
+                table.printstd();
+            }
+            NetworkSubcommand::Cancel { request_id } => {
+                if prover.cancel_request(request_id).await? {
+                    println!("Cancelled {request_id}");
+                } else {
+                    println!("Request {request_id} already finished, nothing to cancel");
+                }
+            }
+            NetworkSubcommand::Download { request_id, out } => {
+                prover.download_proof_to_file(request_id, out).await?;
+                println!("Saved proof to {}", out.display());
+            }
+            NetworkSubcommand::Programs => {
+                let programs = prover.list_programs().await?;
+                if programs.is_empty() {
+                    println!("No programs found.");
+                    return Ok(());
+                }
+
+                let mut table = Table::new();
+                table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+                table.set_titles(row![
+                    "Program", "Size", "First seen", "Last seen", "Proofs", "Failed", "Pending",
+                    "Pinned"
+                ]);
+                for program in programs {
+                    table.add_row(row![
+                        program.program_id,
+                        program.elf_size,
+                        program.first_seen,
+                        program.last_seen,
+                        program.proofs,
+                        program.failed,
+                        program.pending,
+                        if program.pinned { "yes" } else { "no" },
+                    ]);
+                }
+                table.printstd();
+            }
+            NetworkSubcommand::Program { program_id } => {
+                let program = prover.get_program(program_id).await?;
+                println!("Program:    {}", program.program_id);
+                println!("Size:       {} bytes", program.elf_size);
+                println!("First seen: {}", program.first_seen);
+                println!("Last seen:  {}", program.last_seen);
+                println!(
+                    "Proofs:     {} succeeded, {} failed, {} pending",
+                    program.proofs, program.failed, program.pending
+                );
+                println!("Pinned:     {}", if program.pinned { "yes" } else { "no" });
+            }
+            NetworkSubcommand::Pin { program_id } => {
+                prover.pin_program(program_id).await?;
+                println!("Pinned {program_id}");
+            }
+            NetworkSubcommand::Unpin { program_id } => {
+                prover.unpin_program(program_id).await?;
+                println!("Unpinned {program_id}");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn job_status_name(status: i32) -> &'static str {
+    match JobStatus::try_from(status) {
+        Ok(JobStatus::Pending) => "pending",
+        Ok(JobStatus::Running) => "running",
+        Ok(JobStatus::Succeeded) => "succeeded",
+        Ok(JobStatus::Failed) => "failed",
+        Ok(JobStatus::Unspecified) | Err(_) => "unknown",
+    }
+}
+
+fn parse_job_status(status: &str) -> Result<JobStatus> {
+    JobStatus::from_str_name(&format!("JOB_STATUS_{}", status.to_uppercase()))
+        .filter(|status| *status != JobStatus::Unspecified)
+        .ok_or_else(|| {
+            anyhow!("invalid status {status}, expected pending, running, succeeded or failed")
+        })
+}
+
+fn proof_mode_name(mode: i32) -> &'static str {
+    match ProofMode::try_from(mode) {
+        Ok(ProofMode::Core) => "core",
+        Ok(ProofMode::Compressed) => "compressed",
+        Ok(ProofMode::Plonk) => "plonk",
+        Ok(ProofMode::Groth16) => "groth16",
+        Ok(ProofMode::ExecuteOnly) => "execute-only",
+        Ok(ProofMode::Unspecified) | Err(_) => "unknown",
+    }
+}
@@ -1,10 +1,11 @@
-use anyhow::Result;
-use clap::{Args, Parser};
+use anyhow::{Context, Result};
+use clap::{Args, Parser, ValueEnum};
 use std::{
     fs,
     path::Path,
     process::{Command, Stdio},
 };
+use toml_edit::{value, DocumentMut, InlineTable, Item, Value};
 use yansi::Paint;
 
 #[derive(Args)]
@@ -13,6 +14,32 @@ struct TemplateType {
     /// Use the `bare` template which includes just a program and script.
     #[arg(long)]
     bare: bool,
+
+    /// Use the `bare` template with a `no_std` program, which only depends on `core` and `alloc`.
+    #[arg(long)]
+    no_std: bool,
+
+    /// Use the `bare` template with a program written in C or C++, linked with the zkVM runtime.
+    #[arg(long, value_enum)]
+    language: Option<Language>,
+}
+
+/// The language of a program other than Rust.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Language {
+    C,
+    #[value(alias = "c++")]
+    Cpp,
+}
+
+impl Language {
+    /// The path of the source of the program, relative to the program crate.
+    const fn source(self) -> &'static str {
+        match self {
+            Self::C => "c/main.c",
+            Self::Cpp => "c/main.cpp",
+        }
+    }
 }
 
 #[derive(Parser)]
@@ -28,6 +55,10 @@ pub struct NewCmd {
     /// Version of monerochan-project-template to use (branch or tag).
     #[arg(long, default_value = "main")]
     version: String,
+
+    /// Do not add a `lib` crate of the input and output types shared by the program and script.
+    #[arg(long)]
+    no_lib: bool,
 }
 
 const TEMPLATE_REPOSITORY_URL: &str =
@@ -66,12 +97,497 @@ impl NewCmd {
         // Remove the .git directory.
         fs::remove_dir_all(root.join(".git"))?;
 
+        let no_std = self.template.no_std;
+        if no_std {
+            make_program_no_std(root)?;
+        }
+
+        let language = self.template.language;
+        if let Some(language) = language {
+            make_program_ffi(root, language)?;
+        }
+
+        // The types of the shared lib are encoded with bincode, which the C program does not use.
+        let scaffold_lib = !self.no_lib && language.is_none() && !root.join("lib").exists();
+        if scaffold_lib {
+            add_shared_lib(root, &self.name, no_std)?;
+        }
+
         println!(
             " \x1b[1m{}\x1b[0m {}",
             Paint::green("Initialized"),
             self.name
         );
+        if scaffold_lib {
+            println!(
+                " \x1b[1m{}\x1b[0m lib/src/lib.rs, the input and output types shared by the \
+                 program and script",
+                Paint::green("Added")
+            );
+        }
+        if no_std {
+            println!(
+                " \x1b[1m{}\x1b[0m program/src/main.rs, a `no_std` program",
+                Paint::green("Added")
+            );
+        }
+        if let Some(language) = language {
+            println!(
+                " \x1b[1m{}\x1b[0m program/{}, a {} program linked with the zkVM runtime",
+                Paint::green("Added"),
+                language.source(),
+                match language {
+                    Language::C => "C",
+                    Language::Cpp => "C++",
+                }
+            );
+        }
 
         Ok(())
     }
 }
+
+/// The manifest of the shared `lib` crate, with `{name}` replaced by the name of the crate.
+const LIB_MANIFEST: &str = r#"[package]
+name = "{name}"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+serde = { version = "1.0", default-features = false, features = ["derive"] }
+
+[dev-dependencies]
+bincode = "1.3.3"
+"#;
+
+/// The source of the shared `lib` crate.
+const LIB_SOURCE: &str = r#"//! The types shared by the program and the script.
+//!
+//! The script writes an [`Input`] to the stdin of the program, and the program commits an
+//! [`Output`] to its public values. Both sides encode these types with bincode, so they must be
+//! built with this exact crate: `monerochan-build` fails the build of the program if the program
+//! and the script use different versions of it.
+
+use serde::{Deserialize, Serialize};
+
+/// The input of the program, written by the script.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Input {
+    /// The number of Fibonacci numbers to compute.
+    pub n: u32,
+}
+
+/// The output of the program, committed to its public values.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Output {
+    /// The number of Fibonacci numbers computed.
+    pub n: u32,
+    /// The `n - 1`th Fibonacci number, modulo `2^32`.
+    pub a: u32,
+    /// The `n`th Fibonacci number, modulo `2^32`.
+    pub b: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_round_trip() {
+        let input = Input { n: 20 };
+        let bytes = bincode::serialize(&input).unwrap();
+        assert_eq!(bincode::deserialize::<Input>(&bytes).unwrap(), input);
+    }
+
+    #[test]
+    fn test_output_round_trip() {
+        let output = Output { n: 20, a: 4181, b: 6765 };
+        let bytes = bincode::serialize(&output).unwrap();
+        assert_eq!(bincode::deserialize::<Output>(&bytes).unwrap(), output);
+    }
+}
+"#;
+
+/// The crates of the zkVM runtime, as named in the manifest of the program.
+const RUNTIME_CRATES: &[&str] = &["monerochan-zkvm", "monerochan-runtime"];
+
+/// The source of the `no_std` program.
+const NO_STD_PROGRAM_SOURCE: &str = r#"//! A `no_std` program computing Fibonacci numbers.
+//!
+//! The program only depends on `core` and `alloc`. Values are read and committed as bytes, as the
+//! functions (de)serializing them with bincode require `std`. The public values are `n`, `a` and
+//! `b` as little-endian u32s, which is also their bincode encoding, so the script reads them with
+//! `public_values.read::<u32>()`.
+
+#![no_std]
+#![no_main]
+monerochan_runtime::entrypoint!(main);
+
+use monerochan_runtime::io;
+
+pub fn main() {
+    // The script writes `n` with `stdin.write(&n)`, a little-endian u32.
+    let n = u32::from_le_bytes(io::read_vec().try_into().expect("n should be a u32"));
+
+    let (mut a, mut b) = (0u32, 1u32);
+    for _ in 0..n {
+        let c = a.wrapping_add(b);
+        a = b;
+        b = c;
+    }
+
+    io::commit_slice(&n.to_le_bytes());
+    io::commit_slice(&a.to_le_bytes());
+    io::commit_slice(&b.to_le_bytes());
+}
+"#;
+
+/// Makes the program of a new project `no_std`: replaces its source, and disables the `std`
+/// feature of the zkVM runtime.
+fn make_program_no_std(root: &Path) -> Result<()> {
+    let program = root.join("program");
+    fs::create_dir_all(program.join("src"))?;
+    fs::write(program.join("src").join("main.rs"), NO_STD_PROGRAM_SOURCE)?;
+
+    edit_manifest(&program.join("Cargo.toml"), disable_runtime_std)
+}
+
+/// Disables the `std` feature of the zkVM runtime in the manifest of a program, keeping its io
+/// functions.
+fn disable_runtime_std(document: &mut DocumentMut) {
+    edit_runtime_dependencies(document, |table| {
+        add_feature(table, "lib");
+        table.insert("default-features", false.into());
+    });
+}
+
+/// The header declaring the C functions of the zkVM runtime.
+const FFI_HEADER: &str = r#"/*
+ * The C functions of the MONEROCHAN zkVM runtime, provided by its `ffi` feature.
+ *
+ * The C library is not linked into the program: the runtime provides `malloc`, `calloc`,
+ * `realloc` and `free`, and the Rust toolchain provides `memcpy`, `memmove`, `memset` and
+ * `memcmp`. C++ programs are built without exceptions, RTTI or the C++ standard library.
+ */
+
+#ifndef MONEROCHAN_H
+#define MONEROCHAN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The program, called by the Rust entrypoint in `src/main.rs`. */
+void monerochan_main(void);
+
+/*
+ * Reads the next buffer of the input stream, written by the script with `stdin.write`, and
+ * writes its length to `len`. Returns NULL if the input stream is exhausted. The buffer is owned
+ * by the runtime, and must not be freed.
+ */
+uint8_t *monerochan_read(size_t *len);
+
+/* Commits bytes to the public values of the program. */
+void monerochan_commit(const uint8_t *buf, size_t len);
+
+/* Writes bytes to the hint stream. */
+void monerochan_hint(const uint8_t *buf, size_t len);
+
+/* Writes bytes to a file descriptor, 1 for stdout and 2 for stderr. */
+void monerochan_write(uint32_t fd, const uint8_t *buf, size_t len);
+
+/* Halts the program with the given exit code, committing the public values written so far. */
+__attribute__((noreturn)) void monerochan_exit(uint8_t exit_code);
+
+/*
+ * Records the outcome of the program in a header starting its public values, read by the script
+ * with `public_values.outcome()`. It must be called before anything is committed.
+ */
+void monerochan_record_outcome(void);
+
+/*
+ * Rejects the input of the program with an error code, and halts it successfully, so that the
+ * rejection can be proven. The values committed so far are kept. The outcome must be recorded.
+ */
+__attribute__((noreturn)) void monerochan_reject(uint32_t code);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* MONEROCHAN_H */
+"#;
+
+/// The source of the C or C++ program, which is both valid C and C++.
+const FFI_PROGRAM_SOURCE: &str = r#"/*
+ * A program computing Fibonacci numbers.
+ *
+ * The public values are `n`, `a` and `b` as little-endian u32s, which is also their bincode
+ * encoding, so the script reads them with `public_values.read::<u32>()`.
+ */
+
+#include <stdint.h>
+#include <string.h>
+
+#include "monerochan.h"
+
+static void fail(const char *message, size_t len) {
+    monerochan_write(2, (const uint8_t *)message, len);
+    monerochan_exit(1);
+}
+
+void monerochan_main(void) {
+    /* The script writes `n` with `stdin.write(&n)`, a little-endian u32. */
+    size_t len;
+    const uint8_t *input = monerochan_read(&len);
+    if (input == NULL || len != sizeof(uint32_t)) {
+        static const char message[] = "n should be a u32
+";
+        fail(message, sizeof(message) - 1);
+    }
+    /* The zkVM is little-endian, so the bytes are copied as is. */
+    uint32_t n;
+    memcpy(&n, input, sizeof(n));
+
+    uint32_t a = 0, b = 1;
+    for (uint32_t i = 0; i < n; i++) {
+        uint32_t c = a + b;
+        a = b;
+        b = c;
+    }
+
+    monerochan_commit((const uint8_t *)&n, sizeof(n));
+    monerochan_commit((const uint8_t *)&a, sizeof(a));
+    monerochan_commit((const uint8_t *)&b, sizeof(b));
+}
+"#;
+
+/// The entrypoint of the C or C++ program, with `{source}` replaced by the path of its source.
+const FFI_PROGRAM_ENTRYPOINT: &str = r#"//! The entrypoint of the program written in `{source}`.
+//!
+//! The sources are compiled by `build.rs` and linked with the zkVM runtime, whose C functions are
+//! declared in `c/include/monerochan.h`.
+
+#![no_main]
+monerochan_runtime::entrypoint!(main);
+
+extern "C" {
+    fn monerochan_main();
+}
+
+pub fn main() {
+    // SAFETY: The program only calls the runtime through the functions of `monerochan.h`.
+    unsafe { monerochan_main() }
+}
+"#;
+
+/// The build script compiling the C program.
+const C_BUILD_SCRIPT: &str = r#"//! Compiles the C sources of the program.
+//!
+//! For the zkVM, they are compiled with the toolchain installed by `monerochanup --c-toolchain`.
+
+fn main() {
+    println!("cargo:rerun-if-changed=c");
+    cc::Build::new().file("c/main.c").include("c/include").compile("program");
+}
+"#;
+
+/// The build script compiling the C++ program.
+const CPP_BUILD_SCRIPT: &str = r#"//! Compiles the C++ sources of the program.
+//!
+//! For the zkVM, they are compiled with the toolchain installed by `monerochanup --c-toolchain`.
+
+fn main() {
+    println!("cargo:rerun-if-changed=c");
+    cc::Build::new()
+        .cpp(true)
+        // The C++ standard library is not available in the zkVM.
+        .cpp_link_stdlib(None)
+        .flag("-fno-exceptions")
+        .flag("-fno-rtti")
+        .file("c/main.cpp")
+        .include("c/include")
+        .compile("program");
+}
+"#;
+
+/// Makes the program of a new project a C or C++ program: adds its sources and the build script
+/// compiling them, and enables the C functions of the zkVM runtime.
+fn make_program_ffi(root: &Path, language: Language) -> Result<()> {
+    let program = root.join("program");
+    let source = language.source();
+    fs::create_dir_all(program.join("src"))?;
+    fs::create_dir_all(program.join("c").join("include"))?;
+    fs::write(program.join("c").join("include").join("monerochan.h"), FFI_HEADER)?;
+    fs::write(program.join(source), FFI_PROGRAM_SOURCE)?;
+    fs::write(
+        program.join("src").join("main.rs"),
+        FFI_PROGRAM_ENTRYPOINT.replace("{source}", source),
+    )?;
+    let build_script = match language {
+        Language::C => C_BUILD_SCRIPT,
+        Language::Cpp => CPP_BUILD_SCRIPT,
+    };
+    fs::write(program.join("build.rs"), build_script)?;
+
+    edit_manifest(&program.join("Cargo.toml"), enable_runtime_ffi)
+}
+
+/// Enables the C functions of the zkVM runtime in the manifest of a program, and adds the `cc`
+/// crate compiling its C sources.
+fn enable_runtime_ffi(document: &mut DocumentMut) {
+    edit_runtime_dependencies(document, |table| add_feature(table, "ffi"));
+    document["build-dependencies"]["cc"] = value("1.0");
+}
+
+/// Edits the dependencies on the zkVM runtime in the manifest of a program, as inline tables.
+fn edit_runtime_dependencies(document: &mut DocumentMut, edit: impl Fn(&mut InlineTable)) {
+    let Some(dependencies) = document.get_mut("dependencies") else {
+        return;
+    };
+    for name in RUNTIME_CRATES {
+        let Some(dependency) = dependencies.get_mut(name) else {
+            continue;
+        };
+        let mut table = match dependency.as_value() {
+            Some(Value::InlineTable(table)) => table.clone(),
+            Some(Value::String(version)) => {
+                let mut table = InlineTable::new();
+                table.insert("version", version.value().as_str().into());
+                table
+            }
+            _ => continue,
+        };
+        edit(&mut table);
+        *dependency = value(table);
+    }
+}
+
+/// Adds a feature to a dependency, if it does not have it.
+fn add_feature(table: &mut InlineTable, feature: &str) {
+    let mut features =
+        table.get("features").and_then(Value::as_array).cloned().unwrap_or_default();
+    if !features.iter().any(|found| found.as_str() == Some(feature)) {
+        features.push(feature);
+    }
+    table.insert("features", Value::Array(features));
+}
+
+/// Adds a `lib` crate of the types shared by the program and the script to a new project, and
+/// adds it to the workspace and to the dependencies of the program and the script.
+///
+/// With `no_std`, the crate is `no_std` as well, so that the program does not link `std`.
+fn add_shared_lib(root: &Path, project_name: &str, no_std: bool) -> Result<()> {
+    let project_name = Path::new(project_name)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .context("invalid project name")?;
+    let name = format!("{project_name}-lib");
+
+    let lib = root.join("lib");
+    fs::create_dir_all(lib.join("src"))?;
+    fs::write(lib.join("Cargo.toml"), LIB_MANIFEST.replace("{name}", &name))?;
+    let source = if no_std {
+        LIB_SOURCE.replacen("\n\nuse serde", "\n\n#![cfg_attr(not(test), no_std)]\n\nuse serde", 1)
+    } else {
+        LIB_SOURCE.to_string()
+    };
+    fs::write(lib.join("src").join("lib.rs"), source)?;
+
+    edit_manifest(&root.join("Cargo.toml"), |document| {
+        if let Some(members) = document
+            .get_mut("workspace")
+            .and_then(|workspace| workspace.get_mut("members"))
+            .and_then(Item::as_array_mut)
+        {
+            if !members.iter().any(|member| member.as_str() == Some("lib")) {
+                members.push("lib");
+            }
+        }
+    })?;
+
+    for package in ["program", "script"] {
+        edit_manifest(&root.join(package).join("Cargo.toml"), |document| {
+            let mut dependency = InlineTable::new();
+            dependency.insert("path", "../lib".into());
+            document["dependencies"][name.as_str()] = value(dependency);
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Edits a manifest of the project in place, if it exists.
+fn edit_manifest(path: &Path, edit: impl FnOnce(&mut DocumentMut)) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let mut document: DocumentMut =
+        contents.parse().with_context(|| format!("failed to parse {}", path.display()))?;
+    edit(&mut document);
+    fs::write(path, document.to_string())
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disable_runtime_std() {
+        let mut document: DocumentMut = r#"
+[dependencies]
+monerochan-zkvm = "5.2.0"
+monerochan-runtime = { path = "../runtime", features = ["embedded"] }
+serde = "1.0"
+"#
+        .parse()
+        .unwrap();
+        disable_runtime_std(&mut document);
+
+        let dependencies = &document["dependencies"];
+        for (name, features) in
+            [("monerochan-zkvm", vec!["lib"]), ("monerochan-runtime", vec!["embedded", "lib"])]
+        {
+            let dependency = &dependencies[name];
+            assert_eq!(dependency["default-features"].as_bool(), Some(false));
+            let found = dependency["features"].as_array().unwrap();
+            assert_eq!(found.iter().map(|f| f.as_str().unwrap()).collect::<Vec<_>>(), features);
+        }
+        assert_eq!(dependencies["monerochan-zkvm"]["version"].as_str(), Some("5.2.0"));
+        assert_eq!(dependencies["serde"].as_str(), Some("1.0"));
+    }
+
+    #[test]
+    fn test_enable_runtime_ffi() {
+        let mut document: DocumentMut = r#"
+[dependencies]
+monerochan-zkvm = "5.2.0"
+monerochan-runtime = { path = "../runtime", features = ["ffi"] }
+"#
+        .parse()
+        .unwrap();
+        enable_runtime_ffi(&mut document);
+
+        let dependencies = &document["dependencies"];
+        for name in RUNTIME_CRATES {
+            let features = dependencies[name]["features"].as_array().unwrap();
+            assert_eq!(features.iter().map(|f| f.as_str().unwrap()).collect::<Vec<_>>(), ["ffi"]);
+            assert!(dependencies[name].get("default-features").is_none());
+        }
+        assert_eq!(document["build-dependencies"]["cc"].as_str(), Some("1.0"));
+    }
+
+    #[test]
+    fn test_parse_language() {
+        let cmd = NewCmd::parse_from(["new", "fibonacci", "--language", "c++"]);
+        assert_eq!(cmd.template.language, Some(Language::Cpp));
+        assert!(NewCmd::try_parse_from(["new", "fibonacci", "--bare", "--language", "c"]).is_err());
+    }
+}
@@ -0,0 +1,52 @@
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use monerochan::ExecutionReport;
+
+#[derive(Parser)]
+#[command(name = "report", about = "Inspect execution reports saved as JSON.")]
+pub struct ReportCmd {
+    #[command(subcommand)]
+    command: ReportSubcommand,
+}
+
+#[derive(Subcommand)]
+enum ReportSubcommand {
+    /// Compare two execution reports, e.g. of two versions of a program.
+    Diff {
+        /// The baseline report.
+        before: String,
+        /// The report to compare with the baseline.
+        after: String,
+        /// Print the diff as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+impl ReportCmd {
+    pub fn run(&self) -> Result<()> {
+        match &self.command {
+            ReportSubcommand::Diff { before, after, json } => {
+                let diff = load(before)?.diff(&load(after)?);
+                if *json {
+                    println!("{}", serde_json::to_string_pretty(&diff)?);
+                } else if diff.is_empty() {
+                    println!("The execution reports are identical.");
+                } else {
+                    print!("{diff}");
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn load(path: impl AsRef<Path>) -> Result<ExecutionReport> {
+    let path = path.as_ref();
+    let bytes = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    serde_json::from_slice(&bytes)
+        .with_context(|| format!("{} is not a JSON execution report", path.display()))
+}
@@ -0,0 +1,151 @@
+use std::{net::SocketAddr, path::PathBuf};
+
+use anyhow::{bail, Result};
+use clap::{Parser, ValueEnum};
+use monerochan::{
+    network::{
+        server::{store, ApiKeys, ProvingService, Quotas},
+        solana_client_auth::{
+            NonceStore, DEFAULT_MAX_CHALLENGES, DEFAULT_MAX_CHALLENGES_PER_CLIENT,
+            MAX_CLIENT_AUTH_AGE_SECS,
+        },
+    },
+    utils::setup_logger,
+    Prover, ProverClient,
+};
+use monerochan_prover::components::CpuProverComponents;
+
+#[derive(Parser)]
+#[command(
+    name = "serve",
+    about = "Run a self-hosted proving service that implements the network API."
+)]
+pub struct ServeCmd {
+    /// The address to listen on.
+    #[arg(long, default_value = "127.0.0.1:50051")]
+    addr: SocketAddr,
+
+    /// The address the worker pool listens on, which must only be reachable by the workers. The
+    /// pool is not served without it.
+    #[arg(long)]
+    pool_addr: Option<SocketAddr>,
+
+    /// The token the workers must present to the pool.
+    #[arg(long, env = "MONEROCHAN_WORKER_TOKEN", hide_env_values = true)]
+    worker_token: Option<String>,
+
+    /// Where the jobs and proofs are persisted: `sqlite://<path>`, `postgres://...`,
+    /// `redis://...` or a directory. Postgres and Redis require the CLI to be built with the
+    /// `postgres` or `redis` feature.
+    #[arg(long, env = "MONEROCHAN_JOB_STORE", default_value = "sqlite://monerochan-jobs.db")]
+    store: String,
+
+    /// The prover that generates the proofs. With `none`, the jobs are only proved by the
+    /// workers started with `cargo monerochan worker`, which requires `--pool-addr`.
+    #[arg(long, value_enum, default_value_t = ServeProver::Cpu)]
+    prover: ServeProver,
+
+    /// A JSON file with the quotas of the clients. The clients are unlimited without it.
+    #[arg(long, env = "MONEROCHAN_QUOTAS")]
+    quotas: Option<PathBuf>,
+
+    /// A JSON file with the API keys of the clients, as written by `cargo monerochan api-key`.
+    #[arg(long, env = "MONEROCHAN_API_KEYS")]
+    api_keys: Option<PathBuf>,
+
+    /// How far from the current time the timestamp of a client signature may be, in seconds. The
+    /// nonces of the signatures are remembered for as long, so that none is accepted twice.
+    #[arg(long, default_value_t = MAX_CLIENT_AUTH_AGE_SECS)]
+    auth_window_secs: i64,
+
+    /// Require the client signatures to sign a challenge issued by the service, rather than a
+    /// nonce of their choice.
+    #[arg(long)]
+    require_auth_challenge: bool,
+
+    /// The maximum number of outstanding challenges of a client.
+    #[arg(long, default_value_t = DEFAULT_MAX_CHALLENGES_PER_CLIENT)]
+    max_challenges_per_client: usize,
+
+    /// The maximum number of outstanding challenges of all the clients.
+    #[arg(long, default_value_t = DEFAULT_MAX_CHALLENGES)]
+    max_challenges: usize,
+
+    /// A file to persist the nonces and the challenges in, so that the signatures seen before a
+    /// restart are still rejected after it. They are only kept in memory without it.
+    #[arg(long, env = "MONEROCHAN_NONCE_STORE")]
+    nonce_store: Option<PathBuf>,
+
+    /// The token of the admin API, which is only served with a token.
+    #[arg(long, env = "MONEROCHAN_ADMIN_TOKEN", hide_env_values = true)]
+    admin_token: Option<String>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ServeProver {
+    Cpu,
+    Cuda,
+    Mock,
+    None,
+}
+
+impl ServeCmd {
+    pub fn run(&self) -> Result<()> {
+        setup_logger();
+
+        if self.pool_addr.is_some() && self.worker_token.is_none() {
+            bail!("the worker pool requires a --worker-token");
+        }
+        if matches!(self.prover, ServeProver::None) && self.pool_addr.is_none() {
+            bail!("a service without a prover requires a --pool-addr for its workers");
+        }
+
+        let quotas = self.quotas.as_ref().map(Quotas::load).transpose()?.unwrap_or_default();
+        let api_keys = self.api_keys.as_ref().map(ApiKeys::load).transpose()?.unwrap_or_default();
+
+        let prover: Option<Box<dyn Prover<CpuProverComponents>>> = match self.prover {
+            ServeProver::Cpu => Some(Box::new(ProverClient::builder().cpu().build())),
+            ServeProver::Cuda => Some(Box::new(ProverClient::builder().cuda().build())),
+            ServeProver::Mock => Some(Box::new(ProverClient::builder().mock().build())),
+            ServeProver::None => None,
+        };
+
+        let mut nonces = NonceStore::new(self.auth_window_secs)
+            .require_challenge(self.require_auth_challenge)
+            .max_challenges(self.max_challenges_per_client, self.max_challenges);
+        if let Some(path) = &self.nonce_store {
+            nonces = nonces.persist(path)?;
+        }
+
+        tokio::runtime::Runtime::new()?.block_on(async {
+            let store = store::open(&self.store).await?;
+            let mut service = match prover {
+                Some(prover) => ProvingService::new(prover, store)?,
+                None => ProvingService::coordinator(store),
+            }
+            .quotas(quotas)
+            .api_keys(api_keys)
+            .nonce_store(nonces);
+            if let Some(token) = &self.admin_token {
+                service = service.admin_token(token);
+            }
+            if let Some(token) = &self.worker_token {
+                service = service.worker_token(token);
+            }
+
+            println!(
+                "Serving the network API on http://{}, point NETWORK_RPC_URL at it to prove with \
+                 it.",
+                self.addr
+            );
+            match self.pool_addr {
+                Some(pool_addr) => {
+                    println!("Serving the worker pool on http://{pool_addr}.");
+                    let pool = service.clone().serve_pool(pool_addr);
+                    tokio::try_join!(service.serve(self.addr), pool).map(|_| ())
+                }
+                None => service.serve(self.addr).await,
+            }
+        })
+    }
+}
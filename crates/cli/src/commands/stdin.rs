@@ -0,0 +1,246 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{Context, Result};
+use clap::{ArgMatches, Parser};
+use monerochan::MONEROCHANStdin;
+
+#[derive(Parser)]
+#[command(
+    name = "stdin",
+    about = "Build or inspect a serialized MONEROCHANStdin file.",
+    long_about = "Build or inspect a serialized MONEROCHANStdin file.\n\n\
+    Inputs are written in the order the --write-* flags appear on the command line, which must \
+    match the order in which the program reads them."
+)]
+pub struct StdinCmd {
+    /// Write a u32, read in the program with `io::read::<u32>()`.
+    #[arg(long = "write-u32", value_name = "VALUE")]
+    write_u32: Vec<u32>,
+
+    /// Write a u64, read in the program with `io::read::<u64>()`.
+    #[arg(long = "write-u64", value_name = "VALUE")]
+    write_u64: Vec<u64>,
+
+    /// Write a string, read in the program with `io::read::<String>()`.
+    #[arg(long = "write-string", value_name = "VALUE")]
+    write_string: Vec<String>,
+
+    /// Write a JSON document as a string, read in the program with `io::read::<String>()`.
+    ///
+    /// The document is validated before it is written.
+    #[arg(long = "write-json", value_name = "JSON")]
+    write_json: Vec<String>,
+
+    /// Write hex-encoded raw bytes, read in the program with `io::read_vec()`.
+    #[arg(long = "write-hex", value_name = "HEX")]
+    write_hex: Vec<String>,
+
+    /// Write the raw contents of a file, read in the program with `io::read_vec()`.
+    #[arg(long = "write-file", value_name = "PATH")]
+    write_file: Vec<PathBuf>,
+
+    /// Where to write the serialized stdin.
+    #[arg(long, default_value = "stdin.bin", conflicts_with = "inspect")]
+    out: PathBuf,
+
+    /// Print the contents of an existing stdin file instead of building one.
+    #[arg(long, value_name = "PATH")]
+    inspect: Option<PathBuf>,
+}
+
+/// The kind of a `--write-*` flag, used to replay the flags in command line order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WriteKind {
+    U32,
+    U64,
+    String,
+    Json,
+    Hex,
+    File,
+}
+
+impl WriteKind {
+    /// Every kind, with the id of the argument that carries its values.
+    const ALL: [(Self, &'static str); 6] = [
+        (Self::U32, "write_u32"),
+        (Self::U64, "write_u64"),
+        (Self::String, "write_string"),
+        (Self::Json, "write_json"),
+        (Self::Hex, "write_hex"),
+        (Self::File, "write_file"),
+    ];
+
+    fn flag(self) -> &'static str {
+        match self {
+            Self::U32 => "--write-u32",
+            Self::U64 => "--write-u64",
+            Self::String => "--write-string",
+            Self::Json => "--write-json",
+            Self::Hex => "--write-hex",
+            Self::File => "--write-file",
+        }
+    }
+}
+
+impl StdinCmd {
+    /// Runs the command, using `matches` to recover the order of the `--write-*` flags.
+    pub fn run(&self, matches: &ArgMatches) -> Result<()> {
+        if let Some(path) = &self.inspect {
+            return inspect(path);
+        }
+
+        let order = write_order(matches);
+        let stdin = self.build(&order)?;
+
+        let bytes = bincode::serialize(&stdin)?;
+        fs::write(&self.out, bytes)
+            .with_context(|| format!("failed to write stdin to {}", self.out.display()))?;
+        println!("Wrote {} inputs to {}", stdin.buffer.len(), self.out.display());
+
+        Ok(())
+    }
+
+    fn build(&self, order: &[WriteKind]) -> Result<MONEROCHANStdin> {
+        let mut u32s = self.write_u32.iter();
+        let mut u64s = self.write_u64.iter();
+        let mut strings = self.write_string.iter();
+        let mut jsons = self.write_json.iter();
+        let mut hexes = self.write_hex.iter();
+        let mut files = self.write_file.iter();
+
+        let mut stdin = MONEROCHANStdin::new();
+        for kind in order {
+            match kind {
+                WriteKind::U32 => stdin.write(next(&mut u32s, *kind)?),
+                WriteKind::U64 => stdin.write(next(&mut u64s, *kind)?),
+                WriteKind::String => stdin.write(next(&mut strings, *kind)?),
+                WriteKind::Json => {
+                    let json = next(&mut jsons, *kind)?;
+                    serde_json::from_str::<serde_json::Value>(json)
+                        .with_context(|| format!("invalid JSON input: {json}"))?;
+                    stdin.write(json);
+                }
+                WriteKind::Hex => {
+                    let hex = next(&mut hexes, *kind)?;
+                    let bytes = hex::decode(hex.strip_prefix("0x").unwrap_or(hex))
+                        .with_context(|| format!("invalid hex input: {hex}"))?;
+                    stdin.write_vec(bytes);
+                }
+                WriteKind::File => {
+                    let path = next(&mut files, *kind)?;
+                    let bytes = fs::read(path)
+                        .with_context(|| format!("failed to read input file {}", path.display()))?;
+                    stdin.write_vec(bytes);
+                }
+            }
+        }
+
+        Ok(stdin)
+    }
+}
+
+/// Returns the `--write-*` flags in the order they appear on the command line.
+///
+/// The order is recovered from the index clap assigns to every value, so values that look like
+/// flags and arguments that were not parsed as a `--write-*` flag are never counted.
+fn write_order(matches: &ArgMatches) -> Vec<WriteKind> {
+    let mut order = WriteKind::ALL
+        .iter()
+        .flat_map(|&(kind, id)| {
+            matches.indices_of(id).into_iter().flatten().map(move |index| (index, kind))
+        })
+        .collect::<Vec<_>>();
+    order.sort_unstable_by_key(|&(index, _)| index);
+    order.into_iter().map(|(_, kind)| kind).collect()
+}
+
+/// Returns the next value of a `--write-*` flag.
+fn next<'a, T>(values: &mut std::slice::Iter<'a, T>, kind: WriteKind) -> Result<&'a T> {
+    values.next().with_context(|| format!("missing value for {}", kind.flag()))
+}
+
+fn inspect(path: &PathBuf) -> Result<()> {
+    let bytes =
+        fs::read(path).with_context(|| format!("failed to read stdin from {}", path.display()))?;
+    let stdin: MONEROCHANStdin = bincode::deserialize(&bytes)
+        .with_context(|| format!("{} is not a serialized MONEROCHANStdin", path.display()))?;
+
+    println!("{}: {} inputs, {} proofs", path.display(), stdin.buffer.len(), stdin.proofs.len());
+    for (i, input) in stdin.buffer.iter().enumerate() {
+        let preview = &input[..input.len().min(32)];
+        let ellipsis = if input.len() > preview.len() { "..." } else { "" };
+        println!("  [{i}] {} bytes: 0x{}{ellipsis}", input.len(), hex::encode(preview));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn test_write_order_preserves_command_line_order() {
+        let matches = StdinCmd::command().get_matches_from([
+            "stdin",
+            "--write-json",
+            "{}",
+            "--write-u32=5",
+            "--out",
+            "stdin.bin",
+            "--write-hex",
+            "0xff",
+            "--write-string",
+            "text",
+            "--write-u32",
+            "7",
+        ]);
+        assert_eq!(
+            write_order(&matches),
+            vec![
+                WriteKind::Json,
+                WriteKind::U32,
+                WriteKind::Hex,
+                WriteKind::String,
+                WriteKind::U32,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_round_trip() {
+        let cmd = StdinCmd::parse_from([
+            "stdin",
+            "--write-hex",
+            "0x0102",
+            "--write-u32",
+            "5",
+            "--write-json",
+            r#"{"a":1}"#,
+        ]);
+        let order = [WriteKind::Hex, WriteKind::U32, WriteKind::Json];
+        let mut stdin = cmd.build(&order).unwrap();
+
+        let bytes = bincode::serialize(&stdin).unwrap();
+        stdin = bincode::deserialize(&bytes).unwrap();
+
+        assert_eq!(stdin.buffer[0], vec![1, 2]);
+        stdin.ptr = 1;
+        assert_eq!(stdin.read::<u32>(), 5);
+        assert_eq!(stdin.read::<String>(), r#"{"a":1}"#);
+    }
+
+    #[test]
+    fn test_build_rejects_invalid_json() {
+        let cmd = StdinCmd::parse_from(["stdin", "--write-json", "{"]);
+        assert!(cmd.build(&[WriteKind::Json]).is_err());
+    }
+
+    #[test]
+    fn test_build_rejects_missing_value() {
+        let cmd = StdinCmd::parse_from(["stdin", "--write-u32", "5"]);
+        assert!(cmd.build(&[WriteKind::U32, WriteKind::U32]).is_err());
+    }
+}
@@ -0,0 +1,372 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use cargo_metadata::semver::{Version, VersionReq};
+use clap::Parser;
+use toml_edit::{DocumentMut, Item};
+use yansi::Paint;
+
+#[derive(Parser)]
+#[command(
+    name = "upgrade",
+    about = "Upgrade the MONEROCHAN crates and patches of a project to a release."
+)]
+pub struct UpgradeCmd {
+    /// The root directory of the project, searched recursively for manifests.
+    #[arg(long, default_value = ".")]
+    path: PathBuf,
+
+    /// The release to upgrade to, the release of this CLI by default.
+    #[arg(long)]
+    version: Option<String>,
+
+    /// Print the changes without writing them.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+/// The latest tags of the patched crates, with the repository they are fetched from.
+///
+/// A tag is named after the patched crate version, followed by the release it was built for, so a
+/// patch is upgraded to the tag of this list that patches the same crate version.
+const PATCH_TAGS: &[(&str, &str)] = &[
+    ("https://github.com/sp1-patches/curve25519-dalek", "patch-4.1.3-monerochan-5.0.0"),
+    ("https://github.com/sp1-patches/curve25519-dalek-ng", "patch-4.1.1-monerochan-5.0.0"),
+    ("https://github.com/sp1-patches/rust-secp256k1", "patch-0.29.1-monerochan-5.0.0"),
+    ("https://github.com/sp1-patches/rust-secp256k1", "patch-0.30.0-monerochan-5.0.0"),
+    ("https://github.com/sp1-patches/RustCrypto-hashes", "patch-sha2-0.10.8-monerochan-4.0.0"),
+    ("https://github.com/sp1-patches/RustCrypto-hashes", "patch-sha2-0.10.6-monerochan-4.0.0"),
+    ("https://github.com/sp1-patches/RustCrypto-hashes", "patch-sha3-0.10.8-monerochan-4.0.0"),
+    ("https://github.com/sp1-patches/RustCrypto-bigint", "patch-0.5.5-monerochan-5.0.0"),
+    ("https://github.com/sp1-patches/RustCrypto-RSA", "patch-0.9.6-monerochan-5.0.0"),
+    ("https://github.com/sp1-patches/RustCrypto-block-ciphers", "patch-aes-0.8.4-monerochan-5.0.0"),
+    (
+        "https://github.com/sp1-patches/RustCrypto-universal-hashes",
+        "patch-poly1305-0.8.0-monerochan-5.0.0",
+    ),
+    ("https://github.com/sp1-patches/elliptic-curves", "patch-k256-13.4-monerochan-5.0.0"),
+    ("https://github.com/sp1-patches/elliptic-curves", "patch-p256-13.2-monerochan-5.0.0"),
+    ("https://github.com/sp1-patches/signatures", "patch-16.9-monerochan-4.1.0"),
+    ("https://github.com/sp1-patches/tiny-keccak", "patch-2.0.2-monerochan-4.0.0"),
+    ("https://github.com/sp1-patches/bn", "patch-0.6.0-monerochan-5.0.0"),
+    ("https://github.com/sp1-patches/bls12_381", "patch-0.8.0-monerochan-5.0.0"),
+];
+
+/// The separators between the patched crate version and the release in a patch tag.
+const PATCH_TAG_RELEASE_SEPARATORS: &[&str] = &["-sp1-", "-monerochan-"];
+
+/// An API breaking change of a release.
+struct BreakingChange {
+    /// The release that introduced the change.
+    since: &'static str,
+    /// A snippet of the source code affected by the change, if it can be detected.
+    pattern: Option<&'static str>,
+    /// How to migrate.
+    message: &'static str,
+}
+
+const BREAKING_CHANGES: &[BreakingChange] = &[
+    BreakingChange {
+        since: "4.0.0",
+        pattern: Some("ProverClient::new("),
+        message: "`ProverClient::new()` is deprecated, use `ProverClient::from_env()` instead",
+    },
+    BreakingChange {
+        since: "4.0.0",
+        pattern: None,
+        message: "the reserved file descriptors of older releases are no longer supported, \
+                  patches built for releases before 4.0.0 must be upgraded",
+    },
+];
+
+/// The changes made to a manifest.
+#[derive(Default)]
+struct ManifestUpgrade {
+    /// The rewritten dependencies and patches.
+    changes: Vec<String>,
+    /// The dependencies and patches that must be upgraded by hand.
+    warnings: Vec<String>,
+    /// The oldest release required by the manifest.
+    from: Option<Version>,
+}
+
+impl UpgradeCmd {
+    pub fn run(&self) -> Result<()> {
+        let target = match &self.version {
+            Some(version) => Version::parse(version.trim_start_matches('v'))
+                .with_context(|| format!("invalid release {version}"))?,
+            None => Version::parse(env!("CARGO_PKG_VERSION"))?,
+        };
+
+        let mut from: Option<Version> = None;
+        for manifest in find_files(&self.path, "Cargo.toml")? {
+            let contents = fs::read_to_string(&manifest)
+                .with_context(|| format!("failed to read {}", manifest.display()))?;
+            let mut document: DocumentMut = contents
+                .parse()
+                .with_context(|| format!("failed to parse {}", manifest.display()))?;
+
+            let upgrade = upgrade_manifest(&mut document, &target);
+            from = match (from, upgrade.from.clone()) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+            if upgrade.changes.is_empty() && upgrade.warnings.is_empty() {
+                continue;
+            }
+
+            println!("{}", Paint::bold(&manifest.display()));
+            for change in &upgrade.changes {
+                println!("  {} {change}", Paint::green("upgraded"));
+            }
+            for warning in &upgrade.warnings {
+                println!("  {} {warning}", Paint::yellow("warning"));
+            }
+
+            if !self.dry_run && !upgrade.changes.is_empty() {
+                fs::write(&manifest, document.to_string())
+                    .with_context(|| format!("failed to write {}", manifest.display()))?;
+            }
+        }
+
+        self.report_breaking_changes(from.as_ref(), &target)?;
+
+        if self.dry_run {
+            println!("Dry run, no manifest was written.");
+        } else {
+            println!("Upgraded to {target}, run `cargo update` to refresh the lockfiles.");
+        }
+
+        Ok(())
+    }
+
+    /// Prints the breaking changes between the release of the project and the target release.
+    fn report_breaking_changes(&self, from: Option<&Version>, target: &Version) -> Result<()> {
+        let sources = find_files(&self.path, ".rs")?;
+        for change in BREAKING_CHANGES {
+            let since = Version::parse(change.since)?;
+            if &since > target || from.is_some_and(|from| from >= &since) {
+                continue;
+            }
+
+            let Some(pattern) = change.pattern else {
+                println!("{} ({}): {}", Paint::yellow("breaking change"), since, change.message);
+                continue;
+            };
+            for source in &sources {
+                let Ok(contents) = fs::read_to_string(source) else {
+                    continue;
+                };
+                for (i, line) in contents.lines().enumerate() {
+                    if line.contains(pattern) {
+                        println!(
+                            "{} ({}) at {}:{}: {}",
+                            Paint::yellow("breaking change"),
+                            since,
+                            source.display(),
+                            i + 1,
+                            change.message
+                        );
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Rewrites the MONEROCHAN dependencies and the known patches of a manifest to the target release.
+fn upgrade_manifest(document: &mut DocumentMut, target: &Version) -> ManifestUpgrade {
+    let mut upgrade = ManifestUpgrade::default();
+
+    for kind in ["dependencies", "dev-dependencies", "build-dependencies"] {
+        if let Some(table) = document.get_mut(kind) {
+            upgrade_dependencies(table, target, &mut upgrade);
+        }
+        if let Some(table) = document.get_mut("workspace").and_then(|w| w.get_mut(kind)) {
+            upgrade_dependencies(table, target, &mut upgrade);
+        }
+        if let Some(targets) = document.get_mut("target").and_then(Item::as_table_like_mut) {
+            for (_, platform) in targets.iter_mut() {
+                if let Some(table) = platform.get_mut(kind) {
+                    upgrade_dependencies(table, target, &mut upgrade);
+                }
+            }
+        }
+    }
+
+    if let Some(registries) = document.get_mut("patch").and_then(Item::as_table_like_mut) {
+        for (_, patches) in registries.iter_mut() {
+            upgrade_patches(patches, &mut upgrade);
+        }
+    }
+
+    upgrade
+}
+
+fn upgrade_dependencies(table: &mut Item, target: &Version, upgrade: &mut ManifestUpgrade) {
+    let Some(table) = table.as_table_like_mut() else {
+        return;
+    };
+
+    for (name, dependency) in table.iter_mut() {
+        let package = dependency.get("package").and_then(Item::as_str).unwrap_or(name.get());
+        if !package.starts_with("monerochan") {
+            continue;
+        }
+        let package = package.to_string();
+
+        let version = if dependency.is_str() {
+            Some(dependency)
+        } else if dependency.get("git").is_some() {
+            upgrade.warnings.push(format!(
+                "{package} is a git dependency, point it to the {target} release by hand"
+            ));
+            None
+        } else {
+            dependency.get_mut("version")
+        };
+        let Some(version) = version else {
+            continue;
+        };
+
+        let Some(current) = version.as_str().map(ToString::to_string) else {
+            continue;
+        };
+        if let Some(from) = VersionReq::parse(&current).ok().and_then(|req| oldest_version(&req)) {
+            upgrade.from = Some(upgrade.from.take().map_or(from.clone(), |v| v.min(from)));
+        }
+        if current != target.to_string() {
+            set_str(version, &target.to_string());
+            upgrade.changes.push(format!("{package} {current} -> {target}"));
+        }
+    }
+}
+
+fn upgrade_patches(patches: &mut Item, upgrade: &mut ManifestUpgrade) {
+    let Some(patches) = patches.as_table_like_mut() else {
+        return;
+    };
+
+    for (name, patch) in patches.iter_mut() {
+        let Some(repository) = patch.get("git").and_then(Item::as_str) else {
+            continue;
+        };
+        let repository = repository.trim_end_matches('/').trim_end_matches(".git").to_string();
+        let Some(tag) = patch.get_mut("tag") else {
+            continue;
+        };
+        let Some(current) = tag.as_str().map(ToString::to_string) else {
+            continue;
+        };
+        let Some(patched) = patched_version(&current) else {
+            continue;
+        };
+
+        let latest = PATCH_TAGS.iter().find(|(repo, known)| {
+            repo.eq_ignore_ascii_case(&repository) && patched_version(known) == Some(patched)
+        });
+        match latest {
+            Some((_, latest)) if *latest != current => {
+                set_str(tag, latest);
+                upgrade.changes.push(format!("patch {} {current} -> {latest}", name.get()));
+            }
+            Some(_) => {}
+            None => upgrade.warnings.push(format!(
+                "patch {} ({current}) is unknown, check that it has a tag for this release",
+                name.get()
+            )),
+        }
+    }
+}
+
+/// The patched crate version of a patch tag, without the release it was built for.
+fn patched_version(tag: &str) -> Option<&str> {
+    PATCH_TAG_RELEASE_SEPARATORS
+        .iter()
+        .find_map(|separator| tag.split_once(separator).map(|(patched, _)| patched))
+}
+
+/// The oldest version matching a requirement, if it has a lower bound.
+fn oldest_version(req: &VersionReq) -> Option<Version> {
+    let comparator = req.comparators.first()?;
+    Some(Version::new(
+        comparator.major,
+        comparator.minor.unwrap_or(0),
+        comparator.patch.unwrap_or(0),
+    ))
+}
+
+/// Replaces a string value, keeping its surrounding whitespace and comments.
+fn set_str(item: &mut Item, value: &str) {
+    if let Some(current) = item.as_value_mut() {
+        let decor = current.decor().clone();
+        *current = value.into();
+        *current.decor_mut() = decor;
+    }
+}
+
+/// Finds the files with the given name suffix under a directory, skipping build outputs and
+/// hidden directories.
+fn find_files(dir: &Path, suffix: &str) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let entries =
+            fs::read_dir(&dir).with_context(|| format!("failed to read {}", dir.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+            if path.is_dir() {
+                if name != "target" && !name.starts_with('.') {
+                    dirs.push(path);
+                }
+            } else if name.ends_with(suffix) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"
+[dependencies]
+monerochan = "4.1.0" # The SDK.
+monerochan-zkvm = { version = "4.1.0", features = ["verify"] }
+monerochan-build = { path = "../build" }
+serde = "1.0"
+
+[patch.crates-io]
+sha2-v0-10-8 = { git = "https://github.com/sp1-patches/RustCrypto-hashes", package = "sha2", tag = "patch-sha2-0.10.8-sp1-3.0.0" }
+tiny-keccak = { git = "https://github.com/sp1-patches/tiny-keccak", tag = "patch-2.0.2-monerochan-3.0.0" }
+foo = { git = "https://github.com/sp1-patches/foo", tag = "patch-1.0.0-sp1-4.0.0" }
+"#;
+
+    #[test]
+    fn test_upgrade_manifest() {
+        let mut document: DocumentMut = MANIFEST.parse().unwrap();
+        let upgrade = upgrade_manifest(&mut document, &Version::new(5, 2, 0));
+
+        assert_eq!(upgrade.from, Some(Version::new(4, 1, 0)));
+        assert_eq!(upgrade.changes.len(), 4);
+        assert_eq!(upgrade.warnings.len(), 1);
+
+        let upgraded = document.to_string();
+        assert!(upgraded.contains(r#"monerochan = "5.2.0" # The SDK."#));
+        assert!(upgraded.contains(r#"monerochan-zkvm = { version = "5.2.0", features"#));
+        assert!(upgraded.contains(r#"monerochan-build = { path = "../build" }"#));
+        assert!(upgraded.contains(r#"serde = "1.0""#));
+        assert!(upgraded.contains(r#"tag = "patch-sha2-0.10.8-monerochan-4.0.0""#));
+        assert!(upgraded.contains(r#"tag = "patch-2.0.2-monerochan-4.0.0""#));
+        assert!(upgraded.contains(r#"tag = "patch-1.0.0-sp1-4.0.0""#));
+    }
+}
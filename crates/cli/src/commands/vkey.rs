@@ -3,7 +3,7 @@ use std::{fs::File, io::Read};
 use anyhow::Result;
 use clap::{Args, Parser};
 use monerochan_build::{generate_elf_paths, BuildArgs};
-use monerochan::{HashableKey, ProverClient};
+use monerochan::{provenance::verify_provenance, BuildProvenance, HashableKey, ProverClient};
 
 #[derive(Parser)]
 #[command(name = "vkey", about = "View the verification key hash for a program.")]
@@ -11,6 +11,12 @@ pub struct VkeyCmd {
     /// Path to the ELF.
     #[command(flatten)]
     elf: Elf,
+    /// Check the ELF, and its verification key hash if recorded, against this provenance file.
+    #[arg(long, requires = "path")]
+    provenance: Option<String>,
+    /// Record the verification key hash in the provenance file after checking the ELF.
+    #[arg(long, requires = "provenance")]
+    attest: bool,
 }
 
 #[derive(Debug, Clone, Args)]
@@ -24,32 +30,48 @@ pub struct Elf {
     program: Option<String>,
 }
 
-impl VkeyCmd {
-    pub fn run(&self) -> Result<()> {
-        let elf_paths = if let Some(path) = &self.elf.path {
-            vec![(None, path.clone())]
-        } else if let Some(program) = &self.elf.program {
+impl Elf {
+    /// The paths of the ELFs, with the names of their targets when built from a crate.
+    pub fn paths(&self) -> Result<Vec<(Option<String>, String)>> {
+        if let Some(path) = &self.path {
+            Ok(vec![(None, path.clone())])
+        } else if let Some(program) = &self.program {
             let metadata_cmd = cargo_metadata::MetadataCommand::new();
             let metadata = metadata_cmd.exec()?;
             let build_args = BuildArgs { packages: vec![program.clone()], ..Default::default() };
 
-            generate_elf_paths(&metadata, Some(&build_args))?
+            Ok(generate_elf_paths(&metadata, Some(&build_args))?
                 .into_iter()
                 .map(|(target, path)| (Some(target), path.to_string()))
-                .collect()
+                .collect())
         } else {
             unreachable!()
-        };
+        }
+    }
+}
 
-        for (target, elf_path) in elf_paths {
+impl VkeyCmd {
+    pub fn run(&self) -> Result<()> {
+        for (target, elf_path) in self.elf.paths()? {
             // Read the elf file contents
             let mut file = File::open(elf_path)?;
             let mut elf = Vec::new();
             file.read_to_end(&mut elf)?;
 
-            // Get the verification key
+            // Get the verification key, checking the ELF against its provenance if given.
             let prover = ProverClient::from_env();
-            let (_, vk) = prover.setup(&elf);
+            let vk = match &self.provenance {
+                Some(path) => {
+                    let provenance = BuildProvenance::load(path)?;
+                    let vk = verify_provenance(&prover, &elf, &provenance)?;
+                    if self.attest {
+                        BuildProvenance { vk_hash: Some(vk.bytes32()), ..provenance }.save(path)?;
+                        println!("Recorded the verification key hash in {path}");
+                    }
+                    vk
+                }
+                None => prover.try_setup(&elf)?.1,
+            };
 
             // Print the verification key hash
             if let Some(target) = target {
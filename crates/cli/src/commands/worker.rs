@@ -0,0 +1,61 @@
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
+use monerochan::{
+    network::{proto::worker::WorkerCapability, server::PoolWorker},
+    utils::setup_logger,
+    Prover, ProverClient,
+};
+use monerochan_prover::components::CpuProverComponents;
+
+#[derive(Parser)]
+#[command(
+    name = "worker",
+    about = "Prove the jobs of a self-hosted proving service as one of its remote workers."
+)]
+pub struct WorkerCmd {
+    /// The url of the worker pool of the proving service started with `cargo monerochan serve`,
+    /// at its `--pool-addr`.
+    #[arg(long, env = "MONEROCHAN_COORDINATOR_URL")]
+    coordinator: String,
+
+    /// The token of the worker pool.
+    #[arg(long, env = "MONEROCHAN_WORKER_TOKEN", hide_env_values = true)]
+    token: String,
+
+    /// The name the worker is listed under.
+    #[arg(long, default_value = "monerochan-worker")]
+    name: String,
+
+    /// The prover that generates the proofs. The `cuda` prover advertises the worker as a GPU
+    /// worker, which gets jobs before the CPU workers.
+    #[arg(long, value_enum, default_value_t = WorkerProver::Cpu)]
+    prover: WorkerProver,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum WorkerProver {
+    Cpu,
+    Cuda,
+    Mock,
+}
+
+impl WorkerCmd {
+    pub fn run(&self) -> Result<()> {
+        setup_logger();
+
+        let prover: Box<dyn Prover<CpuProverComponents>> = match self.prover {
+            WorkerProver::Cpu => Box::new(ProverClient::builder().cpu().build()),
+            WorkerProver::Cuda => Box::new(ProverClient::builder().cuda().build()),
+            WorkerProver::Mock => Box::new(ProverClient::builder().mock().build()),
+        };
+        let capability = match self.prover {
+            WorkerProver::Cuda => WorkerCapability::Gpu,
+            WorkerProver::Cpu | WorkerProver::Mock => WorkerCapability::Cpu,
+        };
+
+        let worker = PoolWorker::new(self.coordinator.clone(), prover, capability)
+            .name(self.name.clone())
+            .token(self.token.clone());
+        tokio::runtime::Runtime::new()?.block_on(worker.run())
+    }
+}
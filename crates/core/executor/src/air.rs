@@ -16,6 +16,8 @@ use subenum::subenum;
 ///
 /// The [`CoreAirId`]s are the AIRs that are not part of precompile shards and not the program or
 /// byte AIR.
+///
+/// New AIRs are appended at the end, so that the existing identifiers keep their values.
 #[subenum(CoreAirId)]
 #[derive(
     Debug,
@@ -139,6 +141,13 @@ pub enum RiscvAirId {
     Global = 43,
     /// The byte chip.
     Byte = 44,
+    /// The AES encryption round chip.
+    AesRound = 45,
+    /// The bitmanip chip.
+    #[subenum(CoreAirId)]
+    BitManip = 46,
+    /// The f64 arithmetic chip.
+    F64OpAssign = 47,
 }
 
 impl RiscvAirId {
@@ -154,6 +163,7 @@ impl RiscvAirId {
             RiscvAirId::ShiftRight,
             RiscvAirId::DivRem,
             RiscvAirId::Lt,
+            RiscvAirId::BitManip,
             RiscvAirId::Auipc,
             RiscvAirId::MemoryLocal,
             RiscvAirId::MemoryInstrs,
@@ -209,7 +219,9 @@ impl RiscvAirId {
                 RiscvAirId::Bn254FpOpAssign |
                 RiscvAirId::Bn254Fp2AddSubAssign |
                 RiscvAirId::Bn254Fp2MulAssign |
-                RiscvAirId::Bls12381Decompress
+                RiscvAirId::Bls12381Decompress |
+                RiscvAirId::AesRound |
+                RiscvAirId::F64OpAssign
         )
     }
 
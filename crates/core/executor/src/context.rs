@@ -5,7 +5,8 @@ use crate::{
     subproof::SubproofVerifier,
 };
 use hashbrown::HashMap;
-use std::io::Write;
+use std::{io::Write, path::PathBuf};
+use thiserror::Error;
 
 use monerochan_primitives::consts::fd::LOWEST_ALLOWED_FD;
 
@@ -23,6 +24,11 @@ pub struct MONEROCHANContext<'a> {
     /// The maximum number of cpu cycles to use for execution.
     pub max_cycles: Option<u64>,
 
+    /// The maximum number of cycles to spend in unconstrained blocks.
+    ///
+    /// Note: `None` falls back to the `UNCONSTRAINED_CYCLE_LIMIT` environment variable.
+    pub unconstrained_cycle_limit: Option<u64>,
+
     /// Deferred proof verification.
     pub deferred_proof_verification: bool,
 
@@ -34,6 +40,21 @@ pub struct MONEROCHANContext<'a> {
 
     /// The IO options for the [`MONEROCHANExecutor`].
     pub io_options: IoOptions<'a>,
+
+    /// The path to save the execution trace to.
+    ///
+    /// Only used when executing, the trace is not saved while proving.
+    pub trace_path: Option<PathBuf>,
+
+    /// Whether to journal the reads of the inputs in the `ExecutionReport`.
+    pub journal_input_reads: bool,
+
+    /// Whether to trap on the behaviors which execute but cannot be proven.
+    pub strict: bool,
+
+    /// Whether a non-zero exit code is reported in the `ExecutionReport` rather than failing the
+    /// execution.
+    pub report_exit_code: bool,
 }
 
 impl Default for MONEROCHANContext<'_> {
@@ -43,14 +64,31 @@ impl Default for MONEROCHANContext<'_> {
 }
 
 /// A builder for [`MONEROCHANContext`].
+///
+/// The options are checked when the context is built, see [`Self::try_build`].
+///
+/// # Precedence
+/// An option set on the builder takes precedence over the environment: the
+/// `UNCONSTRAINED_CYCLE_LIMIT` environment variable only applies when
+/// [`Self::unconstrained_cycle_limit`] is not set.
+///
+/// The context only governs local execution. The `cycle_limit` and `gas_limit` of a network
+/// proof request are enforced by the network, independently of [`Self::max_cycles`]; when they
+/// are not set, they are derived from the local simulation of the request, which runs with the
+/// default context.
 pub struct MONEROCHANContextBuilder<'a> {
     no_default_hooks: bool,
     hook_registry_entries: Vec<(u32, BoxedHook<'a>)>,
     subproof_verifier: Option<&'a dyn SubproofVerifier>,
     max_cycles: Option<u64>,
+    unconstrained_cycle_limit: Option<u64>,
     deferred_proof_verification: bool,
     calculate_gas: bool,
     io_options: IoOptions<'a>,
+    trace_path: Option<PathBuf>,
+    journal_input_reads: bool,
+    strict: bool,
+    report_exit_code: bool,
 }
 
 impl Default for MONEROCHANContextBuilder<'_> {
@@ -60,10 +98,15 @@ impl Default for MONEROCHANContextBuilder<'_> {
             hook_registry_entries: Vec::new(),
             subproof_verifier: None,
             max_cycles: None,
+            unconstrained_cycle_limit: None,
             // Always verify deferred proofs by default.
             deferred_proof_verification: true,
             calculate_gas: true,
             io_options: IoOptions::default(),
+            trace_path: None,
+            journal_input_reads: false,
+            strict: false,
+            report_exit_code: false,
         }
     }
 }
@@ -88,12 +131,24 @@ impl<'a> MONEROCHANContextBuilder<'a> {
     /// Build and return the [`MONEROCHANContext`].
     ///
     /// Clears and resets the builder, allowing it to be reused.
+    ///
+    /// # Panics
+    /// Panics if the options are invalid, see [`Self::try_build`].
     pub fn build(&mut self) -> MONEROCHANContext<'a> {
+        self.try_build().unwrap_or_else(|err| panic!("invalid MONEROCHANContext: {err}"))
+    }
+
+    /// Build and return the [`MONEROCHANContext`], or an error if the options are invalid.
+    ///
+    /// The options are invalid if a hook is registered on a reserved file descriptor or twice on
+    /// the same one, or if a limit is zero. On error, the builder is left unchanged.
+    ///
+    /// Clears and resets the builder, allowing it to be reused.
+    pub fn try_build(&mut self) -> Result<MONEROCHANContext<'a>, ContextError> {
+        self.validate()?;
+
         // If hook_registry_entries is nonempty or no_default_hooks true,
         // indicating a non-default value of hook_registry.
-        //
-        // Panics:
-        // - If any hook file descriptor is less than [`LOWEST_ALLOWED_FD`].
         let hook_registry =
             (!self.hook_registry_entries.is_empty() || self.no_default_hooks).then(|| {
                 let mut table = if take(&mut self.no_default_hooks) {
@@ -117,16 +172,47 @@ impl<'a> MONEROCHANContextBuilder<'a> {
 
         let subproof_verifier = take(&mut self.subproof_verifier);
         let cycle_limit = take(&mut self.max_cycles);
-        let deferred_proof_verification = take(&mut self.deferred_proof_verification);
-        let calculate_gas = take(&mut self.calculate_gas);
-        MONEROCHANContext {
+        let unconstrained_cycle_limit = take(&mut self.unconstrained_cycle_limit);
+        // The defaults are restored explicitly, since they are not the defaults of the types.
+        let deferred_proof_verification =
+            std::mem::replace(&mut self.deferred_proof_verification, true);
+        let calculate_gas = std::mem::replace(&mut self.calculate_gas, true);
+        Ok(MONEROCHANContext {
             hook_registry,
             subproof_verifier,
             max_cycles: cycle_limit,
+            unconstrained_cycle_limit,
             deferred_proof_verification,
             calculate_gas,
             io_options: take(&mut self.io_options),
+            trace_path: take(&mut self.trace_path),
+            journal_input_reads: take(&mut self.journal_input_reads),
+            strict: take(&mut self.strict),
+            report_exit_code: take(&mut self.report_exit_code),
+        })
+    }
+
+    fn validate(&self) -> Result<(), ContextError> {
+        let mut fds = Vec::with_capacity(self.hook_registry_entries.len());
+        for (fd, _) in &self.hook_registry_entries {
+            if *fd <= LOWEST_ALLOWED_FD {
+                return Err(ContextError::ReservedHookFd(*fd));
+            }
+            if fds.contains(fd) {
+                return Err(ContextError::DuplicateHook(*fd));
+            }
+            fds.push(*fd);
+        }
+        if self.max_cycles == Some(0) {
+            return Err(ContextError::ZeroLimit("max_cycles"));
+        }
+        if self.unconstrained_cycle_limit == Some(0) {
+            return Err(ContextError::ZeroLimit("unconstrained_cycle_limit"));
+        }
+        if self.io_options.output == Some(OutputMode::Capture { max_size: 0 }) {
+            return Err(ContextError::ZeroLimit("max_output_size"));
         }
+        Ok(())
     }
 
     /// Add a runtime [Hook](super::Hook) into the context.
@@ -135,15 +221,14 @@ impl<'a> MONEROCHANContextBuilder<'a> {
     /// with [`monerochan_runtime::io::write`], returning a list of arbitrary data that may be read
     /// with successive calls to [`monerochan_runtime::io::read`].
     ///
-    /// # Panics
-    /// Panics if `fd` <= [`LOWEST_ALLOWED_FD`].
+    /// A hook registered on the file descriptor of a default hook overrides it. The descriptor
+    /// must be greater than [`LOWEST_ALLOWED_FD`], and must not be registered twice, which is
+    /// checked when the context is built.
     pub fn hook(
         &mut self,
         fd: u32,
         f: impl FnMut(HookEnv, &[u8]) -> Vec<Vec<u8>> + Send + Sync + 'a,
     ) -> &mut Self {
-        assert!(fd > LOWEST_ALLOWED_FD, "Hook file descriptors must be greater than 10.");
-
         self.hook_registry_entries.push((fd, hookify(f)));
         self
     }
@@ -178,17 +263,37 @@ impl<'a> MONEROCHANContextBuilder<'a> {
 
     /// Set the maximum number of cpu cycles to use for execution.
     /// `report.total_instruction_count()` will be less than or equal to `max_cycles`.
+    ///
+    /// The limit must be positive.
     pub fn max_cycles(&mut self, max_cycles: u64) -> &mut Self {
         self.max_cycles = Some(max_cycles);
         self
     }
 
-    /// Set the deferred proof verification flag.
-    pub fn set_deferred_proof_verification(&mut self, value: bool) -> &mut Self {
+    /// Set the maximum number of cycles to spend in unconstrained blocks.
+    ///
+    /// Takes precedence over the `UNCONSTRAINED_CYCLE_LIMIT` environment variable. The limit must
+    /// be positive.
+    pub fn unconstrained_cycle_limit(&mut self, limit: u64) -> &mut Self {
+        self.unconstrained_cycle_limit = Some(limit);
+        self
+    }
+
+    /// Whether to verify the deferred proofs while executing. Defaults to `true`.
+    ///
+    /// The deferred proofs are always verified while proving, so disabling the verification only
+    /// speeds up the execution of programs that verify other proofs.
+    pub fn deferred_proof_verification(&mut self, value: bool) -> &mut Self {
         self.deferred_proof_verification = value;
         self
     }
 
+    /// Set the deferred proof verification flag.
+    #[deprecated(note = "use `deferred_proof_verification` instead")]
+    pub fn set_deferred_proof_verification(&mut self, value: bool) -> &mut Self {
+        self.deferred_proof_verification(value)
+    }
+
     /// Set the `stdout` writer.
     pub fn stdout<W: IoWriter>(&mut self, writer: &'a mut W) -> &mut Self {
         self.io_options.stdout = Some(writer);
@@ -200,6 +305,73 @@ impl<'a> MONEROCHANContextBuilder<'a> {
         self.io_options.stderr = Some(writer);
         self
     }
+
+    /// Set how the `stdout` and `stderr` of the program are handled when they are not redirected
+    /// to a writer, see [`OutputMode`].
+    pub fn output(&mut self, mode: OutputMode) -> &mut Self {
+        self.io_options.output = Some(mode);
+        self
+    }
+
+    /// Save the execution trace to the given path.
+    ///
+    /// The trace is only saved when executing, and can be read back with
+    /// [`monerochan_core_trace::TraceReader`].
+    pub fn trace_file(&mut self, path: impl Into<PathBuf>) -> &mut Self {
+        self.trace_path = Some(path.into());
+        self
+    }
+
+    /// Whether to journal which words of the inputs are read while executing. Defaults to
+    /// `false`.
+    ///
+    /// The journal is available as [`ExecutionReport::input_reads`](crate::ExecutionReport), and
+    /// tells which parts of stdin the program actually depends on, so that the inputs of a proof
+    /// can be pruned. It is only meaningful when executing.
+    pub fn journal_input_reads(&mut self, value: bool) -> &mut Self {
+        self.journal_input_reads = value;
+        self
+    }
+
+    /// Whether to trap on the behaviors which execute but cannot be proven. Defaults to `false`.
+    ///
+    /// In strict mode, a jump or branch to a misaligned address, or out of the program without
+    /// halting, traps with [`TrapKind::MisalignedJump`](crate::TrapKind::MisalignedJump) or
+    /// [`TrapKind::JumpOutOfProgram`](crate::TrapKind::JumpOutOfProgram), reporting the program
+    /// counter and the function of the jump. Otherwise, the execution succeeds and only the proof
+    /// fails.
+    pub fn strict(&mut self, value: bool) -> &mut Self {
+        self.strict = value;
+        self
+    }
+
+    /// Whether a program halting with a non-zero exit code completes its execution, with the code
+    /// in [`ExecutionReport::exit_code`](crate::ExecutionReport::exit_code), rather than failing
+    /// with [`ExecutionError::HaltWithNonZeroExitCode`](crate::ExecutionError). Defaults to
+    /// `false`.
+    ///
+    /// A program which exited with a non-zero code cannot be proven, so this is only useful to
+    /// inspect the report of a failed execution.
+    pub fn report_exit_code(&mut self, value: bool) -> &mut Self {
+        self.report_exit_code = value;
+        self
+    }
+}
+
+/// An invalid option of a [`MONEROCHANContextBuilder`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ContextError {
+    /// A hook was registered on a file descriptor reserved by the runtime.
+    #[error("hook file descriptors must be greater than 10, got {0}")]
+    ReservedHookFd(u32),
+
+    /// Two hooks were registered on the same file descriptor.
+    #[error("several hooks are registered on file descriptor {0}")]
+    DuplicateHook(u32),
+
+    /// A limit was set to zero.
+    #[error("{0} must be positive")]
+    ZeroLimit(&'static str),
 }
 
 /// The IO options for the [`MONEROCHANExecutor`].
@@ -211,14 +383,38 @@ pub struct IoOptions<'a> {
     pub stdout: Option<&'a mut dyn IoWriter>,
     /// A writer to redirect `stderr` to.
     pub stderr: Option<&'a mut dyn IoWriter>,
+    /// How the output that is not redirected to a writer is handled.
+    ///
+    /// Note: `None` denotes the default of the caller, which is to print it unless documented
+    /// otherwise.
+    pub output: Option<OutputMode>,
 }
 
 impl Clone for IoOptions<'_> {
     fn clone(&self) -> Self {
-        IoOptions { stdout: None, stderr: None }
+        IoOptions { stdout: None, stderr: None, output: self.output }
     }
 }
 
+/// The default maximum size of each captured output stream, in bytes.
+pub const DEFAULT_MAX_OUTPUT_SIZE: usize = 1 << 20;
+
+/// How the `stdout` and `stderr` of a program are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputMode {
+    /// Print the output to the `stderr` of the host, each line prefixed by its stream.
+    Print,
+    /// Capture the output in the `ExecutionReport`, up to `max_size` bytes per stream.
+    ///
+    /// The lines that do not fit are dropped, and the stream is marked as truncated.
+    Capture {
+        /// The maximum size of each stream, in bytes.
+        max_size: usize,
+    },
+    /// Discard the output.
+    Discard,
+}
+
 /// A trait for [`Write`] types to be used in the executor.
 ///
 /// This trait is generically implemented for any [`Write`] + [`Send`] type.
@@ -228,6 +424,7 @@ impl<W: Write + Send> IoWriter for W {}
 
 #[cfg(test)]
 mod tests {
+    use super::{ContextError, OutputMode};
     use crate::{subproof::NoOpSubproofVerifier, MONEROCHANContext};
 
     #[test]
@@ -268,4 +465,42 @@ mod tests {
             MONEROCHANContext::builder().subproof_verifier(&verifier).build();
         assert!(subproof_verifier.is_some());
     }
+
+    #[test]
+    fn validation() {
+        let mut builder = MONEROCHANContext::builder();
+        builder.hook(5, |_, _| vec![]);
+        assert_eq!(builder.try_build().err(), Some(ContextError::ReservedHookFd(5)));
+
+        let mut builder = MONEROCHANContext::builder();
+        builder.hook(30, |_, _| vec![]).hook(30, |_, _| vec![]);
+        assert_eq!(builder.try_build().err(), Some(ContextError::DuplicateHook(30)));
+
+        let err = MONEROCHANContext::builder().max_cycles(0).try_build().err();
+        assert_eq!(err, Some(ContextError::ZeroLimit("max_cycles")));
+
+        let err = MONEROCHANContext::builder()
+            .output(OutputMode::Capture { max_size: 0 })
+            .try_build()
+            .err();
+        assert_eq!(err, Some(ContextError::ZeroLimit("max_output_size")));
+
+        let context = MONEROCHANContext::builder()
+            .max_cycles(100)
+            .unconstrained_cycle_limit(10)
+            .calculate_gas(false)
+            .deferred_proof_verification(false)
+            .try_build()
+            .unwrap();
+        assert_eq!((context.max_cycles, context.unconstrained_cycle_limit), (Some(100), Some(10)));
+        assert!(!context.calculate_gas && !context.deferred_proof_verification);
+    }
+
+    #[test]
+    fn reuse_restores_defaults() {
+        let mut builder = MONEROCHANContext::builder();
+        builder.calculate_gas(false).deferred_proof_verification(false).build();
+        let context = builder.build();
+        assert!(context.calculate_gas && context.deferred_proof_verification);
+    }
 }
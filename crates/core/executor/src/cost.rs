@@ -2,7 +2,7 @@ use enum_map::EnumMap;
 use hashbrown::HashMap;
 use p3_baby_bear::BabyBear;
 
-use crate::RiscvAirId;
+use crate::{Opcode, RiscvAirId};
 
 const BYTE_NUM_ROWS: u64 = 1 << 16;
 const MAX_PROGRAM_SIZE: u64 = 1 << 22;
@@ -51,6 +51,10 @@ pub fn estimate_riscv_lde_size(
     cells +=
         (num_events_per_air[RiscvAirId::Lt]).next_power_of_two() * costs_per_air[&RiscvAirId::Lt];
 
+    // Compute the bitmanip chip contribution.
+    cells += (num_events_per_air[RiscvAirId::BitManip]).next_power_of_two() *
+        costs_per_air[&RiscvAirId::BitManip];
+
     // Compute the memory local chip contribution.
     cells += (num_events_per_air[RiscvAirId::MemoryLocal]).next_power_of_two() *
         costs_per_air[&RiscvAirId::MemoryLocal];
@@ -102,6 +106,7 @@ pub fn pad_rv32im_event_counts(
         RiscvAirId::ShiftRight => *v += num_cycles,
         RiscvAirId::DivRem => *v += 4 * num_cycles,
         RiscvAirId::Lt => *v += 2 * num_cycles,
+        RiscvAirId::BitManip => *v += num_cycles,
         RiscvAirId::MemoryLocal => *v += 64 * num_cycles,
         RiscvAirId::Branch => *v += 8 * num_cycles,
         RiscvAirId::Jump => *v += 2 * num_cycles,
@@ -114,3 +119,116 @@ pub fn pad_rv32im_event_counts(
     });
     event_counts
 }
+
+/// The worst case number of events that one execution of an opcode adds to each core chip.
+///
+/// The events of the memory and global chips are left out, since they depend on the addresses
+/// that are touched rather than on the opcode. `ebreak` and `unimp` have no events, since their
+/// execution fails, so they are never proven.
+#[must_use]
+pub fn rv32im_opcode_events(opcode: Opcode) -> EnumMap<RiscvAirId, u64> {
+    let mut events = EnumMap::default();
+    if matches!(opcode, Opcode::EBREAK | Opcode::UNIMP) {
+        return events;
+    }
+    events[RiscvAirId::Cpu] = 1;
+    match opcode {
+        Opcode::ADD | Opcode::SUB => events[RiscvAirId::AddSub] += 1,
+        Opcode::XOR | Opcode::OR | Opcode::AND => events[RiscvAirId::Bitwise] += 1,
+        Opcode::SLL => events[RiscvAirId::ShiftLeft] += 1,
+        Opcode::SRL | Opcode::SRA => events[RiscvAirId::ShiftRight] += 1,
+        Opcode::SLT | Opcode::SLTU => events[RiscvAirId::Lt] += 1,
+        Opcode::MUL | Opcode::MULH | Opcode::MULHU | Opcode::MULHSU => {
+            events[RiscvAirId::Mul] += 1;
+        }
+        Opcode::DIV | Opcode::DIVU | Opcode::REM | Opcode::REMU => {
+            events[RiscvAirId::DivRem] += 1;
+            events[RiscvAirId::Mul] += 2;
+            events[RiscvAirId::AddSub] += 2;
+            events[RiscvAirId::Lt] += 1;
+        }
+        Opcode::LB | Opcode::LH | Opcode::LW | Opcode::LBU | Opcode::LHU => {
+            events[RiscvAirId::MemoryInstrs] += 1;
+            events[RiscvAirId::AddSub] += 2;
+        }
+        Opcode::SB | Opcode::SH | Opcode::SW => {
+            events[RiscvAirId::MemoryInstrs] += 1;
+            events[RiscvAirId::AddSub] += 1;
+        }
+        Opcode::BEQ | Opcode::BNE | Opcode::BLT | Opcode::BGE | Opcode::BLTU | Opcode::BGEU => {
+            events[RiscvAirId::Branch] += 1;
+            events[RiscvAirId::AddSub] += 1;
+            events[RiscvAirId::Lt] += 2;
+        }
+        Opcode::JAL | Opcode::JALR => {
+            events[RiscvAirId::Jump] += 1;
+            events[RiscvAirId::AddSub] += 1;
+        }
+        Opcode::AUIPC => events[RiscvAirId::Auipc] += 1,
+        Opcode::EBREAK | Opcode::UNIMP => {}
+        Opcode::ECALL => events[RiscvAirId::SyscallInstrs] += 1,
+        Opcode::ANDN |
+        Opcode::ORN |
+        Opcode::XNOR |
+        Opcode::CLZ |
+        Opcode::CTZ |
+        Opcode::CPOP |
+        Opcode::SEXTB |
+        Opcode::SEXTH |
+        Opcode::ZEXTH |
+        Opcode::ORCB |
+        Opcode::REV8 => events[RiscvAirId::BitManip] += 1,
+        Opcode::MAX | Opcode::MAXU | Opcode::MIN | Opcode::MINU => {
+            events[RiscvAirId::BitManip] += 1;
+            events[RiscvAirId::Lt] += 1;
+        }
+        Opcode::ROL | Opcode::ROR => {
+            events[RiscvAirId::BitManip] += 1;
+            events[RiscvAirId::ShiftLeft] += 1;
+            events[RiscvAirId::ShiftRight] += 1;
+        }
+    }
+    events
+}
+
+/// Estimates the trace cells that one execution of each opcode adds to the core chips.
+///
+/// Dividing by the weight of `ADD` gives the weight of each opcode in cycles, that is how many
+/// additions it costs to prove. The chips missing from `costs_per_air`, such as the chips of the
+/// disabled features, cost nothing.
+#[must_use]
+pub fn rv32im_opcode_weights(costs_per_air: &HashMap<RiscvAirId, u64>) -> EnumMap<Opcode, u64> {
+    EnumMap::from_fn(|opcode| {
+        rv32im_opcode_events(opcode)
+            .iter()
+            .map(|(air, &num_events)| num_events * costs_per_air.get(&air).copied().unwrap_or(0))
+            .sum()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use hashbrown::HashMap;
+
+    use super::rv32im_opcode_weights;
+    use crate::{rv32im_costs, Opcode, RiscvAirId};
+
+    #[test]
+    fn test_opcode_weights() {
+        let costs: HashMap<RiscvAirId, u64> =
+            rv32im_costs().into_iter().map(|(air, cost)| (air, cost as u64)).collect();
+        let weights = rv32im_opcode_weights(&costs);
+
+        // Every opcode takes a cpu row and a row of the chip proving it, but the ones that fail.
+        for (opcode, &weight) in weights.iter() {
+            if matches!(opcode, Opcode::EBREAK | Opcode::UNIMP) {
+                assert_eq!(weight, 0);
+            } else {
+                assert!(weight > costs[&RiscvAirId::Cpu], "{opcode} is too light");
+            }
+        }
+        // A division also proves two multiplications, two additions and a comparison.
+        assert!(weights[Opcode::DIVU] > weights[Opcode::MUL] * 2);
+        assert_eq!(weights[Opcode::DIVU], weights[Opcode::REM]);
+    }
+}
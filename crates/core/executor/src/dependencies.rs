@@ -100,6 +100,58 @@ pub fn emit_divrem_dependencies(executor: &mut Executor, event: AluEvent) {
     }
 }
 
+/// Emits the dependencies for bit manipulation operations.
+///
+/// A rotation is proven as the OR of a left and a right shift of the same word, and a minimum or
+/// maximum as a comparison of its operands.
+pub fn emit_bitmanip_dependencies(executor: &mut Executor, event: AluEvent) {
+    match event.opcode {
+        Opcode::ROL | Opcode::ROR => {
+            let complement = 32 - (event.c & 0x1f);
+            let (first, second) = if event.opcode == Opcode::ROL {
+                (Opcode::SLL, Opcode::SRL)
+            } else {
+                (Opcode::SRL, Opcode::SLL)
+            };
+            for (opcode, c) in [(first, event.c), (second, complement)] {
+                let shift_event = AluEvent {
+                    pc: UNUSED_PC,
+                    opcode,
+                    a: if opcode == Opcode::SLL {
+                        event.b.wrapping_shl(c)
+                    } else {
+                        event.b.wrapping_shr(c)
+                    },
+                    b: event.b,
+                    c,
+                    op_a_0: false,
+                };
+                if opcode == Opcode::SLL {
+                    executor.record.shift_left_events.push(shift_event);
+                } else {
+                    executor.record.shift_right_events.push(shift_event);
+                }
+            }
+        }
+        Opcode::MAX | Opcode::MAXU | Opcode::MIN | Opcode::MINU => {
+            let (opcode, lt) = if matches!(event.opcode, Opcode::MAX | Opcode::MIN) {
+                (Opcode::SLT, (event.b as i32) < (event.c as i32))
+            } else {
+                (Opcode::SLTU, event.b < event.c)
+            };
+            executor.record.lt_events.push(AluEvent {
+                pc: UNUSED_PC,
+                opcode,
+                a: u32::from(lt),
+                b: event.b,
+                c: event.c,
+                op_a_0: false,
+            });
+        }
+        _ => {}
+    }
+}
+
 /// Emit the dependencies for memory instructions.
 pub fn emit_memory_dependencies(
     executor: &mut Executor,
@@ -1,7 +1,7 @@
 use std::cmp::min;
 
 use elf::{
-    abi::{EM_RISCV, ET_EXEC, PF_W, PF_X, PT_LOAD},
+    abi::{EM_RISCV, ET_EXEC, PF_W, PF_X, PT_LOAD, STT_FUNC},
     endian::LittleEndian,
     file::Class,
     ElfBytes,
@@ -10,6 +10,8 @@ use eyre::OptionExt;
 use hashbrown::HashMap;
 use monerochan_primitives::consts::{BABYBEAR_PRIME, MAXIMUM_MEMORY_SIZE, WORD_SIZE};
 
+use crate::program::Symbol;
+
 /// RISC-V 32IM ELF (Executable and Linkable Format) File.
 ///
 /// This file represents a binary in the ELF format, specifically the RISC-V 32IM architecture
@@ -29,6 +31,10 @@ pub(crate) struct Elf {
     pub(crate) pc_base: u32,
     /// The initial memory image, useful for global constants.
     pub(crate) memory_image: HashMap<u32, u32>,
+    /// The function symbols, sorted by address.
+    pub(crate) symbols: Vec<Symbol>,
+    /// The start of the stack guard region, from the `_monerochan_stack_guard` symbol.
+    pub(crate) stack_guard: Option<u32>,
 }
 
 impl Elf {
@@ -39,8 +45,10 @@ impl Elf {
         pc_start: u32,
         pc_base: u32,
         memory_image: HashMap<u32, u32>,
+        symbols: Vec<Symbol>,
+        stack_guard: Option<u32>,
     ) -> Self {
-        Self { instructions, pc_start, pc_base, memory_image }
+        Self { instructions, pc_start, pc_base, memory_image, symbols, stack_guard }
     }
 
     /// Parse the ELF file into a vector of 32-bit encoded instructions and the first memory
@@ -199,6 +207,34 @@ impl Elf {
             eyre::bail!("base address is not found");
         }
 
-        Ok(Elf::new(instructions, entry, base_address.unwrap(), image))
+        // The symbols are only used for diagnostics and to locate the stack guard, so a missing or
+        // malformed symbol table is not an error.
+        let mut symbols = Vec::new();
+        let mut stack_guard = None;
+        if let Ok(Some((table, strings))) = elf.symbol_table() {
+            stack_guard = table
+                .iter()
+                .find(|sym| {
+                    strings
+                        .get(sym.st_name as usize)
+                        .is_ok_and(|name| name == "_monerochan_stack_guard")
+                })
+                .and_then(|sym| u32::try_from(sym.st_value).ok());
+            for sym in table.iter().filter(|sym| sym.st_symtype() == STT_FUNC) {
+                let (Ok(address), Ok(size), Ok(name)) = (
+                    u32::try_from(sym.st_value),
+                    u32::try_from(sym.st_size),
+                    strings.get(sym.st_name as usize),
+                ) else {
+                    continue;
+                };
+                if !name.is_empty() {
+                    symbols.push(Symbol { name: name.to_string(), address, size });
+                }
+            }
+        }
+        symbols.sort_by_key(|sym| sym.address);
+
+        Ok(Elf::new(instructions, entry, base_address.unwrap(), image, symbols, stack_guard))
     }
 }
@@ -2,6 +2,8 @@
 
 mod elf;
 mod rrs;
+#[cfg(feature = "zbb")]
+mod zbb;
 
 pub(crate) use elf::*;
 pub(crate) use rrs::*;
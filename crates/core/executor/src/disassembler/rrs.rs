@@ -3,6 +3,8 @@ use rrs_lib::{
     process_instruction, InstructionProcessor,
 };
 
+#[cfg(feature = "zbb")]
+use super::zbb::decode_zbb;
 use crate::{Instruction, Opcode, Register};
 
 impl Instruction {
@@ -362,15 +364,21 @@ impl InstructionProcessor for InstructionTranspiler {
 
 /// Transpile the [`Instruction`]s from the 32-bit encoded instructions.
 ///
-/// # Panics
-///
-/// This function will return an error if the [`Instruction`] cannot be processed.
+/// The encodings that cannot be decoded, such as atomic instructions, are transpiled to `unimp`,
+/// so that the program traps when it executes them, with the diagnostics of the trap.
 #[must_use]
 pub(crate) fn transpile(instructions_u32: &[u32]) -> Vec<Instruction> {
     let mut instructions = Vec::new();
     let mut transpiler = InstructionTranspiler;
     for instruction_u32 in instructions_u32 {
-        let instruction = process_instruction(&mut transpiler, *instruction_u32).unwrap();
+        // The bit manipulation instructions are not known to `rrs_lib`, so decode them first.
+        #[cfg(feature = "zbb")]
+        if let Some(instruction) = decode_zbb(*instruction_u32) {
+            instructions.push(instruction);
+            continue;
+        }
+        let instruction = process_instruction(&mut transpiler, *instruction_u32)
+            .unwrap_or_else(Instruction::unimp);
         instructions.push(instruction);
     }
     instructions
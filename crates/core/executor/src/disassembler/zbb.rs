@@ -0,0 +1,99 @@
+use crate::{Instruction, Opcode};
+
+/// The major opcode of register-register operations.
+const OP: u32 = 0b011_0011;
+
+/// The major opcode of register-immediate operations.
+const OP_IMM: u32 = 0b001_0011;
+
+/// Decode an instruction of the Zbb (basic bit manipulation) extension.
+///
+/// Returns `None` if the instruction is not a Zbb instruction. The unary operations take their
+/// second operand as the immediate zero, and `rori` is decoded as `ror` with an immediate.
+#[must_use]
+pub(crate) fn decode_zbb(insn: u32) -> Option<Instruction> {
+    let rd = ((insn >> 7) & 0x1f) as u8;
+    let funct3 = (insn >> 12) & 0x7;
+    let rs1 = (insn >> 15) & 0x1f;
+    let rs2 = (insn >> 20) & 0x1f;
+    let funct7 = insn >> 25;
+    let imm = insn >> 20;
+
+    let r_type = |opcode| Some(Instruction::new(opcode, rd, rs1, rs2, false, false));
+    let unary = |opcode| Some(Instruction::new(opcode, rd, rs1, 0, false, true));
+
+    match (insn & 0x7f, funct7, funct3) {
+        (OP, 0b010_0000, 0b111) => r_type(Opcode::ANDN),
+        (OP, 0b010_0000, 0b110) => r_type(Opcode::ORN),
+        (OP, 0b010_0000, 0b100) => r_type(Opcode::XNOR),
+        (OP, 0b000_0101, 0b100) => r_type(Opcode::MIN),
+        (OP, 0b000_0101, 0b101) => r_type(Opcode::MINU),
+        (OP, 0b000_0101, 0b110) => r_type(Opcode::MAX),
+        (OP, 0b000_0101, 0b111) => r_type(Opcode::MAXU),
+        (OP, 0b011_0000, 0b001) => r_type(Opcode::ROL),
+        (OP, 0b011_0000, 0b101) => r_type(Opcode::ROR),
+        (OP, 0b000_0100, 0b100) if rs2 == 0 => unary(Opcode::ZEXTH),
+        (OP_IMM, _, 0b001) => match imm {
+            0x600 => unary(Opcode::CLZ),
+            0x601 => unary(Opcode::CTZ),
+            0x602 => unary(Opcode::CPOP),
+            0x604 => unary(Opcode::SEXTB),
+            0x605 => unary(Opcode::SEXTH),
+            _ => None,
+        },
+        (OP_IMM, 0b011_0000, 0b101) => {
+            Some(Instruction::new(Opcode::ROR, rd, rs1, rs2, false, true))
+        }
+        (OP_IMM, _, 0b101) => match imm {
+            0x287 => unary(Opcode::ORCB),
+            0x698 => unary(Opcode::REV8),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode_zbb;
+    use crate::Opcode;
+
+    #[test]
+    fn test_decode_zbb() {
+        // The encoding, followed by the expected opcode, operands and whether `c` is immediate.
+        let cases = [
+            // andn a0, a1, a2
+            (0x40c5_f533, Opcode::ANDN, 12, false),
+            // xnor a0, a1, a2
+            (0x40c5_c533, Opcode::XNOR, 12, false),
+            // maxu a0, a1, a2
+            (0x0ac5_f533, Opcode::MAXU, 12, false),
+            // rol a0, a1, a2
+            (0x60c5_9533, Opcode::ROL, 12, false),
+            // rori a0, a1, 7
+            (0x6075_d513, Opcode::ROR, 7, true),
+            // clz a0, a1
+            (0x6005_9513, Opcode::CLZ, 0, true),
+            // cpop a0, a1
+            (0x6025_9513, Opcode::CPOP, 0, true),
+            // sext.h a0, a1
+            (0x6055_9513, Opcode::SEXTH, 0, true),
+            // zext.h a0, a1
+            (0x0805_c533, Opcode::ZEXTH, 0, true),
+            // orc.b a0, a1
+            (0x2875_d513, Opcode::ORCB, 0, true),
+            // rev8 a0, a1
+            (0x6985_d513, Opcode::REV8, 0, true),
+        ];
+        for (insn, opcode, op_c, imm_c) in cases {
+            let instruction = decode_zbb(insn).unwrap();
+            assert_eq!(instruction.opcode, opcode);
+            assert_eq!((instruction.op_a, instruction.op_b, instruction.op_c), (10, 11, op_c));
+            assert_eq!((instruction.imm_b, instruction.imm_c), (false, imm_c));
+        }
+
+        // add a0, a1, a2 and slli a0, a1, 3 are not Zbb instructions.
+        assert!(decode_zbb(0x00c5_8533).is_none());
+        assert!(decode_zbb(0x0035_9513).is_none());
+    }
+}
@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+use crate::events::{
+    memory::{MemoryReadRecord, MemoryWriteRecord},
+    MemoryLocalEvent,
+};
+
+/// An f64 arithmetic operation.
+#[derive(Default, PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum F64Operation {
+    /// Addition.
+    #[default]
+    Add,
+    /// Multiplication.
+    Mul,
+    /// Division.
+    Div,
+}
+
+/// F64 Operation Event.
+///
+/// This event is emitted when an f64 arithmetic operation is performed on the input operands. The
+/// result written over the x operand is the correctly rounded IEEE 754 result when the operands
+/// and the result are normal numbers, and the all-zero sentinel otherwise.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct F64OpEvent {
+    /// The shard number.
+    pub shard: u32,
+    /// The clock cycle.
+    pub clk: u32,
+    /// The operation to perform.
+    pub op: F64Operation,
+    /// The pointer to the x operand, which is overwritten with the result.
+    pub x_ptr: u32,
+    /// The x operand, as its low and high words.
+    pub x: [u32; 2],
+    /// The pointer to the y operand.
+    pub y_ptr: u32,
+    /// The y operand, as its low and high words.
+    pub y: [u32; 2],
+    /// The memory records for the x operand.
+    pub x_memory_records: [MemoryWriteRecord; 2],
+    /// The memory records for the y operand.
+    pub y_memory_records: [MemoryReadRecord; 2],
+    /// The local memory access records.
+    pub local_mem_access: Vec<MemoryLocalEvent>,
+}
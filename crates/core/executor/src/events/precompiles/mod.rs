@@ -1,5 +1,7 @@
+mod aes;
 mod ec;
 mod edwards;
+mod float;
 mod fptower;
 mod keccak256_permute;
 mod sha256_compress;
@@ -9,8 +11,10 @@ mod uint256;
 
 use super::{MemoryLocalEvent, SyscallEvent};
 use crate::{deserialize_hashmap_as_vec, serialize_hashmap_as_vec, syscalls::SyscallCode};
+pub use aes::*;
 pub use ec::*;
 pub use edwards::*;
+pub use float::*;
 pub use fptower::*;
 use hashbrown::HashMap;
 pub use keccak256_permute::*;
@@ -74,6 +78,10 @@ pub enum PrecompileEvent {
     Uint256Mul(Uint256MulEvent),
     /// U256XU2048 mul precompile event.
     U256xU2048Mul(U256xU2048MulEvent),
+    /// AES encryption round precompile event.
+    AesRound(AesRoundEvent),
+    /// F64 arithmetic precompile event.
+    F64Op(F64OpEvent),
 }
 
 /// Trait to retrieve all the local memory events from a vec of precompile events.
@@ -125,6 +133,12 @@ impl PrecompileLocalMemory for Vec<(SyscallEvent, PrecompileEvent)> {
                 PrecompileEvent::U256xU2048Mul(e) => {
                     iterators.push(e.local_mem_access.iter());
                 }
+                PrecompileEvent::AesRound(e) => {
+                    iterators.push(e.local_mem_access.iter());
+                }
+                PrecompileEvent::F64Op(e) => {
+                    iterators.push(e.local_mem_access.iter());
+                }
                 PrecompileEvent::Bls12381Fp(e) | PrecompileEvent::Bn254Fp(e) => {
                     iterators.push(e.local_mem_access.iter());
                 }
@@ -1,6 +1,6 @@
 #[cfg(feature = "profiling")]
-use std::{fs::File, io::BufWriter};
-use std::{str::FromStr, sync::Arc};
+use std::io::BufWriter;
+use std::{collections::BTreeMap, fs::File, path::Path, str::FromStr, sync::Arc};
 
 use crate::estimator::RecordEstimator;
 #[cfg(feature = "profiling")]
@@ -11,15 +11,16 @@ use enum_map::EnumMap;
 use hashbrown::HashMap;
 use serde::{Deserialize, Serialize};
 use monerochan_primitives::consts::BABYBEAR_PRIME;
+use monerochan_core_trace::{TraceError, TraceEvent, TraceHeader, TraceWriter};
 use monerochan_stark::{air::PublicValues, MONEROCHANCoreOpts};
 use strum::IntoEnumIterator;
 use thiserror::Error;
 
 use crate::{
-    context::{IoOptions, MONEROCHANContext},
+    context::{IoOptions, MONEROCHANContext, OutputMode},
     dependencies::{
-        emit_auipc_dependency, emit_branch_dependencies, emit_divrem_dependencies,
-        emit_jump_dependencies, emit_memory_dependencies,
+        emit_auipc_dependency, emit_bitmanip_dependencies, emit_branch_dependencies,
+        emit_divrem_dependencies, emit_jump_dependencies, emit_memory_dependencies,
     },
     estimate_riscv_lde_size,
     events::{
@@ -28,11 +29,13 @@ use crate::{
         MemoryRecord, MemoryRecordEnum, MemoryWriteRecord, SyscallEvent,
         NUM_LOCAL_MEMORY_ENTRIES_PER_ROW_EXEC,
     },
+    get_bitmanip_result,
     hook::{HookEnv, HookRegistry},
+    journal::InputJournal,
     memory::{Entry, Memory},
     pad_rv32im_event_counts,
     record::{ExecutionRecord, MemoryAccessRecord},
-    report::ExecutionReport,
+    report::{CapturedOutput, ExecutionReport},
     state::{ExecutionState, ForkState},
     subproof::SubproofVerifier,
     syscalls::{default_syscall_map, Syscall, SyscallCode, SyscallContext},
@@ -46,7 +49,7 @@ pub const DEFAULT_PC_INC: u32 = 4;
 /// A valid pc should be divisible by 4, so we use 1 to indicate that the pc is not used.
 pub const UNUSED_PC: u32 = 1;
 
-/// The maximum number of instructions in a program.
+/// The maximum number of instructions in a program, unless the prover allows large programs.
 pub const MAX_PROGRAM_SIZE: usize = 1 << 22;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -132,6 +135,17 @@ pub struct Executor<'a> {
     /// The maximum number of cpu cycles to use for execution.
     pub max_cycles: Option<u64>,
 
+    /// The maximum number of cycles to spend in unconstrained blocks.
+    pub unconstrained_cycle_limit: Option<u64>,
+
+    /// Whether to trap on the behaviors which execute but cannot be proven, see
+    /// [`MONEROCHANContextBuilder::strict`](crate::MONEROCHANContextBuilder::strict).
+    pub strict: bool,
+
+    /// Whether a non-zero exit code is reported rather than failing the execution, see
+    /// `MONEROCHANContextBuilder::report_exit_code`.
+    pub report_exit_code: bool,
+
     /// The current trace of the execution that is being collected.
     pub record: Box<ExecutionRecord>,
 
@@ -187,8 +201,15 @@ pub struct Executor<'a> {
     /// The options for the IO.
     pub io_options: IoOptions<'a>,
 
+    /// The writer of the execution trace, if one is being saved.
+    pub trace_writer: Option<TraceWriter<File>>,
+
     /// Temporary event counts for the current shard. This is a field to reuse memory.
     event_counts: EnumMap<RiscvAirId, u64>,
+
+    /// The index in the input journal of each input, by address. Only filled when the input
+    /// journal is enabled.
+    input_regions: BTreeMap<u32, usize>,
 }
 
 /// The different modes the executor can run in.
@@ -218,13 +239,14 @@ pub struct LocalCounts {
 /// Errors that the [``Executor``] can throw.
 #[derive(Error, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub enum ExecutionError {
-    /// The execution failed with a non-zero exit code.
-    #[error("execution failed with exit code {0}")]
+    /// The execution failed with a non-zero exit code: 1 if the program panicked, or a code set by
+    /// the program with `monerochan_runtime::ensure!`.
+    #[error("execution failed with exit code {}", crate::report::describe_exit_code(*.0))]
     HaltWithNonZeroExitCode(u32),
 
-    /// The execution failed with an invalid memory access.
-    #[error("invalid memory access for opcode {0} and address {1}")]
-    InvalidMemoryAccess(Opcode, u32),
+    /// The execution trapped on a faulting instruction.
+    #[error("{0}")]
+    Trap(Box<Trap>),
 
     /// The execution failed with an unimplemented syscall.
     #[error("unimplemented syscall {0}")]
@@ -242,10 +264,6 @@ pub enum ExecutionError {
     #[error("syscall called in unconstrained mode")]
     InvalidSyscallUsage(u64),
 
-    /// The execution failed with an unimplemented feature.
-    #[error("got unimplemented as opcode")]
-    Unimplemented(),
-
     /// The program ended in unconstrained mode.
     #[error("program ended in unconstrained mode")]
     EndInUnconstrained(),
@@ -253,6 +271,75 @@ pub enum ExecutionError {
     /// The unconstrained cycle limit was exceeded.
     #[error("unconstrained cycle limit exceeded")]
     UnconstrainedCycleLimitExceeded(u64),
+
+    /// The execution trace could not be written.
+    #[error("failed to write the execution trace: {0}")]
+    Trace(String),
+}
+
+/// The diagnostics of a faulting instruction, see [`ExecutionError::Trap`].
+#[derive(Error, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[error(
+    "{kind} at pc {pc:#010x}{} ({opcode}{}){}",
+    .symbol.as_ref().map(|symbol| format!(" <{symbol}>")).unwrap_or_default(),
+    .encoding.map(|encoding| format!(" {encoding:#010x}")).unwrap_or_default(),
+    .addr.map(|addr| format!(", faulting address {addr:#010x}")).unwrap_or_default()
+)]
+pub struct Trap {
+    /// The cause of the trap.
+    pub kind: TrapKind,
+    /// The program counter of the faulting instruction.
+    pub pc: u32,
+    /// The opcode of the faulting instruction.
+    pub opcode: Opcode,
+    /// The encoding of the faulting instruction, as found in the ELF.
+    pub encoding: Option<u32>,
+    /// The address accessed by the faulting instruction, if it accesses memory.
+    pub addr: Option<u32>,
+    /// The function of the faulting instruction, as `name+0xoffset`, if the ELF has symbols.
+    pub symbol: Option<String>,
+}
+
+/// The cause of a [`Trap`].
+#[derive(Error, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TrapKind {
+    /// A load or store at an address not aligned to its size.
+    #[error("unaligned memory access")]
+    UnalignedAccess,
+    /// A load or store at an address outside of the memory of the program.
+    #[error("out of bounds memory access")]
+    OutOfBoundsAccess,
+    /// An instruction that is not supported, such as `unimp`.
+    #[error("invalid instruction")]
+    InvalidInstruction,
+    /// A load or store in the guard region below the stack, see [`Program::stack_guard`].
+    #[error("stack overflow")]
+    StackOverflow,
+    /// An instruction accessing a CSR, which the zkVM does not have.
+    #[error("unsupported CSR instruction")]
+    UnsupportedCsr,
+    /// An atomic instruction, which the zkVM does not support.
+    #[error("unsupported atomic instruction")]
+    UnsupportedAtomic,
+    /// A jump or branch to an address not aligned to an instruction. Only trapped in strict mode.
+    #[error("misaligned jump")]
+    MisalignedJump,
+    /// A jump, branch or fall through to an address outside of the program, which ends the
+    /// execution without halting. Only trapped in strict mode.
+    #[error("jump out of the program")]
+    JumpOutOfProgram,
+}
+
+impl TrapKind {
+    /// The cause of the trap on an invalid instruction, refined with its encoding.
+    fn of_invalid_instruction(encoding: u32) -> Self {
+        match encoding & 0x7f {
+            // The SYSTEM instructions other than `ecall` and `ebreak` access CSRs.
+            0x73 if (encoding >> 12) & 0x7 != 0 => Self::UnsupportedCsr,
+            0x2f => Self::UnsupportedAtomic,
+            _ => Self::InvalidInstruction,
+        }
+    }
 }
 
 impl<'a> Executor<'a> {
@@ -341,7 +428,12 @@ impl<'a> Executor<'a> {
             executor_mode: ExecutorMode::Trace,
             emit_global_memory_events: true,
             max_syscall_cycles,
-            report: ExecutionReport::default(),
+            report: ExecutionReport {
+                input_reads: context.journal_input_reads.then(InputJournal::default),
+                output: matches!(context.io_options.output, Some(OutputMode::Capture { .. }))
+                    .then(CapturedOutput::default),
+                ..Default::default()
+            },
             local_counts: LocalCounts::default(),
             print_report: false,
             record_estimator: None,
@@ -349,7 +441,12 @@ impl<'a> Executor<'a> {
             hook_registry,
             opts,
             max_cycles: context.max_cycles,
+            unconstrained_cycle_limit: context.unconstrained_cycle_limit.or_else(|| {
+                std::env::var("UNCONSTRAINED_CYCLE_LIMIT").ok().map(|v| v.parse().unwrap())
+            }),
             deferred_proof_verification: context.deferred_proof_verification.into(),
+            strict: context.strict,
+            report_exit_code: context.report_exit_code,
             memory_checkpoint: Memory::default(),
             uninitialized_memory_checkpoint: Memory::default(),
             local_memory_access: HashMap::new(),
@@ -360,6 +457,47 @@ impl<'a> Executor<'a> {
             lde_size_threshold: 0,
             event_counts: EnumMap::default(),
             io_options: context.io_options,
+            trace_writer: None,
+            input_regions: BTreeMap::new(),
+        }
+    }
+
+    /// Saves the execution trace to the given path.
+    ///
+    /// Every instruction, load, store and syscall executed from now on is appended to the trace,
+    /// which is completed by [`Executor::finish_trace`].
+    pub fn save_trace(&mut self, path: impl AsRef<Path>) -> Result<(), TraceError> {
+        let header = TraceHeader {
+            pc_start: self.program.pc_start,
+            opcodes: EnumMap::<Opcode, ()>::default()
+                .iter()
+                .map(|(opcode, ())| (opcode as u8, opcode.mnemonic().to_string()))
+                .collect(),
+            syscalls: SyscallCode::iter().map(|code| (code as u32, format!("{code:?}"))).collect(),
+        };
+        self.trace_writer = Some(TraceWriter::create(path, &header)?);
+        Ok(())
+    }
+
+    /// Flushes and closes the execution trace started by [`Executor::save_trace`].
+    pub fn finish_trace(&mut self) -> Result<(), TraceError> {
+        if let Some(writer) = self.trace_writer.take() {
+            writer.finish()?;
+        }
+        Ok(())
+    }
+
+    /// Appends an event to the execution trace, if one is being saved.
+    #[inline]
+    fn trace(&mut self, event: TraceEvent) {
+        if self.unconstrained {
+            return;
+        }
+        if let Some(writer) = self.trace_writer.as_mut() {
+            if let Err(e) = writer.write(&event) {
+                tracing::error!("failed to write the execution trace, stopping it: {e}");
+                self.trace_writer = None;
+            }
         }
     }
 
@@ -507,6 +645,11 @@ impl<'a> Executor<'a> {
             panic!("Invalid memory access: addr={addr}");
         }
 
+        // A word of an input is read if it is untouched when loaded.
+        if self.report.input_reads.is_some() && self.state.memory.page_table.get(addr).is_none() {
+            self.journal_read(addr);
+        }
+
         // Get the memory record entry.
         let entry = self.state.memory.page_table.entry(addr);
         if self.executor_mode == ExecutorMode::Checkpoint || self.unconstrained {
@@ -972,6 +1115,54 @@ impl<'a> Executor<'a> {
         record.timestamp = timestamp;
     }
 
+    /// Outputs the lines written by the program to `stdout` (`fd` 1) or `stderr` (`fd` 2).
+    ///
+    /// The lines go to the writer the stream is redirected to, if any, and are otherwise handled
+    /// according to the [`OutputMode`].
+    pub(crate) fn output_lines(&mut self, fd: u32, lines: Vec<String>) {
+        let (name, writer) = if fd == 1 {
+            ("stdout", &mut self.io_options.stdout)
+        } else {
+            ("stderr", &mut self.io_options.stderr)
+        };
+        if let Some(writer) = writer {
+            for mut line in lines {
+                line.push('\n');
+                if let Err(e) = writer.write_all(line.as_bytes()) {
+                    tracing::error!("failed to write to {name} io override: {e}");
+                }
+            }
+            return;
+        }
+
+        match self.io_options.output.unwrap_or(OutputMode::Print) {
+            OutputMode::Print => lines.into_iter().for_each(|line| eprintln!("{name}: {line}")),
+            OutputMode::Capture { max_size } => {
+                let output = self.report.output.get_or_insert_with(CapturedOutput::default);
+                let stream = if fd == 1 { &mut output.stdout } else { &mut output.stderr };
+                lines.iter().for_each(|line| stream.push(line, max_size));
+            }
+            OutputMode::Discard => {}
+        }
+    }
+
+    /// Records an input written to `ptr` by `HINT_READ`, if the input journal is enabled.
+    pub(crate) fn journal_input(&mut self, ptr: u32, len: u32) {
+        if let Some(journal) = &mut self.report.input_reads {
+            self.input_regions.insert(ptr, journal.inputs.len());
+            journal.push(ptr, len);
+        }
+    }
+
+    /// Marks the word at `addr` as read, if it belongs to an input.
+    fn journal_read(&mut self, addr: u32) {
+        if let Some(journal) = &mut self.report.input_reads {
+            if let Some((_, &index)) = self.input_regions.range(..=addr).next_back() {
+                journal.inputs[index].mark(addr);
+            }
+        }
+    }
+
     /// Read from memory, assuming that all addresses are aligned.
     #[inline]
     pub fn mr_cpu(&mut self, addr: u32) -> u32 {
@@ -982,6 +1173,9 @@ impl<'a> Executor<'a> {
         if self.executor_mode == ExecutorMode::Trace {
             self.memory_accesses.memory = Some(record.into());
         }
+        if self.trace_writer.is_some() {
+            self.trace(TraceEvent::MemoryRead { addr, value: record.value });
+        }
         record.value
     }
 
@@ -1022,6 +1216,9 @@ impl<'a> Executor<'a> {
             debug_assert!(self.memory_accesses.memory.is_none());
             self.memory_accesses.memory = Some(record.into());
         }
+        if self.trace_writer.is_some() {
+            self.trace(TraceEvent::MemoryWrite { addr, value });
+        }
     }
 
     /// Write to a register.
@@ -1139,6 +1336,26 @@ impl<'a> Executor<'a> {
                 self.record.divrem_events.push(event);
                 emit_divrem_dependencies(self, event);
             }
+            Opcode::ANDN |
+            Opcode::ORN |
+            Opcode::XNOR |
+            Opcode::CLZ |
+            Opcode::CTZ |
+            Opcode::CPOP |
+            Opcode::MAX |
+            Opcode::MAXU |
+            Opcode::MIN |
+            Opcode::MINU |
+            Opcode::SEXTB |
+            Opcode::SEXTH |
+            Opcode::ZEXTH |
+            Opcode::ROL |
+            Opcode::ROR |
+            Opcode::ORCB |
+            Opcode::REV8 => {
+                self.record.bitmanip_events.push(event);
+                emit_bitmanip_dependencies(self, event);
+            }
             _ => unreachable!(),
         }
     }
@@ -1290,23 +1507,89 @@ impl<'a> Executor<'a> {
     }
 
     /// Fetch the input operand values for a load instruction.
-    fn load_rr(&mut self, instruction: &Instruction) -> (Register, u32, u32, u32, u32) {
+    fn load_rr(
+        &mut self,
+        instruction: &Instruction,
+    ) -> Result<(Register, u32, u32, u32, u32), ExecutionError> {
         let (rd, rs1, imm) = instruction.i_type();
         let (b, c) = (self.rr_cpu(rs1, MemoryAccessPosition::B), imm);
         let addr = b.wrapping_add(c);
+        self.check_bounds(instruction, addr)?;
         let memory_value = self.mr_cpu(align(addr));
-        (rd, b, c, addr, memory_value)
+        Ok((rd, b, c, addr, memory_value))
     }
 
     /// Fetch the input operand values for a store instruction.
-    fn store_rr(&mut self, instruction: &Instruction) -> (u32, u32, u32, u32, u32) {
+    fn store_rr(
+        &mut self,
+        instruction: &Instruction,
+    ) -> Result<(u32, u32, u32, u32, u32), ExecutionError> {
         let (rs1, rs2, imm) = instruction.s_type();
         let c = imm;
         let b = self.rr_cpu(rs2, MemoryAccessPosition::B);
         let a = self.rr_cpu(rs1, MemoryAccessPosition::A);
         let addr = b.wrapping_add(c);
+        self.check_bounds(instruction, addr)?;
         let memory_value = self.word(align(addr));
-        (a, b, c, addr, memory_value)
+        Ok((a, b, c, addr, memory_value))
+    }
+
+    /// Checks that the memory accessed by a load or store is within the memory of the program,
+    /// that is neither in the registers' address space nor past the babybear field, and outside
+    /// of the stack guard region.
+    fn check_bounds(&self, instruction: &Instruction, addr: u32) -> Result<(), ExecutionError> {
+        let addr = align(addr);
+        if addr <= Register::X31 as u32 || addr >= BABYBEAR_PRIME {
+            return Err(self.trap(TrapKind::OutOfBoundsAccess, instruction, Some(addr)));
+        }
+        if self.program.in_stack_guard(addr) {
+            return Err(self.trap(TrapKind::StackOverflow, instruction, Some(addr)));
+        }
+        Ok(())
+    }
+
+    /// Builds the error of a trap on `instruction`, at the current program counter.
+    fn trap(&self, kind: TrapKind, instruction: &Instruction, addr: Option<u32>) -> ExecutionError {
+        let pc = self.state.pc;
+        let encoding = self.program.memory_image.get(&pc).copied();
+        let kind = match (kind, encoding) {
+            (TrapKind::InvalidInstruction, Some(encoding)) => {
+                TrapKind::of_invalid_instruction(encoding)
+            }
+            _ => kind,
+        };
+        ExecutionError::Trap(Box::new(Trap {
+            kind,
+            pc,
+            opcode: instruction.opcode,
+            encoding,
+            addr,
+            symbol: self.program.symbolize(pc),
+        }))
+    }
+
+    /// Checks that the program counter after `instruction` is an instruction of the program, or
+    /// zero if the instruction is the `HALT` syscall.
+    ///
+    /// The executor ends the execution at any other address outside of the program, and runs the
+    /// instruction below a misaligned address, but neither can be proven.
+    fn check_next_pc(
+        &self,
+        instruction: &Instruction,
+        syscall: SyscallCode,
+        next_pc: u32,
+    ) -> Result<(), ExecutionError> {
+        if next_pc == 0 && syscall == SyscallCode::HALT {
+            return Ok(());
+        }
+        if !next_pc.is_multiple_of(4) {
+            return Err(self.trap(TrapKind::MisalignedJump, instruction, Some(next_pc)));
+        }
+        let program_size = (self.program.instructions.len() * 4) as u32;
+        if next_pc.wrapping_sub(self.program.pc_base) >= program_size {
+            return Err(self.trap(TrapKind::JumpOutOfProgram, instruction, Some(next_pc)));
+        }
+        Ok(())
     }
 
     /// Fetch the input operand values for a branch instruction.
@@ -1359,6 +1642,14 @@ impl<'a> Executor<'a> {
                 self.local_counts.event_counts[Opcode::MUL] += 2;
                 self.local_counts.event_counts[Opcode::ADD] += 2;
                 self.local_counts.event_counts[Opcode::SLTU] += 1;
+            } else if matches!(instruction.opcode, Opcode::ROL | Opcode::ROR) {
+                self.local_counts.event_counts[Opcode::SLL] += 1;
+                self.local_counts.event_counts[Opcode::SRL] += 1;
+            } else if matches!(
+                instruction.opcode,
+                Opcode::MAX | Opcode::MAXU | Opcode::MIN | Opcode::MINU
+            ) {
+                self.local_counts.event_counts[Opcode::SLTU] += 1;
             }
         }
 
@@ -1383,7 +1674,7 @@ impl<'a> Executor<'a> {
             return Err(ExecutionError::Breakpoint());
         } else if instruction.is_unimp_instruction() {
             // See https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#instruction-aliases
-            return Err(ExecutionError::Unimplemented());
+            return Err(self.trap(TrapKind::InvalidInstruction, instruction, None));
         } else {
             eprintln!("unreachable: {:?}", instruction.opcode);
             unreachable!()
@@ -1411,6 +1702,10 @@ impl<'a> Executor<'a> {
             );
         }
 
+        if self.strict {
+            self.check_next_pc(instruction, syscall, next_pc)?;
+        }
+
         // Update the program counter.
         self.state.pc = next_pc;
 
@@ -1478,6 +1773,9 @@ impl<'a> Executor<'a> {
                     b.wrapping_rem(c)
                 }
             }
+            _ if instruction.is_bitmanip_instruction() => {
+                get_bitmanip_result(instruction.opcode, b, c)
+            }
             _ => unreachable!(),
         };
         self.alu_rw(rd, a);
@@ -1489,26 +1787,26 @@ impl<'a> Executor<'a> {
         &mut self,
         instruction: &Instruction,
     ) -> Result<(u32, u32, u32), ExecutionError> {
-        let (rd, b, c, addr, memory_read_value) = self.load_rr(instruction);
+        let (rd, b, c, addr, memory_read_value) = self.load_rr(instruction)?;
 
         let a = match instruction.opcode {
             Opcode::LB => ((memory_read_value >> ((addr % 4) * 8)) & 0xFF) as i8 as i32 as u32,
             Opcode::LH => {
                 if addr % 2 != 0 {
-                    return Err(ExecutionError::InvalidMemoryAccess(Opcode::LH, addr));
+                    return Err(self.trap(TrapKind::UnalignedAccess, instruction, Some(addr)));
                 }
                 ((memory_read_value >> (((addr / 2) % 2) * 16)) & 0xFFFF) as i16 as i32 as u32
             }
             Opcode::LW => {
                 if addr % 4 != 0 {
-                    return Err(ExecutionError::InvalidMemoryAccess(Opcode::LW, addr));
+                    return Err(self.trap(TrapKind::UnalignedAccess, instruction, Some(addr)));
                 }
                 memory_read_value
             }
             Opcode::LBU => (memory_read_value >> ((addr % 4) * 8)) & 0xFF,
             Opcode::LHU => {
                 if addr % 2 != 0 {
-                    return Err(ExecutionError::InvalidMemoryAccess(Opcode::LHU, addr));
+                    return Err(self.trap(TrapKind::UnalignedAccess, instruction, Some(addr)));
                 }
                 (memory_read_value >> (((addr / 2) % 2) * 16)) & 0xFFFF
             }
@@ -1523,7 +1821,7 @@ impl<'a> Executor<'a> {
         &mut self,
         instruction: &Instruction,
     ) -> Result<(u32, u32, u32), ExecutionError> {
-        let (a, b, c, addr, memory_read_value) = self.store_rr(instruction);
+        let (a, b, c, addr, memory_read_value) = self.store_rr(instruction)?;
 
         let memory_store_value = match instruction.opcode {
             Opcode::SB => {
@@ -1532,14 +1830,14 @@ impl<'a> Executor<'a> {
             }
             Opcode::SH => {
                 if addr % 2 != 0 {
-                    return Err(ExecutionError::InvalidMemoryAccess(Opcode::SH, addr));
+                    return Err(self.trap(TrapKind::UnalignedAccess, instruction, Some(addr)));
                 }
                 let shift = ((addr / 2) % 2) * 16;
                 ((a & 0xFFFF) << shift) | (memory_read_value & !(0xFFFF << shift))
             }
             Opcode::SW => {
                 if addr % 4 != 0 {
-                    return Err(ExecutionError::InvalidMemoryAccess(Opcode::SW, addr));
+                    return Err(self.trap(TrapKind::UnalignedAccess, instruction, Some(addr)));
                 }
                 a
             }
@@ -1586,6 +1884,10 @@ impl<'a> Executor<'a> {
         let b = self.rr_cpu(Register::X10, MemoryAccessPosition::B);
         let syscall = SyscallCode::from_u32(syscall_id);
 
+        if self.trace_writer.is_some() {
+            self.trace(TraceEvent::Syscall { code: syscall_id, arg1: b, arg2: c });
+        }
+
         if self.print_report && !self.unconstrained {
             self.report.syscall_counts[syscall] += 1;
         }
@@ -1608,6 +1910,7 @@ impl<'a> Executor<'a> {
         *syscall_count += 1;
 
         let syscall_impl = self.get_syscall(syscall).cloned();
+        let report_exit_code = self.report_exit_code;
         let mut precompile_rt = SyscallContext::new(self);
         let (a, precompile_next_pc, precompile_cycles, returned_exit_code) =
             if let Some(syscall_impl) = syscall_impl {
@@ -1617,9 +1920,15 @@ impl<'a> Executor<'a> {
                 let res = syscall_impl.execute(&mut precompile_rt, syscall, b, c);
                 let a = if let Some(val) = res { val } else { syscall_id };
 
-                // If the syscall is `HALT` and the exit code is non-zero, return an error.
+                // If the syscall is `HALT` and the exit code is non-zero, return an error, unless
+                // the exit code is reported.
                 if syscall == SyscallCode::HALT && precompile_rt.exit_code != 0 {
-                    return Err(ExecutionError::HaltWithNonZeroExitCode(precompile_rt.exit_code));
+                    if !report_exit_code {
+                        return Err(ExecutionError::HaltWithNonZeroExitCode(
+                            precompile_rt.exit_code,
+                        ));
+                    }
+                    precompile_rt.rt.report.exit_code = precompile_rt.exit_code;
                 }
 
                 (a, precompile_rt.next_pc, syscall_impl.num_extra_cycles(), precompile_rt.exit_code)
@@ -1700,6 +2009,19 @@ impl<'a> Executor<'a> {
         // Log the current state of the runtime.
         self.log(&instruction);
 
+        if self.trace_writer.is_some() {
+            self.trace(TraceEvent::Instruction {
+                clk: self.state.global_clk,
+                pc: self.state.pc,
+                opcode: instruction.opcode as u8,
+                op_a: instruction.op_a,
+                op_b: instruction.op_b,
+                op_c: instruction.op_c,
+                imm_b: instruction.imm_b,
+                imm_c: instruction.imm_c,
+            });
+        }
+
         // Execute the instruction.
         self.execute_instruction(&instruction)?;
 
@@ -2023,8 +2345,7 @@ impl<'a> Executor<'a> {
             self.initialize();
         }
 
-        let unconstrained_cycle_limit =
-            std::env::var("UNCONSTRAINED_CYCLE_LIMIT").map(|v| v.parse::<u64>().unwrap()).ok();
+        let unconstrained_cycle_limit = self.unconstrained_cycle_limit;
 
         // Loop until we've executed `self.shard_batch_size` shards if `self.shard_batch_size` is
         // set.
@@ -2110,17 +2431,9 @@ impl<'a> Executor<'a> {
 
     fn postprocess(&mut self) {
         // Flush remaining stdout/stderr
-        for (fd, buf) in &self.io_buf {
-            if !buf.is_empty() {
-                match fd {
-                    1 => {
-                        eprintln!("stdout: {buf}");
-                    }
-                    2 => {
-                        eprintln!("stderr: {buf}");
-                    }
-                    _ => {}
-                }
+        for fd in [1, 2] {
+            if let Some(buf) = self.io_buf.remove(&fd).filter(|buf| !buf.is_empty()) {
+                self.output_lines(fd, vec![buf]);
             }
         }
 
@@ -2269,6 +2582,25 @@ impl<'a> Executor<'a> {
         // Compute the number of events in the lt chip.
         event_counts[RiscvAirId::Lt] = opcode_counts[Opcode::SLT] + opcode_counts[Opcode::SLTU];
 
+        // Compute the number of events in the bitmanip chip.
+        event_counts[RiscvAirId::BitManip] = opcode_counts[Opcode::ANDN] +
+            opcode_counts[Opcode::ORN] +
+            opcode_counts[Opcode::XNOR] +
+            opcode_counts[Opcode::CLZ] +
+            opcode_counts[Opcode::CTZ] +
+            opcode_counts[Opcode::CPOP] +
+            opcode_counts[Opcode::MAX] +
+            opcode_counts[Opcode::MAXU] +
+            opcode_counts[Opcode::MIN] +
+            opcode_counts[Opcode::MINU] +
+            opcode_counts[Opcode::SEXTB] +
+            opcode_counts[Opcode::SEXTH] +
+            opcode_counts[Opcode::ZEXTH] +
+            opcode_counts[Opcode::ROL] +
+            opcode_counts[Opcode::ROR] +
+            opcode_counts[Opcode::ORCB] +
+            opcode_counts[Opcode::REV8];
+
         // Compute the number of events in the memory local chip.
         event_counts[RiscvAirId::MemoryLocal] =
             touched_addresses.div_ceil(NUM_LOCAL_MEMORY_ENTRIES_PER_ROW_EXEC as u64);
@@ -2348,16 +2680,16 @@ pub const fn align(addr: u32) -> u32 {
 mod tests {
 
     use monerochan_stark::MONEROCHANCoreOpts;
-    use monerochan_runtime::syscalls::SHA_COMPRESS;
+    use monerochan_runtime::syscalls::{HALT, HINT_READ, SHA_COMPRESS, WRITE};
 
     use crate::programs::tests::{
         fibonacci_program, panic_program, secp256r1_add_program, secp256r1_double_program,
         simple_memory_program, simple_program, ssz_withdrawals_program, u256xu2048_mul_program,
     };
 
-    use crate::{Register, MONEROCHANContext};
+    use crate::{OutputMode, Register, Symbol, MONEROCHANContext};
 
-    use super::{Executor, Instruction, Opcode, Program};
+    use super::{ExecutionError, Executor, Instruction, Opcode, Program, TrapKind};
 
     fn _assert_send<T: Send>() {}
 
@@ -2894,6 +3226,46 @@ mod tests {
         simple_op_code_test(Opcode::REMU, 0, 0, 0);
     }
 
+    #[test]
+    #[allow(clippy::unreadable_literal)]
+    fn bitmanip_tests() {
+        simple_op_code_test(Opcode::ANDN, 0x0000f0f0, 0x0000ffff, 0xff0f0f0f);
+        simple_op_code_test(Opcode::ORN, 0xffff0f0f, 0x00000f0f, 0x0000ffff);
+        simple_op_code_test(Opcode::XNOR, 0xffff00ff, 0x12340000, 0x1234ff00);
+
+        simple_op_code_test(Opcode::CLZ, 32, 0, 0);
+        simple_op_code_test(Opcode::CLZ, 0, neg(1), 0);
+        simple_op_code_test(Opcode::CLZ, 19, 0x1000, 0);
+        simple_op_code_test(Opcode::CTZ, 32, 0, 0);
+        simple_op_code_test(Opcode::CTZ, 12, 0x1000, 0);
+        simple_op_code_test(Opcode::CTZ, 31, 1 << 31, 0);
+        simple_op_code_test(Opcode::CPOP, 0, 0, 0);
+        simple_op_code_test(Opcode::CPOP, 32, neg(1), 0);
+        simple_op_code_test(Opcode::CPOP, 13, 0x12345678, 0);
+
+        simple_op_code_test(Opcode::MAX, 5, neg(3), 5);
+        simple_op_code_test(Opcode::MAXU, neg(3), neg(3), 5);
+        simple_op_code_test(Opcode::MIN, neg(3), neg(3), 5);
+        simple_op_code_test(Opcode::MINU, 5, neg(3), 5);
+        simple_op_code_test(Opcode::MIN, 7, 7, 7);
+
+        simple_op_code_test(Opcode::SEXTB, 0x7f, 0x1234567f, 0);
+        simple_op_code_test(Opcode::SEXTB, 0xffffff80, 0x12345680, 0);
+        simple_op_code_test(Opcode::SEXTH, 0x00007fff, 0x12347fff, 0);
+        simple_op_code_test(Opcode::SEXTH, 0xffff8000, 0x12348000, 0);
+        simple_op_code_test(Opcode::ZEXTH, 0x0000abcd, 0x1234abcd, 0);
+
+        simple_op_code_test(Opcode::ROL, 0x23456781, 0x12345678, 4);
+        simple_op_code_test(Opcode::ROL, 0x12345678, 0x12345678, 32);
+        simple_op_code_test(Opcode::ROR, 0x81234567, 0x12345678, 4);
+        simple_op_code_test(Opcode::ROR, 0x12345678, 0x12345678, 0);
+        simple_op_code_test(Opcode::ROR, 0x2468acf0, 0x12345678, 31);
+
+        simple_op_code_test(Opcode::ORCB, 0xff00ffff, 0x12003401, 0);
+        simple_op_code_test(Opcode::ORCB, 0, 0, 0);
+        simple_op_code_test(Opcode::REV8, 0x78563412, 0x12345678, 0);
+    }
+
     #[test]
     #[allow(clippy::unreadable_literal)]
     fn shift_tests() {
@@ -2995,6 +3367,200 @@ mod tests {
         assert_eq!(runtime.register(Register::X11), 0x65256525);
     }
 
+    #[test]
+    fn test_input_journal() {
+        let instructions = vec![
+            Instruction::new(Opcode::ADD, 5, 0, HINT_READ, false, true),
+            Instruction::new(Opcode::ADD, 10, 0, 0x1000, false, true),
+            Instruction::new(Opcode::ADD, 11, 0, 10, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            // Read the last word, and overwrite the second one before reading it.
+            Instruction::new(Opcode::LW, 12, 10, 8, false, true),
+            Instruction::new(Opcode::SW, 0, 10, 4, false, true),
+            Instruction::new(Opcode::LW, 13, 10, 4, false, true),
+        ];
+        let program = Program::new(instructions, 0, 0);
+        let context = MONEROCHANContext::builder().journal_input_reads(true).build();
+        let mut runtime = Executor::with_context(program, MONEROCHANCoreOpts::default(), context);
+        runtime.write_stdin_slice(&[7; 10]);
+        runtime.run().unwrap();
+
+        let journal = runtime.report.input_reads.unwrap();
+        assert_eq!(journal.inputs.len(), 1);
+        assert_eq!(journal.inputs[0].ptr, 0x1000);
+        assert_eq!(journal.inputs[0].read_ranges(), vec![8..10]);
+        assert_eq!(journal.read_pages(4096), vec![0x1000]);
+    }
+
+    #[test]
+    fn test_captured_output() {
+        let run = |max_size| {
+            // Write "hi\nA" to stdout.
+            let instructions = vec![
+                Instruction::new(Opcode::ADD, 29, 0, 0x410a6968, false, true),
+                Instruction::new(Opcode::SW, 29, 0, 0x1000, false, true),
+                Instruction::new(Opcode::ADD, 5, 0, WRITE, false, true),
+                Instruction::new(Opcode::ADD, 10, 0, 1, false, true),
+                Instruction::new(Opcode::ADD, 11, 0, 0x1000, false, true),
+                Instruction::new(Opcode::ADD, 12, 0, 4, false, true),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            ];
+            let program = Program::new(instructions, 0, 0);
+            let context =
+                MONEROCHANContext::builder().output(OutputMode::Capture { max_size }).build();
+            let mut runtime =
+                Executor::with_context(program, MONEROCHANCoreOpts::default(), context);
+            runtime.run().unwrap();
+            runtime.report.output.unwrap()
+        };
+
+        let output = run(1024);
+        assert_eq!(output.stdout.text, "hi\nA\n");
+        assert!(!output.stdout.truncated);
+        assert!(output.stderr.text.is_empty());
+
+        let output = run(3);
+        assert_eq!(output.stdout.text, "hi\n");
+        assert!(output.stdout.truncated);
+    }
+
+    #[test]
+    fn test_trap_diagnostics() {
+        let run = |instructions| {
+            let mut program = Program::new(instructions, 0, 0);
+            program.symbols.push(Symbol { name: "main".to_string(), address: 0, size: 8 });
+            let mut runtime = Executor::new(program, MONEROCHANCoreOpts::default());
+            match runtime.run() {
+                Err(ExecutionError::Trap(trap)) => trap,
+                result => panic!("expected a trap, got {result:?}"),
+            }
+        };
+
+        let trap = run(vec![
+            Instruction::new(Opcode::ADD, 29, 0, 0x1001, false, true),
+            Instruction::new(Opcode::LW, 28, 29, 0, false, true),
+        ]);
+        assert_eq!(trap.kind, TrapKind::UnalignedAccess);
+        assert_eq!(trap.opcode, Opcode::LW);
+        assert_eq!((trap.pc, trap.addr), (4, Some(0x1001)));
+        assert_eq!(trap.symbol.as_deref(), Some("main+0x4"));
+
+        let trap = run(vec![
+            Instruction::new(Opcode::ADD, 29, 0, 20, false, true),
+            Instruction::new(Opcode::SW, 0, 29, 0, false, true),
+        ]);
+        assert_eq!(trap.kind, TrapKind::OutOfBoundsAccess);
+        assert_eq!(trap.addr, Some(20));
+
+        let mut program = Program::new(
+            vec![
+                Instruction::new(Opcode::ADD, 2, 0, 0x2000, false, true),
+                Instruction::new(Opcode::SW, 1, 2, 0, false, true),
+                Instruction::new(Opcode::ADD, 2, 2, u32::MAX - 3, false, true),
+                Instruction::new(Opcode::SW, 1, 2, 0, false, true),
+            ],
+            0,
+            0,
+        );
+        program.stack_guard = Some(0x1000);
+        let mut runtime = Executor::new(program, MONEROCHANCoreOpts::default());
+        let Err(ExecutionError::Trap(trap)) = runtime.run() else { panic!("expected a trap") };
+        assert_eq!(trap.kind, TrapKind::StackOverflow);
+        assert_eq!((trap.pc, trap.addr), (12, Some(0x1ffc)));
+
+        let trap = run(vec![Instruction::unimp()]);
+        assert_eq!(trap.kind, TrapKind::InvalidInstruction);
+        assert_eq!((trap.pc, trap.addr), (0, None));
+        assert_eq!(trap.symbol.as_deref(), Some("main+0x0"));
+
+        // The encoding of an instruction which cannot be decoded tells unsupported ones apart.
+        for (encoding, kind) in [
+            // csrr a0, cycle
+            (0xc000_2573, TrapKind::UnsupportedCsr),
+            // amoadd.w a0, a1, (a2)
+            (0x00b6_252f, TrapKind::UnsupportedAtomic),
+        ] {
+            let mut program = Program::new(vec![Instruction::unimp()], 0, 0);
+            program.memory_image.insert(0, encoding);
+            let mut runtime = Executor::new(program, MONEROCHANCoreOpts::default());
+            let Err(ExecutionError::Trap(trap)) = runtime.run() else { panic!("expected a trap") };
+            assert_eq!(trap.kind, kind);
+            assert_eq!(trap.encoding, Some(encoding));
+        }
+    }
+
+    #[test]
+    fn test_strict_mode() {
+        let run = |instructions: &[Instruction], strict| {
+            let program = Program::new(instructions.to_vec(), 0, 0);
+            let context = MONEROCHANContext::builder().strict(strict).build();
+            Executor::with_context(program, MONEROCHANCoreOpts::default(), context).run()
+        };
+        let strict_trap = |instructions: &[Instruction]| match run(instructions, true) {
+            Err(ExecutionError::Trap(trap)) => trap,
+            result => panic!("expected a trap, got {result:?}"),
+        };
+
+        // A jump to a misaligned address runs the instruction below it.
+        let misaligned = [
+            Instruction::new(Opcode::ADD, 5, 0, 10, false, true),
+            Instruction::new(Opcode::JALR, 1, 5, 0, false, true),
+            Instruction::new(Opcode::ADD, 6, 0, 1, false, true),
+        ];
+        assert!(run(&misaligned, false).is_ok());
+        let trap = strict_trap(&misaligned);
+        assert_eq!(trap.kind, TrapKind::MisalignedJump);
+        assert_eq!((trap.pc, trap.addr), (4, Some(10)));
+
+        // A jump out of the program ends the execution without halting.
+        let out_of_program = [Instruction::new(Opcode::JAL, 1, 0x100, 0, true, true)];
+        assert!(run(&out_of_program, false).is_ok());
+        let trap = strict_trap(&out_of_program);
+        assert_eq!(trap.kind, TrapKind::JumpOutOfProgram);
+        assert_eq!((trap.pc, trap.addr), (0, Some(0x100)));
+
+        // Only the `HALT` syscall may end the execution at address zero.
+        let run_at = |instructions: Vec<Instruction>| {
+            let program = Program::new(instructions, 0x1000, 0x1000);
+            let context = MONEROCHANContext::builder().strict(true).build();
+            Executor::with_context(program, MONEROCHANCoreOpts::default(), context).run()
+        };
+        let halt = vec![
+            Instruction::new(Opcode::ADD, 5, 0, HALT, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+        ];
+        assert!(run_at(halt).is_ok());
+        let jump_to_zero = vec![Instruction::new(Opcode::JALR, 1, 0, 0, false, true)];
+        match run_at(jump_to_zero) {
+            Err(ExecutionError::Trap(trap)) => {
+                assert_eq!(trap.kind, TrapKind::JumpOutOfProgram);
+                assert_eq!((trap.pc, trap.addr), (0x1000, Some(0)));
+            }
+            result => panic!("expected a trap, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn test_report_exit_code() {
+        let run = |report_exit_code| {
+            let instructions = vec![
+                Instruction::new(Opcode::ADD, 5, 0, HALT, false, true),
+                Instruction::new(Opcode::ADD, 10, 0, 3, false, true),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            ];
+            let program = Program::new(instructions, 0, 0);
+            let context = MONEROCHANContext::builder().report_exit_code(report_exit_code).build();
+            let mut runtime =
+                Executor::with_context(program, MONEROCHANCoreOpts::default(), context);
+            runtime.run().map(|()| runtime.report.exit_code)
+        };
+
+        let err = run(false).unwrap_err();
+        assert_eq!(err, ExecutionError::HaltWithNonZeroExitCode(3));
+        assert_eq!(err.to_string(), "execution failed with exit code 3 (set by the program)");
+        assert_eq!(run(true).unwrap(), 3);
+    }
+
     #[test]
     #[should_panic]
     fn test_invalid_address_access_sw() {
@@ -83,6 +83,7 @@ impl Default for HookRegistry<'_> {
             (FD_BLS12_381_INVERSE, hookify(bls::hook_bls12_381_inverse)),
             (FD_FP_SQRT, hookify(fp_ops::hook_fp_sqrt)),
             (FD_FP_INV, hookify(fp_ops::hook_fp_inverse)),
+            (FD_CYCLE_COUNT, hookify(hook_cycle_count)),
         ]);
 
         Self { table }
@@ -108,6 +109,14 @@ pub struct HookEnv<'a, 'b: 'a> {
     pub runtime: &'a Executor<'b>,
 }
 
+/// The hook for `monerochan_runtime::io::cycle_count`.
+///
+/// Returns the global clock of the runtime as little-endian bytes.
+#[must_use]
+pub fn hook_cycle_count(env: HookEnv, _: &[u8]) -> Vec<Vec<u8>> {
+    vec![env.runtime.state.global_clk.to_le_bytes().to_vec()]
+}
+
 /// The hook for the `ecrecover` patches.
 ///
 /// The input should be of the form [(`curve_id_u8` | `r_is_y_odd_u8` << 7) || `r` || `alpha`]
@@ -803,4 +812,13 @@ mod tests {
     pub fn registry_empty_is_empty() {
         assert_eq!(HookRegistry::empty().table.len(), 0);
     }
+
+    #[test]
+    pub fn cycle_count_hook_returns_global_clk() {
+        let mut runtime = Executor::new(crate::Program::new(vec![], 0, 0), Default::default());
+        runtime.state.global_clk = 1234;
+        let registry = HookRegistry::new();
+        let res = registry.get(FD_CYCLE_COUNT).unwrap().invoke_hook(runtime.hook_env(), &[]);
+        assert_eq!(res, vec![1234u64.to_le_bytes().to_vec()]);
+    }
 }
@@ -64,7 +64,24 @@ impl Instruction {
                 Opcode::DIV |
                 Opcode::DIVU |
                 Opcode::REM |
-                Opcode::REMU
+                Opcode::REMU |
+                Opcode::ANDN |
+                Opcode::ORN |
+                Opcode::XNOR |
+                Opcode::CLZ |
+                Opcode::CTZ |
+                Opcode::CPOP |
+                Opcode::MAX |
+                Opcode::MAXU |
+                Opcode::MIN |
+                Opcode::MINU |
+                Opcode::SEXTB |
+                Opcode::SEXTH |
+                Opcode::ZEXTH |
+                Opcode::ROL |
+                Opcode::ROR |
+                Opcode::ORCB |
+                Opcode::REV8
         )
     }
 
@@ -120,6 +137,32 @@ impl Instruction {
         matches!(self.opcode, Opcode::DIV | Opcode::DIVU | Opcode::REM | Opcode::REMU)
     }
 
+    /// Returns if the instruction is a bit manipulation instruction.
+    #[must_use]
+    #[inline]
+    pub const fn is_bitmanip_instruction(&self) -> bool {
+        matches!(
+            self.opcode,
+            Opcode::ANDN |
+                Opcode::ORN |
+                Opcode::XNOR |
+                Opcode::CLZ |
+                Opcode::CTZ |
+                Opcode::CPOP |
+                Opcode::MAX |
+                Opcode::MAXU |
+                Opcode::MIN |
+                Opcode::MINU |
+                Opcode::SEXTB |
+                Opcode::SEXTH |
+                Opcode::ZEXTH |
+                Opcode::ROL |
+                Opcode::ROR |
+                Opcode::ORCB |
+                Opcode::REV8
+        )
+    }
+
     /// Returns if the instruction is an ebreak instruction.
     #[must_use]
     #[inline]
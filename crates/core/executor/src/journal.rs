@@ -0,0 +1,140 @@
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+/// A journal of the reads of the inputs of a program, see
+/// [`crate::MONEROCHANContextBuilder::journal_input_reads`].
+///
+/// An input is a buffer read from stdin with the `HINT_READ` syscall. A word of an input counts as
+/// read if the program loads it before overwriting it, in or out of unconstrained blocks.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputJournal {
+    /// The inputs, in the order they were read from stdin.
+    pub inputs: Vec<InputReads>,
+}
+
+/// The reads of a single input.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputReads {
+    /// The address the input was written to.
+    pub ptr: u32,
+    /// The length of the input, in bytes.
+    pub len: u32,
+    /// A bitmap of the words of the input that were read, the first word being the lowest bit.
+    pub read_words: Vec<u64>,
+}
+
+impl InputJournal {
+    /// Records an input of `len` bytes written to `ptr`.
+    pub(crate) fn push(&mut self, ptr: u32, len: u32) {
+        let words = len.div_ceil(4) as usize;
+        self.inputs.push(InputReads { ptr, len, read_words: vec![0; words.div_ceil(64)] });
+    }
+
+    /// The indices of the inputs that were not read at all.
+    ///
+    /// These inputs can be emptied without changing the execution, as long as the program does
+    /// not depend on their length.
+    pub fn unread_inputs(&self) -> impl Iterator<Item = usize> + '_ {
+        self.inputs.iter().enumerate().filter(|(_, input)| !input.is_read()).map(|(i, _)| i)
+    }
+
+    /// The addresses of the pages of `page_size` bytes holding read words of an input.
+    ///
+    /// `page_size` must be a power of two, and at least 4.
+    #[must_use]
+    pub fn read_pages(&self, page_size: u32) -> Vec<u32> {
+        assert!(page_size.is_power_of_two() && page_size >= 4, "invalid page size {page_size}");
+        let mut pages = self
+            .inputs
+            .iter()
+            .flat_map(|input| {
+                input.read_words().map(|word| (input.ptr + word * 4) & !(page_size - 1))
+            })
+            .collect::<Vec<_>>();
+        pages.sort_unstable();
+        pages.dedup();
+        pages
+    }
+
+    /// The total number of bytes of the inputs, and the number of those that were read.
+    #[must_use]
+    pub fn read_bytes(&self) -> (u64, u64) {
+        self.inputs.iter().fold((0, 0), |(total, read), input| {
+            let input_read =
+                input.read_ranges().iter().map(|r| u64::from(r.end - r.start)).sum::<u64>();
+            (total + u64::from(input.len), read + input_read)
+        })
+    }
+}
+
+impl InputReads {
+    /// Whether any word of the input was read.
+    #[must_use]
+    pub fn is_read(&self) -> bool {
+        self.read_words.iter().any(|bits| *bits != 0)
+    }
+
+    /// Whether the word at `offset` words from the start of the input was read.
+    #[must_use]
+    pub fn is_word_read(&self, offset: u32) -> bool {
+        let offset = offset as usize;
+        self.read_words.get(offset / 64).is_some_and(|bits| bits & (1 << (offset % 64)) != 0)
+    }
+
+    /// The offsets of the words of the input that were read, in words.
+    pub fn read_words(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..self.len.div_ceil(4)).filter(|offset| self.is_word_read(*offset))
+    }
+
+    /// The ranges of bytes of the input that were read, merged and sorted.
+    #[must_use]
+    pub fn read_ranges(&self) -> Vec<Range<u32>> {
+        let mut ranges: Vec<Range<u32>> = Vec::new();
+        for word in self.read_words() {
+            let start = word * 4;
+            let end = (start + 4).min(self.len);
+            match ranges.last_mut() {
+                Some(last) if last.end == start => last.end = end,
+                _ => ranges.push(start..end),
+            }
+        }
+        ranges
+    }
+
+    /// Marks the word at `addr` as read, if it belongs to the input.
+    pub(crate) fn mark(&mut self, addr: u32) {
+        if (self.ptr..self.ptr + self.len).contains(&addr) {
+            let offset = ((addr - self.ptr) / 4) as usize;
+            self.read_words[offset / 64] |= 1 << (offset % 64);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_reads() {
+        let mut journal = InputJournal::default();
+        journal.push(0x1000, 10);
+        journal.push(0x2000, 300);
+        journal.push(0x3000, 4);
+
+        journal.inputs[0].mark(0x1004);
+        journal.inputs[0].mark(0x1008);
+        journal.inputs[1].mark(0x2000);
+        journal.inputs[1].mark(0x2100);
+        // Out of the input.
+        journal.inputs[1].mark(0x3000);
+
+        assert_eq!(journal.inputs[0].read_ranges(), vec![4..10]);
+        assert_eq!(journal.inputs[1].read_ranges(), vec![0..4, 256..260]);
+        assert!(journal.inputs[1].is_word_read(64));
+        assert!(!journal.inputs[1].is_word_read(63));
+        assert_eq!(journal.unread_inputs().collect::<Vec<_>>(), vec![2]);
+        assert_eq!(journal.read_pages(0x100), vec![0x1000, 0x2000, 0x2100]);
+        assert_eq!(journal.read_bytes(), (314, 14));
+    }
+}
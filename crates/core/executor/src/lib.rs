@@ -31,6 +31,7 @@ mod executor;
 mod hook;
 mod instruction;
 mod io;
+mod journal;
 mod memory;
 mod opcode;
 #[cfg(feature = "profiling")]
@@ -50,6 +51,7 @@ pub use cost::*;
 pub use executor::*;
 pub use hook::*;
 pub use instruction::*;
+pub use journal::*;
 pub use opcode::*;
 pub use program::*;
 pub use record::*;
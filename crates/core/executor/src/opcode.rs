@@ -102,6 +102,40 @@ pub enum Opcode {
     EBREAK = 36,
     /// Unimplemented instruction.
     UNIMP = 37,
+    /// rd ← rs1 & ~rs2, pc ← pc + 4
+    ANDN = 38,
+    /// rd ← rs1 | ~rs2, pc ← pc + 4
+    ORN = 39,
+    /// rd ← ~(rs1 ^ rs2), pc ← pc + 4
+    XNOR = 40,
+    /// rd ← number of leading zero bits of rs1, pc ← pc + 4
+    CLZ = 41,
+    /// rd ← number of trailing zero bits of rs1, pc ← pc + 4
+    CTZ = 42,
+    /// rd ← number of set bits of rs1, pc ← pc + 4
+    CPOP = 43,
+    /// rd ← max(rs1, rs2) (signed), pc ← pc + 4
+    MAX = 44,
+    /// rd ← max(rs1, rs2) (unsigned), pc ← pc + 4
+    MAXU = 45,
+    /// rd ← min(rs1, rs2) (signed), pc ← pc + 4
+    MIN = 46,
+    /// rd ← min(rs1, rs2) (unsigned), pc ← pc + 4
+    MINU = 47,
+    /// rd ← sx(rs1[7:0]), pc ← pc + 4
+    SEXTB = 48,
+    /// rd ← sx(rs1[15:0]), pc ← pc + 4
+    SEXTH = 49,
+    /// rd ← zx(rs1[15:0]), pc ← pc + 4
+    ZEXTH = 50,
+    /// rd ← rs1 rotated left by rs2[4:0], pc ← pc + 4
+    ROL = 51,
+    /// rd ← rs1 rotated right by rs2[4:0], pc ← pc + 4
+    ROR = 52,
+    /// rd ← each byte of rs1 set to 0xff if it is non-zero, pc ← pc + 4
+    ORCB = 53,
+    /// rd ← rs1 with its bytes reversed, pc ← pc + 4
+    REV8 = 54,
 }
 /// Byte Opcode.
 ///
@@ -129,6 +163,9 @@ pub enum ByteOpcode {
     MSB = 7,
     /// Unsigned 16-bit Range Check.
     U16Range = 8,
+    /// AES S-box substitution.
+    #[cfg(feature = "aes-precompile")]
+    AesSbox = 9,
 }
 
 impl Opcode {
@@ -174,6 +211,23 @@ impl Opcode {
             Opcode::REM => "rem",
             Opcode::REMU => "remu",
             Opcode::UNIMP => "unimp",
+            Opcode::ANDN => "andn",
+            Opcode::ORN => "orn",
+            Opcode::XNOR => "xnor",
+            Opcode::CLZ => "clz",
+            Opcode::CTZ => "ctz",
+            Opcode::CPOP => "cpop",
+            Opcode::MAX => "max",
+            Opcode::MAXU => "maxu",
+            Opcode::MIN => "min",
+            Opcode::MINU => "minu",
+            Opcode::SEXTB => "sext.b",
+            Opcode::SEXTH => "sext.h",
+            Opcode::ZEXTH => "zext.h",
+            Opcode::ROL => "rol",
+            Opcode::ROR => "ror",
+            Opcode::ORCB => "orc.b",
+            Opcode::REV8 => "rev8",
         }
     }
 
@@ -8,6 +8,7 @@ use crate::{
     RiscvAirId,
 };
 use hashbrown::HashMap;
+use monerochan_primitives::consts::STACK_GUARD_SIZE;
 use p3_field::{AbstractExtensionField, Field, PrimeField32};
 use p3_maybe_rayon::prelude::{IntoParallelIterator, ParallelBridge, ParallelIterator};
 use serde::{Deserialize, Serialize};
@@ -36,6 +37,25 @@ pub struct Program {
     pub memory_image: HashMap<u32, u32>,
     /// The shape for the preprocessed tables.
     pub preprocessed_shape: Option<Shape<RiscvAirId>>,
+    /// The function symbols of the ELF, sorted by address. Only used for diagnostics.
+    #[serde(default)]
+    pub symbols: Vec<Symbol>,
+    /// The start of the stack guard region of the ELF, if it places its stack after the program.
+    ///
+    /// Accesses to the [`STACK_GUARD_SIZE`] bytes from this address trap as a stack overflow.
+    #[serde(default)]
+    pub stack_guard: Option<u32>,
+}
+
+/// A function symbol of an ELF.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Symbol {
+    /// The name of the function, as it appears in the symbol table.
+    pub name: String,
+    /// The address of the function.
+    pub address: u32,
+    /// The size of the function, in bytes.
+    pub size: u32,
 }
 
 impl Program {
@@ -48,6 +68,8 @@ impl Program {
             pc_base,
             memory_image: HashMap::new(),
             preprocessed_shape: None,
+            symbols: Vec::new(),
+            stack_guard: None,
         }
     }
 
@@ -70,6 +92,8 @@ impl Program {
             pc_base: elf.pc_base,
             memory_image: elf.memory_image,
             preprocessed_shape: None,
+            symbols: elf.symbols,
+            stack_guard: elf.stack_guard,
         })
     }
 
@@ -94,6 +118,22 @@ impl Program {
         })
     }
 
+    /// Locate `pc` relative to the nearest function symbol at or before it, as `name+0xoffset`.
+    ///
+    /// Returns `None` if the ELF has no symbol at or before `pc`.
+    #[must_use]
+    pub fn symbolize(&self, pc: u32) -> Option<String> {
+        let index = self.symbols.partition_point(|sym| sym.address <= pc);
+        let sym = self.symbols[..index].last()?;
+        Some(format!("{}+{:#x}", sym.name, pc - sym.address))
+    }
+
+    /// Whether `addr` is in the stack guard region, see [`Program::stack_guard`].
+    #[must_use]
+    pub fn in_stack_guard(&self, addr: u32) -> bool {
+        self.stack_guard.is_some_and(|guard| addr.wrapping_sub(guard) < STACK_GUARD_SIZE)
+    }
+
     #[must_use]
     /// Fetch the instruction at the given program counter.
     pub fn fetch(&self, pc: u32) -> &Instruction {
@@ -47,6 +47,8 @@ pub struct ExecutionRecord {
     pub divrem_events: Vec<AluEvent>,
     /// A trace of the SLT, SLTI, SLTU, and SLTIU events.
     pub lt_events: Vec<AluEvent>,
+    /// A trace of the bit manipulation events, such as CLZ, ROL and MAX.
+    pub bitmanip_events: Vec<AluEvent>,
     /// A trace of the memory instructions.
     pub memory_instr_events: Vec<MemInstrEvent>,
     /// A trace of the AUIPC events.
@@ -278,6 +280,7 @@ impl MachineRecord for ExecutionRecord {
         stats.insert("shift_right_events".to_string(), self.shift_right_events.len());
         stats.insert("divrem_events".to_string(), self.divrem_events.len());
         stats.insert("lt_events".to_string(), self.lt_events.len());
+        stats.insert("bitmanip_events".to_string(), self.bitmanip_events.len());
         stats.insert("memory_instructions_events".to_string(), self.memory_instr_events.len());
         stats.insert("branch_events".to_string(), self.branch_events.len());
         stats.insert("jump_events".to_string(), self.jump_events.len());
@@ -314,6 +317,7 @@ impl MachineRecord for ExecutionRecord {
         self.shift_right_events.append(&mut other.shift_right_events);
         self.divrem_events.append(&mut other.divrem_events);
         self.lt_events.append(&mut other.lt_events);
+        self.bitmanip_events.append(&mut other.bitmanip_events);
         self.memory_instr_events.append(&mut other.memory_instr_events);
         self.branch_events.append(&mut other.branch_events);
         self.jump_events.append(&mut other.jump_events);
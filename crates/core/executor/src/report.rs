@@ -5,11 +5,13 @@ use std::{
 
 use enum_map::{EnumArray, EnumMap};
 use hashbrown::HashMap;
+use monerochan_primitives::consts::PANIC_EXIT_CODE;
+use serde::{Deserialize, Serialize};
 
-use crate::{events::generate_execution_report, syscalls::SyscallCode, Opcode};
+use crate::{events::generate_execution_report, syscalls::SyscallCode, InputJournal, Opcode};
 
 /// An execution report.
-#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ExecutionReport {
     /// The opcode counts.
     pub opcode_counts: Box<EnumMap<Opcode, u64>>,
@@ -23,6 +25,46 @@ pub struct ExecutionReport {
     pub touched_memory_addresses: u64,
     /// The gas, if it was calculated.
     pub gas: Option<u64>,
+    /// The journal of the reads of the inputs, if it was enabled.
+    pub input_reads: Option<InputJournal>,
+    /// The output of the program, if it was captured.
+    pub output: Option<CapturedOutput>,
+    /// The exit code of the program. Only non-zero if the execution was allowed to complete with
+    /// a non-zero exit code, see `MONEROCHANContextBuilder::report_exit_code`.
+    pub exit_code: u32,
+}
+
+/// The `stdout` and `stderr` of a program, captured during its execution.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapturedOutput {
+    /// The captured `stdout`.
+    pub stdout: CapturedStream,
+    /// The captured `stderr`.
+    pub stderr: CapturedStream,
+}
+
+/// An output stream of a program, captured up to a maximum size.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapturedStream {
+    /// The captured lines, each terminated by a newline.
+    pub text: String,
+    /// Whether lines were dropped because the maximum size was reached.
+    pub truncated: bool,
+}
+
+impl CapturedStream {
+    /// Appends a line, unless the stream would exceed `max_size` bytes.
+    ///
+    /// Once a line is dropped, the following ones are dropped too, so that the captured text is
+    /// a prefix of the output.
+    pub(crate) fn push(&mut self, line: &str, max_size: usize) {
+        if self.truncated || self.text.len() + line.len() + 1 > max_size {
+            self.truncated = true;
+            return;
+        }
+        self.text.push_str(line);
+        self.text.push('\n');
+    }
 }
 
 impl ExecutionReport {
@@ -37,6 +79,170 @@ impl ExecutionReport {
     pub fn total_syscall_count(&self) -> u64 {
         self.syscall_counts.values().sum()
     }
+
+    /// Compare this report, taken as the baseline, with another report.
+    ///
+    /// Only the opcodes, syscalls and cycle tracker labels whose counts differ are listed in the
+    /// diff, sorted by decreasing absolute change.
+    #[must_use]
+    pub fn diff(&self, other: &ExecutionReport) -> ExecutionReportDiff {
+        let mut cycle_tracker = self
+            .cycle_tracker
+            .keys()
+            .chain(other.cycle_tracker.keys().filter(|k| !self.cycle_tracker.contains_key(*k)))
+            .map(|label| {
+                let before = self.cycle_tracker.get(label).copied().unwrap_or_default();
+                let after = other.cycle_tracker.get(label).copied().unwrap_or_default();
+                (label.clone(), CountDiff { before, after })
+            })
+            .filter(|(_, diff)| diff.is_changed())
+            .collect::<Vec<_>>();
+        cycle_tracker.sort_by(|(a, x), (b, y)| {
+            y.delta().unsigned_abs().cmp(&x.delta().unsigned_abs()).then_with(|| a.cmp(b))
+        });
+
+        ExecutionReportDiff {
+            total_instructions: CountDiff {
+                before: self.total_instruction_count(),
+                after: other.total_instruction_count(),
+            },
+            total_syscalls: CountDiff {
+                before: self.total_syscall_count(),
+                after: other.total_syscall_count(),
+            },
+            touched_memory_addresses: CountDiff {
+                before: self.touched_memory_addresses,
+                after: other.touched_memory_addresses,
+            },
+            gas: self.gas.zip(other.gas).map(|(before, after)| CountDiff { before, after }),
+            opcode_counts: counts_diff(&self.opcode_counts, &other.opcode_counts),
+            syscall_counts: counts_diff(&self.syscall_counts, &other.syscall_counts),
+            cycle_tracker,
+        }
+    }
+}
+
+/// The change of a count between two execution reports.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CountDiff {
+    /// The count in the baseline report.
+    pub before: u64,
+    /// The count in the compared report.
+    pub after: u64,
+}
+
+impl CountDiff {
+    /// The signed change of the count.
+    #[must_use]
+    pub fn delta(&self) -> i128 {
+        i128::from(self.after) - i128::from(self.before)
+    }
+
+    /// The change of the count relative to the baseline, in percent.
+    ///
+    /// Returns `None` if the baseline count is zero.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn percent(&self) -> Option<f64> {
+        (self.before != 0).then(|| self.delta() as f64 * 100.0 / self.before as f64)
+    }
+
+    /// Whether the count changed.
+    #[must_use]
+    pub fn is_changed(&self) -> bool {
+        self.before != self.after
+    }
+}
+
+impl Display for CountDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} -> {} ({:+}", self.before, self.after, self.delta())?;
+        if let Some(percent) = self.percent() {
+            write!(f, ", {percent:+.2}%")?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// A comparison of two execution reports, see [`ExecutionReport::diff`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionReportDiff {
+    /// The total number of instructions.
+    pub total_instructions: CountDiff,
+    /// The total number of syscalls.
+    pub total_syscalls: CountDiff,
+    /// The number of unique memory addresses touched.
+    pub touched_memory_addresses: CountDiff,
+    /// The gas, if it was calculated for both reports.
+    pub gas: Option<CountDiff>,
+    /// The opcodes whose counts changed.
+    pub opcode_counts: Vec<(Opcode, CountDiff)>,
+    /// The syscalls whose counts changed.
+    pub syscall_counts: Vec<(SyscallCode, CountDiff)>,
+    /// The cycle tracker labels whose counts changed.
+    pub cycle_tracker: Vec<(String, CountDiff)>,
+}
+
+impl ExecutionReportDiff {
+    /// Whether any count changed between the two reports.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        !self.total_instructions.is_changed() &&
+            !self.total_syscalls.is_changed() &&
+            !self.touched_memory_addresses.is_changed() &&
+            !self.gas.is_some_and(|gas| gas.is_changed()) &&
+            self.opcode_counts.is_empty() &&
+            self.syscall_counts.is_empty() &&
+            self.cycle_tracker.is_empty()
+    }
+}
+
+impl Display for ExecutionReportDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if let Some(gas) = self.gas {
+            writeln!(f, "gas: {gas}")?;
+        }
+        writeln!(f, "total instructions: {}", self.total_instructions)?;
+        writeln!(f, "total syscalls: {}", self.total_syscalls)?;
+        writeln!(f, "touched memory addresses: {}", self.touched_memory_addresses)?;
+
+        if !self.opcode_counts.is_empty() {
+            writeln!(f, "opcode counts:")?;
+            for (opcode, diff) in &self.opcode_counts {
+                writeln!(f, "  {opcode}: {diff}")?;
+            }
+        }
+        if !self.syscall_counts.is_empty() {
+            writeln!(f, "syscall counts:")?;
+            for (syscall, diff) in &self.syscall_counts {
+                writeln!(f, "  {syscall}: {diff}")?;
+            }
+        }
+        if !self.cycle_tracker.is_empty() {
+            writeln!(f, "cycle tracker:")?;
+            for (label, diff) in &self.cycle_tracker {
+                writeln!(f, "  {label}: {diff}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Lists the keys whose counts differ between two maps, sorted by decreasing absolute change.
+fn counts_diff<K>(before: &EnumMap<K, u64>, after: &EnumMap<K, u64>) -> Vec<(K, CountDiff)>
+where
+    K: EnumArray<u64> + Ord + Copy,
+{
+    let mut diffs = before
+        .iter()
+        .map(|(k, before)| (k, CountDiff { before: *before, after: after[k] }))
+        .filter(|(_, diff)| diff.is_changed())
+        .collect::<Vec<_>>();
+    diffs.sort_by(|(a, x), (b, y)| {
+        y.delta().unsigned_abs().cmp(&x.delta().unsigned_abs()).then_with(|| a.cmp(b))
+    });
+    diffs
 }
 
 /// Combines two `HashMap`s together. If a key is in both maps, the values are added together.
@@ -50,6 +256,15 @@ where
     }
 }
 
+/// Describes a non-zero exit code, telling a panic from an exit code chosen by the program.
+pub(crate) fn describe_exit_code(exit_code: u32) -> String {
+    if exit_code == u32::from(PANIC_EXIT_CODE) {
+        format!("{exit_code} (the program panicked)")
+    } else {
+        format!("{exit_code} (set by the program)")
+    }
+}
+
 impl AddAssign for ExecutionReport {
     fn add_assign(&mut self, rhs: Self) {
         counts_add_assign(&mut self.opcode_counts, *rhs.opcode_counts);
@@ -69,9 +284,16 @@ impl Add for ExecutionReport {
 
 impl Display for ExecutionReport {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if self.exit_code != 0 {
+            writeln!(f, "exit code: {}", describe_exit_code(self.exit_code))?;
+        }
         if let Some(gas) = self.gas {
             writeln!(f, "gas: {gas}")?;
         }
+        if let Some(journal) = &self.input_reads {
+            let (total, read) = journal.read_bytes();
+            writeln!(f, "input bytes read: {read} of {total}")?;
+        }
         writeln!(f, "opcode counts ({} total instructions):", self.total_instruction_count())?;
         for line in generate_execution_report(self.opcode_counts.as_ref()) {
             writeln!(f, "  {line}")?;
@@ -85,3 +307,55 @@ impl Display for ExecutionReport {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_diff() {
+        let mut before = ExecutionReport::default();
+        before.opcode_counts[Opcode::ADD] = 100;
+        before.opcode_counts[Opcode::MUL] = 10;
+        before.syscall_counts[SyscallCode::SHA_EXTEND] = 2;
+        before.cycle_tracker.insert("verify".to_string(), 500);
+
+        let mut after = before.clone();
+        after.opcode_counts[Opcode::ADD] = 150;
+        after.opcode_counts[Opcode::SUB] = 5;
+        after.cycle_tracker.insert("verify".to_string(), 400);
+        after.cycle_tracker.insert("hash".to_string(), 50);
+
+        let diff = before.diff(&after);
+        assert_eq!(diff.total_instructions, CountDiff { before: 110, after: 165 });
+        assert!(!diff.total_syscalls.is_changed());
+        assert_eq!(
+            diff.opcode_counts,
+            vec![
+                (Opcode::ADD, CountDiff { before: 100, after: 150 }),
+                (Opcode::SUB, CountDiff { before: 0, after: 5 })
+            ]
+        );
+        assert!(diff.syscall_counts.is_empty());
+        assert_eq!(
+            diff.cycle_tracker,
+            vec![
+                ("verify".to_string(), CountDiff { before: 500, after: 400 }),
+                ("hash".to_string(), CountDiff { before: 0, after: 50 })
+            ]
+        );
+        assert_eq!(diff.opcode_counts[0].1.percent(), Some(50.0));
+        assert_eq!(diff.opcode_counts[1].1.percent(), None);
+
+        assert!(before.diff(&before).is_empty());
+    }
+
+    #[test]
+    fn test_report_json_roundtrip() {
+        let mut report = ExecutionReport::default();
+        report.opcode_counts[Opcode::ADD] = 7;
+        report.gas = Some(42);
+        let json = serde_json::to_string(&report).unwrap();
+        assert_eq!(serde_json::from_str::<ExecutionReport>(&json).unwrap(), report);
+    }
+}
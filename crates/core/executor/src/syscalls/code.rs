@@ -156,6 +156,18 @@ pub enum SyscallCode {
 
     /// Executes the `SECP256R1_DECOMPRESS` precompile.
     SECP256R1_DECOMPRESS = 0x00_00_01_2E,
+
+    /// Executes the `AES_ENCRYPT_ROUND` precompile.
+    AES_ENCRYPT_ROUND = 0x00_01_01_30,
+
+    /// Executes the `F64_ADD` precompile.
+    F64_ADD = 0x00_01_01_31,
+
+    /// Executes the `F64_MUL` precompile.
+    F64_MUL = 0x00_01_01_32,
+
+    /// Executes the `F64_DIV` precompile.
+    F64_DIV = 0x00_01_01_33,
 }
 
 impl SyscallCode {
@@ -202,6 +214,10 @@ impl SyscallCode {
             0x00_01_01_2C => SyscallCode::SECP256R1_ADD,
             0x00_00_01_2D => SyscallCode::SECP256R1_DOUBLE,
             0x00_00_01_2E => SyscallCode::SECP256R1_DECOMPRESS,
+            0x00_01_01_30 => SyscallCode::AES_ENCRYPT_ROUND,
+            0x00_01_01_31 => SyscallCode::F64_ADD,
+            0x00_01_01_32 => SyscallCode::F64_MUL,
+            0x00_01_01_33 => SyscallCode::F64_DIV,
             _ => panic!("invalid syscall number: {value}"),
         }
     }
@@ -235,6 +251,8 @@ impl SyscallCode {
             SyscallCode::BLS12381_FP_SUB => SyscallCode::BLS12381_FP_ADD,
             SyscallCode::BLS12381_FP_MUL => SyscallCode::BLS12381_FP_ADD,
             SyscallCode::BLS12381_FP2_SUB => SyscallCode::BLS12381_FP2_ADD,
+            SyscallCode::F64_MUL => SyscallCode::F64_ADD,
+            SyscallCode::F64_DIV => SyscallCode::F64_ADD,
             _ => *self,
         }
     }
@@ -275,6 +293,10 @@ impl SyscallCode {
             SyscallCode::SECP256R1_ADD => RiscvAirId::Secp256r1AddAssign,
             SyscallCode::SECP256R1_DOUBLE => RiscvAirId::Secp256r1DoubleAssign,
             SyscallCode::SECP256R1_DECOMPRESS => RiscvAirId::Secp256r1Decompress,
+            SyscallCode::AES_ENCRYPT_ROUND => RiscvAirId::AesRound,
+            SyscallCode::F64_ADD | SyscallCode::F64_MUL | SyscallCode::F64_DIV => {
+                RiscvAirId::F64OpAssign
+            }
             SyscallCode::HALT |
             SyscallCode::WRITE |
             SyscallCode::ENTER_UNCONSTRAINED |
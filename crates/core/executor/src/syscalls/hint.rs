@@ -29,6 +29,7 @@ impl Syscall for HintReadSyscall {
         assert!(!ctx.rt.unconstrained, "hint read should not be used in a unconstrained block");
         assert_eq!(vec.len() as u32, len, "hint input stream read length mismatch");
         assert_eq!(ptr % 4, 0, "hint read address not aligned to 4 bytes");
+        ctx.rt.journal_input(ptr, len);
         // Iterate through the vec in 4-byte chunks
         for i in (0..len).step_by(4) {
             // Get each byte in the chunk
@@ -21,6 +21,10 @@ use hashbrown::HashMap;
 pub use code::*;
 pub use context::*;
 use hint::{HintLenSyscall, HintReadSyscall};
+#[cfg(feature = "aes-precompile")]
+use precompiles::aes::AesEncryptRoundSyscall;
+#[cfg(feature = "f64-precompile")]
+use precompiles::float::F64OpSyscall;
 use precompiles::{
     edwards::{add::EdwardsAddAssignSyscall, decompress::EdwardsDecompressSyscall},
     fptower::{Fp2AddSubSyscall, Fp2MulSyscall, FpOpSyscall},
@@ -47,6 +51,8 @@ use unconstrained::{EnterUnconstrainedSyscall, ExitUnconstrainedSyscall};
 use verify::VerifySyscall;
 use write::WriteSyscall;
 
+#[cfg(feature = "f64-precompile")]
+use crate::events::F64Operation;
 use crate::events::FieldOperation;
 
 /// A system call in the MONEROCHAN RISC-V zkVM.
@@ -148,6 +154,18 @@ pub fn default_syscall_map() -> HashMap<SyscallCode, Arc<dyn Syscall>> {
 
     syscall_map.insert(SyscallCode::U256XU2048_MUL, Arc::new(U256xU2048MulSyscall));
 
+    #[cfg(feature = "aes-precompile")]
+    syscall_map.insert(SyscallCode::AES_ENCRYPT_ROUND, Arc::new(AesEncryptRoundSyscall));
+
+    #[cfg(feature = "f64-precompile")]
+    {
+        syscall_map.insert(SyscallCode::F64_ADD, Arc::new(F64OpSyscall::new(F64Operation::Add)));
+
+        syscall_map.insert(SyscallCode::F64_MUL, Arc::new(F64OpSyscall::new(F64Operation::Mul)));
+
+        syscall_map.insert(SyscallCode::F64_DIV, Arc::new(F64OpSyscall::new(F64Operation::Div)));
+    }
+
     syscall_map.insert(
         SyscallCode::BLS12381_FP_ADD,
         Arc::new(FpOpSyscall::<Bls12381BaseField>::new(FieldOperation::Add)),
@@ -0,0 +1,88 @@
+use crate::{
+    events::{AesRoundEvent, PrecompileEvent, AES_SBOX},
+    syscalls::{Syscall, SyscallCode, SyscallContext},
+};
+
+pub(crate) struct AesEncryptRoundSyscall;
+
+impl Syscall for AesEncryptRoundSyscall {
+    fn execute(
+        &self,
+        rt: &mut SyscallContext,
+        syscall_code: SyscallCode,
+        arg1: u32,
+        arg2: u32,
+    ) -> Option<u32> {
+        let clk = rt.clk;
+
+        let state_ptr = arg1;
+        if !state_ptr.is_multiple_of(4) {
+            panic!();
+        }
+        let round_key_ptr = arg2;
+        if !round_key_ptr.is_multiple_of(4) {
+            panic!();
+        }
+
+        // Read the state. We can read a slice_unsafe here because we write the output to the state
+        // later.
+        let state: [u32; 4] = rt.slice_unsafe(state_ptr, 4).try_into().unwrap();
+
+        // Read the round key.
+        let (round_key_read_records, round_key) = rt.mr_slice(round_key_ptr, 4);
+        let round_key: [u32; 4] = round_key.try_into().unwrap();
+
+        let output = aes_encrypt_round(state, round_key);
+
+        // Increment clk so that the write is not at the same cycle as the read.
+        rt.clk += 1;
+        // Write the output to the state and keep track of the memory records.
+        let state_write_records = rt.mw_slice(state_ptr, &output);
+
+        let shard = rt.current_shard();
+        let event = PrecompileEvent::AesRound(AesRoundEvent {
+            shard,
+            clk,
+            state_ptr,
+            state,
+            round_key_ptr,
+            round_key,
+            state_write_records: state_write_records.try_into().unwrap(),
+            round_key_read_records: round_key_read_records.try_into().unwrap(),
+            local_mem_access: rt.postprocess(),
+        });
+        let syscall_event =
+            rt.rt.syscall_event(clk, None, None, syscall_code, arg1, arg2, rt.next_pc);
+        rt.add_precompile_event(syscall_code, syscall_event, event);
+
+        None
+    }
+
+    fn num_extra_cycles(&self) -> u32 {
+        1
+    }
+}
+
+/// Computes `AddRoundKey(MixColumns(ShiftRows(SubBytes(state))), round_key)`.
+///
+/// The state is stored column by column, with each word holding one column in little endian.
+fn aes_encrypt_round(state: [u32; 4], round_key: [u32; 4]) -> [u32; 4] {
+    let bytes = state.map(u32::to_le_bytes);
+
+    core::array::from_fn(|c| {
+        // SubBytes and ShiftRows: row `r` of the state is rotated left by `r` columns.
+        let t: [u8; 4] = core::array::from_fn(|r| AES_SBOX[bytes[(c + r) % 4][r] as usize]);
+        let x = t.map(xtime);
+
+        // MixColumns, with `3 * t = xtime(t) ^ t`.
+        let column: [u8; 4] = core::array::from_fn(|r| {
+            x[r] ^ x[(r + 1) % 4] ^ t[(r + 1) % 4] ^ t[(r + 2) % 4] ^ t[(r + 3) % 4]
+        });
+        u32::from_le_bytes(column) ^ round_key[c]
+    })
+}
+
+/// Multiplies a byte by `x` in the AES field GF(2^8).
+fn xtime(b: u8) -> u8 {
+    (b << 1) ^ if b & 0x80 != 0 { 0x1b } else { 0 }
+}
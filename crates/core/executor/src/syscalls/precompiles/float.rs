@@ -0,0 +1,105 @@
+use crate::{
+    events::{F64OpEvent, F64Operation, PrecompileEvent},
+    syscalls::{Syscall, SyscallCode, SyscallContext},
+};
+
+pub(crate) struct F64OpSyscall {
+    op: F64Operation,
+}
+
+impl F64OpSyscall {
+    pub const fn new(op: F64Operation) -> Self {
+        Self { op }
+    }
+}
+
+impl Syscall for F64OpSyscall {
+    fn execute(
+        &self,
+        rt: &mut SyscallContext,
+        syscall_code: SyscallCode,
+        arg1: u32,
+        arg2: u32,
+    ) -> Option<u32> {
+        let clk = rt.clk;
+        let x_ptr = arg1;
+        if !x_ptr.is_multiple_of(4) {
+            panic!();
+        }
+        let y_ptr = arg2;
+        if !y_ptr.is_multiple_of(4) {
+            panic!();
+        }
+
+        let x: [u32; 2] = rt.slice_unsafe(x_ptr, 2).try_into().unwrap();
+        let (y_memory_records, y) = rt.mr_slice(y_ptr, 2);
+        let y: [u32; 2] = y.try_into().unwrap();
+
+        let result = f64_op(self.op, words_to_u64(x), words_to_u64(y));
+
+        rt.clk += 1;
+        let x_memory_records = rt.mw_slice(x_ptr, &[result as u32, (result >> 32) as u32]);
+
+        let shard = rt.current_shard();
+        let event = PrecompileEvent::F64Op(F64OpEvent {
+            shard,
+            clk,
+            op: self.op,
+            x_ptr,
+            x,
+            y_ptr,
+            y,
+            x_memory_records: x_memory_records.try_into().unwrap(),
+            y_memory_records: y_memory_records.try_into().unwrap(),
+            local_mem_access: rt.postprocess(),
+        });
+
+        // All the f64 operations are proven in the same table, so their events are grouped under
+        // the addition syscall code to preserve their ordering.
+        let syscall_event =
+            rt.rt.syscall_event(clk, None, None, syscall_code, arg1, arg2, rt.next_pc);
+        rt.add_precompile_event(SyscallCode::F64_ADD, syscall_event, event);
+
+        None
+    }
+
+    fn num_extra_cycles(&self) -> u32 {
+        1
+    }
+}
+
+/// Computes an f64 operation, or returns the all-zero sentinel if the operation is not
+/// accelerated.
+///
+/// The operation is accelerated when both operands are normal numbers and the result is a normal
+/// number above the lowest binade, whose values may have been rounded as subnormals. The result
+/// is then computed with the host IEEE 754 binary64 arithmetic, which rounds to nearest with ties
+/// to even on every platform, so the result does not depend on the machine running the executor.
+///
+/// Zeros, subnormals, infinities, NaNs, overflows and underflows all return the sentinel, and are
+/// computed in software by the guest. The sentinel is the bit pattern of `+0.0`, which is never an
+/// accelerated result.
+fn f64_op(op: F64Operation, x: u64, y: u64) -> u64 {
+    let (a, b) = (f64::from_bits(x), f64::from_bits(y));
+    if !a.is_normal() || !b.is_normal() {
+        return 0;
+    }
+
+    let result = match op {
+        F64Operation::Add => a + b,
+        F64Operation::Mul => a * b,
+        F64Operation::Div => a / b,
+    }
+    .to_bits();
+
+    let exponent = (result >> 52) & 0x7ff;
+    if (2..=2046).contains(&exponent) {
+        result
+    } else {
+        0
+    }
+}
+
+fn words_to_u64(words: [u32; 2]) -> u64 {
+    u64::from(words[0]) | (u64::from(words[1]) << 32)
+}
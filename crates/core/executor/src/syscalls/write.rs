@@ -15,10 +15,12 @@ impl Syscall for WriteSyscall {
     /// If stdout (fd = 1):
     /// - If the stream is a cycle tracker, either log the cycle tracker or accumulate it in the
     ///   report.
-    /// - Else, print the stream to stdout.
+    /// - Else, output the stream to stdout.
     ///
     /// If stderr (fd = 2):
-    /// - Print the stream to stderr.
+    /// - Output the stream to stderr.
+    ///
+    /// The output is handled as configured by the [`crate::IoOptions`].
     ///
     /// If fd = 3:
     /// - Update the public value stream.
@@ -50,26 +52,10 @@ impl Syscall for WriteSyscall {
             match parse_cycle_tracker_command(s) {
                 Some(command) => handle_cycle_tracker_command(rt, command),
                 None => {
-                    // If the string does not match any known command, print it to stdout.
+                    // If the string does not match any known command, output it to stdout.
                     let flush_s = update_io_buf(rt, fd, s);
-
                     if !flush_s.is_empty() {
-                        match rt.io_options.stdout {
-                            Some(ref mut writer) => {
-                                flush_s.into_iter().for_each(|mut line| {
-                                    line.push('\n');
-
-                                    if let Err(e) = writer.write_all(line.as_bytes()) {
-                                        tracing::error!(
-                                            "failed to write to stdout io override: {e}"
-                                        );
-                                    }
-                                });
-                            }
-                            None => {
-                                flush_s.into_iter().for_each(|line| eprintln!("stdout: {line}"));
-                            }
-                        }
+                        rt.output_lines(fd, flush_s);
                     }
                 }
             }
@@ -77,20 +63,7 @@ impl Syscall for WriteSyscall {
             let s = core::str::from_utf8(slice).unwrap();
             let flush_s = update_io_buf(rt, fd, s);
             if !flush_s.is_empty() {
-                match rt.io_options.stderr {
-                    Some(ref mut writer) => {
-                        flush_s.into_iter().for_each(|mut line| {
-                            line.push('\n');
-
-                            if let Err(e) = writer.write_all(line.as_bytes()) {
-                                tracing::error!("failed to write to stderr io override: {e}");
-                            }
-                        });
-                    }
-                    None => {
-                        flush_s.into_iter().for_each(|line| eprintln!("stderr: {line}"));
-                    }
-                }
+                rt.output_lines(fd, flush_s);
             }
         } else if fd <= LOWEST_ALLOWED_FD {
             if std::env::var("MONEROCHAN_ALLOW_DEPRECATED_HOOKS")
@@ -46,6 +46,37 @@ pub fn get_quotient_and_remainder(b: u32, c: u32, opcode: Opcode) -> (u32, u32)
     }
 }
 
+/// Calculate the result of the bit manipulation operation `opcode` on `b` and `c`.
+///
+/// # Panics
+///
+/// Panics if `opcode` is not a bit manipulation opcode.
+#[must_use]
+pub fn get_bitmanip_result(opcode: Opcode, b: u32, c: u32) -> u32 {
+    match opcode {
+        Opcode::ANDN => b & !c,
+        Opcode::ORN => b | !c,
+        Opcode::XNOR => !(b ^ c),
+        Opcode::CLZ => b.leading_zeros(),
+        Opcode::CTZ => b.trailing_zeros(),
+        Opcode::CPOP => b.count_ones(),
+        Opcode::MAX => (b as i32).max(c as i32) as u32,
+        Opcode::MAXU => b.max(c),
+        Opcode::MIN => (b as i32).min(c as i32) as u32,
+        Opcode::MINU => b.min(c),
+        Opcode::SEXTB => b as i8 as i32 as u32,
+        Opcode::SEXTH => b as i16 as i32 as u32,
+        Opcode::ZEXTH => b & 0xffff,
+        Opcode::ROL => b.rotate_left(c),
+        Opcode::ROR => b.rotate_right(c),
+        Opcode::ORCB => {
+            u32::from_le_bytes(b.to_le_bytes().map(|byte| if byte == 0 { 0 } else { 0xff }))
+        }
+        Opcode::REV8 => b.swap_bytes(),
+        _ => panic!("Invalid opcode for bit manipulation: {opcode:?}"),
+    }
+}
+
 /// Calculate the most significant bit of the given 32-bit integer `a`, and returns it as a u8.
 #[must_use]
 pub const fn get_msb(a: u32) -> u8 {
@@ -0,0 +1,663 @@
+//! The bit manipulation chip, which implements the instructions of the Zbb extension.
+//!
+//! Every row decomposes `b` into bits, from which the counting, extension and byte permutation
+//! instructions are linear combinations. The remaining instructions are proven with lookups:
+//! - ANDN, ORN and XNOR are byte lookups of AND, OR and XOR with `c` or the result negated.
+//! - ROL and ROR are the byte OR of a left and a right shift of `b`, which are sent to the shift
+//!   chips.
+//! - MIN, MAX, MINU and MAXU select `b` or `c` by a comparison sent to the lt chip.
+
+use core::{
+    borrow::{Borrow, BorrowMut},
+    mem::size_of,
+};
+
+use hashbrown::HashMap;
+use itertools::Itertools;
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_field::{AbstractField, PrimeField, PrimeField32};
+use p3_matrix::{dense::RowMajorMatrix, Matrix};
+use p3_maybe_rayon::prelude::{IntoParallelRefIterator, ParallelIterator, ParallelSlice};
+use monerochan_core_executor::{
+    events::{AluEvent, ByteLookupEvent, ByteRecord},
+    get_bitmanip_result, ByteOpcode, ExecutionRecord, Opcode, Program, DEFAULT_PC_INC, UNUSED_PC,
+};
+use monerochan_derive::AlignedBorrow;
+use monerochan_primitives::consts::{BYTE_SIZE, WORD_SIZE};
+use monerochan_stark::{air::MachineAir, Word};
+
+use crate::{
+    air::{MONEROCHANCoreAirBuilder, WordAirBuilder},
+    operations::IsZeroOperation,
+    utils::pad_rows_fixed,
+};
+
+/// The number of main trace columns for `BitManipChip`.
+pub const NUM_BITMANIP_COLS: usize = size_of::<BitManipCols<u8>>();
+
+/// The number of bits in a word.
+const WORD_BITS: usize = WORD_SIZE * BYTE_SIZE;
+
+/// A chip that implements the bit manipulation opcodes of the Zbb extension.
+#[derive(Default)]
+pub struct BitManipChip;
+
+/// The column layout for the chip.
+#[derive(AlignedBorrow, Default, Clone, Copy)]
+#[repr(C)]
+pub struct BitManipCols<T> {
+    /// The program counter.
+    pub pc: T,
+
+    /// The output operand.
+    pub a: Word<T>,
+
+    /// The first input operand.
+    pub b: Word<T>,
+
+    /// The second input operand.
+    pub c: Word<T>,
+
+    /// Whether the first operand is not register 0.
+    pub op_a_not_0: T,
+
+    /// The result of the operation, which is `a` unless the first operand is register 0.
+    pub result: Word<T>,
+
+    /// The bits of `b`, least significant first.
+    pub b_bits: [T; WORD_BITS],
+
+    /// The bits of the least significant byte of `c`, which hold the rotation amount.
+    pub c_bits: [T; BYTE_SIZE],
+
+    /// Whether the `i + 1` most (for CLZ) or least (for CTZ) significant bits of `b` are zero.
+    pub zeros: [T; WORD_BITS],
+
+    /// Whether each byte of `b` is zero, for ORC.B.
+    pub byte_is_zero: [IsZeroOperation<T>; WORD_SIZE],
+
+    /// Whether `b < c`, for MIN, MAX, MINU and MAXU.
+    pub lt: T,
+
+    /// The shifts of `b` by the rotation amount and by its complement, for ROL and ROR.
+    pub first_shift: Word<T>,
+    pub second_shift: Word<T>,
+
+    /// The second input of the byte lookups for ANDN, ORN and XNOR, which is `~c` for ANDN and
+    /// ORN and `c` for XNOR.
+    pub lookup_c: Word<T>,
+
+    /// The output of the byte lookups for ANDN, ORN and XNOR, which is the result for ANDN and
+    /// ORN and the negated result for XNOR.
+    pub lookup_result: Word<T>,
+
+    /// Flags to indicate the opcode.
+    pub is_andn: T,
+    pub is_orn: T,
+    pub is_xnor: T,
+    pub is_clz: T,
+    pub is_ctz: T,
+    pub is_cpop: T,
+    pub is_max: T,
+    pub is_maxu: T,
+    pub is_min: T,
+    pub is_minu: T,
+    pub is_sextb: T,
+    pub is_sexth: T,
+    pub is_zexth: T,
+    pub is_rol: T,
+    pub is_ror: T,
+    pub is_orcb: T,
+    pub is_rev8: T,
+}
+
+impl<T: Copy> BitManipCols<T> {
+    /// The opcode flags, together with their opcodes.
+    fn opcode_flags(&self) -> [(T, Opcode); 17] {
+        [
+            (self.is_andn, Opcode::ANDN),
+            (self.is_orn, Opcode::ORN),
+            (self.is_xnor, Opcode::XNOR),
+            (self.is_clz, Opcode::CLZ),
+            (self.is_ctz, Opcode::CTZ),
+            (self.is_cpop, Opcode::CPOP),
+            (self.is_max, Opcode::MAX),
+            (self.is_maxu, Opcode::MAXU),
+            (self.is_min, Opcode::MIN),
+            (self.is_minu, Opcode::MINU),
+            (self.is_sextb, Opcode::SEXTB),
+            (self.is_sexth, Opcode::SEXTH),
+            (self.is_zexth, Opcode::ZEXTH),
+            (self.is_rol, Opcode::ROL),
+            (self.is_ror, Opcode::ROR),
+            (self.is_orcb, Opcode::ORCB),
+            (self.is_rev8, Opcode::REV8),
+        ]
+    }
+}
+
+impl<F: PrimeField32> MachineAir<F> for BitManipChip {
+    type Record = ExecutionRecord;
+
+    type Program = Program;
+
+    fn name(&self) -> String {
+        "BitManip".to_string()
+    }
+
+    fn generate_trace(
+        &self,
+        input: &ExecutionRecord,
+        _: &mut ExecutionRecord,
+    ) -> RowMajorMatrix<F> {
+        let mut rows = input
+            .bitmanip_events
+            .par_iter()
+            .map(|event| {
+                let mut row = [F::zero(); NUM_BITMANIP_COLS];
+                let cols: &mut BitManipCols<F> = row.as_mut_slice().borrow_mut();
+                let mut blu = Vec::new();
+                self.event_to_row(event, cols, &mut blu);
+                row
+            })
+            .collect::<Vec<_>>();
+
+        // Pad the trace to a power of two.
+        pad_rows_fixed(
+            &mut rows,
+            || [F::zero(); NUM_BITMANIP_COLS],
+            input.fixed_log2_rows::<F, _>(self),
+        );
+
+        // Convert the trace to a row major matrix.
+        RowMajorMatrix::new(rows.into_iter().flatten().collect::<Vec<_>>(), NUM_BITMANIP_COLS)
+    }
+
+    fn generate_dependencies(&self, input: &Self::Record, output: &mut Self::Record) {
+        let chunk_size = std::cmp::max(input.bitmanip_events.len() / num_cpus::get(), 1);
+
+        let blu_batches = input
+            .bitmanip_events
+            .par_chunks(chunk_size)
+            .map(|events| {
+                let mut blu: HashMap<ByteLookupEvent, usize> = HashMap::new();
+                events.iter().for_each(|event| {
+                    let mut row = [F::zero(); NUM_BITMANIP_COLS];
+                    let cols: &mut BitManipCols<F> = row.as_mut_slice().borrow_mut();
+                    self.event_to_row(event, cols, &mut blu);
+                });
+                blu
+            })
+            .collect::<Vec<_>>();
+
+        output.add_byte_lookup_events_from_maps(blu_batches.iter().collect_vec());
+    }
+
+    fn included(&self, shard: &Self::Record) -> bool {
+        if let Some(shape) = shard.shape.as_ref() {
+            shape.included::<F, _>(self)
+        } else {
+            !shard.bitmanip_events.is_empty()
+        }
+    }
+
+    fn local_only(&self) -> bool {
+        true
+    }
+}
+
+impl BitManipChip {
+    /// Create a row from an event.
+    fn event_to_row<F: PrimeField>(
+        &self,
+        event: &AluEvent,
+        cols: &mut BitManipCols<F>,
+        blu: &mut impl ByteRecord,
+    ) {
+        // The result is computed even if the first operand is register 0, since the lookups are
+        // sent regardless.
+        let result = get_bitmanip_result(event.opcode, event.b, event.c);
+
+        cols.pc = F::from_canonical_u32(event.pc);
+        cols.a = Word::from(event.a);
+        cols.b = Word::from(event.b);
+        cols.c = Word::from(event.c);
+        cols.op_a_not_0 = F::from_bool(!event.op_a_0);
+        cols.result = Word::from(result);
+
+        cols.is_andn = F::from_bool(event.opcode == Opcode::ANDN);
+        cols.is_orn = F::from_bool(event.opcode == Opcode::ORN);
+        cols.is_xnor = F::from_bool(event.opcode == Opcode::XNOR);
+        cols.is_clz = F::from_bool(event.opcode == Opcode::CLZ);
+        cols.is_ctz = F::from_bool(event.opcode == Opcode::CTZ);
+        cols.is_cpop = F::from_bool(event.opcode == Opcode::CPOP);
+        cols.is_max = F::from_bool(event.opcode == Opcode::MAX);
+        cols.is_maxu = F::from_bool(event.opcode == Opcode::MAXU);
+        cols.is_min = F::from_bool(event.opcode == Opcode::MIN);
+        cols.is_minu = F::from_bool(event.opcode == Opcode::MINU);
+        cols.is_sextb = F::from_bool(event.opcode == Opcode::SEXTB);
+        cols.is_sexth = F::from_bool(event.opcode == Opcode::SEXTH);
+        cols.is_zexth = F::from_bool(event.opcode == Opcode::ZEXTH);
+        cols.is_rol = F::from_bool(event.opcode == Opcode::ROL);
+        cols.is_ror = F::from_bool(event.opcode == Opcode::ROR);
+        cols.is_orcb = F::from_bool(event.opcode == Opcode::ORCB);
+        cols.is_rev8 = F::from_bool(event.opcode == Opcode::REV8);
+
+        for i in 0..WORD_BITS {
+            cols.b_bits[i] = F::from_canonical_u32((event.b >> i) & 1);
+        }
+        for i in 0..BYTE_SIZE {
+            cols.c_bits[i] = F::from_canonical_u32((event.c >> i) & 1);
+        }
+
+        let b = event.b.to_le_bytes();
+        let result_bytes = result.to_le_bytes();
+        match event.opcode {
+            Opcode::ANDN | Opcode::ORN | Opcode::XNOR => {
+                let (byte_opcode, lookup_c, lookup_result) = match event.opcode {
+                    Opcode::ANDN => (ByteOpcode::AND, !event.c, result),
+                    Opcode::ORN => (ByteOpcode::OR, !event.c, result),
+                    _ => (ByteOpcode::XOR, event.c, !result),
+                };
+                cols.lookup_c = Word::from(lookup_c);
+                cols.lookup_result = Word::from(lookup_result);
+                for ((b, c), a) in
+                    b.into_iter().zip(lookup_c.to_le_bytes()).zip(lookup_result.to_le_bytes())
+                {
+                    blu.add_byte_lookup_event(ByteLookupEvent {
+                        opcode: byte_opcode,
+                        a1: a as u16,
+                        a2: 0,
+                        b,
+                        c,
+                    });
+                }
+            }
+            Opcode::CLZ | Opcode::CTZ => {
+                for zero in cols.zeros.iter_mut().take(result as usize) {
+                    *zero = F::one();
+                }
+            }
+            Opcode::MAX | Opcode::MIN => {
+                cols.lt = F::from_bool((event.b as i32) < (event.c as i32));
+            }
+            Opcode::MAXU | Opcode::MINU => {
+                cols.lt = F::from_bool(event.b < event.c);
+            }
+            Opcode::ROL | Opcode::ROR => {
+                let shift = event.c & 0x1f;
+                let (first, second) = if event.opcode == Opcode::ROL {
+                    (event.b.wrapping_shl(shift), event.b.wrapping_shr(32 - shift))
+                } else {
+                    (event.b.wrapping_shr(shift), event.b.wrapping_shl(32 - shift))
+                };
+                cols.first_shift = Word::from(first);
+                cols.second_shift = Word::from(second);
+                for ((a, b), c) in
+                    result_bytes.into_iter().zip(first.to_le_bytes()).zip(second.to_le_bytes())
+                {
+                    blu.add_byte_lookup_event(ByteLookupEvent {
+                        opcode: ByteOpcode::OR,
+                        a1: a as u16,
+                        a2: 0,
+                        b,
+                        c,
+                    });
+                }
+            }
+            Opcode::ORCB => {
+                for (is_zero, byte) in cols.byte_is_zero.iter_mut().zip(b) {
+                    is_zero.populate(byte as u32);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<F> BaseAir<F> for BitManipChip {
+    fn width(&self) -> usize {
+        NUM_BITMANIP_COLS
+    }
+}
+
+impl<AB> Air<AB> for BitManipChip
+where
+    AB: MONEROCHANCoreAirBuilder,
+{
+    #[allow(clippy::too_many_lines)]
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+        let local: &BitManipCols<AB::Var> = (*local).borrow();
+
+        let byte_max = || AB::Expr::from_canonical_u8(u8::MAX);
+
+        // SAFETY: All opcode flags are checked to be boolean, and `is_real`, their sum, is boolean.
+        // Therefore every real row has exactly one flag set, and padding rows have none, so that
+        // they send no interactions.
+        let mut is_real = AB::Expr::zero();
+        let mut opcode = AB::Expr::zero();
+        for (flag, flag_opcode) in local.opcode_flags() {
+            builder.assert_bool(flag);
+            is_real = is_real.clone() + flag;
+            opcode = opcode.clone() + flag * flag_opcode.as_field::<AB::F>();
+        }
+        builder.assert_bool(is_real.clone());
+
+        // Decompose `b` into bits, which also checks that `b` is a word of bytes.
+        for (byte, bits) in local.b.0.iter().zip(local.b_bits.chunks_exact(BYTE_SIZE)) {
+            let mut sum = AB::Expr::zero();
+            for (i, bit) in bits.iter().enumerate() {
+                builder.assert_bool(*bit);
+                sum = sum.clone() + *bit * AB::F::from_canonical_u32(1 << i);
+            }
+            builder.assert_eq(sum, *byte);
+        }
+        let bit = |i: usize| local.b_bits[i];
+
+        // ANDN, ORN and XNOR, as byte lookups of AND, OR and XOR.
+        let is_negated_c = local.is_andn + local.is_orn;
+        for i in 0..WORD_SIZE {
+            builder
+                .when(is_negated_c.clone())
+                .assert_eq(local.lookup_c[i], byte_max() - local.c[i]);
+            builder.when(is_negated_c.clone()).assert_eq(local.lookup_result[i], local.result[i]);
+            builder.when(local.is_xnor).assert_eq(local.lookup_c[i], local.c[i]);
+            builder
+                .when(local.is_xnor)
+                .assert_eq(local.lookup_result[i], byte_max() - local.result[i]);
+            builder.send_byte(
+                local.is_andn * ByteOpcode::AND.as_field::<AB::F>() +
+                    local.is_orn * ByteOpcode::OR.as_field::<AB::F>() +
+                    local.is_xnor * ByteOpcode::XOR.as_field::<AB::F>(),
+                local.lookup_result[i],
+                local.b[i],
+                local.lookup_c[i],
+                is_negated_c.clone() + local.is_xnor,
+            );
+        }
+
+        // CLZ and CTZ. Scanning `b` from the most (for CLZ) or least (for CTZ) significant bit,
+        // `zeros[i]` is one as long as every scanned bit is zero, and the count is their sum. On
+        // rows of other opcodes, all of `zeros` is zero.
+        let is_count_zeros = local.is_clz + local.is_ctz;
+        let scanned = |i: usize| local.is_clz * bit(WORD_BITS - 1 - i) + local.is_ctz * bit(i);
+        builder.assert_eq(local.zeros[0], is_count_zeros.clone() - scanned(0));
+        for i in 1..WORD_BITS {
+            builder.assert_eq(local.zeros[i], local.zeros[i - 1] - local.zeros[i - 1] * scanned(i));
+        }
+        let num_zeros = local.zeros.iter().fold(AB::Expr::zero(), |acc, &zero| acc + zero);
+        builder.when(is_count_zeros).assert_word_eq(
+            local.result,
+            Word([num_zeros, AB::Expr::zero(), AB::Expr::zero(), AB::Expr::zero()]),
+        );
+
+        // CPOP.
+        let num_ones = local.b_bits.iter().fold(AB::Expr::zero(), |acc, &bit| acc + bit);
+        builder.when(local.is_cpop).assert_word_eq(
+            local.result,
+            Word([num_ones, AB::Expr::zero(), AB::Expr::zero(), AB::Expr::zero()]),
+        );
+
+        // MIN, MAX, MINU and MAXU select `b` or `c` by whether `b < c`, which is checked by
+        // sending an SLT or SLTU instruction.
+        let is_min = local.is_min + local.is_minu;
+        let is_max = local.is_max + local.is_maxu;
+        builder.assert_bool(local.lt);
+        for i in 0..WORD_SIZE {
+            builder
+                .when(is_min.clone())
+                .assert_eq(local.result[i], local.c[i] + local.lt * (local.b[i] - local.c[i]));
+            builder
+                .when(is_max.clone())
+                .assert_eq(local.result[i], local.b[i] + local.lt * (local.c[i] - local.b[i]));
+        }
+        builder.send_instruction(
+            AB::Expr::zero(),
+            AB::Expr::zero(),
+            AB::Expr::from_canonical_u32(UNUSED_PC),
+            AB::Expr::from_canonical_u32(UNUSED_PC + DEFAULT_PC_INC),
+            AB::Expr::zero(),
+            (local.is_min + local.is_max) * Opcode::SLT.as_field::<AB::F>() +
+                (local.is_minu + local.is_maxu) * Opcode::SLTU.as_field::<AB::F>(),
+            Word([local.lt.into(), AB::Expr::zero(), AB::Expr::zero(), AB::Expr::zero()]),
+            local.b,
+            local.c,
+            AB::Expr::zero(),
+            AB::Expr::zero(),
+            AB::Expr::zero(),
+            AB::Expr::zero(),
+            AB::Expr::zero(),
+            is_min + is_max,
+        );
+
+        // SEXT.B, SEXT.H and ZEXT.H.
+        builder.when(local.is_sextb).assert_word_eq(
+            local.result,
+            Word([
+                local.b[0].into(),
+                byte_max() * bit(7),
+                byte_max() * bit(7),
+                byte_max() * bit(7),
+            ]),
+        );
+        builder.when(local.is_sexth).assert_word_eq(
+            local.result,
+            Word([
+                local.b[0].into(),
+                local.b[1].into(),
+                byte_max() * bit(15),
+                byte_max() * bit(15),
+            ]),
+        );
+        builder.when(local.is_zexth).assert_word_eq(
+            local.result,
+            Word([local.b[0].into(), local.b[1].into(), AB::Expr::zero(), AB::Expr::zero()]),
+        );
+
+        // ROL and ROR, as the OR of `b` shifted by the rotation amount in one direction and by its
+        // complement in the other. A shift by 32 is a shift by 0 in the shift chips, and the OR of
+        // `b` with itself is `b`, as expected of a rotation by 0.
+        let is_rotate = local.is_rol + local.is_ror;
+        let mut c_byte = AB::Expr::zero();
+        let mut shift = AB::Expr::zero();
+        for (i, bit) in local.c_bits.iter().enumerate() {
+            builder.assert_bool(*bit);
+            c_byte = c_byte.clone() + *bit * AB::F::from_canonical_u32(1 << i);
+            if i < 5 {
+                shift = shift.clone() + *bit * AB::F::from_canonical_u32(1 << i);
+            }
+        }
+        builder.when(is_rotate.clone()).assert_eq(c_byte, local.c[0]);
+        let sll = Opcode::SLL.as_field::<AB::F>();
+        let srl = Opcode::SRL.as_field::<AB::F>();
+        builder.send_instruction(
+            AB::Expr::zero(),
+            AB::Expr::zero(),
+            AB::Expr::from_canonical_u32(UNUSED_PC),
+            AB::Expr::from_canonical_u32(UNUSED_PC + DEFAULT_PC_INC),
+            AB::Expr::zero(),
+            local.is_rol * sll + local.is_ror * srl,
+            local.first_shift,
+            local.b,
+            local.c,
+            AB::Expr::zero(),
+            AB::Expr::zero(),
+            AB::Expr::zero(),
+            AB::Expr::zero(),
+            AB::Expr::zero(),
+            is_rotate.clone(),
+        );
+        builder.send_instruction(
+            AB::Expr::zero(),
+            AB::Expr::zero(),
+            AB::Expr::from_canonical_u32(UNUSED_PC),
+            AB::Expr::from_canonical_u32(UNUSED_PC + DEFAULT_PC_INC),
+            AB::Expr::zero(),
+            local.is_rol * srl + local.is_ror * sll,
+            local.second_shift,
+            local.b,
+            Word([
+                AB::Expr::from_canonical_u32(32) - shift,
+                AB::Expr::zero(),
+                AB::Expr::zero(),
+                AB::Expr::zero(),
+            ]),
+            AB::Expr::zero(),
+            AB::Expr::zero(),
+            AB::Expr::zero(),
+            AB::Expr::zero(),
+            AB::Expr::zero(),
+            is_rotate.clone(),
+        );
+        for i in 0..WORD_SIZE {
+            builder.send_byte(
+                ByteOpcode::OR.as_field::<AB::F>(),
+                local.result[i],
+                local.first_shift[i],
+                local.second_shift[i],
+                is_rotate.clone(),
+            );
+        }
+
+        // ORC.B.
+        for i in 0..WORD_SIZE {
+            IsZeroOperation::<AB::F>::eval(
+                builder,
+                local.b[i].into(),
+                local.byte_is_zero[i],
+                local.is_orcb.into(),
+            );
+            builder
+                .when(local.is_orcb)
+                .assert_eq(local.result[i], byte_max() - byte_max() * local.byte_is_zero[i].result);
+        }
+
+        // REV8.
+        builder
+            .when(local.is_rev8)
+            .assert_word_eq(local.result, Word([local.b[3], local.b[2], local.b[1], local.b[0]]));
+
+        // The output operand is the result, unless the first operand is register 0.
+        builder.when(local.op_a_not_0).assert_word_eq(local.a, local.result);
+
+        // SAFETY: We check that a padding row has `op_a_not_0 == 0`.
+        builder.when(local.op_a_not_0).assert_one(is_real.clone());
+
+        // Receive the arguments.
+        // SAFETY: This checks the following.
+        // - `next_pc = pc + 4`
+        // - `num_extra_cycles = 0`
+        // - `op_a_val` is constrained to be the result when `op_a_not_0 == 1`
+        // - `op_a_not_0` is correct, due to the sent `op_a_0` being equal to `1 - op_a_not_0`
+        // - `op_a_immutable = 0`
+        // - `is_memory = 0`
+        // - `is_syscall = 0`
+        // - `is_halt = 0`
+        builder.receive_instruction(
+            AB::Expr::zero(),
+            AB::Expr::zero(),
+            local.pc,
+            local.pc + AB::Expr::from_canonical_u32(DEFAULT_PC_INC),
+            AB::Expr::zero(),
+            opcode,
+            local.a,
+            local.b,
+            local.c,
+            AB::Expr::one() - local.op_a_not_0,
+            AB::Expr::zero(),
+            AB::Expr::zero(),
+            AB::Expr::zero(),
+            AB::Expr::zero(),
+            is_real,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_matrix::dense::RowMajorMatrix;
+    use monerochan_core_executor::{events::AluEvent, get_bitmanip_result, ExecutionRecord, Opcode};
+    use monerochan_stark::{
+        air::MachineAir, baby_bear_poseidon2::BabyBearPoseidon2, StarkGenericConfig,
+    };
+
+    use crate::utils::{uni_stark_prove, uni_stark_verify};
+
+    use super::BitManipChip;
+
+    const OPCODES: [Opcode; 17] = [
+        Opcode::ANDN,
+        Opcode::ORN,
+        Opcode::XNOR,
+        Opcode::CLZ,
+        Opcode::CTZ,
+        Opcode::CPOP,
+        Opcode::MAX,
+        Opcode::MAXU,
+        Opcode::MIN,
+        Opcode::MINU,
+        Opcode::SEXTB,
+        Opcode::SEXTH,
+        Opcode::ZEXTH,
+        Opcode::ROL,
+        Opcode::ROR,
+        Opcode::ORCB,
+        Opcode::REV8,
+    ];
+
+    #[test]
+    fn prove_babybear() {
+        let config = BabyBearPoseidon2::new();
+        let mut challenger = config.challenger();
+
+        let operands = [(0x1234_5678, 4), (0x8000_0000, 0xffff_fff0), (0, 31), (0xff00_00ff, 0)];
+        let mut shard = ExecutionRecord::default();
+        shard.bitmanip_events = OPCODES
+            .iter()
+            .flat_map(|&opcode| {
+                operands.iter().map(move |&(b, c)| {
+                    AluEvent::new(0, opcode, get_bitmanip_result(opcode, b, c), b, c, false)
+                })
+            })
+            .collect();
+        let chip = BitManipChip::default();
+        let trace: RowMajorMatrix<BabyBear> =
+            chip.generate_trace(&shard, &mut ExecutionRecord::default());
+        let proof = uni_stark_prove::<BabyBearPoseidon2, _>(&config, &chip, &mut challenger, trace);
+
+        let mut challenger = config.challenger();
+        uni_stark_verify(&config, &chip, &mut challenger, &proof).unwrap();
+    }
+
+    /// The chip is only part of the machine with the `zbb` feature.
+    #[test]
+    #[cfg(feature = "zbb")]
+    fn test_bitmanip_prove() {
+        use monerochan_core_executor::{Instruction, Program};
+        use monerochan_stark::CpuProver;
+
+        use crate::{
+            io::MONEROCHANStdin,
+            utils::{run_test, setup_logger},
+        };
+
+        setup_logger();
+        let operands = [(0x1234_5678, 0x8000_0001), (0xffff_0000, 31), (0, 0), (0x80, 0x7fff_ffff)];
+        for opcode in OPCODES {
+            for (b, c) in operands {
+                let instructions = vec![
+                    Instruction::new(Opcode::ADD, 29, 0, b, false, true),
+                    Instruction::new(Opcode::ADD, 30, 0, c, false, true),
+                    Instruction::new(opcode, 31, 29, 30, false, false),
+                    Instruction::new(opcode, 0, 29, 30, false, false),
+                ];
+                let program = Program::new(instructions, 0, 0);
+                let stdin = MONEROCHANStdin::new();
+                run_test::<CpuProver<_, _>>(program, stdin).unwrap();
+            }
+        }
+    }
+}
@@ -65,11 +65,14 @@ use core::{
     mem::size_of,
 };
 
+use hashbrown::HashMap;
+use itertools::Itertools;
 use p3_air::{Air, AirBuilder, BaseAir};
 use p3_field::{AbstractField, PrimeField32};
 use p3_matrix::{dense::RowMajorMatrix, Matrix};
+use p3_maybe_rayon::prelude::{ParallelIterator, ParallelSlice};
 use monerochan_core_executor::{
-    events::{ByteLookupEvent, ByteRecord},
+    events::{AluEvent, ByteLookupEvent, ByteRecord},
     get_msb, get_quotient_and_remainder, is_signed_operation, ByteOpcode, ExecutionRecord, Opcode,
     Program, DEFAULT_PC_INC, UNUSED_PC,
 };
@@ -198,143 +201,168 @@ pub struct DivRemCols<T> {
     pub remainder_check_multiplicity: T,
 }
 
-impl<F: PrimeField32> MachineAir<F> for DivRemChip {
-    type Record = ExecutionRecord;
+impl DivRemChip {
+    /// Create a row from an event.
+    fn event_to_row<F: PrimeField32>(
+        &self,
+        event: &AluEvent,
+        cols: &mut DivRemCols<F>,
+        blu: &mut impl ByteRecord,
+    ) {
+        assert!(
+            event.opcode == Opcode::DIVU ||
+                event.opcode == Opcode::REMU ||
+                event.opcode == Opcode::REM ||
+                event.opcode == Opcode::DIV
+        );
 
-    type Program = Program;
+        cols.pc = F::from_canonical_u32(event.pc);
 
-    fn name(&self) -> String {
-        "DivRem".to_string()
-    }
+        // Initialize cols with basic operands and flags derived from the current event.
+        {
+            cols.a = Word::from(event.a);
+            cols.b = Word::from(event.b);
+            cols.c = Word::from(event.c);
+            cols.op_a_not_0 = F::from_bool(!event.op_a_0);
+            cols.is_real = F::one();
+            cols.is_divu = F::from_bool(event.opcode == Opcode::DIVU);
+            cols.is_remu = F::from_bool(event.opcode == Opcode::REMU);
+            cols.is_div = F::from_bool(event.opcode == Opcode::DIV);
+            cols.is_rem = F::from_bool(event.opcode == Opcode::REM);
+            cols.is_c_0.populate(event.c);
+        }
 
-    fn generate_trace(
-        &self,
-        input: &ExecutionRecord,
-        output: &mut ExecutionRecord,
-    ) -> RowMajorMatrix<F> {
-        // Generate the trace rows for each event.
-        let mut rows: Vec<[F; NUM_DIVREM_COLS]> = vec![];
-        let divrem_events = input.divrem_events.clone();
-        for event in divrem_events.iter() {
-            assert!(
-                event.opcode == Opcode::DIVU ||
-                    event.opcode == Opcode::REMU ||
-                    event.opcode == Opcode::REM ||
-                    event.opcode == Opcode::DIV
-            );
-            let mut row = [F::zero(); NUM_DIVREM_COLS];
-            let cols: &mut DivRemCols<F> = row.as_mut_slice().borrow_mut();
+        let (quotient, remainder) = get_quotient_and_remainder(event.b, event.c, event.opcode);
+        cols.quotient = Word::from(quotient);
+        cols.remainder = Word::from(remainder);
+
+        // Calculate flags for sign detection.
+        {
+            cols.rem_msb = F::from_canonical_u8(get_msb(remainder));
+            cols.b_msb = F::from_canonical_u8(get_msb(event.b));
+            cols.c_msb = F::from_canonical_u8(get_msb(event.c));
+            cols.is_overflow_b.populate(event.b, i32::MIN as u32);
+            cols.is_overflow_c.populate(event.c, -1i32 as u32);
+            if is_signed_operation(event.opcode) {
+                cols.rem_neg = cols.rem_msb;
+                cols.b_neg = cols.b_msb;
+                cols.c_neg = cols.c_msb;
+                cols.is_overflow = F::from_bool(event.b as i32 == i32::MIN && event.c as i32 == -1);
+                cols.abs_remainder = Word::from((remainder as i32).abs() as u32);
+                cols.abs_c = Word::from((event.c as i32).abs() as u32);
+                cols.max_abs_c_or_1 = Word::from(u32::max(1, (event.c as i32).abs() as u32));
+            } else {
+                cols.abs_remainder = cols.remainder;
+                cols.abs_c = cols.c;
+                cols.max_abs_c_or_1 = Word::from(u32::max(1, event.c));
+            }
 
-            cols.pc = F::from_canonical_u32(event.pc);
+            // Set the `alu_event` flags.
+            cols.abs_c_alu_event = cols.c_neg * cols.is_real;
+            cols.abs_rem_alu_event = cols.rem_neg * cols.is_real;
 
-            // Initialize cols with basic operands and flags derived from the current event.
+            // Insert the MSB lookup events.
             {
-                cols.a = Word::from(event.a);
-                cols.b = Word::from(event.b);
-                cols.c = Word::from(event.c);
-                cols.op_a_not_0 = F::from_bool(!event.op_a_0);
-                cols.is_real = F::one();
-                cols.is_divu = F::from_bool(event.opcode == Opcode::DIVU);
-                cols.is_remu = F::from_bool(event.opcode == Opcode::REMU);
-                cols.is_div = F::from_bool(event.opcode == Opcode::DIV);
-                cols.is_rem = F::from_bool(event.opcode == Opcode::REM);
-                cols.is_c_0.populate(event.c);
+                let words = [event.b, event.c, remainder];
+                let mut blu_events: Vec<ByteLookupEvent> = vec![];
+                for word in words.iter() {
+                    let most_significant_byte = word.to_le_bytes()[WORD_SIZE - 1];
+                    blu_events.push(ByteLookupEvent {
+                        opcode: ByteOpcode::MSB,
+                        a1: get_msb(*word) as u16,
+                        a2: 0,
+                        b: most_significant_byte,
+                        c: 0,
+                    });
+                }
+                blu.add_byte_lookup_events(blu_events);
             }
+        }
 
-            let (quotient, remainder) = get_quotient_and_remainder(event.b, event.c, event.opcode);
-            cols.quotient = Word::from(quotient);
-            cols.remainder = Word::from(remainder);
+        // Calculate the modified multiplicity
+        {
+            cols.remainder_check_multiplicity = cols.is_real * (F::one() - cols.is_c_0.result);
+        }
 
-            // Calculate flags for sign detection.
-            {
-                cols.rem_msb = F::from_canonical_u8(get_msb(remainder));
-                cols.b_msb = F::from_canonical_u8(get_msb(event.b));
-                cols.c_msb = F::from_canonical_u8(get_msb(event.c));
-                cols.is_overflow_b.populate(event.b, i32::MIN as u32);
-                cols.is_overflow_c.populate(event.c, -1i32 as u32);
+        // Calculate c * quotient + remainder.
+        {
+            let c_times_quotient = {
                 if is_signed_operation(event.opcode) {
-                    cols.rem_neg = cols.rem_msb;
-                    cols.b_neg = cols.b_msb;
-                    cols.c_neg = cols.c_msb;
-                    cols.is_overflow =
-                        F::from_bool(event.b as i32 == i32::MIN && event.c as i32 == -1);
-                    cols.abs_remainder = Word::from((remainder as i32).abs() as u32);
-                    cols.abs_c = Word::from((event.c as i32).abs() as u32);
-                    cols.max_abs_c_or_1 = Word::from(u32::max(1, (event.c as i32).abs() as u32));
+                    (((quotient as i32) as i64) * ((event.c as i32) as i64)).to_le_bytes()
                 } else {
-                    cols.abs_remainder = cols.remainder;
-                    cols.abs_c = cols.c;
-                    cols.max_abs_c_or_1 = Word::from(u32::max(1, event.c));
+                    ((quotient as u64) * (event.c as u64)).to_le_bytes()
                 }
+            };
+            cols.c_times_quotient = c_times_quotient.map(F::from_canonical_u8);
 
-                // Set the `alu_event` flags.
-                cols.abs_c_alu_event = cols.c_neg * cols.is_real;
-                cols.abs_rem_alu_event = cols.rem_neg * cols.is_real;
-
-                // Insert the MSB lookup events.
-                {
-                    let words = [event.b, event.c, remainder];
-                    let mut blu_events: Vec<ByteLookupEvent> = vec![];
-                    for word in words.iter() {
-                        let most_significant_byte = word.to_le_bytes()[WORD_SIZE - 1];
-                        blu_events.push(ByteLookupEvent {
-                            opcode: ByteOpcode::MSB,
-                            a1: get_msb(*word) as u16,
-                            a2: 0,
-                            b: most_significant_byte,
-                            c: 0,
-                        });
-                    }
-                    output.add_byte_lookup_events(blu_events);
+            let remainder_bytes = {
+                if is_signed_operation(event.opcode) {
+                    ((remainder as i32) as i64).to_le_bytes()
+                } else {
+                    (remainder as u64).to_le_bytes()
                 }
-            }
+            };
 
-            // Calculate the modified multiplicity
-            {
-                cols.remainder_check_multiplicity = cols.is_real * (F::one() - cols.is_c_0.result);
+            // Add remainder to product.
+            let mut carry = [0u32; 8];
+            let base = 1 << BYTE_SIZE;
+            for i in 0..LONG_WORD_SIZE {
+                let mut x = c_times_quotient[i] as u32 + remainder_bytes[i] as u32;
+                if i > 0 {
+                    x += carry[i - 1];
+                }
+                carry[i] = x / base;
+                cols.carry[i] = F::from_canonical_u32(carry[i]);
             }
 
-            // Calculate c * quotient + remainder.
+            // Range check.
             {
-                let c_times_quotient = {
-                    if is_signed_operation(event.opcode) {
-                        (((quotient as i32) as i64) * ((event.c as i32) as i64)).to_le_bytes()
-                    } else {
-                        ((quotient as u64) * (event.c as u64)).to_le_bytes()
-                    }
-                };
-                cols.c_times_quotient = c_times_quotient.map(F::from_canonical_u8);
+                blu.add_u8_range_checks(&quotient.to_le_bytes());
+                blu.add_u8_range_checks(&remainder.to_le_bytes());
+                blu.add_u8_range_checks(&c_times_quotient);
+            }
+        }
+    }
+}
 
-                let remainder_bytes = {
-                    if is_signed_operation(event.opcode) {
-                        ((remainder as i32) as i64).to_le_bytes()
-                    } else {
-                        (remainder as u64).to_le_bytes()
-                    }
-                };
+impl<F: PrimeField32> MachineAir<F> for DivRemChip {
+    type Record = ExecutionRecord;
 
-                // Add remainder to product.
-                let mut carry = [0u32; 8];
-                let base = 1 << BYTE_SIZE;
-                for i in 0..LONG_WORD_SIZE {
-                    let mut x = c_times_quotient[i] as u32 + remainder_bytes[i] as u32;
-                    if i > 0 {
-                        x += carry[i - 1];
-                    }
-                    carry[i] = x / base;
-                    cols.carry[i] = F::from_canonical_u32(carry[i]);
-                }
+    type Program = Program;
 
-                // Range check.
-                {
-                    output.add_u8_range_checks(&quotient.to_le_bytes());
-                    output.add_u8_range_checks(&remainder.to_le_bytes());
-                    output.add_u8_range_checks(&c_times_quotient);
-                }
-            }
+    fn name(&self) -> String {
+        "DivRem".to_string()
+    }
 
-            rows.push(row);
-        }
+    fn generate_trace(
+        &self,
+        input: &ExecutionRecord,
+        output: &mut ExecutionRecord,
+    ) -> RowMajorMatrix<F> {
+        // Generate the trace rows and the byte lookups of each chunk of events in parallel, and
+        // concatenate the rows in the order of the events.
+        let chunk_size = std::cmp::max(input.divrem_events.len() / num_cpus::get(), 1);
+        let (row_batches, blu_batches): (Vec<_>, Vec<_>) = input
+            .divrem_events
+            .par_chunks(chunk_size)
+            .map(|events| {
+                let mut blu: HashMap<ByteLookupEvent, usize> = HashMap::new();
+                let rows = events
+                    .iter()
+                    .map(|event| {
+                        let mut row = [F::zero(); NUM_DIVREM_COLS];
+                        let cols: &mut DivRemCols<F> = row.as_mut_slice().borrow_mut();
+                        self.event_to_row(event, cols, &mut blu);
+                        row
+                    })
+                    .collect::<Vec<_>>();
+                (rows, blu)
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .unzip();
+        output.add_byte_lookup_events_from_maps(blu_batches.iter().collect_vec());
+        let mut rows = row_batches.into_iter().flatten().collect::<Vec<_>>();
 
         // Pad the trace to a power of two depending on the proof shape in `input`.
         pad_rows_fixed(
@@ -1,4 +1,5 @@
 pub mod add_sub;
+pub mod bitmanip;
 pub mod bitwise;
 pub mod divrem;
 pub mod lt;
@@ -7,6 +8,7 @@ pub mod sll;
 pub mod sr;
 
 pub use add_sub::*;
+pub use bitmanip::*;
 pub use bitwise::*;
 pub use divrem::*;
 pub use lt::*;
@@ -40,7 +40,7 @@ use itertools::Itertools;
 use p3_air::{Air, AirBuilder, BaseAir};
 use p3_field::{AbstractField, PrimeField, PrimeField32};
 use p3_matrix::{dense::RowMajorMatrix, Matrix};
-use p3_maybe_rayon::prelude::{ParallelIterator, ParallelSlice};
+use p3_maybe_rayon::prelude::{ParallelBridge, ParallelIterator, ParallelSlice};
 use monerochan_core_executor::{
     events::{AluEvent, ByteLookupEvent, ByteRecord},
     ExecutionRecord, Opcode, Program, DEFAULT_PC_INC,
@@ -49,7 +49,10 @@ use monerochan_derive::AlignedBorrow;
 use monerochan_primitives::consts::WORD_SIZE;
 use monerochan_stark::{air::MachineAir, Word};
 
-use crate::{air::MONEROCHANCoreAirBuilder, utils::pad_rows_fixed};
+use crate::{
+    air::MONEROCHANCoreAirBuilder,
+    utils::{next_power_of_two, zeroed_f_vec},
+};
 
 /// The number of main trace columns for `ShiftLeft`.
 pub const NUM_SHIFT_LEFT_COLS: usize = size_of::<ShiftLeftCols<u8>>();
@@ -116,45 +119,34 @@ impl<F: PrimeField32> MachineAir<F> for ShiftLeft {
         _: &mut ExecutionRecord,
     ) -> RowMajorMatrix<F> {
         // Generate the trace rows for each event.
-        let mut rows: Vec<[F; NUM_SHIFT_LEFT_COLS]> = vec![];
-        let shift_left_events = input.shift_left_events.clone();
-        for event in shift_left_events.iter() {
-            let mut row = [F::zero(); NUM_SHIFT_LEFT_COLS];
-            let cols: &mut ShiftLeftCols<F> = row.as_mut_slice().borrow_mut();
-            let mut blu = Vec::new();
-            self.event_to_row(event, cols, &mut blu);
-            rows.push(row);
-        }
-
-        // Pad the trace to a power of two depending on the proof shape in `input`.
-        pad_rows_fixed(
-            &mut rows,
-            || [F::zero(); NUM_SHIFT_LEFT_COLS],
-            input.fixed_log2_rows::<F, _>(self),
+        let nb_rows = input.shift_left_events.len();
+        let size_log2 = input.fixed_log2_rows::<F, _>(self);
+        let padded_nb_rows = next_power_of_two(nb_rows, size_log2);
+        let mut values = zeroed_f_vec(padded_nb_rows * NUM_SHIFT_LEFT_COLS);
+        let chunk_size = std::cmp::max((nb_rows + 1) / num_cpus::get(), 1);
+
+        values.chunks_mut(chunk_size * NUM_SHIFT_LEFT_COLS).enumerate().par_bridge().for_each(
+            |(i, rows)| {
+                rows.chunks_mut(NUM_SHIFT_LEFT_COLS).enumerate().for_each(|(j, row)| {
+                    let idx = i * chunk_size + j;
+                    let cols: &mut ShiftLeftCols<F> = row.borrow_mut();
+
+                    if idx < nb_rows {
+                        let mut byte_lookup_events = Vec::new();
+                        let event = &input.shift_left_events[idx];
+                        self.event_to_row(event, cols, &mut byte_lookup_events);
+                    } else {
+                        // Fake rows that don't fail on some sanity checks.
+                        cols.shift_by_n_bits[0] = F::one();
+                        cols.shift_by_n_bytes[0] = F::one();
+                        cols.bit_shift_multiplier = F::one();
+                    }
+                });
+            },
         );
 
         // Convert the trace to a row major matrix.
-        let mut trace = RowMajorMatrix::new(
-            rows.into_iter().flatten().collect::<Vec<_>>(),
-            NUM_SHIFT_LEFT_COLS,
-        );
-
-        // Create the template for the padded rows. These are fake rows that don't fail on some
-        // sanity checks.
-        let padded_row_template = {
-            let mut row = [F::zero(); NUM_SHIFT_LEFT_COLS];
-            let cols: &mut ShiftLeftCols<F> = row.as_mut_slice().borrow_mut();
-            cols.shift_by_n_bits[0] = F::one();
-            cols.shift_by_n_bytes[0] = F::one();
-            cols.bit_shift_multiplier = F::one();
-            row
-        };
-        debug_assert!(padded_row_template.len() == NUM_SHIFT_LEFT_COLS);
-        for i in input.shift_left_events.len() * NUM_SHIFT_LEFT_COLS..trace.values.len() {
-            trace.values[i] = padded_row_template[i % NUM_SHIFT_LEFT_COLS];
-        }
-
-        trace
+        RowMajorMatrix::new(values, NUM_SHIFT_LEFT_COLS)
     }
 
     fn generate_dependencies(&self, input: &Self::Record, output: &mut Self::Record) {
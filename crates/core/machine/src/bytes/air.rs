@@ -66,6 +66,10 @@ impl<AB: MONEROCHANAirBuilder + PairBuilder> Air<AB> for ByteChip<AB::F> {
                     AB::F::zero(),
                     mult,
                 ),
+                #[cfg(feature = "aes-precompile")]
+                ByteOpcode::AesSbox => {
+                    builder.receive_byte(field_op, local.sbox, local.b, AB::F::zero(), mult)
+                }
             }
         }
     }
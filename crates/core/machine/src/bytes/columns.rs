@@ -42,6 +42,10 @@ pub struct BytePreprocessedCols<T> {
 
     /// A u16 value used for `U16Range`.
     pub value_u16: T,
+
+    /// The AES S-box substitution of `b`.
+    #[cfg(feature = "aes-precompile")]
+    pub sbox: T,
 }
 
 /// For each byte operation in the preprocessed table, a corresponding ByteMultCols row tracks the
@@ -5,6 +5,8 @@ pub mod columns;
 pub mod trace;
 pub mod utils;
 
+#[cfg(feature = "aes-precompile")]
+use monerochan_core_executor::events::AES_SBOX;
 use monerochan_core_executor::{events::ByteLookupEvent, ByteOpcode};
 
 use core::borrow::BorrowMut;
@@ -21,7 +23,7 @@ use self::{
 use crate::{bytes::trace::NUM_ROWS, utils::zeroed_f_vec};
 
 /// The number of different byte operations.
-pub const NUM_BYTE_OPS: usize = 9;
+pub const NUM_BYTE_OPS: usize = if cfg!(feature = "aes-precompile") { 10 } else { 9 };
 
 /// A chip for computing byte operations.
 ///
@@ -99,6 +101,12 @@ impl<F: Field> ByteChip<F> {
                         col.value_u16 = F::from_canonical_u32(v);
                         ByteLookupEvent::new(*opcode, v as u16, 0, 0, 0)
                     }
+                    #[cfg(feature = "aes-precompile")]
+                    ByteOpcode::AesSbox => {
+                        let sbox = AES_SBOX[b as usize];
+                        col.sbox = F::from_canonical_u8(sbox);
+                        ByteLookupEvent::new(*opcode, sbox as u16, 0, b, 0)
+                    }
                 };
             }
         }
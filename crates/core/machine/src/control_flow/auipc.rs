@@ -232,7 +232,7 @@ mod tests {
     use p3_field::AbstractField;
     use p3_matrix::dense::RowMajorMatrix;
     use monerochan_core_executor::{
-        ExecutionError, ExecutionRecord, Executor, Instruction, Opcode, Program,
+        ExecutionError, ExecutionRecord, Executor, Instruction, Opcode, Program, TrapKind,
     };
     use monerochan_stark::{
         air::MachineAir, baby_bear_poseidon2::BabyBearPoseidon2, chip_name, CpuProver,
@@ -318,7 +318,10 @@ mod tests {
         runtime.write_vecs(&stdin.buffer);
         let result = runtime.execute();
 
-        assert!(result.is_err() && result.unwrap_err() == ExecutionError::Unimplemented());
+        assert!(matches!(
+            result,
+            Err(ExecutionError::Trap(trap)) if trap.kind == TrapKind::InvalidInstruction
+        ));
     }
 
     #[test]
@@ -1,5 +1,7 @@
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use monerochan_primitives::consts::NAMED_INPUT_TAG;
 use monerochan_stark::{baby_bear_poseidon2::BabyBearPoseidon2, MONEROCHANReduceProof, StarkVerifyingKey};
+use thiserror::Error;
 
 /// Standard input for the prover.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -59,6 +61,147 @@ impl MONEROCHANStdin {
     ) {
         self.proofs.push((proof, vk));
     }
+
+    /// Write a named value to the buffer, to be read with `monerochan_runtime::io::read_named`.
+    ///
+    /// The program panics if it reads the inputs in another order than they were written, instead
+    /// of deserializing one input as another.
+    pub fn insert<T: Serialize>(&mut self, name: &str, data: &T) {
+        self.buffer.push(named_input_tag(name));
+        self.write(data);
+    }
+
+    /// Write a named slice of bytes to the buffer, to be read with
+    /// `monerochan_runtime::io::read_named_vec`.
+    pub fn insert_slice(&mut self, name: &str, slice: &[u8]) {
+        self.buffer.push(named_input_tag(name));
+        self.write_slice(slice);
+    }
+
+    /// Checks that the inputs are the named inputs of the schema, in the same order, and that each
+    /// value deserializes as the type of its input.
+    pub fn validate(&self, schema: &StdinSchema) -> Result<(), StdinSchemaError> {
+        let mut buffers = self.buffer.iter().enumerate();
+        for input in &schema.inputs {
+            let Some((index, tag)) = buffers.next() else {
+                return Err(StdinSchemaError::Missing { name: input.name.clone() });
+            };
+            let found = named_input_name(tag).ok_or(StdinSchemaError::Unnamed { index })?;
+            if found != input.name {
+                return Err(StdinSchemaError::Mismatch {
+                    index,
+                    expected: input.name.clone(),
+                    found: found.to_string(),
+                });
+            }
+            let Some((_, value)) = buffers.next() else {
+                return Err(StdinSchemaError::Missing { name: input.name.clone() });
+            };
+            if !(input.check)(value) {
+                return Err(StdinSchemaError::InvalidValue {
+                    name: input.name.clone(),
+                    type_name: input.type_name,
+                });
+            }
+        }
+        match buffers.next() {
+            None => Ok(()),
+            Some((index, buffer)) => match named_input_name(buffer) {
+                Some(name) => Err(StdinSchemaError::Unexpected { name: name.to_string() }),
+                None => Err(StdinSchemaError::Unnamed { index }),
+            },
+        }
+    }
+}
+
+/// Returns the buffer marking the next input as the value of the input `name`.
+fn named_input_tag(name: &str) -> Vec<u8> {
+    [NAMED_INPUT_TAG, name.as_bytes()].concat()
+}
+
+/// Returns the name of the input marked by the buffer, if it is the tag of a named input.
+fn named_input_name(buffer: &[u8]) -> Option<&str> {
+    buffer.strip_prefix(NAMED_INPUT_TAG).and_then(|name| std::str::from_utf8(name).ok())
+}
+
+/// The named inputs a program reads, in order, to validate a [`MONEROCHANStdin`] before
+/// executing the program.
+///
+/// ### Examples
+/// ```ignore
+/// let schema = StdinSchema::new().input::<BlockHeader>("block_header").input::<u64>("height");
+///
+/// let mut stdin = MONEROCHANStdin::new();
+/// stdin.insert("block_header", &header);
+/// stdin.insert("height", &height);
+/// stdin.validate(&schema).unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StdinSchema {
+    inputs: Vec<SchemaInput>,
+}
+
+/// A named input of a [`StdinSchema`].
+#[derive(Debug, Clone)]
+struct SchemaInput {
+    name: String,
+    type_name: &'static str,
+    /// Whether a buffer deserializes as the type of the input.
+    check: fn(&[u8]) -> bool,
+}
+
+impl StdinSchema {
+    /// Create an empty schema.
+    pub const fn new() -> Self {
+        Self { inputs: Vec::new() }
+    }
+
+    /// Add a named input of type `T`, read after the inputs added before it.
+    #[must_use]
+    pub fn input<T: DeserializeOwned>(mut self, name: &str) -> Self {
+        self.inputs.push(SchemaInput {
+            name: name.to_string(),
+            type_name: std::any::type_name::<T>(),
+            check: |buffer| bincode::deserialize::<T>(buffer).is_ok(),
+        });
+        self
+    }
+
+    /// Add a named input of raw bytes, written with [`MONEROCHANStdin::insert_slice`].
+    #[must_use]
+    pub fn input_slice(mut self, name: &str) -> Self {
+        self.inputs.push(SchemaInput {
+            name: name.to_string(),
+            type_name: "[u8]",
+            check: |_| true,
+        });
+        self
+    }
+
+    /// The names of the inputs, in the order they are read.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.inputs.iter().map(|input| input.name.as_str())
+    }
+}
+
+/// An error returned by [`MONEROCHANStdin::validate`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum StdinSchemaError {
+    /// An input of the schema was not written.
+    #[error("missing input `{name}`")]
+    Missing { name: String },
+    /// An input was written in the place of another input of the schema.
+    #[error("expected input `{expected}` at buffer {index}, found `{found}`")]
+    Mismatch { index: usize, expected: String, found: String },
+    /// An input was written without a name.
+    #[error("unnamed input at buffer {index}")]
+    Unnamed { index: usize },
+    /// An input that is not in the schema was written.
+    #[error("unexpected input `{name}`")]
+    Unexpected { name: String },
+    /// The value of an input does not deserialize as the type of the input.
+    #[error("input `{name}` is not a valid `{type_name}`")]
+    InvalidValue { name: String, type_name: &'static str },
 }
 
 pub mod proof_serde {
@@ -97,3 +240,50 @@ pub mod proof_serde {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_named_inputs() {
+        let schema = StdinSchema::new().input::<(u32, u32)>("header").input_slice("payload");
+        assert_eq!(schema.names().collect::<Vec<_>>(), ["header", "payload"]);
+
+        let mut stdin = MONEROCHANStdin::new();
+        stdin.insert("header", &(1u32, 2u32));
+        stdin.insert_slice("payload", &[1, 2, 3]);
+        stdin.validate(&schema).unwrap();
+        assert_eq!(stdin.buffer.len(), 4);
+
+        let mut swapped = MONEROCHANStdin::new();
+        swapped.insert_slice("payload", &[1, 2, 3]);
+        swapped.insert("header", &(1u32, 2u32));
+        assert_eq!(
+            swapped.validate(&schema),
+            Err(StdinSchemaError::Mismatch {
+                index: 0,
+                expected: "header".to_string(),
+                found: "payload".to_string(),
+            })
+        );
+
+        let mut truncated = MONEROCHANStdin::new();
+        truncated.insert("header", &1u32);
+        assert!(matches!(
+            truncated.validate(&schema),
+            Err(StdinSchemaError::InvalidValue { name, .. }) if name == "header"
+        ));
+
+        let mut missing = MONEROCHANStdin::new();
+        missing.insert("header", &(1u32, 2u32));
+        assert_eq!(
+            missing.validate(&schema),
+            Err(StdinSchemaError::Missing { name: "payload".to_string() })
+        );
+
+        let mut extra = stdin.clone();
+        extra.write(&3u32);
+        assert_eq!(extra.validate(&schema), Err(StdinSchemaError::Unnamed { index: 4 }));
+    }
+}
@@ -11,7 +11,7 @@ use core::{
 use p3_air::{Air, AirBuilder, BaseAir};
 use p3_field::{AbstractField, PrimeField32};
 use p3_matrix::{dense::RowMajorMatrix, Matrix};
-use p3_maybe_rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+use p3_maybe_rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 use monerochan_core_executor::{
     events::{GlobalInteractionEvent, MemoryInitializeFinalizeEvent},
     ExecutionRecord, Program,
@@ -116,9 +116,15 @@ impl<F: PrimeField32> MachineAir<F> for MemoryGlobalChip {
         };
 
         memory_events.sort_by_key(|event| event.addr);
+        let prev_addr =
+            previous_addr_bits.iter().enumerate().map(|(j, bit)| bit * (1 << j)).sum::<u32>();
+
+        // Each row only depends on its event and the previous one, so the rows are generated in
+        // parallel, in the order of the addresses.
         let mut rows: Vec<[F; NUM_MEMORY_INIT_COLS]> = memory_events
             .par_iter()
-            .map(|event| {
+            .enumerate()
+            .map(|(i, event)| {
                 let MemoryInitializeFinalizeEvent { addr, value, shard, timestamp } =
                     event.to_owned();
 
@@ -131,41 +137,32 @@ impl<F: PrimeField32> MachineAir<F> for MemoryGlobalChip {
                 cols.value = array::from_fn(|i| F::from_canonical_u32((value >> i) & 1));
                 cols.is_real = F::one();
 
-                row
-            })
-            .collect::<Vec<_>>();
+                if i == 0 {
+                    cols.is_prev_addr_zero.populate(prev_addr);
+                    cols.is_first_comp = F::from_bool(prev_addr != 0);
+                    if prev_addr != 0 {
+                        debug_assert!(prev_addr < addr, "prev_addr {prev_addr} < addr {addr}");
+                        let addr_bits: [_; 32] = array::from_fn(|i| (addr >> i) & 1);
+                        cols.lt_cols.populate(&previous_addr_bits, &addr_bits);
+                    }
+                }
+                if i != 0 {
+                    cols.is_next_comp = F::one();
+                    let previous_addr = memory_events[i - 1].addr;
+                    assert_ne!(previous_addr, addr);
 
-        for i in 0..memory_events.len() {
-            let addr = memory_events[i].addr;
-            let cols: &mut MemoryInitCols<F> = rows[i].as_mut_slice().borrow_mut();
-            if i == 0 {
-                let prev_addr = previous_addr_bits
-                    .iter()
-                    .enumerate()
-                    .map(|(j, bit)| bit * (1 << j))
-                    .sum::<u32>();
-                cols.is_prev_addr_zero.populate(prev_addr);
-                cols.is_first_comp = F::from_bool(prev_addr != 0);
-                if prev_addr != 0 {
-                    debug_assert!(prev_addr < addr, "prev_addr {prev_addr} < addr {addr}");
                     let addr_bits: [_; 32] = array::from_fn(|i| (addr >> i) & 1);
-                    cols.lt_cols.populate(&previous_addr_bits, &addr_bits);
+                    let prev_addr_bits: [_; 32] = array::from_fn(|i| (previous_addr >> i) & 1);
+                    cols.lt_cols.populate(&prev_addr_bits, &addr_bits);
                 }
-            }
-            if i != 0 {
-                cols.is_next_comp = F::one();
-                let previous_addr = memory_events[i - 1].addr;
-                assert_ne!(previous_addr, addr);
-
-                let addr_bits: [_; 32] = array::from_fn(|i| (addr >> i) & 1);
-                let prev_addr_bits: [_; 32] = array::from_fn(|i| (previous_addr >> i) & 1);
-                cols.lt_cols.populate(&prev_addr_bits, &addr_bits);
-            }
 
-            if i == memory_events.len() - 1 {
-                cols.is_last_addr = F::one();
-            }
-        }
+                if i == memory_events.len() - 1 {
+                    cols.is_last_addr = F::one();
+                }
+
+                row
+            })
+            .collect::<Vec<_>>();
 
         // Pad the trace to a power of two depending on the proof shape in `input`.
         rows.resize(
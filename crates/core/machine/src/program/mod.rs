@@ -203,6 +203,8 @@ mod tests {
                 pc_base: 0,
                 memory_image: HashMap::new(),
                 preprocessed_shape: None,
+                symbols: Vec::new(),
+                stack_guard: None,
             }),
             ..Default::default()
         };
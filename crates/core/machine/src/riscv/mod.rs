@@ -27,7 +27,10 @@ use crate::{
 /// A module for importing all the different RISC-V chips.
 pub(crate) mod riscv_chips {
     pub use crate::{
-        alu::{AddSubChip, BitwiseChip, DivRemChip, LtChip, MulChip, ShiftLeft, ShiftRightChip},
+        alu::{
+            AddSubChip, BitManipChip, BitwiseChip, DivRemChip, LtChip, MulChip, ShiftLeft,
+            ShiftRightChip,
+        },
         bytes::ByteChip,
         cpu::CpuChip,
         memory::MemoryGlobalChip,
@@ -47,6 +50,10 @@ pub(crate) mod riscv_chips {
             },
         },
     };
+    #[cfg(feature = "aes-precompile")]
+    pub use crate::syscall::precompiles::aes::AesRoundChip;
+    #[cfg(feature = "f64-precompile")]
+    pub use crate::syscall::precompiles::float::F64OpChip;
     pub use monerochan_curves::{
         edwards::{ed25519::Ed25519Parameters, EdwardsCurve},
         weierstrass::{
@@ -84,6 +91,9 @@ pub enum RiscvAir<F: PrimeField32> {
     DivRem(DivRemChip),
     /// An AIR for RISC-V Lt instruction.
     Lt(LtChip),
+    /// An AIR for RISC-V Zbb bit manipulation instructions.
+    #[cfg(feature = "zbb")]
+    BitManip(BitManipChip),
     /// An AIR for RISC-V SLL instruction.
     ShiftLeft(ShiftLeft),
     /// An AIR for RISC-V SRL and SRA instruction.
@@ -160,6 +170,12 @@ pub enum RiscvAir<F: PrimeField32> {
     Bn254Fp2Mul(Fp2MulAssignChip<Bn254BaseField>),
     /// A precompile for BN-254 fp2 addition/subtraction.
     Bn254Fp2AddSub(Fp2AddSubAssignChip<Bn254BaseField>),
+    /// A precompile for an AES encryption round.
+    #[cfg(feature = "aes-precompile")]
+    AesRound(AesRoundChip),
+    /// A precompile for f64 addition, multiplication and division.
+    #[cfg(feature = "f64-precompile")]
+    F64Op(F64OpChip),
 }
 
 impl<F: PrimeField32> RiscvAir<F> {
@@ -412,6 +428,28 @@ impl<F: PrimeField32> RiscvAir<F> {
         costs.insert(byte.name(), byte.cost());
         chips.push(byte);
 
+        // The chips of the optional features come last, in the order of their ids.
+        #[cfg(feature = "aes-precompile")]
+        {
+            let aes_round = Chip::new(RiscvAir::AesRound(AesRoundChip::default()));
+            costs.insert(aes_round.name(), aes_round.cost());
+            chips.push(aes_round);
+        }
+
+        #[cfg(feature = "zbb")]
+        {
+            let bitmanip = Chip::new(RiscvAir::BitManip(BitManipChip::default()));
+            costs.insert(bitmanip.name(), bitmanip.cost());
+            chips.push(bitmanip);
+        }
+
+        #[cfg(feature = "f64-precompile")]
+        {
+            let f64_op = Chip::new(RiscvAir::F64Op(F64OpChip::default()));
+            costs.insert(f64_op.name(), f64_op.cost());
+            chips.push(f64_op);
+        }
+
         assert_eq!(chips.len(), costs.len(), "chips and costs must have the same length",);
 
         (chips, costs)
@@ -438,6 +476,8 @@ impl<F: PrimeField32> RiscvAir<F> {
             RiscvAir::Mul(MulChip::default()),
             RiscvAir::DivRem(DivRemChip::default()),
             RiscvAir::Lt(LtChip::default()),
+            #[cfg(feature = "zbb")]
+            RiscvAir::BitManip(BitManipChip::default()),
             RiscvAir::ShiftLeft(ShiftLeft::default()),
             RiscvAir::ShiftRight(ShiftRightChip::default()),
             RiscvAir::Memory(MemoryInstructionsChip::default()),
@@ -527,6 +567,8 @@ impl From<RiscvAirDiscriminants> for RiscvAirId {
             RiscvAirDiscriminants::Mul => RiscvAirId::Mul,
             RiscvAirDiscriminants::DivRem => RiscvAirId::DivRem,
             RiscvAirDiscriminants::Lt => RiscvAirId::Lt,
+            #[cfg(feature = "zbb")]
+            RiscvAirDiscriminants::BitManip => RiscvAirId::BitManip,
             RiscvAirDiscriminants::ShiftLeft => RiscvAirId::ShiftLeft,
             RiscvAirDiscriminants::ShiftRight => RiscvAirId::ShiftRight,
             RiscvAirDiscriminants::Memory => RiscvAirId::MemoryInstrs,
@@ -565,6 +607,10 @@ impl From<RiscvAirDiscriminants> for RiscvAirId {
             RiscvAirDiscriminants::Bn254Fp => RiscvAirId::Bn254FpOpAssign,
             RiscvAirDiscriminants::Bn254Fp2Mul => RiscvAirId::Bn254Fp2MulAssign,
             RiscvAirDiscriminants::Bn254Fp2AddSub => RiscvAirId::Bn254Fp2AddSubAssign,
+            #[cfg(feature = "aes-precompile")]
+            RiscvAirDiscriminants::AesRound => RiscvAirId::AesRound,
+            #[cfg(feature = "f64-precompile")]
+            RiscvAirDiscriminants::F64Op => RiscvAirId::F64OpAssign,
         }
     }
 }
@@ -581,8 +627,6 @@ pub mod tests {
     };
 
     use crate::programs::tests::*;
-    use hashbrown::HashMap;
-    use itertools::Itertools;
     use p3_baby_bear::BabyBear;
     use monerochan_core_executor::{Instruction, Opcode, Program, RiscvAirId, MONEROCHANContext};
     use monerochan_stark::{
@@ -593,18 +637,25 @@ pub mod tests {
     #[test]
     fn test_primitives_and_machine_air_names_match() {
         let chips = RiscvAir::<BabyBear>::chips();
-        for (a, b) in chips.iter().zip_eq(RiscvAirId::iter()) {
-            assert_eq!(a.name(), b.to_string());
+        // The chips are in the order of their ids, which skip the chips of disabled features.
+        let mut ids = RiscvAirId::iter();
+        for chip in chips.iter() {
+            assert!(ids.any(|id| id.to_string() == chip.name()), "{} is out of order", chip.name());
         }
     }
 
+    /// The AES precompile adds a column to the byte chip, whose cost in the file is the one of the
+    /// default build.
     #[test]
+    #[cfg(not(feature = "aes-precompile"))]
     fn core_air_cost_consistency() {
         // Load air costs from file
         let file = std::fs::File::open("../executor/src/artifacts/rv32im_costs.json").unwrap();
-        let costs: HashMap<String, u64> = serde_json::from_reader(file).unwrap();
+        let mut costs: hashbrown::HashMap<String, u64> = serde_json::from_reader(file).unwrap();
         // Compare with costs computed by machine
         let machine_costs = RiscvAir::<BabyBear>::costs();
+        // The file also lists the costs of the chips of the disabled features.
+        costs.retain(|name, _| machine_costs.contains_key(name));
         assert_eq!(costs, machine_costs);
     }
 
@@ -791,6 +842,36 @@ pub mod tests {
         .unwrap();
     }
 
+    #[test]
+    fn test_fibonacci_prove_out_of_order_batches() {
+        setup_logger();
+
+        // Batches of a single shard, traced by several workers, reach the prover out of order.
+        let program = fibonacci_program();
+        let stdin = MONEROCHANStdin::new();
+        let mut opts = MONEROCHANCoreOpts::default();
+        opts.shard_size = 1024;
+        opts.shard_batch_size = 1;
+        opts.trace_gen_workers = 4;
+
+        let config = BabyBearPoseidon2::new();
+        let machine = RiscvAir::machine(config);
+        let prover = CpuProver::new(machine);
+        let (pk, vk) = prover.setup(&program);
+        prove_core::<_, _>(
+            &prover,
+            &pk,
+            &vk,
+            program,
+            &stdin,
+            opts,
+            MONEROCHANContext::default(),
+            None,
+            None,
+        )
+        .unwrap();
+    }
+
     #[test]
     fn test_fibonacci_prove_batch() {
         setup_logger();
@@ -816,6 +897,38 @@ pub mod tests {
         .unwrap();
     }
 
+    #[test]
+    fn test_fibonacci_prove_seeded_is_reproducible() {
+        setup_logger();
+        let program = fibonacci_program();
+        let stdin = MONEROCHANStdin::new();
+
+        let mut opts = MONEROCHANCoreOpts::default();
+        opts.seed = Some(1);
+        let config = BabyBearPoseidon2::new();
+        let machine = RiscvAir::machine(config);
+        let prover = CpuProver::new(machine);
+        let (pk, vk) = prover.setup(&program);
+        let proofs = (0..2)
+            .map(|_| {
+                let (proof, _, _) = prove_core::<_, _>(
+                    &prover,
+                    &pk,
+                    &vk,
+                    program.clone(),
+                    &stdin,
+                    opts,
+                    MONEROCHANContext::default(),
+                    None,
+                    None,
+                )
+                .unwrap();
+                bincode::serialize(&proof).unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(proofs[0], proofs[1]);
+    }
+
     #[test]
     fn test_simple_memory_program_prove() {
         setup_logger();
@@ -36,6 +36,22 @@ const MAXIMAL_SHAPES: &[u8] = include_bytes!("maximal_shapes.json");
 /// These shapes are used to optimize performance for smaller programs.
 const SMALL_SHAPES: &[u8] = include_bytes!("small_shapes.json");
 
+/// The allowed log2 heights of the program chip, which bound the number of instructions of a
+/// program.
+const PROGRAM_LOG2_HEIGHTS: [usize; 4] = [19, 20, 21, 22];
+
+/// The log2 heights of the program chip allowed by [`CoreShapeConfig::with_large_programs`].
+const LARGE_PROGRAM_LOG2_HEIGHTS: [usize; 2] = [23, 24];
+
+/// The log2 heights of the chips proving divisions in the shapes allowed by
+/// [`CoreShapeConfig::with_division_heavy_shapes`], as gaps from the log2 height of the cpu chip.
+const DIVISION_HEAVY_LOG2_GAPS: [(RiscvAirId, usize); 4] = [
+    (RiscvAirId::DivRem, 2),
+    (RiscvAirId::Mul, 1),
+    (RiscvAirId::AddSub, 0),
+    (RiscvAirId::Lt, 1),
+];
+
 /// A configuration for what shapes are allowed to be used by the prover.
 #[derive(Debug)]
 pub struct CoreShapeConfig<F: PrimeField32> {
@@ -56,6 +72,13 @@ impl<F: PrimeField32> CoreShapeConfig<F> {
             return Err(CoreShapeError::PreprocessedShapeAlreadyFixed);
         }
 
+        // Check the size of the program first, since it is the usual reason for no shape to fit.
+        let size = program.instructions.len();
+        let max = self.max_program_size();
+        if size > max {
+            return Err(CoreShapeError::ProgramTooLarge { size, max });
+        }
+
         // Get the heights of the preprocessed chips and find a shape that fits.
         let preprocessed_heights = RiscvAir::<F>::preprocessed_heights(program);
         let preprocessed_shape = self
@@ -69,6 +92,71 @@ impl<F: PrimeField32> CoreShapeConfig<F> {
         Ok(())
     }
 
+    /// The maximum number of instructions of a program.
+    #[must_use]
+    pub fn max_program_size(&self) -> usize {
+        self.partial_preprocessed_shapes
+            .iter()
+            .filter(|(air, _)| **air == RiscvAirId::Program)
+            .flat_map(|(_, log2_heights)| log2_heights.iter().flatten())
+            .map(|log2_height| 1 << log2_height)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Allow programs of up to `2^24` instructions, instead of `2^22`.
+    ///
+    /// The verification keys of the shapes of these larger programs are not among the allowed
+    /// recursion verification keys, so their proofs can only be compressed with `VERIFY_VK=false`.
+    #[must_use]
+    pub fn with_large_programs(mut self) -> Self {
+        let mut log2_heights = self
+            .partial_preprocessed_shapes
+            .iter()
+            .map(|(air, log2_heights)| (*air, log2_heights.clone()))
+            .collect::<HashMap<_, _>>();
+        log2_heights.insert(
+            RiscvAirId::Program,
+            PROGRAM_LOG2_HEIGHTS.into_iter().chain(LARGE_PROGRAM_LOG2_HEIGHTS).map(Some).collect(),
+        );
+        self.partial_preprocessed_shapes = ShapeCluster::new(log2_heights);
+        self
+    }
+
+    /// Allow shapes with room for a division every four cycles.
+    ///
+    /// The divrem chip of the maximal shapes is at most `2^12` rows tall, so the shards of
+    /// division-heavy programs are cut long before the cpu chip is full. For each shard size, this
+    /// adds a shape derived from the maximal shape with the tallest mul chip, since every division
+    /// is also proven by two multiplications, two additions and a comparison.
+    ///
+    /// The verification keys of these shapes are not among the allowed recursion verification
+    /// keys, so their proofs can only be compressed with `VERIFY_VK=false`.
+    #[must_use]
+    pub fn with_division_heavy_shapes(mut self) -> Self {
+        let maximal_shapes: BTreeMap<usize, Vec<Shape<RiscvAirId>>> =
+            serde_json::from_slice(MAXIMAL_SHAPES).unwrap();
+        for (log2_shard_size, maximal_shapes) in maximal_shapes {
+            let Some(clusters) = self.partial_core_shapes.get_mut(&log2_shard_size) else {
+                continue;
+            };
+            let Some(mut shape) = maximal_shapes
+                .into_iter()
+                .max_by_key(|shape| shape.log2_height(&RiscvAirId::Mul))
+            else {
+                continue;
+            };
+
+            let cpu_log2_height = shape.log2_height(&RiscvAirId::Cpu).unwrap();
+            for (air, log2_gap) in DIVISION_HEAVY_LOG2_GAPS {
+                let log2_height = shape.log2_height(&air).unwrap_or_default();
+                shape.insert(air, log2_height.max(cpu_log2_height - log2_gap));
+            }
+            clusters.push(derive_cluster_from_maximal_shape(&shape));
+        }
+        self
+    }
+
     /// Fix the shape of the proof.
     pub fn fix_shape(&self, record: &mut ExecutionRecord) -> Result<(), CoreShapeError> {
         if record.program.preprocessed_shape.is_none() {
@@ -460,7 +548,7 @@ impl<F: PrimeField32> Default for CoreShapeConfig<F> {
 
         // Set the allowed preprocessed log2 heights.
         let allowed_preprocessed_log2_heights = HashMap::from([
-            (RiscvAirId::Program, vec![Some(19), Some(20), Some(21), Some(22)]),
+            (RiscvAirId::Program, PROGRAM_LOG2_HEIGHTS.map(Some).to_vec()),
             (RiscvAirId::Byte, vec![Some(16)]),
         ]);
 
@@ -522,7 +610,17 @@ impl<F: PrimeField32> Default for CoreShapeConfig<F> {
             partial_small_shapes: small_shapes
                 .into_iter()
                 .map(|x| {
-                    ShapeCluster::new(x.into_iter().map(|(k, v)| (k, vec![Some(v)])).collect())
+                    // The small shapes do not list the bitmanip chip, which is either absent or
+                    // as tall as the bitwise chip.
+                    let bitmanip_log2_heights = cfg!(feature = "zbb").then(|| {
+                        (RiscvAirId::BitManip, vec![None, x.log2_height(&RiscvAirId::Bitwise)])
+                    });
+                    ShapeCluster::new(
+                        x.into_iter()
+                            .map(|(k, v)| (k, vec![Some(v)]))
+                            .chain(bitmanip_log2_heights)
+                            .collect(),
+                    )
                 })
                 .collect(),
             costs: serde_json::from_str(include_str!("rv32im_costs.json"))
@@ -584,6 +682,18 @@ fn derive_cluster_from_maximal_shape(shape: &Shape<RiscvAirId>) -> ShapeCluster<
     let shift_left_log_height = shape.log2_height(&RiscvAirId::ShiftLeft);
     maybe_log2_heights.insert(RiscvAirId::ShiftLeft, heuristic(shift_left_log_height, 1));
 
+    // The maximal shapes do not list the bitmanip chip, which is at most as tall as the bitwise
+    // chip. Programs that are not compiled with the bit manipulation extension have no bitmanip
+    // events, so the chip is allowed to be absent from every shape.
+    #[cfg(feature = "zbb")]
+    {
+        let mut bitmanip_log_heights = heuristic(bitwise_log_height, 1);
+        if bitwise_log_height.is_some() {
+            bitmanip_log_heights.insert(0, None);
+        }
+        maybe_log2_heights.insert(RiscvAirId::BitManip, bitmanip_log_heights);
+    }
+
     let memory_instrs_log_height = shape.log2_height(&RiscvAirId::MemoryInstrs);
     maybe_log2_heights.insert(RiscvAirId::MemoryInstrs, heuristic(memory_instrs_log_height, 0));
 
@@ -614,6 +724,12 @@ fn derive_cluster_from_maximal_shape(shape: &Shape<RiscvAirId>) -> ShapeCluster<
 pub enum CoreShapeError {
     #[error("no preprocessed shape found")]
     PreprocessedShapeError,
+    #[error(
+        "the program has {size} instructions ({} bytes of text), {} more than the maximum of {max}",
+        size * 4,
+        size - max
+    )]
+    ProgramTooLarge { size: usize, max: usize },
     #[error("Preprocessed shape already fixed")]
     PreprocessedShapeAlreadyFixed,
     #[error("no shape found {0:?}")]
@@ -684,6 +800,46 @@ pub mod tests {
         assert!(num_shapes < 1 << 24);
     }
 
+    #[test]
+    fn test_program_too_large() {
+        use p3_baby_bear::BabyBear;
+
+        let shape_config = CoreShapeConfig::<BabyBear>::default();
+        assert_eq!(shape_config.max_program_size(), 1 << 22);
+
+        let instruction = Instruction::new(Opcode::ADD, 30, 0, 0, false, false);
+        let program = Program::new(vec![instruction; (1 << 22) + 3], 1 << 5, 1 << 5);
+        let err = shape_config.fix_preprocessed_shape(&mut program.clone()).unwrap_err();
+        assert!(matches!(err, CoreShapeError::ProgramTooLarge { size: 4194307, max: 4194304 }));
+        assert!(err.to_string().contains("3 more than the maximum of 4194304"));
+
+        let shape_config = shape_config.with_large_programs();
+        assert_eq!(shape_config.max_program_size(), 1 << 24);
+        let mut program = program;
+        shape_config.fix_preprocessed_shape(&mut program).unwrap();
+        let shape = program.preprocessed_shape.unwrap();
+        assert_eq!(shape.log2_height(&RiscvAirId::Program), Some(23));
+    }
+
+    #[test]
+    fn test_division_heavy_shapes() {
+        use p3_baby_bear::BabyBear;
+
+        let max_divrem_log2_height = |shape_config: &CoreShapeConfig<BabyBear>| {
+            shape_config
+                .maximal_core_shapes(21)
+                .iter()
+                .filter_map(|shape| shape.log2_height(&RiscvAirId::DivRem))
+                .max()
+        };
+
+        let shape_config = CoreShapeConfig::<BabyBear>::default();
+        assert!(max_divrem_log2_height(&shape_config) < Some(19));
+
+        let shape_config = shape_config.with_division_heavy_shapes();
+        assert_eq!(max_divrem_log2_height(&shape_config), Some(19));
+    }
+
     #[test]
     fn test_dummy_record() {
         use crate::utils::setup_logger;
@@ -706,6 +862,8 @@ pub mod tests {
             (RiscvAirId::ShiftRight, 10),
             (RiscvAirId::ShiftLeft, 10),
             (RiscvAirId::Lt, 10),
+            #[cfg(feature = "zbb")]
+            (RiscvAirId::BitManip, 10),
             (RiscvAirId::MemoryLocal, 10),
             (RiscvAirId::SyscallCore, 10),
             (RiscvAirId::Global, 10),
@@ -59,6 +59,8 @@ impl Shapeable for ExecutionRecord {
             (RiscvAirId::ShiftRight, self.shift_right_events.len()),
             (RiscvAirId::ShiftLeft, self.shift_left_events.len()),
             (RiscvAirId::Lt, self.lt_events.len()),
+            #[cfg(feature = "zbb")]
+            (RiscvAirId::BitManip, self.bitmanip_events.len()),
             (
                 RiscvAirId::MemoryLocal,
                 self.get_local_mem_events()
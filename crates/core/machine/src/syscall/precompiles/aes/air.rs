@@ -0,0 +1,327 @@
+use crate::{
+    air::MemoryAirBuilder,
+    memory::{MemoryCols, MemoryReadCols, MemoryWriteCols},
+    utils::pad_rows_fixed,
+};
+
+use p3_air::{Air, BaseAir};
+use p3_field::{AbstractField, PrimeField32};
+use p3_matrix::{dense::RowMajorMatrix, Matrix};
+use monerochan_core_executor::{
+    events::{ByteLookupEvent, ByteRecord, PrecompileEvent, AES_SBOX},
+    syscalls::SyscallCode,
+    ByteOpcode, ExecutionRecord, Program,
+};
+use monerochan_derive::AlignedBorrow;
+use monerochan_stark::air::{InteractionScope, MachineAir, MONEROCHANAirBuilder};
+use std::{
+    borrow::{Borrow, BorrowMut},
+    mem::size_of,
+};
+
+/// The number of columns in the AesRoundCols.
+const NUM_COLS: usize = size_of::<AesRoundCols<u8>>();
+
+/// The number of words in the AES state and in a round key.
+const STATE_NUM_WORDS: usize = 4;
+
+/// The number of bytes in the AES state and in a round key.
+const STATE_NUM_BYTES: usize = 16;
+
+#[derive(Default)]
+pub struct AesRoundChip;
+
+impl AesRoundChip {
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+/// A set of columns for the AES encryption round operation.
+///
+/// The state is stored column by column, so that byte `r + 4 * c` of the state is in row `r` and
+/// column `c`, and is byte `r` of word `c` in memory.
+#[derive(Debug, Clone, AlignedBorrow)]
+#[repr(C)]
+pub struct AesRoundCols<T> {
+    /// The shard number of the syscall.
+    pub shard: T,
+
+    /// The clock cycle of the syscall.
+    pub clk: T,
+
+    /// The pointer to the state.
+    pub state_ptr: T,
+
+    /// The pointer to the round key.
+    pub round_key_ptr: T,
+
+    // Memory columns.
+    // state_memory is written to with the output of the round, which is why it is of type
+    // MemoryWriteCols.
+    pub state_memory: [MemoryWriteCols<T>; STATE_NUM_WORDS],
+    pub round_key_memory: [MemoryReadCols<T>; STATE_NUM_WORDS],
+
+    /// The state after `SubBytes` and `ShiftRows`.
+    pub sbox: [T; STATE_NUM_BYTES],
+
+    /// The most significant bit of each byte of `sbox`.
+    pub msb: [T; STATE_NUM_BYTES],
+
+    /// Each byte of `sbox` multiplied by `x` in GF(2^8).
+    pub xtime: [T; STATE_NUM_BYTES],
+
+    /// The partial xors of the `MixColumns` and `AddRoundKey` terms of each output byte. The last
+    /// xor is the output byte itself, which is written to `state_memory`.
+    pub xor: [[T; 4]; STATE_NUM_BYTES],
+
+    pub is_real: T,
+}
+
+impl<F: PrimeField32> MachineAir<F> for AesRoundChip {
+    type Record = ExecutionRecord;
+    type Program = Program;
+
+    fn name(&self) -> String {
+        "AesRound".to_string()
+    }
+
+    fn generate_trace(
+        &self,
+        input: &ExecutionRecord,
+        output: &mut ExecutionRecord,
+    ) -> RowMajorMatrix<F> {
+        let mut new_byte_lookup_events = Vec::new();
+
+        let mut rows = input
+            .get_precompile_events(SyscallCode::AES_ENCRYPT_ROUND)
+            .iter()
+            .map(|(_, event)| {
+                let event = if let PrecompileEvent::AesRound(event) = event {
+                    event
+                } else {
+                    unreachable!()
+                };
+                let mut row: [F; NUM_COLS] = [F::zero(); NUM_COLS];
+                let cols: &mut AesRoundCols<F> = row.as_mut_slice().borrow_mut();
+
+                // Assign basic values to the columns.
+                cols.is_real = F::one();
+                cols.shard = F::from_canonical_u32(event.shard);
+                cols.clk = F::from_canonical_u32(event.clk);
+                cols.state_ptr = F::from_canonical_u32(event.state_ptr);
+                cols.round_key_ptr = F::from_canonical_u32(event.round_key_ptr);
+
+                // Populate memory columns.
+                for i in 0..STATE_NUM_WORDS {
+                    cols.state_memory[i]
+                        .populate(event.state_write_records[i], &mut new_byte_lookup_events);
+                    cols.round_key_memory[i]
+                        .populate(event.round_key_read_records[i], &mut new_byte_lookup_events);
+                }
+
+                let state = event.state.map(u32::to_le_bytes);
+                let round_key = event.round_key.map(u32::to_le_bytes);
+
+                // SubBytes and ShiftRows, and the doubling of each byte for MixColumns.
+                let mut sbox = [0u8; STATE_NUM_BYTES];
+                let mut xtime = [0u8; STATE_NUM_BYTES];
+                for c in 0..4 {
+                    for r in 0..4 {
+                        let i = r + 4 * c;
+                        let byte = state[(c + r) % 4][r];
+                        sbox[i] = AES_SBOX[byte as usize];
+                        let msb = sbox[i] >> 7;
+                        let shifted = sbox[i] << 1;
+                        xtime[i] = shifted ^ (0x1b * msb);
+
+                        cols.sbox[i] = F::from_canonical_u8(sbox[i]);
+                        cols.msb[i] = F::from_canonical_u8(msb);
+                        cols.xtime[i] = F::from_canonical_u8(xtime[i]);
+
+                        new_byte_lookup_events.add_byte_lookup_events(vec![
+                            ByteLookupEvent::new(ByteOpcode::AesSbox, sbox[i] as u16, 0, byte, 0),
+                            ByteLookupEvent::new(ByteOpcode::MSB, msb as u16, 0, sbox[i], 0),
+                            ByteLookupEvent::new(
+                                ByteOpcode::XOR,
+                                xtime[i] as u16,
+                                0,
+                                shifted,
+                                0x1b * msb,
+                            ),
+                        ]);
+                    }
+                }
+
+                // MixColumns and AddRoundKey.
+                for c in 0..4 {
+                    for r in 0..4 {
+                        let i = r + 4 * c;
+                        let terms = mix_column_terms(&sbox, &xtime, round_key[c][r], c, r);
+                        let mut acc = terms[0];
+                        for (j, term) in terms[1..].iter().enumerate() {
+                            let xor = acc ^ term;
+                            new_byte_lookup_events.add_byte_lookup_event(ByteLookupEvent::new(
+                                ByteOpcode::XOR,
+                                xor as u16,
+                                0,
+                                acc,
+                                *term,
+                            ));
+                            if j < 4 {
+                                cols.xor[i][j] = F::from_canonical_u8(xor);
+                            }
+                            acc = xor;
+                        }
+                        debug_assert_eq!(acc, event.state_write_records[c].value.to_le_bytes()[r]);
+                    }
+                }
+
+                row
+            })
+            .collect::<Vec<_>>();
+
+        output.add_byte_lookup_events(new_byte_lookup_events);
+
+        pad_rows_fixed(&mut rows, || [F::zero(); NUM_COLS], input.fixed_log2_rows::<F, _>(self));
+
+        // Convert the trace to a row major matrix.
+        RowMajorMatrix::new(rows.into_iter().flatten().collect::<Vec<_>>(), NUM_COLS)
+    }
+
+    fn included(&self, shard: &Self::Record) -> bool {
+        if let Some(shape) = shard.shape.as_ref() {
+            shape.included::<F, _>(self)
+        } else {
+            !shard.get_precompile_events(SyscallCode::AES_ENCRYPT_ROUND).is_empty()
+        }
+    }
+
+    fn local_only(&self) -> bool {
+        true
+    }
+}
+
+/// Returns the terms whose xor is byte `r` of column `c` of the output of the round.
+///
+/// `MixColumns` computes `2 * t[r] + 3 * t[r + 1] + t[r + 2] + t[r + 3]` in GF(2^8), where `t` is
+/// the column, and `3 * t = xtime(t) ^ t`. The last term is the round key byte.
+fn mix_column_terms<T: Copy>(sbox: &[T], xtime: &[T], key: T, c: usize, r: usize) -> [T; 6] {
+    let t = |offset: usize| sbox[4 * c + (r + offset) % 4];
+    let x = |offset: usize| xtime[4 * c + (r + offset) % 4];
+    [x(0), x(1), t(1), t(2), t(3), key]
+}
+
+impl<F> BaseAir<F> for AesRoundChip {
+    fn width(&self) -> usize {
+        NUM_COLS
+    }
+}
+
+impl<AB> Air<AB> for AesRoundChip
+where
+    AB: MONEROCHANAirBuilder,
+{
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+        let local: &AesRoundCols<AB::Var> = (*local).borrow();
+
+        // SubBytes and ShiftRows. The input state is the "prev_value" of the state_memory, since
+        // we write to it later.
+        for c in 0..4 {
+            for r in 0..4 {
+                let i = r + 4 * c;
+                builder.send_byte(
+                    ByteOpcode::AesSbox.as_field::<AB::F>(),
+                    local.sbox[i],
+                    local.state_memory[(c + r) % 4].prev_value[r],
+                    AB::Expr::zero(),
+                    local.is_real,
+                );
+
+                // Compute `xtime = (sbox << 1) ^ (msb * 0x1b)`, the byte lookups guarantee that
+                // `sbox << 1` is a byte and hence that `msb` is the dropped bit.
+                builder.send_byte(
+                    ByteOpcode::MSB.as_field::<AB::F>(),
+                    local.msb[i],
+                    local.sbox[i],
+                    AB::Expr::zero(),
+                    local.is_real,
+                );
+                builder.send_byte(
+                    ByteOpcode::XOR.as_field::<AB::F>(),
+                    local.xtime[i],
+                    local.sbox[i] * AB::F::from_canonical_u32(2) -
+                        local.msb[i] * AB::F::from_canonical_u32(256),
+                    local.msb[i] * AB::F::from_canonical_u32(0x1b),
+                    local.is_real,
+                );
+            }
+        }
+
+        // MixColumns and AddRoundKey, as a chain of byte xors ending in the written output byte.
+        for c in 0..4 {
+            for r in 0..4 {
+                let i = r + 4 * c;
+                let terms = mix_column_terms(
+                    &local.sbox,
+                    &local.xtime,
+                    local.round_key_memory[c].value()[r],
+                    c,
+                    r,
+                );
+                let results = [
+                    local.xor[i][0],
+                    local.xor[i][1],
+                    local.xor[i][2],
+                    local.xor[i][3],
+                    local.state_memory[c].value()[r],
+                ];
+                let mut acc = terms[0];
+                for (term, result) in terms[1..].iter().zip(results) {
+                    builder.send_byte(
+                        ByteOpcode::XOR.as_field::<AB::F>(),
+                        result,
+                        acc,
+                        *term,
+                        local.is_real,
+                    );
+                    acc = result;
+                }
+            }
+        }
+
+        // Read the round key.
+        builder.eval_memory_access_slice(
+            local.shard,
+            local.clk.into(),
+            local.round_key_ptr,
+            &local.round_key_memory,
+            local.is_real,
+        );
+
+        // Read and write the state.
+        builder.eval_memory_access_slice(
+            local.shard,
+            local.clk.into() + AB::Expr::one(),
+            local.state_ptr,
+            &local.state_memory,
+            local.is_real,
+        );
+
+        // Receive the arguments.
+        builder.receive_syscall(
+            local.shard,
+            local.clk,
+            AB::F::from_canonical_u32(SyscallCode::AES_ENCRYPT_ROUND.syscall_id()),
+            local.state_ptr,
+            local.round_key_ptr,
+            local.is_real,
+            InteractionScope::Local,
+        );
+
+        // Assert that is_real is a boolean.
+        builder.assert_bool(local.is_real);
+    }
+}
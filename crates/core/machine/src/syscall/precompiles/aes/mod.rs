@@ -0,0 +1,22 @@
+mod air;
+
+pub use air::*;
+
+#[cfg(test)]
+mod tests {
+    use monerochan_core_executor::Program;
+    use monerochan_stark::CpuProver;
+    use test_artifacts::AES_ROUND_ELF;
+
+    use crate::{
+        io::MONEROCHANStdin,
+        utils::{self, run_test},
+    };
+
+    #[test]
+    fn test_aes_round() {
+        utils::setup_logger();
+        let program = Program::from(AES_ROUND_ELF).unwrap();
+        run_test::<CpuProver<_, _>>(program, MONEROCHANStdin::new()).unwrap();
+    }
+}
@@ -0,0 +1,904 @@
+use crate::{
+    air::{MemoryAirBuilder, WordAirBuilder},
+    memory::{MemoryCols, MemoryReadCols, MemoryWriteCols},
+    operations::{field::util::compute_root_quotient_and_shift, IsZeroOperation},
+    utils::{pad_rows_fixed, zeroed_f_vec},
+};
+
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_field::{AbstractField, Field, PrimeField32};
+use p3_matrix::{dense::RowMajorMatrix, Matrix};
+use monerochan_core_executor::{
+    events::{ByteLookupEvent, ByteRecord, F64OpEvent, F64Operation, PrecompileEvent},
+    syscalls::SyscallCode,
+    ByteOpcode, ExecutionRecord, Program,
+};
+use monerochan_derive::AlignedBorrow;
+use monerochan_stark::{
+    air::{BaseAirBuilder, InteractionScope, MachineAir, Polynomial, MONEROCHANAirBuilder},
+    Word,
+};
+use std::{
+    borrow::{Borrow, BorrowMut},
+    iter::once,
+    mem::size_of,
+};
+
+/// The number of columns in the F64OpCols.
+const NUM_COLS: usize = size_of::<F64OpCols<u8>>();
+
+/// The number of byte limbs of a significand, including its implicit leading bit.
+const SIGNIFICAND_NUM_LIMBS: usize = 7;
+
+/// The number of byte limbs of the exact result of an operation, before rounding.
+const EXACT_NUM_LIMBS: usize = 14;
+
+/// The number of byte limbs of the rounding remainders.
+const REMAINDER_NUM_LIMBS: usize = 8;
+
+/// The smallest exponent gap of an addition whose smaller operand cannot change the rounded
+/// result.
+const FAR_GAP: usize = 55;
+
+/// The number of normalization shifts, from `-SHIFT_OFFSET` for divisions to `55` for additions.
+const NUM_SHIFTS: usize = 109;
+
+/// The offset of the normalization shifts in the one-hot encoding.
+const SHIFT_OFFSET: usize = 53;
+
+/// The exponent of the least significant bit of a significand is its biased exponent minus this.
+const SIGNIFICAND_BIAS: u32 = 1075;
+
+/// The largest biased exponent of a normal f64.
+const MAX_EXPONENT: u32 = 2046;
+
+/// The offset of the carries of the polynomial identities, which are range checked as u16.
+const WITNESS_OFFSET: usize = 1 << 15;
+
+#[derive(Default)]
+pub struct F64OpChip;
+
+impl F64OpChip {
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+/// The sign, exponent and high fraction bits of an f64, which are decomposed from its two most
+/// significant bytes.
+#[derive(AlignedBorrow, Default, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct F64Cols<T> {
+    /// The sign bit.
+    pub sign: T,
+
+    /// The seven high bits of the biased exponent.
+    pub exponent_hi: T,
+
+    /// The four low bits of the biased exponent.
+    pub exponent_lo: T,
+
+    /// The four high bits of the fraction, which are the low half of the seventh byte.
+    pub fraction_hi: T,
+}
+
+/// A set of columns for the f64 arithmetic operations.
+///
+/// A fast path result is proven as follows. The exact result of the operation is the integer
+/// `exact` times a power of two, computed from the significands `M` of the operands:
+///
+/// - `Ma * Mb` for a multiplication,
+/// - `Ma` for a division, whose divisor `Mb` is applied by the rounding,
+/// - `|±Ml * 2^gap ± Ms|` for an addition whose operands, sorted by exponent, are at most `54`
+///   exponents apart.
+///
+/// The rounded significand `Mr` of the result is then `exact * 2^-shift / divisor` rounded to
+/// nearest with ties to even, where the divisor is `Mb` for a division and `1` otherwise. This is
+/// checked as `2 * exact * scale_up + scale_down = 2 * Mr * scale_down + remainder`, where
+/// `scale_down = 2^shift * divisor` and `scale_up = 2^-shift` split the shift by sign, and
+/// `0 <= remainder <= 2 * scale_down`, whose bounds are only reached on ties. The one-hot `shift`
+/// also sets the exponent of the result, and is unique but for a rounding carry into the next
+/// binade, which is allowed by checking that the exact significand is at least `Mr - 1/4` when
+/// `Mr = 2^52`.
+///
+/// An addition whose operands are further apart returns the larger operand, and a slow path
+/// writes the all-zero sentinel.
+#[derive(AlignedBorrow, Debug, Clone)]
+#[repr(C)]
+pub struct F64OpCols<T> {
+    /// The shard number of the syscall.
+    pub shard: T,
+
+    /// The clock cycle of the syscall.
+    pub clk: T,
+
+    /// The pointer to the x operand, which is overwritten with the result.
+    pub x_ptr: T,
+
+    /// The pointer to the y operand.
+    pub y_ptr: T,
+
+    // Memory columns.
+    pub x_memory: [MemoryWriteCols<T>; 2],
+    pub y_memory: [MemoryReadCols<T>; 2],
+
+    /// The operation flags.
+    pub is_add: T,
+    pub is_mul: T,
+    pub is_div: T,
+
+    /// Whether the result is computed, rather than the all-zero sentinel.
+    pub is_fast: T,
+
+    /// The fast path flags, one for each way the result is computed.
+    pub is_fast_mul: T,
+    pub is_fast_div: T,
+    pub is_near_add: T,
+    pub is_far_add: T,
+
+    /// Whether the y operand of an addition has the larger exponent.
+    pub swap: T,
+
+    /// The decomposition of the x operand, the y operand and the result.
+    pub x: F64Cols<T>,
+    pub y: F64Cols<T>,
+    pub result: F64Cols<T>,
+
+    /// The least significant byte of the result is `2 * result_half + result_lsb`.
+    pub result_half: T,
+    pub result_lsb: T,
+
+    /// The significands of the operands of a near addition, sorted by exponent.
+    pub large_significand: [T; SIGNIFICAND_NUM_LIMBS],
+    pub small_significand: [T; SIGNIFICAND_NUM_LIMBS],
+
+    /// Whether the sign of the larger and smaller operands differ from the sign of the result.
+    pub large_negated: T,
+    pub small_negated: T,
+
+    /// The signs, as `1` or `-1`, of the terms of a near addition, and zero otherwise.
+    pub large_sign: T,
+    pub small_sign: T,
+
+    /// The one-hot exponent gap of a near addition.
+    pub near_gap: [T; FAR_GAP],
+
+    /// The exponent gap of a far addition, minus `FAR_GAP`.
+    pub far_gap: T,
+
+    /// The one-hot normalization shift, from `-SHIFT_OFFSET`, of a rounded result.
+    pub shift: [T; NUM_SHIFTS],
+
+    /// The exact result of the operation, before rounding.
+    pub exact: [T; EXACT_NUM_LIMBS],
+
+    /// The rounding remainder, and its complement to `2 * scale_down`.
+    pub remainder: [T; REMAINDER_NUM_LIMBS],
+    pub remainder_complement: [T; REMAINDER_NUM_LIMBS],
+
+    /// The excess of `2 * remainder` over `scale_down` when the rounded significand is `2^52`.
+    pub carry_slack: [T; REMAINDER_NUM_LIMBS],
+
+    /// Whether the fraction of the result is zero.
+    pub is_min_significand: IsZeroOperation<T>,
+
+    /// Whether the fraction of a rounded result is zero.
+    pub is_rounded_min_significand: T,
+
+    /// Whether the remainder or its complement is zero, which is a tie.
+    pub is_remainder_zero: IsZeroOperation<T>,
+    pub is_complement_zero: IsZeroOperation<T>,
+
+    /// The shifted carries of the polynomial identities.
+    pub exact_witness: [T; EXACT_NUM_LIMBS - 1],
+    pub rounding_witness: [T; EXACT_NUM_LIMBS + SIGNIFICAND_NUM_LIMBS - 2],
+    pub remainder_witness: [T; REMAINDER_NUM_LIMBS - 1],
+    pub carry_slack_witness: [T; REMAINDER_NUM_LIMBS - 1],
+
+    pub is_real: T,
+}
+
+impl<F: PrimeField32> MachineAir<F> for F64OpChip {
+    type Record = ExecutionRecord;
+    type Program = Program;
+
+    fn name(&self) -> String {
+        "F64OpAssign".to_string()
+    }
+
+    fn generate_trace(
+        &self,
+        input: &ExecutionRecord,
+        output: &mut ExecutionRecord,
+    ) -> RowMajorMatrix<F> {
+        let mut new_byte_lookup_events = Vec::new();
+
+        // All the f64 events are coalesced to the addition syscall code.
+        let mut rows = input
+            .get_precompile_events(SyscallCode::F64_ADD)
+            .iter()
+            .map(|(_, event)| {
+                let event =
+                    if let PrecompileEvent::F64Op(event) = event { event } else { unreachable!() };
+                let mut row = zeroed_f_vec(NUM_COLS);
+                let cols: &mut F64OpCols<F> = row.as_mut_slice().borrow_mut();
+                Self::populate_row(event, cols, &mut new_byte_lookup_events);
+                row
+            })
+            .collect::<Vec<_>>();
+
+        output.add_byte_lookup_events(new_byte_lookup_events);
+
+        pad_rows_fixed(
+            &mut rows,
+            || {
+                let mut row = zeroed_f_vec(NUM_COLS);
+                let cols: &mut F64OpCols<F> = row.as_mut_slice().borrow_mut();
+                Self::populate_witnesses(cols, &mut Vec::<ByteLookupEvent>::new());
+                row
+            },
+            input.fixed_log2_rows::<F, _>(self),
+        );
+
+        // Convert the trace to a row major matrix.
+        RowMajorMatrix::new(rows.into_iter().flatten().collect::<Vec<_>>(), NUM_COLS)
+    }
+
+    fn included(&self, shard: &Self::Record) -> bool {
+        // All the f64 events are coalesced to the addition syscall code.
+        assert!(
+            shard.get_precompile_events(SyscallCode::F64_MUL).is_empty() &&
+                shard.get_precompile_events(SyscallCode::F64_DIV).is_empty()
+        );
+
+        if let Some(shape) = shard.shape.as_ref() {
+            shape.included::<F, _>(self)
+        } else {
+            !shard.get_precompile_events(SyscallCode::F64_ADD).is_empty()
+        }
+    }
+
+    fn local_only(&self) -> bool {
+        true
+    }
+}
+
+impl F64OpChip {
+    fn populate_row<F: PrimeField32>(
+        event: &F64OpEvent,
+        cols: &mut F64OpCols<F>,
+        blu: &mut Vec<ByteLookupEvent>,
+    ) {
+        cols.is_real = F::one();
+        cols.shard = F::from_canonical_u32(event.shard);
+        cols.clk = F::from_canonical_u32(event.clk);
+        cols.x_ptr = F::from_canonical_u32(event.x_ptr);
+        cols.y_ptr = F::from_canonical_u32(event.y_ptr);
+        for i in 0..2 {
+            cols.x_memory[i].populate(event.x_memory_records[i], blu);
+            cols.y_memory[i].populate(event.y_memory_records[i], blu);
+        }
+
+        let x = words_to_u64(event.x);
+        let y = words_to_u64(event.y);
+        let result = words_to_u64(event.x_memory_records.map(|record| record.value));
+        cols.x.populate(x, blu);
+        cols.y.populate(y, blu);
+        cols.result.populate(result, blu);
+
+        cols.is_add = F::from_bool(event.op == F64Operation::Add);
+        cols.is_mul = F::from_bool(event.op == F64Operation::Mul);
+        cols.is_div = F::from_bool(event.op == F64Operation::Div);
+
+        let result_bytes = result.to_le_bytes();
+        cols.result_half = F::from_canonical_u8(result_bytes[0] >> 1);
+        cols.result_lsb = F::from_canonical_u8(result_bytes[0] & 1);
+        blu.add_u8_range_checks(&result_bytes[..6]);
+        blu.add_u8_range_check(result_bytes[0] >> 1, 0);
+
+        let mut exact = 0u128;
+        let mut remainder = 0u128;
+        let mut complement = 0u128;
+        let mut carry_slack = 0u128;
+        let mut swap = false;
+        // A zero result is the sentinel, the executor never accelerates a zero result.
+        if result != 0 {
+            cols.is_fast = F::one();
+            let (x_exponent, y_exponent) = (exponent(x), exponent(y));
+            let (x_significand, y_significand) = (significand(x), significand(y));
+            blu.add_u16_range_checks(&[
+                (x_exponent - 1) as u16,
+                (MAX_EXPONENT - x_exponent) as u16,
+                (y_exponent - 1) as u16,
+                (MAX_EXPONENT - y_exponent) as u16,
+            ]);
+
+            // The exponent of the least significant bit of `exact`, when it is rounded.
+            let base_exponent = match event.op {
+                F64Operation::Mul => {
+                    cols.is_fast_mul = F::one();
+                    exact = x_significand * y_significand;
+                    Some(x_exponent as i32 + y_exponent as i32 - SIGNIFICAND_BIAS as i32)
+                }
+                F64Operation::Div => {
+                    cols.is_fast_div = F::one();
+                    exact = x_significand;
+                    Some(x_exponent as i32 - y_exponent as i32 + SIGNIFICAND_BIAS as i32)
+                }
+                F64Operation::Add => {
+                    swap = y_exponent > x_exponent;
+                    let (large, small) = if swap { (y, x) } else { (x, y) };
+                    let gap = (exponent(large) - exponent(small)) as usize;
+                    if gap >= FAR_GAP {
+                        cols.is_far_add = F::one();
+                        cols.far_gap = F::from_canonical_usize(gap - FAR_GAP);
+                        blu.add_u16_range_check((gap - FAR_GAP) as u16);
+                        None
+                    } else {
+                        cols.is_near_add = F::one();
+                        cols.near_gap[gap] = F::one();
+                        let large_significand = significand(large);
+                        let small_significand = significand(small);
+                        for i in 0..SIGNIFICAND_NUM_LIMBS {
+                            cols.large_significand[i] =
+                                F::from_canonical_u8(large_significand.to_le_bytes()[i]);
+                            cols.small_significand[i] =
+                                F::from_canonical_u8(small_significand.to_le_bytes()[i]);
+                        }
+                        // The terms are signed relative to the sign of the result.
+                        let signed = |term: u128, operand: u64| {
+                            if (operand ^ result) >> 63 == 1 {
+                                -(term as i128)
+                            } else {
+                                term as i128
+                            }
+                        };
+                        let sum = signed(large_significand << gap, large) +
+                            signed(small_significand, small);
+                        debug_assert!(sum > 0);
+                        exact = sum as u128;
+                        Some(exponent(small) as i32)
+                    }
+                }
+            };
+
+            if let Some(base_exponent) = base_exponent {
+                let result_exponent = exponent(result);
+                let result_significand = significand(result);
+                blu.add_u16_range_checks(&[
+                    (result_exponent - 1) as u16,
+                    (MAX_EXPONENT - result_exponent) as u16,
+                ]);
+
+                let shift = result_exponent as i32 - base_exponent;
+                debug_assert!((0..NUM_SHIFTS as i32).contains(&(shift + SHIFT_OFFSET as i32)));
+                cols.shift[(shift + SHIFT_OFFSET as i32) as usize] = F::one();
+
+                let scale_up = 1u128 << (-shift).max(0);
+                let scale_down = if event.op == F64Operation::Div {
+                    y_significand
+                } else {
+                    1u128 << shift.max(0)
+                };
+                remainder = 2 * exact * scale_up + scale_down - 2 * result_significand * scale_down;
+                complement = 2 * scale_down - remainder;
+                if result_significand == 1 << 52 {
+                    cols.is_rounded_min_significand = F::one();
+                    carry_slack = 2 * remainder - scale_down;
+                }
+            }
+        }
+
+        // The sign columns of the addition terms, which are constrained on all rows.
+        cols.swap = F::from_bool(swap);
+        let (large, small) = if swap { (y, x) } else { (x, y) };
+        let large_negated = (large ^ result) >> 63 == 1;
+        let small_negated = (small ^ result) >> 63 == 1;
+        cols.large_negated = F::from_bool(large_negated);
+        cols.small_negated = F::from_bool(small_negated);
+        if cols.is_near_add == F::one() {
+            cols.large_sign = if large_negated { F::neg_one() } else { F::one() };
+            cols.small_sign = if small_negated { F::neg_one() } else { F::one() };
+        }
+
+        let fraction_hi = ((result >> 48) & 0xf) as u32;
+        cols.is_min_significand.populate(
+            result_bytes[..6].iter().map(|&byte| u32::from(byte)).sum::<u32>() + fraction_hi,
+        );
+
+        let exact = exact.to_le_bytes();
+        let remainder = remainder.to_le_bytes();
+        let complement = complement.to_le_bytes();
+        let carry_slack = carry_slack.to_le_bytes();
+        for i in 0..EXACT_NUM_LIMBS {
+            cols.exact[i] = F::from_canonical_u8(exact[i]);
+        }
+        for i in 0..REMAINDER_NUM_LIMBS {
+            cols.remainder[i] = F::from_canonical_u8(remainder[i]);
+            cols.remainder_complement[i] = F::from_canonical_u8(complement[i]);
+            cols.carry_slack[i] = F::from_canonical_u8(carry_slack[i]);
+        }
+        blu.add_u8_range_checks(&exact[..EXACT_NUM_LIMBS]);
+        blu.add_u8_range_checks(&remainder[..REMAINDER_NUM_LIMBS]);
+        blu.add_u8_range_checks(&complement[..REMAINDER_NUM_LIMBS]);
+        blu.add_u8_range_checks(&carry_slack[..REMAINDER_NUM_LIMBS]);
+        cols.is_remainder_zero.populate(remainder.iter().map(|&byte| u32::from(byte)).sum::<u32>());
+        cols.is_complement_zero
+            .populate(complement.iter().map(|&byte| u32::from(byte)).sum::<u32>());
+
+        Self::populate_witnesses(cols, blu);
+    }
+
+    /// Populates the carries of the polynomial identities, which are zero on padding rows and on
+    /// slow path rows, and hence equal to the offset.
+    fn populate_witnesses<F: PrimeField32>(cols: &mut F64OpCols<F>, blu: &mut impl ByteRecord) {
+        let [exact, rounding, remainder, carry_slack] = cols.vanishing_polynomials::<F>();
+        for (vanishing, witness) in [
+            (exact, &mut cols.exact_witness[..]),
+            (rounding, &mut cols.rounding_witness[..]),
+            (remainder, &mut cols.remainder_witness[..]),
+            (carry_slack, &mut cols.carry_slack_witness[..]),
+        ] {
+            let shifted =
+                compute_root_quotient_and_shift(&vanishing, WITNESS_OFFSET, 8, witness.len());
+            witness.copy_from_slice(&shifted);
+        }
+
+        if cols.is_real == F::one() {
+            let witnesses = cols
+                .exact_witness
+                .iter()
+                .chain(cols.rounding_witness.iter())
+                .chain(cols.remainder_witness.iter())
+                .chain(cols.carry_slack_witness.iter())
+                .map(|witness| witness.as_canonical_u32() as u16)
+                .collect::<Vec<_>>();
+            blu.add_u16_range_checks(&witnesses);
+        }
+    }
+}
+
+impl<F: PrimeField32> F64Cols<F> {
+    fn populate(&mut self, value: u64, blu: &mut impl ByteRecord) {
+        let bytes = value.to_le_bytes();
+        let exponent_hi = bytes[7] & 0x7f;
+        let exponent_lo = bytes[6] >> 4;
+        let fraction_hi = bytes[6] & 0xf;
+        self.sign = F::from_canonical_u8(bytes[7] >> 7);
+        self.exponent_hi = F::from_canonical_u8(exponent_hi);
+        self.exponent_lo = F::from_canonical_u8(exponent_lo);
+        self.fraction_hi = F::from_canonical_u8(fraction_hi);
+        blu.add_u8_range_check(exponent_hi, 2 * exponent_hi);
+        blu.add_u8_range_check(exponent_lo, 16 * exponent_lo);
+        blu.add_u8_range_check(fraction_hi, 16 * fraction_hi);
+    }
+}
+
+impl<F: Field> F64Cols<F> {
+    /// Constrains the decomposition of the two most significant bytes of an f64.
+    fn eval<AB: MONEROCHANAirBuilder>(
+        builder: &mut AB,
+        cols: &F64Cols<AB::Var>,
+        bytes: &[AB::Var; 8],
+        is_real: AB::Var,
+    ) {
+        builder.assert_bool(cols.sign);
+        builder.assert_eq(bytes[7], cols.sign * AB::F::from_canonical_u32(128) + cols.exponent_hi);
+        builder.assert_eq(
+            bytes[6],
+            cols.exponent_lo * AB::F::from_canonical_u32(16) + cols.fraction_hi,
+        );
+
+        // Check that `exponent_hi < 128` and that `exponent_lo, fraction_hi < 16`.
+        for (limb, scale) in [(cols.exponent_hi, 2), (cols.exponent_lo, 16), (cols.fraction_hi, 16)]
+        {
+            builder.send_byte(
+                ByteOpcode::U8Range.as_field::<AB::F>(),
+                AB::Expr::zero(),
+                limb,
+                limb * AB::F::from_canonical_u32(scale),
+                is_real,
+            );
+        }
+    }
+}
+
+impl<V: Copy> F64Cols<V> {
+    /// The biased exponent.
+    fn exponent<E: AbstractField>(&self) -> E
+    where
+        V: Into<E>,
+    {
+        let (exponent_hi, exponent_lo): (E, E) = (self.exponent_hi.into(), self.exponent_lo.into());
+        exponent_hi * E::from_canonical_u32(16) + exponent_lo
+    }
+
+    /// The significand of a normal f64, including its implicit leading bit, as byte limbs.
+    fn significand<E: AbstractField>(&self, bytes: &[V; 8]) -> Polynomial<E>
+    where
+        V: Into<E>,
+    {
+        let fraction_hi: E = self.fraction_hi.into();
+        bytes[..6]
+            .iter()
+            .map(|&byte| byte.into())
+            .chain(once(fraction_hi + E::from_canonical_u32(16)))
+            .collect()
+    }
+}
+
+impl<V: Copy> F64OpCols<V> {
+    /// Returns the bytes of the x operand, of the y operand and of the result.
+    fn bytes(&self) -> [[V; 8]; 3] {
+        let bytes = |words: [&Word<V>; 2]| core::array::from_fn(|i| words[i / 4][i % 4]);
+        [
+            bytes([&self.x_memory[0].prev_value, &self.x_memory[1].prev_value]),
+            bytes([self.y_memory[0].value(), self.y_memory[1].value()]),
+            bytes([self.x_memory[0].value(), self.x_memory[1].value()]),
+        ]
+    }
+
+    /// Returns the polynomials which vanish at `256` when the exact result, the rounding, the
+    /// remainder bound and the rounding carry slack are correct.
+    fn vanishing_polynomials<E: AbstractField>(&self) -> [Polynomial<E>; 4]
+    where
+        V: Into<E>,
+    {
+        let [x_bytes, y_bytes, result_bytes] = self.bytes();
+        let x = self.x.significand::<E>(&x_bytes);
+        let y = self.y.significand::<E>(&y_bytes);
+        let result = self.result.significand::<E>(&result_bytes);
+        let var = |var: V| -> E { var.into() };
+        let limbs = |limbs: &[V]| limbs.iter().map(|&limb| var(limb)).collect::<Polynomial<E>>();
+        let exact = limbs(&self.exact);
+        let remainder = limbs(&self.remainder);
+        let remainder_complement = limbs(&self.remainder_complement);
+        let carry_slack = limbs(&self.carry_slack);
+        let large = limbs(&self.large_significand);
+        let small = limbs(&self.small_significand);
+
+        let gap = powers_of_two(self.near_gap.iter().enumerate().map(|(i, &flag)| (i, flag)));
+        let scale_up = powers_of_two(
+            self.shift.iter().enumerate().map(|(i, &flag)| (SHIFT_OFFSET.saturating_sub(i), flag)),
+        );
+        // The divisor is `Mb` for a division, whose shift is negative, and a power of two
+        // otherwise.
+        let scale_down = (&y + E::neg_one()) * var(self.is_fast_div) +
+            &powers_of_two(
+                self.shift
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &flag)| (i.saturating_sub(SHIFT_OFFSET), flag)),
+            );
+
+        let exact_vanishing = exact.clone() -
+            &((&x * &y) * var(self.is_fast_mul)) -
+            &(x * var(self.is_fast_div)) -
+            &((&large * &gap) * var(self.large_sign)) -
+            &(small * var(self.small_sign));
+
+        let rounding_vanishing = (&exact * &scale_up) * E::two() + &scale_down -
+            &((&result * &scale_down) * E::two()) -
+            &remainder;
+
+        let remainder_vanishing =
+            remainder.clone() + &remainder_complement - &(scale_down.clone() * E::two());
+
+        let carry_slack_vanishing = carry_slack +
+            &((scale_down - &(remainder * E::two())) * var(self.is_rounded_min_significand));
+
+        [exact_vanishing, rounding_vanishing, remainder_vanishing, carry_slack_vanishing]
+    }
+}
+
+/// Returns the sum of the flags times two to the power of their exponents, as byte limbs.
+fn powers_of_two<E: AbstractField, V: Into<E>>(
+    terms: impl Iterator<Item = (usize, V)>,
+) -> Polynomial<E> {
+    let mut limbs = vec![E::zero(); SIGNIFICAND_NUM_LIMBS];
+    for (exponent, flag) in terms {
+        let flag: E = flag.into();
+        limbs[exponent / 8] =
+            limbs[exponent / 8].clone() + flag * E::from_canonical_u32(1 << (exponent % 8));
+    }
+    Polynomial::new(limbs)
+}
+
+/// Constrains `vanishing(256) = 0` with the shifted carries of its division by `x - 256`.
+fn eval_vanishing<AB: MONEROCHANAirBuilder>(
+    builder: &mut AB,
+    vanishing: Polynomial<AB::Expr>,
+    witness: &[AB::Var],
+) {
+    let offset = AB::Expr::from_canonical_usize(WITNESS_OFFSET);
+    let witness = witness
+        .iter()
+        .map(|&limb| {
+            let limb: AB::Expr = limb.into();
+            limb - offset.clone()
+        })
+        .collect::<Polynomial<_>>();
+    let root_monomial = Polynomial::new(vec![-AB::Expr::from_canonical_u32(256), AB::Expr::one()]);
+    let constraints = vanishing - &(witness * root_monomial);
+    for constraint in constraints.as_coefficients() {
+        builder.assert_zero(constraint);
+    }
+}
+
+/// The biased exponent of an f64.
+fn exponent(value: u64) -> u32 {
+    ((value >> 52) & 0x7ff) as u32
+}
+
+/// The significand of a normal f64, including its implicit leading bit.
+fn significand(value: u64) -> u128 {
+    u128::from((value & ((1 << 52) - 1)) | (1 << 52))
+}
+
+fn words_to_u64(words: [u32; 2]) -> u64 {
+    u64::from(words[0]) | (u64::from(words[1]) << 32)
+}
+
+impl<F> BaseAir<F> for F64OpChip {
+    fn width(&self) -> usize {
+        NUM_COLS
+    }
+}
+
+impl<AB> Air<AB> for F64OpChip
+where
+    AB: MONEROCHANAirBuilder,
+{
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+        let local: &F64OpCols<AB::Var> = (*local).borrow();
+
+        let [x_bytes, y_bytes, result_bytes] = local.bytes();
+
+        // Check the operation flags.
+        builder.assert_bool(local.is_real);
+        builder.assert_bool(local.is_add);
+        builder.assert_bool(local.is_mul);
+        builder.assert_bool(local.is_div);
+        builder.assert_eq(local.is_add + local.is_mul + local.is_div, local.is_real);
+
+        // Check the fast path flags.
+        builder.assert_bool(local.is_fast);
+        builder.when_not(local.is_real).assert_zero(local.is_fast);
+        builder.assert_eq(local.is_fast_mul, local.is_mul * local.is_fast);
+        builder.assert_eq(local.is_fast_div, local.is_div * local.is_fast);
+        builder.assert_bool(local.is_near_add);
+        builder.assert_bool(local.is_far_add);
+        builder.assert_eq(local.is_near_add + local.is_far_add, local.is_add * local.is_fast);
+        let is_rounded: AB::Expr = local.is_fast_mul + local.is_fast_div + local.is_near_add;
+
+        // Decompose the operands and the result.
+        F64Cols::<AB::F>::eval(builder, &local.x, &x_bytes, local.is_real);
+        F64Cols::<AB::F>::eval(builder, &local.y, &y_bytes, local.is_real);
+        F64Cols::<AB::F>::eval(builder, &local.result, &result_bytes, local.is_real);
+        let x_exponent: AB::Expr = local.x.exponent();
+        let y_exponent: AB::Expr = local.y.exponent();
+        let result_exponent: AB::Expr = local.result.exponent();
+
+        // The operands of the fast path are normal, and so is a rounded result.
+        for (exponent, multiplicity) in [
+            (x_exponent.clone(), local.is_fast.into()),
+            (y_exponent.clone(), local.is_fast.into()),
+            (result_exponent.clone(), is_rounded.clone()),
+        ] {
+            builder.send_byte(
+                ByteOpcode::U16Range.as_field::<AB::F>(),
+                exponent.clone() - AB::Expr::one(),
+                AB::Expr::zero(),
+                AB::Expr::zero(),
+                multiplicity.clone(),
+            );
+            builder.send_byte(
+                ByteOpcode::U16Range.as_field::<AB::F>(),
+                AB::Expr::from_canonical_u32(MAX_EXPONENT) - exponent,
+                AB::Expr::zero(),
+                AB::Expr::zero(),
+                multiplicity,
+            );
+        }
+
+        // The slow path writes the all-zero sentinel.
+        for byte in result_bytes {
+            builder.when(local.is_real - local.is_fast).assert_zero(byte);
+        }
+
+        // The written bytes of the result are bytes, and expose the least significant bit.
+        builder.slice_range_check_u8(&result_bytes[..6], local.is_real);
+        builder.slice_range_check_u8(&[local.result_half], local.is_real);
+        builder.assert_bool(local.result_lsb);
+        builder.assert_eq(
+            result_bytes[0],
+            local.result_half * AB::F::from_canonical_u32(2) + local.result_lsb,
+        );
+
+        // The sign of a product or quotient is the xor of the signs of the operands.
+        builder.when(local.is_fast_mul + local.is_fast_div).assert_eq(
+            local.result.sign,
+            local.x.sign + local.y.sign -
+                local.x.sign * local.y.sign * AB::F::from_canonical_u32(2),
+        );
+
+        // Sort the operands of an addition by exponent, and sign their terms relative to the sign
+        // of the result.
+        let swap = local.swap;
+        builder.assert_bool(swap);
+        let select = |x: AB::Expr, y: AB::Expr| -> AB::Expr { x.clone() + swap * (y - x) };
+        let large_sign_bit = select(local.x.sign.into(), local.y.sign.into());
+        let small_sign_bit = select(local.y.sign.into(), local.x.sign.into());
+        for (negated, sign_bit) in
+            [(local.large_negated, large_sign_bit), (local.small_negated, small_sign_bit)]
+        {
+            builder.assert_eq(
+                negated,
+                sign_bit.clone() + local.result.sign -
+                    sign_bit * local.result.sign * AB::F::from_canonical_u32(2),
+            );
+        }
+        builder.assert_eq(
+            local.large_sign,
+            local.is_near_add - local.is_near_add * local.large_negated * AB::F::two(),
+        );
+        builder.assert_eq(
+            local.small_sign,
+            local.is_near_add - local.is_near_add * local.small_negated * AB::F::two(),
+        );
+        let x_significand = local.x.significand::<AB::Expr>(&x_bytes).as_coefficients();
+        let y_significand = local.y.significand::<AB::Expr>(&y_bytes).as_coefficients();
+        for i in 0..SIGNIFICAND_NUM_LIMBS {
+            builder.when(local.is_near_add).assert_eq(
+                local.large_significand[i],
+                select(x_significand[i].clone(), y_significand[i].clone()),
+            );
+            builder.when(local.is_near_add).assert_eq(
+                local.small_significand[i],
+                select(y_significand[i].clone(), x_significand[i].clone()),
+            );
+        }
+        let gap =
+            (x_exponent.clone() - y_exponent.clone()) * (AB::Expr::one() - swap * AB::F::two());
+
+        // The exponent gap of a near addition is one-hot encoded.
+        let mut near_gap_sum = AB::Expr::zero();
+        let mut near_gap = AB::Expr::zero();
+        for (i, &flag) in local.near_gap.iter().enumerate() {
+            builder.assert_bool(flag);
+            near_gap_sum = near_gap_sum + flag;
+            near_gap = near_gap + flag * AB::F::from_canonical_usize(i);
+        }
+        builder.assert_eq(near_gap_sum, local.is_near_add);
+        builder.when(local.is_near_add).assert_eq(gap.clone(), near_gap);
+
+        // A far addition returns its larger operand.
+        builder
+            .when(local.is_far_add)
+            .assert_eq(gap, local.far_gap + AB::F::from_canonical_usize(FAR_GAP));
+        builder.send_byte(
+            ByteOpcode::U16Range.as_field::<AB::F>(),
+            local.far_gap,
+            AB::Expr::zero(),
+            AB::Expr::zero(),
+            local.is_far_add,
+        );
+        for i in 0..8 {
+            builder
+                .when(local.is_far_add)
+                .assert_eq(result_bytes[i], select(x_bytes[i].into(), y_bytes[i].into()));
+        }
+
+        // The normalization shift is one-hot encoded, is negative for a division, and sets the
+        // exponent of a rounded result.
+        let mut shift_sum = AB::Expr::zero();
+        let mut positive_shift_sum = AB::Expr::zero();
+        let mut shift = AB::Expr::zero();
+        for (i, &flag) in local.shift.iter().enumerate() {
+            builder.assert_bool(flag);
+            shift_sum = shift_sum + flag;
+            if i >= SHIFT_OFFSET {
+                positive_shift_sum = positive_shift_sum + flag;
+            }
+            shift = shift + flag * AB::F::from_canonical_usize(i) -
+                flag * AB::F::from_canonical_usize(SHIFT_OFFSET);
+        }
+        builder.assert_eq(shift_sum, is_rounded.clone());
+        builder.when(local.is_fast_div).assert_zero(positive_shift_sum);
+        let bias = AB::F::from_canonical_u32(SIGNIFICAND_BIAS);
+        builder.assert_eq(
+            is_rounded.clone() * result_exponent,
+            local.is_fast_mul * (x_exponent.clone() + y_exponent.clone() - bias) +
+                local.is_fast_div * (x_exponent.clone() - y_exponent.clone() + bias) +
+                local.is_near_add * select(y_exponent, x_exponent) +
+                shift,
+        );
+
+        // Check the exact result, the rounding and the remainder bounds.
+        let [exact, rounding, remainder, carry_slack] = local.vanishing_polynomials::<AB::Expr>();
+        eval_vanishing(builder, exact, &local.exact_witness);
+        eval_vanishing(builder, rounding, &local.rounding_witness);
+        eval_vanishing(builder, remainder, &local.remainder_witness);
+        eval_vanishing(builder, carry_slack, &local.carry_slack_witness);
+        builder.slice_range_check_u8(&local.exact, local.is_real);
+        builder.slice_range_check_u8(&local.remainder, local.is_real);
+        builder.slice_range_check_u8(&local.remainder_complement, local.is_real);
+        builder.slice_range_check_u8(&local.carry_slack, local.is_real);
+        builder.slice_range_check_u16(&local.exact_witness, local.is_real);
+        builder.slice_range_check_u16(&local.rounding_witness, local.is_real);
+        builder.slice_range_check_u16(&local.remainder_witness, local.is_real);
+        builder.slice_range_check_u16(&local.carry_slack_witness, local.is_real);
+
+        // A rounding carry into the next binade is only allowed when the exact significand is at
+        // least `2^52 - 1/4`, so that the shift is unique.
+        let fraction_sum = result_bytes[..6]
+            .iter()
+            .fold(local.result.fraction_hi.into(), |acc: AB::Expr, &byte| acc + byte);
+        IsZeroOperation::<AB::F>::eval(
+            builder,
+            fraction_sum,
+            local.is_min_significand,
+            local.is_real.into(),
+        );
+        builder.assert_eq(
+            local.is_rounded_min_significand,
+            local.is_min_significand.result * is_rounded.clone(),
+        );
+
+        // Ties, where the remainder or its complement is zero, are rounded to even.
+        let remainder_sum = local.remainder.iter().fold(AB::Expr::zero(), |acc, &limb| acc + limb);
+        let complement_sum =
+            local.remainder_complement.iter().fold(AB::Expr::zero(), |acc, &limb| acc + limb);
+        IsZeroOperation::<AB::F>::eval(
+            builder,
+            remainder_sum,
+            local.is_remainder_zero,
+            local.is_real.into(),
+        );
+        IsZeroOperation::<AB::F>::eval(
+            builder,
+            complement_sum,
+            local.is_complement_zero,
+            local.is_real.into(),
+        );
+        builder
+            .when(is_rounded)
+            .when(local.result_lsb)
+            .assert_zero(local.is_remainder_zero.result + local.is_complement_zero.result);
+
+        // Read the y operand.
+        builder.eval_memory_access_slice(
+            local.shard,
+            local.clk.into(),
+            local.y_ptr,
+            &local.y_memory,
+            local.is_real,
+        );
+
+        // Read the x operand and write the result.
+        builder.eval_memory_access_slice(
+            local.shard,
+            local.clk.into() + AB::Expr::one(),
+            local.x_ptr,
+            &local.x_memory,
+            local.is_real,
+        );
+
+        // Receive the arguments, with the syscall id of the operation.
+        let syscall_id = local.is_add *
+            AB::F::from_canonical_u32(SyscallCode::F64_ADD.syscall_id()) +
+            local.is_mul * AB::F::from_canonical_u32(SyscallCode::F64_MUL.syscall_id()) +
+            local.is_div * AB::F::from_canonical_u32(SyscallCode::F64_DIV.syscall_id());
+        builder.receive_syscall(
+            local.shard,
+            local.clk,
+            syscall_id,
+            local.x_ptr,
+            local.y_ptr,
+            local.is_real,
+            InteractionScope::Local,
+        );
+    }
+}
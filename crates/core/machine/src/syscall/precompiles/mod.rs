@@ -1,4 +1,8 @@
+#[cfg(feature = "aes-precompile")]
+pub mod aes;
 pub mod edwards;
+#[cfg(feature = "f64-precompile")]
+pub mod float;
 pub mod fptower;
 pub mod keccak256;
 pub mod sha256;
@@ -153,12 +153,10 @@ pub fn monerochan_debug_mode() -> bool {
 }
 
 /// Returns a vector of zeros of the given length. This is faster than vec![F::zero(); len] which
-/// requires copying.
+/// requires copying, and reuses the buffer of a proven trace from [`monerochan_stark::arena`]
+/// when one fits.
 ///
 /// This function is safe to use only for fields that can be transmuted from 0u32.
 pub fn zeroed_f_vec<F: Field>(len: usize) -> Vec<F> {
-    debug_assert!(std::mem::size_of::<F>() == 4);
-
-    let vec = vec![0u32; len];
-    unsafe { std::mem::transmute::<Vec<u32>, Vec<F>>(vec) }
+    monerochan_stark::arena::zeroed_vec(len)
 }
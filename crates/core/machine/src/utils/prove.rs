@@ -1,5 +1,6 @@
 use p3_matrix::dense::RowMajorMatrix;
 use std::{
+    collections::BTreeMap,
     error::Error,
     fs::File,
     io::{self, Seek, SeekFrom},
@@ -18,7 +19,8 @@ use crate::{
     utils::test::MaliciousTracePVGeneratorType,
 };
 use p3_maybe_rayon::prelude::*;
-use monerochan_stark::{MachineProvingKey, StarkVerifyingKey};
+use rayon::ThreadPool;
+use monerochan_stark::{FriParametersError, MachineProvingKey, StarkVerifyingKey};
 use thiserror::Error;
 
 use p3_field::PrimeField32;
@@ -39,8 +41,9 @@ use monerochan_core_executor::{
     Program, MONEROCHANContext,
 };
 use monerochan_stark::{
-    air::PublicValues, shape::OrderedShape, Com, MachineProof, MachineProver, MachineRecord,
-    OpeningProof, PcsProverData, MONEROCHANCoreOpts, ShardProof, StarkGenericConfig, Val,
+    air::PublicValues, arena, resources, shape::OrderedShape, Com, MachineProof, MachineProver,
+    MachineRecord, OpeningProof, PcsProverData, MONEROCHANCoreOpts, ShardProof, StarkGenericConfig,
+    Val,
 };
 
 #[allow(clippy::too_many_arguments)]
@@ -85,6 +88,51 @@ where
     Ok((proof, public_values, cycles))
 }
 
+/// A batch of shards of a checkpoint with their main traces, ready to be proven.
+struct ShardBatch<F> {
+    /// The index of the checkpoint the shards were traced from.
+    checkpoint: usize,
+    /// The index of the batch among the batches of the checkpoint.
+    batch: usize,
+    /// Whether this is the last batch of the checkpoint.
+    last: bool,
+    records: Vec<ExecutionRecord>,
+    traces: Vec<Vec<(String, RowMajorMatrix<F>)>>,
+}
+
+/// Puts the proofs of batches of shards, which are proven in the order their traces are
+/// generated, back in shard order.
+struct ShardOrder<T> {
+    /// The proofs of the batches that arrived before an earlier batch, by checkpoint and batch
+    /// index, with whether the batch is the last of its checkpoint.
+    pending: BTreeMap<(usize, usize), (Vec<T>, bool)>,
+    /// The checkpoint and batch index of the next batch in shard order.
+    next: (usize, usize),
+}
+
+impl<T> ShardOrder<T> {
+    fn new() -> Self {
+        Self { pending: BTreeMap::new(), next: (0, 0) }
+    }
+
+    /// Adds the proofs of a batch, and returns the proofs that are now in shard order.
+    fn push(&mut self, checkpoint: usize, batch: usize, last: bool, proofs: Vec<T>) -> Vec<T> {
+        self.pending.insert((checkpoint, batch), (proofs, last));
+        let mut ready = Vec::new();
+        while let Some((mut proofs, last)) = self.pending.remove(&self.next) {
+            ready.append(&mut proofs);
+            self.next = if last { (self.next.0 + 1, 0) } else { (self.next.0, self.next.1 + 1) };
+        }
+        ready
+    }
+}
+
+/// Proves the execution of a program, sending the shard proofs to `proof_tx` in shard order.
+///
+/// The executor saves checkpoints ahead of the trace generation workers, up to the capacity of
+/// the checkpoint channel. The workers trace each checkpoint into records, and send them to the
+/// prover in batches of `shard_batch_size` shards, generating the main traces of a batch only
+/// before sending it rather than for the whole checkpoint at once.
 #[allow(clippy::too_many_arguments)]
 pub fn prove_core_stream<SC: StarkGenericConfig, P: MachineProver<SC, RiscvAir<SC::Val>>>(
     prover: &P,
@@ -106,6 +154,20 @@ where
     Com<SC>: Send + Sync,
     PcsProverData<SC>: Send + Sync,
 {
+    // Run the trace generation and the proving, and so the memory they first touch, in a pool of
+    // threads pinned to a single NUMA node.
+    let numa_pool = opts.numa_node.and_then(|node| match resources::numa_thread_pool(node) {
+        Ok(pool) => Some(pool),
+        Err(err) => {
+            tracing::warn!("failed to pin the prover to NUMA node {node}: {err}");
+            None
+        }
+    });
+    let numa_pool = numa_pool.as_ref();
+
+    // Keep the trace buffers of the shards of this proof for reuse until it is done.
+    let _arena = arena::session();
+
     // Setup the runtime.
     let mut runtime = Box::new(Executor::with_context(program.clone(), opts, context));
     runtime.maximal_shapes = shape_config.map(|config| {
@@ -182,11 +244,8 @@ where
 
         // Spawn the phase 2 record generator thread.
         let p2_record_gen_sync = Arc::new(TurnBasedSync::new());
-        let p2_trace_gen_sync = Arc::new(TurnBasedSync::new());
         let (p2_records_and_traces_tx, p2_records_and_traces_rx) =
-            sync_channel::<(Vec<ExecutionRecord>, Vec<Vec<(String, RowMajorMatrix<Val<SC>>)>>)>(
-                opts.records_and_traces_channel_capacity,
-            );
+            sync_channel::<ShardBatch<Val<SC>>>(opts.records_and_traces_channel_capacity);
         let p2_records_and_traces_tx = Arc::new(Mutex::new(p2_records_and_traces_tx));
 
         let shape_tx = Arc::new(Mutex::new(shape_and_done_tx));
@@ -197,7 +256,6 @@ where
         let checkpoints_rx = Arc::new(Mutex::new(checkpoints_rx));
         for _ in 0..opts.trace_gen_workers {
             let record_gen_sync = Arc::clone(&p2_record_gen_sync);
-            let trace_gen_sync = Arc::clone(&p2_trace_gen_sync);
             let records_and_traces_tx = Arc::clone(&p2_records_and_traces_tx);
             let checkpoints_rx = Arc::clone(&checkpoints_rx);
 
@@ -211,7 +269,7 @@ where
             #[cfg(feature = "debug")]
             let all_records_tx = all_records_tx.clone();
 
-            let handle = s.spawn(move || {
+            let handle = s.spawn(move || install(numa_pool, move || {
                 let _span = span.enter();
                 tracing::debug_span!("phase 2 trace generation").in_scope(|| {
                     loop {
@@ -375,7 +433,7 @@ where
                                 shape_fixed_records = Some(records);
                             }
 
-                            let mut records = shape_fixed_records.unwrap();
+                            let records = shape_fixed_records.unwrap();
 
                             // Send the shapes to the channel, if necessary.
                             for record in records.iter() {
@@ -398,47 +456,53 @@ where
                             #[cfg(feature = "debug")]
                             all_records_tx.send(records.clone()).unwrap();
 
-                            let mut main_traces = Vec::new();
-                            if let Some(malicious_trace_pv_generator) = malicious_trace_pv_generator
-                            {
-                                tracing::info_span!("generate main traces", index).in_scope(|| {
-                                    main_traces = records
-                                        .par_iter_mut()
-                                        .map(|record| malicious_trace_pv_generator(prover, record))
-                                        .collect::<Vec<_>>();
-                                });
-                            } else {
-                                tracing::info_span!("generate main traces", index).in_scope(|| {
-                                    main_traces = records
-                                        .par_iter()
-                                        .map(|record| prover.generate_traces(record))
-                                        .collect::<Vec<_>>();
-                                });
+                            // Generate the main traces batch by batch, and send each batch to
+                            // the phase 2 prover as soon as it is ready, so that the traces of a
+                            // whole checkpoint are never in memory at once.
+                            let mut chunked_records = chunk_vec(records, opts.shard_batch_size);
+                            // A checkpoint without records still sends an empty last batch, for
+                            // the prover to move on to the next checkpoint.
+                            if chunked_records.is_empty() {
+                                chunked_records.push(Vec::new());
                             }
-
-                            trace_gen_sync.wait_for_turn(index);
-
-                            // Send the records to the phase 2 prover.
-                            let chunked_records = chunk_vec(records, opts.shard_batch_size);
-                            let chunked_main_traces = chunk_vec(main_traces, opts.shard_batch_size);
-                            chunked_records
-                                .into_iter()
-                                .zip(chunked_main_traces.into_iter())
-                                .for_each(|(records, main_traces)| {
-                                    records_and_traces_tx
-                                        .lock()
-                                        .unwrap()
-                                        .send((records, main_traces))
-                                        .unwrap();
+                            let num_batches = chunked_records.len();
+                            for (batch, mut records) in chunked_records.into_iter().enumerate() {
+                                let traces = tracing::info_span!(
+                                    "generate main traces",
+                                    index,
+                                    batch
+                                )
+                                .in_scope(|| {
+                                    if let Some(generator) = malicious_trace_pv_generator {
+                                        records
+                                            .par_iter_mut()
+                                            .map(|record| generator(prover, record))
+                                            .collect::<Vec<_>>()
+                                    } else {
+                                        records
+                                            .par_iter()
+                                            .map(|record| prover.generate_traces(record))
+                                            .collect::<Vec<_>>()
+                                    }
                                 });
-
-                            trace_gen_sync.advance_turn();
+                                records_and_traces_tx
+                                    .lock()
+                                    .unwrap()
+                                    .send(ShardBatch {
+                                        checkpoint: index,
+                                        batch,
+                                        last: batch + 1 == num_batches,
+                                        records,
+                                        traces,
+                                    })
+                                    .unwrap();
+                            }
                         } else {
                             break;
                         }
                     }
                 })
-            });
+            }));
             p2_record_and_trace_gen_handles.push(handle);
         }
         drop(p2_records_and_traces_tx);
@@ -447,12 +511,13 @@ where
 
         // Spawn the phase 2 prover thread.
         let p2_prover_span = tracing::Span::current().clone();
-        let proof_tx = Arc::new(Mutex::new(proof_tx));
-        let p2_prover_handle = s.spawn(move || {
+        let p2_prover_handle = s.spawn(move || install(numa_pool, move || {
             let _span = p2_prover_span.enter();
             tracing::debug_span!("phase 2 prover").in_scope(|| {
-                for (records, traces) in p2_records_and_traces_rx.into_iter() {
-                    tracing::debug_span!("batch").in_scope(|| {
+                let mut order = ShardOrder::new();
+                for shard_batch in p2_records_and_traces_rx.into_iter() {
+                    let ShardBatch { checkpoint, batch, last, records, traces } = shard_batch;
+                    tracing::debug_span!("batch", checkpoint, batch).in_scope(|| {
                         let span = tracing::Span::current().clone();
                         let proofs = records
                             .into_par_iter()
@@ -467,7 +532,13 @@ where
                                     .in_scope(|| prover.commit(&record, main_traces));
 
                                 let proof = tracing::debug_span!("opening", shard).in_scope(|| {
-                                    prover.open(pk, main_data, &mut challenger.clone()).unwrap()
+                                    let mut challenger = challenger.clone();
+                                    if opts.seed.is_some() {
+                                        prover.open_deterministic(pk, main_data, &mut challenger)
+                                    } else {
+                                        prover.open(pk, main_data, &mut challenger)
+                                    }
+                                    .unwrap()
                                 });
 
                                 let elapsed = before.elapsed();
@@ -507,15 +578,15 @@ where
                             })
                             .collect::<Vec<_>>();
 
-                        // Send the batch of proofs to the channel.
-                        let proof_tx = proof_tx.lock().unwrap();
-                        for proof in proofs {
+                        // Send the proofs to the channel in shard order, once the proofs of all
+                        // the earlier batches have been sent.
+                        for proof in order.push(checkpoint, batch, last, proofs) {
                             proof_tx.send(proof).unwrap();
                         }
                     });
                 }
             });
-        });
+        }));
 
         // Wait until the checkpoint generator handle has fully finished.
         let runtime = checkpoint_generator_handle.join().unwrap().unwrap();
@@ -590,6 +661,14 @@ where
     })
 }
 
+/// Runs `f` in `pool` if there is one, and on the current thread otherwise.
+fn install<R: Send>(pool: Option<&ThreadPool>, f: impl FnOnce() -> R + Send) -> R {
+    match pool {
+        Some(pool) => pool.install(f),
+        None => f(),
+    }
+}
+
 pub fn trace_checkpoint<SC: StarkGenericConfig>(
     program: Program,
     file: &File,
@@ -644,4 +723,23 @@ pub enum MONEROCHANCoreProverError {
     IoError(io::Error),
     #[error("serialization error: {0}")]
     SerializationError(bincode::Error),
+    #[error("invalid FRI parameters: {0}")]
+    InvalidFriParameters(FriParametersError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shard_order() {
+        let mut order = ShardOrder::new();
+        assert!(order.push(0, 1, true, vec![2, 3]).is_empty());
+        assert!(order.push(1, 0, false, vec![4]).is_empty());
+        assert_eq!(order.push(0, 0, false, vec![0, 1]), [0, 1, 2, 3, 4]);
+        // A checkpoint without shards sends an empty last batch.
+        assert!(order.push(3, 0, true, vec![7]).is_empty());
+        assert_eq!(order.push(1, 1, true, vec![5]), [5]);
+        assert_eq!(order.push(2, 0, true, vec![]), [7]);
+    }
 }
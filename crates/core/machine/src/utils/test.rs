@@ -177,7 +177,7 @@ where
     A::Record: MachineRecord<Config = MONEROCHANCoreOpts>,
     SC: StarkGenericConfig,
     SC::Val: p3_field::PrimeField32,
-    SC::Challenger: Clone,
+    SC::Challenger: Clone + Send,
     Com<SC>: Send + Sync,
     PcsProverData<SC>: Send + Sync + Serialize + DeserializeOwned,
     OpeningProof<SC>: Send + Sync,
@@ -0,0 +1,272 @@
+//! A stable binary format for MONEROCHAN execution traces.
+//!
+//! A trace file starts with [`TRACE_MAGIC`] and the little-endian [`TRACE_FORMAT_VERSION`],
+//! followed by a zlib-compressed stream holding a bincode-encoded [`TraceHeader`] and then one
+//! bincode-encoded [`TraceEvent`] after another until the end of the stream.
+//!
+//! This crate does not depend on the executor, so analysis tools only need to link it to read the
+//! traces written by `monerochan-core-executor`.
+
+#![warn(clippy::pedantic)]
+#![allow(clippy::module_name_repetitions)]
+#![allow(clippy::missing_errors_doc)]
+#![warn(missing_docs)]
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The bytes every trace file starts with.
+pub const TRACE_MAGIC: [u8; 8] = *b"MCTRACE\0";
+
+/// The version of the trace format.
+///
+/// This must be bumped whenever the encoding of [`TraceHeader`] or [`TraceEvent`] changes.
+pub const TRACE_FORMAT_VERSION: u32 = 1;
+
+/// Errors that can occur when reading or writing a trace.
+#[derive(Debug, Error)]
+pub enum TraceError {
+    /// The underlying reader or writer failed.
+    #[error("trace io error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// A header or an event could not be encoded or decoded.
+    #[error("trace encoding error: {0}")]
+    Encoding(#[from] bincode::Error),
+
+    /// The file does not start with [`TRACE_MAGIC`].
+    #[error("not a MONEROCHAN execution trace")]
+    InvalidMagic,
+
+    /// The trace was written with an unsupported version of the format.
+    #[error("unsupported trace format version {0}, expected {TRACE_FORMAT_VERSION}")]
+    UnsupportedVersion(u32),
+}
+
+/// The metadata at the start of a trace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceHeader {
+    /// The program counter of the first instruction.
+    pub pc_start: u32,
+    /// The mnemonic of each opcode, keyed by the `opcode` of [`TraceEvent::Instruction`].
+    pub opcodes: Vec<(u8, String)>,
+    /// The name of each syscall, keyed by the `code` of [`TraceEvent::Syscall`].
+    pub syscalls: Vec<(u32, String)>,
+}
+
+impl TraceHeader {
+    /// The mnemonic of an opcode.
+    #[must_use]
+    pub fn opcode_name(&self, opcode: u8) -> Option<&str> {
+        self.opcodes.iter().find(|(op, _)| *op == opcode).map(|(_, name)| name.as_str())
+    }
+
+    /// The name of a syscall.
+    #[must_use]
+    pub fn syscall_name(&self, code: u32) -> Option<&str> {
+        self.syscalls.iter().find(|(c, _)| *c == code).map(|(_, name)| name.as_str())
+    }
+}
+
+/// An event of the execution.
+///
+/// Memory accesses and syscalls belong to the last [`TraceEvent::Instruction`] before them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TraceEvent {
+    /// An instruction was executed.
+    Instruction {
+        /// The global clock of the cycle.
+        clk: u64,
+        /// The program counter of the instruction.
+        pc: u32,
+        /// The opcode of the instruction, see [`TraceHeader::opcodes`].
+        opcode: u8,
+        /// The first operand.
+        op_a: u8,
+        /// The second operand.
+        op_b: u32,
+        /// The third operand.
+        op_c: u32,
+        /// Whether the second operand is an immediate value.
+        imm_b: bool,
+        /// Whether the third operand is an immediate value.
+        imm_c: bool,
+    },
+    /// A word was read from memory by a load instruction.
+    MemoryRead {
+        /// The aligned address of the word.
+        addr: u32,
+        /// The value read.
+        value: u32,
+    },
+    /// A word was written to memory by a store instruction.
+    MemoryWrite {
+        /// The aligned address of the word.
+        addr: u32,
+        /// The value written.
+        value: u32,
+    },
+    /// A syscall was invoked.
+    Syscall {
+        /// The syscall code, see [`TraceHeader::syscalls`].
+        code: u32,
+        /// The first argument.
+        arg1: u32,
+        /// The second argument.
+        arg2: u32,
+    },
+}
+
+/// Writes a trace.
+pub struct TraceWriter<W: Write> {
+    encoder: BufWriter<ZlibEncoder<W>>,
+}
+
+impl TraceWriter<File> {
+    /// Creates a trace file at the given path.
+    pub fn create(path: impl AsRef<Path>, header: &TraceHeader) -> Result<Self, TraceError> {
+        Self::new(File::create(path)?, header)
+    }
+}
+
+impl<W: Write> TraceWriter<W> {
+    /// Starts a trace with the given header.
+    pub fn new(mut writer: W, header: &TraceHeader) -> Result<Self, TraceError> {
+        writer.write_all(&TRACE_MAGIC)?;
+        writer.write_all(&TRACE_FORMAT_VERSION.to_le_bytes())?;
+        let mut encoder = BufWriter::new(ZlibEncoder::new(writer, Compression::fast()));
+        bincode::serialize_into(&mut encoder, header)?;
+        Ok(Self { encoder })
+    }
+
+    /// Appends an event to the trace.
+    pub fn write(&mut self, event: &TraceEvent) -> Result<(), TraceError> {
+        bincode::serialize_into(&mut self.encoder, event)?;
+        Ok(())
+    }
+
+    /// Flushes the trace and returns the underlying writer.
+    pub fn finish(self) -> Result<W, TraceError> {
+        let encoder = self.encoder.into_inner().map_err(std::io::IntoInnerError::into_error)?;
+        Ok(encoder.finish()?)
+    }
+}
+
+/// Reads a trace, yielding its events in order.
+pub struct TraceReader<R: Read> {
+    header: TraceHeader,
+    decoder: BufReader<ZlibDecoder<R>>,
+}
+
+impl TraceReader<BufReader<File>> {
+    /// Opens the trace file at the given path.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, TraceError> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> TraceReader<R> {
+    /// Reads the header of a trace.
+    pub fn new(mut reader: R) -> Result<Self, TraceError> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if magic != TRACE_MAGIC {
+            return Err(TraceError::InvalidMagic);
+        }
+        let mut version = [0u8; 4];
+        reader.read_exact(&mut version)?;
+        let version = u32::from_le_bytes(version);
+        if version != TRACE_FORMAT_VERSION {
+            return Err(TraceError::UnsupportedVersion(version));
+        }
+
+        let mut decoder = BufReader::new(ZlibDecoder::new(reader));
+        let header = bincode::deserialize_from(&mut decoder)?;
+        Ok(Self { header, decoder })
+    }
+
+    /// The header of the trace.
+    #[must_use]
+    pub fn header(&self) -> &TraceHeader {
+        &self.header
+    }
+
+    /// Reads the next event, or returns `None` at the end of the trace.
+    pub fn next_event(&mut self) -> Result<Option<TraceEvent>, TraceError> {
+        if self.decoder.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(bincode::deserialize_from(&mut self.decoder)?))
+    }
+}
+
+impl<R: Read> Iterator for TraceReader<R> {
+    type Item = Result<TraceEvent, TraceError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header() -> TraceHeader {
+        TraceHeader {
+            pc_start: 0x2000,
+            opcodes: vec![(0, "add".to_string()), (1, "sub".to_string())],
+            syscalls: vec![(0, "HALT".to_string())],
+        }
+    }
+
+    #[test]
+    fn test_trace_roundtrip() {
+        let events = vec![
+            TraceEvent::Instruction {
+                clk: 0,
+                pc: 0x2000,
+                opcode: 1,
+                op_a: 5,
+                op_b: 6,
+                op_c: 7,
+                imm_b: false,
+                imm_c: true,
+            },
+            TraceEvent::MemoryRead { addr: 0x100, value: 42 },
+            TraceEvent::MemoryWrite { addr: 0x104, value: 43 },
+            TraceEvent::Syscall { code: 0, arg1: 1, arg2: 2 },
+        ];
+
+        let mut writer = TraceWriter::new(Vec::new(), &header()).unwrap();
+        for event in &events {
+            writer.write(event).unwrap();
+        }
+        let bytes = writer.finish().unwrap();
+
+        let reader = TraceReader::new(bytes.as_slice()).unwrap();
+        assert_eq!(reader.header(), &header());
+        assert_eq!(reader.header().opcode_name(1), Some("sub"));
+        assert_eq!(reader.collect::<Result<Vec<_>, _>>().unwrap(), events);
+    }
+
+    #[test]
+    fn test_trace_rejects_other_versions() {
+        let mut bytes = TraceWriter::new(Vec::new(), &header()).unwrap().finish().unwrap();
+        bytes[8] = 2;
+        assert!(matches!(
+            TraceReader::new(bytes.as_slice()),
+            Err(TraceError::UnsupportedVersion(2))
+        ));
+
+        bytes[0] = b'X';
+        assert!(matches!(TraceReader::new(bytes.as_slice()), Err(TraceError::InvalidMagic)));
+    }
+}
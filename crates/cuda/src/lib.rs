@@ -124,7 +124,7 @@ pub struct WrapRequestPayload {
 }
 
 /// Defines how the Moongate server is created.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum MoongateServer {
     External { endpoint: String },
     Local { visible_device_index: Option<u64>, port: Option<u64> },
@@ -172,7 +172,7 @@ fn run_evaluation<C: MONEROCHANProverComponents>(
     let cycles = get_cycles(elf, stdin);
 
     let prover = MONEROCHANProver::<C>::new();
-    let (_, pk_d, program, vk) = prover.setup(elf);
+    let (_, pk_d, program, vk) = prover.setup(elf).expect("failed to set up the program");
 
     let context = MONEROCHANContext::default();
 
@@ -0,0 +1,25 @@
+//! WebAssembly bindings to build MONEROCHAN inputs and verify proofs from JavaScript.
+//!
+//! Build the npm package with `wasm-pack build crates/js --target web` (or `--target nodejs`),
+//! then:
+//!
+//! ```js
+//! import init, { MONEROCHANStdin, verifyGroth16 } from "monerochan-js";
+//!
+//! await init();
+//!
+//! const stdin = new MONEROCHANStdin();
+//! stdin.writeU32(10);
+//! const stdinBytes = stdin.toBytes();
+//!
+//! verifyGroth16(proofBytes, publicValues, vkeyHash);
+//! ```
+//!
+//! The encoding of [`Stdin`] matches `MONEROCHANStdin` of the SDK byte-for-byte, and the proofs are
+//! the onchain bytes returned by `MONEROCHANProofWithPublicValues::bytes`.
+
+mod stdin;
+mod verify;
+
+pub use stdin::Stdin;
+pub use verify::{verify_groth16, verify_plonk};
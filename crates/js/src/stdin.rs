@@ -0,0 +1,135 @@
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+/// The input of a program, encoded like `MONEROCHANStdin` of the SDK.
+///
+/// Each write appends one buffer, read in the program with one `io::read` or `io::read_vec`.
+#[wasm_bindgen(js_name = MONEROCHANStdin)]
+#[derive(Debug, Clone, Default)]
+pub struct Stdin {
+    buffer: Vec<Vec<u8>>,
+}
+
+/// The serialized layout of `MONEROCHANStdin`, without support for proofs.
+#[derive(Serialize)]
+struct Encoding {
+    buffer: Vec<Vec<u8>>,
+    ptr: u64,
+    proofs: Vec<()>,
+}
+
+#[wasm_bindgen(js_class = MONEROCHANStdin)]
+impl Stdin {
+    #[wasm_bindgen(constructor)]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends raw bytes, read in the program with `io::read_vec()`.
+    #[wasm_bindgen(js_name = writeVec)]
+    pub fn write_vec(&mut self, data: &[u8]) {
+        self.buffer.push(data.to_vec());
+    }
+
+    /// Appends bytes, read in the program with `io::read::<Vec<u8>>()`.
+    #[wasm_bindgen(js_name = writeBytes)]
+    pub fn write_bytes(&mut self, data: &[u8]) {
+        self.write(&data);
+    }
+
+    /// Appends a bool, read in the program with `io::read::<bool>()`.
+    #[wasm_bindgen(js_name = writeBool)]
+    pub fn write_bool(&mut self, value: bool) {
+        self.write(&value);
+    }
+
+    /// Appends a u32, read in the program with `io::read::<u32>()`.
+    #[wasm_bindgen(js_name = writeU32)]
+    pub fn write_u32(&mut self, value: u32) {
+        self.write(&value);
+    }
+
+    /// Appends a u64 given as a `BigInt`, read in the program with `io::read::<u64>()`.
+    #[wasm_bindgen(js_name = writeU64)]
+    pub fn write_u64(&mut self, value: u64) {
+        self.write(&value);
+    }
+
+    /// Appends a string, read in the program with `io::read::<String>()`.
+    #[wasm_bindgen(js_name = writeString)]
+    pub fn write_string(&mut self, value: &str) {
+        self.write(&value);
+    }
+
+    /// The number of buffers written.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn length(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Serializes the input in the format of `bincode::serialize(&MONEROCHANStdin)`, which is the
+    /// format written by `cargo monerochan stdin` and accepted by the prover network.
+    #[wasm_bindgen(js_name = toBytes)]
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let encoding = Encoding { buffer: self.buffer.clone(), ptr: 0, proofs: Vec::new() };
+        bincode::serialize(&encoding).expect("serialization failed")
+    }
+
+    /// Deserializes an input serialized by `toBytes` or the SDK.
+    #[wasm_bindgen(js_name = fromBytes)]
+    pub fn from_bytes(data: &[u8]) -> Result<Stdin, JsError> {
+        Self::decode(data).map_err(|e| JsError::new(&e))
+    }
+}
+
+impl Stdin {
+    fn write<T: Serialize + ?Sized>(&mut self, data: &T) {
+        self.buffer.push(bincode::serialize(data).expect("serialization failed"));
+    }
+
+    fn decode(data: &[u8]) -> Result<Self, String> {
+        // Only the buffer and the length of the proofs are decoded, so an input with proofs is
+        // rejected rather than silently dropping them.
+        let (buffer, _ptr, num_proofs): (Vec<Vec<u8>>, u64, u64) =
+            bincode::deserialize(data).map_err(|e| format!("invalid stdin: {e}"))?;
+        if num_proofs != 0 {
+            return Err("stdin with proofs is not supported".to_string());
+        }
+        Ok(Self { buffer })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use monerochan_core_machine::io::MONEROCHANStdin;
+
+    use super::*;
+
+    #[test]
+    fn test_encoding_matches_sdk() {
+        let mut stdin = Stdin::new();
+        stdin.write_vec(b"raw");
+        stdin.write_bytes(b"bytes");
+        stdin.write_bool(true);
+        stdin.write_u32(10);
+        stdin.write_u64(u64::MAX);
+        stdin.write_string("hello");
+
+        let mut expected = MONEROCHANStdin::new();
+        expected.write_vec(b"raw".to_vec());
+        expected.write(&b"bytes".to_vec());
+        expected.write(&true);
+        expected.write(&10u32);
+        expected.write(&u64::MAX);
+        expected.write(&"hello".to_string());
+
+        let bytes = stdin.to_bytes();
+        assert_eq!(bytes, bincode::serialize(&expected).unwrap());
+
+        let decoded = Stdin::decode(&bytes).unwrap();
+        assert_eq!(decoded.buffer, expected.buffer);
+    }
+}
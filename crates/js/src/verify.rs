@@ -0,0 +1,23 @@
+use monerochan_verifier::{Groth16Verifier, PlonkVerifier, GROTH16_VK_BYTES, PLONK_VK_BYTES};
+use wasm_bindgen::prelude::*;
+
+/// Verifies a Groth16 proof against the Groth16 verifying key of this MONEROCHAN version, throwing
+/// if the proof is invalid.
+///
+/// `proof` is the output of `MONEROCHANProofWithPublicValues::bytes`, `public_values` the public
+/// values of the proof and `vkey_hash` the `0x`-prefixed output of `vk.bytes32()`.
+#[wasm_bindgen(js_name = verifyGroth16)]
+pub fn verify_groth16(proof: &[u8], public_values: &[u8], vkey_hash: &str) -> Result<(), JsError> {
+    Groth16Verifier::verify(proof, public_values, vkey_hash, &GROTH16_VK_BYTES)
+        .map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Verifies a PLONK proof against the PLONK verifying key of this MONEROCHAN version, throwing if
+/// the proof is invalid.
+///
+/// The arguments are the same as the ones of [`verify_groth16`].
+#[wasm_bindgen(js_name = verifyPlonk)]
+pub fn verify_plonk(proof: &[u8], public_values: &[u8], vkey_hash: &str) -> Result<(), JsError> {
+    PlonkVerifier::verify(proof, public_values, vkey_hash, &PLONK_VK_BYTES)
+        .map_err(|e| JsError::new(&e.to_string()))
+}
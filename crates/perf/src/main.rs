@@ -59,7 +59,7 @@ fn main() {
     let opts = MONEROCHANProverOpts::auto();
 
     let prover = MONEROCHANProver::<CpuProverComponents>::new();
-    let (pk, pk_d, program, vk) = prover.setup(&elf);
+    let (pk, pk_d, program, vk) = prover.setup(&elf).expect("failed to set up the program");
     match args.mode {
         ProverMode::Cpu => {
             let context = MONEROCHANContext::default();
@@ -95,7 +95,7 @@ fn main() {
 
             // Generate a proof that verifies two deferred proofs from the proof above.
             let (_, pk_verify_proof_d, pk_verify_program, vk_verify_proof) =
-                prover.setup(VERIFY_PROOF_ELF);
+                prover.setup(VERIFY_PROOF_ELF).unwrap();
             let pv = core_proof.public_values.to_vec();
 
             let mut stdin = MONEROCHANStdin::new();
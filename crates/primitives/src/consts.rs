@@ -1,3 +1,8 @@
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
 /// The maximum size of the memory in bytes.
 pub const MAXIMUM_MEMORY_SIZE: u32 = u32::MAX;
 
@@ -13,6 +18,35 @@ pub const LONG_WORD_SIZE: usize = 2 * WORD_SIZE;
 /// The Baby Bear prime.
 pub const BABYBEAR_PRIME: u32 = 0x78000001;
 
+/// The size of the guard region below a stack placed after the program, in bytes.
+///
+/// The region starts at the `_monerochan_stack_guard` symbol, and any access to it traps as a
+/// stack overflow instead of silently overwriting the data of the program.
+pub const STACK_GUARD_SIZE: u32 = 0x1000;
+
+/// The prefix of the input buffer written before a named input, followed by the name.
+///
+/// The input buffer following the tag holds the value of the named input.
+pub const NAMED_INPUT_TAG: &[u8] = b"\0monerochan:input:";
+
+/// The header starting the public values of a program that records its outcome with
+/// `monerochan_runtime::io::record_outcome`.
+///
+/// The header is followed by the outcome, a byte which is 0 on success and 1 on a rejection, and
+/// by the error code of the rejection as a little-endian u32, which is 0 on success. The values
+/// committed by the program follow.
+pub const OUTCOME_HEADER: &[u8] = b"\0monerochan:outcome:";
+
+/// The length of the header recording the outcome of a program, with the outcome.
+pub const OUTCOME_HEADER_LEN: usize = OUTCOME_HEADER.len() + 5;
+
+/// The exit code of a program which panicked.
+///
+/// The exit code 0 is that of a successful execution. The codes from 2 are left to the program,
+/// which halts with them through `monerochan_runtime::ensure!`, so that the host can tell why it
+/// failed without parsing its panic message.
+pub const PANIC_EXIT_CODE: u8 = 1;
+
 pub mod fd {
     /// The minimum file descriptor.
     ///
@@ -61,6 +95,9 @@ pub mod fd {
 
         /// The file descriptor through which to access `hook_fp_inverse`.
         pub const FD_FP_INV: u32 = 11;
+
+        /// The file descriptor through which to access `hook_cycle_count`.
+        pub const FD_CYCLE_COUNT: u32 = 12;
     }
 }
 
@@ -1,7 +1,67 @@
-use crate::types::Buffer;
+use crate::{
+    consts::{OUTCOME_HEADER, OUTCOME_HEADER_LEN},
+    types::Buffer,
+};
 use num_bigint::BigUint;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// An error from a checked read of [`MONEROCHANPublicValues`].
+#[derive(Debug, Error)]
+pub enum PublicValuesError {
+    /// The read needs more bytes than remain in the buffer.
+    #[error("read of {requested} bytes overruns the buffer with {remaining} bytes remaining")]
+    ShortRead { requested: usize, remaining: usize },
+    /// The seek position is past the end of the buffer.
+    #[error("seek to {position} is past the end of the buffer of {len} bytes")]
+    SeekOutOfBounds { position: usize, len: usize },
+    /// The bytes are not a valid encoding of the value.
+    #[error("failed to deserialize public value: {0}")]
+    Deserialize(#[from] bincode::Error),
+    /// The public values do not start with the header recording the outcome of the program.
+    #[error("the public values do not record the outcome of the program")]
+    MissingOutcome,
+}
+
+/// The outcome of a program, as recorded in the header of its public values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Outcome {
+    /// The program succeeded.
+    Success,
+    /// The program rejected its input with an error code, with `monerochan_runtime::io::reject`.
+    Rejected(u32),
+}
+
+impl Outcome {
+    /// The header recording the outcome, which starts the public values of the program, see
+    /// [`OUTCOME_HEADER`].
+    pub fn header(self) -> [u8; OUTCOME_HEADER_LEN] {
+        let (tag, code) = match self {
+            Self::Success => (0, 0),
+            Self::Rejected(code) => (1, code),
+        };
+        let mut header = [0; OUTCOME_HEADER_LEN];
+        header[..OUTCOME_HEADER.len()].copy_from_slice(OUTCOME_HEADER);
+        header[OUTCOME_HEADER.len()] = tag;
+        header[OUTCOME_HEADER.len() + 1..].copy_from_slice(&code.to_le_bytes());
+        header
+    }
+
+    /// Parses the header recording the outcome at the start of `bytes`.
+    fn parse(bytes: &[u8]) -> Result<Self, PublicValuesError> {
+        let outcome = bytes
+            .get(..OUTCOME_HEADER_LEN)
+            .and_then(|header| header.strip_prefix(OUTCOME_HEADER))
+            .ok_or(PublicValuesError::MissingOutcome)?;
+        let code = u32::from_le_bytes(outcome[1..].try_into().unwrap());
+        match (outcome[0], code) {
+            (0, 0) => Ok(Self::Success),
+            (1, code) => Ok(Self::Rejected(code)),
+            _ => Err(PublicValuesError::MissingOutcome),
+        }
+    }
+}
 
 /// Public values for the prover.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -52,6 +112,123 @@ impl MONEROCHANPublicValues {
         self.buffer.write_slice(slice);
     }
 
+    /// Read a length-prefixed slice of bytes from the buffer, as committed by
+    /// `monerochan_runtime::io::commit_framed`.
+    ///
+    /// # Panics
+    /// Panics if the buffer does not hold a complete frame.
+    pub fn read_framed(&mut self) -> Vec<u8> {
+        self.try_read_framed().unwrap_or_else(|err| panic!("framed public value: {err}"))
+    }
+
+    /// The total number of bytes in the buffer.
+    pub fn len(&self) -> usize {
+        self.buffer.data.len()
+    }
+
+    /// Whether the buffer holds no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.buffer.data.is_empty()
+    }
+
+    /// The position of the read cursor.
+    pub fn position(&self) -> usize {
+        self.buffer.ptr
+    }
+
+    /// The number of bytes left to read after the cursor.
+    pub fn remaining(&self) -> usize {
+        self.len().saturating_sub(self.buffer.ptr)
+    }
+
+    /// Move the read cursor back to the start of the buffer.
+    pub fn rewind(&mut self) {
+        self.buffer.head();
+    }
+
+    /// Move the read cursor to `position`, which may be the end of the buffer.
+    pub fn seek(&mut self, position: usize) -> Result<(), PublicValuesError> {
+        if position > self.len() {
+            return Err(PublicValuesError::SeekOutOfBounds { position, len: self.len() });
+        }
+        self.buffer.ptr = position;
+        Ok(())
+    }
+
+    /// Read a value from the buffer, returning an error instead of panicking if the remaining
+    /// bytes do not hold one. The cursor is only advanced on success.
+    pub fn try_read<T: DeserializeOwned>(&mut self) -> Result<T, PublicValuesError> {
+        let mut reader = self.unread();
+        let value = bincode::deserialize_from(&mut reader)?;
+        self.buffer.ptr = self.len() - reader.len();
+        Ok(value)
+    }
+
+    /// Fill `slice` from the buffer, returning an error instead of panicking on a short read. The
+    /// cursor is only advanced on success.
+    pub fn try_read_slice(&mut self, slice: &mut [u8]) -> Result<(), PublicValuesError> {
+        let bytes = take(self.unread(), slice.len())?;
+        slice.copy_from_slice(bytes);
+        self.buffer.ptr += slice.len();
+        Ok(())
+    }
+
+    /// Read a length-prefixed slice of bytes from the buffer, returning an error instead of
+    /// panicking if the buffer does not hold a complete frame. The cursor is only advanced on
+    /// success.
+    pub fn try_read_framed(&mut self) -> Result<Vec<u8>, PublicValuesError> {
+        let (data, rest) = split_frame(self.unread())?;
+        let data = data.to_vec();
+        self.buffer.ptr = self.len() - rest.len();
+        Ok(data)
+    }
+
+    /// Iterate over the framed values after the cursor, without moving it.
+    ///
+    /// The iterator stops after the first incomplete frame, which it yields as an error.
+    pub fn framed(&self) -> FramedValues<'_> {
+        FramedValues { rest: self.unread() }
+    }
+
+    /// The bytes after the cursor.
+    fn unread(&self) -> &[u8] {
+        self.buffer.data.get(self.buffer.ptr..).unwrap_or_default()
+    }
+
+    /// Write a slice of bytes to the buffer, prefixed with its length as a little-endian u32.
+    pub fn write_framed(&mut self, slice: &[u8]) {
+        let len = u32::try_from(slice.len()).expect("framed public value is too long");
+        self.buffer.write_slice(&len.to_le_bytes());
+        self.buffer.write_slice(slice);
+    }
+
+    /// The outcome of the program, as recorded in the header of its public values by
+    /// `monerochan_runtime::io::record_outcome`.
+    ///
+    /// The header is proven like any other public value, so a proof of a rejected input proves
+    /// that the input is invalid. Public values without the header are an error rather than a
+    /// success, so that the values committed by a program cannot be taken for an outcome.
+    pub fn outcome(&self) -> Result<Outcome, PublicValuesError> {
+        Outcome::parse(self.as_slice())
+    }
+
+    /// The public values committed by the program after the header recording its outcome.
+    ///
+    /// The values committed before a rejection are kept, so that the program can commit what it
+    /// found invalid in its input.
+    pub fn committed(&self) -> Result<&[u8], PublicValuesError> {
+        Outcome::parse(self.as_slice())?;
+        Ok(&self.as_slice()[OUTCOME_HEADER_LEN..])
+    }
+
+    /// Read the header recording the outcome of the program, at the cursor, so that the values it
+    /// committed can be read after it. The cursor is only advanced on success.
+    pub fn read_outcome(&mut self) -> Result<Outcome, PublicValuesError> {
+        let outcome = Outcome::parse(self.unread())?;
+        self.buffer.ptr += OUTCOME_HEADER_LEN;
+        Ok(outcome)
+    }
+
     /// Hash the public values using SHA256.
     pub fn hash(&self) -> Vec<u8> {
         sha256_hash(self.buffer.data.as_slice())
@@ -95,6 +272,46 @@ impl AsRef<[u8]> for MONEROCHANPublicValues {
     }
 }
 
+/// An iterator over the framed values of [`MONEROCHANPublicValues`], created by
+/// [`MONEROCHANPublicValues::framed`].
+#[derive(Debug, Clone)]
+pub struct FramedValues<'a> {
+    rest: &'a [u8],
+}
+
+impl<'a> Iterator for FramedValues<'a> {
+    type Item = Result<&'a [u8], PublicValuesError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        match split_frame(self.rest) {
+            Ok((data, rest)) => {
+                self.rest = rest;
+                Some(Ok(data))
+            }
+            Err(err) => {
+                self.rest = &[];
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// Return the first `len` bytes of `bytes`.
+fn take(bytes: &[u8], len: usize) -> Result<&[u8], PublicValuesError> {
+    bytes.get(..len).ok_or(PublicValuesError::ShortRead { requested: len, remaining: bytes.len() })
+}
+
+/// Split a length-prefixed frame off `bytes`, returning its data and the bytes after it.
+fn split_frame(bytes: &[u8]) -> Result<(&[u8], &[u8]), PublicValuesError> {
+    let len = take(bytes, 4)?;
+    let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+    let data = take(&bytes[4..], len)?;
+    Ok((data, &bytes[4 + len..]))
+}
+
 /// Hash the input using SHA256.
 pub fn sha256_hash(input: &[u8]) -> Vec<u8> {
     let mut hasher = Sha256::new();
@@ -140,4 +357,112 @@ mod tests {
 
         assert_eq!(hash, expected_hash_biguint);
     }
+
+    #[test]
+    fn test_outcome() {
+        let mut public_values = MONEROCHANPublicValues::new();
+        public_values.write_slice(&Outcome::Rejected(3).header());
+        public_values.write::<u32>(&7);
+        assert_eq!(public_values.outcome().unwrap(), Outcome::Rejected(3));
+        assert_eq!(public_values.committed().unwrap(), 7u32.to_le_bytes());
+        assert_eq!(public_values.read_outcome().unwrap(), Outcome::Rejected(3));
+        assert_eq!(public_values.read::<u32>(), 7);
+
+        let success = MONEROCHANPublicValues::from(&Outcome::Success.header());
+        assert_eq!(success.outcome().unwrap(), Outcome::Success);
+        assert!(success.committed().unwrap().is_empty());
+
+        // Values committed without the header, even ending like a rejection, have no outcome.
+        let mut unrecorded = MONEROCHANPublicValues::new();
+        unrecorded.write::<u32>(&7);
+        unrecorded.write_slice(&Outcome::Rejected(3).header());
+        assert!(matches!(unrecorded.outcome(), Err(PublicValuesError::MissingOutcome)));
+        assert!(unrecorded.read_outcome().is_err());
+        assert_eq!(unrecorded.position(), 0);
+
+        let mut invalid = Outcome::Success.header();
+        invalid[OUTCOME_HEADER.len()] = 2;
+        assert!(MONEROCHANPublicValues::from(&invalid).outcome().is_err());
+    }
+
+    #[test]
+    fn test_framed_public_values() {
+        let mut public_values = MONEROCHANPublicValues::new();
+        public_values.write_framed(b"hello");
+        public_values.write_framed(&[]);
+        public_values.write::<u32>(&7);
+
+        assert_eq!(&public_values.as_slice()[..9], b"\x05\0\0\0hello");
+        assert_eq!(public_values.read_framed(), b"hello");
+        assert_eq!(public_values.read_framed(), b"");
+        assert_eq!(public_values.read::<u32>(), 7);
+    }
+
+    #[test]
+    fn test_public_values_cursor() {
+        let mut public_values = MONEROCHANPublicValues::new();
+        public_values.write::<u32>(&7);
+        public_values.write::<u64>(&9);
+        assert_eq!(public_values.len(), 12);
+
+        assert_eq!(public_values.try_read::<u32>().unwrap(), 7);
+        assert_eq!((public_values.position(), public_values.remaining()), (4, 8));
+
+        public_values.rewind();
+        assert_eq!(public_values.read::<u32>(), 7);
+
+        public_values.seek(12).unwrap();
+        assert!(matches!(public_values.try_read::<u32>(), Err(PublicValuesError::Deserialize(_))));
+        assert!(matches!(
+            public_values.seek(13),
+            Err(PublicValuesError::SeekOutOfBounds { position: 13, len: 12 })
+        ));
+        assert_eq!(public_values.position(), 12);
+    }
+
+    #[test]
+    fn test_checked_short_reads() {
+        let mut public_values = MONEROCHANPublicValues::from(&[8, 0, 0, 0, 1, 2]);
+
+        assert!(matches!(
+            public_values.try_read_framed(),
+            Err(PublicValuesError::ShortRead { requested: 8, remaining: 2 })
+        ));
+        assert!(matches!(
+            public_values.try_read_slice(&mut [0; 7]),
+            Err(PublicValuesError::ShortRead { requested: 7, remaining: 6 })
+        ));
+        assert!(public_values.try_read::<[u8; 8]>().is_err());
+        assert_eq!(public_values.position(), 0);
+
+        let mut slice = [0; 4];
+        public_values.try_read_slice(&mut slice).unwrap();
+        assert_eq!(slice, [8, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_framed_values_iterator() {
+        let mut public_values = MONEROCHANPublicValues::new();
+        public_values.write_framed(b"first");
+        public_values.write_framed(b"");
+        public_values.write_framed(b"third");
+        public_values.write_slice(&[3, 0, 0, 0, 1]);
+
+        let values = public_values.framed().collect::<Vec<_>>();
+        assert_eq!(values.len(), 4);
+        assert_eq!(values[0].as_ref().unwrap(), b"first");
+        assert_eq!(values[1].as_ref().unwrap(), b"");
+        assert_eq!(values[2].as_ref().unwrap(), b"third");
+        assert!(values[3].is_err());
+
+        public_values.read_framed();
+        assert_eq!(public_values.framed().count(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "overruns the buffer")]
+    fn test_truncated_framed_public_values() {
+        let mut public_values = MONEROCHANPublicValues::from(&[8, 0, 0, 0, 1, 2]);
+        public_values.read_framed();
+    }
 }
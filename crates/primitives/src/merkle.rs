@@ -0,0 +1,218 @@
+//! A SHA-256 Merkle tree over committed values, for revealing single public values on-chain.
+//!
+//! Instead of committing every value, the guest pushes them into a [`MerkleTree`] and commits only
+//! its 32 byte root with `monerochan_runtime::io::commit_merkle_root`. The host rebuilds the same
+//! tree from the same values and hands out a [`MerkleProof`] for each value a consumer needs, so
+//! that only the root, the value and a logarithmic number of hashes have to be posted.
+//!
+//! Leaves are hashed as `sha256(0x00 || value)` and inner nodes as `sha256(0x01 || left || right)`,
+//! so a leaf can never be mistaken for a node. A node without a sibling is carried to the next
+//! level unchanged, and the root of an empty tree is all zeros.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// The domain separator of leaf hashes.
+const LEAF_PREFIX: u8 = 0;
+
+/// The domain separator of inner node hashes.
+const NODE_PREFIX: u8 = 1;
+
+/// A Merkle tree whose leaves are values committed by the guest.
+#[derive(Debug, Clone, Default)]
+pub struct MerkleTree {
+    leaves: Vec<[u8; 32]>,
+}
+
+/// A proof that a value is a leaf of a [`MerkleTree`] with a given root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    /// The index of the leaf.
+    pub index: u32,
+    /// The number of leaves in the tree.
+    pub leaf_count: u32,
+    /// The siblings of the path from the leaf to the root, bottom up.
+    pub siblings: Vec<[u8; 32]>,
+}
+
+impl MerkleTree {
+    /// Create an empty `MerkleTree`.
+    pub const fn new() -> Self {
+        Self { leaves: Vec::new() }
+    }
+
+    /// Push a value as a leaf, serialized with bincode like `monerochan_runtime::io::commit`.
+    pub fn push<T: Serialize>(&mut self, value: &T) {
+        self.push_slice(&bincode::serialize(value).expect("serialization failed"));
+    }
+
+    /// Push a slice of bytes as a leaf.
+    pub fn push_slice(&mut self, data: &[u8]) {
+        self.leaves.push(leaf_hash(data));
+    }
+
+    /// The number of leaves in the tree.
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// Whether the tree has no leaves.
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// Compute the root of the tree.
+    pub fn root(&self) -> [u8; 32] {
+        if self.leaves.is_empty() {
+            return [0; 32];
+        }
+        let mut level = self.leaves.clone();
+        while level.len() > 1 {
+            level = next_level(&level);
+        }
+        level[0]
+    }
+
+    /// Compute the proof of inclusion of the leaf at `index`.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn prove(&self, index: usize) -> MerkleProof {
+        assert!(index < self.leaves.len(), "leaf index {index} is out of bounds");
+        let leaf_count = u32::try_from(self.leaves.len()).expect("too many leaves");
+
+        let mut siblings = Vec::new();
+        let mut level = self.leaves.clone();
+        let mut position = index;
+        while level.len() > 1 {
+            if let Some(sibling) = level.get(position ^ 1) {
+                siblings.push(*sibling);
+            }
+            level = next_level(&level);
+            position /= 2;
+        }
+
+        MerkleProof { index: index as u32, leaf_count, siblings }
+    }
+}
+
+impl MerkleProof {
+    /// Verify that `value`, serialized with bincode, is the proven leaf of the tree with `root`.
+    pub fn verify<T: Serialize>(&self, root: &[u8; 32], value: &T) -> bool {
+        self.verify_slice(root, &bincode::serialize(value).expect("serialization failed"))
+    }
+
+    /// Verify that `data` is the proven leaf of the tree with `root`.
+    pub fn verify_slice(&self, root: &[u8; 32], data: &[u8]) -> bool {
+        if self.index >= self.leaf_count {
+            return false;
+        }
+
+        let mut node = leaf_hash(data);
+        let mut siblings = self.siblings.iter();
+        let (mut position, mut count) = (self.index, self.leaf_count);
+        while count > 1 {
+            // The last node of a level with an odd number of nodes has no sibling.
+            if position ^ 1 < count {
+                let Some(sibling) = siblings.next() else {
+                    return false;
+                };
+                node = if position % 2 == 0 {
+                    node_hash(&node, sibling)
+                } else {
+                    node_hash(sibling, &node)
+                };
+            }
+            position /= 2;
+            count = count.div_ceil(2);
+        }
+
+        siblings.next().is_none() && node == *root
+    }
+}
+
+/// Hash a value into a leaf.
+pub fn leaf_hash(data: &[u8]) -> [u8; 32] {
+    Sha256::new().chain_update([LEAF_PREFIX]).chain_update(data).finalize().into()
+}
+
+/// Hash two children into their parent node.
+pub fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update([NODE_PREFIX])
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+/// Hash each pair of nodes of a level, carrying an unpaired last node up unchanged.
+fn next_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => node_hash(left, right),
+            [node] => *node,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merkle_proofs() {
+        for leaf_count in 1..=9u32 {
+            let mut tree = MerkleTree::new();
+            for i in 0..leaf_count {
+                tree.push(&i);
+            }
+            let root = tree.root();
+
+            for i in 0..leaf_count {
+                let proof = tree.prove(i as usize);
+                assert!(proof.verify(&root, &i));
+                assert!(!proof.verify(&root, &(i + 1)));
+                assert!(!proof.verify(&[0; 32], &i));
+            }
+        }
+    }
+
+    #[test]
+    fn test_merkle_root() {
+        let mut tree = MerkleTree::new();
+        assert_eq!(tree.root(), [0; 32]);
+
+        tree.push_slice(b"a");
+        assert_eq!(tree.root(), leaf_hash(b"a"));
+
+        tree.push_slice(b"b");
+        tree.push_slice(b"c");
+        let ab = node_hash(&leaf_hash(b"a"), &leaf_hash(b"b"));
+        assert_eq!(tree.root(), node_hash(&ab, &leaf_hash(b"c")));
+    }
+
+    #[test]
+    fn test_tampered_merkle_proof() {
+        let mut tree = MerkleTree::new();
+        for i in 0..5u32 {
+            tree.push(&i);
+        }
+        let root = tree.root();
+        let proof = tree.prove(2);
+
+        let mut moved = proof.clone();
+        moved.index = 3;
+        assert!(!moved.verify(&root, &2u32));
+
+        let mut truncated = proof.clone();
+        truncated.siblings.pop();
+        assert!(!truncated.verify(&root, &2u32));
+
+        let mut extended = proof;
+        extended.siblings.push([0; 32]);
+        assert!(!extended.verify(&root, &2u32));
+    }
+}
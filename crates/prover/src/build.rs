@@ -23,7 +23,7 @@ pub use monerochan_recursion_core::stark::monerochan_dev_mode;
 
 use crate::{
     utils::{babybear_bytes_to_bn254, babybears_to_bn254, words_to_bytes},
-    OuterSC, MONEROCHANProver, WrapAir,
+    OuterSC, MONEROCHANProver, WrapAir, WrapOpts,
 };
 
 /// Tries to build the PLONK artifacts inside the development directory.
@@ -64,10 +64,27 @@ pub fn build_plonk_bn254_artifacts(
     template_vk: &StarkVerifyingKey<OuterSC>,
     template_proof: &ShardProof<OuterSC>,
     build_dir: impl Into<PathBuf>,
+) {
+    build_plonk_bn254_artifacts_with_opts(
+        template_vk,
+        template_proof,
+        build_dir,
+        &WrapOpts::default(),
+    );
+}
+
+/// Build the plonk bn254 artifacts to the given directory for the given verification key and
+/// template proof, with a wrap circuit built from the given options.
+pub fn build_plonk_bn254_artifacts_with_opts(
+    template_vk: &StarkVerifyingKey<OuterSC>,
+    template_proof: &ShardProof<OuterSC>,
+    build_dir: impl Into<PathBuf>,
+    opts: &WrapOpts,
 ) {
     let build_dir = build_dir.into();
     std::fs::create_dir_all(&build_dir).expect("failed to create build directory");
-    let (constraints, witness) = build_constraints_and_witness(template_vk, template_proof);
+    let (constraints, witness) =
+        build_constraints_and_witness_with_opts(template_vk, template_proof, opts);
     PlonkBn254Prover::build(constraints, witness, build_dir);
 }
 
@@ -77,10 +94,27 @@ pub fn build_groth16_bn254_artifacts(
     template_vk: &StarkVerifyingKey<OuterSC>,
     template_proof: &ShardProof<OuterSC>,
     build_dir: impl Into<PathBuf>,
+) {
+    build_groth16_bn254_artifacts_with_opts(
+        template_vk,
+        template_proof,
+        build_dir,
+        &WrapOpts::default(),
+    );
+}
+
+/// Build the groth16 bn254 artifacts to the given directory for the given verification key and
+/// template proof, with a wrap circuit built from the given options.
+pub fn build_groth16_bn254_artifacts_with_opts(
+    template_vk: &StarkVerifyingKey<OuterSC>,
+    template_proof: &ShardProof<OuterSC>,
+    build_dir: impl Into<PathBuf>,
+    opts: &WrapOpts,
 ) {
     let build_dir = build_dir.into();
     std::fs::create_dir_all(&build_dir).expect("failed to create build directory");
-    let (constraints, witness) = build_constraints_and_witness(template_vk, template_proof);
+    let (constraints, witness) =
+        build_constraints_and_witness_with_opts(template_vk, template_proof, opts);
     Groth16Bn254Prover::build(constraints, witness, build_dir);
 }
 
@@ -122,6 +156,18 @@ pub fn build_groth16_bn254_artifacts_with_dummy(build_dir: impl Into<PathBuf>) {
 pub fn build_constraints_and_witness(
     template_vk: &StarkVerifyingKey<OuterSC>,
     template_proof: &ShardProof<OuterSC>,
+) -> (Vec<Constraint>, OuterWitness<OuterConfig>) {
+    build_constraints_and_witness_with_opts(template_vk, template_proof, &WrapOpts::default())
+}
+
+/// Build the verifier constraints and template witness for the circuit, verifying wrap proofs
+/// generated with the given options.
+///
+/// The template proof must have been generated by a prover with the same options.
+pub fn build_constraints_and_witness_with_opts(
+    template_vk: &StarkVerifyingKey<OuterSC>,
+    template_proof: &ShardProof<OuterSC>,
+    opts: &WrapOpts,
 ) -> (Vec<Constraint>, OuterWitness<OuterConfig>) {
     tracing::info!("building verifier constraints");
     let template_input = MONEROCHANCompressWitnessValues {
@@ -129,7 +175,7 @@ pub fn build_constraints_and_witness(
         is_complete: true,
     };
     let constraints =
-        tracing::info_span!("wrap circuit").in_scope(|| build_outer_circuit(&template_input));
+        tracing::info_span!("wrap circuit").in_scope(|| build_outer_circuit(&template_input, opts));
 
     let pv: &RecursionPublicValues<BabyBear> = template_proof.public_values.as_slice().borrow();
     let vkey_hash = babybears_to_bn254(&pv.monerochan_vk_digest);
@@ -157,7 +203,7 @@ pub fn dummy_proof() -> (StarkVerifyingKey<OuterSC>, ShardProof<OuterSC>) {
     let context = MONEROCHANContext::default();
 
     tracing::info!("setup elf");
-    let (_, pk_d, program, vk) = prover.setup(elf);
+    let (_, pk_d, program, vk) = prover.setup(elf).unwrap();
 
     tracing::info!("prove core");
     let mut stdin = MONEROCHANStdin::new();
@@ -176,8 +222,13 @@ pub fn dummy_proof() -> (StarkVerifyingKey<OuterSC>, ShardProof<OuterSC>) {
     (wrapped_proof.vk, wrapped_proof.proof)
 }
 
-fn build_outer_circuit(template_input: &MONEROCHANCompressWitnessValues<OuterSC>) -> Vec<Constraint> {
-    let wrap_machine = WrapAir::wrap_machine(OuterSC::default());
+fn build_outer_circuit(
+    template_input: &MONEROCHANCompressWitnessValues<OuterSC>,
+    opts: &WrapOpts,
+) -> Vec<Constraint> {
+    // The circuit verifies proofs with the FRI parameters of the wrap prover for these options.
+    let wrap_machine =
+        WrapAir::wrap_machine(OuterSC::new_with_fri_params(opts.log_blowup, opts.num_queries));
 
     let wrap_span = tracing::debug_span!("build wrap circuit").entered();
     let mut builder = Builder::<OuterConfig>::default();
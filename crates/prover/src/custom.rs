@@ -0,0 +1,236 @@
+//! Custom recursion programs.
+//!
+//! The compress, shrink and wrap programs cover the aggregation topology of [`MONEROCHANProver`].
+//! Applications that need another topology, e.g. a binary tree of 64 leaves whose nodes check an
+//! application-specific relation between their children, can write their own programs in the
+//! recursion DSL and prove them with the compress prover.
+//!
+//! A [`CustomRecursionProgram`] is compiled once for the shape of its input, a [`Witnessable`]
+//! value, and can then be proven on any input of the same shape. The verifiers of the
+//! `monerochan_recursion_circuit::machine` module can be used in the program to verify the
+//! proofs of other programs, such as [`MONEROCHANReduceProof`]s of the compress stage or of other
+//! custom programs.
+//!
+//! The verifying key of a custom program is not in the allowed recursion verification keys of the
+//! prover, so its proofs can only be verified by programs that do not check the verification keys
+//! against them. Outside of recursion, the proofs are verified against the verifying key of the
+//! program, given by [`MONEROCHANProver::custom_program_vk`].
+//!
+//! # Example
+//! ```ignore
+//! use monerochan_prover::{components::CpuProverComponents, MONEROCHANProver};
+//! use monerochan_stark::MONEROCHANProverOpts;
+//!
+//! let prover = MONEROCHANProver::<CpuProverComponents>::new();
+//!
+//! // A program checking that its two inputs are equal.
+//! let input = (BabyBear::one(), BabyBear::one());
+//! let program = prover.compile_custom_program(&input, |builder, (a, b)| {
+//!     builder.assert_felt_eq(a, b);
+//! });
+//!
+//! let vk = prover.custom_program_vk(&program);
+//! let proof = prover.prove_custom_program(&program, &input, MONEROCHANProverOpts::auto())?;
+//! prover.verify_custom_program(&vk, &proof)?;
+//! ```
+
+use std::{marker::PhantomData, sync::Arc};
+
+use p3_baby_bear::BabyBear;
+use monerochan_recursion_circuit::witness::Witnessable;
+use monerochan_recursion_compiler::{
+    circuit::AsmCompiler,
+    config::InnerConfig,
+    ir::{Builder, DslIrProgram},
+};
+use monerochan_recursion_core::{RecursionProgram, Runtime as RecursionRuntime};
+use monerochan_stark::{
+    Challenge, MachineProof, MachineProver, MachineProvingKey, MachineVerificationError,
+    MONEROCHANProverOpts, MONEROCHANReduceProof, StarkGenericConfig, StarkVerifyingKey, Val,
+};
+
+use crate::{
+    components::MONEROCHANProverComponents, HashableKey, InnerSC, MONEROCHANProver,
+    MONEROCHANRecursionProverError,
+};
+
+/// A recursion program written with the recursion DSL, whose input is a witness of type `I`.
+pub struct CustomRecursionProgram<I> {
+    program: Arc<RecursionProgram<BabyBear>>,
+    _input: PhantomData<fn(&I)>,
+}
+
+impl<I> CustomRecursionProgram<I> {
+    /// The compiled program.
+    pub fn program(&self) -> &Arc<RecursionProgram<BabyBear>> {
+        &self.program
+    }
+}
+
+impl<I> Clone for CustomRecursionProgram<I> {
+    fn clone(&self) -> Self {
+        Self { program: self.program.clone(), _input: PhantomData }
+    }
+}
+
+impl<C: MONEROCHANProverComponents> MONEROCHANProver<C> {
+    /// Compiles a custom recursion program for inputs of the shape of `input`.
+    ///
+    /// The input is read into the program, and `build` writes the body of the program with the
+    /// variables of the input. Only the shape of the input matters: the program can be proven on
+    /// any input of the same shape, such as proofs with the same chips and trace heights.
+    pub fn compile_custom_program<I: Witnessable<InnerConfig>>(
+        &self,
+        input: &I,
+        build: impl FnOnce(&mut Builder<InnerConfig>, I::WitnessVariable),
+    ) -> CustomRecursionProgram<I> {
+        // Get the operations.
+        let builder_span = tracing::debug_span!("build custom program").entered();
+        let mut builder = Builder::<InnerConfig>::default();
+        let input = input.read(&mut builder);
+        build(&mut builder, input);
+        let block = builder.into_root_block();
+        builder_span.exit();
+        // SAFETY: The circuits of the DSL do not use synchronization primitives (or possibly other
+        // means) to violate the invariants.
+        let dsl_program = unsafe { DslIrProgram::new_unchecked(block) };
+
+        // Compile the program.
+        let compiler_span = tracing::debug_span!("compile custom program").entered();
+        let mut compiler = AsmCompiler::<InnerConfig>::default();
+        let program = Arc::new(compiler.compile(dsl_program));
+        compiler_span.exit();
+
+        CustomRecursionProgram { program, _input: PhantomData }
+    }
+
+    /// Proves a custom recursion program on an input, with the compress prover.
+    ///
+    /// The input must have the shape of the input the program was compiled for.
+    pub fn prove_custom_program<I: Witnessable<InnerConfig>>(
+        &self,
+        program: &CustomRecursionProgram<I>,
+        input: &I,
+        opts: MONEROCHANProverOpts,
+    ) -> Result<MONEROCHANReduceProof<InnerSC>, MONEROCHANRecursionProverError> {
+        let program = program.program.clone();
+
+        // Execute the runtime.
+        let record = tracing::debug_span!("execute runtime").in_scope(|| {
+            let mut witness_stream = Vec::new();
+            Witnessable::<InnerConfig>::write(input, &mut witness_stream);
+            let mut runtime = RecursionRuntime::<Val<InnerSC>, Challenge<InnerSC>, _>::new(
+                program.clone(),
+                self.compress_prover.config().perm.clone(),
+            );
+            runtime.witness_stream = witness_stream.into();
+            runtime
+                .run()
+                .map_err(|e| MONEROCHANRecursionProverError::RuntimeError(e.to_string()))?;
+            Ok::<_, MONEROCHANRecursionProverError>(runtime.record)
+        })?;
+
+        // Generate the dependencies and the traces.
+        let mut records = vec![record];
+        tracing::debug_span!("generate dependencies").in_scope(|| {
+            self.compress_prover.machine().generate_dependencies(
+                &mut records,
+                &opts.recursion_opts,
+                None,
+            )
+        });
+        let record = records.into_iter().next().unwrap();
+        let traces = tracing::debug_span!("generate traces")
+            .in_scope(|| self.compress_prover.generate_traces(&record));
+
+        // Get the keys and observe the proving key.
+        let (pk, vk) = tracing::debug_span!("setup custom program")
+            .in_scope(|| self.compress_prover.setup(&program));
+        let mut challenger = self.compress_prover.config().challenger();
+        pk.observe_into(&mut challenger);
+
+        // Commit to the record and traces, and generate the proof.
+        let data = tracing::debug_span!("commit")
+            .in_scope(|| self.compress_prover.commit(&record, traces));
+        let proof = tracing::debug_span!("open")
+            .in_scope(|| {
+                if opts.recursion_opts.seed.is_some() {
+                    self.compress_prover.open_deterministic(&pk, data, &mut challenger)
+                } else {
+                    self.compress_prover.open(&pk, data, &mut challenger)
+                }
+            })
+            .map_err(|e| MONEROCHANRecursionProverError::RuntimeError(e.to_string()))?;
+
+        Ok(MONEROCHANReduceProof { vk, proof })
+    }
+
+    /// Gets the verifying key of a custom recursion program.
+    pub fn custom_program_vk<I>(
+        &self,
+        program: &CustomRecursionProgram<I>,
+    ) -> StarkVerifyingKey<InnerSC> {
+        tracing::debug_span!("setup custom program")
+            .in_scope(|| self.compress_prover.setup(&program.program).1)
+    }
+
+    /// Verifies the proof of a custom recursion program against its verifying key.
+    ///
+    /// The verifying key carried by the proof must be `vk`, otherwise a proof of any program
+    /// would be accepted. Unlike [`MONEROCHANProver::verify_compressed`], the public values of the
+    /// proof are not checked, since their meaning is up to the program.
+    pub fn verify_custom_program(
+        &self,
+        vk: &StarkVerifyingKey<InnerSC>,
+        proof: &MONEROCHANReduceProof<InnerSC>,
+    ) -> Result<(), MachineVerificationError<InnerSC>> {
+        let MONEROCHANReduceProof { vk: proof_vk, proof } = proof;
+        if proof_vk.hash_babybear() != vk.hash_babybear() {
+            return Err(MachineVerificationError::InvalidVerificationKey);
+        }
+        let mut challenger = self.compress_prover.config().challenger();
+        let machine_proof = MachineProof { shard_proofs: vec![proof.clone()] };
+        self.compress_prover.machine().verify(vk, &machine_proof, &mut challenger)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_field::AbstractField;
+    use monerochan_core_machine::utils::setup_logger;
+    use serial_test::serial;
+
+    use super::*;
+    use crate::components::CpuProverComponents;
+
+    #[test]
+    #[serial]
+    fn test_custom_program() {
+        setup_logger();
+        let prover = MONEROCHANProver::<CpuProverComponents>::new();
+
+        let input = (BabyBear::from_canonical_u32(7), BabyBear::from_canonical_u32(7));
+        let program = prover.compile_custom_program(&input, |builder, (a, b)| {
+            builder.assert_felt_eq(a, b);
+        });
+
+        let vk = prover.custom_program_vk(&program);
+        let opts = MONEROCHANProverOpts::auto();
+        let proof = prover.prove_custom_program(&program, &input, opts).unwrap();
+        prover.verify_custom_program(&vk, &proof).unwrap();
+
+        // The proof is rejected against the verifying key of another program.
+        let other = prover.compile_custom_program(&input, |builder, (a, b)| {
+            builder.assert_felt_ne(a, b);
+        });
+        let other_vk = prover.custom_program_vk(&other);
+        assert!(matches!(
+            prover.verify_custom_program(&other_vk, &proof),
+            Err(MachineVerificationError::InvalidVerificationKey)
+        ));
+
+        // The program rejects inputs that do not satisfy its assertions.
+        let input = (BabyBear::from_canonical_u32(7), BabyBear::from_canonical_u32(8));
+        assert!(prover.prove_custom_program(&program, &input, opts).is_err());
+    }
+}
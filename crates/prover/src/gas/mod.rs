@@ -3,13 +3,15 @@ mod model;
 pub use model::*;
 use thiserror::Error;
 
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::BTreeMap};
 
 use enum_map::EnumMap;
 use hashbrown::HashMap;
 use p3_field::PrimeField32;
 
-use monerochan_core_executor::{estimator::RecordEstimator, RiscvAirId};
+use monerochan_core_executor::{
+    estimator::RecordEstimator, rv32im_costs, rv32im_opcode_weights, Opcode, RiscvAirId,
+};
 use monerochan_core_machine::shape::{CoreShapeConfig, CoreShapeError, Shapeable, ShardKind};
 use monerochan_stark::{shape::Shape, MONEROCHANCoreOpts, SplitOpts};
 
@@ -27,6 +29,9 @@ pub const GAS_OPTS: MONEROCHANCoreOpts = MONEROCHANCoreOpts {
     trace_gen_workers: 4,
     checkpoints_channel_capacity: 128,
     records_and_traces_channel_capacity: 4,
+    fri_queries: None,
+    seed: None,
+    numa_node: None,
 };
 
 #[derive(Error, Debug)]
@@ -52,6 +57,18 @@ pub fn final_transform(raw_gas: f64) -> Result<u64, GasError> {
     }
 }
 
+/// The weight of each opcode in cycles: the trace area that one of its executions adds to the
+/// core chips, relative to an `ADD`.
+///
+/// A division, for instance, is also proven by two multiplications, two additions and a
+/// comparison, so it weighs several cycles.
+pub fn opcode_cycle_weights() -> EnumMap<Opcode, f64> {
+    let costs = rv32im_costs().into_iter().map(|(air, cost)| (air, cost as u64)).collect();
+    let weights = rv32im_opcode_weights(&costs);
+    let add_weight = weights[Opcode::ADD] as f64;
+    EnumMap::from_fn(|opcode| weights[opcode] as f64 / add_weight)
+}
+
 /// Calculates core, precompile, mem records. Does not implement packed or last shard logic.
 pub fn estimated_records<'a>(
     split_opts: &SplitOpts,
@@ -118,6 +135,44 @@ pub fn estimated_records<'a>(
     core_records.chain(global_memory_records).chain(precompile_records)
 }
 
+/// The shards the core prover is estimated to split an execution into.
+///
+/// The estimate follows [`estimated_records`], so shards are not packed together and the last
+/// shard is not special-cased.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShardPlan {
+    /// The number of cycles per core shard of the options the plan was made with.
+    pub shard_size: usize,
+    /// The estimated number of rows of each chip, for each shard.
+    pub shards: Vec<EnumMap<RiscvAirId, u64>>,
+}
+
+impl ShardPlan {
+    /// Plans the shards of an execution from its record estimate.
+    pub fn new(opts: &MONEROCHANCoreOpts, estimator: &RecordEstimator) -> Self {
+        let shards = estimated_records(&opts.split_opts, estimator).map(Cow::into_owned).collect();
+        Self { shard_size: opts.shard_size, shards }
+    }
+
+    /// The total number of shards.
+    #[must_use]
+    pub fn num_shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// The number of shards in which each chip has rows. Chips without rows are omitted.
+    #[must_use]
+    pub fn shards_per_chip(&self) -> BTreeMap<RiscvAirId, usize> {
+        let mut counts = BTreeMap::new();
+        for shard in &self.shards {
+            for (air, _) in shard.iter().filter(|(_, &rows)| rows > 0) {
+                *counts.entry(air).or_default() += 1;
+            }
+        }
+        counts
+    }
+}
+
 pub fn fit_records_to_shapes<'a, F: PrimeField32>(
     config: &'a CoreShapeConfig<F>,
     records: impl IntoIterator<Item = Cow<'a, EnumMap<RiscvAirId, u64>>> + 'a,
@@ -177,3 +232,29 @@ impl Shapeable for CoreShard<'_> {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shards_per_chip() {
+        let plan = ShardPlan {
+            shard_size: 1 << 21,
+            shards: vec![
+                EnumMap::from_iter([(RiscvAirId::Cpu, 100), (RiscvAirId::AddSub, 40)]),
+                EnumMap::from_iter([(RiscvAirId::Cpu, 10), (RiscvAirId::AddSub, 0)]),
+                EnumMap::from_iter([(RiscvAirId::MemoryGlobalInit, 5)]),
+            ],
+        };
+        assert_eq!(plan.num_shards(), 3);
+        assert_eq!(
+            plan.shards_per_chip(),
+            BTreeMap::from([
+                (RiscvAirId::Cpu, 2),
+                (RiscvAirId::AddSub, 1),
+                (RiscvAirId::MemoryGlobalInit, 1)
+            ])
+        );
+    }
+}
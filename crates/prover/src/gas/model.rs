@@ -1,8 +1,62 @@
+use enum_map::EnumMap;
 use itertools::izip;
+use monerochan_core_executor::RiscvAirId;
 
 pub const INPUT_SIZE: usize = 90;
 
-pub fn predict(input: &[usize; INPUT_SIZE / 2]) -> f64 {
+/// The airs whose log2 heights are the inputs of the model, in the order it was fitted with.
+///
+/// The airs added since then are not priced by the model.
+pub const MODEL_AIRS: [RiscvAirId; INPUT_SIZE / 2] = [
+    RiscvAirId::Cpu,
+    RiscvAirId::Program,
+    RiscvAirId::ShaExtend,
+    RiscvAirId::ShaCompress,
+    RiscvAirId::EdAddAssign,
+    RiscvAirId::EdDecompress,
+    RiscvAirId::Secp256k1Decompress,
+    RiscvAirId::Secp256k1AddAssign,
+    RiscvAirId::Secp256k1DoubleAssign,
+    RiscvAirId::Secp256r1Decompress,
+    RiscvAirId::Secp256r1AddAssign,
+    RiscvAirId::Secp256r1DoubleAssign,
+    RiscvAirId::KeccakPermute,
+    RiscvAirId::Bn254AddAssign,
+    RiscvAirId::Bn254DoubleAssign,
+    RiscvAirId::Bls12381AddAssign,
+    RiscvAirId::Bls12381DoubleAssign,
+    RiscvAirId::Uint256MulMod,
+    RiscvAirId::U256XU2048Mul,
+    RiscvAirId::Bls12381FpOpAssign,
+    RiscvAirId::Bls12381Fp2AddSubAssign,
+    RiscvAirId::Bls12381Fp2MulAssign,
+    RiscvAirId::Bn254FpOpAssign,
+    RiscvAirId::Bn254Fp2AddSubAssign,
+    RiscvAirId::Bn254Fp2MulAssign,
+    RiscvAirId::Bls12381Decompress,
+    RiscvAirId::SyscallCore,
+    RiscvAirId::SyscallPrecompile,
+    RiscvAirId::DivRem,
+    RiscvAirId::AddSub,
+    RiscvAirId::Bitwise,
+    RiscvAirId::Mul,
+    RiscvAirId::ShiftRight,
+    RiscvAirId::ShiftLeft,
+    RiscvAirId::Lt,
+    RiscvAirId::MemoryInstrs,
+    RiscvAirId::Auipc,
+    RiscvAirId::Branch,
+    RiscvAirId::Jump,
+    RiscvAirId::SyscallInstrs,
+    RiscvAirId::MemoryGlobalInit,
+    RiscvAirId::MemoryGlobalFinalize,
+    RiscvAirId::MemoryLocal,
+    RiscvAirId::Global,
+    RiscvAirId::Byte,
+];
+
+pub fn predict(log2_heights: &EnumMap<RiscvAirId, usize>) -> f64 {
+    let input = MODEL_AIRS.map(|air| log2_heights[air]);
     let input = [input.map(|x| x as f64), input.map(|x| 2f64.powi(x.try_into().unwrap()))].concat();
     // Asserts that the zip does not end early.
     assert_eq!(input.len(), INPUT_SIZE);
@@ -13,6 +13,7 @@
 
 pub mod build;
 pub mod components;
+pub mod custom;
 pub mod gas;
 pub mod shapes;
 pub mod types;
@@ -37,6 +38,7 @@ use std::{
 use crate::shapes::MONEROCHANCompressProgramShape;
 use lru::LruCache;
 use p3_baby_bear::BabyBear;
+use p3_bn254_fr::Bn254Fr;
 use p3_field::{AbstractField, PrimeField, PrimeField32};
 use p3_matrix::dense::RowMajorMatrix;
 use shapes::MONEROCHANProofShape;
@@ -68,7 +70,7 @@ use monerochan_recursion_circuit::{
 };
 use monerochan_recursion_compiler::{
     circuit::AsmCompiler,
-    config::InnerConfig,
+    config::{InnerConfig, OuterConfig},
     ir::{Builder, DslIrProgram, Witness},
 };
 use monerochan_recursion_core::{
@@ -79,16 +81,22 @@ use monerochan_recursion_core::{
     stark::BabyBearPoseidon2Outer,
     RecursionProgram, Runtime as RecursionRuntime,
 };
-pub use monerochan_recursion_gnark_ffi::proof::{Groth16Bn254Proof, PlonkBn254Proof};
+pub use monerochan_recursion_gnark_ffi::{
+    proof::{Groth16Bn254Proof, PlonkBn254Proof},
+    WrapSystem, WrapWorker,
+};
 use monerochan_recursion_gnark_ffi::{groth16_bn254::Groth16Bn254Prover, plonk_bn254::PlonkBn254Prover};
 use monerochan_stark::{
     baby_bear_poseidon2::BabyBearPoseidon2,
     shape::{OrderedShape, Shape},
-    Challenge, MachineProver, MachineProvingKey, MONEROCHANProverOpts, ShardProof, SplitOpts,
-    StarkGenericConfig, StarkVerifyingKey, Val, Word, DIGEST_SIZE,
+    Challenge, FriParameters, FriParametersError, MachineProver, MachineProvingKey,
+    MONEROCHANProverOpts, ShardProof, SplitOpts, StarkGenericConfig, StarkVerifyingKey, Val, Word,
+    DIGEST_SIZE,
 };
+use eyre::WrapErr;
 use tracing::instrument;
 
+pub use custom::CustomRecursionProgram;
 pub use types::*;
 use utils::{monerochan_committed_values_digest_bn254, monerochan_vkey_digest_bn254, words_to_bytes};
 
@@ -159,6 +167,8 @@ pub struct MONEROCHANProver<C: MONEROCHANProverComponents = CpuProverComponents>
     pub wrap_vk: OnceLock<StarkVerifyingKey<OuterSC>>,
     /// Whether to verify verification keys.
     pub vk_verification: bool,
+    /// The options of the wrap circuit.
+    pub wrap_opts: WrapOpts,
 }
 
 impl<C: MONEROCHANProverComponents> MONEROCHANProver<C> {
@@ -180,8 +190,8 @@ impl<C: MONEROCHANProverComponents> MONEROCHANProver<C> {
         let shrink_machine = ShrinkAir::shrink_machine(InnerSC::compressed());
         let shrink_prover = C::ShrinkProver::new(shrink_machine);
 
-        let wrap_machine = WrapAir::wrap_machine(OuterSC::default());
-        let wrap_prover = C::WrapProver::new(wrap_machine);
+        let wrap_opts = WrapOpts::default();
+        let wrap_prover = Self::wrap_prover_for(&wrap_opts);
 
         let core_cache_size = NonZeroUsize::new(
             env::var("PROVER_CORE_CACHE_SIZE")
@@ -191,10 +201,25 @@ impl<C: MONEROCHANProverComponents> MONEROCHANProver<C> {
         )
         .expect("PROVER_CORE_CACHE_SIZE must be a non-zero usize");
 
+        let large_programs = env::var("MONEROCHAN_LARGE_PROGRAMS")
+            .map(|v| v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+        let division_heavy_shapes = env::var("MONEROCHAN_DIVISION_HEAVY_SHAPES")
+            .map(|v| v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
         let core_shape_config = env::var("FIX_CORE_SHAPES")
             .map(|v| v.eq_ignore_ascii_case("true"))
             .unwrap_or(true)
-            .then_some(CoreShapeConfig::default());
+            .then(|| {
+                let mut config = CoreShapeConfig::default();
+                if large_programs {
+                    config = config.with_large_programs();
+                }
+                if division_heavy_shapes {
+                    config = config.with_division_heavy_shapes();
+                }
+                config
+            });
 
         let recursion_shape_config = env::var("FIX_RECURSION_SHAPES")
             .map(|v| v.eq_ignore_ascii_case("true"))
@@ -204,6 +229,18 @@ impl<C: MONEROCHANProverComponents> MONEROCHANProver<C> {
         let vk_verification =
             env::var("VERIFY_VK").map(|v| v.eq_ignore_ascii_case("true")).unwrap_or(true);
         tracing::debug!("vk verification: {}", vk_verification);
+        if large_programs && vk_verification {
+            tracing::warn!(
+                "MONEROCHAN_LARGE_PROGRAMS is set: the proofs of programs of more than 2^22 \
+                 instructions can only be compressed with VERIFY_VK=false"
+            );
+        }
+        if division_heavy_shapes && vk_verification {
+            tracing::warn!(
+                "MONEROCHAN_DIVISION_HEAVY_SHAPES is set: the proofs of shards with the \
+                 division-heavy shapes can only be compressed with VERIFY_VK=false"
+            );
+        }
 
         // Read the shapes from the shapes directory and deserialize them into memory.
         let allowed_vk_map: BTreeMap<[BabyBear; DIGEST_SIZE], usize> = if vk_verification {
@@ -260,16 +297,128 @@ impl<C: MONEROCHANProverComponents> MONEROCHANProver<C> {
             vk_verification,
             wrap_program: OnceLock::new(),
             wrap_vk: OnceLock::new(),
+            wrap_opts,
+        }
+    }
+
+    /// Sets the options of the wrap circuit, instead of reading them from the `MONEROCHAN_DEV` and
+    /// `FRI_QUERIES` environment variables.
+    ///
+    /// Only the development circuit can be customized: the production circuit is proven with the
+    /// artifacts of the trusted setup, which are built for [`WrapOpts::production`].
+    ///
+    /// # Panics
+    /// Panics if `opts.dev_circuit` is set and the `forbid-dev-mode` feature is enabled, or if
+    /// `opts.dev_circuit` is not set and `opts` are not [`WrapOpts::production`].
+    #[must_use]
+    pub fn with_wrap_opts(mut self, opts: WrapOpts) -> Self {
+        assert!(
+            !(opts.dev_circuit && cfg!(feature = "forbid-dev-mode")),
+            "dev mode is forbidden in this build"
+        );
+        assert!(
+            opts.dev_circuit || opts == WrapOpts::production(),
+            "the production wrap circuit only supports WrapOpts::production(), got {opts:?}"
+        );
+        if opts != self.wrap_opts {
+            self.wrap_prover = Self::wrap_prover_for(&opts);
+            self.wrap_program = OnceLock::new();
+            self.wrap_vk = OnceLock::new();
+            self.wrap_opts = opts;
+        }
+        self
+    }
+
+    /// Allows programs of up to `2^24` instructions, as `MONEROCHAN_LARGE_PROGRAMS=true` does.
+    ///
+    /// Has no effect if the core shapes are not fixed. See
+    /// [`CoreShapeConfig::with_large_programs`].
+    #[must_use]
+    pub fn with_large_programs(mut self) -> Self {
+        if self.vk_verification {
+            tracing::warn!(
+                "large programs are allowed: the proofs of programs of more than 2^22 \
+                 instructions can only be compressed with VERIFY_VK=false"
+            );
+        }
+        self.core_shape_config = self.core_shape_config.map(CoreShapeConfig::with_large_programs);
+        self
+    }
+
+    /// Whether the Plonk and Groth16 proofs use the small development circuit.
+    pub fn dev_mode(&self) -> bool {
+        self.wrap_opts.dev_circuit
+    }
+
+    fn wrap_prover_for(opts: &WrapOpts) -> C::WrapProver {
+        let config = OuterSC::new_with_fri_params(opts.log_blowup, opts.num_queries);
+        C::WrapProver::new(WrapAir::wrap_machine(config))
+    }
+
+    /// Proves and verifies the core proofs with the given FRI parameters, e.g. with a larger
+    /// blowup and fewer queries than the default ones.
+    ///
+    /// The verifying keys commit to the traces extended with the blowup, so the programs must be
+    /// set up with the returned prover, and their proofs verified with
+    /// [`MONEROCHANProver::verify_with_fri_parameters`] and the same parameters. The recursion
+    /// programs in the allowed verification keys verify core proofs with the default parameters,
+    /// so the core proofs of the returned prover can only be compressed with `VERIFY_VK=false`.
+    pub fn with_core_fri_parameters(
+        mut self,
+        fri_parameters: FriParameters,
+    ) -> Result<Self, FriParametersError> {
+        fri_parameters.validate()?;
+        if fri_parameters != self.core_prover.config().fri_parameters() {
+            let config = CoreSC::new_with_fri_parameters(fri_parameters);
+            self.core_prover = C::CoreProver::new(RiscvAir::machine(config));
         }
+        Ok(self)
+    }
+
+    /// A core prover with `num_queries` FRI queries, or `None` if the core prover already has
+    /// this number of queries.
+    fn core_prover_with_queries(
+        &self,
+        num_queries: Option<usize>,
+    ) -> Result<Option<C::CoreProver>, FriParametersError> {
+        let default = self.core_prover.config().fri_parameters();
+        let Some(num_queries) = num_queries.filter(|&n| n != default.num_queries) else {
+            return Ok(None);
+        };
+        let fri_parameters = FriParameters { num_queries, ..default };
+        fri_parameters.validate()?;
+        let config = CoreSC::new_with_fri_parameters(fri_parameters);
+        Ok(Some(C::CoreProver::new(RiscvAir::machine(config))))
+    }
+
+    /// A compress prover with `num_queries` FRI queries, or `None` if the compress prover already
+    /// has this number of queries.
+    fn compress_prover_with_queries(
+        &self,
+        num_queries: Option<usize>,
+    ) -> Result<Option<C::CompressProver>, FriParametersError> {
+        let default = self.compress_prover.config().fri_parameters();
+        let Some(num_queries) = num_queries.filter(|&n| n != default.num_queries) else {
+            return Ok(None);
+        };
+        let fri_parameters = FriParameters { num_queries, ..default };
+        fri_parameters.validate()?;
+        let config = InnerSC::new_with_fri_parameters(fri_parameters);
+        Ok(Some(C::CompressProver::new(CompressAir::compress_machine(config))))
     }
 
     /// Creates a proving key and a verifying key for a given RISC-V ELF.
+    ///
+    /// Fails if the ELF is invalid or if the program does not fit the prover, e.g. if it is too
+    /// large, before any setup work.
     #[instrument(name = "setup", level = "debug", skip_all)]
+    #[allow(clippy::type_complexity)]
     pub fn setup(
         &self,
         elf: &[u8],
-    ) -> (MONEROCHANProvingKey, DeviceProvingKey<C>, Program, MONEROCHANVerifyingKey) {
-        let program = self.get_program(elf).unwrap();
+    ) -> eyre::Result<(MONEROCHANProvingKey, DeviceProvingKey<C>, Program, MONEROCHANVerifyingKey)>
+    {
+        let program = self.get_program(elf).wrap_err("failed to set up the program")?;
         let (pk, vk) = self.core_prover.setup(&program);
         let vk = MONEROCHANVerifyingKey { vk };
         let pk = MONEROCHANProvingKey {
@@ -278,7 +427,7 @@ impl<C: MONEROCHANProverComponents> MONEROCHANProver<C> {
             vk: vk.clone(),
         };
         let pk_d = self.core_prover.pk_to_device(&pk.pk);
-        (pk, pk_d, program, vk)
+        Ok((pk, pk_d, program, vk))
     }
 
     /// Get a program with an allowed preprocessed shape.
@@ -304,7 +453,7 @@ impl<C: MONEROCHANProverComponents> MONEROCHANProver<C> {
                         let mut shape: Shape<RiscvAirId> = shape.map_err(Box::new)?;
                         shape.extend(preprocessed_shape.iter().map(|(k, v)| (*k, *v)));
                         tracing::debug!("shape for estimated shard {i}: {:?}", &shape.inner);
-                        Ok(gas::predict(enum_map::EnumMap::from_iter(shape).as_array()))
+                        Ok(gas::predict(&enum_map::EnumMap::from_iter(shape)))
                     })
                     .sum::<Result<_, Box<dyn Error>>>()?;
             let gas = gas::final_transform(raw_gas).map_err(Box::new)?;
@@ -330,8 +479,12 @@ impl<C: MONEROCHANProverComponents> MONEROCHANProver<C> {
             (monerochan_stark::MONEROCHANCoreOpts::default(), Program::from(elf).unwrap())
         };
         let preprocessed_shape = program.preprocessed_shape.clone();
+        let trace_path = context.trace_path.take();
 
         let mut runtime = Executor::with_context(program, opts, context);
+        if let Some(path) = trace_path {
+            runtime.save_trace(path).map_err(|e| ExecutionError::Trace(e.to_string()))?;
+        }
 
         if calculate_gas {
             // Needed to figure out where the shard boundaries are.
@@ -347,7 +500,10 @@ impl<C: MONEROCHANProverComponents> MONEROCHANProver<C> {
         for (proof, vkey) in stdin.proofs.iter() {
             runtime.write_proof(proof.clone(), vkey.clone());
         }
-        runtime.run_fast()?;
+        // Finish the trace even if the execution failed, since it is most useful then.
+        let result = runtime.run_fast();
+        runtime.finish_trace().map_err(|e| ExecutionError::Trace(e.to_string()))?;
+        result?;
 
         if calculate_gas {
             let gas = self.get_gas_calculator(preprocessed_shape.unwrap(), opts.split_opts)(
@@ -374,6 +530,34 @@ impl<C: MONEROCHANProverComponents> MONEROCHANProver<C> {
         ))
     }
 
+    /// Execute the program and estimate the shards the core prover will split it into with the
+    /// given options.
+    pub fn shard_plan<'a>(
+        &'a self,
+        elf: &[u8],
+        stdin: &MONEROCHANStdin,
+        opts: monerochan_stark::MONEROCHANCoreOpts,
+        mut context: MONEROCHANContext<'a>,
+    ) -> Result<gas::ShardPlan, ExecutionError> {
+        context.subproof_verifier = Some(self);
+
+        let program = self.get_program(elf).unwrap();
+        let mut runtime = Executor::with_context(program, opts, context);
+        // Needed to figure out where the shard boundaries are.
+        runtime.maximal_shapes = self.core_shape_config.as_ref().map(|config| {
+            config.maximal_core_shapes(opts.shard_size.ilog2() as usize).into_iter().collect()
+        });
+        runtime.record_estimator = Some(Box::default());
+
+        runtime.write_vecs(&stdin.buffer);
+        for (proof, vkey) in stdin.proofs.iter() {
+            runtime.write_proof(proof.clone(), vkey.clone());
+        }
+        runtime.run_fast()?;
+
+        Ok(gas::ShardPlan::new(&opts, runtime.record_estimator.as_ref().unwrap()))
+    }
+
     /// Generate shard proofs which split up and prove the valid execution of a RISC-V program with
     /// the core prover. Uses the provided context.
     #[instrument(name = "prove_core", level = "info", skip_all)]
@@ -390,6 +574,12 @@ impl<C: MONEROCHANProverComponents> MONEROCHANProver<C> {
     ) -> Result<MONEROCHANCoreProof, MONEROCHANCoreProverError> {
         context.subproof_verifier = Some(self);
 
+        // Prove with the number of FRI queries of the options, if they differ from the default.
+        let custom_prover = self
+            .core_prover_with_queries(opts.core_opts.fri_queries)
+            .map_err(MONEROCHANCoreProverError::InvalidFriParameters)?;
+        let core_prover = custom_prover.as_ref().unwrap_or(&self.core_prover);
+
         // Launch two threads to simultaneously prove the core and compile the first few
         // recursion programs in parallel.
         let span = tracing::Span::current().clone();
@@ -436,7 +626,7 @@ impl<C: MONEROCHANProverComponents> MONEROCHANProver<C> {
 
                 // Prove the core and stream the proofs and shapes.
                 monerochan_core_machine::utils::prove_core_stream::<_, C::CoreProver>(
-                    &self.core_prover,
+                    core_prover,
                     pk,
                     program,
                     stdin,
@@ -520,6 +710,14 @@ impl<C: MONEROCHANProverComponents> MONEROCHANProver<C> {
             expected_height += 1;
         }
 
+        // Prove the root of the tree with the number of FRI queries of the options, if they differ
+        // from the default. The other proofs must have the default number, which the recursion
+        // programs verifying them are compiled for.
+        let root_prover = self
+            .compress_prover_with_queries(opts.recursion_opts.fri_queries)
+            .map_err(MONEROCHANRecursionProverError::InvalidFriParameters)?;
+        let root_prover = root_prover.as_ref();
+
         // Generate the proofs.
         let span = tracing::Span::current().clone();
         let (vk, proof) = thread::scope(|s| {
@@ -686,43 +884,52 @@ impl<C: MONEROCHANProverComponents> MONEROCHANProver<C> {
                             received
                         {
                             let (program, record, traces) = *boxed_prt;
+                            let prover = match root_prover {
+                                Some(root_prover) if height == expected_height => root_prover,
+                                _ => &self.compress_prover,
+                            };
                             tracing::debug_span!("batch").in_scope(|| {
                                 // Get the keys.
                                 let (pk, vk) = tracing::debug_span!("Setup compress program")
-                                    .in_scope(|| self.compress_prover.setup(&program));
+                                    .in_scope(|| prover.setup(&program));
 
                                 // Observe the proving key.
-                                let mut challenger = self.compress_prover.config().challenger();
+                                let mut challenger = prover.config().challenger();
                                 tracing::debug_span!("observe proving key").in_scope(|| {
                                     pk.observe_into(&mut challenger);
                                 });
 
                                 #[cfg(feature = "debug")]
-                                self.compress_prover.debug_constraints(
-                                    &self.compress_prover.pk_to_host(&pk),
+                                prover.debug_constraints(
+                                    &prover.pk_to_host(&pk),
                                     vec![record.clone()],
                                     &mut challenger.clone(),
                                 );
 
                                 // Commit to the record and traces.
                                 let data = tracing::debug_span!("commit")
-                                    .in_scope(|| self.compress_prover.commit(&record, traces));
+                                    .in_scope(|| prover.commit(&record, traces));
 
                                 // Generate the proof.
                                 let proof = tracing::debug_span!("open").in_scope(|| {
-                                    self.compress_prover.open(&pk, data, &mut challenger).unwrap()
+                                    if opts.recursion_opts.seed.is_some() {
+                                        prover.open_deterministic(&pk, data, &mut challenger)
+                                    } else {
+                                        prover.open(&pk, data, &mut challenger)
+                                    }
+                                    .unwrap()
                                 });
 
                                 // Verify the proof.
                                 #[cfg(feature = "debug")]
-                                self.compress_prover
+                                prover
                                     .machine()
                                     .verify(
                                         &vk,
                                         &monerochan_stark::MachineProof {
                                             shard_proofs: vec![proof.clone()],
                                         },
-                                        &mut self.compress_prover.config().challenger(),
+                                        &mut prover.config().challenger(),
                                     )
                                     .unwrap();
 
@@ -969,27 +1176,65 @@ impl<C: MONEROCHANProverComponents> MONEROCHANProver<C> {
         Ok(MONEROCHANReduceProof { vk: wrap_vk, proof: wrap_proof.shard_proofs.pop().unwrap() })
     }
 
-    /// Wrap the STARK proven over a SNARK-friendly field into a PLONK proof.
-    #[instrument(name = "wrap_plonk_bn254", level = "info", skip_all)]
-    pub fn wrap_plonk_bn254(
-        &self,
-        proof: MONEROCHANReduceProof<OuterSC>,
-        build_dir: &Path,
-    ) -> PlonkBn254Proof {
+    /// The witness of the gnark circuit for a wrapped proof, with its vkey hash and committed
+    /// values digest.
+    fn wrap_bn254_witness(
+        proof: &MONEROCHANReduceProof<OuterSC>,
+    ) -> (Witness<OuterConfig>, Bn254Fr, Bn254Fr) {
         let input = MONEROCHANCompressWitnessValues {
             vks_and_proofs: vec![(proof.vk.clone(), proof.proof.clone())],
             is_complete: true,
         };
-        let vkey_hash = monerochan_vkey_digest_bn254(&proof);
-        let committed_values_digest = monerochan_committed_values_digest_bn254(&proof);
+        let vkey_hash = monerochan_vkey_digest_bn254(proof);
+        let committed_values_digest = monerochan_committed_values_digest_bn254(proof);
 
         let mut witness = Witness::default();
         input.write(&mut witness);
         witness.write_committed_values_digest(committed_values_digest);
         witness.write_vkey_hash(vkey_hash);
+        (witness, vkey_hash, committed_values_digest)
+    }
+
+    /// Wrap the STARK proven over a SNARK-friendly field into a PLONK proof.
+    #[instrument(name = "wrap_plonk_bn254", level = "info", skip_all)]
+    pub fn wrap_plonk_bn254(
+        &self,
+        proof: MONEROCHANReduceProof<OuterSC>,
+        build_dir: &Path,
+    ) -> PlonkBn254Proof {
+        self.wrap_plonk_bn254_seeded(proof, build_dir, None, None)
+    }
+
+    /// Like [`Self::wrap_plonk_bn254`], but proves with a long-lived [`WrapWorker`] which keeps the
+    /// circuit in memory between proofs.
+    #[instrument(name = "wrap_plonk_bn254", level = "info", skip_all)]
+    pub fn wrap_plonk_bn254_with_worker(
+        &self,
+        proof: MONEROCHANReduceProof<OuterSC>,
+        build_dir: &Path,
+        worker: &WrapWorker,
+    ) -> PlonkBn254Proof {
+        self.wrap_plonk_bn254_seeded(proof, build_dir, Some(worker), None)
+    }
+
+    /// Like [`Self::wrap_plonk_bn254`], but proves with `worker` if set, and derives the
+    /// randomness of the proof from `seed` if set, so that wrapping the same proof with the same
+    /// seed gives the same proof.
+    pub fn wrap_plonk_bn254_seeded(
+        &self,
+        proof: MONEROCHANReduceProof<OuterSC>,
+        build_dir: &Path,
+        worker: Option<&WrapWorker>,
+        seed: Option<u64>,
+    ) -> PlonkBn254Proof {
+        let (witness, vkey_hash, committed_values_digest) = Self::wrap_bn254_witness(&proof);
 
         let prover = PlonkBn254Prover::new();
-        let proof = prover.prove(witness, build_dir.to_path_buf());
+        if let Some(worker) = worker {
+            // The worker verifies the proofs it generates.
+            return prover.prove_with_worker(worker, witness, build_dir.to_path_buf(), seed);
+        }
+        let proof = prover.prove_with_seed(witness, build_dir.to_path_buf(), seed);
 
         // Verify the proof.
         prover
@@ -1011,20 +1256,39 @@ impl<C: MONEROCHANProverComponents> MONEROCHANProver<C> {
         proof: MONEROCHANReduceProof<OuterSC>,
         build_dir: &Path,
     ) -> Groth16Bn254Proof {
-        let input = MONEROCHANCompressWitnessValues {
-            vks_and_proofs: vec![(proof.vk.clone(), proof.proof.clone())],
-            is_complete: true,
-        };
-        let vkey_hash = monerochan_vkey_digest_bn254(&proof);
-        let committed_values_digest = monerochan_committed_values_digest_bn254(&proof);
+        self.wrap_groth16_bn254_seeded(proof, build_dir, None, None)
+    }
 
-        let mut witness = Witness::default();
-        input.write(&mut witness);
-        witness.write_committed_values_digest(committed_values_digest);
-        witness.write_vkey_hash(vkey_hash);
+    /// Like [`Self::wrap_groth16_bn254`], but proves with a long-lived [`WrapWorker`] which keeps
+    /// the circuit in memory between proofs.
+    #[instrument(name = "wrap_groth16_bn254", level = "info", skip_all)]
+    pub fn wrap_groth16_bn254_with_worker(
+        &self,
+        proof: MONEROCHANReduceProof<OuterSC>,
+        build_dir: &Path,
+        worker: &WrapWorker,
+    ) -> Groth16Bn254Proof {
+        self.wrap_groth16_bn254_seeded(proof, build_dir, Some(worker), None)
+    }
+
+    /// Like [`Self::wrap_groth16_bn254`], but proves with `worker` if set, and derives the
+    /// randomness of the proof from `seed` if set, so that wrapping the same proof with the same
+    /// seed gives the same proof.
+    pub fn wrap_groth16_bn254_seeded(
+        &self,
+        proof: MONEROCHANReduceProof<OuterSC>,
+        build_dir: &Path,
+        worker: Option<&WrapWorker>,
+        seed: Option<u64>,
+    ) -> Groth16Bn254Proof {
+        let (witness, vkey_hash, committed_values_digest) = Self::wrap_bn254_witness(&proof);
 
         let prover = Groth16Bn254Prover::new();
-        let proof = prover.prove(witness, build_dir.to_path_buf());
+        if let Some(worker) = worker {
+            // The worker verifies the proofs it generates.
+            return prover.prove_with_worker(worker, witness, build_dir.to_path_buf(), seed);
+        }
+        let proof = prover.prove_with_seed(witness, build_dir.to_path_buf(), seed);
 
         // Verify the proof.
         prover
@@ -1454,7 +1718,7 @@ pub mod tests {
 
     use crate::build::try_build_plonk_bn254_artifacts_dev;
     use anyhow::Result;
-    use build::{build_constraints_and_witness, try_build_groth16_bn254_artifacts_dev};
+    use build::{build_constraints_and_witness_with_opts, try_build_groth16_bn254_artifacts_dev};
     use itertools::Itertools;
     use p3_field::PrimeField32;
 
@@ -1509,7 +1773,7 @@ pub mod tests {
         let context = MONEROCHANContext::default();
 
         tracing::info!("setup elf");
-        let (_, pk_d, program, vk) = prover.setup(elf);
+        let (_, pk_d, program, vk) = prover.setup(elf).unwrap();
 
         tracing::info!("prove core");
         let core_proof = prover.prove_core(&pk_d, program, &stdin, opts, context)?;
@@ -1594,8 +1858,11 @@ pub mod tests {
         assert_eq!(vk_digest_bn254, vk.hash_bn254());
 
         tracing::info!("Test the outer Plonk circuit");
-        let (constraints, witness) =
-            build_constraints_and_witness(&wrapped_bn254_proof.vk, &wrapped_bn254_proof.proof);
+        let (constraints, witness) = build_constraints_and_witness_with_opts(
+            &wrapped_bn254_proof.vk,
+            &wrapped_bn254_proof.proof,
+            &prover.wrap_opts,
+        );
         PlonkBn254Prover::test(constraints, witness);
         tracing::info!("Circuit test succeeded");
 
@@ -1647,10 +1914,10 @@ pub mod tests {
         let prover = MONEROCHANProver::<C>::new();
 
         tracing::info!("setup keccak elf");
-        let (_, keccak_pk_d, keccak_program, keccak_vk) = prover.setup(keccak_elf);
+        let (_, keccak_pk_d, keccak_program, keccak_vk) = prover.setup(keccak_elf).unwrap();
 
         tracing::info!("setup verify elf");
-        let (_, verify_pk_d, verify_program, verify_vk) = prover.setup(verify_elf);
+        let (_, verify_pk_d, verify_program, verify_vk) = prover.setup(verify_elf).unwrap();
 
         tracing::info!("prove subproof 1");
         let mut stdin = MONEROCHANStdin::new();
@@ -1765,6 +2032,48 @@ pub mod tests {
         test_e2e_with_deferred_proofs_prover::<CpuProverComponents>(MONEROCHANProverOpts::auto())
     }
 
+    /// Checks that the wrap options are applied to the wrap circuit: the circuit verifying proofs
+    /// with more FRI queries has more constraints.
+    #[test]
+    #[serial]
+    fn test_wrap_opts_change_circuit() -> Result<()> {
+        setup_logger();
+        let opts = MONEROCHANProverOpts::auto();
+        let num_constraints = |num_queries| -> Result<usize> {
+            let wrap_opts = WrapOpts { num_queries, ..WrapOpts::dev() };
+            let prover = MONEROCHANProver::<CpuProverComponents>::new().with_wrap_opts(wrap_opts);
+            let (_, pk_d, program, vk) = prover.setup(test_artifacts::FIBONACCI_ELF).unwrap();
+            let core_proof = prover.prove_core(
+                &pk_d,
+                program,
+                &MONEROCHANStdin::default(),
+                opts,
+                MONEROCHANContext::default(),
+            )?;
+            let compressed_proof = prover.compress(&vk, core_proof, vec![], opts)?;
+            let shrink_proof = prover.shrink(compressed_proof, opts)?;
+            let wrapped_proof = prover.wrap_bn254(shrink_proof, opts)?;
+            assert_eq!(wrapped_proof.proof.opening_proof.fri_proof.query_proofs.len(), num_queries);
+            let (constraints, _) = build_constraints_and_witness_with_opts(
+                &wrapped_proof.vk,
+                &wrapped_proof.proof,
+                &wrap_opts,
+            );
+            Ok(constraints.len())
+        };
+        assert!(num_constraints(2)? > num_constraints(1)?);
+        Ok(())
+    }
+
+    /// Checks that the FRI parameters of the production circuit cannot be customized, since its
+    /// artifacts come from the trusted setup.
+    #[test]
+    #[should_panic(expected = "only supports WrapOpts::production()")]
+    fn test_wrap_opts_reject_custom_production_circuit() {
+        let wrap_opts = WrapOpts { num_queries: 1, ..WrapOpts::production() };
+        let _ = MONEROCHANProver::<CpuProverComponents>::new().with_wrap_opts(wrap_opts);
+    }
+
     /// Checks that the constants, types, etc. in monerochan-verifier are valid.
     ///
     /// # How to obtain constants
@@ -16,7 +16,10 @@ use monerochan_recursion_circuit::machine::{
 
 use monerochan_recursion_gnark_ffi::proof::{Groth16Bn254Proof, PlonkBn254Proof};
 
-use monerochan_stark::{ShardProof, StarkGenericConfig, StarkProvingKey, StarkVerifyingKey, DIGEST_SIZE};
+use monerochan_stark::{
+    FriParametersError, ShardProof, StarkGenericConfig, StarkProvingKey, StarkVerifyingKey,
+    DIGEST_SIZE,
+};
 use thiserror::Error;
 
 use crate::{
@@ -24,6 +27,55 @@ use crate::{
     CoreSC, InnerSC,
 };
 
+/// Options for the wrap circuit, which also determine the Plonk and Groth16 circuits built from it.
+///
+/// The FRI parameters can only be customized for the development circuit, see
+/// [`crate::MONEROCHANProver::with_wrap_opts`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WrapOpts {
+    /// Whether to use the small development circuit, with Plonk and Groth16 artifacts built
+    /// locally instead of the ones from the trusted setup. Proofs generated this way are not
+    /// secure.
+    pub dev_circuit: bool,
+    /// The log blowup of the wrap FRI config.
+    pub log_blowup: usize,
+    /// The number of queries of the wrap FRI config.
+    pub num_queries: usize,
+}
+
+impl WrapOpts {
+    /// The options of the production circuit, ignoring the `MONEROCHAN_DEV` and `FRI_QUERIES`
+    /// environment variables.
+    #[must_use]
+    pub const fn production() -> Self {
+        Self { dev_circuit: false, log_blowup: 4, num_queries: 25 }
+    }
+
+    /// The options of the small development circuit.
+    ///
+    /// # Panics
+    /// Panics if the `forbid-dev-mode` feature is enabled.
+    #[must_use]
+    pub fn dev() -> Self {
+        assert!(!cfg!(feature = "forbid-dev-mode"), "dev mode is forbidden in this build");
+        Self { dev_circuit: true, log_blowup: 4, num_queries: 1 }
+    }
+}
+
+impl Default for WrapOpts {
+    /// The options from the `MONEROCHAN_DEV` and `FRI_QUERIES` environment variables.
+    fn default() -> Self {
+        if monerochan_recursion_core::stark::monerochan_dev_mode() {
+            return Self::dev();
+        }
+        let num_queries = match std::env::var("FRI_QUERIES") {
+            Ok(value) => value.parse().unwrap(),
+            Err(_) => Self::production().num_queries,
+        };
+        Self { num_queries, ..Self::production() }
+    }
+}
+
 /// The information necessary to generate a proof for a given RISC-V program.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct MONEROCHANProvingKey {
@@ -253,6 +305,8 @@ pub enum MONEROCHANReduceProofWrapper {
 pub enum MONEROCHANRecursionProverError {
     #[error("Runtime error: {0}")]
     RuntimeError(String),
+    #[error("Invalid FRI parameters: {0}")]
+    InvalidFriParameters(FriParametersError),
 }
 
 #[allow(clippy::large_enum_variant)]
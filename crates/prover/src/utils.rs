@@ -8,7 +8,6 @@ use std::{
 use itertools::Itertools;
 use p3_baby_bear::BabyBear;
 use p3_bn254_fr::Bn254Fr;
-use p3_field::{AbstractField, PrimeField32};
 use p3_symmetric::CryptographicHasher;
 use monerochan_core_executor::{Executor, Program};
 use monerochan_core_machine::{io::MONEROCHANStdin, reduce::MONEROCHANReduceProof};
@@ -117,35 +116,7 @@ pub fn words_to_bytes<T: Copy>(words: &[Word<T>]) -> Vec<T> {
     words.iter().flat_map(|word| word.0).collect()
 }
 
-/// Convert 8 BabyBear words into a Bn254Fr field element by shifting by 31 bits each time. The last
-/// word becomes the least significant bits.
-pub fn babybears_to_bn254(digest: &[BabyBear; 8]) -> Bn254Fr {
-    let mut result = Bn254Fr::zero();
-    for word in digest.iter() {
-        // Since BabyBear prime is less than 2^31, we can shift by 31 bits each time and still be
-        // within the Bn254Fr field, so we don't have to truncate the top 3 bits.
-        result *= Bn254Fr::from_canonical_u64(1 << 31);
-        result += Bn254Fr::from_canonical_u32(word.as_canonical_u32());
-    }
-    result
-}
-
-/// Convert 32 BabyBear bytes into a Bn254Fr field element. The first byte's most significant 3 bits
-/// (which would become the 3 most significant bits) are truncated.
-pub fn babybear_bytes_to_bn254(bytes: &[BabyBear; 32]) -> Bn254Fr {
-    let mut result = Bn254Fr::zero();
-    for (i, byte) in bytes.iter().enumerate() {
-        debug_assert!(byte < &BabyBear::from_canonical_u32(256));
-        if i == 0 {
-            // 32 bytes is more than Bn254 prime, so we need to truncate the top 3 bits.
-            result = Bn254Fr::from_canonical_u32(byte.as_canonical_u32() & 0x1f);
-        } else {
-            result *= Bn254Fr::from_canonical_u32(256);
-            result += Bn254Fr::from_canonical_u32(byte.as_canonical_u32());
-        }
-    }
-    result
-}
+pub use monerochan_recursion_gnark_ffi::babybear::{babybear_bytes_to_bn254, babybears_to_bn254};
 
 /// Utility method for converting u32 words to bytes in big endian.
 pub fn words_to_bytes_be(words: &[u32; 8]) -> [u8; 32] {
@@ -5,7 +5,8 @@ use num_bigint::BigUint;
 use p3_baby_bear::BabyBear;
 use p3_field::{AbstractField, PrimeField};
 use monerochan_core_executor::subproof::SubproofVerifier;
-use monerochan_core_machine::cpu::MAX_CPU_LOG_DEGREE;
+use itertools::Itertools;
+use monerochan_core_machine::{cpu::MAX_CPU_LOG_DEGREE, riscv::RiscvAir};
 use monerochan_primitives::{
     consts::WORD_SIZE,
     io::{blake3_hash, MONEROCHANPublicValues},
@@ -19,17 +20,62 @@ use monerochan_recursion_gnark_ffi::{
 use monerochan_stark::{
     air::{PublicValues, POSEIDON_NUM_WORDS, PV_DIGEST_NUM_WORDS},
     baby_bear_poseidon2::BabyBearPoseidon2,
-    MachineProof, MachineProver, MachineVerificationError, MONEROCHANReduceProof, StarkGenericConfig,
-    Word,
+    FriParameters, FriParametersError, MachineProof, MachineProver, MachineVerificationError,
+    MONEROCHANReduceProof, ShardProof, StarkGenericConfig, Word,
 };
 use thiserror::Error;
 
 use crate::{
     components::MONEROCHANProverComponents,
     utils::{is_recursion_public_values_valid, is_root_public_values_valid},
-    CoreSC, HashableKey, OuterSC, MONEROCHANCoreProofData, MONEROCHANProver, MONEROCHANVerifyingKey,
+    CompressAir, CoreSC, HashableKey, InnerSC, OuterSC, MONEROCHANCoreProofData, MONEROCHANProver,
+    MONEROCHANVerifyingKey,
 };
 
+/// The FRI parameters of the shards of a proof, or `None` if they do not all have the same, e.g.
+/// for mock proofs, which have no queries.
+///
+/// The number of queries is the number of query proofs, and the log blowup is the height of the
+/// largest committed matrix, authenticated by the openings of the queries, minus the number of
+/// folding rounds. The proof of work is not part of the parameters found in a proof, so the
+/// parameters have the given number of proof of work bits.
+pub fn proof_fri_parameters(
+    shard_proofs: &[ShardProof<BabyBearPoseidon2>],
+    proof_of_work_bits: usize,
+) -> Option<FriParameters> {
+    let shard_fri_parameters = |shard_proof: &ShardProof<BabyBearPoseidon2>| {
+        let fri_proof = &shard_proof.opening_proof.fri_proof;
+        let log_max_height = shard_proof
+            .opening_proof
+            .query_openings
+            .first()?
+            .iter()
+            .map(|batch_opening| batch_opening.opening_proof.len())
+            .max()?;
+        let log_blowup = log_max_height.checked_sub(fri_proof.commit_phase_commits.len())?;
+        let num_queries = fri_proof.query_proofs.len();
+        Some(FriParameters { log_blowup, num_queries, proof_of_work_bits })
+    };
+    shard_proofs.iter().map(shard_fri_parameters).dedup().exactly_one().ok().flatten()
+}
+
+/// Checks that a proof was generated with the FRI parameters of the verifier, `expected`, which
+/// must be secure enough if they are not the ones of the `default` config of the verifier.
+fn check_fri_parameters(
+    default: &BabyBearPoseidon2,
+    expected: FriParameters,
+    proof: &MachineProof<BabyBearPoseidon2>,
+) -> Result<(), FriParametersError> {
+    if expected != default.fri_parameters() {
+        expected.validate()?;
+    }
+    let actual = proof_fri_parameters(&proof.shard_proofs, expected.proof_of_work_bits);
+    if actual != Some(expected) {
+        return Err(FriParametersError::Mismatch { expected, actual });
+    }
+    Ok(())
+}
+
 #[derive(Error, Debug)]
 pub enum PlonkVerificationError {
     #[error(
@@ -57,10 +103,27 @@ pub enum Groth16VerificationError {
 impl<C: MONEROCHANProverComponents> MONEROCHANProver<C> {
     /// Verify a core proof by verifying the shards, verifying lookup bus, verifying that the
     /// shards are contiguous and complete.
+    ///
+    /// The proof must have the FRI parameters of the core prover.
     pub fn verify(
         &self,
         proof: &MONEROCHANCoreProofData,
         vk: &MONEROCHANVerifyingKey,
+    ) -> Result<(), MachineVerificationError<CoreSC>> {
+        self.verify_with_fri_parameters(proof, vk, self.core_prover.config().fri_parameters())
+    }
+
+    /// Verify a core proof generated with the given FRI parameters, e.g. with a number of queries
+    /// set in [`monerochan_stark::MONEROCHANCoreOpts::fri_queries`].
+    ///
+    /// Proofs with other parameters are rejected, and parameters other than the ones of the core
+    /// prover must give at least [`monerochan_stark::MIN_CONJECTURED_SECURITY_BITS`] bits of
+    /// security.
+    pub fn verify_with_fri_parameters(
+        &self,
+        proof: &MONEROCHANCoreProofData,
+        vk: &MONEROCHANVerifyingKey,
+        fri_parameters: FriParameters,
     ) -> Result<(), MachineVerificationError<CoreSC>> {
         // The proof should not be empty.
         if proof.0.is_empty() {
@@ -292,24 +355,63 @@ impl<C: MONEROCHANProverComponents> MONEROCHANProver<C> {
             return Err(MachineVerificationError::TooManyShards);
         }
 
-        // Verify the shard proof.
-        let mut challenger = self.core_prover.config().challenger();
+        // Verify the shard proof, with the FRI parameters of the verifier.
         let machine_proof = MachineProof { shard_proofs: proof.0.to_vec() };
-        self.core_prover.machine().verify(&vk.vk, &machine_proof, &mut challenger)?;
+        let default = self.core_prover.config();
+        check_fri_parameters(default, fri_parameters, &machine_proof)
+            .map_err(MachineVerificationError::InvalidFriParameters)?;
+        if fri_parameters == default.fri_parameters() {
+            let mut challenger = default.challenger();
+            self.core_prover.machine().verify(&vk.vk, &machine_proof, &mut challenger)?;
+        } else {
+            let config = CoreSC::new_with_fri_parameters(fri_parameters);
+            let mut challenger = config.challenger();
+            RiscvAir::machine(config).verify(&vk.vk, &machine_proof, &mut challenger)?;
+        }
 
         Ok(())
     }
 
     /// Verify a compressed proof.
+    ///
+    /// The proof must have the FRI parameters of the compress prover.
     pub fn verify_compressed(
         &self,
         proof: &MONEROCHANReduceProof<BabyBearPoseidon2>,
         vk: &MONEROCHANVerifyingKey,
+    ) -> Result<(), MachineVerificationError<CoreSC>> {
+        let fri_parameters = self.compress_prover.config().fri_parameters();
+        self.verify_compressed_with_fri_parameters(proof, vk, fri_parameters)
+    }
+
+    /// Verify a compressed proof generated with the given FRI parameters, e.g. with a number of
+    /// queries set in [`monerochan_stark::MONEROCHANCoreOpts::fri_queries`] of the recursion
+    /// options.
+    ///
+    /// See [`MONEROCHANProver::verify_with_fri_parameters`].
+    pub fn verify_compressed_with_fri_parameters(
+        &self,
+        proof: &MONEROCHANReduceProof<BabyBearPoseidon2>,
+        vk: &MONEROCHANVerifyingKey,
+        fri_parameters: FriParameters,
     ) -> Result<(), MachineVerificationError<CoreSC>> {
         let MONEROCHANReduceProof { vk: compress_vk, proof } = proof;
-        let mut challenger = self.compress_prover.config().challenger();
         let machine_proof = MachineProof { shard_proofs: vec![proof.clone()] };
-        self.compress_prover.machine().verify(compress_vk, &machine_proof, &mut challenger)?;
+        let default = self.compress_prover.config();
+        check_fri_parameters(default, fri_parameters, &machine_proof)
+            .map_err(MachineVerificationError::InvalidFriParameters)?;
+        if fri_parameters == default.fri_parameters() {
+            let mut challenger = default.challenger();
+            self.compress_prover.machine().verify(compress_vk, &machine_proof, &mut challenger)?;
+        } else {
+            let config = InnerSC::new_with_fri_parameters(fri_parameters);
+            let mut challenger = config.challenger();
+            CompressAir::compress_machine(config).verify(
+                compress_vk,
+                &machine_proof,
+                &mut challenger,
+            )?;
+        }
 
         // Validate public values
         let public_values: &RecursionPublicValues<_> = proof.public_values.as_slice().borrow();
@@ -339,7 +441,7 @@ impl<C: MONEROCHANProverComponents> MONEROCHANProver<C> {
         // Verify that the proof is for the monerochan vkey we are expecting.
         let vkey_hash = vk.hash_babybear();
         if public_values.monerochan_vk_digest != vkey_hash {
-            return Err(MachineVerificationError::InvalidPublicValues("monerochan vk hash mismatch"));
+            return Err(MachineVerificationError::ProgramVkeyMismatch);
         }
 
         Ok(())
@@ -381,7 +483,7 @@ impl<C: MONEROCHANProverComponents> MONEROCHANProver<C> {
         // Verify that the proof is for the monerochan vkey we are expecting.
         let vkey_hash = vk.hash_babybear();
         if public_values.monerochan_vk_digest != vkey_hash {
-            return Err(MachineVerificationError::InvalidPublicValues("monerochan vk hash mismatch"));
+            return Err(MachineVerificationError::ProgramVkeyMismatch);
         }
 
         Ok(())
@@ -410,7 +512,7 @@ impl<C: MONEROCHANProverComponents> MONEROCHANProver<C> {
         // Verify that the proof is for the monerochan vkey we are expecting.
         let vkey_hash = vk.hash_babybear();
         if *public_values.monerochan_vk_digest() != vkey_hash {
-            return Err(MachineVerificationError::InvalidPublicValues("monerochan vk hash mismatch"));
+            return Err(MachineVerificationError::ProgramVkeyMismatch);
         }
 
         Ok(())
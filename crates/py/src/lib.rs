@@ -0,0 +1,306 @@
+//! Python bindings for the MONEROCHAN SDK.
+//!
+//! Build and install the `monerochan_py` module with `maturin develop --release`, then:
+//!
+//! ```python
+//! from monerochan_py import MONEROCHANStdin, ProverClient
+//!
+//! elf = open("program.elf", "rb").read()
+//! stdin = MONEROCHANStdin()
+//! stdin.write_u32(10)
+//!
+//! client = ProverClient.from_env()
+//! public_values, report = client.execute(elf, stdin)
+//! pk, vk = client.setup(elf)
+//! proof = client.prove(pk, stdin, "compressed")
+//! client.verify(proof, vk)
+//! proof.save("proof.bin")
+//! ```
+//!
+//! Proving and verifying release the GIL, so other Python threads keep running meanwhile.
+
+use std::fmt::Display;
+
+use monerochan::{
+    CpuProver, EnvProver, ExecutionReport, HashableKey, MONEROCHANProofMode,
+    MONEROCHANProofWithPublicValues, MONEROCHANProvingKey, MONEROCHANStdin, MONEROCHANVerifyingKey,
+    Prover, MONEROCHAN_CIRCUIT_VERSION,
+};
+use monerochan_prover::components::CpuProverComponents;
+use pyo3::{
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+    types::PyBytes,
+};
+
+fn runtime_error(err: impl Display) -> PyErr {
+    PyRuntimeError::new_err(format!("{err:#}"))
+}
+
+fn parse_mode(mode: &str) -> PyResult<MONEROCHANProofMode> {
+    match mode {
+        "core" => Ok(MONEROCHANProofMode::Core),
+        "compressed" => Ok(MONEROCHANProofMode::Compressed),
+        "plonk" => Ok(MONEROCHANProofMode::Plonk),
+        "groth16" => Ok(MONEROCHANProofMode::Groth16),
+        _ => Err(PyValueError::new_err(format!(
+            "invalid proof mode {mode:?}, expected one of core, compressed, plonk or groth16"
+        ))),
+    }
+}
+
+/// The input of a program.
+#[pyclass(name = "MONEROCHANStdin")]
+#[derive(Clone)]
+struct PyStdin {
+    inner: MONEROCHANStdin,
+}
+
+#[pymethods]
+impl PyStdin {
+    #[new]
+    fn new() -> Self {
+        Self { inner: MONEROCHANStdin::new() }
+    }
+
+    /// Appends raw bytes, read in the program with `io::read_vec()`.
+    fn write_vec(&mut self, data: &[u8]) {
+        self.inner.write_vec(data.to_vec());
+    }
+
+    /// Appends a u32, read in the program with `io::read::<u32>()`.
+    fn write_u32(&mut self, value: u32) {
+        self.inner.write(&value);
+    }
+
+    /// Appends a u64, read in the program with `io::read::<u64>()`.
+    fn write_u64(&mut self, value: u64) {
+        self.inner.write(&value);
+    }
+
+    /// Appends a string, read in the program with `io::read::<String>()`.
+    fn write_string(&mut self, value: &str) {
+        self.inner.write(&value);
+    }
+
+    /// Serializes the input in the format written by `cargo monerochan stdin`.
+    fn to_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let bytes = bincode::serialize(&self.inner).map_err(runtime_error)?;
+        Ok(PyBytes::new_bound(py, &bytes))
+    }
+
+    /// Deserializes an input serialized by `to_bytes` or `cargo monerochan stdin`.
+    #[staticmethod]
+    fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        let inner = bincode::deserialize(data).map_err(runtime_error)?;
+        Ok(Self { inner })
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.buffer.len()
+    }
+}
+
+/// The proving key of a program.
+#[pyclass(name = "MONEROCHANProvingKey")]
+struct PyProvingKey {
+    inner: MONEROCHANProvingKey,
+}
+
+/// The verifying key of a program.
+#[pyclass(name = "MONEROCHANVerifyingKey")]
+struct PyVerifyingKey {
+    inner: MONEROCHANVerifyingKey,
+}
+
+#[pymethods]
+impl PyVerifyingKey {
+    /// The hash of the verifying key, as a `0x`-prefixed hex string.
+    fn bytes32(&self) -> String {
+        self.inner.bytes32()
+    }
+
+    /// Serializes the verifying key, e.g. to verify proofs in another process.
+    fn to_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let bytes = bincode::serialize(&self.inner).map_err(runtime_error)?;
+        Ok(PyBytes::new_bound(py, &bytes))
+    }
+
+    /// Deserializes a verifying key serialized by `to_bytes`.
+    #[staticmethod]
+    fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        let inner = bincode::deserialize(data).map_err(runtime_error)?;
+        Ok(Self { inner })
+    }
+}
+
+/// A proof bundled with its public values.
+#[pyclass(name = "MONEROCHANProofWithPublicValues")]
+struct PyProof {
+    inner: MONEROCHANProofWithPublicValues,
+}
+
+#[pymethods]
+impl PyProof {
+    /// The public values committed by the program.
+    #[getter]
+    fn public_values<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, self.inner.public_values.as_slice())
+    }
+
+    /// The version of MONEROCHAN the proof was generated with.
+    #[getter]
+    fn monerochan_version(&self) -> String {
+        self.inner.monerochan_version.clone()
+    }
+
+    /// The encoding of a PLONK or Groth16 proof accepted by the onchain verifiers.
+    fn onchain_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, &self.inner.bytes())
+    }
+
+    /// Serializes the proof in the format of `save`.
+    fn to_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let bytes = bincode::serialize(&self.inner).map_err(runtime_error)?;
+        Ok(PyBytes::new_bound(py, &bytes))
+    }
+
+    /// Deserializes a proof serialized by `to_bytes`.
+    #[staticmethod]
+    fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        let inner = bincode::deserialize(data).map_err(runtime_error)?;
+        Ok(Self { inner })
+    }
+
+    /// Saves the proof to a file.
+    fn save(&self, path: &str) -> PyResult<()> {
+        self.inner.save(path).map_err(runtime_error)
+    }
+
+    /// Loads a proof from a file.
+    #[staticmethod]
+    fn load(path: &str) -> PyResult<Self> {
+        let inner = MONEROCHANProofWithPublicValues::load(path).map_err(runtime_error)?;
+        Ok(Self { inner })
+    }
+}
+
+/// The report of an execution.
+#[pyclass(name = "ExecutionReport")]
+struct PyExecutionReport {
+    inner: ExecutionReport,
+}
+
+#[pymethods]
+impl PyExecutionReport {
+    /// The number of instructions executed.
+    #[getter]
+    fn cycles(&self) -> u64 {
+        self.inner.total_instruction_count()
+    }
+
+    /// The number of syscalls made.
+    #[getter]
+    fn syscalls(&self) -> u64 {
+        self.inner.total_syscall_count()
+    }
+
+    /// The gas used, if it was calculated.
+    #[getter]
+    fn gas(&self) -> Option<u64> {
+        self.inner.gas
+    }
+
+    fn __str__(&self) -> String {
+        self.inner.to_string()
+    }
+}
+
+/// A client to execute, prove and verify programs.
+#[pyclass(name = "ProverClient")]
+struct PyProverClient {
+    inner: Box<dyn Prover<CpuProverComponents>>,
+}
+
+#[pymethods]
+impl PyProverClient {
+    /// Creates a client configured by the `MONEROCHAN_PROVER` environment variable and its
+    /// companions.
+    #[staticmethod]
+    fn from_env() -> PyResult<Self> {
+        let prover = EnvProver::try_new().map_err(runtime_error)?;
+        Ok(Self { inner: Box::new(prover) })
+    }
+
+    /// Creates a client that proves on the CPU.
+    #[staticmethod]
+    fn cpu() -> Self {
+        Self { inner: Box::new(CpuProver::new()) }
+    }
+
+    /// Creates a client that generates mock proofs, for testing.
+    #[staticmethod]
+    fn mock() -> Self {
+        Self { inner: Box::new(CpuProver::mock()) }
+    }
+
+    /// Computes the proving and verifying keys of a program.
+    fn setup(&self, py: Python<'_>, elf: &[u8]) -> (PyProvingKey, PyVerifyingKey) {
+        let (pk, vk) = py.allow_threads(|| self.inner.setup(elf));
+        (PyProvingKey { inner: pk }, PyVerifyingKey { inner: vk })
+    }
+
+    /// Executes a program, returning its public values and the execution report.
+    fn execute<'py>(
+        &self,
+        py: Python<'py>,
+        elf: &[u8],
+        stdin: PyRef<'_, PyStdin>,
+    ) -> PyResult<(Bound<'py, PyBytes>, PyExecutionReport)> {
+        let stdin = &stdin.inner;
+        let (public_values, report) =
+            py.allow_threads(|| self.inner.execute(elf, stdin)).map_err(runtime_error)?;
+        Ok((PyBytes::new_bound(py, public_values.as_slice()), PyExecutionReport { inner: report }))
+    }
+
+    /// Proves a program in the given mode: `core`, `compressed`, `plonk` or `groth16`.
+    #[pyo3(signature = (pk, stdin, mode = "core"))]
+    fn prove(
+        &self,
+        py: Python<'_>,
+        pk: PyRef<'_, PyProvingKey>,
+        stdin: PyRef<'_, PyStdin>,
+        mode: &str,
+    ) -> PyResult<PyProof> {
+        let mode = parse_mode(mode)?;
+        let (pk, stdin) = (&pk.inner, &stdin.inner);
+        let proof =
+            py.allow_threads(|| self.inner.prove(pk, stdin, mode)).map_err(runtime_error)?;
+        Ok(PyProof { inner: proof })
+    }
+
+    /// Verifies a proof against the verifying key of a program, raising on invalid proofs.
+    fn verify(
+        &self,
+        py: Python<'_>,
+        proof: PyRef<'_, PyProof>,
+        vk: PyRef<'_, PyVerifyingKey>,
+    ) -> PyResult<()> {
+        let (proof, vk) = (&proof.inner, &vk.inner);
+        // The verification error is not `Send`, so it is formatted before reacquiring the GIL.
+        py.allow_threads(|| self.inner.verify(proof, vk).map_err(|err| err.to_string()))
+            .map_err(runtime_error)
+    }
+}
+
+#[pymodule]
+fn monerochan_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("CIRCUIT_VERSION", MONEROCHAN_CIRCUIT_VERSION)?;
+    m.add_class::<PyStdin>()?;
+    m.add_class::<PyProvingKey>()?;
+    m.add_class::<PyVerifyingKey>()?;
+    m.add_class::<PyProof>()?;
+    m.add_class::<PyExecutionReport>()?;
+    m.add_class::<PyProverClient>()?;
+    Ok(())
+}
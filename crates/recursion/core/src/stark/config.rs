@@ -72,10 +72,6 @@ pub fn outer_perm() -> OuterPerm {
 
 /// The FRI config for outer recursion.
 pub fn outer_fri_config() -> FriConfig<OuterChallengeMmcs> {
-    let perm = outer_perm();
-    let hash = OuterHash::new(perm.clone()).unwrap();
-    let compress = OuterCompress::new(perm.clone());
-    let challenge_mmcs = OuterChallengeMmcs::new(OuterValMmcs::new(hash, compress));
     let num_queries = if monerochan_dev_mode() {
         1
     } else {
@@ -84,7 +80,19 @@ pub fn outer_fri_config() -> FriConfig<OuterChallengeMmcs> {
             Err(_) => 25,
         }
     };
-    FriConfig { log_blowup: 4, num_queries, proof_of_work_bits: 16, mmcs: challenge_mmcs }
+    outer_fri_config_with_params(4, num_queries)
+}
+
+/// The FRI config for outer recursion with the given log blowup and number of queries.
+pub fn outer_fri_config_with_params(
+    log_blowup: usize,
+    num_queries: usize,
+) -> FriConfig<OuterChallengeMmcs> {
+    let perm = outer_perm();
+    let hash = OuterHash::new(perm.clone()).unwrap();
+    let compress = OuterCompress::new(perm.clone());
+    let challenge_mmcs = OuterChallengeMmcs::new(OuterValMmcs::new(hash, compress));
+    FriConfig { log_blowup, num_queries, proof_of_work_bits: 16, mmcs: challenge_mmcs }
 }
 
 /// The FRI config for outer recursion.
@@ -153,6 +161,21 @@ impl BabyBearPoseidon2Outer {
         let pcs = OuterPcs::new(27, dft, val_mmcs, fri_config);
         Self { pcs, perm }
     }
+    /// Creates the outer config with the given FRI log blowup and number of queries, ignoring the
+    /// `MONEROCHAN_DEV` and `FRI_QUERIES` environment variables.
+    ///
+    /// The wrap circuit, and the Plonk and Groth16 circuits built from it, depend on these
+    /// parameters, so proofs are only verified by the artifacts built with the same parameters.
+    pub fn new_with_fri_params(log_blowup: usize, num_queries: usize) -> Self {
+        let perm = outer_perm();
+        let hash = OuterHash::new(perm.clone()).unwrap();
+        let compress = OuterCompress::new(perm.clone());
+        let val_mmcs = OuterValMmcs::new(hash, compress);
+        let dft = OuterDft {};
+        let fri_config = outer_fri_config_with_params(log_blowup, num_queries);
+        let pcs = OuterPcs::new(27, dft, val_mmcs, fri_config);
+        Self { pcs, perm }
+    }
 }
 
 impl Default for BabyBearPoseidon2Outer {
@@ -1,18 +1,19 @@
 //! A simple CLI that wraps the gnark-ffi crate. This is called using Docker in gnark-ffi when the
-//! native feature is disabled.
+//! native feature is disabled, and by the wrap workers of gnark-ffi.
 
 use monerochan_recursion_gnark_ffi::{
     ffi::{
-        build_groth16_bn254, build_plonk_bn254, test_groth16_bn254, test_plonk_bn254,
+        build_groth16_bn254, build_info, build_plonk_bn254, test_groth16_bn254, test_plonk_bn254,
         verify_groth16_bn254, verify_plonk_bn254,
     },
-    ProofBn254,
+    ProofBn254, WrapRequest, WrapResponse, WRAP_RESPONSE_PREFIX,
 };
 
 use clap::{Args, Parser, Subcommand};
 use std::{
     fs::File,
-    io::{read_to_string, Write},
+    io::{read_to_string, stdin, stdout, BufRead, Write},
+    panic::catch_unwind,
 };
 
 #[derive(Debug, Parser)]
@@ -27,6 +28,8 @@ enum Command {
     Prove(ProveArgs),
     Verify(VerifyArgs),
     Test(TestArgs),
+    BuildInfo(BuildInfoArgs),
+    Worker(WorkerArgs),
 }
 
 #[derive(Debug, Args)]
@@ -43,6 +46,9 @@ struct ProveArgs {
     output_path: String,
     #[arg(short, long)]
     system: String,
+    /// The seed to derive the randomness of the proof from, for a reproducible proof.
+    #[arg(long)]
+    seed: Option<u64>,
 }
 
 #[derive(Debug, Args)]
@@ -64,6 +70,18 @@ struct TestArgs {
     system: String,
 }
 
+#[derive(Debug, Args)]
+struct BuildInfoArgs {
+    output_path: String,
+}
+
+#[derive(Debug, Args)]
+struct WorkerArgs {
+    data_dir: String,
+    #[arg(short, long)]
+    system: String,
+}
+
 fn run_build(args: BuildArgs) {
     match args.system.as_str() {
         "plonk" => build_plonk_bn254(&args.data_dir),
@@ -74,20 +92,28 @@ fn run_build(args: BuildArgs) {
 
 fn run_prove(args: ProveArgs) {
     let proof = match args.system.as_str() {
-        "plonk" => prove_plonk_bn254(&args.data_dir, &args.witness_path),
-        "groth16" => prove_groth16_bn254(&args.data_dir, &args.witness_path),
+        "plonk" => prove_plonk_bn254(&args.data_dir, &args.witness_path, args.seed),
+        "groth16" => prove_groth16_bn254(&args.data_dir, &args.witness_path, args.seed),
         _ => panic!("Unsupported system: {}", args.system),
     };
     let mut file = File::create(&args.output_path).unwrap();
     bincode::serialize_into(&mut file, &proof).unwrap();
 }
 
-fn prove_plonk_bn254(data_dir: &str, witness_path: &str) -> ProofBn254 {
-    ProofBn254::Plonk(monerochan_recursion_gnark_ffi::ffi::prove_plonk_bn254(data_dir, witness_path))
+fn prove_plonk_bn254(data_dir: &str, witness_path: &str, seed: Option<u64>) -> ProofBn254 {
+    ProofBn254::Plonk(monerochan_recursion_gnark_ffi::ffi::prove_plonk_bn254(
+        data_dir,
+        witness_path,
+        seed,
+    ))
 }
 
-fn prove_groth16_bn254(data_dir: &str, witness_path: &str) -> ProofBn254 {
-    ProofBn254::Groth16(monerochan_recursion_gnark_ffi::ffi::prove_groth16_bn254(data_dir, witness_path))
+fn prove_groth16_bn254(data_dir: &str, witness_path: &str, seed: Option<u64>) -> ProofBn254 {
+    ProofBn254::Groth16(monerochan_recursion_gnark_ffi::ffi::prove_groth16_bn254(
+        data_dir,
+        witness_path,
+        seed,
+    ))
 }
 
 fn run_verify(args: VerifyArgs) {
@@ -124,6 +150,60 @@ fn run_test(args: TestArgs) {
     }
 }
 
+fn run_build_info(args: BuildInfoArgs) {
+    let info = build_info().unwrap();
+    let file = File::create(&args.output_path).unwrap();
+    serde_json::to_writer(file, &info).unwrap();
+}
+
+/// Proves the requests read from stdin until it is closed, keeping the circuit in memory.
+fn run_worker(args: WorkerArgs) {
+    for line in stdin().lock().lines() {
+        let line = line.unwrap();
+        let response = match serde_json::from_str::<WrapRequest>(&line) {
+            Ok(request) => prove_request(&args, request),
+            Err(e) => WrapResponse::Error(format!("invalid request: {e}")),
+        };
+        let mut stdout = stdout().lock();
+        writeln!(stdout, "{WRAP_RESPONSE_PREFIX}{}", serde_json::to_string(&response).unwrap())
+            .unwrap();
+        stdout.flush().unwrap();
+    }
+}
+
+fn prove_request(args: &WorkerArgs, request: WrapRequest) -> WrapResponse {
+    let witness_file = tempfile::NamedTempFile::new().unwrap();
+    request.witness.save(witness_file.path().to_str().unwrap());
+    let witness_path = witness_file.path().to_str().unwrap();
+    let proof = catch_unwind(|| match args.system.as_str() {
+        "plonk" => prove_plonk_bn254(&args.data_dir, witness_path, request.seed),
+        "groth16" => prove_groth16_bn254(&args.data_dir, witness_path, request.seed),
+        _ => panic!("Unsupported system: {}", args.system),
+    });
+    let Ok(proof) = proof else {
+        return WrapResponse::Error("proving panicked".to_string());
+    };
+
+    // Verify the proof, so that the caller does not need to start another process to do it.
+    let (vkey_hash, committed_values_digest) =
+        (&request.witness.vkey_hash, &request.witness.committed_values_digest);
+    let verified = match &proof {
+        ProofBn254::Plonk(proof) => {
+            verify_plonk_bn254(&args.data_dir, &proof.raw_proof, vkey_hash, committed_values_digest)
+        }
+        ProofBn254::Groth16(proof) => verify_groth16_bn254(
+            &args.data_dir,
+            &proof.raw_proof,
+            vkey_hash,
+            committed_values_digest,
+        ),
+    };
+    match verified {
+        Ok(()) => WrapResponse::Proof(proof),
+        Err(e) => WrapResponse::Error(format!("failed to verify proof: {e}")),
+    }
+}
+
 fn main() {
     let cli = Cli::parse();
 
@@ -132,5 +212,7 @@ fn main() {
         Command::Prove(args) => run_prove(args),
         Command::Verify(args) => run_verify(args),
         Command::Test(args) => run_test(args),
+        Command::BuildInfo(args) => run_build_info(args),
+        Command::Worker(args) => run_worker(args),
     }
 }
@@ -11,6 +11,7 @@ fn main() {
     cfg_if! {
         if #[cfg(feature = "native")] {
             println!("cargo:rerun-if-changed=go");
+            println!("cargo:rerun-if-changed=MONEROCHAN_VERSION");
             // Define the output directory
             let out_dir = env::var("OUT_DIR").unwrap();
             let dest_path = PathBuf::from(&out_dir);
@@ -31,6 +32,11 @@ fn main() {
                 Err(_) => HashMap::new(),
             };
 
+            // Embed the circuit version, so that it can be checked against the crate at runtime.
+            let circuit_version = std::fs::read_to_string("MONEROCHAN_VERSION")
+                .expect("Failed to read MONEROCHAN_VERSION");
+            let ldflags = format!("-ldflags=-X main.circuitVersion={}", circuit_version.trim());
+
             println!("Building Go library at {}", dest.display());
 
             // Run the go build command
@@ -41,6 +47,7 @@ fn main() {
                 .args([
                     "build",
                     "-tags=debug",
+                    &ldflags,
                     "-o",
                     dest.to_str().unwrap(),
                     "-buildmode=c-archive",
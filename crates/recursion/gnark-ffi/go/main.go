@@ -21,6 +21,8 @@ import (
 	"encoding/json"
 	"fmt"
 	"os"
+	"runtime"
+	"runtime/debug"
 	"sync"
 	"unsafe"
 
@@ -38,12 +40,42 @@ import (
 
 func main() {}
 
+// circuitVersion is the MONEROCHAN circuit version the library is built for. It is set by the
+// build script with -ldflags.
+var circuitVersion = "unknown"
+
+//export BuildInfo
+func BuildInfo() *C.char {
+	gnarkVersion := "unknown"
+	if info, ok := debug.ReadBuildInfo(); ok {
+		for _, dep := range info.Deps {
+			if dep.Path != "github.com/consensys/gnark" {
+				continue
+			}
+			gnarkVersion = dep.Version
+			if dep.Replace != nil {
+				gnarkVersion = dep.Replace.Path + "@" + dep.Replace.Version
+			}
+		}
+	}
+
+	data, err := json.Marshal(map[string]string{
+		"circuit_version": circuitVersion,
+		"gnark_version":   gnarkVersion,
+		"go_version":      runtime.Version(),
+	})
+	if err != nil {
+		panic(err)
+	}
+	return C.CString(string(data))
+}
+
 //export ProvePlonkBn254
-func ProvePlonkBn254(dataDir *C.char, witnessPath *C.char) *C.C_PlonkBn254Proof {
+func ProvePlonkBn254(dataDir *C.char, witnessPath *C.char, seed *C.ulonglong) *C.C_PlonkBn254Proof {
 	dataDirString := C.GoString(dataDir)
 	witnessPathString := C.GoString(witnessPath)
 
-	monerochanPlonkBn254Proof := monerochan.ProvePlonk(dataDirString, witnessPathString)
+	monerochanPlonkBn254Proof := monerochan.ProvePlonk(dataDirString, witnessPathString, goSeed(seed))
 
 	ms := C.malloc(C.sizeof_C_PlonkBn254Proof)
 	if ms == nil {
@@ -58,6 +90,16 @@ func ProvePlonkBn254(dataDir *C.char, witnessPath *C.char) *C.C_PlonkBn254Proof
 	return structPtr
 }
 
+// goSeed converts the optional seed of a prove call, which is a null pointer if the proof is not
+// seeded.
+func goSeed(seed *C.ulonglong) *uint64 {
+	if seed == nil {
+		return nil
+	}
+	value := uint64(*seed)
+	return &value
+}
+
 //export FreePlonkBn254Proof
 func FreePlonkBn254Proof(proof *C.C_PlonkBn254Proof) {
 	C.free(unsafe.Pointer(proof.EncodedProof))
@@ -108,11 +150,11 @@ func TestPlonkBn254(witnessPath *C.char, constraintsJson *C.char) *C.char {
 }
 
 //export ProveGroth16Bn254
-func ProveGroth16Bn254(dataDir *C.char, witnessPath *C.char) *C.C_Groth16Bn254Proof {
+func ProveGroth16Bn254(dataDir *C.char, witnessPath *C.char, seed *C.ulonglong) *C.C_Groth16Bn254Proof {
 	dataDirString := C.GoString(dataDir)
 	witnessPathString := C.GoString(witnessPath)
 
-	monerochanGroth16Bn254Proof := monerochan.ProveGroth16(dataDirString, witnessPathString)
+	monerochanGroth16Bn254Proof := monerochan.ProveGroth16(dataDirString, witnessPathString, goSeed(seed))
 
 	ms := C.malloc(C.sizeof_C_Groth16Bn254Proof)
 	if ms == nil {
@@ -16,45 +16,111 @@ import (
 )
 
 var globalMutex sync.RWMutex
-var globalR1cs constraint.ConstraintSystem = groth16.NewCS(ecc.BN254)
-var globalR1csInitialized = false
-var globalPk groth16.ProvingKey = groth16.NewProvingKey(ecc.BN254)
-var globalPkInitialized = false
 
-func ProvePlonk(dataDir string, witnessPath string) Proof {
-	// Sanity check the required arguments have been provided.
-	if dataDir == "" {
-		panic("dataDirStr is required")
+// groth16Circuit is a Groth16 circuit read from a data directory, with its proving key.
+type groth16Circuit struct {
+	r1cs constraint.ConstraintSystem
+	pk   groth16.ProvingKey
+}
+
+// globalGroth16Circuits caches the Groth16 circuits by data directory.
+var globalGroth16Circuits = map[string]*groth16Circuit{}
+
+// readGroth16Circuit reads the Groth16 circuit and the proving key in dataDir.
+func readGroth16Circuit(dataDir string) *groth16Circuit {
+	circuit := &groth16Circuit{
+		r1cs: groth16.NewCS(ecc.BN254),
+		pk:   groth16.NewProvingKey(ecc.BN254),
+	}
+
+	start := time.Now()
+	r1csFile, err := os.Open(dataDir + "/" + groth16CircuitPath)
+	if err != nil {
+		panic(err)
+	}
+	defer r1csFile.Close()
+	r1csReader := bufio.NewReaderSize(r1csFile, 1024*1024)
+	circuit.r1cs.ReadFrom(r1csReader)
+	fmt.Printf("Reading R1CS took %s\n", time.Since(start))
+
+	start = time.Now()
+	pkFile, err := os.Open(dataDir + "/" + groth16PkPath)
+	if err != nil {
+		panic(err)
+	}
+	defer pkFile.Close()
+	pkReader := bufio.NewReaderSize(pkFile, 1024*1024)
+	circuit.pk.ReadDump(pkReader)
+	fmt.Printf("Reading proving key took %s\n", time.Since(start))
+
+	return circuit
+}
+
+// plonkCircuit is a PLONK circuit read from a data directory, with its keys.
+type plonkCircuit struct {
+	scs constraint.ConstraintSystem
+	pk  plonk.ProvingKey
+	vk  plonk.VerifyingKey
+}
+
+// globalPlonkCircuits caches the PLONK circuits by data directory, so that a long-lived process
+// proving with several circuit versions never proves with the keys of another directory.
+var globalPlonkCircuits = map[string]*plonkCircuit{}
+
+// readPlonkCircuit reads the PLONK circuit, the proving key and the verifier key in dataDir.
+func readPlonkCircuit(dataDir string) *plonkCircuit {
+	start := time.Now()
+	circuit := &plonkCircuit{
+		scs: plonk.NewCS(ecc.BN254),
+		pk:  plonk.NewProvingKey(ecc.BN254),
+		vk:  plonk.NewVerifyingKey(ecc.BN254),
 	}
-	os.Setenv("CONSTRAINTS_JSON", dataDir+"/"+constraintsJsonFile)
 
-	// Read the R1CS.
 	scsFile, err := os.Open(dataDir + "/" + plonkCircuitPath)
 	if err != nil {
 		panic(err)
 	}
-	scs := plonk.NewCS(ecc.BN254)
-	scs.ReadFrom(scsFile)
 	defer scsFile.Close()
+	circuit.scs.ReadFrom(scsFile)
 
-	// Read the proving key.
 	pkFile, err := os.Open(dataDir + "/" + plonkPkPath)
 	if err != nil {
 		panic(err)
 	}
-	pk := plonk.NewProvingKey(ecc.BN254)
-	bufReader := bufio.NewReaderSize(pkFile, 1024*1024)
-	pk.UnsafeReadFrom(bufReader)
 	defer pkFile.Close()
+	bufReader := bufio.NewReaderSize(pkFile, 1024*1024)
+	circuit.pk.UnsafeReadFrom(bufReader)
 
-	// Read the verifier key.
 	vkFile, err := os.Open(dataDir + "/" + plonkVkPath)
 	if err != nil {
 		panic(err)
 	}
-	vk := plonk.NewVerifyingKey(ecc.BN254)
-	vk.ReadFrom(vkFile)
 	defer vkFile.Close()
+	circuit.vk.ReadFrom(vkFile)
+
+	fmt.Printf("Reading PLONK circuit and keys took %s\n", time.Since(start))
+	return circuit
+}
+
+// ProvePlonk proves the witness at witnessPath with the PLONK circuit in dataDir. If seed is not
+// nil, the blinding factors of the proof are derived from it, so that the proof is reproducible.
+func ProvePlonk(dataDir string, witnessPath string, seed *uint64) Proof {
+	// Sanity check the required arguments have been provided.
+	if dataDir == "" {
+		panic("dataDirStr is required")
+	}
+	os.Setenv("CONSTRAINTS_JSON", dataDir+"/"+constraintsJsonFile)
+
+	// Read the R1CS, the proving key and the verifier key once per data directory, so that a
+	// long-lived process proves without loading the circuit again.
+	globalMutex.Lock()
+	circuit, ok := globalPlonkCircuits[dataDir]
+	if !ok {
+		circuit = readPlonkCircuit(dataDir)
+		globalPlonkCircuits[dataDir] = circuit
+	}
+	scs, pk, vk := circuit.scs, circuit.pk, circuit.vk
+	globalMutex.Unlock()
 
 	// Read the file.
 	data, err := os.ReadFile(witnessPath)
@@ -81,7 +147,10 @@ func ProvePlonk(dataDir string, witnessPath string) Proof {
 	}
 
 	// Generate the proof.
-	proof, err := plonk.Prove(scs, pk, witness)
+	var proof plonk.Proof
+	withRandomness(seed, func() {
+		proof, err = plonk.Prove(scs, pk, witness)
+	})
 	if err != nil {
 		panic(err)
 	}
@@ -95,7 +164,10 @@ func ProvePlonk(dataDir string, witnessPath string) Proof {
 	return NewMONEROCHANPlonkBn254Proof(&proof, witnessInput)
 }
 
-func ProveGroth16(dataDir string, witnessPath string) Proof {
+// ProveGroth16 proves the witness at witnessPath with the Groth16 circuit in dataDir. If seed is
+// not nil, the blinding factors of the proof are derived from it, so that the proof is
+// reproducible.
+func ProveGroth16(dataDir string, witnessPath string, seed *uint64) Proof {
 	// Sanity check the required arguments have been provided.
 	if dataDir == "" {
 		panic("dataDirStr is required")
@@ -106,35 +178,12 @@ func ProveGroth16(dataDir string, witnessPath string) Proof {
 	os.Setenv("GROTH16", "1")
 	fmt.Printf("Setting environment variables took %s\n", time.Since(start))
 
-	// Read the R1CS.
-	globalMutex.Lock()
-	if !globalR1csInitialized {
-		start = time.Now()
-		r1csFile, err := os.Open(dataDir + "/" + groth16CircuitPath)
-		if err != nil {
-			panic(err)
-		}
-		r1csReader := bufio.NewReaderSize(r1csFile, 1024*1024)
-		globalR1cs.ReadFrom(r1csReader)
-		defer r1csFile.Close()
-		globalR1csInitialized = true
-		fmt.Printf("Reading R1CS took %s\n", time.Since(start))
-	}
-	globalMutex.Unlock()
-
-	// Read the proving key.
+	// Read the R1CS and the proving key once per data directory.
 	globalMutex.Lock()
-	if !globalPkInitialized {
-		start = time.Now()
-		pkFile, err := os.Open(dataDir + "/" + groth16PkPath)
-		if err != nil {
-			panic(err)
-		}
-		pkReader := bufio.NewReaderSize(pkFile, 1024*1024)
-		globalPk.ReadDump(pkReader)
-		defer pkFile.Close()
-		globalPkInitialized = true
-		fmt.Printf("Reading proving key took %s\n", time.Since(start))
+	circuit, ok := globalGroth16Circuits[dataDir]
+	if !ok {
+		circuit = readGroth16Circuit(dataDir)
+		globalGroth16Circuits[dataDir] = circuit
 	}
 	globalMutex.Unlock()
 
@@ -166,7 +215,10 @@ func ProveGroth16(dataDir string, witnessPath string) Proof {
 
 	start = time.Now()
 	// Generate the proof.
-	proof, err := groth16.Prove(globalR1cs, globalPk, witness)
+	var proof groth16.Proof
+	withRandomness(seed, func() {
+		proof, err = groth16.Prove(circuit.r1cs, circuit.pk, witness)
+	})
 	if err != nil {
 		fmt.Printf("Error: %v\n", err)
 		panic(err)
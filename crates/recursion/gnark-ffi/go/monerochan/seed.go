@@ -0,0 +1,61 @@
+package monerochan
+
+import (
+	"crypto/rand"
+	"crypto/sha256"
+	"encoding/binary"
+	"sync"
+)
+
+// The provers draw their blinding factors from crypto/rand.Reader, so a seeded proof replaces it
+// for its whole duration. Unseeded proofs hold the read lock, so that they never read from the
+// seeded reader and a seeded proof is the only reader of its seeded stream.
+var randMutex sync.RWMutex
+
+// withRandomness runs prove with the randomness of crypto/rand derived from seed, or from the
+// operating system if seed is nil.
+func withRandomness(seed *uint64, prove func()) {
+	if seed == nil {
+		randMutex.RLock()
+		defer randMutex.RUnlock()
+		prove()
+		return
+	}
+
+	randMutex.Lock()
+	defer randMutex.Unlock()
+	reader := rand.Reader
+	rand.Reader = newSeededReader(*seed)
+	defer func() { rand.Reader = reader }()
+	prove()
+}
+
+// seededReader is a stream of bytes derived from a seed, made of the SHA-256 hashes of the seed
+// followed by a counter.
+type seededReader struct {
+	seed    uint64
+	counter uint64
+	buf     []byte
+}
+
+func newSeededReader(seed uint64) *seededReader {
+	return &seededReader{seed: seed}
+}
+
+func (r *seededReader) Read(p []byte) (int, error) {
+	n := 0
+	for n < len(p) {
+		if len(r.buf) == 0 {
+			var block [16]byte
+			binary.LittleEndian.PutUint64(block[:8], r.seed)
+			binary.LittleEndian.PutUint64(block[8:], r.counter)
+			r.counter++
+			sum := sha256.Sum256(block[:])
+			r.buf = sum[:]
+		}
+		copied := copy(p[n:], r.buf)
+		r.buf = r.buf[copied:]
+		n += copied
+	}
+	return n, nil
+}
@@ -1,8 +1,62 @@
+//! Conversions between BabyBear and Bn254 field elements.
+//!
+//! The wrap circuit exposes two Bn254 public inputs: the hash of the verifying key, folded from
+//! its BabyBear Poseidon2 digest by [`babybears_to_bn254`], and the digest of the committed values,
+//! packed from their bytes by [`babybear_bytes_to_bn254`]. These helpers reproduce that
+//! derivation, for tooling and verifiers that do not run the prover.
+//!
+//! The gnark circuits also call back into [`babybearinv`] and [`babybearextinv`] to compute
+//! BabyBear inverses.
+
 use p3_baby_bear::BabyBear;
+use p3_bn254_fr::Bn254Fr;
 use p3_field::{
     extension::BinomialExtensionField, AbstractExtensionField, AbstractField, Field, PrimeField32,
 };
+use monerochan_stark::Word;
+
+/// Convert 8 BabyBear words into a Bn254Fr field element by shifting by 31 bits each time. The last
+/// word becomes the least significant bits.
+pub fn babybears_to_bn254(digest: &[BabyBear; 8]) -> Bn254Fr {
+    let mut result = Bn254Fr::zero();
+    for word in digest.iter() {
+        // Since BabyBear prime is less than 2^31, we can shift by 31 bits each time and still be
+        // within the Bn254Fr field, so we don't have to truncate the top 3 bits.
+        result *= Bn254Fr::from_canonical_u64(1 << 31);
+        result += Bn254Fr::from_canonical_u32(word.as_canonical_u32());
+    }
+    result
+}
+
+/// Convert 32 BabyBear bytes into a Bn254Fr field element. The first byte's most significant 3 bits
+/// (which would become the 3 most significant bits) are truncated.
+///
+/// # Panics
+/// Panics if an element is not a byte, as it would overflow into the previous one.
+pub fn babybear_bytes_to_bn254(bytes: &[BabyBear; 32]) -> Bn254Fr {
+    let mut result = Bn254Fr::zero();
+    for (i, byte) in bytes.iter().enumerate() {
+        assert!(byte.as_canonical_u32() < 256, "element {i} of the bytes is not a byte");
+        if i == 0 {
+            // 32 bytes is more than Bn254 prime, so we need to truncate the top 3 bits.
+            result = Bn254Fr::from_canonical_u32(byte.as_canonical_u32() & 0x1f);
+        } else {
+            result *= Bn254Fr::from_canonical_u32(256);
+            result += Bn254Fr::from_canonical_u32(byte.as_canonical_u32());
+        }
+    }
+    result
+}
 
+/// Convert the committed value digest of the recursion public values, 8 words of BabyBear bytes,
+/// into the Bn254Fr public input of the wrap circuit.
+pub fn committed_values_digest_to_bn254(digest: &[Word<BabyBear>; 8]) -> Bn254Fr {
+    let bytes: [BabyBear; 32] = core::array::from_fn(|i| digest[i / 4].0[i % 4]);
+    babybear_bytes_to_bn254(&bytes)
+}
+
+/// Returns the `i`-th coefficient of the inverse of the degree 4 extension element with
+/// coefficients `[a, b, c, d]`, which are reduced modulo the BabyBear prime.
 #[no_mangle]
 pub extern "C" fn babybearextinv(a: u32, b: u32, c: u32, d: u32, i: u32) -> u32 {
     let a = BabyBear::from_wrapped_u32(a);
@@ -14,6 +68,7 @@ pub extern "C" fn babybearextinv(a: u32, b: u32, c: u32, d: u32, i: u32) -> u32
     inv[i as usize].as_canonical_u32()
 }
 
+/// Returns the inverse of `a`, reduced modulo the BabyBear prime.
 #[no_mangle]
 pub extern "C" fn babybearinv(a: u32) -> u32 {
     let a = BabyBear::from_wrapped_u32(a);
@@ -22,10 +77,43 @@ pub extern "C" fn babybearinv(a: u32) -> u32 {
 
 #[cfg(test)]
 pub mod test {
-    use super::babybearextinv;
+    use super::*;
 
     #[test]
     fn test_babybearextinv() {
         babybearextinv(1, 2, 3, 4, 0);
     }
+
+    #[test]
+    fn test_babybears_to_bn254() {
+        let mut digest = [BabyBear::zero(); 8];
+        digest[7] = BabyBear::from_canonical_u32(5);
+        digest[6] = BabyBear::one();
+        assert_eq!(babybears_to_bn254(&digest), Bn254Fr::from_canonical_u64((1 << 31) + 5));
+    }
+
+    #[test]
+    fn test_babybear_bytes_to_bn254() {
+        let mut bytes = [BabyBear::zero(); 32];
+        bytes[0] = BabyBear::from_canonical_u32(0xff);
+        bytes[31] = BabyBear::from_canonical_u32(7);
+        let mut expected = Bn254Fr::from_canonical_u32(0x1f);
+        for _ in 0..31 {
+            expected *= Bn254Fr::from_canonical_u32(256);
+        }
+        expected += Bn254Fr::from_canonical_u32(7);
+        assert_eq!(babybear_bytes_to_bn254(&bytes), expected);
+
+        let words: [Word<BabyBear>; 8] =
+            core::array::from_fn(|i| Word(core::array::from_fn(|j| bytes[i * 4 + j])));
+        assert_eq!(committed_values_digest_to_bn254(&words), expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "not a byte")]
+    fn test_babybear_bytes_to_bn254_not_bytes() {
+        let mut bytes = [BabyBear::zero(); 32];
+        bytes[5] = BabyBear::from_canonical_u32(256);
+        babybear_bytes_to_bn254(&bytes);
+    }
 }
@@ -1,5 +1,6 @@
-use crate::{Groth16Bn254Proof, PlonkBn254Proof, ProofBn254, MONEROCHAN_CIRCUIT_VERSION};
-use anyhow::{anyhow, Result};
+use super::{get_docker_image, GnarkBuildInfo};
+use crate::{Groth16Bn254Proof, PlonkBn254Proof, ProofBn254};
+use anyhow::{anyhow, Context, Result};
 use std::{io::Write, process::Command};
 
 /// Represents the proof system being used
@@ -30,12 +31,6 @@ fn assert_docker() {
     }
 }
 
-fn get_docker_image() -> String {
-    std::env::var("MONEROCHAN_GNARK_IMAGE").unwrap_or_else(|_| {
-        format!("ghcr.io/monero-chan-foundation/monerochan-rs-gnark:{MONEROCHAN_CIRCUIT_VERSION}")
-    })
-}
-
 /// Calls `docker run` with the given arguments and bind mounts.
 ///
 /// Note: files created here by `call_docker` are read-only for after the process exits.
@@ -68,7 +63,12 @@ fn call_docker(args: &[&str], mounts: &[(&str, &str)]) -> Result<()> {
     Ok(())
 }
 
-fn prove(system: ProofSystem, data_dir: &str, witness_path: &str) -> Result<Vec<u8>> {
+fn prove(
+    system: ProofSystem,
+    data_dir: &str,
+    witness_path: &str,
+    seed: Option<u64>,
+) -> Result<Vec<u8>> {
     let output_file = tempfile::NamedTempFile::new()?;
     let mounts = [
         (data_dir, "/circuit"),
@@ -76,16 +76,23 @@ fn prove(system: ProofSystem, data_dir: &str, witness_path: &str) -> Result<Vec<
         (output_file.path().to_str().unwrap(), "/output"),
     ];
     assert_docker();
-    call_docker(
-        &["prove", "--system", system.as_str(), "/circuit", "/witness", "/output"],
-        &mounts,
-    )?;
+    let seed = seed.map(|seed| seed.to_string());
+    let mut args = vec!["prove", "--system", system.as_str()];
+    if let Some(seed) = &seed {
+        args.extend(["--seed", seed]);
+    }
+    args.extend(["/circuit", "/witness", "/output"]);
+    call_docker(&args, &mounts)?;
     Ok(std::fs::read(output_file.path())?)
 }
 
-pub fn prove_plonk_bn254(data_dir: &str, witness_path: &str) -> PlonkBn254Proof {
-    let result =
-        prove(ProofSystem::Plonk, data_dir, witness_path).expect("failed to prove with docker");
+pub fn prove_plonk_bn254(
+    data_dir: &str,
+    witness_path: &str,
+    seed: Option<u64>,
+) -> PlonkBn254Proof {
+    let result = prove(ProofSystem::Plonk, data_dir, witness_path, seed)
+        .expect("failed to prove with docker");
     let deserialized: ProofBn254 =
         bincode::deserialize(&result).expect("failed to deserialize result");
     match deserialized {
@@ -94,9 +101,13 @@ pub fn prove_plonk_bn254(data_dir: &str, witness_path: &str) -> PlonkBn254Proof
     }
 }
 
-pub fn prove_groth16_bn254(data_dir: &str, witness_path: &str) -> Groth16Bn254Proof {
-    let result =
-        prove(ProofSystem::Groth16, data_dir, witness_path).expect("failed to prove with docker");
+pub fn prove_groth16_bn254(
+    data_dir: &str,
+    witness_path: &str,
+    seed: Option<u64>,
+) -> Groth16Bn254Proof {
+    let result = prove(ProofSystem::Groth16, data_dir, witness_path, seed)
+        .expect("failed to prove with docker");
     let deserialized: ProofBn254 =
         bincode::deserialize(&result).expect("failed to deserialize result");
     match deserialized {
@@ -175,6 +186,22 @@ pub fn verify_groth16_bn254(
     verify(ProofSystem::Groth16, data_dir, proof, vkey_hash, committed_values_digest)
 }
 
+/// Returns the build information of the gnark library in the docker image.
+pub fn build_info() -> Result<GnarkBuildInfo> {
+    if !check_docker() {
+        return Err(anyhow!(
+            "Failed to run `docker info`. Please ensure that docker is installed and running."
+        ));
+    }
+    let output_file = tempfile::NamedTempFile::new()?;
+    let mounts = [(output_file.path().to_str().unwrap(), "/output")];
+    call_docker(&["build-info", "/output"], &mounts).with_context(|| {
+        format!("failed to get the build info of the gnark docker image {}", get_docker_image())
+    })?;
+    let info = std::fs::read_to_string(output_file.path())?;
+    Ok(serde_json::from_str(&info)?)
+}
+
 fn test(system: ProofSystem, witness_json: &str, constraints_json: &str) -> Result<()> {
     let mounts = [(witness_json, "/witness"), (constraints_json, "/constraints")];
     assert_docker();
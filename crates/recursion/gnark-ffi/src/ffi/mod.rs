@@ -7,3 +7,42 @@ cfg_if::cfg_if! {
         pub use docker::*;
     }
 }
+
+use anyhow::{ensure, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::MONEROCHAN_CIRCUIT_VERSION;
+
+/// Build information reported by the gnark backend.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GnarkBuildInfo {
+    /// The MONEROCHAN circuit version the backend was built for.
+    pub circuit_version: String,
+    /// The version of gnark the backend was built with.
+    pub gnark_version: String,
+    /// The version of Go the backend was built with.
+    pub go_version: String,
+}
+
+/// The docker image of the gnark CLI, used by the docker backend and by the wrap workers.
+pub(crate) fn get_docker_image() -> String {
+    std::env::var("MONEROCHAN_GNARK_IMAGE").unwrap_or_else(|_| {
+        format!("ghcr.io/monero-chan-foundation/monerochan-rs-gnark:{MONEROCHAN_CIRCUIT_VERSION}")
+    })
+}
+
+/// Checks that the gnark backend, i.e. the linked library with the `native` feature or the docker
+/// image otherwise, is available and was built for [`MONEROCHAN_CIRCUIT_VERSION`].
+///
+/// This is meant to be called at startup, so that a mismatched backend fails fast instead of
+/// producing proofs that do not verify.
+pub fn probe() -> Result<GnarkBuildInfo> {
+    let info = build_info()?;
+    ensure!(
+        info.circuit_version == MONEROCHAN_CIRCUIT_VERSION,
+        "the gnark backend was built for circuit version {}, but MONEROCHAN expects {}",
+        info.circuit_version,
+        MONEROCHAN_CIRCUIT_VERSION
+    );
+    Ok(info)
+}
@@ -5,6 +5,7 @@
 //! Although we cast to *mut c_char because the Go signatures can't be immutable, the Go functions
 //! should not modify the strings.
 
+use super::GnarkBuildInfo;
 use crate::{Groth16Bn254Proof, PlonkBn254Proof, MONEROCHAN_CIRCUIT_VERSION};
 use cfg_if::cfg_if;
 use std::{
@@ -62,8 +63,8 @@ impl ProofSystem {
 }
 
 enum ProveFunction {
-    Plonk(unsafe extern "C" fn(*mut c_char, *mut c_char) -> *mut C_PlonkBn254Proof),
-    Groth16(unsafe extern "C" fn(*mut c_char, *mut c_char) -> *mut C_Groth16Bn254Proof),
+    Plonk(unsafe extern "C" fn(*mut c_char, *mut c_char, *mut u64) -> *mut C_PlonkBn254Proof),
+    Groth16(unsafe extern "C" fn(*mut c_char, *mut c_char, *mut u64) -> *mut C_Groth16Bn254Proof),
 }
 
 fn build(system: ProofSystem, data_dir: &str) {
@@ -73,20 +74,34 @@ fn build(system: ProofSystem, data_dir: &str) {
     }
 }
 
-fn prove(system: ProofSystem, data_dir: &str, witness_path: &str) -> ProofResult {
+fn prove(
+    system: ProofSystem,
+    data_dir: &str,
+    witness_path: &str,
+    seed: Option<u64>,
+) -> ProofResult {
     let data_dir = CString::new(data_dir).expect("CString::new failed");
     let witness_path = CString::new(witness_path).expect("CString::new failed");
+    // The seed is passed as a pointer, which is null if the proof is not seeded.
+    let mut seed = seed;
+    let seed_ptr = seed.as_mut().map_or(std::ptr::null_mut(), |seed| seed as *mut u64);
 
     unsafe {
         match system.prove_fn() {
             ProveFunction::Plonk(func) => {
-                let proof =
-                    func(data_dir.as_ptr() as *mut c_char, witness_path.as_ptr() as *mut c_char);
+                let proof = func(
+                    data_dir.as_ptr() as *mut c_char,
+                    witness_path.as_ptr() as *mut c_char,
+                    seed_ptr,
+                );
                 ProofResult::Plonk(proof)
             }
             ProveFunction::Groth16(func) => {
-                let proof =
-                    func(data_dir.as_ptr() as *mut c_char, witness_path.as_ptr() as *mut c_char);
+                let proof = func(
+                    data_dir.as_ptr() as *mut c_char,
+                    witness_path.as_ptr() as *mut c_char,
+                    seed_ptr,
+                );
                 ProofResult::Groth16(proof)
             }
         }
@@ -145,8 +160,8 @@ pub fn build_plonk_bn254(data_dir: &str) {
     build(ProofSystem::Plonk, data_dir)
 }
 
-pub fn prove_plonk_bn254(data_dir: &str, witness_path: &str) -> PlonkBn254Proof {
-    match prove(ProofSystem::Plonk, data_dir, witness_path) {
+pub fn prove_plonk_bn254(data_dir: &str, witness_path: &str, seed: Option<u64>) -> PlonkBn254Proof {
+    match prove(ProofSystem::Plonk, data_dir, witness_path, seed) {
         ProofResult::Plonk(proof) => unsafe { PlonkBn254Proof::from_raw(proof) },
         _ => unreachable!(),
     }
@@ -169,8 +184,12 @@ pub fn build_groth16_bn254(data_dir: &str) {
     build(ProofSystem::Groth16, data_dir)
 }
 
-pub fn prove_groth16_bn254(data_dir: &str, witness_path: &str) -> Groth16Bn254Proof {
-    match prove(ProofSystem::Groth16, data_dir, witness_path) {
+pub fn prove_groth16_bn254(
+    data_dir: &str,
+    witness_path: &str,
+    seed: Option<u64>,
+) -> Groth16Bn254Proof {
+    match prove(ProofSystem::Groth16, data_dir, witness_path, seed) {
         ProofResult::Groth16(proof) => unsafe { Groth16Bn254Proof::from_raw(proof) },
         _ => unreachable!(),
     }
@@ -199,6 +218,13 @@ pub fn test_babybear_poseidon2() {
     }
 }
 
+/// Returns the build information of the linked gnark library.
+pub fn build_info() -> anyhow::Result<GnarkBuildInfo> {
+    // Safety: The build info is returned from the go code and is guaranteed to be valid.
+    let info = unsafe { ptr_to_string_freed(bind::BuildInfo()) };
+    Ok(serde_json::from_str(&info)?)
+}
+
 /// Converts a C string into a Rust String.
 ///
 /// # Safety
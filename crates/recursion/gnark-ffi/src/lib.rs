@@ -1,15 +1,16 @@
-mod babybear;
-
+pub mod babybear;
 pub mod ffi;
 pub mod groth16_bn254;
 pub mod plonk_bn254;
 pub mod proof;
 pub mod witness;
+pub mod worker;
 
 pub use groth16_bn254::*;
 pub use plonk_bn254::*;
 pub use proof::*;
 pub use witness::*;
+pub use worker::*;
 
 /// The global version for all components of MONEROCHAN.
 ///
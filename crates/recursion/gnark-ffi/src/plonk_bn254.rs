@@ -7,7 +7,7 @@ use std::{
 use crate::{
     ffi::{build_plonk_bn254, prove_plonk_bn254, test_plonk_bn254, verify_plonk_bn254},
     witness::GnarkWitness,
-    PlonkBn254Proof, MONEROCHAN_CIRCUIT_VERSION,
+    PlonkBn254Proof, ProofBn254, WrapSystem, WrapWorker, MONEROCHAN_CIRCUIT_VERSION,
 };
 use anyhow::Result;
 
@@ -84,14 +84,48 @@ impl PlonkBn254Prover {
 
     /// Generates a PLONK proof given a witness.
     pub fn prove<C: Config>(&self, witness: Witness<C>, build_dir: PathBuf) -> PlonkBn254Proof {
+        self.prove_with_seed(witness, build_dir, None)
+    }
+
+    /// Generates a PLONK proof given a witness, with the randomness of the proof derived from
+    /// `seed` if set, so that proving the same witness with the same seed gives the same proof.
+    pub fn prove_with_seed<C: Config>(
+        &self,
+        witness: Witness<C>,
+        build_dir: PathBuf,
+        seed: Option<u64>,
+    ) -> PlonkBn254Proof {
         // Write witness.
         let mut witness_file = tempfile::NamedTempFile::new().unwrap();
         let gnark_witness = GnarkWitness::new(witness);
         let serialized = serde_json::to_string(&gnark_witness).unwrap();
         witness_file.write_all(serialized.as_bytes()).unwrap();
 
-        let mut proof =
-            prove_plonk_bn254(build_dir.to_str().unwrap(), witness_file.path().to_str().unwrap());
+        let mut proof = prove_plonk_bn254(
+            build_dir.to_str().unwrap(),
+            witness_file.path().to_str().unwrap(),
+            seed,
+        );
+        proof.plonk_vkey_hash = Self::get_vkey_hash(&build_dir);
+        proof
+    }
+
+    /// Generates a PLONK proof given a witness, with a long-lived [WrapWorker] and the randomness
+    /// of the proof derived from `seed` if set.
+    pub fn prove_with_worker<C: Config>(
+        &self,
+        worker: &WrapWorker,
+        witness: Witness<C>,
+        build_dir: PathBuf,
+        seed: Option<u64>,
+    ) -> PlonkBn254Proof {
+        assert_eq!(worker.system(), WrapSystem::Plonk, "expected a PLONK wrap worker");
+        let proof = worker
+            .prove(GnarkWitness::new(witness), &build_dir, seed)
+            .expect("failed to prove with the wrap worker");
+        let ProofBn254::Plonk(mut proof) = proof else {
+            panic!("unexpected proof type");
+        };
         proof.plonk_vkey_hash = Self::get_vkey_hash(&build_dir);
         proof
     }
@@ -1,7 +1,9 @@
 use std::{fs::File, io::Write};
 
+use anyhow::{anyhow, ensure, Result};
+use num_bigint::BigUint;
 use p3_field::{AbstractExtensionField, AbstractField, PrimeField};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use monerochan_recursion_compiler::ir::{Config, Witness};
 
 /// A witness that can be used to initialize values for witness generation inside Gnark.
@@ -48,4 +50,232 @@ impl GnarkWitness {
         let mut file = File::create(path).unwrap();
         file.write_all(serialized.as_bytes()).unwrap();
     }
+
+    /// Loads a witness saved with [`GnarkWitness::save`].
+    pub fn load(path: &str) -> Result<Self> {
+        let file = File::open(path)?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    /// The number of variables, felts and extension elements of the witness.
+    pub fn shape(&self) -> GnarkWitnessShape {
+        GnarkWitnessShape {
+            num_vars: self.vars.len(),
+            num_felts: self.felts.len(),
+            num_exts: self.exts.len(),
+        }
+    }
+
+    /// Serializes the witness to the JSON format of gnark's `witness.ToJSON`, using the schema of
+    /// the MONEROCHAN verifier circuit.
+    pub fn to_gnark_json(&self) -> Result<String> {
+        let witness = GnarkJsonWitness {
+            vkey_hash: self.vkey_hash.clone(),
+            committed_values_digest: self.committed_values_digest.clone(),
+            vars: self.vars.clone(),
+            felts: self.felts.iter().map(|felt| GnarkJsonFelt { value: felt.clone() }).collect(),
+            exts: self
+                .exts
+                .iter()
+                .map(|ext| {
+                    let value = ext
+                        .iter()
+                        .map(|felt| GnarkJsonFelt { value: felt.clone() })
+                        .collect::<Vec<_>>()
+                        .try_into()
+                        .map_err(|_| anyhow!("extension element must have 4 coefficients"))?;
+                    Ok(GnarkJsonExt { value })
+                })
+                .collect::<Result<_>>()?,
+        };
+        Ok(serde_json::to_string(&witness)?)
+    }
+
+    /// Deserializes a witness from the JSON format of gnark's `witness.ToJSON`.
+    pub fn from_gnark_json(json: &str) -> Result<Self> {
+        let witness: GnarkJsonWitness = serde_json::from_str(json)?;
+        Ok(GnarkWitness {
+            vars: witness.vars,
+            felts: witness.felts.into_iter().map(|felt| felt.value).collect(),
+            exts: witness
+                .exts
+                .into_iter()
+                .map(|ext| ext.value.into_iter().map(|felt| felt.value).collect())
+                .collect(),
+            vkey_hash: witness.vkey_hash,
+            committed_values_digest: witness.committed_values_digest,
+        })
+    }
+
+    /// Serializes the witness to the binary format of gnark's `witness.MarshalBinary` over BN254.
+    ///
+    /// The format is the number of public and secret elements as big-endian `u32`s, followed by
+    /// the length of the vector as a big-endian `u32` and the elements as 32-byte big-endian
+    /// integers. The public elements come first.
+    pub fn to_gnark_binary(&self) -> Result<Vec<u8>> {
+        let public = [&self.vkey_hash, &self.committed_values_digest];
+        let secret = self
+            .vars
+            .iter()
+            .chain(self.felts.iter())
+            .chain(self.exts.iter().flat_map(|ext| ext.iter()))
+            .collect::<Vec<_>>();
+        let num_elements = u32::try_from(public.len() + secret.len())?;
+
+        let mut bytes = Vec::with_capacity(12 + 32 * num_elements as usize);
+        bytes.extend_from_slice(&u32::try_from(public.len())?.to_be_bytes());
+        bytes.extend_from_slice(&u32::try_from(secret.len())?.to_be_bytes());
+        bytes.extend_from_slice(&num_elements.to_be_bytes());
+        for element in public.into_iter().chain(secret) {
+            bytes.extend_from_slice(&element_to_bytes(element)?);
+        }
+        Ok(bytes)
+    }
+
+    /// Deserializes a witness from the binary format of gnark's `witness.MarshalBinary`.
+    ///
+    /// The binary format does not record how the secret elements are split between variables,
+    /// felts and extension elements, so the shape of the witness must be provided.
+    pub fn from_gnark_binary(bytes: &[u8], shape: GnarkWitnessShape) -> Result<Self> {
+        ensure!(bytes.len() >= 12, "gnark witness is too short");
+        let read_u32 = |i: usize| u32::from_be_bytes(bytes[i..i + 4].try_into().unwrap()) as usize;
+        let (num_public, num_secret, num_elements) = (read_u32(0), read_u32(4), read_u32(8));
+        ensure!(num_public == 2, "expected 2 public elements, got {num_public}");
+        ensure!(
+            num_secret == shape.num_vars + shape.num_felts + 4 * shape.num_exts,
+            "the number of secret elements {num_secret} does not match the witness shape {shape:?}"
+        );
+        ensure!(num_elements == num_public + num_secret, "invalid gnark witness vector length");
+        ensure!(bytes.len() == 12 + 32 * num_elements, "invalid gnark witness length");
+
+        let mut elements =
+            bytes[12..].chunks_exact(32).map(|chunk| BigUint::from_bytes_be(chunk).to_string());
+        let mut take = |n: usize| elements.by_ref().take(n).collect::<Vec<_>>();
+        let public = take(2);
+        let vars = take(shape.num_vars);
+        let felts = take(shape.num_felts);
+        let exts = (0..shape.num_exts).map(|_| take(4)).collect();
+        Ok(GnarkWitness {
+            vars,
+            felts,
+            exts,
+            vkey_hash: public[0].clone(),
+            committed_values_digest: public[1].clone(),
+        })
+    }
+}
+
+/// The number of variables, felts and extension elements of a [`GnarkWitness`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GnarkWitnessShape {
+    pub num_vars: usize,
+    pub num_felts: usize,
+    pub num_exts: usize,
+}
+
+/// The JSON layout of the MONEROCHAN verifier circuit witness in gnark.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GnarkJsonWitness {
+    #[serde(deserialize_with = "deserialize_element")]
+    vkey_hash: String,
+    #[serde(deserialize_with = "deserialize_element")]
+    committed_values_digest: String,
+    #[serde(deserialize_with = "deserialize_elements")]
+    vars: Vec<String>,
+    felts: Vec<GnarkJsonFelt>,
+    exts: Vec<GnarkJsonExt>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GnarkJsonFelt {
+    #[serde(deserialize_with = "deserialize_element")]
+    value: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GnarkJsonExt {
+    value: [GnarkJsonFelt; 4],
+}
+
+/// gnark writes small field elements as JSON numbers and large ones as strings.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonElement {
+    Number(u64),
+    String(String),
+}
+
+impl From<JsonElement> for String {
+    fn from(element: JsonElement) -> Self {
+        match element {
+            JsonElement::Number(n) => n.to_string(),
+            JsonElement::String(s) => s,
+        }
+    }
+}
+
+fn deserialize_element<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    JsonElement::deserialize(deserializer).map(String::from)
+}
+
+fn deserialize_elements<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<String>, D::Error> {
+    Vec::<JsonElement>::deserialize(deserializer)
+        .map(|elements| elements.into_iter().map(String::from).collect())
+}
+
+/// Converts a decimal field element to 32 big-endian bytes.
+fn element_to_bytes(element: &str) -> Result<[u8; 32]> {
+    let value: BigUint = element.parse()?;
+    let digits = value.to_bytes_be();
+    ensure!(digits.len() <= 32, "field element {element} does not fit in 32 bytes");
+    let mut bytes = [0u8; 32];
+    bytes[32 - digits.len()..].copy_from_slice(&digits);
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn witness() -> GnarkWitness {
+        GnarkWitness {
+            vars: vec!["1".into(), "999".into()],
+            felts: vec!["2".into(), "999".into()],
+            exts: vec![vec!["3".into(), "4".into(), "5".into(), "6".into()]],
+            vkey_hash: "123456789012345678901234567890".into(),
+            committed_values_digest: "42".into(),
+        }
+    }
+
+    #[test]
+    fn test_gnark_json_roundtrip() {
+        let witness = witness();
+        let json = witness.to_gnark_json().unwrap();
+        assert!(json.contains("\"Exts\":[{\"Value\":[{\"Value\":\"3\"}"));
+        let loaded = GnarkWitness::from_gnark_json(&json).unwrap();
+        assert_eq!(serde_json::to_value(loaded).unwrap(), serde_json::to_value(witness).unwrap());
+
+        // Small elements may be written as numbers.
+        let json = json.replace("\"42\"", "42");
+        assert_eq!(GnarkWitness::from_gnark_json(&json).unwrap().committed_values_digest, "42");
+    }
+
+    #[test]
+    fn test_gnark_binary_roundtrip() {
+        let witness = witness();
+        let bytes = witness.to_gnark_binary().unwrap();
+        assert_eq!(&bytes[..12], &[0, 0, 0, 2, 0, 0, 0, 8, 0, 0, 0, 10]);
+        assert_eq!(bytes.len(), 12 + 32 * 10);
+
+        let loaded = GnarkWitness::from_gnark_binary(&bytes, witness.shape()).unwrap();
+        assert_eq!(serde_json::to_value(loaded).unwrap(), serde_json::to_value(witness).unwrap());
+
+        let shape = GnarkWitnessShape { num_vars: 1, ..witness.shape() };
+        assert!(GnarkWitness::from_gnark_binary(&bytes, shape).is_err());
+    }
 }
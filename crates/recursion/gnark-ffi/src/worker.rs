@@ -0,0 +1,164 @@
+//! Long-lived gnark processes for wrapping proofs.
+//!
+//! Every call to [`crate::ffi::prove_plonk_bn254`] or [`crate::ffi::prove_groth16_bn254`] with the
+//! docker backend starts a new container, which loads the circuit and the proving key before
+//! proving. A [`WrapWorker`] instead starts the gnark CLI once with its `worker` command, which
+//! keeps the circuit and the proving key in memory and proves the witnesses sent to it.
+//!
+//! The worker reads one [`WrapRequest`] per line on its stdin, and answers each of them with one
+//! [`WrapResponse`] per line on its stdout, prefixed with [`WRAP_RESPONSE_PREFIX`] since gnark
+//! also logs to stdout.
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    sync::Mutex,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{ffi::get_docker_image, witness::GnarkWitness, ProofBn254};
+
+/// The prefix of the lines of the stdout of a worker holding a [`WrapResponse`].
+pub const WRAP_RESPONSE_PREFIX: &str = "monerochan-wrap-response:";
+
+/// The proof system a [`WrapWorker`] proves with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WrapSystem {
+    Plonk,
+    Groth16,
+}
+
+impl WrapSystem {
+    /// The name of the proof system, as expected by the `--system` argument of the gnark CLI.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WrapSystem::Plonk => "plonk",
+            WrapSystem::Groth16 => "groth16",
+        }
+    }
+}
+
+/// A request to prove a witness, sent to a worker.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WrapRequest {
+    pub witness: GnarkWitness,
+    /// The seed to derive the randomness of the proof from, for a reproducible proof.
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+/// The answer of a worker to a [`WrapRequest`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum WrapResponse {
+    Proof(ProofBn254),
+    Error(String),
+}
+
+/// A gnark process proving witnesses for one proof system, started on the first request.
+///
+/// The process is restarted if it exits, e.g. after a failed proof, or if a request is for the
+/// circuit of another build directory. Requests are proven one at a time.
+#[derive(Debug)]
+pub struct WrapWorker {
+    system: WrapSystem,
+    process: Mutex<Option<WorkerProcess>>,
+}
+
+impl WrapWorker {
+    /// Creates a new [WrapWorker] for the given proof system.
+    pub fn new(system: WrapSystem) -> Self {
+        Self { system, process: Mutex::new(None) }
+    }
+
+    /// The proof system of the worker.
+    pub fn system(&self) -> WrapSystem {
+        self.system
+    }
+
+    /// Proves a witness with the circuit in `build_dir`, with the randomness of the proof derived
+    /// from `seed` if set.
+    pub fn prove(
+        &self,
+        witness: GnarkWitness,
+        build_dir: &Path,
+        seed: Option<u64>,
+    ) -> Result<ProofBn254> {
+        let mut process = self.process.lock().unwrap_or_else(|e| e.into_inner());
+        if process.as_ref().map_or(true, |process| process.build_dir != build_dir) {
+            // Dropping the previous process, if any, stops it.
+            *process = None;
+            *process = Some(WorkerProcess::spawn(self.system, build_dir)?);
+        }
+
+        let response = process.as_mut().unwrap().request(&WrapRequest { witness, seed });
+        if response.is_err() {
+            // The worker is in an unknown state, so start a new one for the next request.
+            *process = None;
+        }
+        match response? {
+            WrapResponse::Proof(proof) => Ok(proof),
+            WrapResponse::Error(err) => Err(anyhow!("the wrap worker failed to prove: {err}")),
+        }
+    }
+}
+
+/// A running gnark CLI `worker` command.
+#[derive(Debug)]
+struct WorkerProcess {
+    build_dir: PathBuf,
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl WorkerProcess {
+    fn spawn(system: WrapSystem, build_dir: &Path) -> Result<Self> {
+        let build_dir_str = build_dir
+            .to_str()
+            .ok_or_else(|| anyhow!("Failed to convert build dir to string"))?;
+        tracing::info!("Starting the {} wrap worker for {}", system.as_str(), build_dir_str);
+        let mut child = Command::new("docker")
+            .args(["run", "-i", "--rm", "-v"])
+            .arg(format!("{build_dir_str}:/circuit"))
+            .arg(get_docker_image())
+            .args(["worker", "--system", system.as_str(), "/circuit"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .context("failed to start the wrap worker, please ensure that docker is installed")?;
+        let stdin = child.stdin.take();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        Ok(Self { build_dir: build_dir.to_path_buf(), child, stdin, stdout })
+    }
+
+    fn request(&mut self, request: &WrapRequest) -> Result<WrapResponse> {
+        let stdin = self.stdin.as_mut().unwrap();
+        serde_json::to_writer(&mut *stdin, request)?;
+        stdin.write_all(b"\n")?;
+        stdin.flush()?;
+
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.stdout.read_line(&mut line)? == 0 {
+                bail!("the wrap worker exited with {:?}", self.child.wait()?);
+            }
+            match line.trim_end().strip_prefix(WRAP_RESPONSE_PREFIX) {
+                Some(response) => return Ok(serde_json::from_str(response)?),
+                None => tracing::debug!("wrap worker: {}", line.trim_end()),
+            }
+        }
+    }
+}
+
+impl Drop for WorkerProcess {
+    fn drop(&mut self) {
+        // Closing the stdin of the worker makes it exit, and docker remove its container, which
+        // killing the docker client would not.
+        self.stdin = None;
+        let _ = self.child.wait();
+    }
+}
@@ -7,10 +7,19 @@ fn main() {
     // Compile proto file for network API (only when network feature is enabled)
     // Check if tonic-build is available by checking for the feature flag via environment
     if std::env::var("CARGO_FEATURE_NETWORK").is_ok() {
+        println!("cargo:rerun-if-changed=src/network/proto/admin.proto");
         println!("cargo:rerun-if-changed=src/network/proto/api.proto");
+        println!("cargo:rerun-if-changed=src/network/proto/worker.proto");
         tonic_build::configure()
             .build_server(true)
-            .compile_protos(&["src/network/proto/api.proto"], &["src/network/proto"])
+            .compile_protos(
+                &[
+                    "src/network/proto/admin.proto",
+                    "src/network/proto/api.proto",
+                    "src/network/proto/worker.proto",
+                ],
+                &["src/network/proto"],
+            )
             .expect("failed to compile network api proto");
     }
 }
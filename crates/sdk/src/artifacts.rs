@@ -2,12 +2,148 @@
 //!
 //! A library for exporting the MONEROCHAN artifacts to the specified output directory.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
 
-use crate::install::try_install_circuit_artifacts;
+use crate::{install::try_install_circuit_artifacts, MONEROCHANProofMode};
 pub use monerochan_prover::build::build_plonk_bn254_artifacts_with_dummy;
+use monerochan_prover::MONEROCHAN_CIRCUIT_VERSION;
+
+/// The library recomputing the public inputs of the gnark verifiers, shared by the gateways and
+/// mirrored by `monerochan_verifier::public_inputs`.
+const PUBLIC_INPUTS_LIBRARY: &str = r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// @title MONEROCHAN Public Inputs
+/// @notice Recomputes the public inputs MONEROCHAN Plonk and Groth16 proofs are verified against.
+library MONEROCHANPublicInputs {
+    /// @notice Hashes the public values to a field element inside Bn254.
+    function hashPublicValues(bytes memory publicValues) internal pure returns (bytes32) {
+        return sha256(publicValues) & bytes32(uint256((1 << 253) - 1));
+    }
+
+    /// @notice The public inputs of a proof of the program with the given verification key and
+    /// public values.
+    /// @param programVKey The verification key of the program.
+    /// @param publicValues The public values committed by the program.
+    function publicInputs(bytes32 programVKey, bytes memory publicValues)
+        internal
+        pure
+        returns (uint256[2] memory inputs)
+    {
+        inputs[0] = uint256(programVKey);
+        inputs[1] = uint256(hashPublicValues(publicValues));
+    }
+}
+"#;
+
+/// The gateway contract for PLONK proofs, wrapping the gnark verifier contract.
+const PLONK_GATEWAY_TEMPLATE: &str = r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import {PlonkVerifier} from "./PlonkVerifier.sol";
+import {MONEROCHANPublicInputs} from "./MONEROCHANPublicInputs.sol";
+
+/// @title MONEROCHAN PLONK Gateway
+/// @notice Verifies MONEROCHAN PLONK proofs generated with circuit version {VERSION}.
+contract MONEROCHANPlonkGateway is PlonkVerifier {
+    /// @notice The proof was generated for a different verifier.
+    error WrongVerifierSelector(bytes4 received, bytes4 expected);
+
+    /// @notice The proof is invalid.
+    error InvalidProof();
+
+    function VERSION() external pure returns (string memory) {
+        return "{VERSION}";
+    }
+
+    /// @notice The SHA-256 hash of the gnark verifying key.
+    function VERIFIER_HASH() public pure returns (bytes32) {
+        return {VERIFIER_HASH};
+    }
+
+    /// @notice Hashes the public values to a field element inside Bn254.
+    function hashPublicValues(bytes calldata publicValues) public pure returns (bytes32) {
+        return MONEROCHANPublicInputs.hashPublicValues(publicValues);
+    }
+
+    /// @notice Verifies a proof of the program with the given verification key and public values.
+    /// @param programVKey The verification key of the program.
+    /// @param publicValues The public values committed by the program.
+    /// @param proofBytes The proof, prefixed with the first 4 bytes of the verifier hash.
+    function verifyProof(bytes32 programVKey, bytes calldata publicValues, bytes calldata proofBytes)
+        external
+        view
+    {
+        bytes4 receivedSelector = bytes4(proofBytes[:4]);
+        bytes4 expectedSelector = bytes4(VERIFIER_HASH());
+        if (receivedSelector != expectedSelector) {
+            revert WrongVerifierSelector(receivedSelector, expectedSelector);
+        }
+
+        uint256[2] memory publicInputs =
+            MONEROCHANPublicInputs.publicInputs(programVKey, publicValues);
+        uint256[] memory inputs = new uint256[](2);
+        inputs[0] = publicInputs[0];
+        inputs[1] = publicInputs[1];
+        bool success = this.Verify(proofBytes[4:], inputs);
+        if (!success) {
+            revert InvalidProof();
+        }
+    }
+}
+"#;
+
+/// The gateway contract for Groth16 proofs, wrapping the gnark verifier contract.
+const GROTH16_GATEWAY_TEMPLATE: &str = r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import {Groth16Verifier} from "./Groth16Verifier.sol";
+import {MONEROCHANPublicInputs} from "./MONEROCHANPublicInputs.sol";
+
+/// @title MONEROCHAN Groth16 Gateway
+/// @notice Verifies MONEROCHAN Groth16 proofs generated with circuit version {VERSION}.
+contract MONEROCHANGroth16Gateway is Groth16Verifier {
+    /// @notice The proof was generated for a different verifier.
+    error WrongVerifierSelector(bytes4 received, bytes4 expected);
+
+    function VERSION() external pure returns (string memory) {
+        return "{VERSION}";
+    }
+
+    /// @notice The SHA-256 hash of the gnark verifying key.
+    function VERIFIER_HASH() public pure returns (bytes32) {
+        return {VERIFIER_HASH};
+    }
+
+    /// @notice Hashes the public values to a field element inside Bn254.
+    function hashPublicValues(bytes calldata publicValues) public pure returns (bytes32) {
+        return MONEROCHANPublicInputs.hashPublicValues(publicValues);
+    }
+
+    /// @notice Verifies a proof of the program with the given verification key and public values.
+    /// @dev Reverts if the proof is invalid.
+    /// @param programVKey The verification key of the program.
+    /// @param publicValues The public values committed by the program.
+    /// @param proofBytes The proof, prefixed with the first 4 bytes of the verifier hash.
+    function verifyProof(bytes32 programVKey, bytes calldata publicValues, bytes calldata proofBytes)
+        external
+        view
+    {
+        bytes4 receivedSelector = bytes4(proofBytes[:4]);
+        bytes4 expectedSelector = bytes4(VERIFIER_HASH());
+        if (receivedSelector != expectedSelector) {
+            revert WrongVerifierSelector(receivedSelector, expectedSelector);
+        }
+
+        uint256[2] memory inputs = MONEROCHANPublicInputs.publicInputs(programVKey, publicValues);
+        uint256[8] memory proof = abi.decode(proofBytes[4:], (uint256[8]));
+        this.Verify(proof, inputs);
+    }
+}
+"#;
 
 /// Exports the solidity verifier for PLONK proofs to the specified output directory.
 ///
@@ -66,3 +202,78 @@ pub fn export_solidity_groth16_bn254_verifier(output_dir: impl Into<PathBuf>) ->
 
     Ok(())
 }
+
+/// Exports the solidity verifier for the given proof mode to the specified output directory.
+///
+/// This writes the gnark verifier contract (`PlonkVerifier.sol` or `Groth16Verifier.sol`) of the
+/// current circuit artifacts, and a gateway contract (`MONEROCHANPlonkGateway.sol` or
+/// `MONEROCHANGroth16Gateway.sol`) that embeds the hash of the gnark verifying key and the circuit
+/// version, and checks proofs in the format of
+/// [`crate::MONEROCHANProofWithPublicValues::bytes`] for proofs without a TEE integrity proof.
+/// The gateway recomputes the public inputs of the proof with the `MONEROCHANPublicInputs.sol`
+/// library, written alongside it, which integrators can also use directly.
+///
+/// WARNING: If you are on development mode, this function assumes that the artifacts have
+/// already been built.
+pub fn export_solidity_verifier(
+    mode: MONEROCHANProofMode,
+    output_dir: impl Into<PathBuf>,
+) -> Result<()> {
+    let (system, verifier_file, gateway_name, template) = match mode {
+        MONEROCHANProofMode::Plonk => {
+            ("plonk", "PlonkVerifier.sol", "MONEROCHANPlonkGateway", PLONK_GATEWAY_TEMPLATE)
+        }
+        MONEROCHANProofMode::Groth16 => {
+            ("groth16", "Groth16Verifier.sol", "MONEROCHANGroth16Gateway", GROTH16_GATEWAY_TEMPLATE)
+        }
+        mode => {
+            return Err(anyhow::anyhow!(
+                "{mode:?} proofs are not verifiable onchain, only Plonk and Groth16 proofs are"
+            ))
+        }
+    };
+
+    let output_dir: PathBuf = output_dir.into();
+    let artifacts_dir = if monerochan_prover::build::monerochan_dev_mode() {
+        match mode {
+            MONEROCHANProofMode::Plonk => monerochan_prover::build::plonk_bn254_artifacts_dev_dir(),
+            _ => monerochan_prover::build::groth16_bn254_artifacts_dev_dir(),
+        }
+    } else {
+        try_install_circuit_artifacts(system)
+    };
+
+    let verifier_path = artifacts_dir.join(verifier_file);
+    if !verifier_path.exists() {
+        return Err(anyhow::anyhow!("verifier file not found at {:?}", verifier_path));
+    }
+    let vk_path = artifacts_dir.join(format!("{system}_vk.bin"));
+    let vk_hash = verifier_hash(&vk_path)?;
+
+    std::fs::create_dir_all(&output_dir).context("Failed to create output directory.")?;
+    let output_path = output_dir.join(verifier_file);
+    std::fs::copy(&verifier_path, &output_path).context("Failed to copy verifier file.")?;
+
+    let gateway = template
+        .replace("{VERSION}", MONEROCHAN_CIRCUIT_VERSION)
+        .replace("{VERIFIER_HASH}", &format!("0x{}", hex::encode(vk_hash)));
+    let gateway_path = output_dir.join(format!("{gateway_name}.sol"));
+    std::fs::write(&gateway_path, gateway).context("Failed to write gateway file.")?;
+    let library_path = output_dir.join("MONEROCHANPublicInputs.sol");
+    std::fs::write(&library_path, PUBLIC_INPUTS_LIBRARY).context("Failed to write library file.")?;
+    tracing::info!(
+        "exported verifier from {} to {} and {}",
+        verifier_path.display(),
+        output_path.display(),
+        gateway_path.display()
+    );
+
+    Ok(())
+}
+
+/// The hash of the gnark verifying key, whose first 4 bytes prefix the onchain proofs.
+fn verifier_hash(vk_path: &Path) -> Result<[u8; 32]> {
+    let vk = std::fs::read(vk_path)
+        .with_context(|| format!("Failed to read verifying key at {}.", vk_path.display()))?;
+    Ok(Sha256::digest(vk).into())
+}
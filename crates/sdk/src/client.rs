@@ -2,7 +2,9 @@
 //!
 //! A client for interacting with the prover for the MONEROCHAN RISC-V zkVM.
 
-use crate::{cpu::builder::CpuProverBuilder, cuda::builder::CudaProverBuilder, env::EnvProver};
+use std::path::PathBuf;
+
+use crate::{cpu::builder::CpuProverBuilder, cuda::builder::CudaProverBuilder, env::{EnvProver, EnvProverError}, MONEROCHANProofMode};
 
 #[cfg(feature = "network")]
 use crate::network::{builder::NetworkProverBuilder, NetworkMode};
@@ -36,6 +38,48 @@ impl ProverClient {
         EnvProver::new()
     }
 
+    /// Builds an [`EnvProver`] like [`ProverClient::from_env`], but returns an error instead of
+    /// panicking if the environment variables are invalid.
+    ///
+    /// See [`EnvProver::try_new`] for the environment variables that are read.
+    ///
+    /// # Errors
+    /// Returns an [`EnvProverError`] naming the offending environment variable.
+    ///
+    /// # Usage
+    /// ```no_run
+    /// use monerochan::ProverClient;
+    ///
+    /// std::env::set_var("MONEROCHAN_PROVER", "cpu");
+    /// std::env::set_var("MONEROCHAN_CYCLE_LIMIT", "1000000000");
+    /// let prover = ProverClient::try_from_env().unwrap();
+    /// ```
+    pub fn try_from_env() -> Result<EnvProver, EnvProverError> {
+        EnvProver::try_new()
+    }
+
+    /// Exports the solidity verifier contracts for the given proof mode to the output directory.
+    ///
+    /// See [`crate::artifacts::export_solidity_verifier`] for the contracts that are written.
+    ///
+    /// # Errors
+    /// Returns an error if the mode is not [`MONEROCHANProofMode::Plonk`] or
+    /// [`MONEROCHANProofMode::Groth16`], or if the contracts cannot be written.
+    ///
+    /// # Usage
+    /// ```no_run
+    /// use monerochan::{MONEROCHANProofMode, ProverClient};
+    ///
+    /// ProverClient::export_solidity_verifier(MONEROCHANProofMode::Groth16, "contracts/src")
+    ///     .unwrap();
+    /// ```
+    pub fn export_solidity_verifier(
+        mode: MONEROCHANProofMode,
+        output_dir: impl Into<PathBuf>,
+    ) -> anyhow::Result<()> {
+        crate::artifacts::export_solidity_verifier(mode, output_dir)
+    }
+
     /// Creates a new [`ProverClientBuilder`] so that you can configure the prover client.
     #[must_use]
     pub fn builder() -> ProverClientBuilder {
@@ -80,7 +124,15 @@ impl ProverClientBuilder {
     /// ```
     #[must_use]
     pub fn mock(&self) -> CpuProverBuilder {
-        CpuProverBuilder { mock: true }
+        CpuProverBuilder {
+            mock: true,
+            mock_seed: 0,
+            wrap_opts: None,
+            moongate_compress: None,
+            wrap_worker: false,
+            large_programs: false,
+            core_fri_parameters: None,
+        }
     }
 
     /// Builds a [`CpuProver`] specifically for local CPU proving.
@@ -98,7 +150,15 @@ impl ProverClientBuilder {
     /// ```
     #[must_use]
     pub fn cpu(&self) -> CpuProverBuilder {
-        CpuProverBuilder { mock: false }
+        CpuProverBuilder {
+            mock: false,
+            mock_seed: 0,
+            wrap_opts: None,
+            moongate_compress: None,
+            wrap_worker: false,
+            large_programs: false,
+            core_fri_parameters: None,
+        }
     }
 
     /// Builds a [`CudaProver`] specifically for local proving on NVIDIA GPUs.
@@ -141,7 +201,14 @@ impl ProverClientBuilder {
         NetworkProverBuilder {
             rpc_url: None,
             tee_signers: None,
+            tee_signer_refresh_interval: None,
+            on_tee_signers_changed: None,
             network_mode: Some(NetworkMode::default()),
+            compression: None,
+            transport: None,
+            receipt_key: None,
+            api_key: None,
+            nonce_source: None,
         }
     }
 
@@ -165,7 +232,14 @@ impl ProverClientBuilder {
         NetworkProverBuilder {
             rpc_url: None,
             tee_signers: None,
+            tee_signer_refresh_interval: None,
+            on_tee_signers_changed: None,
             network_mode: Some(mode),
+            compression: None,
+            transport: None,
+            receipt_key: None,
+            api_key: None,
+            nonce_source: None,
         }
     }
 }
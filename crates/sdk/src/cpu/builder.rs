@@ -2,18 +2,192 @@
 //!
 //! This module provides a builder for the [`CpuProver`].
 
+use monerochan_cuda::MoongateServer;
+use monerochan_prover::WrapOpts;
+use monerochan_stark::FriParameters;
+
 use crate::utils::setup_memory_usage_monitoring;
 
-use super::CpuProver;
+use super::{
+    stages::{MoongateCompress, WrapWorkers},
+    CpuProver,
+};
 
 /// A builder for the [`CpuProver`].
 ///
 /// The builder is used to configure the [`CpuProver`] before it is built.
 pub struct CpuProverBuilder {
     pub(crate) mock: bool,
+    pub(crate) mock_seed: u64,
+    pub(crate) wrap_opts: Option<WrapOpts>,
+    pub(crate) moongate_compress: Option<MoongateServer>,
+    pub(crate) wrap_worker: bool,
+    pub(crate) large_programs: bool,
+    pub(crate) core_fri_parameters: Option<FriParameters>,
 }
 
 impl CpuProverBuilder {
+    /// Sets the seed of the mock prover.
+    ///
+    /// # Details
+    /// Mock proofs are deterministic: the same seed, program, inputs and proof mode always produce
+    /// the same proof. A mock prover only accepts proofs generated by a mock prover with the same
+    /// seed, which lets tests check that a proof is routed to the right verifier. The default seed
+    /// is `0`. This has no effect unless the builder was created with
+    /// [`crate::client::ProverClientBuilder::mock`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::ProverClient;
+    ///
+    /// let prover = ProverClient::builder().mock().mock_seed(42).build();
+    /// ```
+    #[must_use]
+    pub fn mock_seed(mut self, seed: u64) -> Self {
+        self.mock_seed = seed;
+        self
+    }
+
+    /// Sets the options of the wrap circuit used for Plonk and Groth16 proofs.
+    ///
+    /// # Details
+    /// By default, the options are read from the `MONEROCHAN_DEV` and `FRI_QUERIES` environment
+    /// variables. Tests can use [`WrapOpts::dev`] to opt into the small development circuit
+    /// without touching the environment. The FRI parameters of the production circuit cannot be
+    /// changed, since its artifacts come from the trusted setup.
+    ///
+    /// # Panics
+    /// [`Self::build`] panics if the options are neither [`WrapOpts::production`] nor for the
+    /// development circuit.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{ProverClient, WrapOpts};
+    ///
+    /// let prover = ProverClient::builder().cpu().wrap_opts(WrapOpts::dev()).build();
+    /// ```
+    #[must_use]
+    pub fn wrap_opts(mut self, opts: WrapOpts) -> Self {
+        self.wrap_opts = Some(opts);
+        self
+    }
+
+    /// Delegates the compress stage to the embedded Moongate server.
+    ///
+    /// # Details
+    /// The compress stage recursively proves the shards of the core proof, and dominates the
+    /// proving time of compressed, Plonk and Groth16 proofs. With this option, the core proof is
+    /// sent to a Moongate server, which runs the whole compress stage on its GPU and returns the
+    /// compressed proof: the core proof, shrink and wrap stages still run on the CPU. This is not
+    /// a GPU implementation of the compress prover of [`CpuProverComponents`]: the server
+    /// compresses with its own options, so proofs with a seed or a number of FRI queries fail
+    /// rather than being compressed with other options than requested. This has no effect on a
+    /// mock prover.
+    ///
+    /// The server is started on the first compressed proof, and a server that cannot be started
+    /// or reached fails that proof.
+    ///
+    /// [`CpuProverComponents`]: monerochan_prover::components::CpuProverComponents
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::ProverClient;
+    ///
+    /// let prover = ProverClient::builder().cpu().compress_on_moongate().build();
+    /// ```
+    #[must_use]
+    pub fn compress_on_moongate(mut self) -> Self {
+        self.moongate_compress = Some(MoongateServer::default());
+        self
+    }
+
+    /// Delegates the compress stage to an external Moongate server.
+    ///
+    /// # Details
+    /// See [`Self::compress_on_moongate`]. The server at the provided endpoint is used instead of
+    /// the embedded one, e.g. to share a GPU machine between several CPU provers.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::ProverClient;
+    ///
+    /// let prover =
+    ///     ProverClient::builder().cpu().compress_on_moongate_server("http://...").build();
+    /// ```
+    #[must_use]
+    pub fn compress_on_moongate_server(mut self, endpoint: &str) -> Self {
+        self.moongate_compress = Some(MoongateServer::External { endpoint: endpoint.to_string() });
+        self
+    }
+
+    /// Wraps Plonk and Groth16 proofs with long-lived gnark processes.
+    ///
+    /// # Details
+    /// By default, every Plonk or Groth16 proof starts a gnark docker container, which loads the
+    /// circuit and its proving key before proving. With this option, a worker container is started
+    /// on the first proof of each proof system and keeps them in memory, so that the next proofs
+    /// only pay for proving. The workers stop when the prover is dropped.
+    ///
+    /// The workers run the gnark docker image, even with the `native-gnark` feature.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::ProverClient;
+    ///
+    /// let prover = ProverClient::builder().cpu().wrap_worker().build();
+    /// ```
+    #[must_use]
+    pub fn wrap_worker(mut self) -> Self {
+        self.wrap_worker = true;
+        self
+    }
+
+    /// Allows programs of up to `2^24` instructions, instead of `2^22`.
+    ///
+    /// # Details
+    /// This is the same as setting `MONEROCHAN_LARGE_PROGRAMS=true`. The proofs of programs of
+    /// more than `2^22` instructions can only be compressed with `VERIFY_VK=false`, since the
+    /// verification keys of their shapes are not among the allowed recursion verification keys.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::ProverClient;
+    ///
+    /// let prover = ProverClient::builder().cpu().large_programs().build();
+    /// ```
+    #[must_use]
+    pub fn large_programs(mut self) -> Self {
+        self.large_programs = true;
+        self
+    }
+
+    /// Sets the FRI parameters of the core proofs, such as their blowup.
+    ///
+    /// # Details
+    /// A larger blowup allows fewer queries for the same security level, which gives smaller
+    /// proofs that are slower to generate. The verifying keys depend on the blowup, so the
+    /// programs must be set up with the built prover, and their proofs verified with
+    /// [`crate::Prover::verify_with_fri_parameters`] and the same parameters. The core proofs can
+    /// only be compressed with `VERIFY_VK=false`, since the allowed recursion verification keys
+    /// verify core proofs with the default parameters.
+    ///
+    /// Building the prover panics if the parameters are not secure enough, see
+    /// [`FriParameters::validate`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{FriParameters, ProverClient};
+    ///
+    /// let fri_parameters =
+    ///     FriParameters { log_blowup: 2, num_queries: 50, proof_of_work_bits: 16 };
+    /// let prover = ProverClient::builder().cpu().core_fri_parameters(fri_parameters).build();
+    /// ```
+    #[must_use]
+    pub fn core_fri_parameters(mut self, fri_parameters: FriParameters) -> Self {
+        self.core_fri_parameters = Some(fri_parameters);
+        self
+    }
+
     /// Builds a [`CpuProver`].
     ///
     /// # Details
@@ -28,11 +202,30 @@ impl CpuProverBuilder {
     /// ```
     #[must_use]
     pub fn build(self) -> CpuProver {
-        if self.mock {
-            CpuProver::mock()
+        let mut prover = if self.mock {
+            CpuProver::mock_with_seed(self.mock_seed)
         } else {
             setup_memory_usage_monitoring();
             CpuProver::new()
+        };
+        if let Some(opts) = self.wrap_opts {
+            prover.prover = prover.prover.with_wrap_opts(opts);
+        }
+        if self.large_programs {
+            prover.prover = prover.prover.with_large_programs();
+        }
+        if let Some(fri_parameters) = self.core_fri_parameters {
+            prover.prover = prover
+                .prover
+                .with_core_fri_parameters(fri_parameters)
+                .expect("invalid core FRI parameters");
+        }
+        if self.wrap_worker && !self.mock {
+            prover.wrap_workers = Some(WrapWorkers::new());
+        }
+        if let (false, Some(moongate_server)) = (self.mock, self.moongate_compress) {
+            prover.moongate_compress = Some(MoongateCompress::new(moongate_server));
         }
+        prover
     }
 }
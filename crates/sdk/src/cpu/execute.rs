@@ -2,11 +2,17 @@
 //!
 //! This module provides a builder for simulating the execution of a program on the CPU.
 
+use std::path::PathBuf;
+
 use anyhow::Result;
-use monerochan_core_executor::{ExecutionReport, HookEnv, IoWriter, MONEROCHANContextBuilder};
-use monerochan_core_machine::io::MONEROCHANStdin;
+use monerochan_core_executor::{
+    ExecutionReport, HookEnv, IoWriter, MONEROCHANContextBuilder, OutputMode,
+    DEFAULT_MAX_OUTPUT_SIZE,
+};
+use monerochan_core_machine::io::{MONEROCHANStdin, StdinSchema};
 use monerochan_primitives::io::MONEROCHANPublicValues;
-use monerochan_prover::{components::CpuProverComponents, MONEROCHANProver};
+use monerochan_prover::{components::CpuProverComponents, gas::ShardPlan, MONEROCHANProver};
+use monerochan_stark::MONEROCHANCoreOpts;
 
 /// A builder for simulating the execution of a program on the CPU.
 ///
@@ -17,6 +23,8 @@ pub struct CpuExecuteBuilder<'a> {
     pub(crate) stdin: MONEROCHANStdin,
     pub(crate) prover: &'a MONEROCHANProver<CpuProverComponents>,
     pub(crate) context_builder: MONEROCHANContextBuilder<'a>,
+    pub(crate) core_opts: MONEROCHANCoreOpts,
+    pub(crate) schema: Option<StdinSchema>,
 }
 
 impl<'a> CpuExecuteBuilder<'a> {
@@ -106,7 +114,7 @@ impl<'a> CpuExecuteBuilder<'a> {
     /// ```
     #[must_use]
     pub fn deferred_proof_verification(mut self, value: bool) -> Self {
-        self.context_builder.set_deferred_proof_verification(value);
+        self.context_builder.deferred_proof_verification(value);
         self
     }
 
@@ -179,6 +187,278 @@ impl<'a> CpuExecuteBuilder<'a> {
         self
     }
 
+    /// Capture the output of the guest program in the [`ExecutionReport`].
+    ///
+    /// # Arguments
+    /// * `max_size` - The maximum size of each captured stream, in bytes.
+    ///
+    /// # Details
+    /// By default, the `stdout` and `stderr` of the program that are not redirected with
+    /// [`Self::stdout`] or [`Self::stderr`] are captured in [`ExecutionReport::output`], up to
+    /// 1 MiB per stream, instead of being printed by the host. Once a stream is full, the
+    /// following lines are dropped.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{include_elf, Prover, ProverClient, MONEROCHANStdin};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let client = ProverClient::builder().cpu().build();
+    /// let (_, report) = client.execute(elf, &stdin).capture_output(1 << 16).run().unwrap();
+    /// print!("{}", report.output.unwrap().stdout.text);
+    /// ```
+    #[must_use]
+    pub fn capture_output(mut self, max_size: usize) -> Self {
+        self.context_builder.output(OutputMode::Capture { max_size });
+        self
+    }
+
+    /// Discard the output of the guest program.
+    ///
+    /// # Details
+    /// The `stdout` and `stderr` of the program that are not redirected with [`Self::stdout`] or
+    /// [`Self::stderr`] are neither captured nor printed, which speeds up the execution of
+    /// programs that print a lot.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{include_elf, Prover, ProverClient, MONEROCHANStdin};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let client = ProverClient::builder().cpu().build();
+    /// client.execute(elf, &stdin).discard_output().run().unwrap();
+    /// ```
+    #[must_use]
+    pub fn discard_output(mut self) -> Self {
+        self.context_builder.output(OutputMode::Discard);
+        self
+    }
+
+    /// Print the output of the guest program to the `stderr` of the host, as it is written.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{include_elf, Prover, ProverClient, MONEROCHANStdin};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let client = ProverClient::builder().cpu().build();
+    /// client.execute(elf, &stdin).print_output().run().unwrap();
+    /// ```
+    #[must_use]
+    pub fn print_output(mut self) -> Self {
+        self.context_builder.output(OutputMode::Print);
+        self
+    }
+
+    /// Save the execution trace to a file.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the trace file.
+    ///
+    /// # Details
+    /// Every instruction, load, store and syscall of the execution is written to the file in a
+    /// versioned, compressed binary format, which can be read back with
+    /// [`crate::trace::TraceReader`]. Instructions run in unconstrained blocks are not traced.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{include_elf, trace::TraceReader, Prover, ProverClient, MONEROCHANStdin};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let client = ProverClient::builder().cpu().build();
+    /// client.execute(elf, &stdin).save_trace("program.trace").run().unwrap();
+    ///
+    /// for event in TraceReader::open("program.trace").unwrap() {
+    ///     println!("{:?}", event.unwrap());
+    /// }
+    /// ```
+    #[must_use]
+    pub fn save_trace(mut self, path: impl Into<PathBuf>) -> Self {
+        self.context_builder.trace_file(path);
+        self
+    }
+
+    /// Journal which parts of the inputs are read by the program.
+    ///
+    /// # Arguments
+    /// * `value` - Whether to journal the reads of the inputs.
+    ///
+    /// # Details
+    /// Default: `false`. The journal is available as [`ExecutionReport::input_reads`], and lists,
+    /// for each buffer read from stdin, the bytes the program actually loaded. Witness generators
+    /// can use it to prune the stdin of the proving run.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{include_elf, Prover, ProverClient, MONEROCHANStdin};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let client = ProverClient::builder().cpu().build();
+    /// let (_, report) = client.execute(elf, &stdin).journal_input_reads(true).run().unwrap();
+    /// for (i, input) in report.input_reads.unwrap().inputs.iter().enumerate() {
+    ///     println!("input {i}: read {:?} of {} bytes", input.read_ranges(), input.len);
+    /// }
+    /// ```
+    #[must_use]
+    pub fn journal_input_reads(mut self, value: bool) -> Self {
+        self.context_builder.journal_input_reads(value);
+        self
+    }
+
+    /// Trap on the behaviors which execute but cannot be proven.
+    ///
+    /// # Arguments
+    /// * `value` - Whether to execute in strict mode.
+    ///
+    /// # Details
+    /// Default: `false`. In strict mode, a jump to a misaligned address, or out of the program
+    /// without halting, fails the execution with a trap reporting the program counter and the
+    /// function of the jump, instead of succeeding and failing the proof.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{include_elf, Prover, ProverClient, MONEROCHANStdin};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let client = ProverClient::builder().cpu().build();
+    /// let (public_values, report) = client.execute(elf, &stdin).strict(true).run().unwrap();
+    /// ```
+    #[must_use]
+    pub fn strict(mut self, value: bool) -> Self {
+        self.context_builder.strict(value);
+        self
+    }
+
+    /// Complete the execution of a program halting with a non-zero exit code, and report the code.
+    ///
+    /// # Arguments
+    /// * `value` - Whether to report a non-zero exit code rather than failing the execution.
+    ///
+    /// # Details
+    /// Default: `false`, a non-zero exit code fails the execution with
+    /// [`monerochan_core_executor::ExecutionError::HaltWithNonZeroExitCode`]. Otherwise, the code
+    /// is in [`ExecutionReport::exit_code`]: 1 if the program panicked, or the code of a failed
+    /// `monerochan_runtime::ensure!`, so that the failure can be told apart from a bug of the
+    /// program without parsing its output.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{include_elf, Prover, ProverClient, MONEROCHANStdin};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let client = ProverClient::builder().cpu().build();
+    /// let (_, report) = client.execute(elf, &stdin).report_exit_code(true).run().unwrap();
+    /// if report.exit_code != 0 {
+    ///     println!("the program failed with exit code {}", report.exit_code);
+    /// }
+    /// ```
+    #[must_use]
+    pub fn report_exit_code(mut self, value: bool) -> Self {
+        self.context_builder.report_exit_code(value);
+        self
+    }
+
+    /// Validate the inputs against a schema before executing the program.
+    ///
+    /// # Arguments
+    /// * `schema` - The named inputs the program reads, in order.
+    ///
+    /// # Details
+    /// The inputs must be written with [`MONEROCHANStdin::insert`] in the order of the schema, and
+    /// each value must deserialize as the type of its input, otherwise [`Self::run`] returns an
+    /// error without executing the program.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{include_elf, Prover, ProverClient, MONEROCHANStdin, StdinSchema};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let mut stdin = MONEROCHANStdin::new();
+    /// stdin.insert("height", &1000u64);
+    /// stdin.insert_slice("block", &[1, 2, 3]);
+    ///
+    /// let schema = StdinSchema::new().input::<u64>("height").input_slice("block");
+    /// let client = ProverClient::builder().cpu().build();
+    /// let (public_values, execution_report) =
+    ///     client.execute(elf, &stdin).schema(schema).run().unwrap();
+    /// ```
+    #[must_use]
+    pub fn schema(mut self, schema: StdinSchema) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Set the shard size used by [`Self::shard_plan`].
+    ///
+    /// # Details
+    /// Default: the shard size of the core prover, which can be set with the `SHARD_SIZE`
+    /// environment variable. Must be a power of two, like the shard size of
+    /// [`crate::cpu::prove::CpuProveBuilder::shard_size`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{include_elf, Prover, ProverClient, MONEROCHANStdin};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let client = ProverClient::builder().cpu().build();
+    /// let plan = client.execute(elf, &stdin).shard_size(1 << 20).shard_plan().unwrap();
+    /// ```
+    #[must_use]
+    pub fn shard_size(mut self, value: usize) -> Self {
+        assert!(value.is_power_of_two(), "shard size must be a power of 2");
+        self.core_opts.shard_size = value;
+        self
+    }
+
+    /// Executes the program and estimates how many shards the core prover will produce.
+    ///
+    /// # Details
+    /// The program is executed with the built arguments, keeping track of where the core prover
+    /// splits the execution into shards under the current options. The returned [`ShardPlan`]
+    /// holds the estimated rows of each chip in each shard, and
+    /// [`ShardPlan::shards_per_chip`] the number of shards each chip appears in. This is useful
+    /// for sizing a proving cluster before proving.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{include_elf, Prover, ProverClient, MONEROCHANStdin};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let client = ProverClient::builder().cpu().build();
+    /// let plan = client.execute(elf, &stdin).shard_plan().unwrap();
+    /// println!("{} shards", plan.num_shards());
+    /// for (chip, shards) in plan.shards_per_chip() {
+    ///     println!("{chip:?}: {shards} shards");
+    /// }
+    /// ```
+    pub fn shard_plan(self) -> Result<ShardPlan> {
+        let Self { prover, elf, stdin, mut context_builder, core_opts, schema } = self;
+        if let Some(schema) = &schema {
+            stdin.validate(schema)?;
+        }
+        let mut context = context_builder.build();
+        context.io_options.output.get_or_insert(OutputMode::Discard);
+        Ok(prover.shard_plan(elf, &stdin, core_opts, context)?)
+    }
+
     /// Executes the program on the input with the built arguments.
     ///
     /// # Details
@@ -196,9 +476,17 @@ impl<'a> CpuExecuteBuilder<'a> {
     /// let (public_values, execution_report) = client.execute(elf, &stdin).run().unwrap();
     /// ```
     pub fn run(self) -> Result<(MONEROCHANPublicValues, ExecutionReport)> {
-        let Self { prover, elf, stdin, mut context_builder } = self;
-        let context = context_builder.build();
+        let Self { prover, elf, stdin, mut context_builder, schema, .. } = self;
+        if let Some(schema) = &schema {
+            stdin.validate(schema)?;
+        }
+        let span = tracing::info_span!("execute", cycles = tracing::field::Empty).entered();
+        let mut context = context_builder.build();
+        context.io_options.output.get_or_insert(OutputMode::Capture {
+            max_size: DEFAULT_MAX_OUTPUT_SIZE,
+        });
         let (pv, _, report) = prover.execute(elf, &stdin, context)?;
+        span.record("cycles", report.total_instruction_count());
         Ok((pv, report))
     }
 }
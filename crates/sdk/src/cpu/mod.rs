@@ -5,21 +5,26 @@
 pub mod builder;
 pub mod execute;
 pub mod prove;
+pub mod stages;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use execute::CpuExecuteBuilder;
 use prove::CpuProveBuilder;
+pub use stages::{MONEROCHANShrunkProof, WrapMode};
 use monerochan_core_executor::{MONEROCHANContext, MONEROCHANContextBuilder};
 use monerochan_core_machine::io::MONEROCHANStdin;
 use monerochan_prover::{
     components::CpuProverComponents,
     verify::{verify_groth16_bn254_public_inputs, verify_plonk_bn254_public_inputs},
-    Groth16Bn254Proof, PlonkBn254Proof, MONEROCHANCoreProofData, MONEROCHANProofWithMetadata, MONEROCHANProver,
+    Groth16Bn254Proof, HashableKey, InnerSC, PlonkBn254Proof, MONEROCHANCoreProof,
+    MONEROCHANCoreProofData, MONEROCHANProofWithMetadata, MONEROCHANProver,
+};
+use monerochan_stark::{
+    FriParameters, MONEROCHANCoreOpts, MONEROCHANProverOpts, MONEROCHANReduceProof,
 };
-use monerochan_stark::{MONEROCHANCoreOpts, MONEROCHANProverOpts};
 
 use crate::{
-    install::try_install_circuit_artifacts, prover::verify_proof, Prover, MONEROCHANProof, MONEROCHANProofMode,
+    proof::mock_proof_tag, prover::{diagnose_bn254_public_inputs, verify_proof}, Prover, MONEROCHANProof, MONEROCHANProofMode,
     MONEROCHANProofWithPublicValues, MONEROCHANProvingKey, MONEROCHANVerificationError, MONEROCHANVerifyingKey,
 };
 
@@ -27,6 +32,11 @@ use crate::{
 pub struct CpuProver {
     pub(crate) prover: MONEROCHANProver<CpuProverComponents>,
     pub(crate) mock: bool,
+    pub(crate) mock_seed: u64,
+    /// The Moongate server the compress stage is delegated to, if it does not run on the CPU.
+    pub(crate) moongate_compress: Option<stages::MoongateCompress>,
+    /// The long-lived gnark processes the wrap stage proves with, if any.
+    pub(crate) wrap_workers: Option<stages::WrapWorkers>,
 }
 
 impl CpuProver {
@@ -39,7 +49,20 @@ impl CpuProver {
     /// Creates a new [`CpuProver`] in mock mode.
     #[must_use]
     pub fn mock() -> Self {
-        Self { prover: MONEROCHANProver::new(), mock: true }
+        Self::mock_with_seed(0)
+    }
+
+    /// Creates a new [`CpuProver`] in mock mode, whose proofs are only accepted by mock provers
+    /// created with the same seed.
+    #[must_use]
+    pub fn mock_with_seed(seed: u64) -> Self {
+        Self {
+            prover: MONEROCHANProver::new(),
+            mock: true,
+            mock_seed: seed,
+            moongate_compress: None,
+            wrap_workers: None,
+        }
     }
 
     /// Creates a new [`CpuExecuteBuilder`] for simulating the execution of a program on the CPU.
@@ -64,6 +87,8 @@ impl CpuProver {
             elf,
             stdin: stdin.clone(),
             context_builder: MONEROCHANContextBuilder::default(),
+            core_opts: MONEROCHANCoreOpts::default(),
+            schema: None,
         }
     }
 
@@ -104,6 +129,13 @@ impl CpuProver {
         context: MONEROCHANContext<'a>,
         mode: MONEROCHANProofMode,
     ) -> Result<MONEROCHANProofWithPublicValues> {
+        let _span = tracing::info_span!(
+            "prove",
+            mode = ?mode,
+            vk_hash = %pk.vk.bytes32(),
+            cycles = tracing::field::Empty
+        )
+        .entered();
         let program = self.prover.get_program(&pk.elf).unwrap();
 
         // If we're in mock mode, return a mock proof.
@@ -112,8 +144,11 @@ impl CpuProver {
         }
 
         // Generate the core proof.
-        let proof: MONEROCHANProofWithMetadata<MONEROCHANCoreProofData> =
-            self.prover.prove_core(&pk.pk, program, stdin, opts, context)?;
+        let proof: MONEROCHANProofWithMetadata<MONEROCHANCoreProofData> = tracing::info_span!(
+            "prove_core"
+        )
+        .in_scope(|| self.prover.prove_core(&pk.pk, program, stdin, opts, context))?;
+        tracing::Span::current().record("cycles", proof.cycles);
         if mode == MONEROCHANProofMode::Core {
             return Ok(MONEROCHANProofWithPublicValues::new(
                 MONEROCHANProof::Core(proof.proof.0),
@@ -126,7 +161,7 @@ impl CpuProver {
         let deferred_proofs =
             stdin.proofs.iter().map(|(reduce_proof, _)| reduce_proof.clone()).collect();
         let public_values = proof.public_values.clone();
-        let reduce_proof = self.prover.compress(&pk.vk, proof, deferred_proofs, opts)?;
+        let reduce_proof = self.compress_core(&pk.vk, proof, deferred_proofs, opts)?;
         if mode == MONEROCHANProofMode::Compressed {
             return Ok(MONEROCHANProofWithPublicValues::new(
                 MONEROCHANProof::Compressed(Box::new(reduce_proof)),
@@ -136,47 +171,40 @@ impl CpuProver {
         }
 
         // Generate the shrink proof.
-        let compress_proof = self.prover.shrink(reduce_proof, opts)?;
+        let compress_proof =
+            tracing::info_span!("shrink").in_scope(|| self.prover.shrink(reduce_proof, opts))?;
 
         // Generate the wrap proof.
-        let outer_proof = self.prover.wrap_bn254(compress_proof, opts)?;
+        let outer_proof = tracing::info_span!("wrap")
+            .in_scope(|| self.prover.wrap_bn254(compress_proof, opts))?;
 
         // Generate the gnark proof.
-        match mode {
-            MONEROCHANProofMode::Groth16 => {
-                let groth16_bn254_artifacts = if monerochan_prover::build::monerochan_dev_mode() {
-                    monerochan_prover::build::try_build_groth16_bn254_artifacts_dev(
-                        &outer_proof.vk,
-                        &outer_proof.proof,
-                    )
-                } else {
-                    try_install_circuit_artifacts("groth16")
-                };
-
-                let proof = self.prover.wrap_groth16_bn254(outer_proof, &groth16_bn254_artifacts);
-                Ok(MONEROCHANProofWithPublicValues::new(
-                    MONEROCHANProof::Groth16(proof),
-                    public_values,
-                    self.version().to_string(),
-                ))
-            }
-            MONEROCHANProofMode::Plonk => {
-                let plonk_bn254_artifacts = if monerochan_prover::build::monerochan_dev_mode() {
-                    monerochan_prover::build::try_build_plonk_bn254_artifacts_dev(
-                        &outer_proof.vk,
-                        &outer_proof.proof,
-                    )
-                } else {
-                    try_install_circuit_artifacts("plonk")
-                };
-                let proof = self.prover.wrap_plonk_bn254(outer_proof, &plonk_bn254_artifacts);
-                Ok(MONEROCHANProofWithPublicValues::new(
-                    MONEROCHANProof::Plonk(proof),
-                    public_values,
-                    self.version().to_string(),
-                ))
-            }
+        let wrap_mode = match mode {
+            MONEROCHANProofMode::Groth16 => WrapMode::Groth16,
+            MONEROCHANProofMode::Plonk => WrapMode::Plonk,
             _ => unreachable!(),
+        };
+        Ok(MONEROCHANProofWithPublicValues::new(
+            self.wrap_outer(outer_proof, wrap_mode, opts.recursion_opts.seed),
+            public_values,
+            self.version().to_string(),
+        ))
+    }
+
+    /// Compresses a core proof, on the Moongate server if the compress stage was delegated to it
+    /// with [`builder::CpuProverBuilder::compress_on_moongate`].
+    pub(crate) fn compress_core(
+        &self,
+        vk: &MONEROCHANVerifyingKey,
+        proof: MONEROCHANCoreProof,
+        deferred_proofs: Vec<MONEROCHANReduceProof<InnerSC>>,
+        opts: MONEROCHANProverOpts,
+    ) -> Result<MONEROCHANReduceProof<InnerSC>> {
+        let _span =
+            tracing::info_span!("compress", moongate = self.moongate_compress.is_some()).entered();
+        match &self.moongate_compress {
+            Some(moongate) => moongate.compress(vk, proof, deferred_proofs, &opts.recursion_opts),
+            None => Ok(self.prover.compress(vk, proof, deferred_proofs, opts)?),
         }
     }
 
@@ -188,21 +216,47 @@ impl CpuProver {
         mode: MONEROCHANProofMode,
     ) -> Result<MONEROCHANProofWithPublicValues> {
         let (public_values, _, _) = self.prover.execute(&pk.elf, stdin, context)?;
-        Ok(MONEROCHANProofWithPublicValues::create_mock_proof(pk, public_values, mode, self.version()))
+        Ok(MONEROCHANProofWithPublicValues::create_mock_proof_with_seed(
+            pk,
+            public_values,
+            mode,
+            self.version(),
+            self.mock_seed,
+        ))
     }
 
     fn mock_verify(
+        &self,
         bundle: &MONEROCHANProofWithPublicValues,
         vkey: &MONEROCHANVerifyingKey,
     ) -> Result<(), MONEROCHANVerificationError> {
+        // The tag binds the proof to this mock prover, the program and the public values.
+        let expected = mock_proof_tag(
+            self.mock_seed,
+            &vkey.vk.bytes32(),
+            &bundle.public_values,
+            MONEROCHANProofMode::from(&bundle.proof),
+        );
+        if bundle.mock_proof_tag() != Some(expected) {
+            return Err(MONEROCHANVerificationError::Other(anyhow::anyhow!(
+                "mock proof was not generated by this mock prover for this program and public values"
+            )));
+        }
+
         match &bundle.proof {
             MONEROCHANProof::Plonk(PlonkBn254Proof { public_inputs, .. }) => {
                 verify_plonk_bn254_public_inputs(vkey, &bundle.public_values, public_inputs)
-                    .map_err(MONEROCHANVerificationError::Plonk)
+                    .map_err(|err| {
+                        diagnose_bn254_public_inputs(vkey, &bundle.public_values, public_inputs)
+                            .unwrap_or(MONEROCHANVerificationError::Plonk(err))
+                    })
             }
             MONEROCHANProof::Groth16(Groth16Bn254Proof { public_inputs, .. }) => {
                 verify_groth16_bn254_public_inputs(vkey, &bundle.public_values, public_inputs)
-                    .map_err(MONEROCHANVerificationError::Groth16)
+                    .map_err(|err| {
+                        diagnose_bn254_public_inputs(vkey, &bundle.public_values, public_inputs)
+                            .unwrap_or(MONEROCHANVerificationError::Groth16(err))
+                    })
             }
             _ => Ok(()),
         }
@@ -211,8 +265,14 @@ impl CpuProver {
 
 impl Prover<CpuProverComponents> for CpuProver {
     fn setup(&self, elf: &[u8]) -> (MONEROCHANProvingKey, MONEROCHANVerifyingKey) {
-        let (pk, _, _, vk) = self.prover.setup(elf);
-        (pk, vk)
+        self.try_setup(elf).unwrap_or_else(|err| panic!("{err:#}"))
+    }
+
+    fn try_setup(&self, elf: &[u8]) -> Result<(MONEROCHANProvingKey, MONEROCHANVerifyingKey)> {
+        let span = tracing::info_span!("setup", vk_hash = tracing::field::Empty).entered();
+        let (pk, _, _, vk) = self.prover.setup(elf).map_err(|err| anyhow!("{err:#}"))?;
+        span.record("vk_hash", vk.bytes32());
+        Ok((pk, vk))
     }
 
     fn inner(&self) -> &MONEROCHANProver<CpuProverComponents> {
@@ -235,15 +295,28 @@ impl Prover<CpuProverComponents> for CpuProver {
     ) -> Result<(), MONEROCHANVerificationError> {
         if self.mock {
             tracing::warn!("using mock verifier");
-            return Self::mock_verify(bundle, vkey);
+            return self.mock_verify(bundle, vkey);
+        }
+        verify_proof(self.inner(), self.version(), bundle, vkey, None)
+    }
+
+    fn verify_with_fri_parameters(
+        &self,
+        bundle: &MONEROCHANProofWithPublicValues,
+        vkey: &MONEROCHANVerifyingKey,
+        fri_parameters: FriParameters,
+    ) -> Result<(), MONEROCHANVerificationError> {
+        if self.mock {
+            tracing::warn!("using mock verifier");
+            return self.mock_verify(bundle, vkey);
         }
-        verify_proof(self.inner(), self.version(), bundle, vkey)
+        verify_proof(self.inner(), self.version(), bundle, vkey, Some(fri_parameters))
     }
 }
 
 impl Default for CpuProver {
     fn default() -> Self {
         let prover = MONEROCHANProver::new();
-        Self { prover, mock: false }
+        Self { prover, mock: false, mock_seed: 0, moongate_compress: None, wrap_workers: None }
     }
 }
@@ -2,14 +2,14 @@
 //!
 //! This module provides a builder for proving a program on the CPU.
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use monerochan_core_executor::{IoWriter, MONEROCHANContextBuilder};
 use monerochan_core_machine::io::MONEROCHANStdin;
 use monerochan_prover::MONEROCHANProvingKey;
 use monerochan_stark::{MONEROCHANCoreOpts, MONEROCHANProverOpts};
 
-use super::CpuProver;
-use crate::{MONEROCHANProofMode, MONEROCHANProofWithPublicValues};
+use super::{stages::MoongateCompress, CpuProver};
+use crate::{MONEROCHANProofMode, MONEROCHANProofWithPublicValues, ProveConfig};
 
 /// A builder for proving a program on the CPU.
 ///
@@ -144,6 +144,35 @@ impl<'a> CpuProveBuilder<'a> {
         self
     }
 
+    /// Apply a [`ProveConfig`] to the proof.
+    ///
+    /// # Details
+    /// Sets the mode and the cycle limit to those of the config which are set. The fields specific
+    /// to the network are ignored. The methods called after this one override the config.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{Prover, ProverClient, ProveConfig, MONEROCHANStdin};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let config = ProveConfig::load("prove-config.json").unwrap();
+    /// let client = ProverClient::builder().cpu().build();
+    /// let (pk, vk) = client.setup(elf);
+    /// let proof = client.prove(&pk, &stdin).with_config(&config).run();
+    /// ```
+    #[must_use]
+    pub fn with_config(mut self, config: &ProveConfig) -> Self {
+        if let Some(mode) = config.mode {
+            self.mode = mode;
+        }
+        if let Some(cycle_limit) = config.cycle_limit {
+            self.context_builder.max_cycles(cycle_limit);
+        }
+        self
+    }
+
     /// Set the shard size for proving.
     ///
     /// # Details
@@ -193,6 +222,96 @@ impl<'a> CpuProveBuilder<'a> {
         self
     }
 
+    /// Pin the prover threads to a NUMA node.
+    ///
+    /// # Details
+    /// On machines with several sockets, a thread reading memory of another NUMA node runs at a
+    /// fraction of the throughput. The core prover then generates and proves the traces in a
+    /// dedicated pool of threads pinned to the CPUs of the node, which keeps the traces in the
+    /// memory of that node, while the other threads of the process keep running on any CPU. Can
+    /// also be set with the `NUMA_NODE` environment variable.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{include_elf, Prover, ProverClient, MONEROCHANStdin};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let client = ProverClient::builder().cpu().build();
+    /// let (pk, vk) = client.setup(elf);
+    /// let builder = client.prove(&pk, &stdin).numa_node(1).run();
+    /// ```
+    #[must_use]
+    pub fn numa_node(mut self, node: usize) -> Self {
+        self.core_opts.numa_node = Some(node);
+        self.recursion_opts.numa_node = Some(node);
+        self
+    }
+
+    /// Set the number of FRI queries of the proof.
+    ///
+    /// # Details
+    /// Fewer queries give a smaller proof that is faster to verify, at the cost of a lower
+    /// security level, which must stay above [`monerochan_stark::MIN_CONJECTURED_SECURITY_BITS`]
+    /// bits. The security level of a proof is reported by
+    /// [`MONEROCHANProofWithPublicValues::fri_parameters`]. The proof must be verified with
+    /// [`crate::Prover::verify_with_fri_parameters`] and these parameters, since
+    /// [`crate::Prover::verify`] only accepts the default ones.
+    ///
+    /// This is only supported for [`MONEROCHANProofMode::Core`] and
+    /// [`MONEROCHANProofMode::Compressed`] proofs, and proving in other modes will return an error.
+    /// Compressed proofs with a number of FRI queries are not supported when the compress stage
+    /// runs on a Moongate server.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{include_elf, Prover, ProverClient, MONEROCHANStdin};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let client = ProverClient::builder().cpu().build();
+    /// let (pk, vk) = client.setup(elf);
+    /// let builder = client.prove(&pk, &stdin).compressed().fri_queries(84).run();
+    /// ```
+    #[must_use]
+    pub fn fri_queries(mut self, value: usize) -> Self {
+        self.core_opts.fri_queries = Some(value);
+        self.recursion_opts.fri_queries = Some(value);
+        self
+    }
+
+    /// Set the seed to derive the randomness of the prover from.
+    ///
+    /// # Details
+    /// With a seed, proving the same program and inputs gives byte-identical proofs, for example
+    /// to compare the proofs of redundant provers or to cache them. The STARK provers have no
+    /// randomness, but they then search the FRI proof of work on a single thread so that they find
+    /// the same one, which makes proving slower. The seed itself derives the blinding of
+    /// [`MONEROCHANProofMode::Plonk`] and [`MONEROCHANProofMode::Groth16`] proofs.
+    ///
+    /// Seeded proofs are not supported when the compress stage runs on a Moongate server, unless
+    /// the proof is a [`MONEROCHANProofMode::Core`] proof.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{include_elf, Prover, ProverClient, MONEROCHANStdin};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let client = ProverClient::builder().cpu().build();
+    /// let (pk, vk) = client.setup(elf);
+    /// let builder = client.prove(&pk, &stdin).seed(42).run();
+    /// ```
+    #[must_use]
+    pub fn seed(mut self, value: u64) -> Self {
+        self.core_opts.seed = Some(value);
+        self.recursion_opts.seed = Some(value);
+        self
+    }
+
     /// Set the maximum number of cpu cycles to use for execution.
     ///
     /// # Details
@@ -240,7 +359,7 @@ impl<'a> CpuProveBuilder<'a> {
     /// ```
     #[must_use]
     pub fn deferred_proof_verification(mut self, value: bool) -> Self {
-        self.context_builder.set_deferred_proof_verification(value);
+        self.context_builder.deferred_proof_verification(value);
         self
     }
 
@@ -303,8 +422,36 @@ impl<'a> CpuProveBuilder<'a> {
     /// ```
     pub fn run(self) -> Result<MONEROCHANProofWithPublicValues> {
         // Get the arguments.
-        let Self { prover, mode, pk, stdin, mut context_builder, core_opts, recursion_opts, mock } =
-            self;
+        let Self {
+            prover,
+            mode,
+            pk,
+            stdin,
+            mut context_builder,
+            mut core_opts,
+            mut recursion_opts,
+            mock,
+        } = self;
+
+        // The number of FRI queries only applies to the last proof of the mode, since the proofs
+        // verified by the recursion programs must have the default number.
+        match mode {
+            MONEROCHANProofMode::Core => recursion_opts.fri_queries = None,
+            MONEROCHANProofMode::Compressed => core_opts.fri_queries = None,
+            MONEROCHANProofMode::Plonk | MONEROCHANProofMode::Groth16 => {
+                if core_opts.fri_queries.is_some() || recursion_opts.fri_queries.is_some() {
+                    bail!(
+                        "the number of FRI queries can only be set for core and compressed proofs"
+                    );
+                }
+            }
+        }
+
+        // Fail before the core proof if the Moongate server cannot honor the recursion options.
+        if prover.moongate_compress.is_some() && mode != MONEROCHANProofMode::Core {
+            MoongateCompress::check_opts(&recursion_opts)?;
+        }
+
         let opts = MONEROCHANProverOpts { core_opts, recursion_opts };
         let context = context_builder.build();
 
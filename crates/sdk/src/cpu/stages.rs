@@ -0,0 +1,343 @@
+//! # CPU Proving Stages
+//!
+//! The stages of proof generation after the core proof, exposed as separate operations so that
+//! they can run on different machines: the compress and shrink stages are cheap, while the wrap
+//! stage needs a lot of memory. Every intermediate proof can be saved and loaded.
+
+use std::{
+    fs::File,
+    path::Path,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use monerochan_core_machine::io::MONEROCHANStdin;
+use monerochan_cuda::{MoongateServer, MONEROCHANCudaProver};
+use monerochan_primitives::io::MONEROCHANPublicValues;
+use monerochan_prover::{
+    InnerSC, MONEROCHANCoreProof, MONEROCHANCoreProofData, MONEROCHANVerifyingKey, OuterSC,
+    WrapSystem, WrapWorker,
+};
+use monerochan_stark::{MONEROCHANCoreOpts, MONEROCHANProverOpts, MONEROCHANReduceProof};
+use serde::{Deserialize, Serialize};
+
+use super::CpuProver;
+use crate::{
+    install::try_install_circuit_artifacts, MONEROCHANProof, MONEROCHANProofMode,
+    MONEROCHANProofWithPublicValues,
+};
+
+/// The long-lived gnark processes of a [`CpuProver`], one per proof system.
+pub(crate) struct WrapWorkers {
+    pub(crate) plonk: WrapWorker,
+    pub(crate) groth16: WrapWorker,
+}
+
+impl WrapWorkers {
+    pub(crate) fn new() -> Self {
+        Self {
+            plonk: WrapWorker::new(WrapSystem::Plonk),
+            groth16: WrapWorker::new(WrapSystem::Groth16),
+        }
+    }
+}
+
+/// The Moongate server the compress stage of a [`CpuProver`] is delegated to.
+///
+/// The server is started, or connected to, on the first compressed proof, so that a server that
+/// cannot be reached fails that proof rather than the construction of the prover.
+pub(crate) struct MoongateCompress {
+    server: MoongateServer,
+    prover: Mutex<Option<Arc<MONEROCHANCudaProver>>>,
+}
+
+impl MoongateCompress {
+    pub(crate) fn new(server: MoongateServer) -> Self {
+        Self { server, prover: Mutex::new(None) }
+    }
+
+    /// Checks that the server can compress with the recursion options of a proof.
+    ///
+    /// The server compresses with its own options, so it cannot honor a seed nor a number of FRI
+    /// queries. The other options only tune the resources of the prover that runs the stage.
+    pub(crate) fn check_opts(opts: &MONEROCHANCoreOpts) -> Result<()> {
+        if opts.seed.is_some() {
+            bail!(
+                "seeded proofs are not supported when the compress stage runs on a Moongate server"
+            );
+        }
+        if opts.fri_queries.is_some() {
+            bail!(
+                "the number of FRI queries cannot be set when the compress stage runs on a \
+                 Moongate server"
+            );
+        }
+        Ok(())
+    }
+
+    /// Compresses a core proof on the server.
+    pub(crate) fn compress(
+        &self,
+        vk: &MONEROCHANVerifyingKey,
+        proof: MONEROCHANCoreProof,
+        deferred_proofs: Vec<MONEROCHANReduceProof<InnerSC>>,
+        opts: &MONEROCHANCoreOpts,
+    ) -> Result<MONEROCHANReduceProof<InnerSC>> {
+        Self::check_opts(opts)?;
+        Ok(self.prover()?.compress(vk, proof, deferred_proofs)?)
+    }
+
+    /// Returns the client of the server, starting or connecting to it on the first call.
+    fn prover(&self) -> Result<Arc<MONEROCHANCudaProver>> {
+        let mut prover = self.prover.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(prover) = prover.as_ref() {
+            return Ok(Arc::clone(prover));
+        }
+        let started = MONEROCHANCudaProver::new(self.server.clone())
+            .map_err(|err| anyhow!("failed to start the Moongate server: {err}"))?;
+        Ok(Arc::clone(prover.insert(Arc::new(started))))
+    }
+}
+
+/// The SNARK a shrunk proof is wrapped into by [`CpuProver::wrap_bn254`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WrapMode {
+    /// A Plonk proof, see [`MONEROCHANProofMode::Plonk`].
+    Plonk,
+    /// A Groth16 proof, see [`MONEROCHANProofMode::Groth16`].
+    Groth16,
+}
+
+impl From<WrapMode> for MONEROCHANProofMode {
+    fn from(mode: WrapMode) -> Self {
+        match mode {
+            WrapMode::Plonk => MONEROCHANProofMode::Plonk,
+            WrapMode::Groth16 => MONEROCHANProofMode::Groth16,
+        }
+    }
+}
+
+/// A compressed proof shrunk for the wrap stage, as returned by [`CpuProver::shrink`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MONEROCHANShrunkProof {
+    /// The shrunk proof.
+    pub proof: MONEROCHANReduceProof<InnerSC>,
+    /// The public values of the program.
+    pub public_values: MONEROCHANPublicValues,
+    /// The version of the MONEROCHAN RISC-V zkVM the proof was generated with.
+    pub monerochan_version: String,
+}
+
+impl MONEROCHANShrunkProof {
+    /// Saves the proof to a path.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        bincode::serialize_into(
+            File::create(path.as_ref()).with_context(|| {
+                format!("failed to create file for saving proof: {}", path.as_ref().display())
+            })?,
+            self,
+        )
+        .map_err(Into::into)
+    }
+
+    /// Loads a proof from a path.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        bincode::deserialize_from(File::open(path.as_ref()).with_context(|| {
+            format!("failed to open file for loading proof: {}", path.as_ref().display())
+        })?)
+        .map_err(Into::into)
+    }
+}
+
+impl CpuProver {
+    /// Compresses a core proof into a proof of constant size.
+    ///
+    /// # Details
+    /// This is the compress stage of [`MONEROCHANProofMode::Compressed`] proofs. If the program
+    /// verifies proofs, use [`Self::compress_with_deferred_proofs`] instead.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{Prover, ProverClient, MONEROCHANStdin};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let client = ProverClient::builder().cpu().build();
+    /// let (pk, vk) = client.setup(elf);
+    /// let core_proof = client.prove(&pk, &stdin).core().run().unwrap();
+    /// let compressed = client.compress(&vk, core_proof).unwrap();
+    /// compressed.save("compressed.bin").unwrap();
+    /// ```
+    pub fn compress(
+        &self,
+        vk: &MONEROCHANVerifyingKey,
+        core_proof: MONEROCHANProofWithPublicValues,
+    ) -> Result<MONEROCHANProofWithPublicValues> {
+        self.compress_with_deferred_proofs(vk, core_proof, &MONEROCHANStdin::new())
+    }
+
+    /// Compresses a core proof into a proof of constant size, verifying the proofs written to the
+    /// stdin of the program with [`MONEROCHANStdin::write_proof`].
+    pub fn compress_with_deferred_proofs(
+        &self,
+        vk: &MONEROCHANVerifyingKey,
+        core_proof: MONEROCHANProofWithPublicValues,
+        stdin: &MONEROCHANStdin,
+    ) -> Result<MONEROCHANProofWithPublicValues> {
+        self.ensure_not_mock()?;
+        let MONEROCHANProofWithPublicValues {
+            proof: MONEROCHANProof::Core(shard_proofs),
+            public_values,
+            monerochan_version,
+            ..
+        } = core_proof
+        else {
+            bail!(
+                "expected a core proof, got a {:?} proof",
+                MONEROCHANProofMode::from(&core_proof.proof)
+            );
+        };
+
+        let proof = MONEROCHANCoreProof {
+            proof: MONEROCHANCoreProofData(shard_proofs),
+            stdin: stdin.clone(),
+            public_values: public_values.clone(),
+            cycles: 0,
+        };
+        let deferred_proofs =
+            stdin.proofs.iter().map(|(reduce_proof, _)| reduce_proof.clone()).collect();
+        let reduce_proof =
+            self.compress_core(vk, proof, deferred_proofs, MONEROCHANProverOpts::default())?;
+
+        Ok(MONEROCHANProofWithPublicValues::new(
+            MONEROCHANProof::Compressed(Box::new(reduce_proof)),
+            public_values,
+            monerochan_version,
+        ))
+    }
+
+    /// Shrinks a compressed proof for the wrap stage.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{MONEROCHANProofWithPublicValues, ProverClient};
+    ///
+    /// let client = ProverClient::builder().cpu().build();
+    /// let compressed = MONEROCHANProofWithPublicValues::load("compressed.bin").unwrap();
+    /// let shrunk = client.shrink(compressed).unwrap();
+    /// shrunk.save("shrunk.bin").unwrap();
+    /// ```
+    pub fn shrink(
+        &self,
+        compressed_proof: MONEROCHANProofWithPublicValues,
+    ) -> Result<MONEROCHANShrunkProof> {
+        self.ensure_not_mock()?;
+        let MONEROCHANProofWithPublicValues {
+            proof: MONEROCHANProof::Compressed(reduce_proof),
+            public_values,
+            monerochan_version,
+            ..
+        } = compressed_proof
+        else {
+            bail!(
+                "expected a compressed proof, got a {:?} proof",
+                MONEROCHANProofMode::from(&compressed_proof.proof)
+            );
+        };
+
+        let proof = tracing::info_span!("shrink").in_scope(|| {
+            self.prover.shrink(*reduce_proof, MONEROCHANProverOpts::default())
+        })?;
+        Ok(MONEROCHANShrunkProof { proof, public_values, monerochan_version })
+    }
+
+    /// Wraps a shrunk proof into a Plonk or Groth16 proof that can be verified on chain.
+    ///
+    /// # Details
+    /// This is the memory heavy stage of [`MONEROCHANProofMode::Plonk`] and
+    /// [`MONEROCHANProofMode::Groth16`] proofs. The circuit artifacts are downloaded if they are
+    /// not installed.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{cpu::{MONEROCHANShrunkProof, WrapMode}, ProverClient};
+    ///
+    /// let client = ProverClient::builder().cpu().build();
+    /// let shrunk = MONEROCHANShrunkProof::load("shrunk.bin").unwrap();
+    /// let proof = client.wrap_bn254(shrunk, WrapMode::Groth16).unwrap();
+    /// ```
+    pub fn wrap_bn254(
+        &self,
+        shrunk_proof: MONEROCHANShrunkProof,
+        mode: WrapMode,
+    ) -> Result<MONEROCHANProofWithPublicValues> {
+        self.ensure_not_mock()?;
+        let MONEROCHANShrunkProof { proof, public_values, monerochan_version } = shrunk_proof;
+        let outer_proof = tracing::info_span!("wrap").in_scope(|| {
+            self.prover.wrap_bn254(proof, MONEROCHANProverOpts::default())
+        })?;
+        Ok(MONEROCHANProofWithPublicValues::new(
+            self.wrap_outer(outer_proof, mode, None),
+            public_values,
+            monerochan_version,
+        ))
+    }
+
+    /// Wraps a proof over the SNARK-friendly field into a Plonk or Groth16 proof, with the wrap
+    /// workers if the prover was built with [`super::builder::CpuProverBuilder::wrap_worker`], and
+    /// with the randomness of the proof derived from `seed` if set.
+    pub(crate) fn wrap_outer(
+        &self,
+        outer_proof: MONEROCHANReduceProof<OuterSC>,
+        mode: WrapMode,
+        seed: Option<u64>,
+    ) -> MONEROCHANProof {
+        match mode {
+            WrapMode::Groth16 => {
+                let groth16_bn254_artifacts = if self.prover.dev_mode() {
+                    monerochan_prover::build::try_build_groth16_bn254_artifacts_dev(
+                        &outer_proof.vk,
+                        &outer_proof.proof,
+                    )
+                } else {
+                    try_install_circuit_artifacts("groth16")
+                };
+                MONEROCHANProof::Groth16(tracing::info_span!("wrap_groth16").in_scope(|| {
+                    self.prover.wrap_groth16_bn254_seeded(
+                        outer_proof,
+                        &groth16_bn254_artifacts,
+                        self.wrap_workers.as_ref().map(|workers| &workers.groth16),
+                        seed,
+                    )
+                }))
+            }
+            WrapMode::Plonk => {
+                let plonk_bn254_artifacts = if self.prover.dev_mode() {
+                    monerochan_prover::build::try_build_plonk_bn254_artifacts_dev(
+                        &outer_proof.vk,
+                        &outer_proof.proof,
+                    )
+                } else {
+                    try_install_circuit_artifacts("plonk")
+                };
+                MONEROCHANProof::Plonk(tracing::info_span!("wrap_plonk").in_scope(|| {
+                    self.prover.wrap_plonk_bn254_seeded(
+                        outer_proof,
+                        &plonk_bn254_artifacts,
+                        self.wrap_workers.as_ref().map(|workers| &workers.plonk),
+                        seed,
+                    )
+                }))
+            }
+        }
+    }
+
+    /// The stages need real proofs, which a mock prover does not generate.
+    fn ensure_not_mock(&self) -> Result<()> {
+        if self.mock {
+            bail!("the proving stages are not supported by a mock prover, use `prove` instead");
+        }
+        Ok(())
+    }
+}
@@ -5,15 +5,17 @@
 pub mod builder;
 pub mod prove;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use prove::CudaProveBuilder;
 use monerochan_core_executor::MONEROCHANContextBuilder;
 use monerochan_core_machine::io::MONEROCHANStdin;
 use monerochan_cuda::{MoongateServer, MONEROCHANCudaProver};
-use monerochan_prover::{components::CpuProverComponents, MONEROCHANProver};
+use monerochan_prover::{components::CpuProverComponents, HashableKey, MONEROCHANProver};
+use monerochan_stark::MONEROCHANCoreOpts;
 
 use crate::{
-    cpu::execute::CpuExecuteBuilder, install::try_install_circuit_artifacts, Prover, MONEROCHANProof,
+    cpu::execute::CpuExecuteBuilder, install::try_install_circuit_artifacts, Prover,
+    MONEROCHANProof,
     MONEROCHANProofMode, MONEROCHANProofWithPublicValues, MONEROCHANProvingKey, MONEROCHANVerifyingKey,
 };
 
@@ -55,6 +57,8 @@ impl CudaProver {
             elf,
             stdin: stdin.clone(),
             context_builder: MONEROCHANContextBuilder::default(),
+            core_opts: MONEROCHANCoreOpts::default(),
+            schema: None,
         }
     }
 
@@ -87,10 +91,20 @@ impl CudaProver {
         stdin: &MONEROCHANStdin,
         kind: MONEROCHANProofMode,
     ) -> Result<(MONEROCHANProofWithPublicValues, u64)> {
+        let span = tracing::info_span!(
+            "prove",
+            mode = ?kind,
+            vk_hash = %pk.vk.bytes32(),
+            cycles = tracing::field::Empty
+        )
+        .entered();
+
         // Generate the core proof.
-        let proof = self.cuda_prover.prove_core_stateless(pk, stdin)?;
+        let proof = tracing::info_span!("prove_core")
+            .in_scope(|| self.cuda_prover.prove_core_stateless(pk, stdin))?;
         // TODO: Return the prover gas
         let cycles = proof.cycles;
+        span.record("cycles", cycles);
         if kind == MONEROCHANProofMode::Core {
             let proof_with_pv = MONEROCHANProofWithPublicValues::new(
                 MONEROCHANProof::Core(proof.proof.0),
@@ -121,7 +135,7 @@ impl CudaProver {
         let outer_proof = self.cuda_prover.wrap_bn254(compress_proof)?;
 
         if kind == MONEROCHANProofMode::Plonk {
-            let plonk_bn254_artifacts = if monerochan_prover::build::monerochan_dev_mode() {
+            let plonk_bn254_artifacts = if self.cpu_prover.dev_mode() {
                 monerochan_prover::build::try_build_plonk_bn254_artifacts_dev(
                     &outer_proof.vk,
                     &outer_proof.proof,
@@ -137,7 +151,7 @@ impl CudaProver {
             );
             return Ok((proof_with_pv, cycles));
         } else if kind == MONEROCHANProofMode::Groth16 {
-            let groth16_bn254_artifacts = if monerochan_prover::build::monerochan_dev_mode() {
+            let groth16_bn254_artifacts = if self.cpu_prover.dev_mode() {
                 monerochan_prover::build::try_build_groth16_bn254_artifacts_dev(
                     &outer_proof.vk,
                     &outer_proof.proof,
@@ -161,8 +175,13 @@ impl CudaProver {
 
 impl Prover<CpuProverComponents> for CudaProver {
     fn setup(&self, elf: &[u8]) -> (MONEROCHANProvingKey, MONEROCHANVerifyingKey) {
-        let (pk, vk) = self.cuda_prover.setup(elf).unwrap();
-        (pk, vk)
+        self.try_setup(elf).unwrap_or_else(|err| panic!("{err:#}"))
+    }
+
+    fn try_setup(&self, elf: &[u8]) -> Result<(MONEROCHANProvingKey, MONEROCHANVerifyingKey)> {
+        // Check that the program fits before sending it to the container.
+        self.cpu_prover.get_program(elf).map_err(|err| anyhow!("{err:#}"))?;
+        self.cuda_prover.setup(elf).map_err(|err| anyhow!("failed to set up the program: {err}"))
     }
 
     fn inner(&self) -> &MONEROCHANProver<CpuProverComponents> {
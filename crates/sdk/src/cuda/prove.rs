@@ -7,7 +7,7 @@ use monerochan_core_machine::io::MONEROCHANStdin;
 use monerochan_prover::{components::CpuProverComponents, MONEROCHANProvingKey};
 
 use super::CudaProver;
-use crate::{Prover, MONEROCHANProofMode, MONEROCHANProofWithPublicValues};
+use crate::{ProveConfig, Prover, MONEROCHANProofMode, MONEROCHANProofWithPublicValues};
 
 /// A builder for proving a program on the CUDA.
 ///
@@ -138,6 +138,32 @@ impl CudaProveBuilder<'_> {
         self
     }
 
+    /// Apply a [`ProveConfig`] to the proof.
+    ///
+    /// # Details
+    /// Sets the mode to that of the config, if it is set. The other fields are ignored. The methods
+    /// called after this one override the config.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{Prover, ProverClient, ProveConfig, MONEROCHANStdin};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let config = ProveConfig::load("prove-config.json").unwrap();
+    /// let client = ProverClient::builder().cuda().build();
+    /// let (pk, vk) = client.setup(elf);
+    /// let proof = client.prove(&pk, &stdin).with_config(&config).run();
+    /// ```
+    #[must_use]
+    pub fn with_config(mut self, config: &ProveConfig) -> Self {
+        if let Some(mode) = config.mode {
+            self.mode = mode;
+        }
+        self
+    }
+
     /// Run the prover with the built arguments.
     ///
     /// # Details
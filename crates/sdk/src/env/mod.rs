@@ -5,7 +5,7 @@
 
 pub mod prove;
 
-use std::env;
+use std::{env, sync::Arc};
 
 use anyhow::Result;
 use prove::EnvProveBuilder;
@@ -13,8 +13,9 @@ use monerochan_core_executor::MONEROCHANContextBuilder;
 use monerochan_core_machine::io::MONEROCHANStdin;
 use monerochan_cuda::MoongateServer;
 use monerochan_prover::{components::CpuProverComponents, MONEROCHANProver, MONEROCHANProvingKey, MONEROCHANVerifyingKey};
+use monerochan_stark::MONEROCHANCoreOpts;
 
-use super::{Prover, MONEROCHANVerificationError};
+use super::{AsyncProver, Prover, MONEROCHANVerificationError};
 #[cfg(feature = "network")]
 use crate::network::builder::NetworkProverBuilder;
 use crate::{
@@ -24,62 +25,149 @@ use crate::{
     MONEROCHANProofMode, MONEROCHANProofWithPublicValues,
 };
 
+/// An error that occurs when the environment does not describe a valid prover configuration.
+#[derive(Debug, thiserror::Error)]
+pub enum EnvProverError {
+    /// `MONEROCHAN_PROVER` is not one of the supported provers.
+    #[error("invalid MONEROCHAN_PROVER value '{0}', expected one of: mock, cpu, cuda, or network")]
+    InvalidProver(String),
+    /// The network prover was selected, but the SDK was built without the `network` feature.
+    #[error(
+        "MONEROCHAN_PROVER=network requires the 'network' feature, enable it in your Cargo.toml \
+         with: monerochan = {{ version = \"...\", features = [\"network\"] }}"
+    )]
+    NetworkFeatureDisabled,
+    /// An environment variable has an invalid value.
+    #[error("invalid {name} value '{value}': {reason}")]
+    InvalidVar {
+        /// The name of the environment variable.
+        name: &'static str,
+        /// The value of the environment variable.
+        value: String,
+        /// Why the value is invalid.
+        reason: String,
+    },
+}
+
 /// A prover that can execute programs and generate proofs with a different implementation based on
 /// the value of certain environment variables.
 ///
-/// The environment variables are described in [`EnvProver::new`].
+/// The environment variables are described in [`EnvProver::try_new`].
 pub struct EnvProver {
-    pub(crate) prover: Box<dyn Prover<CpuProverComponents>>,
+    pub(crate) prover: Arc<dyn Prover<CpuProverComponents>>,
+    /// The prover, if it implements [`AsyncProver`], i.e. if it proves on a remote service.
+    pub(crate) async_prover: Option<Arc<dyn AsyncProver<CpuProverComponents>>>,
+    pub(crate) cycle_limit: Option<u64>,
 }
 
 impl EnvProver {
     /// Creates a new [`EnvProver`] with the given configuration.
     ///
-    /// The following environment variables are used to configure the prover:
-    /// - `MONEROCHAN_PROVER`: The type of prover to use. Must be one of `mock`, `local`, `cuda`, or
-    ///   `network`.
-    /// - `MONEROCHAN_NETWORK_PRIVATE_KEY`: The Solana private key (hex or base58) for client authentication when using network prover.
-    /// - `NETWORK_RPC_URL`: The RPC URL to use for the network prover (defaults to monerochan production network).
+    /// The environment variables are described in [`EnvProver::try_new`].
+    ///
+    /// # Panics
+    /// Panics if the environment variables are invalid.
     #[must_use]
     pub fn new() -> Self {
+        Self::try_new().unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Checks the environment variables described in [`EnvProver::try_new`], without creating
+    /// the prover.
+    ///
+    /// # Errors
+    /// Returns an [`EnvProverError`] for the first invalid environment variable.
+    pub fn validate_env() -> Result<(), EnvProverError> {
+        let mode = env::var("MONEROCHAN_PROVER").unwrap_or_else(|_| "cpu".to_string());
+        parse_var::<u64>("MONEROCHAN_CYCLE_LIMIT")?;
+
+        match mode.as_str() {
+            "mock" => {
+                parse_var::<u64>("MONEROCHAN_MOCK_SEED")?;
+            }
+            "cpu" | "cuda" => {}
+            "network" => {
+                #[cfg(not(feature = "network"))]
+                return Err(EnvProverError::NetworkFeatureDisabled);
+
+                #[cfg(feature = "network")]
+                validate_network_env()?;
+            }
+            _ => return Err(EnvProverError::InvalidProver(mode)),
+        }
+
+        Ok(())
+    }
+
+    /// Creates a new [`EnvProver`] with the given configuration, validating the environment.
+    ///
+    /// The following environment variables are used to configure the prover:
+    /// - `MONEROCHAN_PROVER`: The type of prover to use. Must be one of `mock`, `cpu`, `cuda`, or
+    ///   `network`. Defaults to `cpu`.
+    /// - `MONEROCHAN_CYCLE_LIMIT`: The maximum number of cycles a program may execute for, with any
+    ///   prover. Programs are executed with the limit before proving, so that a runaway program
+    ///   fails fast.
+    /// - `MONEROCHAN_MOCK_SEED`: The seed of the `mock` prover, see
+    ///   [`crate::cpu::builder::CpuProverBuilder::mock_seed`].
+    /// - `MONEROCHAN_NETWORK_PRIVATE_KEY` (or `BASE_PRIVATE_KEY`): The Solana private key (hex or
+    ///   base58) for client authentication when using the `network` prover.
+    /// - `NETWORK_RPC_URL`: The RPC URL to use for the `network` prover (defaults to monerochan
+    ///   production network).
+    ///
+    /// # Errors
+    /// Returns an [`EnvProverError`] if one of the environment variables is invalid, or if the
+    /// `network` prover is selected without the `network` feature.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::ProverClient;
+    ///
+    /// std::env::set_var("MONEROCHAN_PROVER", "mock");
+    /// std::env::set_var("MONEROCHAN_CYCLE_LIMIT", "100000000");
+    /// let prover = ProverClient::try_from_env().unwrap();
+    /// ```
+    pub fn try_new() -> Result<Self, EnvProverError> {
         let mode = if let Ok(mode) = env::var("MONEROCHAN_PROVER") {
             mode
         } else {
             tracing::warn!("MONEROCHAN_PROVER environment variable not set, defaulting to 'cpu'");
             "cpu".to_string()
         };
+        let cycle_limit = parse_var::<u64>("MONEROCHAN_CYCLE_LIMIT")?;
 
-        let prover: Box<dyn Prover<CpuProverComponents>> = match mode.as_str() {
-            "mock" => Box::new(CpuProver::mock()),
+        let prover: Arc<dyn Prover<CpuProverComponents>> = match mode.as_str() {
+            "mock" => {
+                let seed = parse_var::<u64>("MONEROCHAN_MOCK_SEED")?.unwrap_or_default();
+                Arc::new(CpuProver::mock_with_seed(seed))
+            }
             "cpu" => {
                 check_release_build();
                 setup_memory_usage_monitoring();
-                Box::new(CpuProver::new())
-            },
+                Arc::new(CpuProver::new())
+            }
             "cuda" => {
                 check_release_build();
                 setup_memory_usage_monitoring();
-                Box::new(CudaProver::new(MONEROCHANProver::new(), MoongateServer::default()))
+                Arc::new(CudaProver::new(MONEROCHANProver::new(), MoongateServer::default()))
             }
             "network" => {
                 #[cfg(not(feature = "network"))]
-                panic!(
-                    r#"The network prover requires the 'network' feature to be enabled.
-                    Please enable it in your Cargo.toml with:
-                    monerochan = {{ version = "...", features = ["network"] }}"#
-                );
+                return Err(EnvProverError::NetworkFeatureDisabled);
 
                 #[cfg(feature = "network")]
                 {
-                    Box::new(NetworkProverBuilder::default().build())
+                    validate_network_env()?;
+                    let prover = Arc::new(NetworkProverBuilder::default().build());
+                    return Ok(EnvProver {
+                        prover: prover.clone(),
+                        async_prover: Some(prover),
+                        cycle_limit,
+                    });
                 }
             }
-            _ => panic!(
-                "Invalid MONEROCHAN_PROVER value. Expected one of: mock, cpu, cuda, or network. Got: '{mode}'.\n\
-                Please set the MONEROCHAN_PROVER environment variable to one of the supported values."
-            ),
+            _ => return Err(EnvProverError::InvalidProver(mode)),
         };
-        EnvProver { prover }
+        Ok(EnvProver { prover, async_prover: None, cycle_limit })
     }
 
     /// Creates a new [`CpuExecuteBuilder`] for simulating the execution of a program on the CPU.
@@ -100,11 +188,17 @@ impl EnvProver {
     /// ```
     #[must_use]
     pub fn execute<'a>(&'a self, elf: &'a [u8], stdin: &MONEROCHANStdin) -> CpuExecuteBuilder<'a> {
+        let mut context_builder = MONEROCHANContextBuilder::default();
+        if let Some(cycle_limit) = self.cycle_limit {
+            context_builder.max_cycles(cycle_limit);
+        }
         CpuExecuteBuilder {
             prover: self.prover.inner(),
             elf,
             stdin: stdin.clone(),
-            context_builder: MONEROCHANContextBuilder::default(),
+            context_builder,
+            core_opts: MONEROCHANCoreOpts::default(),
+            schema: None,
         }
     }
 
@@ -127,10 +221,12 @@ impl EnvProver {
     #[must_use]
     pub fn prove<'a>(&'a self, pk: &'a MONEROCHANProvingKey, stdin: &'a MONEROCHANStdin) -> EnvProveBuilder<'a> {
         EnvProveBuilder {
-            prover: self.prover.as_ref(),
+            prover: self.prover.clone(),
+            async_prover: self.async_prover.clone(),
             mode: MONEROCHANProofMode::Core,
             pk,
             stdin: stdin.clone(),
+            cycle_limit: self.cycle_limit,
         }
     }
 
@@ -157,12 +253,32 @@ impl EnvProver {
         self.prover.verify(proof, vk)
     }
 
+    /// Verifies that the given proof, generated with the given circuit version, is valid and
+    /// matches the given verification key.
+    ///
+    /// See [`Prover::verify_with_version`] for the supported versions.
+    #[cfg(feature = "legacy-verifiers")]
+    pub fn verify_with_version(
+        &self,
+        proof: &MONEROCHANProofWithPublicValues,
+        vk: &MONEROCHANVerifyingKey,
+        version: &str,
+    ) -> Result<(), MONEROCHANVerificationError> {
+        self.prover.verify_with_version(proof, vk, version)
+    }
+
     /// Setup a program to be proven and verified by the MONEROCHAN RISC-V zkVM by computing the proving
     /// and verifying keys.
     #[must_use]
     pub fn setup(&self, elf: &[u8]) -> (MONEROCHANProvingKey, MONEROCHANVerifyingKey) {
         self.prover.setup(elf)
     }
+
+    /// Like [`Self::setup`], but fails rather than panics if the ELF is invalid or if the program
+    /// does not fit the prover, e.g. if it is too large.
+    pub fn try_setup(&self, elf: &[u8]) -> Result<(MONEROCHANProvingKey, MONEROCHANVerifyingKey)> {
+        self.prover.try_setup(elf)
+    }
 }
 
 impl Default for EnvProver {
@@ -180,6 +296,10 @@ impl Prover<CpuProverComponents> for EnvProver {
         self.prover.setup(elf)
     }
 
+    fn try_setup(&self, elf: &[u8]) -> Result<(MONEROCHANProvingKey, MONEROCHANVerifyingKey)> {
+        self.prover.try_setup(elf)
+    }
+
     fn prove(
         &self,
         pk: &MONEROCHANProvingKey,
@@ -197,3 +317,69 @@ impl Prover<CpuProverComponents> for EnvProver {
         self.prover.verify(bundle, vkey)
     }
 }
+
+/// Parses an optional environment variable.
+fn parse_var<T>(name: &'static str) -> Result<Option<T>, EnvProverError>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    let Ok(value) = env::var(name) else {
+        return Ok(None);
+    };
+    value.trim().replace('_', "").parse().map(Some).map_err(|err: T::Err| {
+        EnvProverError::InvalidVar { name, value, reason: err.to_string() }
+    })
+}
+
+/// Checks the environment variables of the network prover.
+#[cfg(feature = "network")]
+fn validate_network_env() -> Result<(), EnvProverError> {
+    if let Ok(value) = env::var("NETWORK_RPC_URL") {
+        if !value.starts_with("http://") && !value.starts_with("https://") {
+            return Err(EnvProverError::InvalidVar {
+                name: "NETWORK_RPC_URL",
+                value,
+                reason: "expected an http:// or https:// URL".to_string(),
+            });
+        }
+    }
+
+    for name in ["MONEROCHAN_NETWORK_PRIVATE_KEY", "BASE_PRIVATE_KEY"] {
+        let Ok(value) = env::var(name) else {
+            continue;
+        };
+        if value.is_empty() {
+            continue;
+        }
+        if crate::network::prover::decode_private_key(&value).map_or(true, |key| key.len() != 32)
+        {
+            return Err(EnvProverError::InvalidVar {
+                name,
+                // Never echo the key back.
+                value: "<redacted>".to_string(),
+                reason: "expected a 32 byte key in hex or base58".to_string(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_var() {
+        env::set_var("MONEROCHAN_TEST_PARSE_VAR", "1_000_000");
+        assert_eq!(parse_var::<u64>("MONEROCHAN_TEST_PARSE_VAR").unwrap(), Some(1_000_000));
+
+        env::set_var("MONEROCHAN_TEST_PARSE_VAR", "lots");
+        let err = parse_var::<u64>("MONEROCHAN_TEST_PARSE_VAR").unwrap_err();
+        assert!(err.to_string().starts_with("invalid MONEROCHAN_TEST_PARSE_VAR value 'lots'"));
+
+        env::remove_var("MONEROCHAN_TEST_PARSE_VAR");
+        assert_eq!(parse_var::<u64>("MONEROCHAN_TEST_PARSE_VAR").unwrap(), None);
+    }
+}
@@ -2,22 +2,34 @@
 //!
 //! This module provides a builder for proving a program.
 
+use std::{
+    future::{Future, IntoFuture},
+    pin::Pin,
+    sync::Arc,
+};
+
 use anyhow::Result;
+use monerochan_core_executor::MONEROCHANContextBuilder;
 use monerochan_core_machine::io::MONEROCHANStdin;
 use monerochan_prover::{components::CpuProverComponents, MONEROCHANProvingKey};
 
-use crate::{Prover, MONEROCHANProofMode, MONEROCHANProofWithPublicValues};
+use crate::{
+    utils::spawn_blocking, AsyncProver, ProveConfig, Prover, MONEROCHANProofMode,
+    MONEROCHANProofWithPublicValues,
+};
 
 /// Builder to prepare and configure proving execution of a program on an input.
-/// May be run with [`Self::run`].
+/// May be run with [`Self::run`], or awaited with [`Self::run_async`].
 pub struct EnvProveBuilder<'a> {
-    pub(crate) prover: &'a dyn Prover<CpuProverComponents>,
+    pub(crate) prover: Arc<dyn Prover<CpuProverComponents>>,
+    pub(crate) async_prover: Option<Arc<dyn AsyncProver<CpuProverComponents>>>,
     pub(crate) mode: MONEROCHANProofMode,
     pub(crate) pk: &'a MONEROCHANProvingKey,
     pub(crate) stdin: MONEROCHANStdin,
+    pub(crate) cycle_limit: Option<u64>,
 }
 
-impl EnvProveBuilder<'_> {
+impl<'a> EnvProveBuilder<'a> {
     /// Set the proof kind to [`MONEROCHANProofMode::Core`] mode.
     ///
     /// # Details
@@ -135,6 +147,35 @@ impl EnvProveBuilder<'_> {
         self
     }
 
+    /// Apply a [`ProveConfig`] to the proof.
+    ///
+    /// # Details
+    /// Sets the mode and the cycle limit to those of the config which are set. The fields specific
+    /// to the network are ignored. The methods called after this one override the config.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{Prover, ProverClient, ProveConfig, MONEROCHANStdin};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let config = ProveConfig::load("prove-config.json").unwrap();
+    /// let client = ProverClient::from_env();
+    /// let (pk, vk) = client.setup(elf);
+    /// let proof = client.prove(&pk, &stdin).with_config(&config).run();
+    /// ```
+    #[must_use]
+    pub fn with_config(mut self, config: &ProveConfig) -> Self {
+        if let Some(mode) = config.mode {
+            self.mode = mode;
+        }
+        if let Some(cycle_limit) = config.cycle_limit {
+            self.cycle_limit = Some(cycle_limit);
+        }
+        self
+    }
+
     /// Run the prover with the built arguments.
     ///
     /// # Details
@@ -153,11 +194,78 @@ impl EnvProveBuilder<'_> {
     /// let proof = client.prove(&pk, &stdin).run().unwrap();
     /// ```
     pub fn run(self) -> Result<MONEROCHANProofWithPublicValues> {
-        let Self { prover, mode: kind, pk, stdin } = self;
+        prepare(self.prover.as_ref(), self.pk, &self.stdin, self.cycle_limit)?;
+        self.prover.prove(self.pk, &self.stdin, self.mode)
+    }
+
+    /// Run the prover with the built arguments asynchronously.
+    ///
+    /// # Details
+    /// Unlike [`Self::run`], this method never blocks the async runtime, so it can be used from
+    /// inside any async runtime. The network prover proves asynchronously, while the program is
+    /// executed and proven on a dedicated thread otherwise, with a copy of the proving key. The
+    /// builder can also be awaited directly.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{Prover, ProverClient, MONEROCHANStdin};
+    ///
+    /// tokio_test::block_on(async {
+    ///     let elf = &[1, 2, 3];
+    ///     let stdin = MONEROCHANStdin::new();
+    ///
+    ///     let client = ProverClient::from_env();
+    ///     let (pk, vk) = client.setup(elf);
+    ///     let proof = client.prove(&pk, &stdin).await.unwrap();
+    /// });
+    /// ```
+    pub async fn run_async(self) -> Result<MONEROCHANProofWithPublicValues> {
+        let Self { prover, async_prover, mode, pk, stdin, cycle_limit } = self;
+        let pk = pk.clone();
+        match async_prover {
+            Some(async_prover) => {
+                let (pk, stdin) = spawn_blocking(move || {
+                    prepare(prover.as_ref(), &pk, &stdin, cycle_limit).map(|()| (pk, stdin))
+                })
+                .await?;
+                async_prover.prove_async(&pk, &stdin, mode).await
+            }
+            None => {
+                spawn_blocking(move || {
+                    prepare(prover.as_ref(), &pk, &stdin, cycle_limit)?;
+                    prover.prove(&pk, &stdin, mode)
+                })
+                .await
+            }
+        }
+    }
+}
+
+/// Dumps the program and checks the cycle limit before proving.
+fn prepare(
+    prover: &dyn Prover<CpuProverComponents>,
+    pk: &MONEROCHANProvingKey,
+    stdin: &MONEROCHANStdin,
+    cycle_limit: Option<u64>,
+) -> Result<()> {
+    // Dump the program and stdin to files for debugging if `MONEROCHAN_DUMP` is set.
+    crate::utils::monerochan_dump(&pk.elf, stdin);
+
+    // Enforce `MONEROCHAN_CYCLE_LIMIT` before proving, whichever prover is used.
+    if let Some(cycle_limit) = cycle_limit {
+        let context = MONEROCHANContextBuilder::default().max_cycles(cycle_limit).build();
+        prover.inner().execute(&pk.elf, stdin, context)?;
+    }
+
+    Ok(())
+}
+
+impl<'a> IntoFuture for EnvProveBuilder<'a> {
+    type Output = Result<MONEROCHANProofWithPublicValues>;
 
-        // Dump the program and stdin to files for debugging if `MONEROCHAN_DUMP` is set.
-        crate::utils::monerochan_dump(&pk.elf, &stdin);
+    type IntoFuture = Pin<Box<dyn Future<Output = Self::Output> + Send + 'a>>;
 
-        prover.prove(pk, &stdin, kind)
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(self.run_async())
     }
 }
@@ -27,15 +27,30 @@ pub mod cpu;
 pub mod cuda;
 pub mod env;
 pub mod install;
+pub mod metadata;
+pub mod proof_store;
+pub mod prove_config;
 #[cfg(feature = "network")]
 pub mod network;
+pub mod provenance;
 pub mod utils;
+pub mod wasm;
 
 // Re-export the client.
 pub use crate::client::ProverClient;
 
+// Re-export the program metadata and build provenance.
+pub use crate::{metadata::ProgramMetadata, provenance::BuildProvenance};
+
+// Re-export the prove config.
+pub use crate::prove_config::{NetworkStrategy, ProveConfig};
+
 // Re-export the provers.
-pub use crate::{cpu::CpuProver, cuda::CudaProver, env::EnvProver};
+pub use crate::{
+    cpu::CpuProver,
+    cuda::CudaProver,
+    env::{EnvProver, EnvProverError},
+};
 
 #[cfg(feature = "network")]
 pub use crate::network::{
@@ -48,27 +63,41 @@ pub mod proof;
 pub use proof::*;
 pub mod prover;
 
-pub use prover::{Prover, MONEROCHANVerificationError};
+pub use prover::{AsyncProver, Prover, MONEROCHANVerificationError};
+#[cfg(feature = "legacy-verifiers")]
+pub use prover::PREVIOUS_CIRCUIT_VERSIONS;
 
 // Re-export the build utilities and executor primitives.
 pub use monerochan_build::include_elf;
-pub use monerochan_core_executor::{ExecutionReport, Executor, HookEnv, MONEROCHANContext, MONEROCHANContextBuilder};
+pub use monerochan_core_executor::{CapturedOutput, CapturedStream, ExecutionReport, ExecutionReportDiff, Executor, HookEnv, InputJournal, InputReads, MONEROCHANContext, MONEROCHANContextBuilder, OutputMode};
+pub use monerochan_core_trace as trace;
 
 // Re-export the machine/prover primitives.
-pub use monerochan_core_machine::io::MONEROCHANStdin;
-pub use monerochan_primitives::io::MONEROCHANPublicValues;
+pub use monerochan_core_machine::io::{MONEROCHANStdin, StdinSchema, StdinSchemaError};
+pub use monerochan_primitives::{
+    io::{FramedValues, MONEROCHANPublicValues, Outcome, PublicValuesError},
+    merkle::{MerkleProof, MerkleTree},
+};
 pub use monerochan_prover::{
-    HashableKey, ProverMode, MONEROCHANProver, MONEROCHANProvingKey, MONEROCHANVerifyingKey, MONEROCHAN_CIRCUIT_VERSION,
+    gas::ShardPlan, HashableKey, ProverMode, MONEROCHANProver, MONEROCHANProvingKey, MONEROCHANVerifyingKey, WrapOpts,
+    MONEROCHAN_CIRCUIT_VERSION,
 };
+pub use monerochan_stark::{FriParameters, FriParametersError, MIN_CONJECTURED_SECURITY_BITS};
 
 // Re-export the utilities.
 pub use utils::setup_logger;
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use monerochan_primitives::io::MONEROCHANPublicValues;
+    use p3_field::PrimeField;
 
-    use crate::{utils, Prover, ProverClient, MONEROCHANStdin};
+    use crate::{
+        prover::diagnose_bn254_public_inputs, utils, CpuProver, EnvProver, FriParameters,
+        HashableKey, MONEROCHANVerificationError, Outcome, Prover, ProverClient, MONEROCHANStdin,
+    };
 
     #[test]
     fn test_execute() {
@@ -91,6 +120,16 @@ mod tests {
         client.execute(elf, &stdin).run().unwrap();
     }
 
+    #[test]
+    #[should_panic]
+    fn test_execute_outcome_after_commit() {
+        utils::setup_logger();
+        let client = ProverClient::builder().cpu().build();
+        let elf = test_artifacts::OUTCOME_MISUSE_ELF;
+        let stdin = MONEROCHANStdin::new();
+        client.execute(elf, &stdin).run().unwrap();
+    }
+
     #[should_panic]
     #[test]
     fn test_cycle_limit_fail() {
@@ -102,6 +141,13 @@ mod tests {
         client.execute(elf, &stdin).cycle_limit(1).run().unwrap();
     }
 
+    #[test]
+    fn test_try_setup_invalid_elf() {
+        utils::setup_logger();
+        let client = ProverClient::builder().cpu().build();
+        assert!(client.try_setup(&[1, 2, 3]).is_err());
+    }
+
     #[test]
     fn test_e2e_core() {
         utils::setup_logger();
@@ -122,6 +168,78 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_e2e_outcome() {
+        utils::setup_logger();
+        let client = ProverClient::builder().cpu().build();
+        let elf = test_artifacts::OUTCOME_ELF;
+        let (pk, vk) = client.setup(elf);
+
+        let mut stdin = MONEROCHANStdin::new();
+        stdin.write(&10u32);
+        let (mut public_values, _) = client.execute(elf, &stdin).run().unwrap();
+        assert_eq!(public_values.read_outcome().unwrap(), Outcome::Success);
+        assert_eq!(public_values.read::<u32>(), 10);
+        assert_eq!(public_values.read::<u32>(), 5);
+
+        // A rejected input is proven, with the values committed before the rejection.
+        let mut stdin = MONEROCHANStdin::new();
+        stdin.write(&7u32);
+        let mut proof = client.prove(&pk, &stdin).run().unwrap();
+        client.verify(&proof, &vk).unwrap();
+        assert_eq!(proof.public_values.read_outcome().unwrap(), Outcome::Rejected(7));
+        assert_eq!(proof.public_values.read::<u32>(), 7);
+        assert_eq!(proof.public_values.remaining(), 0);
+    }
+
+    #[test]
+    fn test_e2e_core_fri_queries() {
+        utils::setup_logger();
+        let client = ProverClient::builder().cpu().build();
+        let (pk, vk) = client.setup(test_artifacts::FIBONACCI_ELF);
+        let mut stdin = MONEROCHANStdin::new();
+        stdin.write(&10usize);
+        let proof = client.prove(&pk, &stdin).fri_queries(90).run().unwrap();
+        let fri_parameters = proof.fri_parameters().unwrap();
+        assert_eq!(fri_parameters.num_queries, 90);
+        assert_eq!(fri_parameters.log_blowup, 1);
+
+        // The verifier only accepts the proof with the parameters it was generated with.
+        client.verify_with_fri_parameters(&proof, &vk, fri_parameters).unwrap();
+        assert!(client.verify(&proof, &vk).is_err());
+        let other = FriParameters { num_queries: 100, ..fri_parameters };
+        assert!(client.verify_with_fri_parameters(&proof, &vk, other).is_err());
+    }
+
+    #[test]
+    fn test_e2e_verify_diagnostics() {
+        utils::setup_logger();
+        let client = ProverClient::builder().cpu().build();
+        let (pk, vk) = client.setup(test_artifacts::FIBONACCI_ELF);
+        let (_, other_vk) = client.setup(test_artifacts::FIBONACCI_BLAKE3_ELF);
+        let mut stdin = MONEROCHANStdin::new();
+        stdin.write(&10usize);
+        let proof = client.prove(&pk, &stdin).compressed().run().unwrap();
+
+        // A proof for another program is reported as such.
+        let err = client.verify(&proof, &other_vk).unwrap_err();
+        assert!(matches!(err, MONEROCHANVerificationError::VkeyMismatch { .. }), "{err}");
+
+        // So are tampered public values.
+        let mut tampered = proof.clone();
+        tampered.public_values = MONEROCHANPublicValues::from(&[255, 4, 84]);
+        let err = client.verify(&tampered, &vk).unwrap_err();
+        assert!(
+            matches!(err, MONEROCHANVerificationError::PublicValuesDigestMismatch { .. }),
+            "{err}"
+        );
+
+        // Malformed public inputs of a Plonk or Groth16 proof are not diagnosed.
+        assert!(diagnose_bn254_public_inputs(&vk, &proof.public_values, &[]).is_none());
+        let inputs = [vk.hash_bn254().as_canonical_biguint().to_string()];
+        assert!(diagnose_bn254_public_inputs(&vk, &proof.public_values, &inputs).is_none());
+    }
+
     #[test]
     fn test_e2e_io_override() {
         utils::setup_logger();
@@ -188,6 +306,44 @@ mod tests {
         let proof = client.prove(&pk, &stdin).plonk().run().unwrap();
         client.verify(&proof, &vk).unwrap();
     }
+
+    #[test]
+    fn test_mock_verify_only_accepts_matching_prover() {
+        utils::setup_logger();
+        let client = ProverClient::builder().mock().mock_seed(1).build();
+        let elf = test_artifacts::FIBONACCI_ELF;
+        let (pk, vk) = client.setup(elf);
+        let mut stdin = MONEROCHANStdin::new();
+        stdin.write(&10usize);
+
+        // Mock proofs are deterministic.
+        let mut proof = client.prove(&pk, &stdin).compressed().run().unwrap();
+        let again = client.prove(&pk, &stdin).compressed().run().unwrap();
+        assert_eq!(bincode::serialize(&proof).unwrap(), bincode::serialize(&again).unwrap());
+        client.verify(&proof, &vk).unwrap();
+
+        // A mock prover with another seed rejects the proof.
+        let other = ProverClient::builder().mock().mock_seed(2).build();
+        assert!(other.verify(&proof, &vk).is_err());
+
+        // So does the matching mock prover if the public values are changed.
+        proof.public_values = MONEROCHANPublicValues::from(&[255, 4, 84]);
+        assert!(client.verify(&proof, &vk).is_err());
+    }
+
+    #[test]
+    fn test_e2e_prove_async_mock() {
+        utils::setup_logger();
+        let prover = Arc::new(CpuProver::mock());
+        let client = EnvProver { prover, async_prover: None, cycle_limit: None };
+        let elf = test_artifacts::FIBONACCI_ELF;
+        let (pk, vk) = client.setup(elf);
+        let mut stdin = MONEROCHANStdin::new();
+        stdin.write(&10usize);
+
+        let proof = tokio_test::block_on(client.prove(&pk, &stdin).core().run_async()).unwrap();
+        client.verify(&proof, &vk).unwrap();
+    }
 }
 
 #[cfg(all(feature = "cuda", not(monerochan_ci_in_progress)))]
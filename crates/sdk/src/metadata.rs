@@ -0,0 +1,108 @@
+//! # Program Metadata
+//!
+//! Reads the version and the git commit embedded in an ELF by the
+//! `monerochan_runtime::program_metadata!` macro, to check that a deployment proves the intended
+//! build of a program.
+
+use anyhow::{bail, Context, Result};
+use elf::{endian::LittleEndian, ElfBytes};
+
+/// The section of the ELF holding the metadata.
+pub const METADATA_SECTION: &str = ".monerochan_metadata";
+
+/// The version of the format of the metadata section.
+const FORMAT_VERSION: u8 = 1;
+
+/// The version and the git commit of a program, as embedded in its ELF.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramMetadata {
+    /// The version of the program.
+    pub version: String,
+    /// The git commit of the program, if one was embedded.
+    pub commit: Option<String>,
+}
+
+impl ProgramMetadata {
+    /// Reads the metadata embedded in an ELF.
+    ///
+    /// Returns `None` if the program does not embed metadata, and an error if the ELF or the
+    /// metadata is malformed.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{include_elf, ProgramMetadata};
+    ///
+    /// let elf = include_elf!("my-program");
+    /// let metadata = ProgramMetadata::from_elf(elf).unwrap().expect("no metadata");
+    /// assert_eq!(metadata.version, "1.2.0");
+    /// ```
+    pub fn from_elf(elf: &[u8]) -> Result<Option<Self>> {
+        let elf = ElfBytes::<LittleEndian>::minimal_parse(elf).context("failed to parse elf")?;
+        let Some(header) =
+            elf.section_header_by_name(METADATA_SECTION).context("failed to read elf sections")?
+        else {
+            return Ok(None);
+        };
+        let (data, _) = elf.section_data(&header).context("failed to read the metadata section")?;
+        Self::decode(data).map(Some)
+    }
+
+    /// Checks that the metadata matches the expected version, and the expected commit if given.
+    pub fn ensure(&self, version: &str, commit: Option<&str>) -> Result<()> {
+        if self.version != version {
+            bail!("program version mismatch: expected {version}, got {}", self.version);
+        }
+        if let Some(commit) = commit {
+            if self.commit.as_deref() != Some(commit) {
+                bail!(
+                    "program commit mismatch: expected {commit}, got {}",
+                    self.commit.as_deref().unwrap_or("none")
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Decodes the content of the metadata section: the format version, then the version and the
+    /// commit as NUL terminated strings.
+    fn decode(data: &[u8]) -> Result<Self> {
+        let Some((&format, data)) = data.split_first() else {
+            bail!("empty metadata section");
+        };
+        if format != FORMAT_VERSION {
+            bail!("unsupported metadata format {format}");
+        }
+
+        let mut fields = data.split(|byte| *byte == 0);
+        let mut next = |name| -> Result<String> {
+            let field = fields.next().with_context(|| format!("missing {name} in metadata"))?;
+            String::from_utf8(field.to_vec()).with_context(|| format!("invalid {name} in metadata"))
+        };
+        let version = next("version")?;
+        let commit = next("commit")?;
+        Ok(Self { version, commit: (!commit.is_empty()).then_some(commit) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_metadata() {
+        let metadata = ProgramMetadata::decode(b"\x011.2.0\x004f2a9c1\x00").unwrap();
+        assert_eq!(metadata.version, "1.2.0");
+        assert_eq!(metadata.commit.as_deref(), Some("4f2a9c1"));
+        metadata.ensure("1.2.0", Some("4f2a9c1")).unwrap();
+        metadata.ensure("1.2.0", None).unwrap();
+        assert!(metadata.ensure("1.2.1", None).is_err());
+        assert!(metadata.ensure("1.2.0", Some("0000000")).is_err());
+
+        let metadata = ProgramMetadata::decode(b"\x010.1.0\x00\x00").unwrap();
+        assert_eq!(metadata.commit, None);
+
+        assert!(ProgramMetadata::decode(b"").is_err());
+        assert!(ProgramMetadata::decode(b"\x021.2.0\x00\x00").is_err());
+        assert!(ProgramMetadata::decode(b"\x01\xff\x00\x00").is_err());
+    }
+}
@@ -2,10 +2,16 @@
 //!
 //! This module provides a builder for the [`NetworkProver`].
 
+use std::{sync::Arc, time::Duration};
+
 use alloy_primitives::Address;
 
 use crate::{
-    network::{NetworkMode, MAINNET_RPC_URL, utils::get_default_rpc_url_for_mode},
+    network::{
+        solana_client_auth::NonceSource,
+        tee::registry::{TeeSignerChangeCallback, TeeSignerRegistry},
+        NetworkMode, Transport, MAINNET_RPC_URL, utils::get_default_rpc_url_for_mode,
+    },
     NetworkProver,
 };
 
@@ -15,12 +21,19 @@ use crate::network::retry::{self, DEFAULT_RETRY_TIMEOUT};
 /// A builder for the [`NetworkProver`].
 ///
 /// The builder is used to configure the [`NetworkProver`] before it is built.
-/// All network proving now uses the monerochan network API (no direct SP1 or auction mode).
+/// All network proving uses the monerochan network API.
 #[derive(Default)]
 pub struct NetworkProverBuilder {
     pub(crate) rpc_url: Option<String>,
     pub(crate) tee_signers: Option<Vec<Address>>,
+    pub(crate) tee_signer_refresh_interval: Option<Duration>,
+    pub(crate) on_tee_signers_changed: Option<TeeSignerChangeCallback>,
     pub(crate) network_mode: Option<NetworkMode>,
+    pub(crate) compression: Option<bool>,
+    pub(crate) transport: Option<Transport>,
+    pub(crate) receipt_key: Option<[u8; 32]>,
+    pub(crate) api_key: Option<String>,
+    pub(crate) nonce_source: Option<NonceSource>,
 }
 
 impl NetworkProverBuilder {
@@ -44,12 +57,141 @@ impl NetworkProverBuilder {
     }
 
     /// Sets the list of TEE signers, used for verifying TEE proofs.
+    ///
+    /// # Details
+    /// Without a refresh interval, only these signers are ever trusted (pinning). With
+    /// [`Self::tee_signer_refresh_interval`], they are the starting point of the signer rotations
+    /// followed by the prover.
     #[must_use]
     pub fn tee_signers(mut self, tee_signers: &[Address]) -> Self {
         self.tee_signers = Some(tee_signers.to_vec());
         self
     }
 
+    /// Refreshes the TEE signers from the TEE server when they are older than the interval, or
+    /// when a proof is signed by an unknown signer.
+    ///
+    /// # Details
+    /// A new signer set is only trusted if it is signed by a currently trusted signer, so that a
+    /// long-lived prover keeps accepting TEE proofs across signer rotations without restarting.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    ///
+    /// use monerochan::ProverClient;
+    ///
+    /// let prover = ProverClient::builder()
+    ///     .network()
+    ///     .tee_signer_refresh_interval(Duration::from_secs(3600))
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn tee_signer_refresh_interval(mut self, interval: Duration) -> Self {
+        self.tee_signer_refresh_interval = Some(interval);
+        self
+    }
+
+    /// Sets a callback invoked with the previous and the new signer set when the trusted TEE
+    /// signers change.
+    #[must_use]
+    pub fn on_tee_signers_changed(
+        mut self,
+        callback: impl Fn(&[Address], &[Address]) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_tee_signers_changed = Some(Box::new(callback));
+        self
+    }
+
+    /// Sets whether the ELF and stdin of proof requests are compressed with zstd.
+    ///
+    /// # Details
+    /// Compression is enabled by default, and skipped for the payloads it would not shrink.
+    /// Disable it for a network that does not support it.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::ProverClient;
+    ///
+    /// let prover = ProverClient::builder().network().compression(false).build();
+    /// ```
+    #[must_use]
+    pub fn compression(mut self, compression: bool) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Sets the transport used to reach the network API.
+    ///
+    /// # Details
+    /// gRPC is used by default. [`Transport::Rest`] sends the requests as JSON over HTTPS, for
+    /// networks which block gRPC or HTTP/2, and only supports requesting proofs and fetching their
+    /// status and result.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{network::Transport, ProverClient};
+    ///
+    /// let prover = ProverClient::builder().network().transport(Transport::Rest).build();
+    /// ```
+    #[must_use]
+    pub fn transport(mut self, transport: Transport) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Sets the Ed25519 public key the network signs proof receipts with.
+    ///
+    /// # Details
+    /// Required by [`NetworkProver::get_receipt`], which verifies the receipts of succeeded
+    /// requests against this key.
+    #[must_use]
+    pub fn receipt_key(mut self, receipt_key: [u8; 32]) -> Self {
+        self.receipt_key = Some(receipt_key);
+        self
+    }
+
+    /// Sets the API key the requests are authenticated with.
+    ///
+    /// # Details
+    /// API keys are minted by the network operator, each for a client and a set of permissions,
+    /// and can be revoked without touching a wallet. When set, the key is used instead of the
+    /// signature of `MONEROCHAN_NETWORK_PRIVATE_KEY`. By default, the key is read from the
+    /// `MONEROCHAN_NETWORK_API_KEY` environment variable.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::ProverClient;
+    ///
+    /// let prover = ProverClient::builder().network().api_key("mck_0123").build();
+    /// ```
+    #[must_use]
+    pub fn api_key(mut self, api_key: &str) -> Self {
+        self.api_key = Some(api_key.to_string());
+        self
+    }
+
+    /// Sets where the nonces of the signatures of the network private key come from.
+    ///
+    /// # Details
+    /// The network accepts each signature once, and random nonces are used by default. A
+    /// [`NonceSource::Counter`] persists a counter so that nonces never repeat across runs, and
+    /// [`NonceSource::Challenge`] signs a challenge fetched from the network for each request, for
+    /// networks that require it.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{network::solana_client_auth::NonceSource, ProverClient};
+    ///
+    /// let nonces = NonceSource::counter("monerochan-nonce").unwrap();
+    /// let prover = ProverClient::builder().network().nonce_source(nonces).build();
+    /// ```
+    #[must_use]
+    pub fn nonce_source(mut self, nonce_source: NonceSource) -> Self {
+        self.nonce_source = Some(nonce_source);
+        self
+    }
+
     /// Builds a [`NetworkProver`].
     ///
     /// # Details
@@ -100,6 +242,30 @@ impl NetworkProverBuilder {
                 }
             });
 
-        NetworkProver::new(network_mode, rpc_url).with_tee_signers(tee_signers)
+        let mut registry = match self.tee_signer_refresh_interval {
+            Some(interval) => TeeSignerRegistry::refreshing(tee_signers, interval),
+            None => TeeSignerRegistry::pinned(tee_signers),
+        };
+        if let Some(callback) = self.on_tee_signers_changed {
+            registry = registry.on_change(callback);
+        }
+
+        let mut prover = NetworkProver::new(network_mode, rpc_url)
+            .with_tee_signer_registry(Arc::new(registry))
+            .with_compression(self.compression.unwrap_or(true))
+            .with_transport(self.transport.unwrap_or_default());
+        if let Some(receipt_key) = self.receipt_key {
+            prover = prover.with_receipt_key(receipt_key);
+        }
+        let api_key = self
+            .api_key
+            .or_else(|| std::env::var("MONEROCHAN_NETWORK_API_KEY").ok().filter(|k| !k.is_empty()));
+        if let Some(api_key) = api_key {
+            prover = prover.with_api_key(api_key);
+        }
+        if let Some(nonce_source) = self.nonce_source {
+            prover = prover.with_nonce_source(nonce_source);
+        }
+        prover
     }
 }
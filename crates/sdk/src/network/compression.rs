@@ -0,0 +1,126 @@
+//! # Payload Compression
+//!
+//! The ELF and stdin of a proof request are sent compressed with zstd, as flagged by the
+//! `compression` field of the request. Witnesses compress well, and dominate the size of a
+//! submission.
+//!
+//! A server that does not know the field would take the compressed payloads as is, so a client
+//! only compresses once the server advertised zstd in the [`ACCEPT_HEADER`] of a response: the
+//! first request to a server is always sent uncompressed.
+
+use std::io::Read;
+
+use anyhow::{bail, Context, Result};
+use tonic::{metadata::MetadataMap, Response};
+
+use crate::network::proto::api::{Compression, RequestProofRequest};
+
+/// The zstd level, a trade-off between the speed and the ratio of the compression.
+const LEVEL: i32 = 3;
+
+/// The response header a server lists the compressions it accepts in.
+pub(crate) const ACCEPT_HEADER: &str = "monerochan-accept-compression";
+
+/// Advertises the compressions the server accepts in a response.
+pub(crate) fn advertise<T>(response: &mut Response<T>) {
+    response.metadata_mut().insert(ACCEPT_HEADER, "zstd".parse().expect("a valid header"));
+}
+
+/// Returns whether the response of a server advertised zstd.
+pub(crate) fn accepts_zstd(metadata: &MetadataMap) -> bool {
+    metadata
+        .get(ACCEPT_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').any(|name| name.trim() == "zstd"))
+}
+
+/// Compresses the ELF and stdin of a request, unless that would not make it smaller.
+pub(crate) fn compress(request: &mut RequestProofRequest) -> Result<()> {
+    if request.compression != Compression::None as i32 {
+        return Ok(());
+    }
+
+    let elf = zstd::encode_all(request.elf.as_slice(), LEVEL).context("failed to compress elf")?;
+    let stdin =
+        zstd::encode_all(request.stdin.as_slice(), LEVEL).context("failed to compress stdin")?;
+    if elf.len() + stdin.len() < request.elf.len() + request.stdin.len() {
+        request.elf = elf;
+        request.stdin = stdin;
+        request.compression = Compression::Zstd as i32;
+    }
+    Ok(())
+}
+
+/// Decompresses the ELF and stdin of a request, as flagged by its `compression`, failing if
+/// either would exceed `max_size` bytes.
+pub(crate) fn decompress(request: &mut RequestProofRequest, max_size: usize) -> Result<()> {
+    match Compression::try_from(request.compression) {
+        Ok(Compression::None) => {}
+        Ok(Compression::Zstd) => {
+            request.elf = decode(&request.elf, max_size).context("failed to decompress elf")?;
+            request.stdin =
+                decode(&request.stdin, max_size).context("failed to decompress stdin")?;
+        }
+        Err(_) => bail!("unsupported compression {}", request.compression),
+    }
+    request.compression = Compression::None as i32;
+    Ok(())
+}
+
+/// Decodes a zstd frame, reading at most one byte past `max_size` to detect larger payloads.
+fn decode(data: &[u8], max_size: usize) -> Result<Vec<u8>> {
+    let mut decoded = Vec::new();
+    zstd::Decoder::new(data)?.take(max_size as u64 + 1).read_to_end(&mut decoded)?;
+    if decoded.len() > max_size {
+        bail!("the payload exceeds {max_size} bytes once decompressed");
+    }
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compression_roundtrip() {
+        let original = RequestProofRequest {
+            elf: vec![7; 4096],
+            stdin: vec![0; 1 << 16],
+            ..Default::default()
+        };
+
+        let mut request = original.clone();
+        compress(&mut request).unwrap();
+        assert_eq!(request.compression, Compression::Zstd as i32);
+        assert!(request.stdin.len() < original.stdin.len());
+        decompress(&mut request, 1 << 16).unwrap();
+        assert_eq!(request, original);
+
+        // A payload larger than the limit once decompressed is rejected.
+        let mut request = original.clone();
+        compress(&mut request).unwrap();
+        assert!(decompress(&mut request, (1 << 16) - 1).is_err());
+
+        // Incompressible payloads are sent as is.
+        let mut request = RequestProofRequest { elf: vec![1], ..Default::default() };
+        compress(&mut request).unwrap();
+        assert_eq!(request.compression, Compression::None as i32);
+
+        let mut request = RequestProofRequest { compression: 42, ..Default::default() };
+        assert!(decompress(&mut request, 1 << 16).is_err());
+    }
+
+    #[test]
+    fn test_accept_header() {
+        let mut response = Response::new(());
+        assert!(!accepts_zstd(response.metadata()));
+        advertise(&mut response);
+        assert!(accepts_zstd(response.metadata()));
+
+        let mut metadata = MetadataMap::new();
+        metadata.insert(ACCEPT_HEADER, "gzip, zstd".parse().unwrap());
+        assert!(accepts_zstd(&metadata));
+        metadata.insert(ACCEPT_HEADER, "gzip".parse().unwrap());
+        assert!(!accepts_zstd(&metadata));
+    }
+}
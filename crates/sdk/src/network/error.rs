@@ -8,6 +8,29 @@ pub enum Error {
     #[error("Program simulation failed")]
     SimulationFailed,
 
+    /// The program halted with a non-zero exit code in simulation.
+    #[error(
+        "Program simulation halted: {}",
+        monerochan_core_executor::ExecutionError::HaltWithNonZeroExitCode(*.exit_code)
+    )]
+    ProgramExited {
+        /// The exit code: 1 if the program panicked, or the code of a failed
+        /// `monerochan_runtime::ensure!`.
+        exit_code: u32,
+    },
+
+    /// The program executed for more cycles in simulation than the request may use.
+    #[error(
+        "Program simulation executed {cycles} cycles, more than the limit of {limit} cycles of \
+         the request"
+    )]
+    CycleLimitExceeded {
+        /// The cycles the program executed for in simulation.
+        cycles: u64,
+        /// The cycle limit of the request, at most the hard cap of the network mode.
+        limit: u64,
+    },
+
     /// The proof request is unexecutable.
     #[error("Proof request 0x{} is unexecutable", hex::encode(.request_id))]
     RequestUnexecutable {
@@ -36,6 +59,31 @@ pub enum Error {
         request_id: Vec<u8>,
     },
 
+    /// The network returned a proof that does not verify against the verifying key.
+    #[error("The network returned an invalid proof: {0}")]
+    InvalidProof(#[source] crate::MONEROCHANVerificationError),
+
+    /// The network returned a proof of other public values than the simulation committed.
+    #[error(
+        "Proof request {request_id} returned public values with digest 0x{}, but the simulation \
+         committed public values with digest 0x{}",
+        hex::encode(.found),
+        hex::encode(.expected)
+    )]
+    PublicValuesMismatch {
+        /// The ID of the request.
+        request_id: String,
+        /// The digest of the public values committed in the simulation.
+        expected: [u8; 32],
+        /// The digest of the public values of the returned proof.
+        found: [u8; 32],
+    },
+
+    /// The service has too many outstanding authentication challenges, for the client or in
+    /// total, to issue another one.
+    #[error("Too many outstanding authentication challenges")]
+    TooManyChallenges,
+
     /// An error occurred while interacting with the RPC server.
     #[error("RPC error")]
     RpcError(#[from] Status),
@@ -0,0 +1,176 @@
+//! # Network Execute
+//!
+//! This module provides a builder for requesting the execution of a program from the network,
+//! without generating a proof.
+
+use std::{
+    collections::HashMap,
+    future::{Future, IntoFuture},
+    pin::Pin,
+    time::Duration,
+};
+
+use anyhow::Result;
+use monerochan_core_machine::io::MONEROCHANStdin;
+
+use crate::{utils::block_on, MONEROCHANPublicValues, NetworkProver};
+
+/// The outcome of executing a program on the network.
+#[derive(Debug, Clone)]
+pub struct NetworkExecution {
+    /// The public values committed by the program.
+    pub public_values: MONEROCHANPublicValues,
+    /// The number of cycles the program executed for.
+    pub cycles: u64,
+    /// The gas used by the program, if the network computed it.
+    pub gas: Option<u64>,
+}
+
+/// A builder for requesting the execution of a program from the network.
+pub struct NetworkExecuteBuilder<'a> {
+    pub(crate) prover: &'a NetworkProver,
+    pub(crate) elf: &'a [u8],
+    pub(crate) stdin: MONEROCHANStdin,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) cycle_limit: Option<u64>,
+    pub(crate) gas_limit: Option<u64>,
+    pub(crate) idempotency_key: Option<String>,
+    pub(crate) labels: HashMap<String, String>,
+}
+
+impl NetworkExecuteBuilder<'_> {
+    /// Set the timeout for the execution request.
+    ///
+    /// # Details
+    /// The request fails if the network has not executed the program within the timeout.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    ///
+    /// use monerochan::{MONEROCHANStdin, ProverClient};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let client = ProverClient::builder().network().build();
+    /// let execution =
+    ///     client.execute_only(elf, &stdin).timeout(Duration::from_secs(60)).run().unwrap();
+    /// ```
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the cycle limit for the execution request.
+    ///
+    /// # Details
+    /// The execution fails if the program runs for more cycles than the limit.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{MONEROCHANStdin, ProverClient};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let client = ProverClient::builder().network().build();
+    /// let execution = client.execute_only(elf, &stdin).cycle_limit(1_000_000).run().unwrap();
+    /// ```
+    #[must_use]
+    pub fn cycle_limit(mut self, cycle_limit: u64) -> Self {
+        self.cycle_limit = Some(cycle_limit);
+        self
+    }
+
+    /// Set the gas limit for the execution request.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{MONEROCHANStdin, ProverClient};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let client = ProverClient::builder().network().build();
+    /// let execution = client.execute_only(elf, &stdin).gas_limit(1_000_000).run().unwrap();
+    /// ```
+    #[must_use]
+    pub fn gas_limit(mut self, gas_limit: u64) -> Self {
+        self.gas_limit = Some(gas_limit);
+        self
+    }
+
+    /// Set the idempotency key of the request.
+    ///
+    /// # Details
+    /// Requests submitted with the same key return the existing request instead of creating a new
+    /// one. A random key is used by default.
+    #[must_use]
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// Add a label to the request, see [`crate::network::prove::NetworkProveBuilder::label`].
+    #[must_use]
+    pub fn label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
+    /// Execute the program on the network and wait for the outcome.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{MONEROCHANStdin, ProverClient};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let client = ProverClient::builder().network().build();
+    /// let execution = client.execute_only(elf, &stdin).run().unwrap();
+    /// ```
+    pub fn run(self) -> Result<NetworkExecution> {
+        block_on(self.run_async())
+    }
+
+    /// Execute the program on the network and wait for the outcome asynchronously.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{MONEROCHANStdin, ProverClient};
+    ///
+    /// tokio_test::block_on(async {
+    ///     let elf = &[1, 2, 3];
+    ///     let stdin = MONEROCHANStdin::new();
+    ///
+    ///     let client = ProverClient::builder().network().build();
+    ///     let execution = client.execute_only(elf, &stdin).run_async().await.unwrap();
+    /// })
+    /// ```
+    pub async fn run_async(self) -> Result<NetworkExecution> {
+        self.prover
+            .execute_only_impl(
+                self.elf,
+                &self.stdin,
+                self.timeout,
+                self.cycle_limit,
+                self.gas_limit,
+                self.idempotency_key,
+                self.labels,
+            )
+            .await
+    }
+}
+
+impl<'a> IntoFuture for NetworkExecuteBuilder<'a> {
+    type Output = Result<NetworkExecution>;
+
+    type IntoFuture = Pin<Box<dyn Future<Output = Self::Output> + Send + 'a>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(self.run_async())
+    }
+}
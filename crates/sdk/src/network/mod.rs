@@ -3,6 +3,7 @@
 //! A library for interacting with the MONEROCHAN prover over the network.
 
 pub mod client;
+mod compression;
 pub mod prover;
 #[rustfmt::skip]
 #[allow(missing_docs)]
@@ -12,8 +13,12 @@ pub mod proto;
 pub mod builder;
 mod error;
 mod grpc;
+pub mod execute;
 pub mod prove;
+pub mod receipt;
+mod rest;
 mod retry;
+pub mod server;
 pub mod signer;
 #[cfg(feature = "network")]
 pub mod solana_client_auth;
@@ -65,6 +70,32 @@ impl std::str::FromStr for NetworkMode {
     }
 }
 
+/// The transport used to reach the network API.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Transport {
+    /// gRPC over HTTP/2.
+    #[default]
+    Grpc,
+    /// JSON over HTTPS, through the gateway in front of the network API, for networks which
+    /// block gRPC or HTTP/2.
+    ///
+    /// Only requesting proofs and fetching their status and result are supported: the other
+    /// operations of the [`NetworkProver`](crate::NetworkProver) return an error.
+    Rest,
+}
+
+impl std::str::FromStr for Transport {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "grpc" => Ok(Transport::Grpc),
+            "rest" | "http" | "json" => Ok(Transport::Rest),
+            _ => Err(format!("Invalid transport: {s}")),
+        }
+    }
+}
+
 pub(crate) const MAINNET_EXPLORER_URL: &str = "https://explorer.monero-chan.org";
 pub(crate) const MAINNET_RPC_URL: &str = "https://rpc.mainnet.monero-chan.org";
 pub(crate) const RESERVED_EXPLORER_URL: &str = "https://explorer.reserved.monero-chan.org";
@@ -5,12 +5,24 @@
 #[rustfmt::skip]
 pub mod artifact;
 
+// Admin API proto definitions, for the administration of a self-hosted proving service.
+#[cfg(feature = "network")]
+pub mod admin {
+    tonic::include_proto!("monerochan.admin.v1");
+}
+
 // Network API proto definitions
 #[cfg(feature = "network")]
 pub mod api {
     tonic::include_proto!("monerochan.network.v1");
 }
 
+// Worker pool proto definitions, for the proving service to dispatch jobs to remote workers.
+#[cfg(feature = "network")]
+pub mod worker {
+    tonic::include_proto!("monerochan.worker.v1");
+}
+
 // Export both auction and base proto modules directly for runtime selection.
 #[rustfmt::skip]
 pub mod auction {
@@ -2,19 +2,21 @@
 //!
 //! This module provides a builder for creating a proof request to the network.
 
-use std::time::Duration;
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use alloy_primitives::{Address, B256};
-use anyhow::Result;
+use anyhow::{Context, Result};
 use monerochan_core_machine::io::MONEROCHANStdin;
 use monerochan_prover::MONEROCHANProvingKey;
 
 use crate::{
     utils::{block_on, monerochan_dump},
-    NetworkProver, MONEROCHANProofMode, MONEROCHANProofWithPublicValues,
+    NetworkProver, MONEROCHANProofMode, MONEROCHANProofWithPublicValues, ProveConfig,
 };
 
-use super::{proto::types::FulfillmentStrategy, validation};
+use super::{
+    proto::types::FulfillmentStrategy, prover::verify_network_proof, validation, Error,
+};
 
 use std::{
     future::{Future, IntoFuture},
@@ -41,6 +43,9 @@ pub struct NetworkProveBuilder<'a> {
     pub(crate) treasury: Option<Address>,
     pub(crate) max_price_per_pgu: Option<u64>,
     pub(crate) auction_timeout: Option<Duration>,
+    pub(crate) idempotency_key: Option<String>,
+    pub(crate) labels: HashMap<String, String>,
+    pub(crate) verify_locally: bool,
 }
 
 impl NetworkProveBuilder<'_> {
@@ -193,6 +198,10 @@ impl NetworkProveBuilder<'_> {
     /// succeeds locally (without generating a proof). This feature is recommended for users who
     /// want to optimize the latency of the proof generation on the network.
     ///
+    /// Unless it is skipped, the digest of the public values committed in the simulation is sent
+    /// with the request, and the proof returned by the network must commit the same public values,
+    /// or [`crate::network::Error::PublicValuesMismatch`] is returned.
+    ///
     /// # Example
     /// ```rust,no_run
     /// use monerochan::{Prover, ProverClient, MONEROCHANStdin};
@@ -243,6 +252,9 @@ impl NetworkProveBuilder<'_> {
     /// The cycle limit ensures that a prover on the network will stop generating a proof once the
     /// cycle limit is reached, which prevents denial of service attacks.
     ///
+    /// The limit is enforced by the network, and is independent of the
+    /// [`crate::MONEROCHANContextBuilder::max_cycles`] of local executions.
+    ///
     /// # Example
     /// ```rust,no_run
     /// use monerochan::{Prover, ProverClient, MONEROCHANStdin};
@@ -546,6 +558,130 @@ impl NetworkProveBuilder<'_> {
         self
     }
 
+    /// Sets the idempotency key of the proof request.
+    ///
+    /// # Details
+    /// Submitting a request with the key of an existing request returns the existing request
+    /// instead of creating a new one, so a submission retried after an ambiguous network failure
+    /// is only paid for once. If a key is not specified, a random one is generated for each
+    /// request, which still deduplicates the retries made by the SDK itself.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{Prover, ProverClient, MONEROCHANStdin};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let client = ProverClient::builder().network().build();
+    /// let (pk, vk) = client.setup(elf);
+    /// let request_id =
+    ///     client.prove(&pk, &stdin).idempotency_key("order-1234").request().unwrap();
+    /// ```
+    #[must_use]
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// Add a label to the request.
+    ///
+    /// # Details
+    /// Labels are arbitrary key/value pairs, such as `team=bridge` or `env=staging`, that the
+    /// network echoes back in the status of the request and that requests can be listed by, to
+    /// attribute the cost of proofs. Adding a label with an existing key replaces its value.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{Prover, ProverClient, MONEROCHANStdin};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let client = ProverClient::builder().network().build();
+    /// let (pk, vk) = client.setup(elf);
+    /// let proof =
+    ///     client.prove(&pk, &stdin).label("team", "bridge").label("env", "staging").run();
+    /// ```
+    #[must_use]
+    pub fn label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set whether to verify the proof locally before returning it.
+    ///
+    /// # Details
+    /// By default, the proof returned by the network is verified against the verifying key of the
+    /// program, and [`Error::InvalidProof`] is returned if it does not verify. Disabling this skips
+    /// the verification, for example when the proof is verified later anyway.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{Prover, ProverClient, MONEROCHANStdin};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let client = ProverClient::builder().network().build();
+    /// let (pk, vk) = client.setup(elf);
+    /// let proof = client.prove(&pk, &stdin).verify_locally(false).run();
+    /// ```
+    #[must_use]
+    pub fn verify_locally(mut self, verify_locally: bool) -> Self {
+        self.verify_locally = verify_locally;
+        self
+    }
+
+    /// Apply a [`ProveConfig`] to the request.
+    ///
+    /// # Details
+    /// Sets the mode, the strategy, the timeouts, the limits, the max price per PGU and whether to
+    /// skip the simulation to those of the config which are set, and adds its labels. The methods
+    /// called after this one override the config.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{Prover, ProverClient, ProveConfig, MONEROCHANStdin};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let config = ProveConfig::load("prove-config.json").unwrap();
+    /// let client = ProverClient::builder().network().build();
+    /// let (pk, vk) = client.setup(elf);
+    /// let proof = client.prove(&pk, &stdin).with_config(&config).run();
+    /// ```
+    #[must_use]
+    pub fn with_config(mut self, config: &ProveConfig) -> Self {
+        if let Some(mode) = config.mode {
+            self.mode = mode;
+        }
+        if let Some(strategy) = config.strategy {
+            self.strategy = strategy.into();
+        }
+        if let Some(timeout) = config.timeout() {
+            self.timeout = Some(timeout);
+        }
+        if let Some(auction_timeout) = config.auction_timeout() {
+            self.auction_timeout = Some(auction_timeout);
+        }
+        if let Some(cycle_limit) = config.cycle_limit {
+            self.cycle_limit = Some(cycle_limit);
+        }
+        if let Some(gas_limit) = config.gas_limit {
+            self.gas_limit = Some(gas_limit);
+        }
+        if let Some(max_price_per_pgu) = config.max_price_per_pgu {
+            self.max_price_per_pgu = Some(max_price_per_pgu);
+        }
+        if let Some(skip_simulation) = config.skip_simulation {
+            self.skip_simulation = skip_simulation;
+        }
+        self.labels.extend(config.labels.iter().map(|(k, v)| (k.clone(), v.clone())));
+        self
+    }
+
     /// Request a proof from the monero-chan network.
     ///
     /// # Details
@@ -605,6 +741,9 @@ impl NetworkProveBuilder<'_> {
                 self.verifier,
                 self.treasury,
                 self.max_price_per_pgu,
+                self.auction_timeout,
+                self.idempotency_key,
+                self.labels,
             )
             .await
     }
@@ -661,7 +800,8 @@ impl NetworkProveBuilder<'_> {
 
         monerochan_dump(&self.pk.elf, &self.stdin);
 
-        self.prover
+        let proof = self
+            .prover
             .prove_impl(
                 self.pk,
                 &self.stdin,
@@ -680,8 +820,29 @@ impl NetworkProveBuilder<'_> {
                 self.treasury,
                 self.max_price_per_pgu,
                 self.auction_timeout,
+                self.idempotency_key,
+                self.labels,
             )
-            .await
+            .await?;
+
+        if !self.verify_locally {
+            return Ok(proof);
+        }
+
+        // Verify the proof, so that callers never receive a proof that does not verify. The
+        // verification takes seconds, so it runs on the blocking pool rather than on the runtime.
+        self.prover.refresh_tee_signers(&proof, &self.pk.vk).await;
+        let prover = Arc::clone(&self.prover.prover);
+        let tee_signers = Arc::clone(&self.prover.tee_signers);
+        let vk = self.pk.vk.clone();
+        let (proof, verified) = tokio::task::spawn_blocking(move || {
+            let verified = verify_network_proof(&prover, &tee_signers, &proof, &vk);
+            (proof, verified)
+        })
+        .await
+        .context("the verification task failed")?;
+        verified.map_err(Error::InvalidProof)?;
+        Ok(proof)
     }
 }
 
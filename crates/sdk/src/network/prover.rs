@@ -3,46 +3,108 @@
 //! This module provides an implementation of the [`crate::Prover`] trait that can generate proofs
 //! on a remote RPC server.
 
-use std::time::{Duration, Instant};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
 
-use super::prove::NetworkProveBuilder;
+use super::{
+    execute::{NetworkExecuteBuilder, NetworkExecution},
+    prove::NetworkProveBuilder,
+};
 use crate::{
     cpu::{execute::CpuExecuteBuilder, CpuProver},
     network::{
         proto::types::FulfillmentStrategy,
-        Error, NetworkMode,
+        receipt::{public_values_digest, verify_receipt, ProofReceipt},
+        rest::RestClient,
+        solana_client_auth::{create_client_auth_with_nonce, derive_solana_address, NonceSource},
+        tee::registry::{TeeSignerRefreshTask, TeeSignerRegistry},
+        get_default_cycle_limit_for_mode, validation, Error, NetworkMode, Transport,
     },
     prover::verify_proof,
     MONEROCHANProofMode, MONEROCHANProofWithPublicValues, MONEROCHANProvingKey,
-    MONEROCHANVerifyingKey, ProofFromNetwork, Prover,
+    MONEROCHANPublicValues, MONEROCHANVerifyingKey, ProofFromNetwork, AsyncProver, Prover,
 };
 
 use alloy_primitives::{Address, B256};
 use std::str::FromStr;
 use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
 use bincode;
 use hex;
 use monerochan_core_executor::MONEROCHANContextBuilder;
 use monerochan_core_machine::io::MONEROCHANStdin;
+use monerochan_stark::MONEROCHANCoreOpts;
 use crate::network::proto::api::network_client::NetworkClient;
 use crate::network::proto::api::{
-    ClientAuth, FulfillmentStrategy as NetworkApiFulfillmentStrategy, 
-    GetProofStatusRequest, GetProofStatusResponse, JobStatus, ProofMode as NetworkApiProofMode, RequestProofRequest,
+    ClientAuth, Compression, FulfillmentStrategy as NetworkApiFulfillmentStrategy, 
+    CancelProofRequest, GetAuthChallengeRequest, GetProgramRequest, GetProofChunkRequest,
+    GetProofStatusRequest,
+    GetProofStatusResponse,
+    JobStatus, ListProgramsRequest, ListProofRequestsRequest, PinProgramRequest, ProgramInfo,
+    ProofMode as NetworkApiProofMode, ProofRequestSummary, RequestProofRequest,
 };
 use monerochan_prover::{
     components::CpuProverComponents, HashableKey, MONEROCHANProver,
 };
+use tokio::io::AsyncWriteExt;
 use tonic::transport::Channel;
 use tonic::Request;
 
 use crate::utils::block_on;
+use super::retry::{retry_operation, DEFAULT_RETRY_TIMEOUT};
+
+/// The filters of a listing of proof requests, see [`NetworkProver::list_requests_filtered`].
+///
+/// Every filter is optional, and a request must pass all of them to be listed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequestFilter {
+    /// Only requests in one of these statuses, any if empty.
+    pub statuses: Vec<JobStatus>,
+    /// Only requests for this program id.
+    pub program_id: Option<String>,
+    /// Only requests created at or after this time, in unix seconds.
+    pub created_after: Option<i64>,
+    /// Only requests created before this time, in unix seconds.
+    pub created_before: Option<i64>,
+    /// Only requests carrying all of these labels.
+    pub labels: HashMap<String, String>,
+}
+
+/// A page of a listing of proof requests, see [`NetworkProver::list_requests_page`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RequestPage {
+    /// The requests of the page, oldest first.
+    pub requests: Vec<ProofRequestSummary>,
+    /// The token of the next page, if this is not the last one.
+    pub next_page_token: Option<String>,
+}
 
 /// An implementation of [`crate::ProverClient`] that can generate proofs via the monerochan network API.
 pub struct NetworkProver {
     pub(crate) endpoint: String,
-    pub(crate) prover: CpuProver,
-    pub(crate) tee_signers: Vec<Address>,
+    /// Shared with the blocking tasks that verify the proofs.
+    pub(crate) prover: Arc<CpuProver>,
+    pub(crate) tee_signers: Arc<TeeSignerRegistry>,
+    /// Refreshes the TEE signers periodically, if they follow signer rotations.
+    pub(crate) tee_refresh_task: Option<TeeSignerRefreshTask>,
     pub(crate) network_mode: NetworkMode,
+    pub(crate) compression: bool,
+    /// Whether the server advertised that it accepts compressed requests.
+    pub(crate) compression_accepted: AtomicBool,
+    pub(crate) transport: Transport,
+    pub(crate) receipt_key: Option<[u8; 32]>,
+    pub(crate) api_key: Option<String>,
+    pub(crate) nonce_source: NonceSource,
+    /// The digests of the public values simulated for the requests submitted with
+    /// `request_async`, checked by [`NetworkProver::wait_proof`].
+    pub(crate) simulated_public_values: Mutex<HashMap<B256, [u8; 32]>>,
 }
 
 impl NetworkProver {
@@ -66,30 +128,123 @@ impl NetworkProver {
         // Install default CryptoProvider if not already installed.
         let _ = rustls::crypto::ring::default_provider().install_default();
 
-        let prover = CpuProver::new();
+        let prover = Arc::new(CpuProver::new());
         Self { 
             endpoint: rpc_url, 
             prover, 
-            tee_signers: vec![], 
+            tee_signers: Arc::new(TeeSignerRegistry::pinned(vec![])),
+            tee_refresh_task: None,
             network_mode,
+            compression: true,
+            compression_accepted: AtomicBool::new(false),
+            transport: Transport::Grpc,
+            receipt_key: None,
+            api_key: None,
+            nonce_source: NonceSource::Random,
+            simulated_public_values: Mutex::new(HashMap::new()),
         }
     }
 
     /// Sets the list of TEE signers, used for verifying TEE proofs.
+    ///
+    /// Only these signers are trusted, see [`Self::with_tee_signer_registry`] to follow signer
+    /// rotations.
     #[must_use]
     pub fn with_tee_signers(mut self, tee_signers: Vec<Address>) -> Self {
-        self.tee_signers = tee_signers;
+        self.tee_signers = Arc::new(TeeSignerRegistry::pinned(tee_signers));
+        self.tee_refresh_task = None;
+        self
+    }
+
+    /// Sets the registry of TEE signers, used for verifying TEE proofs.
+    ///
+    /// If the registry follows signer rotations, the prover refreshes it every refresh interval
+    /// for as long as it is alive, see [`TeeSignerRegistry::spawn_refresh_task`].
+    #[must_use]
+    pub fn with_tee_signer_registry(mut self, registry: Arc<TeeSignerRegistry>) -> Self {
+        self.tee_refresh_task = registry.spawn_refresh_task();
+        self.tee_signers = registry;
         self
     }
 
+    /// Sets whether the ELF and stdin of proof requests are compressed with zstd.
+    ///
+    /// Compression is enabled by default, and only used once the server advertised that it
+    /// accepts it, so the first request to a server, and the requests sent over REST, are sent
+    /// uncompressed.
+    #[must_use]
+    pub fn with_compression(mut self, compression: bool) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Sets the transport used to reach the network API.
+    ///
+    /// gRPC is used by default. See [`Transport::Rest`] for the operations supported over JSON.
+    #[must_use]
+    pub fn with_transport(mut self, transport: Transport) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Sets the Ed25519 public key of the network, used for verifying proof receipts.
+    #[must_use]
+    pub fn with_receipt_key(mut self, receipt_key: [u8; 32]) -> Self {
+        self.receipt_key = Some(receipt_key);
+        self
+    }
+
+    /// Sets the API key the requests are authenticated with, instead of the signature of the
+    /// network private key.
+    #[must_use]
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Sets where the nonces of the signatures of the network private key come from.
+    ///
+    /// Random nonces are used by default. See [`NonceSource`] for the alternatives.
+    #[must_use]
+    pub fn with_nonce_source(mut self, nonce_source: NonceSource) -> Self {
+        self.nonce_source = nonce_source;
+        self
+    }
+
+    /// Refreshes the TEE signers if they are stale for the signer of the proof, so that the
+    /// verification of the proof sees a rotation of the signers.
+    pub(crate) async fn refresh_tee_signers(
+        &self,
+        bundle: &MONEROCHANProofWithPublicValues,
+        vkey: &MONEROCHANVerifyingKey,
+    ) {
+        let Some(tee_proof) = &bundle.tee_proof else {
+            return;
+        };
+        if let Ok(signer) = monerochan_verifier::tee::recover_tee_signer(
+            tee_proof,
+            &vkey.bytes32_raw(),
+            bundle.public_values.as_slice(),
+        ) {
+            self.tee_signers.refresh_if_stale(&Address::from(signer)).await;
+        }
+    }
+
+    /// Gets the registry of TEE signers, used for verifying TEE proofs.
+    #[must_use]
+    pub fn tee_signer_registry(&self) -> &Arc<TeeSignerRegistry> {
+        &self.tee_signers
+    }
+
     /// Gets the network mode of this prover.
     pub fn network_mode(&self) -> NetworkMode {
         self.network_mode
     }
 
     /// Gets the default fulfillment strategy for this prover's network mode.
-    /// 
-    /// The network API only supports Reserved or Hosted fulfillment strategies.
+    ///
+    /// Reserved capacity is used by default in every mode. Mainnet requests may instead be
+    /// fulfilled by an auction among the provers, with [`FulfillmentStrategy::Auction`].
     pub fn default_fulfillment_strategy(&self) -> FulfillmentStrategy {
         FulfillmentStrategy::Reserved
     }
@@ -116,6 +271,42 @@ impl NetworkProver {
             elf,
             stdin: stdin.clone(),
             context_builder: MONEROCHANContextBuilder::default(),
+            core_opts: MONEROCHANCoreOpts::default(),
+            schema: None,
+        }
+    }
+
+    /// A request to execute a program on the network, without generating a proof.
+    ///
+    /// # Details
+    /// The network runs the program and returns the committed public values, the cycle count and
+    /// the gas. This is a cheap pre-flight check for inputs too large to execute locally.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{ProverClient, MONEROCHANStdin};
+    ///
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let client = ProverClient::builder().network().build();
+    /// let execution = client.execute_only(elf, &stdin).run().unwrap();
+    /// println!("{} cycles", execution.cycles);
+    /// ```
+    pub fn execute_only<'a>(
+        &'a self,
+        elf: &'a [u8],
+        stdin: &MONEROCHANStdin,
+    ) -> NetworkExecuteBuilder<'a> {
+        NetworkExecuteBuilder {
+            prover: self,
+            elf,
+            stdin: stdin.clone(),
+            timeout: None,
+            cycle_limit: None,
+            gas_limit: None,
+            idempotency_key: None,
+            labels: HashMap::new(),
         }
     }
 
@@ -160,6 +351,9 @@ impl NetworkProver {
             treasury: None,
             max_price_per_pgu: None,
             auction_timeout: None,
+            idempotency_key: None,
+            labels: HashMap::new(),
+            verify_locally: true,
         }
     }
 
@@ -171,6 +365,8 @@ impl NetworkProver {
     ///
     /// Note: With the network API, program registration happens automatically when you submit
     /// a proof request. This method returns the vk hash (program_id) that will be used.
+    /// The programs stored by the network are managed with [`NetworkProver::list_programs`] and
+    /// [`NetworkProver::pin_program`].
     ///
     /// # Example
     /// ```rust,no_run
@@ -208,6 +404,8 @@ impl NetworkProver {
         treasury: Option<Address>,
         max_price_per_pgu: Option<u64>,
         auction_timeout: Option<Duration>,
+        idempotency_key: Option<String>,
+        labels: HashMap<String, String>,
     ) -> Result<MONEROCHANProofWithPublicValues> {
         if tee_2fa {
             return Err(anyhow!(
@@ -215,88 +413,29 @@ impl NetworkProver {
             ));
         }
 
-        // Network API only supports Reserved/Hosted mode, not Auction
-        // Reject auction strategy early with clear error message
-        if strategy == FulfillmentStrategy::Auction {
-            return Err(anyhow!(
-                "Auction mode is disabled. \
-                The network API only supports Reserved or Hosted fulfillment strategies. \
-                Please use FulfillmentStrategy::Reserved or FulfillmentStrategy::Hosted instead."
-            ));
-        }
-
-        // Reject auction-specific parameters
-        if min_auction_period != 0 {
-            return Err(anyhow!(
-                "min_auction_period is not supported. \
-                Auction mode is disabled - please set min_auction_period to 0 or use Reserved/Hosted strategy."
-            ));
-        }
-
-        if whitelist.is_some() && !whitelist.as_ref().unwrap().is_empty() {
-            return Err(anyhow!(
-                "whitelist is not supported. \
-                Auction mode is disabled - please remove whitelist or use Reserved/Hosted strategy."
-            ));
-        }
-
-        if auctioneer.is_some() {
-            return Err(anyhow!(
-                "auctioneer is not supported. \
-                Auction mode is disabled - please remove auctioneer or use Reserved/Hosted strategy."
-            ));
-        }
-
-        if executor.is_some() {
-            return Err(anyhow!(
-                "executor is not supported. \
-                Auction mode is disabled - please remove executor or use Reserved/Hosted strategy."
-            ));
-        }
-
-        if verifier.is_some() {
-            return Err(anyhow!(
-                "verifier is not supported. \
-                Auction mode is disabled - please remove verifier or use Reserved/Hosted strategy."
-            ));
-        }
-
-        if treasury.is_some() {
-            return Err(anyhow!(
-                "treasury is not supported. \
-                Auction mode is disabled - please remove treasury or use Reserved/Hosted strategy."
-            ));
-        }
-
-        if max_price_per_pgu.is_some() {
-            return Err(anyhow!(
-                "max_price_per_pgu is not supported. \
-                Auction mode is disabled - please remove max_price_per_pgu or use Reserved/Hosted strategy."
-            ));
-        }
-
-        if auction_timeout.is_some() {
-            return Err(anyhow!(
-                "auction_timeout is not supported. \
-                Auction mode is disabled - please remove auction_timeout or use Reserved/Hosted strategy."
-            ));
-        }
-
-        // Use strategy as-is (should be Reserved or Hosted at this point)
-        let api_strategy = strategy;
-        let api_min_auction_period = 0;
+        validation::validate_auction_parameters(
+            strategy,
+            min_auction_period,
+            whitelist.as_deref(),
+            auctioneer,
+            max_price_per_pgu,
+            auction_timeout,
+        )?;
+        validation::validate_settlement_parameters(executor, verifier, treasury)?;
+        let (cycle_limit, public_values_hash) =
+            self.simulate(&pk.elf, stdin, skip_simulation, cycle_limit).await?;
 
         self
             .prove_via_api(
                 pk,
                 stdin,
                 mode,
-                api_strategy,
+                strategy,
                 timeout,
                 skip_simulation,
-                cycle_limit,
+                Some(cycle_limit),
                 gas_limit,
-                api_min_auction_period,
+                min_auction_period,
                 whitelist,
                 auctioneer,
                 executor,
@@ -304,11 +443,92 @@ impl NetworkProver {
                 treasury,
                 max_price_per_pgu,
                 auction_timeout,
+                idempotency_key,
+                labels,
+                public_values_hash,
             )
             .await
     }
 
+    /// Returns the cycle limit of a request: the given limit, lowered to the hard cap of the
+    /// network mode, or the hard cap itself, and the digest of the public values committed in the
+    /// simulation.
+    ///
+    /// Unless the simulation is skipped, the program is executed first, so that a request the
+    /// network would reject for its cycles fails before it is submitted, and so that the proof
+    /// returned by the network can be checked against the simulated public values.
+    async fn simulate(
+        &self,
+        elf: &[u8],
+        stdin: &MONEROCHANStdin,
+        skip_simulation: bool,
+        cycle_limit: Option<u64>,
+    ) -> Result<(u64, Option<[u8; 32]>)> {
+        let limit = request_cycle_limit(self.network_mode, cycle_limit);
+        if skip_simulation {
+            return Ok((limit, None));
+        }
+        // The execution takes as long as the program runs, so it runs on the blocking pool rather
+        // than on the runtime.
+        let prover = Arc::clone(&self.prover);
+        let (elf, stdin) = (elf.to_vec(), stdin.clone());
+        let (public_values, report) = tokio::task::spawn_blocking(move || {
+            prover.execute(&elf, &stdin).report_exit_code(true).run()
+        })
+        .await
+        .context("the simulation task failed")?
+        .context(Error::SimulationFailed)?;
+        if report.exit_code != 0 {
+            return Err(Error::ProgramExited { exit_code: report.exit_code }.into());
+        }
+        check_cycles(report.total_instruction_count(), limit)?;
+        Ok((limit, Some(public_values_digest(public_values.as_slice()))))
+    }
+
+    /// Builds the client address and authentication of a request: the API key if one is set, or
+    /// the signature of the network private key, with a nonce from the nonce source.
+    ///
+    /// Returns `(None, None)` if neither is configured.
+    async fn client_credentials(&self) -> Result<(Option<String>, Option<ClientAuth>)> {
+        if let Some(api_key) = &self.api_key {
+            // The network resolves the client from the key.
+            let auth = ClientAuth { api_key: api_key.clone(), ..Default::default() };
+            return Ok((None, Some(auth)));
+        }
+        let Some(private_key) = network_private_key() else {
+            return Ok((None, None));
+        };
+
+        let nonce = match &self.nonce_source {
+            NonceSource::Random => uuid::Uuid::new_v4().to_string(),
+            NonceSource::Counter(counter) => counter.next()?,
+            NonceSource::Challenge => {
+                let client_address = derive_solana_address(&private_key)?;
+                self.auth_challenge(client_address).await?
+            }
+        };
+        let (job_id, nonce, timestamp, signature, client_address) =
+            create_client_auth_with_nonce(&private_key, nonce)?;
+        let auth = ClientAuth { job_id, nonce, timestamp, signature, api_key: String::new() };
+        Ok((Some(client_address), Some(auth)))
+    }
+
+    /// Gets a challenge to sign from the network, for [`NonceSource::Challenge`].
+    async fn auth_challenge(&self, client_address: String) -> Result<String> {
+        let mut client = self.client().await?;
+        let response = client
+            .get_auth_challenge(Request::new(GetAuthChallengeRequest { client_address }))
+            .await
+            .context("network auth challenge request failed")?;
+        Ok(response.into_inner().challenge)
+    }
+
     async fn client(&self) -> Result<NetworkClient<Channel>> {
+        if self.transport == Transport::Rest {
+            return Err(anyhow!(
+                "this operation is not supported over the REST transport, use the gRPC transport"
+            ));
+        }
         // Use grpc::configure_endpoint which handles TLS automatically for HTTPS URLs
         let channel = super::grpc::configure_endpoint(&self.endpoint)?
             .connect()
@@ -335,51 +555,28 @@ impl NetworkProver {
         verifier: Option<Address>,
         treasury: Option<Address>,
         max_price_per_pgu: Option<u64>,
+        auction_timeout: Option<Duration>,
+        idempotency_key: Option<String>,
+        labels: HashMap<String, String>,
     ) -> Result<B256> {
+        validation::validate_auction_parameters(
+            strategy,
+            min_auction_period,
+            whitelist.as_deref(),
+            auctioneer,
+            max_price_per_pgu,
+            auction_timeout,
+        )?;
+        validation::validate_settlement_parameters(executor, verifier, treasury)?;
+        let (cycle_limit, public_values_hash) =
+            self.simulate(&pk.elf, stdin, skip_simulation, cycle_limit).await?;
+
         let stdin_bytes =
             bincode::serialize(stdin).context("failed to serialize stdin for API request")?;
 
         let whitelist_bytes =
             whitelist.unwrap_or_default().into_iter().map(|address| address.to_vec()).collect();
 
-        // Check for Solana private key from env var and create client auth if so
-        #[cfg(feature = "network")]
-        let (client_address, client_auth) = {
-            // Check MONEROCHAN_NETWORK_PRIVATE_KEY first, then fall back to BASE_PRIVATE_KEY
-            let private_key_str = std::env::var("MONEROCHAN_NETWORK_PRIVATE_KEY")
-                .ok()
-                .or_else(|| std::env::var("BASE_PRIVATE_KEY").ok());
-
-            let private_key_bytes = private_key_str.and_then(|solana_key_str| {
-                // Parse private key (support both hex and base58)
-                if solana_key_str.starts_with("0x") {
-                    hex::decode(&solana_key_str[2..]).ok()
-                } else {
-                    bs58::decode(&solana_key_str).into_vec().ok()
-                        .or_else(|| hex::decode(&solana_key_str).ok())
-                }
-            });
-            
-            if let Some(key_bytes) = private_key_bytes {
-                // Create client auth
-                let (job_id, nonce, timestamp, signature, addr) = 
-                    crate::network::solana_client_auth::create_client_auth(&key_bytes)?;
-                
-                let auth = Some(ClientAuth {
-                    job_id,
-                    nonce,
-                    timestamp,
-                    signature,
-                });
-                
-                (Some(addr), auth)
-            } else {
-                (None, None)
-            }
-        };
-        
-        #[cfg(not(feature = "network"))]
-        let (client_address, client_auth) = (None, None);
 
         let request = RequestProofRequest {
             program_id: format!("0x{}", hex::encode(pk.vk.bytes32())),
@@ -389,7 +586,7 @@ impl NetworkProver {
             strategy: network_api_strategy(strategy) as i32,
             timeout_secs: timeout.map(|value| value.as_secs()),
             skip_simulation,
-            cycle_limit,
+            cycle_limit: Some(cycle_limit),
             gas_limit,
             min_auction_period,
             whitelist: whitelist_bytes,
@@ -398,20 +595,56 @@ impl NetworkProver {
             verifier: address_vec(verifier),
             treasury: address_vec(treasury),
             max_price_per_pgu,
-            auction_timeout_secs: None,
-            client_address,
-            client_auth,
+            auction_timeout_secs: auction_timeout.map(|value| value.as_secs()),
+            // Signed by request_proof, anew for each attempt.
+            client_address: None,
+            client_auth: None,
+            idempotency_key: Some(idempotency_key.unwrap_or_else(new_idempotency_key)),
+            compression: Compression::None as i32,
+            labels,
+            public_values_hash: public_values_hash.map(Vec::from),
         };
 
         let request_id = self.request_proof(request).await?;
-        Ok(B256::from_str(&request_id).context("invalid request_id format")?)
+        let request_id = B256::from_str(&request_id).context("invalid request_id format")?;
+        if let Some(expected) = public_values_hash {
+            self.simulated_public_values.lock().unwrap().insert(request_id, expected);
+        }
+        Ok(request_id)
     }
 
-    async fn request_proof(&self, request: RequestProofRequest) -> Result<String> {
-        let mut client = self.client().await?;
-        let response =
-            client.request_proof(Request::new(request)).await.context("network request failed")?;
-        let inner = response.into_inner();
+    #[tracing::instrument(name = "network_submit", skip_all, fields(program_id = %request.program_id))]
+    async fn request_proof(&self, mut request: RequestProofRequest) -> Result<String> {
+        if self.compression && self.compression_accepted.load(Ordering::Relaxed) {
+            super::compression::compress(&mut request)?;
+        }
+        // The request carries an idempotency key, so a retry after an ambiguous failure returns
+        // the existing request instead of creating a duplicate one.
+        let inner = retry_operation(
+            || async {
+                // Each attempt is signed anew, since the network rejects a replayed signature.
+                let mut request = request.clone();
+                (request.client_address, request.client_auth) = self.client_credentials().await?;
+                if self.transport == Transport::Rest {
+                    return RestClient::new(&self.endpoint)?
+                        .request_proof(&request)
+                        .await
+                        .context("network request failed");
+                }
+                let mut client = self.client().await?;
+                let response = client
+                    .request_proof(Request::new(request))
+                    .await
+                    .context("network request failed")?;
+                if super::compression::accepts_zstd(response.metadata()) {
+                    self.compression_accepted.store(true, Ordering::Relaxed);
+                }
+                Ok(response.into_inner())
+            },
+            Some(DEFAULT_RETRY_TIMEOUT),
+            "requesting proof",
+        )
+        .await?;
         
         // Log explorer URL if provided
         if !inner.explorer_url.is_empty() {
@@ -425,22 +658,482 @@ impl NetworkProver {
         Ok(inner.request_id)
     }
 
-    async fn fetch_status(&self, request_id: &str) -> Result<GetProofStatusResponse> {
+    async fn fetch_status(
+        &self,
+        request_id: &str,
+        omit_proof: bool,
+    ) -> Result<GetProofStatusResponse> {
+        let request = GetProofStatusRequest { request_id: request_id.to_string(), omit_proof };
+        if self.transport == Transport::Rest {
+            return RestClient::new(&self.endpoint)?
+                .get_proof_status(&request)
+                .await
+                .context("network status request failed");
+        }
         let mut client = self.client().await?;
         let response = client
-            .get_proof_status(Request::new(GetProofStatusRequest {
-                request_id: request_id.to_string(),
-            }))
+            .get_proof_status(Request::new(request))
             .await
             .context("network status request failed")?;
         Ok(response.into_inner())
     }
 
+    /// Gets the current status of a proof request.
+    ///
+    /// # Details
+    /// The `request_id` is the identifier returned by the network when the request was submitted.
+    /// If the request has succeeded, the response also carries the serialized proof.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::ProverClient;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = ProverClient::builder().network().build();
+    /// let status = client.get_proof_status("0x1234").await.unwrap();
+    /// # });
+    /// ```
+    pub async fn get_proof_status(&self, request_id: &str) -> Result<GetProofStatusResponse> {
+        self.fetch_status(request_id, false).await
+    }
+
+    /// Gets the signed receipt of a request that has already succeeded, and verifies it against
+    /// the network receipt key.
+    ///
+    /// # Details
+    /// The receipt records the program, the digest of the public values, the cycles and the price
+    /// of the request, see [`crate::network::receipt`]. The network receipt key must be set with
+    /// [`NetworkProverBuilder::receipt_key`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::ProverClient;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = ProverClient::builder().network().receipt_key([0; 32]).build();
+    /// let receipt = client.get_receipt("0x1234").await.unwrap();
+    /// println!("charged {} for {} cycles", receipt.price, receipt.cycles);
+    /// # });
+    /// ```
+    ///
+    /// [`NetworkProverBuilder::receipt_key`]: crate::network::builder::NetworkProverBuilder::receipt_key
+    pub async fn get_receipt(&self, request_id: &str) -> Result<ProofReceipt> {
+        let network_key =
+            self.receipt_key.ok_or_else(|| anyhow!("verifying receipts requires a receipt key"))?;
+        let status = self.fetch_status(request_id, true).await?;
+        ensure_succeeded(request_id, &status)?;
+        let signed = status
+            .receipt
+            .ok_or_else(|| anyhow!("the network returned no receipt for request {request_id}"))?;
+        let receipt = verify_receipt(&signed, &network_key)?;
+        if receipt.request_id != request_id {
+            return Err(anyhow!(
+                "the network returned the receipt of request {} for request {request_id}",
+                receipt.request_id
+            ));
+        }
+        Ok(receipt)
+    }
+
+    /// Downloads the proof of a request that has already succeeded.
+    ///
+    /// # Details
+    /// Unlike [`NetworkProver::wait_proof`], this method does not poll: it returns an error if
+    /// the request has not succeeded yet.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::ProverClient;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = ProverClient::builder().network().build();
+    /// let proof = client.download_proof("0x1234").await.unwrap();
+    /// proof.save("proof.bin").unwrap();
+    /// # });
+    /// ```
+    pub async fn download_proof(&self, request_id: &str) -> Result<MONEROCHANProofWithPublicValues> {
+        let status = self.fetch_status(request_id, false).await?;
+        ensure_succeeded(request_id, &status)?;
+        decode_network_proof(&status.proof)
+    }
+
+    /// Downloads the proof of a request that has already succeeded to a file.
+    ///
+    /// # Details
+    /// The proof is streamed to disk in chunks rather than buffered in memory, which matters for
+    /// large Core proofs. The file is only created once the download completes, and can be read
+    /// with [`MONEROCHANProofWithPublicValues::load`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{MONEROCHANProofWithPublicValues, ProverClient};
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = ProverClient::builder().network().build();
+    /// client.download_proof_to_file("0x1234", "proof.bin").await.unwrap();
+    /// let proof = MONEROCHANProofWithPublicValues::load("proof.bin").unwrap();
+    /// # });
+    /// ```
+    pub async fn download_proof_to_file(
+        &self,
+        request_id: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        let status = self.fetch_status(request_id, true).await?;
+        ensure_succeeded(request_id, &status)?;
+        self.stream_proof_to_file(request_id, path.as_ref()).await
+    }
+
+    /// Streams the proof of a succeeded request to a file, through a `.part` file so that an
+    /// interrupted download never leaves a truncated proof behind.
+    async fn stream_proof_to_file(&self, request_id: &str, path: &Path) -> Result<()> {
+        let mut part = path.as_os_str().to_owned();
+        part.push(".part");
+        let part = PathBuf::from(part);
+
+        let download = async {
+            let mut file = tokio::fs::File::create(&part)
+                .await
+                .with_context(|| format!("failed to create {}", part.display()))?;
+            let failed = "network proof download failed";
+            // The REST transport has no streaming, so the proof comes whole with the status.
+            if self.transport == Transport::Rest {
+                let status = self.fetch_status(request_id, false).await.context(failed)?;
+                file.write_all(&status.proof).await?;
+                file.flush().await?;
+                file.sync_all().await?;
+                return Ok(());
+            }
+            let mut client = self.client().await?;
+            let mut chunks = client
+                .get_proof_chunk(Request::new(GetProofChunkRequest {
+                    request_id: request_id.to_string(),
+                    offset: 0,
+                }))
+                .await
+                .context(failed)?
+                .into_inner();
+
+            let mut written = 0;
+            let mut total_size = None;
+            while let Some(chunk) = chunks.message().await.context(failed)? {
+                if chunk.offset != written {
+                    return Err(anyhow!("network sent the proof chunks out of order"));
+                }
+                file.write_all(&chunk.data).await?;
+                written += chunk.data.len() as u64;
+                total_size = Some(chunk.total_size);
+            }
+            if total_size != Some(written) {
+                return Err(anyhow!("network proof download ended after {written} bytes"));
+            }
+            file.flush().await?;
+            file.sync_all().await?;
+            Ok::<_, anyhow::Error>(())
+        };
+
+        if let Err(err) = download.await {
+            let _ = tokio::fs::remove_file(&part).await;
+            return Err(err);
+        }
+        tokio::fs::rename(&part, path)
+            .await
+            .with_context(|| format!("failed to write proof to {}", path.display()))
+    }
+
+    /// Cancels a pending or running proof request.
+    ///
+    /// # Details
+    /// Only the client that submitted the request can cancel it, so the network private key must
+    /// be configured. Returns `false` if the request had already finished.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::ProverClient;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = ProverClient::builder().network().build();
+    /// let cancelled = client.cancel_request("0x1234").await.unwrap();
+    /// # });
+    /// ```
+    pub async fn cancel_request(&self, request_id: &str) -> Result<bool> {
+        let (client_address, client_auth) = self.client_credentials().await?;
+        if client_auth.is_none() {
+            return Err(anyhow!(
+                "cancelling a request requires MONEROCHAN_NETWORK_PRIVATE_KEY or an API key"
+            ));
+        }
+
+        let mut client = self.client().await?;
+        let response = client
+            .cancel_proof(Request::new(CancelProofRequest {
+                request_id: request_id.to_string(),
+                client_address,
+                client_auth,
+            }))
+            .await
+            .context("network cancel request failed")?;
+        Ok(response.into_inner().cancelled)
+    }
+
+    /// Lists the proof requests submitted by the configured client key, oldest first.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::ProverClient;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = ProverClient::builder().network().build();
+    /// let requests = client.list_requests().await.unwrap();
+    /// # });
+    /// ```
+    pub async fn list_requests(&self) -> Result<Vec<ProofRequestSummary>> {
+        self.list_requests_filtered(&RequestFilter::default()).await
+    }
+
+    /// Lists the proof requests submitted by the configured client key that carry all of the
+    /// given labels, see [`NetworkProveBuilder::label`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use std::collections::HashMap;
+    ///
+    /// use monerochan::ProverClient;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = ProverClient::builder().network().build();
+    /// let labels = HashMap::from([("team".to_string(), "bridge".to_string())]);
+    /// let requests = client.list_requests_with_labels(labels).await.unwrap();
+    /// # });
+    /// ```
+    pub async fn list_requests_with_labels(
+        &self,
+        labels: HashMap<String, String>,
+    ) -> Result<Vec<ProofRequestSummary>> {
+        self.list_requests_filtered(&RequestFilter { labels, ..RequestFilter::default() }).await
+    }
+
+    /// Lists the proof requests submitted by the configured client key that pass the filter,
+    /// oldest first, fetching every page.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{
+    ///     network::{proto::api::JobStatus, prover::RequestFilter},
+    ///     ProverClient,
+    /// };
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = ProverClient::builder().network().build();
+    /// let filter = RequestFilter {
+    ///     statuses: vec![JobStatus::Pending, JobStatus::Running],
+    ///     ..Default::default()
+    /// };
+    /// let pending = client.list_requests_filtered(&filter).await.unwrap();
+    /// # });
+    /// ```
+    pub async fn list_requests_filtered(
+        &self,
+        filter: &RequestFilter,
+    ) -> Result<Vec<ProofRequestSummary>> {
+        let mut requests = Vec::new();
+        let mut page_token = None;
+        loop {
+            let page = self.list_requests_page(filter, 0, page_token.as_deref()).await?;
+            requests.extend(page.requests);
+            match page.next_page_token {
+                Some(next_page_token) => page_token = Some(next_page_token),
+                None => return Ok(requests),
+            }
+        }
+    }
+
+    /// Lists one page of the proof requests submitted by the configured client key that pass the
+    /// filter, oldest first.
+    ///
+    /// # Details
+    /// A `page_size` of zero lets the network choose it. The first page is requested without a
+    /// `page_token`, and the following ones with the [`RequestPage::next_page_token`] of the
+    /// previous page.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{network::prover::RequestFilter, ProverClient};
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = ProverClient::builder().network().build();
+    /// let filter = RequestFilter::default();
+    /// let page = client.list_requests_page(&filter, 50, None).await.unwrap();
+    /// if let Some(token) = page.next_page_token {
+    ///     let next = client.list_requests_page(&filter, 50, Some(&token)).await.unwrap();
+    /// }
+    /// # });
+    /// ```
+    pub async fn list_requests_page(
+        &self,
+        filter: &RequestFilter,
+        page_size: u32,
+        page_token: Option<&str>,
+    ) -> Result<RequestPage> {
+        let (client_address, client_auth) = self.client_credentials().await?;
+        if client_auth.is_none() {
+            return Err(anyhow!(
+                "listing requests requires MONEROCHAN_NETWORK_PRIVATE_KEY or an API key"
+            ));
+        }
+
+        let mut client = self.client().await?;
+        let response = client
+            .list_proof_requests(Request::new(ListProofRequestsRequest {
+                client_address,
+                client_auth,
+                labels: filter.labels.clone(),
+                statuses: filter.statuses.iter().map(|status| *status as i32).collect(),
+                program_id: filter.program_id.clone(),
+                created_after: filter.created_after,
+                created_before: filter.created_before,
+                page_size,
+                page_token: page_token.unwrap_or_default().to_string(),
+            }))
+            .await
+            .context("network list request failed")?
+            .into_inner();
+        Ok(RequestPage {
+            requests: response.requests,
+            next_page_token: Some(response.next_page_token).filter(|token| !token.is_empty()),
+        })
+    }
+
+    /// Lists the programs the configured client key has requested proofs for.
+    ///
+    /// # Details
+    /// Each program comes with its metadata: the vk hash it was requested with, the size of its
+    /// ELF, when it was first and last requested, its proof counts and whether it is pinned.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::ProverClient;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = ProverClient::builder().network().build();
+    /// for program in client.list_programs().await.unwrap() {
+    ///     println!("{}: {} proofs", program.program_id, program.proofs);
+    /// }
+    /// # });
+    /// ```
+    pub async fn list_programs(&self) -> Result<Vec<ProgramInfo>> {
+        let (client_address, client_auth) = self.client_credentials().await?;
+        if client_auth.is_none() {
+            return Err(anyhow!(
+                "listing programs requires MONEROCHAN_NETWORK_PRIVATE_KEY or an API key"
+            ));
+        }
+
+        let mut client = self.client().await?;
+        let response = client
+            .list_programs(Request::new(ListProgramsRequest { client_address, client_auth }))
+            .await
+            .context("network list programs request failed")?;
+        Ok(response.into_inner().programs)
+    }
+
+    /// Gets the metadata of a program the configured client key has requested proofs for.
+    ///
+    /// # Details
+    /// The `program_id` is the vk hash the program was requested with, as listed by
+    /// [`NetworkProver::list_programs`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::ProverClient;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = ProverClient::builder().network().build();
+    /// let program = client.get_program("0x1234").await.unwrap();
+    /// # });
+    /// ```
+    pub async fn get_program(&self, program_id: &str) -> Result<ProgramInfo> {
+        let (client_address, client_auth) = self.client_credentials().await?;
+        if client_auth.is_none() {
+            return Err(anyhow!(
+                "getting a program requires MONEROCHAN_NETWORK_PRIVATE_KEY or an API key"
+            ));
+        }
+
+        let mut client = self.client().await?;
+        let response = client
+            .get_program(Request::new(GetProgramRequest {
+                program_id: program_id.to_string(),
+                client_address,
+                client_auth,
+            }))
+            .await
+            .context("network get program request failed")?;
+        response.into_inner().program.ok_or_else(|| anyhow!("network returned no program"))
+    }
+
+    /// Pins a program, so the network retains it regardless of its retention policy.
+    ///
+    /// # Details
+    /// Pins are kept per client, so the network private key or an API key must be configured, and
+    /// only the programs the client has requested proofs for can be pinned.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::ProverClient;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = ProverClient::builder().network().build();
+    /// client.pin_program("0x1234").await.unwrap();
+    /// # });
+    /// ```
+    pub async fn pin_program(&self, program_id: &str) -> Result<()> {
+        self.set_pinned(program_id, true).await
+    }
+
+    /// Unpins a program pinned with [`NetworkProver::pin_program`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::ProverClient;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = ProverClient::builder().network().build();
+    /// client.unpin_program("0x1234").await.unwrap();
+    /// # });
+    /// ```
+    pub async fn unpin_program(&self, program_id: &str) -> Result<()> {
+        self.set_pinned(program_id, false).await
+    }
+
+    async fn set_pinned(&self, program_id: &str, pinned: bool) -> Result<()> {
+        let (client_address, client_auth) = self.client_credentials().await?;
+        if client_auth.is_none() {
+            return Err(anyhow!(
+                "pinning a program requires MONEROCHAN_NETWORK_PRIVATE_KEY or an API key"
+            ));
+        }
+
+        let mut client = self.client().await?;
+        client
+            .pin_program(Request::new(PinProgramRequest {
+                program_id: program_id.to_string(),
+                pinned,
+                client_address,
+                client_auth,
+            }))
+            .await
+            .context("network pin request failed")?;
+        Ok(())
+    }
+
     /// Wait until the network returns a completed proof or an error.
     ///
     /// # Details
     /// This method polls the network until the proof request completes or times out.
-    /// The `request_id` should be obtained from a previous `request_async()` call.
+    /// The `request_id` should be obtained from a previous `request_async()` call. If that call
+    /// simulated the program, the proof must commit the simulated public values, or
+    /// [`Error::PublicValuesMismatch`] is returned.
     ///
     /// # Example
     /// ```rust,no_run
@@ -464,7 +1157,45 @@ impl NetworkProver {
         auction_timeout: Option<Duration>,
     ) -> Result<MONEROCHANProofWithPublicValues> {
         let request_id_str = format!("0x{}", hex::encode(request_id.as_slice()));
-        self.wait_for_proof(&request_id_str, timeout, auction_timeout).await
+        let proof = self.wait_for_proof(&request_id_str, timeout, auction_timeout).await?;
+        let expected = self.simulated_public_values.lock().unwrap().remove(&request_id);
+        if let Some(expected) = expected {
+            check_public_values(&request_id_str, &expected, &proof)?;
+        }
+        Ok(proof)
+    }
+
+    /// Wait until the network returns a completed proof, and stream it to a file.
+    ///
+    /// # Details
+    /// This is [`NetworkProver::wait_proof`] for proofs too large to buffer in memory: the proof is
+    /// written to `path` in chunks as it is downloaded, and can be read back with
+    /// [`MONEROCHANProofWithPublicValues::load`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use monerochan::{Prover, ProverClient, MONEROCHANStdin};
+    ///
+    /// # tokio_test::block_on(async {
+    /// let elf = &[1, 2, 3];
+    /// let stdin = MONEROCHANStdin::new();
+    ///
+    /// let client = ProverClient::builder().network().build();
+    /// let (pk, vk) = client.setup(elf);
+    /// let request_id = client.prove(&pk, &stdin).request_async().await.unwrap();
+    /// client.wait_proof_to_file(request_id, "proof.bin", None, None).await.unwrap();
+    /// # });
+    /// ```
+    pub async fn wait_proof_to_file(
+        &self,
+        request_id: B256,
+        path: impl AsRef<Path>,
+        timeout: Option<Duration>,
+        auction_timeout: Option<Duration>,
+    ) -> Result<()> {
+        let request_id = format!("0x{}", hex::encode(request_id.as_slice()));
+        self.wait_for_success(&request_id, timeout, auction_timeout, true).await?;
+        self.stream_proof_to_file(&request_id, path.as_ref()).await
     }
 
     /// Wait until the network returns a completed proof or an error.
@@ -474,6 +1205,20 @@ impl NetworkProver {
         timeout: Option<Duration>,
         auction_timeout: Option<Duration>,
     ) -> Result<MONEROCHANProofWithPublicValues> {
+        let status = self.wait_for_success(request_id, timeout, auction_timeout, false).await?;
+        decode_network_proof(&status.proof)
+    }
+
+    /// Polls the network until the request succeeds, and returns its final status, without the
+    /// proof if `omit_proof` is set.
+    #[tracing::instrument(name = "network_wait", skip(self, timeout, auction_timeout))]
+    async fn wait_for_success(
+        &self,
+        request_id: &str,
+        timeout: Option<Duration>,
+        auction_timeout: Option<Duration>,
+        omit_proof: bool,
+    ) -> Result<GetProofStatusResponse> {
         let start = Instant::now();
         let mut pending_start: Option<Instant> = None;
 
@@ -487,17 +1232,9 @@ impl NetworkProver {
                 }
             }
 
-            let status = self.fetch_status(request_id).await?;
+            let status = self.fetch_status(request_id, omit_proof).await?;
             match JobStatus::try_from(status.status).ok() {
-                Some(JobStatus::Succeeded) => {
-                    if status.proof.is_empty() {
-                        return Err(anyhow!("network reported success but no proof was returned"));
-                    }
-                    // Network returns proof.bytes() from SP1 SDK, which is serialized ProofFromNetwork
-                    let proof_from_network: ProofFromNetwork =
-                        bincode::deserialize(&status.proof).context("failed to decode proof")?;
-                    return Ok(proof_from_network.into());
-                }
+                Some(JobStatus::Succeeded) => return Ok(status),
                 Some(JobStatus::Failed) => {
                     let err = if status.error_message.is_empty() {
                         "network job failed".to_string()
@@ -529,7 +1266,55 @@ impl NetworkProver {
         }
     }
 
+    /// Executes a program on the network and returns the committed public values, the cycle
+    /// count and the gas, without generating a proof.
+    pub(crate) async fn execute_only_impl(
+        &self,
+        elf: &[u8],
+        stdin: &MONEROCHANStdin,
+        timeout: Option<Duration>,
+        cycle_limit: Option<u64>,
+        gas_limit: Option<u64>,
+        idempotency_key: Option<String>,
+        labels: HashMap<String, String>,
+    ) -> Result<NetworkExecution> {
+        let stdin_bytes =
+            bincode::serialize(stdin).context("failed to serialize stdin for API request")?;
+
+        let request = RequestProofRequest {
+            elf: elf.to_vec(),
+            stdin: stdin_bytes,
+            proof_mode: NetworkApiProofMode::ExecuteOnly as i32,
+            strategy: NetworkApiFulfillmentStrategy::Hosted as i32,
+            timeout_secs: timeout.map(|value| value.as_secs()),
+            cycle_limit,
+            gas_limit,
+            // Signed by request_proof, anew for each attempt.
+            client_address: None,
+            client_auth: None,
+            idempotency_key: Some(idempotency_key.unwrap_or_else(new_idempotency_key)),
+            labels,
+            ..Default::default()
+        };
+
+        let request_id = self.request_proof(request).await?;
+        let status = self.wait_for_success(&request_id, timeout, None, false).await?;
+        let execution = status
+            .execution
+            .ok_or_else(|| anyhow!("network reported success but no execution was returned"))?;
+        Ok(NetworkExecution {
+            public_values: MONEROCHANPublicValues::from(execution.public_values.as_slice()),
+            cycles: execution.cycles,
+            gas: execution.gas,
+        })
+    }
+
     #[allow(clippy::too_many_arguments)]
+    #[tracing::instrument(
+        name = "network_prove",
+        skip_all,
+        fields(mode = ?mode, vk_hash = %pk.vk.bytes32(), request_id = tracing::field::Empty)
+    )]
     async fn prove_via_api(
         &self,
         pk: &MONEROCHANProvingKey,
@@ -548,6 +1333,9 @@ impl NetworkProver {
         treasury: Option<Address>,
         max_price_per_pgu: Option<u64>,
         auction_timeout: Option<Duration>,
+        idempotency_key: Option<String>,
+        labels: HashMap<String, String>,
+        public_values_hash: Option<[u8; 32]>,
     ) -> Result<MONEROCHANProofWithPublicValues> {
         let stdin_bytes =
             bincode::serialize(stdin).context("failed to serialize stdin for API request")?;
@@ -555,44 +1343,6 @@ impl NetworkProver {
         let whitelist_bytes =
             whitelist.unwrap_or_default().into_iter().map(|address| address.to_vec()).collect();
 
-        // Check for Solana private key from env var and create client auth if so
-        #[cfg(feature = "network")]
-        let (client_address, client_auth) = {
-            // Check MONEROCHAN_NETWORK_PRIVATE_KEY first, then fall back to BASE_PRIVATE_KEY
-            let private_key_str = std::env::var("MONEROCHAN_NETWORK_PRIVATE_KEY")
-                .ok()
-                .or_else(|| std::env::var("BASE_PRIVATE_KEY").ok());
-
-            let private_key_bytes = private_key_str.and_then(|solana_key_str| {
-                // Parse private key (support both hex and base58)
-                if solana_key_str.starts_with("0x") {
-                    hex::decode(&solana_key_str[2..]).ok()
-                } else {
-                    bs58::decode(&solana_key_str).into_vec().ok()
-                        .or_else(|| hex::decode(&solana_key_str).ok())
-                }
-            });
-            
-            if let Some(key_bytes) = private_key_bytes {
-                // Create client auth
-                let (job_id, nonce, timestamp, signature, addr) = 
-                    crate::network::solana_client_auth::create_client_auth(&key_bytes)?;
-                
-                let auth = Some(ClientAuth {
-                    job_id,
-                    nonce,
-                    timestamp,
-                    signature,
-                });
-                
-                (Some(addr), auth)
-            } else {
-                (None, None)
-            }
-        };
-        
-        #[cfg(not(feature = "network"))]
-        let (client_address, client_auth) = (None, None);
 
         let request = RequestProofRequest {
             program_id: format!("0x{}", hex::encode(pk.vk.bytes32())),
@@ -612,14 +1362,24 @@ impl NetworkProver {
             treasury: address_vec(treasury),
             max_price_per_pgu,
             auction_timeout_secs: auction_timeout.map(|value| value.as_secs()),
-            client_address,
-            client_auth,
+            // Signed by request_proof, anew for each attempt.
+            client_address: None,
+            client_auth: None,
+            idempotency_key: Some(idempotency_key.unwrap_or_else(new_idempotency_key)),
+            compression: Compression::None as i32,
+            labels,
+            public_values_hash: public_values_hash.map(Vec::from),
         };
 
         let request_id = self.request_proof(request).await?;
+        tracing::Span::current().record("request_id", request_id.as_str());
         // Explorer URL is already logged by request_proof()
 
-        self.wait_for_proof(&request_id, timeout, auction_timeout).await
+        let proof = self.wait_for_proof(&request_id, timeout, auction_timeout).await?;
+        if let Some(expected) = public_values_hash {
+            check_public_values(&request_id, &expected, &proof)?;
+        }
+        Ok(proof)
     }
 
     // /// The cycle limit and gas limit are determined according to the following priority:
@@ -690,22 +1450,15 @@ impl NetworkProver {
 
 }
 
-impl Prover<CpuProverComponents> for NetworkProver {
-    fn setup(&self, elf: &[u8]) -> (MONEROCHANProvingKey, MONEROCHANVerifyingKey) {
-        self.prover.setup(elf)
-    }
-
-    fn inner(&self) -> &MONEROCHANProver {
-        self.prover.inner()
-    }
-
-    fn prove(
+#[async_trait]
+impl AsyncProver<CpuProverComponents> for NetworkProver {
+    async fn prove_async(
         &self,
         pk: &MONEROCHANProvingKey,
         stdin: &MONEROCHANStdin,
         mode: MONEROCHANProofMode,
     ) -> Result<MONEROCHANProofWithPublicValues> {
-        block_on(self.prove_impl(
+        self.prove_impl(
             pk,
             stdin,
             mode,
@@ -723,7 +1476,32 @@ impl Prover<CpuProverComponents> for NetworkProver {
             None,
             None,
             None,
-        ))
+            None,
+        )
+        .await
+    }
+}
+
+impl Prover<CpuProverComponents> for NetworkProver {
+    fn setup(&self, elf: &[u8]) -> (MONEROCHANProvingKey, MONEROCHANVerifyingKey) {
+        self.prover.setup(elf)
+    }
+
+    fn try_setup(&self, elf: &[u8]) -> Result<(MONEROCHANProvingKey, MONEROCHANVerifyingKey)> {
+        self.prover.try_setup(elf)
+    }
+
+    fn inner(&self) -> &MONEROCHANProver {
+        self.prover.inner()
+    }
+
+    fn prove(
+        &self,
+        pk: &MONEROCHANProvingKey,
+        stdin: &MONEROCHANStdin,
+        mode: MONEROCHANProofMode,
+    ) -> Result<MONEROCHANProofWithPublicValues> {
+        block_on(self.prove_async(pk, stdin, mode))
     }
 
     fn verify(
@@ -731,60 +1509,113 @@ impl Prover<CpuProverComponents> for NetworkProver {
         bundle: &MONEROCHANProofWithPublicValues,
         vkey: &MONEROCHANVerifyingKey,
     ) -> Result<(), crate::MONEROCHANVerificationError> {
-        if let Some(tee_proof) = &bundle.tee_proof {
-            if self.tee_signers.is_empty() {
-                return Err(crate::MONEROCHANVerificationError::Other(anyhow::anyhow!(
-                    "TEE integrity proof verification is enabled, but no TEE signers are provided"
-                )));
-            }
+        verify_network_proof(&self.prover, &self.tee_signers, bundle, vkey)
+    }
+}
 
-            let mut bytes = Vec::new();
+/// Verifies a proof returned by the network, with its TEE integrity proof if any.
+pub(crate) fn verify_network_proof(
+    prover: &CpuProver,
+    tee_signers: &Arc<TeeSignerRegistry>,
+    bundle: &MONEROCHANProofWithPublicValues,
+    vkey: &MONEROCHANVerifyingKey,
+) -> Result<(), crate::MONEROCHANVerificationError> {
+    if let Some(tee_proof) = &bundle.tee_proof {
+        if tee_signers.signers().is_empty() {
+            return Err(crate::MONEROCHANVerificationError::Other(anyhow::anyhow!(
+                "TEE integrity proof verification is enabled, but no TEE signers are provided"
+            )));
+        }
 
-            // Push the version hash.
-            let version_hash = alloy_primitives::keccak256(
-                crate::network::tee::MONEROCHAN_TEE_VERSION.to_le_bytes(),
-            );
-            bytes.extend_from_slice(version_hash.as_ref());
+        // Recover the signer, and check it against the registry, which may refresh its
+        // signers if the address is unknown.
+        let signer = monerochan_verifier::tee::recover_tee_signer(
+            tee_proof,
+            &vkey.bytes32_raw(),
+            bundle.public_values.as_slice(),
+        )
+        .map_err(|e| crate::MONEROCHANVerificationError::Other(e.into()))?;
+        let address = Address::from(signer);
+        // The signers are refreshed periodically and ahead of time by the async paths, see
+        // `NetworkProver::refresh_tee_signers`. An unknown signer may still follow a rotation
+        // the registry has not seen yet, so a stale registry is refreshed before rejecting.
+        if !tee_signers.is_trusted(&address) && tee_signers.is_stale(&address) {
+            crate::utils::block_on(tee_signers.refresh_if_stale(&address));
+        }
+        if !tee_signers.is_trusted(&address) {
+            return Err(crate::MONEROCHANVerificationError::Other(
+                monerochan_verifier::TeeError::UnknownSigner(signer).into(),
+            ));
+        }
+    }
 
-            // Push the vkey.
-            bytes.extend_from_slice(&vkey.bytes32_raw());
+    verify_proof(prover.inner(), prover.version(), bundle, vkey, None)
+}
 
-            // Push the public values hash.
-            let public_values_hash = alloy_primitives::keccak256(&bundle.public_values);
-            bytes.extend_from_slice(public_values_hash.as_ref());
+/// Reads the configured network private key.
+///
+/// `MONEROCHAN_NETWORK_PRIVATE_KEY` is checked first, then `BASE_PRIVATE_KEY`. Both hex and base58
+/// encodings are supported. Returns `None` if no key is configured.
+fn network_private_key() -> Option<Vec<u8>> {
+    std::env::var("MONEROCHAN_NETWORK_PRIVATE_KEY")
+        .ok()
+        .or_else(|| std::env::var("BASE_PRIVATE_KEY").ok())
+        .as_deref()
+        .and_then(decode_private_key)
+}
 
-            // Compute the message digest.
-            let message_digest = alloy_primitives::keccak256(&bytes);
+/// Decodes a Solana private key, given either in hex (with or without `0x`) or in base58.
+pub(crate) fn decode_private_key(key: &str) -> Option<Vec<u8>> {
+    if let Some(hex_key) = key.strip_prefix("0x") {
+        hex::decode(hex_key).ok()
+    } else {
+        bs58::decode(key).into_vec().ok().or_else(|| hex::decode(key).ok())
+    }
+}
 
-            // Parse the signature.
-            let signature = k256::ecdsa::Signature::from_bytes(tee_proof[5..69].into())
-                .expect("Invalid signature");
-            // The recovery id is the last byte of the signature minus 27.
-            let recovery_id =
-                k256::ecdsa::RecoveryId::from_byte(tee_proof[4] - 27).expect("Invalid recovery id");
+/// Checks that a request has succeeded, for downloading its proof.
+fn ensure_succeeded(request_id: &str, status: &GetProofStatusResponse) -> Result<()> {
+    match JobStatus::try_from(status.status).ok() {
+        Some(JobStatus::Succeeded) => Ok(()),
+        Some(JobStatus::Failed) => {
+            Err(anyhow!("proof request {request_id} failed: {}", status.error_message))
+        }
+        Some(status) => Err(anyhow!(
+            "proof request {request_id} is not fulfilled yet ({})",
+            status.as_str_name()
+        )),
+        None => Err(anyhow!("unknown network job status")),
+    }
+}
 
-            // Recover the signer.
-            let signer = k256::ecdsa::VerifyingKey::recover_from_prehash(
-                message_digest.as_ref(),
-                &signature,
-                recovery_id,
-            )
-            .unwrap();
-            let address = alloy_primitives::Address::from_public_key(&signer);
-
-            // Verify the proof.
-            if self.tee_signers.contains(&address) {
-                verify_proof(self.prover.inner(), self.version(), bundle, vkey)
-            } else {
-                Err(crate::MONEROCHANVerificationError::Other(anyhow::anyhow!(
-                    "Invalid TEE proof, signed by unknown address {}",
-                    address
-                )))
-            }
-        } else {
-            verify_proof(self.prover.inner(), self.version(), bundle, vkey)
+/// Checks that a proof returned by the network commits the public values of the simulation.
+fn check_public_values(
+    request_id: &str,
+    expected: &[u8; 32],
+    proof: &MONEROCHANProofWithPublicValues,
+) -> Result<()> {
+    let found = public_values_digest(proof.public_values.as_slice());
+    if found != *expected {
+        return Err(Error::PublicValuesMismatch {
+            request_id: request_id.to_string(),
+            expected: *expected,
+            found,
         }
+        .into());
+    }
+    Ok(())
+}
+
+/// Decodes the proof bytes returned by the network.
+///
+/// The network returns a serialized [`ProofFromNetwork`], which lacks the TEE proof field.
+fn decode_network_proof(bytes: &[u8]) -> Result<MONEROCHANProofWithPublicValues> {
+    if bytes.is_empty() {
+        return Err(anyhow!("network reported success but no proof was returned"));
     }
+    let proof_from_network: ProofFromNetwork =
+        bincode::deserialize(bytes).context("failed to decode proof")?;
+    Ok(proof_from_network.into())
 }
 
 fn network_api_proof_mode(mode: MONEROCHANProofMode) -> NetworkApiProofMode {
@@ -805,7 +1636,70 @@ fn network_api_strategy(strategy: FulfillmentStrategy) -> NetworkApiFulfillmentS
     }
 }
 
+/// Returns the cycle limit of a request in a network mode: the given limit, lowered to the hard cap
+/// of the mode, or the hard cap itself.
+fn request_cycle_limit(network_mode: NetworkMode, cycle_limit: Option<u64>) -> u64 {
+    let cap = get_default_cycle_limit_for_mode(network_mode);
+    cycle_limit.map_or(cap, |limit| limit.min(cap))
+}
+
+/// Checks that a simulation executed at most the cycle limit of its request.
+fn check_cycles(cycles: u64, limit: u64) -> Result<(), Error> {
+    if cycles > limit {
+        return Err(Error::CycleLimitExceeded { cycles, limit });
+    }
+    Ok(())
+}
+
+/// Generates a key that identifies a proof request across retried submissions.
+fn new_idempotency_key() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
 fn address_vec(address: Option<Address>) -> Vec<u8> {
     address.map(|addr| addr.as_slice().to_vec()).unwrap_or_default()
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MONEROCHANProof;
+
+    #[test]
+    fn test_check_public_values() {
+        let proof = MONEROCHANProofWithPublicValues {
+            proof: MONEROCHANProof::Core(vec![]),
+            public_values: MONEROCHANPublicValues::from(&[1, 2, 3]),
+            monerochan_version: String::new(),
+            tee_proof: None,
+        };
+
+        check_public_values("id", &public_values_digest(&[1, 2, 3]), &proof).unwrap();
+        let err = check_public_values("id", &public_values_digest(&[1, 2]), &proof).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<Error>(),
+            Some(Error::PublicValuesMismatch { request_id, .. }) if request_id == "id"
+        ));
+    }
+
+    #[test]
+    fn test_request_cycle_limit() {
+        use crate::network::{MAINNET_DEFAULT_CYCLE_LIMIT, RESERVED_DEFAULT_CYCLE_LIMIT};
+
+        assert_eq!(request_cycle_limit(NetworkMode::Reserved, None), RESERVED_DEFAULT_CYCLE_LIMIT);
+        assert_eq!(request_cycle_limit(NetworkMode::Mainnet, None), MAINNET_DEFAULT_CYCLE_LIMIT);
+        assert_eq!(request_cycle_limit(NetworkMode::Reserved, Some(1_000)), 1_000);
+        let above = Some(RESERVED_DEFAULT_CYCLE_LIMIT + 1);
+        assert_eq!(request_cycle_limit(NetworkMode::Reserved, above), RESERVED_DEFAULT_CYCLE_LIMIT);
+    }
+
+    #[test]
+    fn test_check_cycles() {
+        check_cycles(1_000, 1_000).unwrap();
+        assert!(matches!(
+            check_cycles(1_001, 1_000),
+            Err(Error::CycleLimitExceeded { cycles: 1_001, limit: 1_000 })
+        ));
+    }
+}
@@ -0,0 +1,111 @@
+//! # Proof Receipts
+//!
+//! A receipt is a statement signed by the network once it has fulfilled a proof request: which
+//! program it proved, the digest of the public values, the cycles it executed for and what the
+//! client was charged. Receipts are evidence for billing disputes and audits, and can be checked
+//! without a prover with [`verify_receipt`].
+//!
+//! The network signs the sha256 digest of [`RECEIPT_DOMAIN`] followed by the encoded
+//! [`ProofReceipt`] with Ed25519, and the encoded receipt is carried as is, so that the signed
+//! bytes never depend on how a client re-encodes the message.
+
+use anyhow::{anyhow, ensure, Context, Result};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use prost::Message;
+use sha2::{Digest, Sha256};
+
+pub use crate::network::proto::api::{ProofReceipt, SignedProofReceipt};
+
+/// The prefix of the signed message, so that a receipt signature is never valid for anything else.
+pub const RECEIPT_DOMAIN: &[u8] = b"monerochan-proof-receipt-v1";
+
+/// Returns the digest of the public values, as found in [`ProofReceipt::public_values_digest`].
+#[must_use]
+pub fn public_values_digest(public_values: &[u8]) -> [u8; 32] {
+    Sha256::digest(public_values).into()
+}
+
+/// Returns the digest signed for an encoded receipt.
+fn receipt_digest(receipt: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(RECEIPT_DOMAIN);
+    hasher.update(receipt);
+    hasher.finalize().into()
+}
+
+/// Signs a receipt with the Ed25519 private key of the network.
+#[must_use]
+pub fn sign_receipt(private_key: &[u8; 32], receipt: &ProofReceipt) -> SignedProofReceipt {
+    let signing_key = SigningKey::from_bytes(private_key);
+    let receipt = receipt.encode_to_vec();
+    let signature = signing_key.sign(&receipt_digest(&receipt));
+    SignedProofReceipt {
+        receipt,
+        signer: signing_key.verifying_key().to_bytes().to_vec(),
+        signature: signature.to_bytes().to_vec(),
+    }
+}
+
+/// Verifies that a receipt is signed by the network key, and returns the receipt.
+///
+/// # Arguments
+///
+/// * `signed` - The receipt, as returned in the status of a succeeded proof request.
+/// * `network_key` - The Ed25519 public key published by the network.
+pub fn verify_receipt(signed: &SignedProofReceipt, network_key: &[u8; 32]) -> Result<ProofReceipt> {
+    ensure!(
+        signed.signer == network_key,
+        "receipt signed by 0x{} instead of the network key 0x{}",
+        hex::encode(&signed.signer),
+        hex::encode(network_key)
+    );
+    let verifying_key =
+        VerifyingKey::from_bytes(network_key).context("invalid network receipt key")?;
+    let signature = Signature::from_slice(&signed.signature).context("invalid receipt signature")?;
+    verifying_key
+        .verify(&receipt_digest(&signed.receipt), &signature)
+        .map_err(|_| anyhow!("invalid receipt signature"))?;
+    ProofReceipt::decode(signed.receipt.as_slice()).context("invalid receipt")
+}
+
+impl ProofReceipt {
+    /// Whether the receipt is for an execution with the given public values.
+    #[must_use]
+    pub fn matches_public_values(&self, public_values: &[u8]) -> bool {
+        self.public_values_digest == public_values_digest(public_values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn receipt() -> ProofReceipt {
+        ProofReceipt {
+            request_id: "0x01".to_string(),
+            vk_hash: vec![7; 32],
+            public_values_digest: public_values_digest(b"public values").to_vec(),
+            cycles: 42,
+            price: 0,
+        }
+    }
+
+    #[test]
+    fn test_verify_receipt() {
+        let key = [1u8; 32];
+        let network_key = SigningKey::from_bytes(&key).verifying_key().to_bytes();
+        let signed = sign_receipt(&key, &receipt());
+
+        let verified = verify_receipt(&signed, &network_key).unwrap();
+        assert_eq!(verified, receipt());
+        assert!(verified.matches_public_values(b"public values"));
+        assert!(!verified.matches_public_values(b"other values"));
+
+        let other_key = SigningKey::from_bytes(&[2u8; 32]).verifying_key().to_bytes();
+        assert!(verify_receipt(&signed, &other_key).is_err());
+
+        let mut tampered = signed.clone();
+        tampered.receipt = ProofReceipt { cycles: 1, ..receipt() }.encode_to_vec();
+        assert!(verify_receipt(&tampered, &network_key).is_err());
+    }
+}
@@ -0,0 +1,367 @@
+//! # REST Transport
+//!
+//! A client for the network API which sends its requests as JSON over HTTPS, for networks which
+//! block gRPC or HTTP/2. The server is fronted by a gateway which transcodes the requests to gRPC,
+//! so every method is a `POST` to `{rpc_url}/monerochan.network.v1.Network/{Method}`, and the
+//! messages use the canonical proto3 JSON mapping: camel case field names, base64 bytes, enums by
+//! name and 64-bit integers as strings.
+//!
+//! Only requesting proofs and fetching their status and result are supported. Errors are
+//! returned as [`tonic::Status`], so that they are retried like the ones of the gRPC transport.
+
+use std::{collections::HashMap, time::Duration};
+
+use anyhow::Result;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+use serde_json::{json, Map, Value};
+use tonic::{Code, Status};
+
+use crate::network::proto::api::{
+    ExecutionResult, GetProofStatusRequest, GetProofStatusResponse, JobStatus,
+    RequestProofRequest, RequestProofResponse, SignedProofReceipt,
+};
+
+/// The path of the network API service, as routed by the gateway.
+const SERVICE_PATH: &str = "monerochan.network.v1.Network";
+
+/// A JSON-over-HTTPS client for the network API.
+pub(crate) struct RestClient {
+    http: reqwest::Client,
+    endpoint: String,
+}
+
+impl RestClient {
+    /// Creates a client for the gateway at `endpoint`, with the timeouts of the gRPC endpoint.
+    pub(crate) fn new(endpoint: &str) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(60))
+            .connect_timeout(Duration::from_secs(15))
+            .tcp_keepalive(Duration::from_secs(60))
+            .tcp_nodelay(true)
+            .build()?;
+        Ok(Self { http, endpoint: endpoint.trim_end_matches('/').to_string() })
+    }
+
+    /// Submits a proof request.
+    pub(crate) async fn request_proof(
+        &self,
+        request: &RequestProofRequest,
+    ) -> Result<RequestProofResponse> {
+        let response: RequestProofJson =
+            self.call("RequestProof", request_proof_json(request)).await?;
+        Ok(RequestProofResponse {
+            request_id: response.request_id,
+            job_id: response.job_id,
+            explorer_url: response.explorer_url,
+        })
+    }
+
+    /// Gets the status of a proof request, with its proof unless `omit_proof` is set.
+    pub(crate) async fn get_proof_status(
+        &self,
+        request: &GetProofStatusRequest,
+    ) -> Result<GetProofStatusResponse> {
+        let body = json!({ "requestId": request.request_id, "omitProof": request.omit_proof });
+        let response: ProofStatusJson = self.call("GetProofStatus", body).await?;
+        Ok(GetProofStatusResponse {
+            status: response.status,
+            proof: response.proof,
+            error_message: response.error_message,
+            request_id: response.request_id,
+            execution: response.execution.map(|execution| ExecutionResult {
+                public_values: execution.public_values,
+                cycles: execution.cycles,
+                gas: execution.gas,
+            }),
+            labels: response.labels,
+            receipt: response.receipt.map(|receipt| SignedProofReceipt {
+                receipt: receipt.receipt,
+                signer: receipt.signer,
+                signature: receipt.signature,
+            }),
+        })
+    }
+
+    /// Calls a method of the network API.
+    async fn call<T: DeserializeOwned>(&self, method: &str, body: Value) -> Result<T, Status> {
+        let url = format!("{}/{SERVICE_PATH}/{method}", self.endpoint);
+        let response = self
+            .http
+            .post(&url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|err| transport_status(&url, &err))?;
+
+        let status = response.status();
+        let bytes = response.bytes().await.map_err(|err| transport_status(&url, &err))?;
+        if !status.is_success() {
+            return Err(error_status(status, &bytes));
+        }
+        serde_json::from_slice(&bytes).map_err(|err| {
+            Status::internal(format!("invalid {method} response from {url}: {err}"))
+        })
+    }
+}
+
+/// Encodes a proof request as JSON, leaving out the optional fields which are not set.
+fn request_proof_json(request: &RequestProofRequest) -> Value {
+    let mut body = json!({
+        "programId": request.program_id,
+        "elf": BASE64.encode(&request.elf),
+        "stdin": BASE64.encode(&request.stdin),
+        "proofMode": request.proof_mode().as_str_name(),
+        "strategy": request.strategy().as_str_name(),
+        "skipSimulation": request.skip_simulation,
+        "minAuctionPeriod": request.min_auction_period.to_string(),
+        "whitelist": request.whitelist.iter().map(|a| BASE64.encode(a)).collect::<Vec<_>>(),
+        "auctioneer": BASE64.encode(&request.auctioneer),
+        "executor": BASE64.encode(&request.executor),
+        "verifier": BASE64.encode(&request.verifier),
+        "treasury": BASE64.encode(&request.treasury),
+        "compression": request.compression().as_str_name(),
+        "labels": request.labels,
+    });
+
+    let fields = body.as_object_mut().expect("the request is an object");
+    let optional_u64s = [
+        ("timeoutSecs", request.timeout_secs),
+        ("cycleLimit", request.cycle_limit),
+        ("gasLimit", request.gas_limit),
+        ("maxPricePerPgu", request.max_price_per_pgu),
+        ("auctionTimeoutSecs", request.auction_timeout_secs),
+    ];
+    for (name, value) in optional_u64s {
+        insert(fields, name, value.map(|value| value.to_string()));
+    }
+    insert(fields, "clientAddress", request.client_address.clone());
+    insert(fields, "idempotencyKey", request.idempotency_key.clone());
+    let public_values_hash = request.public_values_hash.as_ref().map(|hash| BASE64.encode(hash));
+    insert(fields, "publicValuesHash", public_values_hash);
+    insert(
+        fields,
+        "clientAuth",
+        request.client_auth.as_ref().map(|auth| {
+            let mut auth_json = json!({
+                "jobId": auth.job_id,
+                "nonce": auth.nonce,
+                "timestamp": auth.timestamp.to_string(),
+                "signature": BASE64.encode(&auth.signature),
+            });
+            if !auth.api_key.is_empty() {
+                auth_json["apiKey"] = json!(auth.api_key);
+            }
+            auth_json
+        }),
+    );
+    body
+}
+
+/// Inserts a field into a JSON object if it is set.
+fn insert(fields: &mut Map<String, Value>, name: &str, value: Option<impl Into<Value>>) {
+    if let Some(value) = value {
+        fields.insert(name.to_string(), value.into());
+    }
+}
+
+/// Converts a failure to reach the gateway to a status, retried as an unavailable network.
+fn transport_status(url: &str, err: &reqwest::Error) -> Status {
+    let code = if err.is_timeout() { Code::DeadlineExceeded } else { Code::Unavailable };
+    Status::new(code, format!("failed to reach network endpoint {url}: {err}"))
+}
+
+/// Converts an error response of the gateway to a status.
+///
+/// The gateway returns the gRPC status of the server as `{"code": .., "message": ..}`. Other
+/// errors, for example from a proxy in front of the gateway, are mapped from their HTTP status.
+fn error_status(status: reqwest::StatusCode, body: &[u8]) -> Status {
+    #[derive(Deserialize)]
+    struct ErrorJson {
+        code: i32,
+        #[serde(default)]
+        message: String,
+    }
+
+    if let Ok(error) = serde_json::from_slice::<ErrorJson>(body) {
+        return Status::new(Code::from(error.code), error.message);
+    }
+
+    let code = match status.as_u16() {
+        400 => Code::InvalidArgument,
+        401 => Code::Unauthenticated,
+        403 => Code::PermissionDenied,
+        404 => Code::NotFound,
+        409 => Code::AlreadyExists,
+        429 => Code::ResourceExhausted,
+        500 => Code::Internal,
+        501 => Code::Unimplemented,
+        502 | 503 => Code::Unavailable,
+        504 => Code::DeadlineExceeded,
+        _ => Code::Unknown,
+    };
+    let body = String::from_utf8_lossy(body);
+    Status::new(code, format!("network endpoint returned {status}: {body}"))
+}
+
+/// A [`RequestProofResponse`] in JSON.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct RequestProofJson {
+    #[serde(alias = "request_id")]
+    request_id: String,
+    #[serde(alias = "job_id")]
+    job_id: String,
+    #[serde(alias = "explorer_url")]
+    explorer_url: String,
+}
+
+/// A [`GetProofStatusResponse`] in JSON.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct ProofStatusJson {
+    #[serde(deserialize_with = "job_status")]
+    status: i32,
+    #[serde(deserialize_with = "base64_bytes")]
+    proof: Vec<u8>,
+    #[serde(alias = "error_message")]
+    error_message: String,
+    #[serde(alias = "request_id")]
+    request_id: String,
+    execution: Option<ExecutionJson>,
+    labels: HashMap<String, String>,
+    receipt: Option<ReceiptJson>,
+}
+
+/// An [`ExecutionResult`] in JSON.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct ExecutionJson {
+    #[serde(alias = "public_values", deserialize_with = "base64_bytes")]
+    public_values: Vec<u8>,
+    #[serde(deserialize_with = "u64_value")]
+    cycles: u64,
+    #[serde(deserialize_with = "optional_u64_value")]
+    gas: Option<u64>,
+}
+
+/// A [`SignedProofReceipt`] in JSON.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ReceiptJson {
+    #[serde(deserialize_with = "base64_bytes")]
+    receipt: Vec<u8>,
+    #[serde(deserialize_with = "base64_bytes")]
+    signer: Vec<u8>,
+    #[serde(deserialize_with = "base64_bytes")]
+    signature: Vec<u8>,
+}
+
+/// Deserializes a [`JobStatus`], given by name or by number.
+fn job_status<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i32, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::String(name) => JobStatus::from_str_name(&name)
+            .map(|status| status as i32)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown job status {name}"))),
+        Value::Number(number) => number
+            .as_i64()
+            .and_then(|number| i32::try_from(number).ok())
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid job status {number}"))),
+        Value::Null => Ok(JobStatus::Unspecified as i32),
+        value => Err(serde::de::Error::custom(format!("invalid job status {value}"))),
+    }
+}
+
+/// Deserializes base64 bytes.
+fn base64_bytes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let encoded = Option::<String>::deserialize(deserializer)?.unwrap_or_default();
+    BASE64.decode(encoded).map_err(serde::de::Error::custom)
+}
+
+/// Deserializes a 64-bit integer, given as a string or as a number.
+fn u64_value<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    Ok(optional_u64_value(deserializer)?.unwrap_or_default())
+}
+
+/// Deserializes an optional 64-bit integer, given as a string or as a number.
+fn optional_u64_value<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::String(value) => value.parse().map(Some).map_err(serde::de::Error::custom),
+        Value::Number(number) => number
+            .as_u64()
+            .map(Some)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid integer {number}"))),
+        Value::Null => Ok(None),
+        value => Err(serde::de::Error::custom(format!("invalid integer {value}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::proto::api::{ClientAuth, Compression, ProofMode};
+
+    #[test]
+    fn test_request_proof_json() {
+        let request = RequestProofRequest {
+            program_id: "0x01".to_string(),
+            elf: vec![1, 2, 3],
+            proof_mode: ProofMode::Groth16 as i32,
+            cycle_limit: Some(u64::MAX),
+            client_auth: Some(ClientAuth {
+                timestamp: 7,
+                signature: vec![0xff],
+                ..Default::default()
+            }),
+            compression: Compression::Zstd as i32,
+            public_values_hash: Some(vec![0xff; 2]),
+            ..Default::default()
+        };
+
+        let body = request_proof_json(&request);
+        assert_eq!(body["elf"], "AQID");
+        assert_eq!(body["proofMode"], "PROOF_MODE_GROTH16");
+        assert_eq!(body["compression"], "COMPRESSION_ZSTD");
+        assert_eq!(body["cycleLimit"], u64::MAX.to_string());
+        assert_eq!(body["clientAuth"]["timestamp"], "7");
+        assert_eq!(body["clientAuth"]["signature"], "/w==");
+        assert_eq!(body["publicValuesHash"], "//8=");
+        assert!(body.get("gasLimit").is_none());
+        assert!(body.get("idempotencyKey").is_none());
+    }
+
+    #[test]
+    fn test_proof_status_json() {
+        let status: ProofStatusJson = serde_json::from_str(
+            r#"{"status": "JOB_STATUS_SUCCEEDED", "proof": "AQID", "requestId": "id",
+                "execution": {"publicValues": "BA==", "cycles": "42", "gas": 5}}"#,
+        )
+        .unwrap();
+        assert_eq!(status.status, JobStatus::Succeeded as i32);
+        assert_eq!(status.proof, [1, 2, 3]);
+        assert_eq!(status.request_id, "id");
+        let execution = status.execution.unwrap();
+        assert_eq!(
+            (execution.public_values, execution.cycles, execution.gas),
+            (vec![4], 42, Some(5))
+        );
+
+        let status: ProofStatusJson =
+            serde_json::from_str(r#"{"status": 4, "error_message": "out of gas"}"#).unwrap();
+        assert_eq!(status.status, JobStatus::Failed as i32);
+        assert_eq!(status.error_message, "out of gas");
+        assert!(status.proof.is_empty());
+    }
+
+    #[test]
+    fn test_error_status() {
+        let status = error_status(
+            reqwest::StatusCode::NOT_FOUND,
+            br#"{"code": 14, "message": "prover is down"}"#,
+        );
+        assert_eq!((status.code(), status.message()), (Code::Unavailable, "prover is down"));
+
+        let status = error_status(reqwest::StatusCode::BAD_GATEWAY, b"<html>bad gateway</html>");
+        assert_eq!(status.code(), Code::Unavailable);
+    }
+}
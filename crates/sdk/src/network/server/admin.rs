@@ -0,0 +1,175 @@
+//! # Admin API
+//!
+//! The administration API of a [`ProvingService`](super::ProvingService), served alongside the
+//! network API when the service has an admin token. Every call must carry the token in an
+//! `authorization: Bearer <token>` header.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use tonic::{service::Interceptor, Request, Response, Status};
+
+use super::{
+    internal,
+    quota::{Quota, Quotas, Usage},
+    Job, JobStore,
+};
+use crate::network::proto::admin::{
+    self, admin_server::Admin, ClientUsage, GetUsageRequest, GetUsageResponse,
+};
+
+/// The admin API of a service.
+#[derive(Clone)]
+pub struct AdminService {
+    store: Arc<dyn JobStore>,
+    quotas: Arc<Quotas>,
+}
+
+impl AdminService {
+    pub(crate) fn new(store: Arc<dyn JobStore>, quotas: Arc<Quotas>) -> Self {
+        Self { store, quotas }
+    }
+}
+
+/// Rejects the calls that do not carry the admin token.
+#[derive(Clone)]
+pub struct AdminAuth {
+    token: String,
+}
+
+impl AdminAuth {
+    pub(crate) fn new(token: String) -> Self {
+        Self { token }
+    }
+}
+
+impl Interceptor for AdminAuth {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let token = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        match token {
+            Some(token) if constant_time_eq(token.as_bytes(), self.token.as_bytes()) => Ok(request),
+            _ => Err(Status::unauthenticated("invalid admin token")),
+        }
+    }
+}
+
+/// Compares two byte strings in a time that does not depend on where they differ.
+pub(super) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn quota_to_proto(quota: Quota) -> admin::Quota {
+    admin::Quota {
+        max_proofs: quota.max_proofs,
+        max_cycles: quota.max_cycles,
+        max_proving_time_secs: quota.max_proving_time_secs,
+        max_pending: quota.max_pending,
+    }
+}
+
+#[tonic::async_trait]
+impl Admin for AdminService {
+    async fn get_usage(
+        &self,
+        request: Request<GetUsageRequest>,
+    ) -> Result<Response<GetUsageResponse>, Status> {
+        let request = request.into_inner();
+        let since = request.since.unwrap_or_else(|| self.quotas.period_start());
+
+        let jobs = self.store.list(request.client_address.as_deref()).await.map_err(internal)?;
+        let mut by_client: BTreeMap<Option<String>, Vec<Job>> = BTreeMap::new();
+        if request.client_address.is_some() {
+            // A client without jobs is reported with no usage.
+            by_client.insert(request.client_address.clone(), Vec::new());
+        }
+        for job in jobs {
+            by_client.entry(job.client_address.clone()).or_default().push(job);
+        }
+
+        let clients = by_client
+            .into_iter()
+            .map(|(client_address, jobs)| {
+                let usage = Usage::of(&jobs, since);
+                let quota = self.quotas.quota(client_address.as_deref());
+                ClientUsage {
+                    client_address,
+                    proofs: usage.proofs,
+                    failed: usage.failed,
+                    pending: usage.pending,
+                    cycles: usage.cycles,
+                    proving_time_ms: usage.proving_time_ms,
+                    quota: Some(quota_to_proto(quota)),
+                }
+            })
+            .collect();
+        Ok(Response::new(GetUsageResponse { since, clients }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::server::{
+        store::{tests::job, FileJobStore},
+        JobInput, JobUsage,
+    };
+
+    #[test]
+    fn test_admin_auth() {
+        let mut auth = AdminAuth::new("secret".to_string());
+        let request = |value: Option<&str>| {
+            let mut request = Request::new(());
+            if let Some(value) = value {
+                request.metadata_mut().insert("authorization", value.parse().unwrap());
+            }
+            request
+        };
+
+        assert!(auth.call(request(Some("Bearer secret"))).is_ok());
+        assert!(auth.call(request(Some("Bearer secreT"))).is_err());
+        assert!(auth.call(request(Some("secret"))).is_err());
+        assert!(auth.call(request(None)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_usage() {
+        let dir = tempfile::tempdir().unwrap();
+        let store: Arc<dyn JobStore> = Arc::new(FileJobStore::open(dir.path()).unwrap());
+        let input = JobInput { elf: b"elf".to_vec(), stdin: b"stdin".to_vec() };
+        let alice = Job { client_address: Some("alice".to_string()), ..job("0x01", 1, None) };
+        store.insert(alice, input.clone()).await.unwrap();
+        store.insert(job("0x02", 2, None), input).await.unwrap();
+        let claim = store.claim(std::time::Duration::from_secs(60)).await.unwrap().unwrap();
+        let usage = JobUsage { cycles: 1000, proving_time_ms: 2000 };
+        store.complete("0x01", &claim.token, Ok(b"proof".to_vec()), usage, None).await.unwrap();
+
+        let quotas = Quotas {
+            clients: [("alice".to_string(), Quota { max_proofs: Some(5), ..Quota::default() })]
+                .into(),
+            ..Quotas::default()
+        };
+        let service = AdminService::new(store, Arc::new(quotas));
+        let get_usage = |client_address: Option<&str>| {
+            let request = GetUsageRequest {
+                client_address: client_address.map(str::to_string),
+                since: Some(0),
+            };
+            service.get_usage(Request::new(request))
+        };
+
+        let clients = get_usage(None).await.unwrap().into_inner().clients;
+        assert_eq!(clients.len(), 2);
+        assert_eq!(clients[0].client_address, None);
+        assert_eq!(clients[0].pending, 1);
+        assert_eq!(clients[1].client_address.as_deref(), Some("alice"));
+        assert_eq!((clients[1].proofs, clients[1].cycles), (1, 1000));
+        assert_eq!(clients[1].quota.as_ref().unwrap().max_proofs, Some(5));
+
+        let clients = get_usage(Some("bob")).await.unwrap().into_inner().clients;
+        assert_eq!(clients.len(), 1);
+        assert_eq!(clients[0].proofs, 0);
+    }
+}
@@ -0,0 +1,167 @@
+//! # API Keys
+//!
+//! API keys authenticate the clients of a [`ProvingService`](super::ProvingService) that have no
+//! wallet, such as CI pipelines. Each key is granted to a client, under which its requests are
+//! listed and accounted as if it were a client address, and to a set of [`Scope`]s bounding what
+//! it may do. Several keys may be granted to the same client, and each is revoked on its own.
+//!
+//! The keys are read from a JSON file, which only holds the sha256 digests of the keys, so that
+//! reading the file does not give access to the service:
+//!
+//! ```json
+//! {
+//!     "keys": {
+//!         "<sha256 of the key, in hex>": {
+//!             "client": "ci-bridge",
+//!             "scopes": ["prove", "read"],
+//!             "revoked": false
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Keys are minted with [`ApiKeys::mint`], which returns the key once, and revoked with
+//! [`ApiKeys::revoke`]. The service reads the file when it starts, so changes to it apply once
+//! the service is restarted.
+
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// The prefix of the minted keys, to recognize them in logs and secret scanners.
+pub const API_KEY_PREFIX: &str = "mck_";
+
+/// What an API key may do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    /// Request proofs and executions.
+    Prove,
+    /// List the requests and the programs of the client.
+    Read,
+    /// Cancel the requests of the client.
+    Cancel,
+    /// Pin and unpin the programs of the client.
+    Pin,
+}
+
+/// The grant of an API key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKeyGrant {
+    /// The client the key authenticates as.
+    pub client: String,
+    /// What the key may do.
+    pub scopes: Vec<Scope>,
+    /// Whether the key is revoked. Revoked keys are kept, so that they are reported as such.
+    #[serde(default)]
+    pub revoked: bool,
+}
+
+/// The API keys of a service, by the digest of the key.
+///
+/// See the [module documentation](self) for the file format.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiKeys {
+    /// The grants, by the sha256 digest of their key in hex, see [`digest`].
+    pub keys: HashMap<String, ApiKeyGrant>,
+}
+
+impl ApiKeys {
+    /// Reads the keys from a JSON file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("failed to read api keys {}", path.display()))?;
+        serde_json::from_str(&json).with_context(|| format!("invalid api keys {}", path.display()))
+    }
+
+    /// Writes the keys to a JSON file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("failed to write api keys {}", path.display()))
+    }
+
+    /// Mints a key for a client, and returns it. Only its digest is kept.
+    pub fn mint(&mut self, client: impl Into<String>, scopes: Vec<Scope>) -> String {
+        let key = format!(
+            "{API_KEY_PREFIX}{}{}",
+            uuid::Uuid::new_v4().simple(),
+            uuid::Uuid::new_v4().simple()
+        );
+        let grant = ApiKeyGrant { client: client.into(), scopes, revoked: false };
+        self.keys.insert(digest(&key), grant);
+        key
+    }
+
+    /// Revokes the key with the given digest, and returns whether it was found.
+    pub fn revoke(&mut self, digest: &str) -> bool {
+        self.keys.get_mut(digest).map(|grant| grant.revoked = true).is_some()
+    }
+
+    /// Returns the grant of a key, if the key is known, not revoked, and has the scope, or why
+    /// not.
+    pub fn authenticate(&self, key: &str, scope: Scope) -> Result<&ApiKeyGrant, String> {
+        let grant = self.keys.get(&digest(key)).ok_or("unknown api key")?;
+        if grant.revoked {
+            return Err("the api key was revoked".to_string());
+        }
+        if !grant.scopes.contains(&scope) {
+            return Err(format!("the api key is not allowed to {}", scope.action()));
+        }
+        Ok(grant)
+    }
+}
+
+impl Scope {
+    fn action(self) -> &'static str {
+        match self {
+            Self::Prove => "request proofs",
+            Self::Read => "list requests and programs",
+            Self::Cancel => "cancel requests",
+            Self::Pin => "pin programs",
+        }
+    }
+}
+
+/// Returns the digest a key is stored under: its sha256 digest, in hex.
+#[must_use]
+pub fn digest(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_keys() {
+        let mut keys = ApiKeys::default();
+        let key = keys.mint("ci", vec![Scope::Prove, Scope::Read]);
+        assert!(key.starts_with(API_KEY_PREFIX));
+        assert!(!serde_json::to_string(&keys).unwrap().contains(&key));
+
+        assert_eq!(keys.authenticate(&key, Scope::Prove).unwrap().client, "ci");
+        assert!(keys.authenticate(&key, Scope::Cancel).is_err());
+        assert!(keys.authenticate("mck_unknown", Scope::Prove).is_err());
+
+        assert!(keys.revoke(&digest(&key)));
+        assert!(!keys.revoke(&digest("mck_unknown")));
+        assert_eq!(keys.authenticate(&key, Scope::Prove).unwrap_err(), "the api key was revoked");
+    }
+
+    #[test]
+    fn test_api_keys_file() {
+        let keys: ApiKeys = serde_json::from_str(
+            r#"{"keys": {"00": {"client": "ci", "scopes": ["prove", "pin"]}}}"#,
+        )
+        .unwrap();
+        let grant = &keys.keys["00"];
+        assert_eq!(grant.scopes, vec![Scope::Prove, Scope::Pin]);
+        assert!(!grant.revoked);
+    }
+}
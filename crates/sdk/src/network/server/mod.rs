@@ -0,0 +1,1436 @@
+//! # Proving Service
+//!
+//! A self-hosted implementation of the network API, backed by a local prover.
+//!
+//! The service speaks the same gRPC protocol as the monero-chan network, so a [`NetworkProver`]
+//! pointed at it with [`NetworkProverBuilder::rpc_url`] works unchanged:
+//!
+//! ```rust,no_run
+//! use monerochan::{
+//!     network::server::{store, ProvingService},
+//!     ProverClient,
+//! };
+//!
+//! # tokio_test::block_on(async {
+//! let prover = ProverClient::builder().cpu().build();
+//! let store = store::open("monerochan-jobs").await.unwrap();
+//! let service = ProvingService::new(Box::new(prover), store).unwrap();
+//! service.serve("127.0.0.1:50051".parse().unwrap()).await.unwrap();
+//! # });
+//! ```
+//!
+//! Each service proves one job at a time, oldest first. The jobs are persisted in a [`JobStore`],
+//! so queued and interrupted jobs are resumed when the service restarts and finished proofs stay
+//! downloadable. Several services sharing a SQLite, Postgres or Redis store split its jobs among
+//! them.
+//!
+//! The service also coordinates a pool of remote workers, which register with it over gRPC and
+//! prove whole jobs on their own machines. The pool is served on a listener of its own with
+//! [`ProvingService::serve_pool`], and only to the workers that present the token set with
+//! [`ProvingService::worker_token`]. See the [`pool`] module for the protocol, and
+//! [`ProvingService::coordinator`] for a service that only dispatches jobs to its workers.
+//!
+//! Requests that carry a client address must be signed by it, and only that client can then list
+//! or cancel them. Each signature is only accepted once, within the window of the
+//! [`NonceStore`] set with [`ProvingService::nonce_store`], which may also require the clients to
+//! sign challenges issued by the service. Clients without a wallet authenticate with the
+//! [API keys](api_key) set with [`ProvingService::api_keys`] instead, each bound to a client and
+//! to what it may do. Requests without a client address nor a key are accepted as is, so the
+//! service must not be exposed beyond the clients that are allowed to use it.
+//!
+//! The usage of each client is accounted, and can be bounded with [`ProvingService::quotas`] and
+//! queried through the [`admin`] API once [`ProvingService::admin_token`] is set.
+//!
+//! With [`ProvingService::receipt_key`], the status of each succeeded request carries a
+//! [receipt](crate::network::receipt) signed by the service.
+//!
+//! [`NetworkProver`]: crate::NetworkProver
+//! [`NetworkProverBuilder::rpc_url`]: crate::network::builder::NetworkProverBuilder::rpc_url
+
+pub mod admin;
+pub mod api_key;
+pub mod pool;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+pub mod quota;
+#[cfg(feature = "redis")]
+pub mod redis;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod store;
+
+use std::{
+    collections::{hash_map::Entry, BTreeMap, HashMap},
+    net::SocketAddr,
+    panic::{catch_unwind, AssertUnwindSafe},
+    pin::Pin,
+    sync::{
+        mpsc::{self, RecvTimeoutError},
+        Arc, PoisonError, RwLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use futures::Stream;
+use monerochan_core_machine::io::MONEROCHANStdin;
+use monerochan_prover::{components::CpuProverComponents, MONEROCHANProvingKey};
+use prost::Message;
+use sha2::{Digest, Sha256};
+use tonic::{service::interceptor::InterceptedService, Request, Response, Status};
+
+use crate::{
+    network::{
+        compression,
+        proto::{
+            admin::admin_server::AdminServer,
+            api::{
+                network_server::{Network, NetworkServer},
+                CancelProofRequest, CancelProofResponse, ClientAuth, ExecutionResult,
+                FulfillmentStrategy, GetAuthChallengeRequest, GetAuthChallengeResponse,
+                GetProgramRequest, GetProgramResponse, GetProofChunkRequest, GetProofStatusRequest,
+                GetProofStatusResponse, JobStatus, ListProgramsRequest, ListProgramsResponse,
+                ListProofRequestsRequest, ListProofRequestsResponse, PinProgramRequest,
+                PinProgramResponse, ProgramInfo, ProofChunk, ProofMode, ProofReceipt,
+                RequestProofRequest, RequestProofResponse, SignedProofReceipt,
+            },
+            worker::{worker_pool_server::WorkerPoolServer, ClaimedJob},
+        },
+        receipt::{public_values_digest, sign_receipt},
+        solana_client_auth::NonceStore,
+        Error,
+    },
+    utils::unix_timestamp,
+    MONEROCHANProofMode, ProofFromNetwork, Prover,
+};
+pub use admin::{AdminAuth, AdminService};
+pub use api_key::{ApiKeyGrant, ApiKeys, Scope};
+pub use pool::{Coordinator, PoolWorker, WorkerAuth};
+pub use quota::{Quota, Quotas, Usage};
+pub use store::{Claim, Job, JobFilter, JobInput, JobStore, JobUsage};
+
+/// How long a claimed job is leased to its worker before another worker may claim it.
+///
+/// The worker renews the lease every third of it while it proves.
+const LEASE: Duration = Duration::from_secs(300);
+
+/// How often an idle worker polls the store, for jobs submitted to other services sharing it and
+/// for expired leases.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The size of the chunks proofs are streamed in.
+const PROOF_CHUNK_SIZE: usize = 1 << 20;
+
+/// The maximum size of a gRPC message by default, which bounds the ELF and stdin of a request and
+/// the proof a worker uploads.
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 1 << 30;
+
+/// The number of requests listed per page when the client does not choose it.
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// The maximum number of requests listed per page.
+const MAX_PAGE_SIZE: usize = 1000;
+
+/// The maximum number of labels of a request.
+const MAX_LABELS: usize = 32;
+
+/// The maximum length of the key or the value of a label, in bytes.
+const MAX_LABEL_LENGTH: usize = 256;
+
+/// The stream of the chunks of a proof.
+type ProofChunkStream = Pin<Box<dyn Stream<Item = Result<ProofChunk, Status>> + Send>>;
+
+/// A self-hosted proving service that implements the network API with a local prover.
+///
+/// See the [module documentation](self) for an overview.
+#[derive(Clone)]
+pub struct ProvingService {
+    store: Arc<dyn JobStore>,
+    /// Wakes up the local worker, if any, when a job is submitted.
+    wake: mpsc::Sender<()>,
+    coordinator: Coordinator,
+    quotas: Arc<Quotas>,
+    api_keys: Arc<ApiKeys>,
+    nonces: Arc<NonceStore>,
+    admin_token: Option<String>,
+    worker_token: Option<String>,
+    receipt_key: ReceiptKey,
+    max_message_size: usize,
+}
+
+/// The Ed25519 private key receipts are signed with, if any.
+///
+/// The key is shared by a service, its clones, its local worker and its worker pool, which sign
+/// the receipt of a job once, when it succeeds.
+#[derive(Clone, Default)]
+pub(crate) struct ReceiptKey(Arc<RwLock<Option<[u8; 32]>>>);
+
+impl ReceiptKey {
+    fn get(&self) -> Option<[u8; 32]> {
+        *self.0.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn set(&self, private_key: [u8; 32]) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = Some(private_key);
+    }
+}
+
+impl ProvingService {
+    /// Creates a service that proves with the given prover the jobs of `store`.
+    ///
+    /// The jobs left pending or running by a previous run are resumed. The prover runs on its own
+    /// thread, which stops once the service and all its clones are dropped.
+    pub fn new(
+        prover: Box<dyn Prover<CpuProverComponents>>,
+        store: Arc<dyn JobStore>,
+    ) -> Result<Self> {
+        let (wake, woken) = mpsc::channel();
+        let receipt_key = ReceiptKey::default();
+        let worker_store = Arc::clone(&store);
+        let worker_key = receipt_key.clone();
+        thread::Builder::new()
+            .name("monerochan-prover".to_string())
+            .spawn(move || worker(worker_store.as_ref(), &worker_key, prover.as_ref(), &woken))
+            .context("failed to spawn the prover thread")?;
+
+        let coordinator = Coordinator::with_receipt_key(Arc::clone(&store), receipt_key.clone());
+        Ok(Self {
+            store,
+            wake,
+            coordinator,
+            quotas: Arc::default(),
+            api_keys: Arc::default(),
+            nonces: Arc::default(),
+            admin_token: None,
+            worker_token: None,
+            receipt_key,
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+        })
+    }
+
+    /// Creates a service without a local prover, that dispatches the jobs of `store` to the
+    /// remote workers registered with it.
+    #[must_use]
+    pub fn coordinator(store: Arc<dyn JobStore>) -> Self {
+        // Nobody listens for the wake ups, the workers poll for jobs.
+        let (wake, _) = mpsc::channel();
+        let receipt_key = ReceiptKey::default();
+        let coordinator = Coordinator::with_receipt_key(Arc::clone(&store), receipt_key.clone());
+        Self {
+            store,
+            wake,
+            coordinator,
+            quotas: Arc::default(),
+            api_keys: Arc::default(),
+            nonces: Arc::default(),
+            admin_token: None,
+            worker_token: None,
+            receipt_key,
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+        }
+    }
+
+    /// Sets the quotas of the clients, which are unlimited by default.
+    #[must_use]
+    pub fn quotas(mut self, quotas: Quotas) -> Self {
+        self.quotas = Arc::new(quotas);
+        self
+    }
+
+    /// Sets the API keys the clients may authenticate with, none by default.
+    #[must_use]
+    pub fn api_keys(mut self, api_keys: ApiKeys) -> Self {
+        self.api_keys = Arc::new(api_keys);
+        self
+    }
+
+    /// Sets the store of the nonces of the client signatures, which sets the window signatures are
+    /// accepted in and whether they must sign a challenge issued by the service. Signatures are
+    /// accepted within an hour by default, with any nonce not seen before.
+    #[must_use]
+    pub fn nonce_store(mut self, nonces: NonceStore) -> Self {
+        self.nonces = Arc::new(nonces);
+        self
+    }
+
+    /// Sets the token of the admin API, which is only served with a token.
+    #[must_use]
+    pub fn admin_token(mut self, token: impl Into<String>) -> Self {
+        self.admin_token = Some(token.into());
+        self
+    }
+
+    /// Sets the token the workers of the pool must present, which is only served with a token.
+    #[must_use]
+    pub fn worker_token(mut self, token: impl Into<String>) -> Self {
+        self.worker_token = Some(token.into());
+        self
+    }
+
+    /// Sets the maximum size of the gRPC messages the service accepts and sends, in bytes,
+    /// [`DEFAULT_MAX_MESSAGE_SIZE`] by default.
+    #[must_use]
+    pub fn max_message_size(mut self, bytes: usize) -> Self {
+        self.max_message_size = bytes;
+        self
+    }
+
+    /// Sets the Ed25519 private key the receipts of the succeeded requests are signed with.
+    ///
+    /// The receipt of a job is signed once, when it succeeds, and stored with its proof, so the
+    /// jobs that succeeded before the key was set have no receipt. With a key, the program id of
+    /// a request must be empty or a `0x`-prefixed 32 bytes hex string, the vk hash of its receipt.
+    ///
+    /// Clients verify the receipts against the public key of the service, see
+    /// [`NetworkProverBuilder::receipt_key`].
+    ///
+    /// [`NetworkProverBuilder::receipt_key`]: crate::network::builder::NetworkProverBuilder::receipt_key
+    #[must_use]
+    pub fn receipt_key(self, private_key: [u8; 32]) -> Self {
+        self.receipt_key.set(private_key);
+        self
+    }
+
+    /// Returns the gRPC service of the admin API, if the service has an admin token.
+    #[must_use]
+    pub fn admin_server(&self) -> Option<InterceptedService<AdminServer<AdminService>, AdminAuth>> {
+        let token = self.admin_token.clone()?;
+        let service = AdminService::new(Arc::clone(&self.store), Arc::clone(&self.quotas));
+        Some(AdminServer::with_interceptor(service, AdminAuth::new(token)))
+    }
+
+    /// Returns the gRPC service of the worker pool, if the service has a worker token.
+    ///
+    /// The pool trusts its workers with the jobs and their outcomes, so it must not be served on
+    /// the listener of the network API.
+    #[must_use]
+    pub fn pool_server(
+        &self,
+    ) -> Option<InterceptedService<WorkerPoolServer<Coordinator>, WorkerAuth>> {
+        let token = self.worker_token.clone()?;
+        // Claimed jobs carry their ELF and stdin, and completed ones their proof.
+        let server = WorkerPoolServer::new(self.coordinator.clone())
+            .max_decoding_message_size(self.max_message_size)
+            .max_encoding_message_size(self.max_message_size);
+        Some(InterceptedService::new(server, WorkerAuth::new(token)))
+    }
+
+    /// Returns the gRPC service, to serve it alongside other services.
+    #[must_use]
+    pub fn into_server(self) -> NetworkServer<Self> {
+        // Requests carry the ELF and stdin of the program, so the default limit of 4 MiB is too
+        // small for most of them.
+        let max_message_size = self.max_message_size;
+        NetworkServer::new(self).max_decoding_message_size(max_message_size)
+    }
+
+    /// Serves the network API, and the admin API if the service has an admin token, on `addr`
+    /// until the process is stopped.
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        tracing::info!(%addr, "serving the network api");
+        tonic::transport::Server::builder()
+            .add_optional_service(self.admin_server())
+            .add_service(self.into_server())
+            .serve(addr)
+            .await
+            .context("the proving service stopped")
+    }
+
+    /// Serves the worker pool on `addr` until the process is stopped.
+    ///
+    /// The address should only be reachable by the workers. Fails if the service has no worker
+    /// token.
+    pub async fn serve_pool(self, addr: SocketAddr) -> Result<()> {
+        let server = self.pool_server().context("the worker pool requires a worker token")?;
+        tracing::info!(%addr, "serving the worker pool");
+        tonic::transport::Server::builder()
+            .add_service(server)
+            .serve(addr)
+            .await
+            .context("the worker pool stopped")
+    }
+}
+
+/// Claims and proves the jobs of the store one at a time.
+fn worker(
+    store: &dyn JobStore,
+    receipt_key: &ReceiptKey,
+    prover: &dyn Prover<CpuProverComponents>,
+    woken: &mpsc::Receiver<()>,
+) {
+    // The heartbeat thread renews the lease on this runtime while the worker thread proves, so
+    // the runtime needs a thread of its own to drive the connections of the store.
+    let runtime =
+        match tokio::runtime::Builder::new_multi_thread().worker_threads(1).enable_all().build() {
+            Ok(runtime) => runtime,
+            Err(err) => {
+                tracing::error!("failed to start the prover runtime: {err}");
+                return;
+            }
+        };
+
+    let mut keys = HashMap::new();
+    loop {
+        let Claim { job, token } = match runtime.block_on(store.claim(LEASE)) {
+            Ok(Some(claim)) => claim,
+            Ok(None) => {
+                if woken.recv_timeout(POLL_INTERVAL) == Err(RecvTimeoutError::Disconnected) {
+                    return;
+                }
+                continue;
+            }
+            Err(err) => {
+                tracing::error!("failed to claim a job: {err:#}");
+                thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+        };
+
+        let (request_id, token) = (job.request_id.as_str(), token.as_str());
+        tracing::info!(%request_id, "proving");
+        let outcome = thread::scope(|scope| {
+            let (stop, stopped) = mpsc::channel::<()>();
+            let handle = runtime.handle();
+            scope.spawn(move || {
+                while stopped.recv_timeout(LEASE / 3) == Err(RecvTimeoutError::Timeout) {
+                    match handle.block_on(store.renew(request_id, token, LEASE)) {
+                        Ok(true) => {}
+                        Ok(false) => break,
+                        Err(err) => tracing::warn!(%request_id, "failed to renew lease: {err:#}"),
+                    }
+                }
+            });
+
+            let outcome = match runtime.block_on(store.input(request_id)) {
+                Ok(input) => prove(prover, &mut keys, &claimed_job(&job, input)),
+                Err(err) => (Err(err), JobUsage::default()),
+            };
+            drop(stop);
+            outcome
+        });
+
+        let (outcome, usage) = outcome;
+        if let Err(err) = &outcome {
+            tracing::warn!(%request_id, "proving failed: {err:#}");
+        }
+        let outcome = outcome.map_err(|err| format!("{err:#}"));
+        let recorded = record_outcome(store, receipt_key, request_id, token, outcome, usage);
+        match runtime.block_on(recorded) {
+            Ok(true) => tracing::info!(%request_id, "job completed"),
+            Ok(false) => {
+                tracing::info!(%request_id, "job was cancelled or reassigned, dropping its outcome")
+            }
+            Err(err) => tracing::error!(%request_id, "failed to complete job: {err:#}"),
+        }
+    }
+}
+
+/// Bundles a job with its input, as it is handed to a worker.
+fn claimed_job(job: &Job, input: JobInput) -> ClaimedJob {
+    ClaimedJob {
+        request_id: job.request_id.clone(),
+        elf: input.elf,
+        stdin: input.stdin,
+        proof_mode: job.proof_mode,
+        skip_simulation: job.skip_simulation,
+        cycle_limit: job.cycle_limit,
+    }
+}
+
+/// Proves a job and returns the serialized [`ProofFromNetwork`] with the resources the job
+/// consumed, turning a panic of the prover into an error.
+fn prove(
+    prover: &dyn Prover<CpuProverComponents>,
+    keys: &mut HashMap<[u8; 32], MONEROCHANProvingKey>,
+    job: &ClaimedJob,
+) -> (Result<Vec<u8>>, JobUsage) {
+    let start = Instant::now();
+    let mut cycles = 0;
+    let outcome =
+        catch_unwind(AssertUnwindSafe(|| prove_unwinding(prover, keys, job, &mut cycles)))
+            .unwrap_or_else(|panic| {
+                Err(anyhow!("the prover panicked: {}", panic_message(&*panic)))
+            });
+    let usage = JobUsage { cycles, proving_time_ms: start.elapsed().as_millis() as u64 };
+    (outcome, usage)
+}
+
+/// Proves a job and returns the serialized [`ProofFromNetwork`], recording the cycles the
+/// program executed for. An execute-only job is only executed, and returns the encoded
+/// [`ExecutionResult`] instead.
+///
+/// The proving keys are cached by the digest of the ELF, since the program id is chosen by the
+/// client.
+fn prove_unwinding(
+    prover: &dyn Prover<CpuProverComponents>,
+    keys: &mut HashMap<[u8; 32], MONEROCHANProvingKey>,
+    job: &ClaimedJob,
+    cycles: &mut u64,
+) -> Result<Vec<u8>> {
+    let stdin: MONEROCHANStdin = bincode::deserialize(&job.stdin).context("invalid stdin")?;
+
+    if job.proof_mode == ProofMode::ExecuteOnly as i32 {
+        let (public_values, report) =
+            prover.execute(&job.elf, &stdin).context("execution failed")?;
+        *cycles = report.total_instruction_count();
+        check_cycle_limit(*cycles, job.cycle_limit)?;
+        let result = ExecutionResult {
+            public_values: public_values.to_vec(),
+            cycles: *cycles,
+            gas: report.gas,
+        };
+        return Ok(result.encode_to_vec());
+    }
+
+    let mode = proof_mode(job.proof_mode)?;
+    if !job.skip_simulation {
+        let (_, report) = prover.execute(&job.elf, &stdin).context("execution failed")?;
+        *cycles = report.total_instruction_count();
+        check_cycle_limit(*cycles, job.cycle_limit)?;
+    }
+
+    let pk = match keys.entry(Sha256::digest(&job.elf).into()) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => entry.insert(prover.try_setup(&job.elf)?.0),
+    };
+    let proof = prover.prove(pk, &stdin, mode)?;
+    let proof = ProofFromNetwork {
+        proof: proof.proof,
+        public_values: proof.public_values,
+        monerochan_version: proof.monerochan_version,
+    };
+    Ok(bincode::serialize(&proof)?)
+}
+
+fn check_cycle_limit(cycles: u64, cycle_limit: Option<u64>) -> Result<()> {
+    if let Some(cycle_limit) = cycle_limit.filter(|limit| cycles > *limit) {
+        bail!("the program executed {cycles} cycles, more than the limit of {cycle_limit}");
+    }
+    Ok(())
+}
+
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+fn proof_mode(mode: i32) -> Result<MONEROCHANProofMode> {
+    match ProofMode::try_from(mode) {
+        Ok(ProofMode::Core) => Ok(MONEROCHANProofMode::Core),
+        Ok(ProofMode::Compressed) => Ok(MONEROCHANProofMode::Compressed),
+        Ok(ProofMode::Plonk) => Ok(MONEROCHANProofMode::Plonk),
+        Ok(ProofMode::Groth16) => Ok(MONEROCHANProofMode::Groth16),
+        Ok(ProofMode::Unspecified | ProofMode::ExecuteOnly) | Err(_) => {
+            Err(anyhow!("unsupported proof mode {mode}"))
+        }
+    }
+}
+
+/// Checks that a request is signed by its client address, if it has one, and that the signature
+/// is not replayed.
+fn authenticate(
+    nonces: &NonceStore,
+    client_address: Option<&String>,
+    client_auth: Option<&ClientAuth>,
+) -> Result<(), Status> {
+    let Some(client_address) = client_address else {
+        return Ok(());
+    };
+    let auth = client_auth
+        .ok_or_else(|| Status::unauthenticated("client_auth is required with client_address"))?;
+    nonces
+        .verify(client_address, &auth.job_id, &auth.nonce, auth.timestamp, &auth.signature)
+        .map_err(|err| Status::unauthenticated(format!("{err:#}")))
+}
+
+/// Checks that the labels of a request are within [`MAX_LABELS`] and [`MAX_LABEL_LENGTH`], and
+/// that their keys are not empty.
+fn validate_labels(labels: &HashMap<String, String>) -> Result<(), Status> {
+    if labels.len() > MAX_LABELS {
+        return Err(Status::invalid_argument(format!(
+            "the request has {} labels, more than the maximum of {MAX_LABELS}",
+            labels.len()
+        )));
+    }
+    for (key, value) in labels {
+        if key.is_empty() || key.len() > MAX_LABEL_LENGTH || value.len() > MAX_LABEL_LENGTH {
+            return Err(Status::invalid_argument(format!(
+                "invalid label `{key}`: keys must be non-empty, and keys and values at most \
+                 {MAX_LABEL_LENGTH} bytes"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Rejects the requests that ask for an auction or for their own settlement contracts, which the
+/// service cannot honor as it proves every request itself.
+fn reject_unsupported(request: &RequestProofRequest) -> Result<(), Status> {
+    let fields = [
+        ("the auction strategy", request.strategy == FulfillmentStrategy::Auction as i32),
+        ("min_auction_period", request.min_auction_period != 0),
+        ("whitelist", !request.whitelist.is_empty()),
+        ("auctioneer", !request.auctioneer.is_empty()),
+        ("max_price_per_pgu", request.max_price_per_pgu.is_some()),
+        ("auction_timeout_secs", request.auction_timeout_secs.is_some()),
+        ("executor", !request.executor.is_empty()),
+        ("verifier", !request.verifier.is_empty()),
+        ("treasury", !request.treasury.is_empty()),
+    ];
+    match fields.into_iter().find(|(_, set)| *set) {
+        Some((field, _)) => {
+            Err(Status::unimplemented(format!("{field} is not supported by this service")))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Records the outcome of a job claimed under `token`, with its signed receipt if it succeeded
+/// and the service signs receipts, and returns whether the job was still running under the claim.
+///
+/// A succeeded job whose receipt cannot be signed fails instead, as its outcome is malformed.
+async fn record_outcome(
+    store: &dyn JobStore,
+    receipt_key: &ReceiptKey,
+    request_id: &str,
+    token: &str,
+    mut outcome: Result<Vec<u8>, String>,
+    usage: JobUsage,
+) -> Result<bool> {
+    let mut receipt = None;
+    if let (Ok(proof), Some(key)) = (&outcome, receipt_key.get()) {
+        let job =
+            store.get(request_id).await?.with_context(|| format!("unknown job {request_id}"))?;
+        match job_receipt(&key, &job, proof, usage) {
+            Ok(signed) => receipt = Some(signed.encode_to_vec()),
+            Err(err) => outcome = Err(format!("failed to sign the receipt: {err:#}")),
+        }
+    }
+    store.complete(request_id, token, outcome, usage, receipt).await
+}
+
+/// Signs the receipt of a succeeded job, given its outcome: the serialized [`ProofFromNetwork`],
+/// or the encoded [`ExecutionResult`] of an execute-only job.
+///
+/// The service does not charge for its requests, so their price is zero.
+fn job_receipt(
+    key: &[u8; 32],
+    job: &Job,
+    outcome: &[u8],
+    usage: JobUsage,
+) -> Result<SignedProofReceipt> {
+    let public_values = if job.proof_mode == ProofMode::ExecuteOnly as i32 {
+        ExecutionResult::decode(outcome)?.public_values
+    } else {
+        bincode::deserialize::<ProofFromNetwork>(outcome)?.public_values.to_vec()
+    };
+    let receipt = ProofReceipt {
+        request_id: job.request_id.clone(),
+        vk_hash: vk_hash(&job.program_id)?,
+        public_values_digest: public_values_digest(&public_values).to_vec(),
+        cycles: usage.cycles,
+        price: 0,
+    };
+    Ok(sign_receipt(key, &receipt))
+}
+
+/// Returns the vk hash of a program id, which is empty for a request without a program id.
+fn vk_hash(program_id: &str) -> Result<Vec<u8>> {
+    if program_id.is_empty() {
+        return Ok(Vec::new());
+    }
+    program_id
+        .strip_prefix("0x")
+        .and_then(|hash| hex::decode(hash).ok())
+        .filter(|hash| hash.len() == 32)
+        .with_context(|| format!("the program id {program_id} is not a 0x-prefixed 32 bytes hash"))
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn internal(err: anyhow::Error) -> Status {
+    tracing::error!("{err:#}");
+    Status::internal(format!("{err:#}"))
+}
+
+fn new_request_id() -> String {
+    format!("0x{}", hex::encode(Sha256::digest(uuid::Uuid::new_v4().as_bytes())))
+}
+
+impl ProvingService {
+    /// Authenticates the client of a request, and returns it: the client of its API key, with
+    /// the scope, or its client address, signed by it.
+    ///
+    /// With an API key, the client address, if any, must be the client of the key.
+    fn client(
+        &self,
+        client_address: Option<&String>,
+        client_auth: Option<&ClientAuth>,
+        scope: Scope,
+    ) -> Result<Option<String>, Status> {
+        let Some(api_key) = client_auth.map(|auth| &auth.api_key).filter(|key| !key.is_empty())
+        else {
+            authenticate(&self.nonces, client_address, client_auth)?;
+            return Ok(client_address.cloned());
+        };
+        let grant =
+            self.api_keys.authenticate(api_key, scope).map_err(Status::permission_denied)?;
+        if client_address.is_some_and(|address| *address != grant.client) {
+            return Err(Status::permission_denied("the api key belongs to another client"));
+        }
+        Ok(Some(grant.client.clone()))
+    }
+
+    /// Rejects a request whose client is over quota, and caps its cycle limit by the cycles left
+    /// to the client.
+    async fn enforce_quota(&self, request: &mut RequestProofRequest) -> Result<(), Status> {
+        let client_address = request.client_address.as_deref();
+        let quota = self.quotas.quota(client_address);
+        if quota.is_unlimited() {
+            return Ok(());
+        }
+
+        let mut jobs = self.store.list(client_address).await.map_err(internal)?;
+        // The anonymous requests are listed with every other request.
+        jobs.retain(|job| job.client_address.as_deref() == client_address);
+        let usage = Usage::of(&jobs, self.quotas.period_start());
+        quota.check(&usage).map_err(Status::resource_exhausted)?;
+
+        if let Some(remaining) = quota.remaining_cycles(&usage) {
+            let cycle_limit = request.cycle_limit.map_or(remaining, |limit| limit.min(remaining));
+            request.cycle_limit = Some(cycle_limit);
+            // The cycles of a job are only known once it is executed.
+            request.skip_simulation = false;
+        }
+        Ok(())
+    }
+
+    /// Summarizes the programs requested by a client, or by every client, ordered by program id.
+    ///
+    /// The execute-only requests have no program id, and are left out.
+    async fn programs(&self, client_address: Option<&str>) -> Result<Vec<ProgramInfo>> {
+        let jobs = self.store.list(client_address).await?;
+        let pinned = match client_address {
+            Some(client_address) => self.store.pinned(client_address).await?,
+            None => Vec::new(),
+        };
+
+        // The latest request of each program, oldest first, gives the current size of its ELF.
+        let mut programs: BTreeMap<&str, (ProgramInfo, &str)> = BTreeMap::new();
+        for job in jobs.iter().filter(|job| !job.program_id.is_empty()) {
+            let (program, latest) = programs.entry(&job.program_id).or_insert_with(|| {
+                let program = ProgramInfo {
+                    program_id: job.program_id.clone(),
+                    first_seen: job.created_at,
+                    pinned: pinned.contains(&job.program_id),
+                    ..ProgramInfo::default()
+                };
+                (program, &job.request_id)
+            });
+            program.last_seen = job.created_at;
+            *latest = &job.request_id;
+            match JobStatus::try_from(job.status) {
+                Ok(JobStatus::Succeeded) => program.proofs += 1,
+                Ok(JobStatus::Failed) => program.failed += 1,
+                _ => program.pending += 1,
+            }
+        }
+
+        let mut infos = Vec::with_capacity(programs.len());
+        for (program, latest) in programs.into_values() {
+            let elf_size = self.store.input(latest).await?.elf.len() as u64;
+            infos.push(ProgramInfo { elf_size, ..program });
+        }
+        Ok(infos)
+    }
+}
+
+#[tonic::async_trait]
+impl Network for ProvingService {
+    async fn request_proof(
+        &self,
+        request: Request<RequestProofRequest>,
+    ) -> Result<Response<RequestProofResponse>, Status> {
+        let mut request = request.into_inner();
+        request.client_address = self.client(
+            request.client_address.as_ref(),
+            request.client_auth.as_ref(),
+            Scope::Prove,
+        )?;
+        compression::decompress(&mut request, self.max_message_size)
+            .map_err(|err| Status::invalid_argument(format!("{err:#}")))?;
+        if request.proof_mode != ProofMode::ExecuteOnly as i32 {
+            proof_mode(request.proof_mode)
+                .map_err(|err| Status::invalid_argument(err.to_string()))?;
+        }
+        if request.elf.is_empty() {
+            return Err(Status::invalid_argument("the request has no elf"));
+        }
+        bincode::deserialize::<MONEROCHANStdin>(&request.stdin)
+            .map_err(|err| Status::invalid_argument(format!("invalid stdin: {err}")))?;
+        validate_labels(&request.labels)?;
+        reject_unsupported(&request)?;
+        if self.receipt_key.get().is_some() {
+            vk_hash(&request.program_id)
+                .map_err(|err| Status::invalid_argument(format!("{err:#}")))?;
+        }
+        self.enforce_quota(&mut request).await?;
+
+        let job = Job {
+            request_id: new_request_id(),
+            program_id: request.program_id,
+            proof_mode: request.proof_mode,
+            status: JobStatus::Pending as i32,
+            error_message: String::new(),
+            created_at: unix_timestamp(),
+            skip_simulation: request.skip_simulation,
+            cycle_limit: request.cycle_limit,
+            client_address: request.client_address,
+            idempotency_key: request.idempotency_key,
+            labels: request.labels.into_iter().collect(),
+            usage: JobUsage::default(),
+        };
+        let input = JobInput { elf: request.elf, stdin: request.stdin };
+        // A retried submission gets the job created by the first one.
+        let job = self.store.insert(job, input).await.map_err(internal)?;
+        // Fails without a local worker, whose thread only stops with the last clone of the service.
+        let _ = self.wake.send(());
+
+        tracing::info!(request_id = %job.request_id, "queued proof request");
+        let mut response = Response::new(RequestProofResponse {
+            request_id: job.request_id.clone(),
+            job_id: job.request_id,
+            explorer_url: String::new(),
+        });
+        compression::advertise(&mut response);
+        Ok(response)
+    }
+
+    async fn get_proof_status(
+        &self,
+        request: Request<GetProofStatusRequest>,
+    ) -> Result<Response<GetProofStatusResponse>, Status> {
+        let GetProofStatusRequest { request_id, omit_proof } = request.into_inner();
+        let job = self
+            .store
+            .get(&request_id)
+            .await
+            .map_err(internal)?
+            .ok_or_else(|| Status::not_found(format!("unknown request {request_id}")))?;
+
+        let execute_only = job.proof_mode == ProofMode::ExecuteOnly as i32;
+        let succeeded = job.status == JobStatus::Succeeded as i32;
+        let mut proof = if succeeded && (execute_only || !omit_proof) {
+            self.store.proof(&request_id).await.map_err(internal)?.unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        let receipt = if succeeded {
+            let receipt = self.store.receipt(&request_id).await.map_err(internal)?;
+            receipt
+                .map(|receipt| SignedProofReceipt::decode(receipt.as_slice()))
+                .transpose()
+                .map_err(|err| internal(err.into()))?
+        } else {
+            None
+        };
+        // The outcome of an execute-only job is its execution result rather than a proof.
+        let execution = if execute_only && !proof.is_empty() {
+            let execution =
+                ExecutionResult::decode(proof.as_slice()).map_err(|err| internal(err.into()))?;
+            proof.clear();
+            Some(execution)
+        } else {
+            None
+        };
+
+        Ok(Response::new(GetProofStatusResponse {
+            status: job.status,
+            proof,
+            error_message: job.error_message,
+            request_id,
+            execution,
+            labels: job.labels.into_iter().collect(),
+            receipt,
+        }))
+    }
+
+    type GetProofChunkStream = ProofChunkStream;
+
+    async fn get_proof_chunk(
+        &self,
+        request: Request<GetProofChunkRequest>,
+    ) -> Result<Response<Self::GetProofChunkStream>, Status> {
+        let GetProofChunkRequest { request_id, offset } = request.into_inner();
+        let job = self
+            .store
+            .get(&request_id)
+            .await
+            .map_err(internal)?
+            .ok_or_else(|| Status::not_found(format!("unknown request {request_id}")))?;
+        if job.status != JobStatus::Succeeded as i32 ||
+            job.proof_mode == ProofMode::ExecuteOnly as i32
+        {
+            return Err(Status::failed_precondition(format!("request {request_id} has no proof")));
+        }
+
+        let proof = self.store.proof(&request_id).await.map_err(internal)?.unwrap_or_default();
+        let total_size = proof.len() as u64;
+        if offset > total_size {
+            return Err(Status::out_of_range(format!(
+                "offset {offset} is past the end of the proof ({total_size} bytes)"
+            )));
+        }
+        let chunks = (offset as usize..proof.len()).step_by(PROOF_CHUNK_SIZE).map(move |start| {
+            let end = (start + PROOF_CHUNK_SIZE).min(proof.len());
+            Ok(ProofChunk { data: proof[start..end].to_vec(), offset: start as u64, total_size })
+        });
+        Ok(Response::new(Box::pin(futures::stream::iter(chunks))))
+    }
+
+    async fn cancel_proof(
+        &self,
+        request: Request<CancelProofRequest>,
+    ) -> Result<Response<CancelProofResponse>, Status> {
+        let request = request.into_inner();
+        let job =
+            self.store.get(&request.request_id).await.map_err(internal)?.ok_or_else(|| {
+                Status::not_found(format!("unknown request {}", request.request_id))
+            })?;
+
+        if let Some(owner) = &job.client_address {
+            let client = self.client(
+                request.client_address.as_ref(),
+                request.client_auth.as_ref(),
+                Scope::Cancel,
+            )?;
+            if client.as_ref() != Some(owner) {
+                return Err(Status::permission_denied("the request belongs to another client"));
+            }
+        }
+
+        let cancelled = self.store.cancel(&request.request_id).await.map_err(internal)?;
+        Ok(Response::new(CancelProofResponse { cancelled }))
+    }
+
+    async fn list_proof_requests(
+        &self,
+        request: Request<ListProofRequestsRequest>,
+    ) -> Result<Response<ListProofRequestsResponse>, Status> {
+        let request = request.into_inner();
+        let client = self.client(
+            request.client_address.as_ref(),
+            request.client_auth.as_ref(),
+            Scope::Read,
+        )?;
+
+        // The page token is the id of the last request of the previous page, which is looked up
+        // on its own since it may no longer pass the filters, for example its status.
+        let after = if request.page_token.is_empty() {
+            None
+        } else {
+            let last = self.store.get(&request.page_token).await.map_err(internal)?;
+            let last = last.ok_or_else(|| {
+                Status::invalid_argument(format!("invalid page token {}", request.page_token))
+            })?;
+            Some(last.created_at)
+        };
+        let page_size = match request.page_size {
+            0 => DEFAULT_PAGE_SIZE,
+            page_size => (page_size as usize).min(MAX_PAGE_SIZE),
+        };
+
+        let filter = JobFilter {
+            client_address: client,
+            statuses: request.statuses,
+            program_id: request.program_id,
+            created_after: request.created_after,
+            created_before: request.created_before,
+            labels: request.labels.into_iter().collect(),
+        };
+        let after = after.map(|created_at| (created_at, request.page_token.as_str()));
+        // One more job than the page tells whether there is a next page.
+        let mut jobs = self.store.list_page(&filter, after, page_size + 1).await.map_err(internal)?;
+        let next_page_token = if jobs.len() > page_size {
+            jobs.truncate(page_size);
+            jobs.last().map(|last| last.request_id.clone()).unwrap_or_default()
+        } else {
+            String::new()
+        };
+        let requests = jobs.iter().map(Job::summary).collect();
+        Ok(Response::new(ListProofRequestsResponse { requests, next_page_token }))
+    }
+
+    async fn list_programs(
+        &self,
+        request: Request<ListProgramsRequest>,
+    ) -> Result<Response<ListProgramsResponse>, Status> {
+        let request = request.into_inner();
+        let client = self.client(
+            request.client_address.as_ref(),
+            request.client_auth.as_ref(),
+            Scope::Read,
+        )?;
+
+        let programs = self.programs(client.as_deref()).await.map_err(internal)?;
+        Ok(Response::new(ListProgramsResponse { programs }))
+    }
+
+    async fn get_program(
+        &self,
+        request: Request<GetProgramRequest>,
+    ) -> Result<Response<GetProgramResponse>, Status> {
+        let request = request.into_inner();
+        let client = self.client(
+            request.client_address.as_ref(),
+            request.client_auth.as_ref(),
+            Scope::Read,
+        )?;
+
+        let program = self
+            .programs(client.as_deref())
+            .await
+            .map_err(internal)?
+            .into_iter()
+            .find(|program| program.program_id == request.program_id)
+            .ok_or_else(|| Status::not_found(format!("unknown program {}", request.program_id)))?;
+        Ok(Response::new(GetProgramResponse { program: Some(program) }))
+    }
+
+    async fn pin_program(
+        &self,
+        request: Request<PinProgramRequest>,
+    ) -> Result<Response<PinProgramResponse>, Status> {
+        let request = request.into_inner();
+        let client = self.client(
+            request.client_address.as_ref(),
+            request.client_auth.as_ref(),
+            Scope::Pin,
+        )?;
+        let Some(client_address) = &client else {
+            return Err(Status::unauthenticated("pinning a program requires a client_address"));
+        };
+
+        // A client may only pin the programs it requested proofs for.
+        let jobs = self.store.list(Some(client_address)).await.map_err(internal)?;
+        if !jobs.iter().any(|job| job.program_id == request.program_id) {
+            return Err(Status::not_found(format!("unknown program {}", request.program_id)));
+        }
+        self.store
+            .pin(client_address, &request.program_id, request.pinned)
+            .await
+            .map_err(internal)?;
+        Ok(Response::new(PinProgramResponse {}))
+    }
+
+    async fn get_auth_challenge(
+        &self,
+        request: Request<GetAuthChallengeRequest>,
+    ) -> Result<Response<GetAuthChallengeResponse>, Status> {
+        let GetAuthChallengeRequest { client_address } = request.into_inner();
+        if client_address.is_empty() {
+            return Err(Status::invalid_argument("a challenge requires a client_address"));
+        }
+        let (challenge, expires_at) =
+            self.nonces.issue_challenge(&client_address).map_err(|err| match err.downcast_ref() {
+                Some(Error::TooManyChallenges) => Status::resource_exhausted(err.to_string()),
+                _ => internal(err),
+            })?;
+        Ok(Response::new(GetAuthChallengeResponse { challenge, expires_at }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::solana_client_auth::create_client_auth;
+
+    #[test]
+    fn test_authenticate() {
+        let key = [7u8; 32];
+        let (job_id, nonce, timestamp, signature, address) = create_client_auth(&key).unwrap();
+        let auth = ClientAuth { job_id, nonce, timestamp, signature, api_key: String::new() };
+
+        let nonces = NonceStore::default();
+        assert!(authenticate(&nonces, None, None).is_ok());
+        assert!(authenticate(&nonces, Some(&address), Some(&auth)).is_ok());
+        assert!(authenticate(&nonces, Some(&address), Some(&auth)).is_err());
+        assert!(authenticate(&nonces, Some(&address), None).is_err());
+
+        let (.., other) = create_client_auth(&[8u8; 32]).unwrap();
+        assert!(authenticate(&nonces, Some(&other), Some(&auth)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_api_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let store: Arc<dyn JobStore> = Arc::new(store::FileJobStore::open(dir.path()).unwrap());
+        let mut api_keys = ApiKeys::default();
+        let prover_key = api_keys.mint("ci", vec![Scope::Prove, Scope::Read]);
+        let reader_key = api_keys.mint("ci", vec![Scope::Read]);
+        let service = ProvingService::coordinator(Arc::clone(&store)).api_keys(api_keys);
+        let auth = |api_key: &str| {
+            Some(ClientAuth { api_key: api_key.to_string(), ..ClientAuth::default() })
+        };
+        let request = |api_key: &str| RequestProofRequest {
+            elf: b"elf".to_vec(),
+            stdin: bincode::serialize(&MONEROCHANStdin::new()).unwrap(),
+            proof_mode: ProofMode::Core as i32,
+            client_auth: auth(api_key),
+            ..Default::default()
+        };
+
+        let response = service.request_proof(Request::new(request(&prover_key))).await.unwrap();
+        let job = store.get(&response.into_inner().request_id).await.unwrap().unwrap();
+        assert_eq!(job.client_address.as_deref(), Some("ci"));
+
+        let status = service.request_proof(Request::new(request(&reader_key))).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+        let status = service.request_proof(Request::new(request("mck_unknown"))).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+        let list =
+            ListProofRequestsRequest { client_auth: auth(&reader_key), ..Default::default() };
+        let requests = service.list_proof_requests(Request::new(list)).await.unwrap();
+        assert_eq!(requests.into_inner().requests.len(), 1);
+
+        let cancel = CancelProofRequest {
+            request_id: job.request_id,
+            client_auth: auth(&reader_key),
+            ..Default::default()
+        };
+        let status = service.cancel_proof(Request::new(cancel)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+    }
+
+    #[tokio::test]
+    async fn test_quota_enforced() {
+        let dir = tempfile::tempdir().unwrap();
+        let store: Arc<dyn JobStore> = Arc::new(store::FileJobStore::open(dir.path()).unwrap());
+        let quota = Quota { max_pending: Some(1), max_cycles: Some(100), ..Quota::default() };
+        let service = ProvingService::coordinator(Arc::clone(&store))
+            .quotas(Quotas { default: quota, ..Quotas::default() });
+        let request = || RequestProofRequest {
+            elf: b"elf".to_vec(),
+            stdin: bincode::serialize(&MONEROCHANStdin::new()).unwrap(),
+            proof_mode: ProofMode::Core as i32,
+            skip_simulation: true,
+            ..Default::default()
+        };
+
+        let response = service.request_proof(Request::new(request())).await.unwrap();
+        let job = store.get(&response.into_inner().request_id).await.unwrap().unwrap();
+        assert_eq!(job.cycle_limit, Some(100));
+        assert!(!job.skip_simulation);
+
+        let status = service.request_proof(Request::new(request())).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+    }
+
+    #[tokio::test]
+    async fn test_programs() {
+        let dir = tempfile::tempdir().unwrap();
+        let store: Arc<dyn JobStore> = Arc::new(store::FileJobStore::open(dir.path()).unwrap());
+        let service = ProvingService::coordinator(Arc::clone(&store));
+        let input = JobInput { elf: b"elf".to_vec(), stdin: b"stdin".to_vec() };
+        let alice = |request_id: &str, created_at: i64, program_id: &str| Job {
+            program_id: program_id.to_string(),
+            client_address: Some("alice".to_string()),
+            ..store::tests::job(request_id, created_at, None)
+        };
+        store.insert(alice("0x01", 1, "0xaa"), input.clone()).await.unwrap();
+        store.insert(alice("0x02", 2, "0xbb"), input.clone()).await.unwrap();
+        store.insert(alice("0x03", 3, "0xaa"), input.clone()).await.unwrap();
+        store.insert(alice("0x04", 4, ""), input).await.unwrap();
+        let claim = store.claim(Duration::from_secs(60)).await.unwrap().unwrap();
+        let outcome = Ok(b"proof".to_vec());
+        store.complete("0x01", &claim.token, outcome, JobUsage::default(), None).await.unwrap();
+        store.pin("alice", "0xaa", true).await.unwrap();
+
+        let programs = service.programs(Some("alice")).await.unwrap();
+        assert_eq!(programs.len(), 2);
+        assert_eq!(
+            programs[0],
+            ProgramInfo {
+                program_id: "0xaa".to_string(),
+                elf_size: 3,
+                first_seen: 1,
+                last_seen: 3,
+                proofs: 1,
+                failed: 0,
+                pending: 1,
+                pinned: true,
+            }
+        );
+        assert_eq!(programs[1].program_id, "0xbb");
+        assert!(!programs[1].pinned);
+        assert!(service.programs(Some("bob")).await.unwrap().is_empty());
+
+        let request = PinProgramRequest { program_id: "0xaa".to_string(), ..Default::default() };
+        let status = service.pin_program(Request::new(request)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+    }
+
+    #[tokio::test]
+    async fn test_proof_chunks() {
+        use futures::StreamExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let store: Arc<dyn JobStore> = Arc::new(store::FileJobStore::open(dir.path()).unwrap());
+        let service = ProvingService::coordinator(Arc::clone(&store));
+        let input = JobInput { elf: b"elf".to_vec(), stdin: b"stdin".to_vec() };
+        store.insert(store::tests::job("0x01", 1, None), input).await.unwrap();
+        let claim = store.claim(Duration::from_secs(60)).await.unwrap().unwrap();
+        let proof: Vec<u8> = (0..PROOF_CHUNK_SIZE + 10).map(|i| i as u8).collect();
+        let outcome = Ok(proof.clone());
+        store.complete("0x01", &claim.token, outcome, JobUsage::default(), None).await.unwrap();
+
+        let chunks = |offset: u64| {
+            let request = GetProofChunkRequest { request_id: "0x01".to_string(), offset };
+            service.get_proof_chunk(Request::new(request))
+        };
+        let data = |chunks: &[ProofChunk]| {
+            chunks.iter().flat_map(|chunk| chunk.data.iter().copied()).collect::<Vec<u8>>()
+        };
+        let downloaded: Vec<ProofChunk> =
+            chunks(0).await.unwrap().into_inner().map(Result::unwrap).collect().await;
+        assert_eq!(downloaded.len(), 2);
+        assert_eq!(downloaded[1].offset, PROOF_CHUNK_SIZE as u64);
+        assert_eq!(downloaded[1].total_size, proof.len() as u64);
+        assert_eq!(data(&downloaded), proof);
+
+        let resumed: Vec<ProofChunk> = chunks(PROOF_CHUNK_SIZE as u64 + 4)
+            .await
+            .unwrap()
+            .into_inner()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(data(&resumed), &proof[PROOF_CHUNK_SIZE + 4..]);
+        let past_end = chunks(proof.len() as u64 + 1).await.err().unwrap();
+        assert_eq!(past_end.code(), tonic::Code::OutOfRange);
+
+        let status = GetProofStatusRequest { request_id: "0x01".to_string(), omit_proof: true };
+        let status = service.get_proof_status(Request::new(status)).await.unwrap().into_inner();
+        assert_eq!(status.status, JobStatus::Succeeded as i32);
+        assert!(status.proof.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_execute_only_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let store: Arc<dyn JobStore> = Arc::new(store::FileJobStore::open(dir.path()).unwrap());
+        let service = ProvingService::coordinator(Arc::clone(&store));
+        let request = RequestProofRequest {
+            elf: b"elf".to_vec(),
+            stdin: bincode::serialize(&MONEROCHANStdin::new()).unwrap(),
+            proof_mode: ProofMode::ExecuteOnly as i32,
+            ..Default::default()
+        };
+        let request_id = service.request_proof(Request::new(request)).await.unwrap();
+        let request_id = request_id.into_inner().request_id;
+
+        let result = ExecutionResult { public_values: vec![1, 2, 3], cycles: 42, gas: Some(7) };
+        let claim = store.claim(Duration::from_secs(60)).await.unwrap().unwrap();
+        let usage = JobUsage { cycles: 42, proving_time_ms: 1 };
+        let outcome = Ok(result.encode_to_vec());
+        store.complete(&request_id, &claim.token, outcome, usage, None).await.unwrap();
+
+        let status = GetProofStatusRequest { request_id, omit_proof: false };
+        let status = service.get_proof_status(Request::new(status)).await.unwrap().into_inner();
+        assert_eq!(status.status, JobStatus::Succeeded as i32);
+        assert!(status.proof.is_empty());
+        assert_eq!(status.execution, Some(result));
+    }
+
+    #[tokio::test]
+    async fn test_list_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let store: Arc<dyn JobStore> = Arc::new(store::FileJobStore::open(dir.path()).unwrap());
+        let service = ProvingService::coordinator(Arc::clone(&store));
+        let input = JobInput { elf: b"elf".to_vec(), stdin: b"stdin".to_vec() };
+        for (request_id, created_at, program_id) in
+            [("0x01", 1, "0xaa"), ("0x02", 2, "0xbb"), ("0x03", 3, "0xaa"), ("0x04", 4, "0xaa")]
+        {
+            let job = Job {
+                program_id: program_id.to_string(),
+                ..store::tests::job(request_id, created_at, None)
+            };
+            store.insert(job, input.clone()).await.unwrap();
+        }
+        let claim = store.claim(Duration::from_secs(60)).await.unwrap().unwrap();
+        let outcome = Ok(b"proof".to_vec());
+        store.complete("0x01", &claim.token, outcome, JobUsage::default(), None).await.unwrap();
+
+        async fn list(
+            service: &ProvingService,
+            filter: ListProofRequestsRequest,
+        ) -> Result<(Vec<String>, String), Status> {
+            let page = service.list_proof_requests(Request::new(filter)).await?.into_inner();
+            let ids = page.requests.into_iter().map(|request| request.request_id).collect();
+            Ok((ids, page.next_page_token))
+        }
+        let program = ListProofRequestsRequest {
+            program_id: Some("0xaa".to_string()),
+            page_size: 2,
+            ..Default::default()
+        };
+        let (ids, token) = list(&service, program.clone()).await.unwrap();
+        assert_eq!((ids, token.as_str()), (vec!["0x01".to_string(), "0x03".to_string()], "0x03"));
+        let next = ListProofRequestsRequest { page_token: token, ..program.clone() };
+        assert_eq!(list(&service, next).await.unwrap(), (vec!["0x04".to_string()], String::new()));
+
+        let pending = ListProofRequestsRequest {
+            statuses: vec![JobStatus::Pending as i32],
+            created_after: Some(2),
+            created_before: Some(4),
+            ..Default::default()
+        };
+        let (ids, _) = list(&service, pending).await.unwrap();
+        assert_eq!(ids, ["0x02", "0x03"]);
+
+        let invalid = ListProofRequestsRequest { page_token: "0xff".to_string(), ..program };
+        assert_eq!(list(&service, invalid).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_receipt() {
+        let dir = tempfile::tempdir().unwrap();
+        let store: Arc<dyn JobStore> = Arc::new(store::FileJobStore::open(dir.path()).unwrap());
+        let service = ProvingService::coordinator(Arc::clone(&store)).receipt_key([9; 32]);
+        let request = RequestProofRequest {
+            program_id: format!("0x{}", hex::encode([5; 32])),
+            elf: b"elf".to_vec(),
+            stdin: bincode::serialize(&MONEROCHANStdin::new()).unwrap(),
+            proof_mode: ProofMode::ExecuteOnly as i32,
+            ..Default::default()
+        };
+        let request_id = service.request_proof(Request::new(request)).await.unwrap();
+        let request_id = request_id.into_inner().request_id;
+
+        let status = || {
+            let status = GetProofStatusRequest { request_id: request_id.clone(), omit_proof: true };
+            service.get_proof_status(Request::new(status))
+        };
+        assert!(status().await.unwrap().into_inner().receipt.is_none());
+
+        let result = ExecutionResult { public_values: vec![1, 2, 3], cycles: 42, gas: None };
+        let claim = store.claim(Duration::from_secs(60)).await.unwrap().unwrap();
+        let usage = JobUsage { cycles: 42, proving_time_ms: 1 };
+        let outcome = Ok(result.encode_to_vec());
+        let key = &service.receipt_key;
+        record_outcome(store.as_ref(), key, &request_id, &claim.token, outcome, usage)
+            .await
+            .unwrap();
+
+        // The receipt is signed at completion, and the status returns the stored one.
+        let stored = store.receipt(&request_id).await.unwrap().unwrap();
+        let signed = status().await.unwrap().into_inner().receipt.unwrap();
+        assert_eq!(signed.encode_to_vec(), stored);
+        let network_key = ed25519_dalek::SigningKey::from_bytes(&[9; 32]).verifying_key();
+        let receipt =
+            crate::network::receipt::verify_receipt(&signed, network_key.as_bytes()).unwrap();
+        assert_eq!(receipt.request_id, request_id);
+        assert_eq!(receipt.vk_hash, [5; 32]);
+        assert_eq!(receipt.cycles, 42);
+        assert!(receipt.matches_public_values(&[1, 2, 3]));
+
+        // A malformed outcome cannot be signed for, so its job fails.
+        let request = RequestProofRequest {
+            program_id: String::new(),
+            elf: b"elf".to_vec(),
+            stdin: bincode::serialize(&MONEROCHANStdin::new()).unwrap(),
+            proof_mode: ProofMode::ExecuteOnly as i32,
+            ..Default::default()
+        };
+        let response = service.request_proof(Request::new(request.clone())).await.unwrap();
+        let request_id = response.into_inner().request_id;
+        let claim = store.claim(Duration::from_secs(60)).await.unwrap().unwrap();
+        let outcome = Ok(b"garbage".to_vec());
+        let key = &service.receipt_key;
+        record_outcome(store.as_ref(), key, &request_id, &claim.token, outcome, usage)
+            .await
+            .unwrap();
+        let job = store.get(&request_id).await.unwrap().unwrap();
+        assert_eq!(job.status, JobStatus::Failed as i32);
+        assert!(store.receipt(&request_id).await.unwrap().is_none());
+
+        // The program id of a request is the vk hash of its receipt.
+        for program_id in ["0xaa", "aa".repeat(32).as_str(), "0xzz"] {
+            let request =
+                RequestProofRequest { program_id: program_id.to_string(), ..request.clone() };
+            let err = service.request_proof(Request::new(request)).await.unwrap_err();
+            assert_eq!(err.code(), tonic::Code::InvalidArgument);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_labels() {
+        let dir = tempfile::tempdir().unwrap();
+        let store: Arc<dyn JobStore> = Arc::new(store::FileJobStore::open(dir.path()).unwrap());
+        let service = ProvingService::coordinator(Arc::clone(&store));
+        let request = |labels: &[(&str, &str)]| RequestProofRequest {
+            elf: b"elf".to_vec(),
+            stdin: bincode::serialize(&MONEROCHANStdin::new()).unwrap(),
+            proof_mode: ProofMode::Core as i32,
+            labels: labels.iter().map(|(k, v)| ((*k).to_string(), (*v).to_string())).collect(),
+            ..Default::default()
+        };
+
+        let bridge = request(&[("team", "bridge"), ("env", "staging")]);
+        let response = service.request_proof(Request::new(bridge.clone())).await.unwrap();
+        let request_id = response.into_inner().request_id;
+        service.request_proof(Request::new(request(&[("team", "wallet")]))).await.unwrap();
+
+        let status = GetProofStatusRequest { request_id: request_id.clone(), omit_proof: true };
+        let status = service.get_proof_status(Request::new(status)).await.unwrap().into_inner();
+        assert_eq!(status.labels, bridge.labels);
+
+        let list = |labels: &[(&str, &str)]| {
+            let filter =
+                ListProofRequestsRequest { labels: request(labels).labels, ..Default::default() };
+            service.list_proof_requests(Request::new(filter))
+        };
+        let requests = list(&[("team", "bridge")]).await.unwrap().into_inner().requests;
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].request_id, request_id);
+        assert_eq!(list(&[]).await.unwrap().into_inner().requests.len(), 2);
+        assert!(list(&[("env", "prod")]).await.unwrap().into_inner().requests.is_empty());
+
+        let status = service.request_proof(Request::new(request(&[("", "x")]))).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let store: Arc<dyn JobStore> = Arc::new(store::FileJobStore::open(dir.path()).unwrap());
+        let service = ProvingService::coordinator(Arc::clone(&store));
+        let request = RequestProofRequest {
+            elf: b"elf".to_vec(),
+            stdin: bincode::serialize(&MONEROCHANStdin::new()).unwrap(),
+            proof_mode: ProofMode::Core as i32,
+            strategy: FulfillmentStrategy::Hosted as i32,
+            ..Default::default()
+        };
+
+        let auction = FulfillmentStrategy::Auction as i32;
+        let unsupported = [
+            RequestProofRequest { strategy: auction, ..request.clone() },
+            RequestProofRequest { min_auction_period: 10, ..request.clone() },
+            RequestProofRequest { whitelist: vec![vec![1; 20]], ..request.clone() },
+            RequestProofRequest { max_price_per_pgu: Some(1_000), ..request.clone() },
+            RequestProofRequest { auction_timeout_secs: Some(60), ..request.clone() },
+            RequestProofRequest { treasury: vec![1; 20], ..request.clone() },
+        ];
+        for unsupported in unsupported {
+            let status = service.request_proof(Request::new(unsupported)).await.unwrap_err();
+            assert_eq!(status.code(), tonic::Code::Unimplemented);
+        }
+        assert!(store.list(None).await.unwrap().is_empty());
+        service.request_proof(Request::new(request)).await.unwrap();
+    }
+}
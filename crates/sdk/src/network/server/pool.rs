@@ -0,0 +1,717 @@
+//! # Worker Pool
+//!
+//! Remote workers that prove the jobs of a [`ProvingService`](super::ProvingService).
+//!
+//! A worker registers with the service, advertising whether it proves on a CPU or a GPU, and then
+//! polls it for jobs. Each claimed job is leased to its worker, which heartbeats while it proves
+//! and finally uploads the proof or the error. A worker that stops heartbeating, because it
+//! crashed or lost its connection, loses its job to the next worker once the lease expires. A job
+//! whose workers all vanished after [`MAX_ATTEMPTS`] claims fails instead, so that a job crashing
+//! its workers does not take the whole pool down. Jobs are dispatched whole, never split across
+//! workers.
+//!
+//! Idle GPU workers take precedence: a CPU worker is only handed a job while every GPU worker is
+//! busy.
+//!
+//! ```rust,no_run
+//! use monerochan::{
+//!     network::{proto::worker::WorkerCapability, server::PoolWorker},
+//!     ProverClient,
+//! };
+//!
+//! # tokio_test::block_on(async {
+//! let prover = ProverClient::builder().cuda().build();
+//! PoolWorker::new("http://10.0.0.1:50052", Box::new(prover), WorkerCapability::Gpu)
+//!     .token("worker-token")
+//!     .run()
+//!     .await
+//!     .unwrap();
+//! # });
+//! ```
+//!
+//! The coordinator keeps its workers in memory, so they register again when it restarts, and
+//! trusts them with the jobs and their outcomes: the pool is served on a listener of its own, and
+//! only to the workers that present the token of the service, set with [`PoolWorker::token`].
+//! That listener must still only be reachable by the workers.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context, Result};
+use monerochan_prover::{components::CpuProverComponents, MONEROCHANProvingKey};
+use tokio::time::{interval_at, sleep, Instant};
+use tonic::{
+    service::Interceptor,
+    transport::{Channel, Endpoint},
+    Code, Request, Response, Status,
+};
+
+use super::{
+    admin::constant_time_eq, claimed_job, internal, prove, record_outcome, unix_timestamp, Claim,
+    JobStore, JobUsage, ReceiptKey, DEFAULT_MAX_MESSAGE_SIZE, POLL_INTERVAL,
+};
+use crate::{
+    network::{
+        proto::worker::{
+            complete_job_request::Outcome, worker_pool_client::WorkerPoolClient,
+            worker_pool_server::WorkerPool, ClaimJobRequest, ClaimJobResponse, ClaimedJob,
+            CompleteJobRequest, CompleteJobResponse, HeartbeatRequest, HeartbeatResponse,
+            ListWorkersRequest, ListWorkersResponse, RegisterWorkerRequest, RegisterWorkerResponse,
+            WorkerCapability, WorkerInfo,
+        },
+        retry::retry_operation,
+    },
+    Prover,
+};
+
+/// How many workers may claim a job before it fails.
+pub const MAX_ATTEMPTS: u32 = 3;
+
+/// How often a worker heartbeats while it proves a job.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long a job stays leased to a worker, and the worker registered, without hearing from it.
+const WORKER_LEASE: Duration = Duration::from_secs(90);
+
+/// Rejects the calls that do not carry the worker token.
+#[derive(Clone)]
+pub struct WorkerAuth {
+    token: String,
+}
+
+impl WorkerAuth {
+    pub(crate) fn new(token: String) -> Self {
+        Self { token }
+    }
+}
+
+impl Interceptor for WorkerAuth {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let token = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        match token {
+            Some(token) if constant_time_eq(token.as_bytes(), self.token.as_bytes()) => Ok(request),
+            _ => Err(Status::unauthenticated("invalid worker token")),
+        }
+    }
+}
+
+/// The worker pool of a [`ProvingService`](super::ProvingService), which dispatches the jobs of
+/// its store to the registered workers.
+///
+/// See the [module documentation](self) for an overview.
+#[derive(Clone)]
+pub struct Coordinator {
+    store: Arc<dyn JobStore>,
+    state: Arc<Mutex<PoolState>>,
+    receipt_key: ReceiptKey,
+}
+
+#[derive(Default)]
+struct PoolState {
+    workers: HashMap<String, WorkerEntry>,
+    /// How many workers claimed each unfinished job.
+    attempts: HashMap<String, u32>,
+}
+
+struct WorkerEntry {
+    name: String,
+    capability: WorkerCapability,
+    /// The job the worker proves, if any.
+    job: Option<Assignment>,
+    /// When the worker last contacted the coordinator, in unix seconds.
+    last_seen: i64,
+}
+
+/// A job assigned to a worker, with the token of the claim the coordinator made for it.
+struct Assignment {
+    request_id: String,
+    token: String,
+}
+
+impl WorkerEntry {
+    /// Returns the token of the claim of a job, if the job is assigned to the worker.
+    fn token(&self, request_id: &str) -> Option<String> {
+        self.job.as_ref().filter(|job| job.request_id == request_id).map(|job| job.token.clone())
+    }
+}
+
+impl PoolState {
+    /// Forgets the workers that have not been heard from for a lease.
+    fn prune(&mut self) {
+        let expired = unix_timestamp() - WORKER_LEASE.as_secs() as i64;
+        self.workers.retain(|_, worker| worker.last_seen >= expired);
+    }
+
+    /// Returns a registered worker, and records that it was just heard from.
+    fn touch(&mut self, worker_id: &str) -> Result<&mut WorkerEntry, Status> {
+        self.prune();
+        let worker = self
+            .workers
+            .get_mut(worker_id)
+            .ok_or_else(|| Status::not_found(format!("unknown worker {worker_id}")))?;
+        worker.last_seen = unix_timestamp();
+        Ok(worker)
+    }
+
+    fn has_idle_gpu(&self) -> bool {
+        self.workers
+            .values()
+            .any(|worker| worker.capability == WorkerCapability::Gpu && worker.job.is_none())
+    }
+}
+
+impl Coordinator {
+    /// Creates a pool that dispatches the jobs of `store`.
+    #[must_use]
+    pub fn new(store: Arc<dyn JobStore>) -> Self {
+        Self::with_receipt_key(store, ReceiptKey::default())
+    }
+
+    /// Creates a pool that dispatches the jobs of `store`, and signs the receipts of the succeeded
+    /// ones with the key of its service.
+    pub(crate) fn with_receipt_key(store: Arc<dyn JobStore>, receipt_key: ReceiptKey) -> Self {
+        Self { store, state: Arc::default(), receipt_key }
+    }
+
+    fn state(&self) -> MutexGuard<'_, PoolState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[tonic::async_trait]
+impl WorkerPool for Coordinator {
+    async fn register_worker(
+        &self,
+        request: Request<RegisterWorkerRequest>,
+    ) -> Result<Response<RegisterWorkerResponse>, Status> {
+        let request = request.into_inner();
+        let capability = match WorkerCapability::try_from(request.capability) {
+            Ok(capability @ (WorkerCapability::Cpu | WorkerCapability::Gpu)) => capability,
+            Ok(WorkerCapability::Unspecified) | Err(_) => {
+                return Err(Status::invalid_argument("the worker must advertise a capability"));
+            }
+        };
+
+        let worker_id = uuid::Uuid::new_v4().to_string();
+        tracing::info!(%worker_id, name = %request.name, ?capability, "worker registered");
+        self.state().workers.insert(
+            worker_id.clone(),
+            WorkerEntry {
+                name: request.name,
+                capability,
+                job: None,
+                last_seen: unix_timestamp(),
+            },
+        );
+        Ok(Response::new(RegisterWorkerResponse {
+            worker_id,
+            heartbeat_interval_secs: HEARTBEAT_INTERVAL.as_secs(),
+        }))
+    }
+
+    async fn claim_job(
+        &self,
+        request: Request<ClaimJobRequest>,
+    ) -> Result<Response<ClaimJobResponse>, Status> {
+        let worker_id = request.into_inner().worker_id;
+        {
+            let mut state = self.state();
+            let worker = state.touch(&worker_id)?;
+            // A worker claiming again gave up on its previous job, which is retried once its
+            // lease expires.
+            worker.job = None;
+            if worker.capability == WorkerCapability::Cpu && state.has_idle_gpu() {
+                return Ok(Response::new(ClaimJobResponse { job: None }));
+            }
+        }
+
+        loop {
+            let Some(Claim { job, token }) =
+                self.store.claim(WORKER_LEASE).await.map_err(internal)?
+            else {
+                return Ok(Response::new(ClaimJobResponse { job: None }));
+            };
+            let request_id = job.request_id.clone();
+
+            let attempts = {
+                let mut state = self.state();
+                let attempts = state.attempts.entry(request_id.clone()).or_default();
+                *attempts += 1;
+                *attempts
+            };
+            if attempts > MAX_ATTEMPTS {
+                tracing::warn!(%request_id, "job abandoned by {MAX_ATTEMPTS} workers");
+                let error = format!("the job was abandoned by {MAX_ATTEMPTS} workers");
+                let usage = JobUsage::default();
+                let completed = self.store.complete(&request_id, &token, Err(error), usage, None);
+                completed.await.map_err(internal)?;
+                self.state().attempts.remove(&request_id);
+                continue;
+            }
+
+            let input = self.store.input(&request_id).await.map_err(internal)?;
+            if let Some(worker) = self.state().workers.get_mut(&worker_id) {
+                worker.job = Some(Assignment { request_id: request_id.clone(), token });
+            }
+            tracing::info!(%request_id, %worker_id, attempts, "job dispatched");
+            return Ok(Response::new(ClaimJobResponse { job: Some(claimed_job(&job, input)) }));
+        }
+    }
+
+    async fn heartbeat(
+        &self,
+        request: Request<HeartbeatRequest>,
+    ) -> Result<Response<HeartbeatResponse>, Status> {
+        let HeartbeatRequest { worker_id, request_id } = request.into_inner();
+        let token = self.state().touch(&worker_id)?.token(&request_id);
+        let renewed = match token {
+            Some(token) => {
+                self.store.renew(&request_id, &token, WORKER_LEASE).await.map_err(internal)?
+            }
+            None => false,
+        };
+        Ok(Response::new(HeartbeatResponse { abandon: !renewed }))
+    }
+
+    async fn complete_job(
+        &self,
+        request: Request<CompleteJobRequest>,
+    ) -> Result<Response<CompleteJobResponse>, Status> {
+        let CompleteJobRequest { worker_id, request_id, outcome, cycles, proving_time_ms } =
+            request.into_inner();
+        let outcome = match outcome {
+            Some(Outcome::Proof(proof)) => Ok(proof),
+            Some(Outcome::Error(error)) => Err(error),
+            None => return Err(Status::invalid_argument("the completion has no outcome")),
+        };
+
+        // The outcome of a worker whose job was reassigned is discarded, by this coordinator or
+        // by the store if another service claimed the job again.
+        let token = {
+            let mut state = self.state();
+            let worker = state.touch(&worker_id)?;
+            let token = worker.token(&request_id);
+            if token.is_some() {
+                worker.job = None;
+            }
+            token
+        };
+        let Some(token) = token else {
+            return Ok(Response::new(CompleteJobResponse { accepted: false }));
+        };
+
+        let usage = JobUsage { cycles, proving_time_ms };
+        let store = self.store.as_ref();
+        let accepted =
+            record_outcome(store, &self.receipt_key, &request_id, &token, outcome, usage)
+                .await
+                .map_err(internal)?;
+        self.state().attempts.remove(&request_id);
+        tracing::info!(%request_id, %worker_id, accepted, "job completed");
+        Ok(Response::new(CompleteJobResponse { accepted }))
+    }
+
+    async fn list_workers(
+        &self,
+        _: Request<ListWorkersRequest>,
+    ) -> Result<Response<ListWorkersResponse>, Status> {
+        let mut state = self.state();
+        state.prune();
+        let mut workers: Vec<_> = state
+            .workers
+            .iter()
+            .map(|(worker_id, worker)| WorkerInfo {
+                worker_id: worker_id.clone(),
+                name: worker.name.clone(),
+                capability: worker.capability as i32,
+                request_id: worker.job.as_ref().map(|job| job.request_id.clone()),
+                last_seen: worker.last_seen,
+            })
+            .collect();
+        workers.sort_by(|a, b| (&a.name, &a.worker_id).cmp(&(&b.name, &b.worker_id)));
+        Ok(Response::new(ListWorkersResponse { workers }))
+    }
+}
+
+/// A remote worker, which proves the jobs of a [`Coordinator`] with a local prover.
+///
+/// See the [module documentation](self) for an overview.
+pub struct PoolWorker {
+    coordinator: String,
+    name: String,
+    capability: WorkerCapability,
+    prover: Arc<dyn Prover<CpuProverComponents>>,
+    token: Option<String>,
+    max_message_size: usize,
+}
+
+impl PoolWorker {
+    /// Creates a worker for the proving service at `coordinator`, that proves with the given
+    /// prover on the advertised hardware.
+    #[must_use]
+    pub fn new(
+        coordinator: impl Into<String>,
+        prover: Box<dyn Prover<CpuProverComponents>>,
+        capability: WorkerCapability,
+    ) -> Self {
+        Self {
+            coordinator: coordinator.into(),
+            name: "monerochan-worker".to_string(),
+            capability,
+            prover: Arc::from(prover),
+            token: None,
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+        }
+    }
+
+    /// Sets the name the worker is listed under, `monerochan-worker` by default.
+    #[must_use]
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Sets the token the worker presents to the coordinator, set there with
+    /// [`ProvingService::worker_token`](super::ProvingService::worker_token).
+    #[must_use]
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Sets the maximum size of the gRPC messages the worker accepts and sends, in bytes,
+    /// [`DEFAULT_MAX_MESSAGE_SIZE`] by default.
+    #[must_use]
+    pub fn max_message_size(mut self, bytes: usize) -> Self {
+        self.max_message_size = bytes;
+        self
+    }
+
+    /// Wraps a message in a request that carries the token of the worker.
+    fn request<T>(&self, message: T) -> Result<Request<T>> {
+        let mut request = Request::new(message);
+        if let Some(token) = &self.token {
+            let value = format!("Bearer {token}").parse().context("invalid worker token")?;
+            request.metadata_mut().insert("authorization", value);
+        }
+        Ok(request)
+    }
+
+    /// Proves the jobs of the coordinator until the process is stopped.
+    ///
+    /// The worker waits for the coordinator to be reachable, and registers again when the
+    /// coordinator restarts.
+    pub async fn run(self) -> Result<()> {
+        // Fail on an invalid token now rather than on every call.
+        self.request(())?;
+        let channel = Endpoint::from_shared(self.coordinator.clone())
+            .context("invalid coordinator url")?
+            .connect_lazy();
+        let client = WorkerPoolClient::new(channel)
+            .max_decoding_message_size(self.max_message_size)
+            .max_encoding_message_size(self.max_message_size);
+
+        let keys = Arc::new(Mutex::new(HashMap::new()));
+        let mut registration = self.register(&client).await?;
+        loop {
+            let request = ClaimJobRequest { worker_id: registration.worker_id.clone() };
+            let job = match client.clone().claim_job(self.request(request)?).await {
+                Ok(response) => response.into_inner().job,
+                Err(status) if status.code() == Code::Unauthenticated => {
+                    return Err(anyhow!("the coordinator rejected the worker token"));
+                }
+                Err(status) if status.code() == Code::NotFound => {
+                    tracing::warn!("the coordinator forgot this worker, registering again");
+                    registration = self.register(&client).await?;
+                    continue;
+                }
+                Err(status) => {
+                    tracing::warn!("failed to claim a job: {}", status.message());
+                    None
+                }
+            };
+
+            match job {
+                Some(job) => self.work(&client, &registration, job, &keys).await,
+                None => sleep(POLL_INTERVAL).await,
+            }
+        }
+    }
+
+    async fn register(&self, client: &WorkerPoolClient<Channel>) -> Result<RegisterWorkerResponse> {
+        let request =
+            RegisterWorkerRequest { name: self.name.clone(), capability: self.capability as i32 };
+        let registration = retry_operation(
+            || {
+                let mut client = client.clone();
+                let request = self.request(request.clone());
+                async move { Ok(client.register_worker(request?).await?.into_inner()) }
+            },
+            None,
+            "registering the worker",
+        )
+        .await?;
+        tracing::info!(worker_id = %registration.worker_id, "registered with the coordinator");
+        Ok(registration)
+    }
+
+    /// Proves a job while heartbeating, and uploads its outcome.
+    async fn work(
+        &self,
+        client: &WorkerPoolClient<Channel>,
+        registration: &RegisterWorkerResponse,
+        job: ClaimedJob,
+        keys: &Arc<Mutex<HashMap<[u8; 32], MONEROCHANProvingKey>>>,
+    ) {
+        let worker_id = registration.worker_id.clone();
+        let request_id = job.request_id.clone();
+        tracing::info!(%request_id, "proving");
+
+        let prover = Arc::clone(&self.prover);
+        let keys = Arc::clone(keys);
+        let mut proving = tokio::task::spawn_blocking(move || {
+            let mut keys = keys.lock().unwrap_or_else(PoisonError::into_inner);
+            prove(prover.as_ref(), &mut keys, &job)
+        });
+
+        let period = Duration::from_secs(registration.heartbeat_interval_secs.max(1));
+        let mut heartbeat = interval_at(Instant::now() + period, period);
+        let mut abandoned = false;
+        let (outcome, usage) = loop {
+            tokio::select! {
+                outcome = &mut proving => {
+                    break outcome.unwrap_or_else(|err| {
+                        (Err(anyhow!("the prover task failed: {err}")), JobUsage::default())
+                    });
+                }
+                _ = heartbeat.tick() => {
+                    let request = HeartbeatRequest {
+                        worker_id: worker_id.clone(),
+                        request_id: request_id.clone(),
+                    };
+                    let Ok(request) = self.request(request) else { continue };
+                    match client.clone().heartbeat(request).await {
+                        Ok(response) => {
+                            if response.into_inner().abandon && !abandoned {
+                                tracing::warn!(%request_id, "the job was cancelled or reassigned");
+                                abandoned = true;
+                            }
+                        }
+                        Err(status) => {
+                            let error = status.message();
+                            tracing::warn!(%request_id, "failed to heartbeat: {error}");
+                        }
+                    }
+                }
+            }
+        };
+        if abandoned {
+            tracing::info!(%request_id, "discarding the outcome of the abandoned job");
+            return;
+        }
+
+        let outcome = match outcome {
+            Ok(proof) => Outcome::Proof(proof),
+            Err(err) => {
+                tracing::warn!(%request_id, "proving failed: {err:#}");
+                Outcome::Error(format!("{err:#}"))
+            }
+        };
+        let request = CompleteJobRequest {
+            worker_id,
+            request_id,
+            outcome: Some(outcome),
+            cycles: usage.cycles,
+            proving_time_ms: usage.proving_time_ms,
+        };
+        let completed = retry_operation(
+            || {
+                let mut client = client.clone();
+                let request = self.request(request.clone());
+                async move { Ok(client.complete_job(request?).await?.into_inner()) }
+            },
+            Some(WORKER_LEASE),
+            "completing the job",
+        )
+        .await;
+
+        let request_id = &request.request_id;
+        match completed {
+            Ok(response) if response.accepted => tracing::info!(%request_id, "job completed"),
+            Ok(_) => tracing::info!(%request_id, "job was reassigned, discarding its outcome"),
+            Err(err) => tracing::error!(%request_id, "failed to complete job: {err:#}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::{
+        proto::api::JobStatus,
+        server::store::{tests::job, FileJobStore, JobInput},
+    };
+
+    async fn register(coordinator: &Coordinator, capability: WorkerCapability) -> String {
+        let request =
+            RegisterWorkerRequest { name: "test".to_string(), capability: capability as i32 };
+        coordinator.register_worker(Request::new(request)).await.unwrap().into_inner().worker_id
+    }
+
+    async fn claim(coordinator: &Coordinator, worker_id: &str) -> Option<ClaimedJob> {
+        let request = ClaimJobRequest { worker_id: worker_id.to_string() };
+        coordinator.claim_job(Request::new(request)).await.unwrap().into_inner().job
+    }
+
+    #[test]
+    fn test_worker_auth() {
+        let mut auth = WorkerAuth::new("secret".to_string());
+        let request = |value: Option<&str>| {
+            let mut request = Request::new(());
+            if let Some(value) = value {
+                request.metadata_mut().insert("authorization", value.parse().unwrap());
+            }
+            request
+        };
+
+        assert!(auth.call(request(Some("Bearer secret"))).is_ok());
+        assert!(auth.call(request(Some("Bearer other"))).is_err());
+        assert!(auth.call(request(None)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_coordinator() {
+        let dir = tempfile::tempdir().unwrap();
+        let store: Arc<dyn JobStore> = Arc::new(FileJobStore::open(dir.path()).unwrap());
+        let input = JobInput { elf: b"elf".to_vec(), stdin: b"stdin".to_vec() };
+        store.insert(job("0x01", 1, None), input.clone()).await.unwrap();
+        store.insert(job("0x02", 2, None), input).await.unwrap();
+
+        let coordinator = Coordinator::new(Arc::clone(&store));
+        let cpu = register(&coordinator, WorkerCapability::Cpu).await;
+        let gpu = register(&coordinator, WorkerCapability::Gpu).await;
+
+        // The idle GPU worker takes precedence over the CPU worker.
+        assert!(claim(&coordinator, &cpu).await.is_none());
+        let claimed = claim(&coordinator, &gpu).await.unwrap();
+        assert_eq!(claimed.request_id, "0x01");
+        assert_eq!(claimed.elf, b"elf");
+        assert_eq!(claim(&coordinator, &cpu).await.unwrap().request_id, "0x02");
+
+        let heartbeat = |worker_id: &str, request_id: &str| {
+            let request = HeartbeatRequest {
+                worker_id: worker_id.to_string(),
+                request_id: request_id.to_string(),
+            };
+            coordinator.heartbeat(Request::new(request))
+        };
+        assert!(!heartbeat(&gpu, "0x01").await.unwrap().into_inner().abandon);
+        assert!(heartbeat(&cpu, "0x01").await.unwrap().into_inner().abandon);
+
+        // Only the worker the job is assigned to may complete it.
+        let complete = |worker_id: &str, request_id: &str| {
+            let request = CompleteJobRequest {
+                worker_id: worker_id.to_string(),
+                request_id: request_id.to_string(),
+                outcome: Some(Outcome::Proof(b"proof".to_vec())),
+                cycles: 1000,
+                proving_time_ms: 2000,
+            };
+            coordinator.complete_job(Request::new(request))
+        };
+        assert!(!complete(&cpu, "0x01").await.unwrap().into_inner().accepted);
+        assert!(complete(&gpu, "0x01").await.unwrap().into_inner().accepted);
+        assert_eq!(store.get("0x01").await.unwrap().unwrap().status, JobStatus::Succeeded as i32);
+        assert_eq!(store.proof("0x01").await.unwrap().unwrap(), b"proof");
+
+        let workers = coordinator
+            .list_workers(Request::new(ListWorkersRequest {}))
+            .await
+            .unwrap()
+            .into_inner()
+            .workers;
+        assert_eq!(workers.len(), 2);
+        let busy: Vec<_> =
+            workers.iter().filter_map(|worker| worker.request_id.as_deref()).collect();
+        assert_eq!(busy, ["0x02"]);
+
+        let request = ClaimJobRequest { worker_id: "unknown".to_string() };
+        let status = coordinator.claim_job(Request::new(request)).await.unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_job_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let store: Arc<dyn JobStore> = Arc::new(FileJobStore::open(dir.path()).unwrap());
+        let input = JobInput { elf: b"elf".to_vec(), stdin: b"stdin".to_vec() };
+        store.insert(job("0x01", 1, None), input).await.unwrap();
+
+        let coordinator = Coordinator::new(Arc::clone(&store));
+        for _ in 0..MAX_ATTEMPTS {
+            let worker_id = register(&coordinator, WorkerCapability::Gpu).await;
+            assert_eq!(claim(&coordinator, &worker_id).await.unwrap().request_id, "0x01");
+            // The worker vanishes, and the lease of its job expires.
+            let token = coordinator.state().workers[&worker_id].token("0x01").unwrap();
+            store.renew("0x01", &token, Duration::ZERO).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(1100)).await;
+        }
+
+        let worker_id = register(&coordinator, WorkerCapability::Gpu).await;
+        assert!(claim(&coordinator, &worker_id).await.is_none());
+        let job = store.get("0x01").await.unwrap().unwrap();
+        assert_eq!(job.status, JobStatus::Failed as i32);
+        assert!(job.error_message.contains("abandoned"));
+    }
+
+    #[tokio::test]
+    async fn test_reclaimed_job_rejects_stale_worker() {
+        let dir = tempfile::tempdir().unwrap();
+        let store: Arc<dyn JobStore> = Arc::new(FileJobStore::open(dir.path()).unwrap());
+        let input = JobInput { elf: b"elf".to_vec(), stdin: b"stdin".to_vec() };
+        store.insert(job("0x01", 1, None), input).await.unwrap();
+
+        // Two services share the store, and the job is claimed again through the second one
+        // once the lease of the first worker expires.
+        let first = Coordinator::new(Arc::clone(&store));
+        let second = Coordinator::new(Arc::clone(&store));
+        let stale = register(&first, WorkerCapability::Gpu).await;
+        assert_eq!(claim(&first, &stale).await.unwrap().request_id, "0x01");
+        let token = first.state().workers[&stale].token("0x01").unwrap();
+        store.renew("0x01", &token, Duration::ZERO).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        let current = register(&second, WorkerCapability::Gpu).await;
+        assert_eq!(claim(&second, &current).await.unwrap().request_id, "0x01");
+
+        let heartbeat = |coordinator: &Coordinator, worker_id: &str| {
+            let request =
+                HeartbeatRequest { worker_id: worker_id.to_string(), request_id: "0x01".into() };
+            let coordinator = coordinator.clone();
+            async move { coordinator.heartbeat(Request::new(request)).await.unwrap().into_inner() }
+        };
+        assert!(heartbeat(&first, &stale).await.abandon);
+        assert!(!heartbeat(&second, &current).await.abandon);
+
+        let complete = |coordinator: &Coordinator, worker_id: &str, proof: &[u8]| {
+            let request = CompleteJobRequest {
+                worker_id: worker_id.to_string(),
+                request_id: "0x01".to_string(),
+                outcome: Some(Outcome::Proof(proof.to_vec())),
+                cycles: 0,
+                proving_time_ms: 0,
+            };
+            let coordinator = coordinator.clone();
+            async move { coordinator.complete_job(Request::new(request)).await.unwrap() }
+        };
+        assert!(!complete(&first, &stale, b"stale").await.into_inner().accepted);
+        assert!(complete(&second, &current, b"proof").await.into_inner().accepted);
+        assert_eq!(store.proof("0x01").await.unwrap().unwrap(), b"proof");
+    }
+}
@@ -0,0 +1,441 @@
+//! A [`JobStore`] backed by a Postgres database.
+
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use tokio_postgres::{types::ToSql, Client, NoTls, Row, Transaction};
+
+use super::{
+    store::{claim_token, Claim, Job, JobFilter, JobInput, JobStore, JobUsage, CANCELLED_MESSAGE},
+    unix_timestamp,
+};
+use crate::network::proto::api::JobStatus;
+
+/// The migrations of the schema, in order. The version of a database is the number of migrations
+/// applied to it, recorded in `monerochan_schema`. Applied migrations must never change.
+const MIGRATIONS: &[&str] = &[
+    // 1: the jobs.
+    "CREATE TABLE IF NOT EXISTS monerochan_jobs (
+        request_id TEXT PRIMARY KEY,
+        program_id TEXT NOT NULL,
+        proof_mode INTEGER NOT NULL,
+        status INTEGER NOT NULL,
+        error_message TEXT NOT NULL,
+        created_at BIGINT NOT NULL,
+        skip_simulation BOOLEAN NOT NULL,
+        cycle_limit BIGINT,
+        client_address TEXT,
+        idempotency_key TEXT UNIQUE,
+        elf BYTEA NOT NULL,
+        stdin BYTEA NOT NULL,
+        proof BYTEA,
+        lease_expires_at BIGINT NOT NULL DEFAULT 0
+    );
+    CREATE INDEX IF NOT EXISTS monerochan_jobs_status ON monerochan_jobs (status, created_at);",
+    // 2: the usage of the jobs.
+    "ALTER TABLE monerochan_jobs ADD COLUMN cycles BIGINT NOT NULL DEFAULT 0,
+        ADD COLUMN proving_time_ms BIGINT NOT NULL DEFAULT 0;",
+    // 3: the pinned programs.
+    "CREATE TABLE monerochan_pins (
+        client_address TEXT NOT NULL,
+        program_id TEXT NOT NULL,
+        PRIMARY KEY (client_address, program_id)
+    );",
+    // 4: the labels of the jobs.
+    "ALTER TABLE monerochan_jobs ADD COLUMN labels TEXT NOT NULL DEFAULT '{}';",
+    // 5: idempotency keys scoped by client.
+    "ALTER TABLE monerochan_jobs DROP CONSTRAINT monerochan_jobs_idempotency_key_key;
+    CREATE UNIQUE INDEX monerochan_jobs_idempotency
+        ON monerochan_jobs ((COALESCE(client_address, '')), idempotency_key);",
+    // 6: the signed receipts of the jobs.
+    "ALTER TABLE monerochan_jobs ADD COLUMN receipt BYTEA;",
+    // 7: the indexes of the pages of the listings.
+    "CREATE INDEX monerochan_jobs_created ON monerochan_jobs (created_at, request_id);
+    CREATE INDEX monerochan_jobs_client
+        ON monerochan_jobs (client_address, created_at, request_id);",
+    // 8: the tokens fencing the leases.
+    "ALTER TABLE monerochan_jobs ADD COLUMN lease_token TEXT;",
+];
+
+/// An arbitrary key of the advisory lock held while migrating, so that the services sharing the
+/// database do not migrate it twice.
+const MIGRATION_LOCK: i64 = 0x6d6f_6e65_726f;
+
+const JOB_COLUMNS: &str = "request_id, program_id, proof_mode, status, error_message, \
+                           created_at, skip_simulation, cycle_limit, client_address, \
+                           idempotency_key, cycles, proving_time_ms, labels";
+
+/// A [`JobStore`] backed by a Postgres database.
+///
+/// The database may be shared by several services, each claiming jobs from the same queue. The
+/// connection is not encrypted, so the database must be reachable over a trusted network.
+pub struct PostgresJobStore {
+    client: Client,
+}
+
+impl PostgresJobStore {
+    /// Connects to the database at `url`, and creates or migrates the `monerochan_jobs` and
+    /// `monerochan_pins` tables if needed.
+    pub async fn connect(url: &str) -> Result<Self> {
+        let (mut client, connection) =
+            tokio_postgres::connect(url, NoTls).await.context("failed to connect to postgres")?;
+        tokio::spawn(async move {
+            if let Err(err) = connection.await {
+                tracing::error!("postgres connection failed: {err}");
+            }
+        });
+        migrate(&mut client).await?;
+        Ok(Self { client })
+    }
+}
+
+/// Applies the migrations the database lacks, at once.
+async fn migrate(client: &mut Client) -> Result<()> {
+    let tx = client.transaction().await?;
+    tx.execute("SELECT pg_advisory_xact_lock($1)", &[&MIGRATION_LOCK]).await?;
+    tx.batch_execute("CREATE TABLE IF NOT EXISTS monerochan_schema (version BIGINT NOT NULL)")
+        .await?;
+    let version = match tx.query_opt("SELECT version FROM monerochan_schema", &[]).await? {
+        Some(row) => row.get::<_, i64>(0) as usize,
+        None => {
+            let version = unversioned_version(&tx).await?;
+            tx.execute("INSERT INTO monerochan_schema (version) VALUES ($1)", &[&(version as i64)])
+                .await?;
+            version
+        }
+    };
+    if version > MIGRATIONS.len() {
+        bail!(
+            "the database has schema version {version}, but this service only knows {}",
+            MIGRATIONS.len()
+        );
+    }
+    for migration in &MIGRATIONS[version..] {
+        tx.batch_execute(migration).await?;
+    }
+    tx.execute("UPDATE monerochan_schema SET version = $1", &[&(MIGRATIONS.len() as i64)]).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// The version of a database created before the schema was versioned, from its tables and
+/// columns, or 0 for an empty database.
+async fn unversioned_version(tx: &Transaction<'_>) -> Result<usize> {
+    let columns: Vec<String> = tx
+        .query(
+            "SELECT column_name::TEXT FROM information_schema.columns \
+             WHERE table_name = 'monerochan_jobs' AND table_schema = current_schema()",
+            &[],
+        )
+        .await?
+        .iter()
+        .map(|row| row.get(0))
+        .collect();
+    let has_column = |name: &str| columns.iter().any(|column| column == name);
+    let has_pins: bool = tx
+        .query_one("SELECT to_regclass('monerochan_pins') IS NOT NULL", &[])
+        .await?
+        .get(0);
+
+    Ok(if columns.is_empty() {
+        0
+    } else if has_column("labels") {
+        4
+    } else if has_pins {
+        3
+    } else if has_column("cycles") {
+        2
+    } else {
+        1
+    })
+}
+
+fn job_from_row(row: &Row) -> Job {
+    Job {
+        request_id: row.get(0),
+        program_id: row.get(1),
+        proof_mode: row.get(2),
+        status: row.get(3),
+        error_message: row.get(4),
+        created_at: row.get(5),
+        skip_simulation: row.get(6),
+        cycle_limit: row.get::<_, Option<i64>>(7).map(|limit| limit as u64),
+        client_address: row.get(8),
+        idempotency_key: row.get(9),
+        usage: JobUsage {
+            cycles: row.get::<_, i64>(10) as u64,
+            proving_time_ms: row.get::<_, i64>(11) as u64,
+        },
+        labels: serde_json::from_str(row.get::<_, &str>(12)).unwrap_or_default(),
+    }
+}
+
+#[async_trait]
+impl JobStore for PostgresJobStore {
+    async fn insert(&self, job: Job, input: JobInput) -> Result<Job> {
+        let inserted = self
+            .client
+            .execute(
+                &format!(
+                    "INSERT INTO monerochan_jobs ({JOB_COLUMNS}, elf, stdin) \
+                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15) \
+                     ON CONFLICT ((COALESCE(client_address, '')), idempotency_key) DO NOTHING"
+                ),
+                &[
+                    &job.request_id,
+                    &job.program_id,
+                    &job.proof_mode,
+                    &job.status,
+                    &job.error_message,
+                    &job.created_at,
+                    &job.skip_simulation,
+                    &job.cycle_limit.map(|limit| limit as i64),
+                    &job.client_address,
+                    &job.idempotency_key,
+                    &(job.usage.cycles as i64),
+                    &(job.usage.proving_time_ms as i64),
+                    &serde_json::to_string(&job.labels)?,
+                    &input.elf,
+                    &input.stdin,
+                ],
+            )
+            .await?;
+        if inserted > 0 {
+            return Ok(job);
+        }
+
+        let row = self
+            .client
+            .query_one(
+                &format!(
+                    "SELECT {JOB_COLUMNS} FROM monerochan_jobs \
+                     WHERE idempotency_key = $1 AND client_address IS NOT DISTINCT FROM $2"
+                ),
+                &[&job.idempotency_key, &job.client_address],
+            )
+            .await?;
+        Ok(job_from_row(&row))
+    }
+
+    async fn get(&self, request_id: &str) -> Result<Option<Job>> {
+        let row = self
+            .client
+            .query_opt(
+                &format!("SELECT {JOB_COLUMNS} FROM monerochan_jobs WHERE request_id = $1"),
+                &[&request_id],
+            )
+            .await?;
+        Ok(row.as_ref().map(job_from_row))
+    }
+
+    async fn list(&self, client_address: Option<&str>) -> Result<Vec<Job>> {
+        let rows = self
+            .client
+            .query(
+                &format!(
+                    "SELECT {JOB_COLUMNS} FROM monerochan_jobs \
+                     WHERE $1::TEXT IS NULL OR client_address = $1 ORDER BY created_at"
+                ),
+                &[&client_address],
+            )
+            .await?;
+        Ok(rows.iter().map(job_from_row).collect())
+    }
+
+    async fn list_page(
+        &self,
+        filter: &JobFilter,
+        after: Option<(i64, &str)>,
+        limit: usize,
+    ) -> Result<Vec<Job>> {
+        // Each condition binds its value to the next numbered parameter.
+        let mut sql = format!("SELECT {JOB_COLUMNS} FROM monerochan_jobs WHERE TRUE");
+        let mut values: Vec<Box<dyn ToSql + Sync + Send>> = Vec::new();
+        let mut param = |value: Box<dyn ToSql + Sync + Send>| {
+            values.push(value);
+            format!("${}", values.len())
+        };
+        if let Some(client_address) = &filter.client_address {
+            sql += &format!(" AND client_address = {}", param(Box::new(client_address.clone())));
+        }
+        if !filter.statuses.is_empty() {
+            sql += &format!(" AND status = ANY({})", param(Box::new(filter.statuses.clone())));
+        }
+        if let Some(program_id) = &filter.program_id {
+            sql += &format!(" AND program_id = {}", param(Box::new(program_id.clone())));
+        }
+        if let Some(created_after) = filter.created_after {
+            sql += &format!(" AND created_at >= {}", param(Box::new(created_after)));
+        }
+        if let Some(created_before) = filter.created_before {
+            sql += &format!(" AND created_at < {}", param(Box::new(created_before)));
+        }
+        if !filter.labels.is_empty() {
+            let labels = serde_json::to_string(&filter.labels)?;
+            sql += &format!(" AND labels::JSONB @> {}::TEXT::JSONB", param(Box::new(labels)));
+        }
+        if let Some((created_at, request_id)) = after {
+            sql += &format!(
+                " AND (created_at, request_id) > ({}, {})",
+                param(Box::new(created_at)),
+                param(Box::new(request_id.to_string()))
+            );
+        }
+        let limit = param(Box::new(limit as i64));
+        sql += &format!(" ORDER BY created_at, request_id LIMIT {limit}");
+
+        let params =
+            values.iter().map(|value| value.as_ref() as &(dyn ToSql + Sync)).collect::<Vec<_>>();
+        let rows = self.client.query(&sql, &params).await?;
+        Ok(rows.iter().map(job_from_row).collect())
+    }
+
+    async fn input(&self, request_id: &str) -> Result<JobInput> {
+        let row = self
+            .client
+            .query_one(
+                "SELECT elf, stdin FROM monerochan_jobs WHERE request_id = $1",
+                &[&request_id],
+            )
+            .await?;
+        Ok(JobInput { elf: row.get(0), stdin: row.get(1) })
+    }
+
+    async fn proof(&self, request_id: &str) -> Result<Option<Vec<u8>>> {
+        let row = self
+            .client
+            .query_opt("SELECT proof FROM monerochan_jobs WHERE request_id = $1", &[&request_id])
+            .await?;
+        Ok(row.and_then(|row| row.get(0)))
+    }
+
+    async fn receipt(&self, request_id: &str) -> Result<Option<Vec<u8>>> {
+        let row = self
+            .client
+            .query_opt("SELECT receipt FROM monerochan_jobs WHERE request_id = $1", &[&request_id])
+            .await?;
+        Ok(row.and_then(|row| row.get(0)))
+    }
+
+    async fn claim(&self, lease: Duration) -> Result<Option<Claim>> {
+        let now = unix_timestamp();
+        let token = claim_token();
+        // `SKIP LOCKED` lets services sharing the database claim different jobs concurrently.
+        let row = self
+            .client
+            .query_opt(
+                &format!(
+                    "UPDATE monerochan_jobs SET status = $1, lease_expires_at = $2, \
+                     lease_token = $5 \
+                     WHERE request_id = ( \
+                         SELECT request_id FROM monerochan_jobs \
+                         WHERE status = $3 OR (status = $1 AND lease_expires_at < $4) \
+                         ORDER BY created_at LIMIT 1 FOR UPDATE SKIP LOCKED \
+                     ) \
+                     RETURNING {JOB_COLUMNS}"
+                ),
+                &[
+                    &(JobStatus::Running as i32),
+                    &(now + lease.as_secs() as i64),
+                    &(JobStatus::Pending as i32),
+                    &now,
+                    &token,
+                ],
+            )
+            .await?;
+        Ok(row.map(|row| Claim { job: job_from_row(&row), token }))
+    }
+
+    async fn renew(&self, request_id: &str, token: &str, lease: Duration) -> Result<bool> {
+        let updated = self
+            .client
+            .execute(
+                "UPDATE monerochan_jobs SET lease_expires_at = $1 \
+                 WHERE request_id = $2 AND status = $3 AND lease_token = $4",
+                &[
+                    &(unix_timestamp() + lease.as_secs() as i64),
+                    &request_id,
+                    &(JobStatus::Running as i32),
+                    &token,
+                ],
+            )
+            .await?;
+        Ok(updated > 0)
+    }
+
+    async fn complete(
+        &self,
+        request_id: &str,
+        token: &str,
+        outcome: Result<Vec<u8>, String>,
+        usage: JobUsage,
+        receipt: Option<Vec<u8>>,
+    ) -> Result<bool> {
+        let (status, proof, receipt, error_message) = match outcome {
+            Ok(proof) => (JobStatus::Succeeded, Some(proof), receipt, String::new()),
+            Err(error) => (JobStatus::Failed, None, None, error),
+        };
+        let updated = self
+            .client
+            .execute(
+                "UPDATE monerochan_jobs SET status = $1, proof = $2, error_message = $3, \
+                 cycles = $4, proving_time_ms = $5, receipt = $6 \
+                 WHERE request_id = $7 AND status = $8 AND lease_token = $9",
+                &[
+                    &(status as i32),
+                    &proof,
+                    &error_message,
+                    &(usage.cycles as i64),
+                    &(usage.proving_time_ms as i64),
+                    &receipt,
+                    &request_id,
+                    &(JobStatus::Running as i32),
+                    &token,
+                ],
+            )
+            .await?;
+        Ok(updated > 0)
+    }
+
+    async fn cancel(&self, request_id: &str) -> Result<bool> {
+        let updated = self
+            .client
+            .execute(
+                "UPDATE monerochan_jobs SET status = $1, error_message = $2 \
+                 WHERE request_id = $3 AND status IN ($4, $5)",
+                &[
+                    &(JobStatus::Failed as i32),
+                    &CANCELLED_MESSAGE,
+                    &request_id,
+                    &(JobStatus::Pending as i32),
+                    &(JobStatus::Running as i32),
+                ],
+            )
+            .await?;
+        Ok(updated > 0)
+    }
+
+    async fn pin(&self, client_address: &str, program_id: &str, pinned: bool) -> Result<()> {
+        let sql = if pinned {
+            "INSERT INTO monerochan_pins (client_address, program_id) VALUES ($1, $2) \
+             ON CONFLICT DO NOTHING"
+        } else {
+            "DELETE FROM monerochan_pins WHERE client_address = $1 AND program_id = $2"
+        };
+        self.client.execute(sql, &[&client_address, &program_id]).await?;
+        Ok(())
+    }
+
+    async fn pinned(&self, client_address: &str) -> Result<Vec<String>> {
+        let rows = self
+            .client
+            .query(
+                "SELECT program_id FROM monerochan_pins WHERE client_address = $1 \
+                 ORDER BY program_id",
+                &[&client_address],
+            )
+            .await?;
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+}
@@ -0,0 +1,224 @@
+//! # Quotas
+//!
+//! The accounting and quotas of the clients of a [`ProvingService`](super::ProvingService).
+//!
+//! The usage of a client is derived from its jobs in the [`JobStore`](super::JobStore): the
+//! proofs it got, the cycles its programs executed for and the wall-clock time its jobs took,
+//! counting the jobs submitted during the last quota period. The requests without a client
+//! address are accounted together, as a single anonymous client.
+//!
+//! Quotas bound that usage, and are checked when a request is submitted: a request over quota is
+//! rejected with `RESOURCE_EXHAUSTED`, and the cycle limit of an accepted request is capped by the
+//! cycles left to its client. The checks are not atomic, so concurrent requests of a client may
+//! overshoot its quota by a few jobs.
+//!
+//! Quotas are read from a JSON file, where the `default` quota applies to the clients that are
+//! not listed and every limit is optional:
+//!
+//! ```json
+//! {
+//!     "period_secs": 86400,
+//!     "default": { "max_proofs": 10, "max_pending": 2 },
+//!     "clients": {
+//!         "7EcDhSYGxXyscszYEp35KHN8vvw3svAuLKTzXwCFLtV": { "max_cycles": 1000000000000 }
+//!     }
+//! }
+//! ```
+
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::{unix_timestamp, Job};
+use crate::network::proto::api::JobStatus;
+
+/// The limits on the usage of a client over the quota period.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Quota {
+    /// The maximum number of proofs, counting the pending jobs.
+    pub max_proofs: Option<u64>,
+    /// The maximum number of cycles executed.
+    pub max_cycles: Option<u64>,
+    /// The maximum wall-clock time spent proving, in seconds.
+    pub max_proving_time_secs: Option<u64>,
+    /// The maximum number of pending and running jobs at once.
+    pub max_pending: Option<u64>,
+}
+
+impl Quota {
+    /// Whether the quota has no limit.
+    #[must_use]
+    pub fn is_unlimited(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Checks that a client with the given usage may submit another request, or returns why not.
+    pub fn check(&self, usage: &Usage) -> Result<(), String> {
+        if let Some(max) = self.max_pending.filter(|max| usage.pending >= *max) {
+            return Err(format!("quota exceeded: at most {max} pending requests"));
+        }
+        if let Some(max) = self.max_proofs.filter(|max| usage.proofs + usage.pending >= *max) {
+            return Err(format!("quota exceeded: at most {max} proofs per period"));
+        }
+        if let Some(max) = self.max_cycles.filter(|max| usage.cycles >= *max) {
+            return Err(format!("quota exceeded: at most {max} cycles per period"));
+        }
+        if let Some(max) =
+            self.max_proving_time_secs.filter(|max| usage.proving_time_ms >= max * 1000)
+        {
+            return Err(format!("quota exceeded: at most {max} seconds of proving per period"));
+        }
+        Ok(())
+    }
+
+    /// Returns the cycles left to a client with the given usage, if its cycles are limited.
+    #[must_use]
+    pub fn remaining_cycles(&self, usage: &Usage) -> Option<u64> {
+        self.max_cycles.map(|max| max.saturating_sub(usage.cycles))
+    }
+}
+
+/// The quotas of the clients of a service.
+///
+/// See the [module documentation](self) for the file format.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Quotas {
+    /// The period over which the usage is counted, in seconds. One day by default.
+    pub period_secs: u64,
+    /// The quota of the clients without a quota of their own, including the anonymous client.
+    pub default: Quota,
+    /// The quotas of specific clients, by client address.
+    pub clients: HashMap<String, Quota>,
+}
+
+impl Default for Quotas {
+    fn default() -> Self {
+        Self { period_secs: 24 * 60 * 60, default: Quota::default(), clients: HashMap::new() }
+    }
+}
+
+impl Quotas {
+    /// Reads the quotas from a JSON file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("failed to read quotas {}", path.display()))?;
+        serde_json::from_str(&json).with_context(|| format!("invalid quotas {}", path.display()))
+    }
+
+    /// Returns the quota of a client.
+    #[must_use]
+    pub fn quota(&self, client_address: Option<&str>) -> Quota {
+        client_address
+            .and_then(|address| self.clients.get(address))
+            .copied()
+            .unwrap_or(self.default)
+    }
+
+    /// Returns the start of the current quota period, in unix seconds.
+    #[must_use]
+    pub fn period_start(&self) -> i64 {
+        unix_timestamp() - self.period_secs as i64
+    }
+}
+
+/// The usage of a client.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Usage {
+    /// The jobs that succeeded.
+    pub proofs: u64,
+    /// The jobs that failed or were cancelled.
+    pub failed: u64,
+    /// The jobs that are pending or running.
+    pub pending: u64,
+    /// The cycles executed by the finished jobs.
+    pub cycles: u64,
+    /// The wall-clock time spent on the finished jobs, in milliseconds.
+    pub proving_time_ms: u64,
+}
+
+impl Usage {
+    /// Adds up the usage of jobs, counting the finished jobs submitted at or after `since` and
+    /// every unfinished job.
+    pub fn of<'a>(jobs: impl IntoIterator<Item = &'a Job>, since: i64) -> Self {
+        let mut usage = Self::default();
+        for job in jobs {
+            if job.status == JobStatus::Pending as i32 || job.status == JobStatus::Running as i32 {
+                usage.pending += 1;
+                continue;
+            }
+            if job.created_at < since {
+                continue;
+            }
+            if job.status == JobStatus::Succeeded as i32 {
+                usage.proofs += 1;
+            } else {
+                usage.failed += 1;
+            }
+            usage.cycles += job.usage.cycles;
+            usage.proving_time_ms += job.usage.proving_time_ms;
+        }
+        usage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::server::{store::tests::job, JobUsage};
+
+    fn finished(request_id: &str, created_at: i64, status: JobStatus, cycles: u64) -> Job {
+        Job {
+            status: status as i32,
+            usage: JobUsage { cycles, proving_time_ms: cycles / 10 },
+            ..job(request_id, created_at, None)
+        }
+    }
+
+    #[test]
+    fn test_usage() {
+        let jobs = [
+            finished("0x01", 10, JobStatus::Succeeded, 1000),
+            finished("0x02", 20, JobStatus::Succeeded, 2000),
+            finished("0x03", 20, JobStatus::Failed, 500),
+            job("0x04", 5, None),
+        ];
+
+        let usage = Usage::of(&jobs, 15);
+        assert_eq!(
+            usage,
+            Usage { proofs: 1, failed: 1, pending: 1, cycles: 2500, proving_time_ms: 250 }
+        );
+
+        let quota = Quota { max_cycles: Some(3000), ..Quota::default() };
+        assert!(quota.check(&usage).is_ok());
+        assert_eq!(quota.remaining_cycles(&usage), Some(500));
+        assert!(quota.check(&Usage::of(&jobs, 0)).is_err());
+
+        assert!(Quota { max_proofs: Some(3), ..Quota::default() }.check(&usage).is_ok());
+        assert!(Quota { max_proofs: Some(2), ..Quota::default() }.check(&usage).is_err());
+        assert!(Quota { max_pending: Some(1), ..Quota::default() }.check(&usage).is_err());
+        assert!(Quota { max_proving_time_secs: Some(1), ..Quota::default() }.check(&usage).is_ok());
+    }
+
+    #[test]
+    fn test_quotas() {
+        let quotas: Quotas = serde_json::from_str(
+            r#"{
+                "default": { "max_proofs": 10 },
+                "clients": { "alice": { "max_pending": 2 } }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(quotas.period_secs, 24 * 60 * 60);
+        assert_eq!(quotas.quota(None).max_proofs, Some(10));
+        assert_eq!(quotas.quota(Some("bob")).max_proofs, Some(10));
+        assert_eq!(quotas.quota(Some("alice")), Quota { max_pending: Some(2), ..Quota::default() });
+        assert!(Quotas::default().quota(None).is_unlimited());
+
+        assert!(serde_json::from_str::<Quotas>(r#"{ "default": { "max_proof": 1 } }"#).is_err());
+    }
+}
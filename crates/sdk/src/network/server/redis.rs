@@ -0,0 +1,320 @@
+//! A [`JobStore`] backed by Redis.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use redis::{aio::MultiplexedConnection, AsyncCommands, Script};
+
+use super::{
+    store::{claim_token, Claim, Job, JobInput, JobStore, JobUsage, CANCELLED_MESSAGE},
+    unix_timestamp,
+};
+use crate::network::proto::api::JobStatus;
+
+/// The prefix of the keys of the store.
+const PREFIX: &str = "monerochan";
+
+// Each job is a hash at `<prefix>:job:<request_id>` with the fields `meta` (the JSON of the
+// `Job`), `status`, `error`, `cycles`, `proving_time_ms`, `input`, `proof`, `receipt` and
+// `lease_token`. The ids
+// of the jobs are indexed in the sorted sets `<prefix>:jobs` and `<prefix>:queue` by creation
+// time, for listing and claiming, and the running ones in `<prefix>:leases` by lease expiry. The
+// programs pinned by a client are the set `<prefix>:pins:<client_address>`.
+
+/// Stores a job unless its idempotency key is taken, and returns the id of the stored job.
+///
+/// ARGV: prefix, request id, meta, status, input, created at, idempotency key or '', client
+/// address or ''.
+const INSERT: &str = r"
+local p = ARGV[1]
+if ARGV[7] ~= '' then
+  local key = p .. ':idempotency:' .. ARGV[8] .. ':' .. ARGV[7]
+  local existing = redis.call('GET', key)
+  if existing then
+    return existing
+  end
+  redis.call('SET', key, ARGV[2])
+end
+redis.call('HSET', p .. ':job:' .. ARGV[2], 'meta', ARGV[3], 'status', ARGV[4], 'error', '', 'input', ARGV[5])
+redis.call('ZADD', p .. ':jobs', ARGV[6], ARGV[2])
+redis.call('ZADD', p .. ':queue', ARGV[6], ARGV[2])
+return ARGV[2]
+";
+
+/// Claims the oldest pending job, or else a job whose lease expired, and returns its id.
+///
+/// ARGV: prefix, now, lease expiry, running status, lease token.
+const CLAIM: &str = r"
+local p = ARGV[1]
+local id = redis.call('ZRANGE', p .. ':queue', 0, 0)[1]
+if id then
+  redis.call('ZREM', p .. ':queue', id)
+else
+  id = redis.call('ZRANGEBYSCORE', p .. ':leases', '-inf', '(' .. ARGV[2], 'LIMIT', 0, 1)[1]
+  if not id then
+    return false
+  end
+end
+redis.call('ZADD', p .. ':leases', ARGV[3], id)
+redis.call('HSET', p .. ':job:' .. id, 'status', ARGV[4], 'lease_token', ARGV[5])
+return id
+";
+
+/// Extends the lease of a running job held under a token.
+///
+/// ARGV: prefix, request id, lease expiry, running status, lease token.
+const RENEW: &str = r"
+local p = ARGV[1]
+local job = redis.call('HMGET', p .. ':job:' .. ARGV[2], 'status', 'lease_token')
+if job[1] ~= ARGV[4] or job[2] ~= ARGV[5] then
+  return 0
+end
+redis.call('ZADD', p .. ':leases', ARGV[3], ARGV[2])
+return 1
+";
+
+/// Records the outcome of a running job held under a token.
+///
+/// ARGV: prefix, request id, running status, outcome status, error, proof or '', cycles, proving
+/// time, receipt or '', lease token.
+const COMPLETE: &str = r"
+local p = ARGV[1]
+local key = p .. ':job:' .. ARGV[2]
+local job = redis.call('HMGET', key, 'status', 'lease_token')
+if job[1] ~= ARGV[3] or job[2] ~= ARGV[10] then
+  return 0
+end
+redis.call('HSET', key, 'status', ARGV[4], 'error', ARGV[5], 'proof', ARGV[6],
+  'cycles', ARGV[7], 'proving_time_ms', ARGV[8], 'receipt', ARGV[9])
+redis.call('ZREM', p .. ':leases', ARGV[2])
+return 1
+";
+
+/// Cancels a pending or running job.
+///
+/// ARGV: prefix, request id, pending status, running status, failed status, error.
+const CANCEL: &str = r"
+local p = ARGV[1]
+local key = p .. ':job:' .. ARGV[2]
+local status = redis.call('HGET', key, 'status')
+if status ~= ARGV[3] and status ~= ARGV[4] then
+  return 0
+end
+redis.call('HSET', key, 'status', ARGV[5], 'error', ARGV[6])
+redis.call('ZREM', p .. ':queue', ARGV[2])
+redis.call('ZREM', p .. ':leases', ARGV[2])
+return 1
+";
+
+/// A [`JobStore`] backed by Redis.
+///
+/// The instance may be shared by several services, each claiming jobs from the same queue. The
+/// updates run as Lua scripts over keys built at runtime, so Redis Cluster is not supported, and
+/// the instance must persist its data (with AOF or RDB snapshots) for the jobs to survive a
+/// restart of Redis.
+pub struct RedisJobStore {
+    conn: MultiplexedConnection,
+}
+
+impl RedisJobStore {
+    /// Connects to the Redis instance at `url`.
+    pub async fn connect(url: &str) -> Result<Self> {
+        let client = redis::Client::open(url).context("invalid redis url")?;
+        let conn = client
+            .get_multiplexed_async_connection()
+            .await
+            .context("failed to connect to redis")?;
+        Ok(Self { conn })
+    }
+
+    fn key(request_id: &str) -> String {
+        format!("{PREFIX}:job:{request_id}")
+    }
+}
+
+fn lease_expiry(lease: Duration) -> i64 {
+    unix_timestamp() + lease.as_secs() as i64
+}
+
+#[async_trait]
+impl JobStore for RedisJobStore {
+    async fn insert(&self, job: Job, input: JobInput) -> Result<Job> {
+        let mut conn = self.conn.clone();
+        let request_id: String = Script::new(INSERT)
+            .arg(PREFIX)
+            .arg(&job.request_id)
+            .arg(serde_json::to_string(&job)?)
+            .arg(job.status)
+            .arg(bincode::serialize(&input)?)
+            .arg(job.created_at)
+            .arg(job.idempotency_key.as_deref().unwrap_or_default())
+            .arg(job.client_address.as_deref().unwrap_or_default())
+            .invoke_async(&mut conn)
+            .await?;
+
+        if request_id == job.request_id {
+            Ok(job)
+        } else {
+            self.get(&request_id).await?.ok_or_else(|| anyhow!("unknown request {request_id}"))
+        }
+    }
+
+    async fn get(&self, request_id: &str) -> Result<Option<Job>> {
+        let mut conn = self.conn.clone();
+        let (meta, status, error, cycles, proving_time_ms): (
+            Option<String>,
+            Option<i32>,
+            Option<String>,
+            Option<u64>,
+            Option<u64>,
+        ) = redis::cmd("HMGET")
+            .arg(Self::key(request_id))
+            .arg("meta")
+            .arg("status")
+            .arg("error")
+            .arg("cycles")
+            .arg("proving_time_ms")
+            .query_async(&mut conn)
+            .await?;
+        let Some(meta) = meta else {
+            return Ok(None);
+        };
+
+        let mut job: Job = serde_json::from_str(&meta)?;
+        job.status = status.unwrap_or(job.status);
+        job.error_message = error.unwrap_or_default();
+        job.usage = JobUsage {
+            cycles: cycles.unwrap_or_default(),
+            proving_time_ms: proving_time_ms.unwrap_or_default(),
+        };
+        Ok(Some(job))
+    }
+
+    async fn list(&self, client_address: Option<&str>) -> Result<Vec<Job>> {
+        let mut conn = self.conn.clone();
+        let request_ids: Vec<String> = conn.zrange(format!("{PREFIX}:jobs"), 0, -1).await?;
+
+        let mut jobs = Vec::with_capacity(request_ids.len());
+        for request_id in request_ids {
+            if let Some(job) = self.get(&request_id).await? {
+                if client_address.is_none() || job.client_address.as_deref() == client_address {
+                    jobs.push(job);
+                }
+            }
+        }
+        Ok(jobs)
+    }
+
+    async fn input(&self, request_id: &str) -> Result<JobInput> {
+        let mut conn = self.conn.clone();
+        let input: Option<Vec<u8>> = conn.hget(Self::key(request_id), "input").await?;
+        let input = input.ok_or_else(|| anyhow!("unknown request {request_id}"))?;
+        Ok(bincode::deserialize(&input)?)
+    }
+
+    async fn proof(&self, request_id: &str) -> Result<Option<Vec<u8>>> {
+        let mut conn = self.conn.clone();
+        let proof: Option<Vec<u8>> = conn.hget(Self::key(request_id), "proof").await?;
+        Ok(proof.filter(|proof| !proof.is_empty()))
+    }
+
+    async fn receipt(&self, request_id: &str) -> Result<Option<Vec<u8>>> {
+        let mut conn = self.conn.clone();
+        let receipt: Option<Vec<u8>> = conn.hget(Self::key(request_id), "receipt").await?;
+        Ok(receipt.filter(|receipt| !receipt.is_empty()))
+    }
+
+    async fn claim(&self, lease: Duration) -> Result<Option<Claim>> {
+        let mut conn = self.conn.clone();
+        let token = claim_token();
+        let request_id: Option<String> = Script::new(CLAIM)
+            .arg(PREFIX)
+            .arg(unix_timestamp())
+            .arg(lease_expiry(lease))
+            .arg(JobStatus::Running as i32)
+            .arg(&token)
+            .invoke_async(&mut conn)
+            .await?;
+        let Some(request_id) = request_id else {
+            return Ok(None);
+        };
+        let job = self.get(&request_id).await?;
+        Ok(job.map(|job| Claim { job, token }))
+    }
+
+    async fn renew(&self, request_id: &str, token: &str, lease: Duration) -> Result<bool> {
+        let mut conn = self.conn.clone();
+        let renewed: bool = Script::new(RENEW)
+            .arg(PREFIX)
+            .arg(request_id)
+            .arg(lease_expiry(lease))
+            .arg(JobStatus::Running as i32)
+            .arg(token)
+            .invoke_async(&mut conn)
+            .await?;
+        Ok(renewed)
+    }
+
+    async fn complete(
+        &self,
+        request_id: &str,
+        token: &str,
+        outcome: Result<Vec<u8>, String>,
+        usage: JobUsage,
+        receipt: Option<Vec<u8>>,
+    ) -> Result<bool> {
+        let (status, proof, receipt, error) = match outcome {
+            Ok(proof) => (JobStatus::Succeeded, proof, receipt.unwrap_or_default(), String::new()),
+            Err(error) => (JobStatus::Failed, Vec::new(), Vec::new(), error),
+        };
+        let mut conn = self.conn.clone();
+        let completed: bool = Script::new(COMPLETE)
+            .arg(PREFIX)
+            .arg(request_id)
+            .arg(JobStatus::Running as i32)
+            .arg(status as i32)
+            .arg(error)
+            .arg(proof)
+            .arg(usage.cycles)
+            .arg(usage.proving_time_ms)
+            .arg(receipt)
+            .arg(token)
+            .invoke_async(&mut conn)
+            .await?;
+        Ok(completed)
+    }
+
+    async fn cancel(&self, request_id: &str) -> Result<bool> {
+        let mut conn = self.conn.clone();
+        let cancelled: bool = Script::new(CANCEL)
+            .arg(PREFIX)
+            .arg(request_id)
+            .arg(JobStatus::Pending as i32)
+            .arg(JobStatus::Running as i32)
+            .arg(JobStatus::Failed as i32)
+            .arg(CANCELLED_MESSAGE)
+            .invoke_async(&mut conn)
+            .await?;
+        Ok(cancelled)
+    }
+
+    async fn pin(&self, client_address: &str, program_id: &str, pinned: bool) -> Result<()> {
+        let mut conn = self.conn.clone();
+        let key = format!("{PREFIX}:pins:{client_address}");
+        if pinned {
+            conn.sadd::<_, _, ()>(key, program_id).await?;
+        } else {
+            conn.srem::<_, _, ()>(key, program_id).await?;
+        }
+        Ok(())
+    }
+
+    async fn pinned(&self, client_address: &str) -> Result<Vec<String>> {
+        let mut conn = self.conn.clone();
+        let mut program_ids: Vec<String> =
+            conn.smembers(format!("{PREFIX}:pins:{client_address}")).await?;
+        program_ids.sort();
+        Ok(program_ids)
+    }
+}
@@ -0,0 +1,559 @@
+//! A [`JobStore`] backed by a SQLite database.
+
+use std::{
+    path::Path,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use rusqlite::{
+    params, params_from_iter, types::Value, Connection, OptionalExtension, Row,
+    TransactionBehavior,
+};
+
+use super::{
+    store::{claim_token, Claim, Job, JobFilter, JobInput, JobStore, JobUsage, CANCELLED_MESSAGE},
+    unix_timestamp,
+};
+use crate::network::proto::api::JobStatus;
+
+/// The migrations of the schema, in order. The version of a database is the number of migrations
+/// applied to it, recorded in its `user_version`. Applied migrations must never change.
+const MIGRATIONS: &[&str] = &[
+    // 1: the jobs.
+    "CREATE TABLE IF NOT EXISTS jobs (
+        request_id TEXT PRIMARY KEY,
+        program_id TEXT NOT NULL,
+        proof_mode INTEGER NOT NULL,
+        status INTEGER NOT NULL,
+        error_message TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        skip_simulation INTEGER NOT NULL,
+        cycle_limit INTEGER,
+        client_address TEXT,
+        idempotency_key TEXT UNIQUE,
+        elf BLOB NOT NULL,
+        stdin BLOB NOT NULL,
+        proof BLOB,
+        lease_expires_at INTEGER NOT NULL DEFAULT 0
+    );
+    CREATE INDEX IF NOT EXISTS jobs_status ON jobs (status, created_at);",
+    // 2: the usage of the jobs.
+    "ALTER TABLE jobs ADD COLUMN cycles INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE jobs ADD COLUMN proving_time_ms INTEGER NOT NULL DEFAULT 0;",
+    // 3: the pinned programs.
+    "CREATE TABLE pins (
+        client_address TEXT NOT NULL,
+        program_id TEXT NOT NULL,
+        PRIMARY KEY (client_address, program_id)
+    );",
+    // 4: the labels of the jobs.
+    "ALTER TABLE jobs ADD COLUMN labels TEXT NOT NULL DEFAULT '{}';",
+    // 5: idempotency keys scoped by client. SQLite cannot drop the unique constraint of a column,
+    // so the table is rebuilt.
+    "CREATE TABLE jobs_v5 (
+        request_id TEXT PRIMARY KEY,
+        program_id TEXT NOT NULL,
+        proof_mode INTEGER NOT NULL,
+        status INTEGER NOT NULL,
+        error_message TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        skip_simulation INTEGER NOT NULL,
+        cycle_limit INTEGER,
+        client_address TEXT,
+        idempotency_key TEXT,
+        elf BLOB NOT NULL,
+        stdin BLOB NOT NULL,
+        proof BLOB,
+        cycles INTEGER NOT NULL DEFAULT 0,
+        proving_time_ms INTEGER NOT NULL DEFAULT 0,
+        labels TEXT NOT NULL DEFAULT '{}',
+        lease_expires_at INTEGER NOT NULL DEFAULT 0
+    );
+    INSERT INTO jobs_v5 (request_id, program_id, proof_mode, status, error_message, created_at,
+        skip_simulation, cycle_limit, client_address, idempotency_key, elf, stdin, proof, cycles,
+        proving_time_ms, labels, lease_expires_at)
+    SELECT request_id, program_id, proof_mode, status, error_message, created_at,
+        skip_simulation, cycle_limit, client_address, idempotency_key, elf, stdin, proof, cycles,
+        proving_time_ms, labels, lease_expires_at
+    FROM jobs;
+    DROP TABLE jobs;
+    ALTER TABLE jobs_v5 RENAME TO jobs;
+    CREATE INDEX jobs_status ON jobs (status, created_at);
+    CREATE UNIQUE INDEX jobs_idempotency ON jobs (IFNULL(client_address, ''), idempotency_key);",
+    // 6: the signed receipts of the jobs.
+    "ALTER TABLE jobs ADD COLUMN receipt BLOB;",
+    // 7: the indexes of the pages of the listings.
+    "CREATE INDEX jobs_created ON jobs (created_at, request_id);
+    CREATE INDEX jobs_client ON jobs (client_address, created_at, request_id);",
+    // 8: the tokens fencing the leases.
+    "ALTER TABLE jobs ADD COLUMN lease_token TEXT;",
+];
+
+const JOB_COLUMNS: &str = "request_id, program_id, proof_mode, status, error_message, \
+                           created_at, skip_simulation, cycle_limit, client_address, \
+                           idempotency_key, cycles, proving_time_ms, labels";
+
+/// A [`JobStore`] backed by a SQLite database.
+///
+/// The database may be shared by several services on the same host. The queries block, so they
+/// run on the blocking thread pool of tokio rather than on the async workers.
+pub struct SqliteJobStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteJobStore {
+    /// Opens the database at `path`, creating it if needed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let mut conn = Connection::open(path)?;
+        // Wait for the lock of another service sharing the database rather than failing.
+        conn.busy_timeout(Duration::from_secs(30))?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        migrate(&mut conn)?;
+        Ok(Self { conn: Arc::new(Mutex::new(conn)) })
+    }
+
+    /// Runs `f` with the connection on the blocking thread pool.
+    async fn with_conn<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut Connection) -> Result<T> + Send + 'static,
+    ) -> Result<T> {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = conn.lock().unwrap_or_else(PoisonError::into_inner);
+            f(&mut conn)
+        })
+        .await?
+    }
+}
+
+/// Applies the migrations the database lacks, at once.
+fn migrate(conn: &mut Connection) -> Result<()> {
+    // The immediate transaction keeps the services sharing the database from migrating it twice.
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let mut version: usize = tx.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if version == 0 {
+        version = unversioned_version(&tx)?;
+    }
+    if version > MIGRATIONS.len() {
+        bail!(
+            "the database has schema version {version}, but this service only knows {}",
+            MIGRATIONS.len()
+        );
+    }
+    for migration in &MIGRATIONS[version..] {
+        tx.execute_batch(migration)?;
+    }
+    tx.pragma_update(None, "user_version", MIGRATIONS.len())?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// The version of a database created before the schema was versioned, from its tables and
+/// columns, or 0 for an empty database.
+fn unversioned_version(conn: &Connection) -> Result<usize> {
+    let mut statement = conn.prepare("SELECT name FROM pragma_table_info('jobs')")?;
+    let columns = statement
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let has_column = |name: &str| columns.iter().any(|column| column == name);
+    let has_pins: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'pins')",
+        [],
+        |row| row.get(0),
+    )?;
+
+    Ok(if columns.is_empty() {
+        0
+    } else if has_column("labels") {
+        4
+    } else if has_pins {
+        3
+    } else if has_column("cycles") {
+        2
+    } else {
+        1
+    })
+}
+
+fn job_from_row(row: &Row<'_>) -> rusqlite::Result<Job> {
+    Ok(Job {
+        request_id: row.get(0)?,
+        program_id: row.get(1)?,
+        proof_mode: row.get(2)?,
+        status: row.get(3)?,
+        error_message: row.get(4)?,
+        created_at: row.get(5)?,
+        skip_simulation: row.get(6)?,
+        cycle_limit: row.get::<_, Option<i64>>(7)?.map(|limit| limit as u64),
+        client_address: row.get(8)?,
+        idempotency_key: row.get(9)?,
+        usage: JobUsage {
+            cycles: row.get::<_, i64>(10)? as u64,
+            proving_time_ms: row.get::<_, i64>(11)? as u64,
+        },
+        labels: serde_json::from_str(&row.get::<_, String>(12)?).unwrap_or_default(),
+    })
+}
+
+#[async_trait]
+impl JobStore for SqliteJobStore {
+    async fn insert(&self, job: Job, input: JobInput) -> Result<Job> {
+        self.with_conn(move |conn| {
+            let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+            if let Some(key) = &job.idempotency_key {
+                let existing = tx
+                    .query_row(
+                        &format!(
+                            "SELECT {JOB_COLUMNS} FROM jobs \
+                             WHERE idempotency_key = ?1 AND client_address IS ?2"
+                        ),
+                        params![key, job.client_address],
+                        job_from_row,
+                    )
+                    .optional()?;
+                if let Some(existing) = existing {
+                    return Ok(existing);
+                }
+            }
+
+            tx.execute(
+                &format!(
+                    "INSERT INTO jobs ({JOB_COLUMNS}, elf, stdin) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)"
+                ),
+                params![
+                    job.request_id,
+                    job.program_id,
+                    job.proof_mode,
+                    job.status,
+                    job.error_message,
+                    job.created_at,
+                    job.skip_simulation,
+                    job.cycle_limit.map(|limit| limit as i64),
+                    job.client_address,
+                    job.idempotency_key,
+                    job.usage.cycles as i64,
+                    job.usage.proving_time_ms as i64,
+                    serde_json::to_string(&job.labels)?,
+                    input.elf,
+                    input.stdin,
+                ],
+            )?;
+            tx.commit()?;
+            Ok(job)
+        })
+        .await
+    }
+
+    async fn get(&self, request_id: &str) -> Result<Option<Job>> {
+        let request_id = request_id.to_string();
+        self.with_conn(move |conn| {
+            Ok(conn
+                .query_row(
+                    &format!("SELECT {JOB_COLUMNS} FROM jobs WHERE request_id = ?1"),
+                    [request_id],
+                    job_from_row,
+                )
+                .optional()?)
+        })
+        .await
+    }
+
+    async fn list(&self, client_address: Option<&str>) -> Result<Vec<Job>> {
+        let client_address = client_address.map(str::to_string);
+        self.with_conn(move |conn| {
+            let mut statement = conn.prepare(&format!(
+                "SELECT {JOB_COLUMNS} FROM jobs \
+                 WHERE ?1 IS NULL OR client_address = ?1 ORDER BY created_at"
+            ))?;
+            let jobs = statement.query_map([client_address], job_from_row)?;
+            Ok(jobs.collect::<rusqlite::Result<_>>()?)
+        })
+        .await
+    }
+
+    async fn list_page(
+        &self,
+        filter: &JobFilter,
+        after: Option<(i64, &str)>,
+        limit: usize,
+    ) -> Result<Vec<Job>> {
+        // Each condition binds its values to the next numbered parameters.
+        let mut sql = format!("SELECT {JOB_COLUMNS} FROM jobs WHERE TRUE");
+        let mut values = Vec::new();
+        let mut param = |value: Value| {
+            values.push(value);
+            format!("?{}", values.len())
+        };
+        if let Some(client_address) = &filter.client_address {
+            sql += &format!(" AND client_address = {}", param(Value::Text(client_address.clone())));
+        }
+        if !filter.statuses.is_empty() {
+            let statuses = filter
+                .statuses
+                .iter()
+                .map(|&status| param(Value::Integer(status.into())))
+                .collect::<Vec<_>>();
+            sql += &format!(" AND status IN ({})", statuses.join(", "));
+        }
+        if let Some(program_id) = &filter.program_id {
+            sql += &format!(" AND program_id = {}", param(Value::Text(program_id.clone())));
+        }
+        if let Some(created_after) = filter.created_after {
+            sql += &format!(" AND created_at >= {}", param(Value::Integer(created_after)));
+        }
+        if let Some(created_before) = filter.created_before {
+            sql += &format!(" AND created_at < {}", param(Value::Integer(created_before)));
+        }
+        for (key, value) in &filter.labels {
+            sql += &format!(
+                " AND EXISTS (SELECT 1 FROM json_each(jobs.labels) WHERE key = {} AND value = {})",
+                param(Value::Text(key.clone())),
+                param(Value::Text(value.clone()))
+            );
+        }
+        if let Some((created_at, request_id)) = after {
+            sql += &format!(
+                " AND (created_at, request_id) > ({}, {})",
+                param(Value::Integer(created_at)),
+                param(Value::Text(request_id.to_string()))
+            );
+        }
+        let limit = param(Value::Integer(limit as i64));
+        sql += &format!(" ORDER BY created_at, request_id LIMIT {limit}");
+
+        self.with_conn(move |conn| {
+            let mut statement = conn.prepare(&sql)?;
+            let jobs = statement.query_map(params_from_iter(values), job_from_row)?;
+            Ok(jobs.collect::<rusqlite::Result<_>>()?)
+        })
+        .await
+    }
+
+    async fn input(&self, request_id: &str) -> Result<JobInput> {
+        let request_id = request_id.to_string();
+        self.with_conn(move |conn| {
+            Ok(conn.query_row(
+                "SELECT elf, stdin FROM jobs WHERE request_id = ?1",
+                [request_id],
+                |row| Ok(JobInput { elf: row.get(0)?, stdin: row.get(1)? }),
+            )?)
+        })
+        .await
+    }
+
+    async fn proof(&self, request_id: &str) -> Result<Option<Vec<u8>>> {
+        let request_id = request_id.to_string();
+        self.with_conn(move |conn| {
+            let proof = conn
+                .query_row("SELECT proof FROM jobs WHERE request_id = ?1", [request_id], |row| {
+                    row.get(0)
+                })
+                .optional()?;
+            Ok(proof.flatten())
+        })
+        .await
+    }
+
+    async fn receipt(&self, request_id: &str) -> Result<Option<Vec<u8>>> {
+        let request_id = request_id.to_string();
+        self.with_conn(move |conn| {
+            let receipt = conn
+                .query_row("SELECT receipt FROM jobs WHERE request_id = ?1", [request_id], |row| {
+                    row.get(0)
+                })
+                .optional()?;
+            Ok(receipt.flatten())
+        })
+        .await
+    }
+
+    async fn claim(&self, lease: Duration) -> Result<Option<Claim>> {
+        self.with_conn(move |conn| {
+            let now = unix_timestamp();
+            let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+            let job = tx
+                .query_row(
+                    &format!(
+                        "SELECT {JOB_COLUMNS} FROM jobs \
+                         WHERE status = ?1 OR (status = ?2 AND lease_expires_at < ?3) \
+                         ORDER BY created_at LIMIT 1"
+                    ),
+                    params![JobStatus::Pending as i32, JobStatus::Running as i32, now],
+                    job_from_row,
+                )
+                .optional()?;
+            let Some(mut job) = job else {
+                return Ok(None);
+            };
+
+            let token = claim_token();
+            tx.execute(
+                "UPDATE jobs SET status = ?1, lease_expires_at = ?2, lease_token = ?3 \
+                 WHERE request_id = ?4",
+                params![
+                    JobStatus::Running as i32,
+                    now + lease.as_secs() as i64,
+                    token,
+                    job.request_id
+                ],
+            )?;
+            tx.commit()?;
+            job.status = JobStatus::Running as i32;
+            Ok(Some(Claim { job, token }))
+        })
+        .await
+    }
+
+    async fn renew(&self, request_id: &str, token: &str, lease: Duration) -> Result<bool> {
+        let (request_id, token) = (request_id.to_string(), token.to_string());
+        self.with_conn(move |conn| {
+            let updated = conn.execute(
+                "UPDATE jobs SET lease_expires_at = ?1 \
+                 WHERE request_id = ?2 AND status = ?3 AND lease_token = ?4",
+                params![
+                    unix_timestamp() + lease.as_secs() as i64,
+                    request_id,
+                    JobStatus::Running as i32,
+                    token
+                ],
+            )?;
+            Ok(updated > 0)
+        })
+        .await
+    }
+
+    async fn complete(
+        &self,
+        request_id: &str,
+        token: &str,
+        outcome: Result<Vec<u8>, String>,
+        usage: JobUsage,
+        receipt: Option<Vec<u8>>,
+    ) -> Result<bool> {
+        let (request_id, token) = (request_id.to_string(), token.to_string());
+        let (status, proof, receipt, error_message) = match outcome {
+            Ok(proof) => (JobStatus::Succeeded, Some(proof), receipt, String::new()),
+            Err(error) => (JobStatus::Failed, None, None, error),
+        };
+        self.with_conn(move |conn| {
+            let updated = conn.execute(
+                "UPDATE jobs SET status = ?1, proof = ?2, error_message = ?3, cycles = ?4, \
+                 proving_time_ms = ?5, receipt = ?6 \
+                 WHERE request_id = ?7 AND status = ?8 AND lease_token = ?9",
+                params![
+                    status as i32,
+                    proof,
+                    error_message,
+                    usage.cycles as i64,
+                    usage.proving_time_ms as i64,
+                    receipt,
+                    request_id,
+                    JobStatus::Running as i32,
+                    token
+                ],
+            )?;
+            Ok(updated > 0)
+        })
+        .await
+    }
+
+    async fn cancel(&self, request_id: &str) -> Result<bool> {
+        let request_id = request_id.to_string();
+        self.with_conn(move |conn| {
+            let updated = conn.execute(
+                "UPDATE jobs SET status = ?1, error_message = ?2 \
+                 WHERE request_id = ?3 AND status IN (?4, ?5)",
+                params![
+                    JobStatus::Failed as i32,
+                    CANCELLED_MESSAGE,
+                    request_id,
+                    JobStatus::Pending as i32,
+                    JobStatus::Running as i32
+                ],
+            )?;
+            Ok(updated > 0)
+        })
+        .await
+    }
+
+    async fn pin(&self, client_address: &str, program_id: &str, pinned: bool) -> Result<()> {
+        let (client_address, program_id) = (client_address.to_string(), program_id.to_string());
+        let sql = if pinned {
+            "INSERT OR IGNORE INTO pins (client_address, program_id) VALUES (?1, ?2)"
+        } else {
+            "DELETE FROM pins WHERE client_address = ?1 AND program_id = ?2"
+        };
+        self.with_conn(move |conn| {
+            conn.execute(sql, [client_address, program_id])?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn pinned(&self, client_address: &str) -> Result<Vec<String>> {
+        let client_address = client_address.to_string();
+        self.with_conn(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT program_id FROM pins WHERE client_address = ?1 ORDER BY program_id",
+            )?;
+            let program_ids = statement.query_map([client_address], |row| row.get(0))?;
+            Ok(program_ids.collect::<rusqlite::Result<_>>()?)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::server::store::tests::check_store;
+
+    #[tokio::test]
+    async fn test_sqlite_job_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SqliteJobStore::open(dir.path().join("jobs.db")).unwrap();
+        check_store(&store).await;
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_migrations() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("jobs.db");
+
+        // A database created before the schema was versioned, with the first schema.
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(MIGRATIONS[0]).unwrap();
+        conn.execute(
+            "INSERT INTO jobs (request_id, program_id, proof_mode, status, error_message, \
+             created_at, skip_simulation, idempotency_key, elf, stdin) \
+             VALUES ('0x01', '0x00', 1, 1, '', 1, 0, 'key', x'00', x'00')",
+            [],
+        )
+        .unwrap();
+        drop(conn);
+
+        let store = SqliteJobStore::open(&path).unwrap();
+        let job = store.get("0x01").await.unwrap().unwrap();
+        assert_eq!(job.usage, JobUsage::default());
+        assert!(job.labels.is_empty());
+        drop(store);
+
+        // Reopening a migrated database applies nothing.
+        let conn = Connection::open(&path).unwrap();
+        let version: usize =
+            conn.pragma_query_value(None, "user_version", |row| row.get(0)).unwrap();
+        assert_eq!(version, MIGRATIONS.len());
+        drop(conn);
+        SqliteJobStore::open(&path).unwrap();
+
+        // A database of a newer service is refused.
+        let conn = Connection::open(&path).unwrap();
+        conn.pragma_update(None, "user_version", MIGRATIONS.len() + 1).unwrap();
+        drop(conn);
+        assert!(SqliteJobStore::open(&path).is_err());
+    }
+}
@@ -0,0 +1,669 @@
+//! # Job Stores
+//!
+//! The [`JobStore`] trait persists the jobs of a [`ProvingService`](super::ProvingService), and the
+//! backends implementing it.
+//!
+//! Jobs are executed at least once: a worker claims a job for a lease, and renews the lease while
+//! it proves. If the worker dies, the lease expires and the job is claimed again, by the same
+//! service after a restart or by another service sharing the store.
+//!
+//! Every claim carries a fresh token that fences its lease: a worker whose lease expired cannot
+//! renew nor complete the job once another worker has claimed it again.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::unix_timestamp;
+use crate::{
+    network::proto::api::{JobStatus, ProofRequestSummary},
+    utils::write_atomic,
+};
+
+/// The error message of jobs cancelled by their client.
+pub const CANCELLED_MESSAGE: &str = "cancelled by the client";
+
+/// The metadata of a proof request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Job {
+    /// The id of the request, a `0x`-prefixed 32 bytes hex string.
+    pub request_id: String,
+    /// The program id given by the client.
+    pub program_id: String,
+    /// The proof mode, a [`crate::network::proto::api::ProofMode`].
+    pub proof_mode: i32,
+    /// The status, a [`JobStatus`].
+    pub status: i32,
+    /// Why the job failed, if it did.
+    pub error_message: String,
+    /// When the job was submitted, in unix seconds.
+    pub created_at: i64,
+    /// Whether to prove without executing the program first.
+    pub skip_simulation: bool,
+    /// The maximum number of cycles the program may execute for.
+    pub cycle_limit: Option<u64>,
+    /// The address of the client that submitted the job, if it authenticated.
+    pub client_address: Option<String>,
+    /// The key identifying retried submissions of the same request.
+    pub idempotency_key: Option<String>,
+    /// The labels given by the client, to attribute the cost of the job.
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    /// The resources the job consumed, once it has finished.
+    pub usage: JobUsage,
+}
+
+/// The resources consumed by a job.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobUsage {
+    /// The cycles the program executed for, zero if the job skipped the execution.
+    pub cycles: u64,
+    /// The wall-clock time the worker spent on the job, in milliseconds.
+    pub proving_time_ms: u64,
+}
+
+impl Job {
+    pub(crate) fn summary(&self) -> ProofRequestSummary {
+        ProofRequestSummary {
+            request_id: self.request_id.clone(),
+            program_id: self.program_id.clone(),
+            proof_mode: self.proof_mode,
+            status: self.status,
+            created_at: self.created_at,
+            labels: self.labels.clone().into_iter().collect(),
+        }
+    }
+
+    /// Whether the job carries all of the given labels.
+    pub(crate) fn has_labels<'a>(
+        &self,
+        labels: impl IntoIterator<Item = (&'a String, &'a String)>,
+    ) -> bool {
+        labels.into_iter().all(|(key, value)| self.labels.get(key) == Some(value))
+    }
+
+    fn has_status(&self, statuses: &[JobStatus]) -> bool {
+        statuses.iter().any(|status| self.status == *status as i32)
+    }
+}
+
+/// The filters of a listing of jobs, see [`JobStore::list_page`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JobFilter {
+    /// Only the jobs of this client, if set.
+    pub client_address: Option<String>,
+    /// Only the jobs in one of these statuses, any if empty.
+    pub statuses: Vec<i32>,
+    /// Only the jobs of this program, if set.
+    pub program_id: Option<String>,
+    /// Only the jobs created at or after this time, in unix seconds.
+    pub created_after: Option<i64>,
+    /// Only the jobs created before this time, in unix seconds.
+    pub created_before: Option<i64>,
+    /// Only the jobs carrying all of these labels.
+    pub labels: BTreeMap<String, String>,
+}
+
+impl JobFilter {
+    /// Whether a job passes the filter.
+    #[must_use]
+    pub fn matches(&self, job: &Job) -> bool {
+        (self.client_address.is_none() || job.client_address == self.client_address) &&
+            (self.statuses.is_empty() || self.statuses.contains(&job.status)) &&
+            self.program_id.as_ref().map_or(true, |program_id| job.program_id == *program_id) &&
+            self.created_after.map_or(true, |after| job.created_at >= after) &&
+            self.created_before.map_or(true, |before| job.created_at < before) &&
+            job.has_labels(&self.labels)
+    }
+}
+
+/// The program and input of a job.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobInput {
+    /// The ELF of the program.
+    pub elf: Vec<u8>,
+    /// The stdin, serialized with [`bincode`].
+    pub stdin: Vec<u8>,
+}
+
+/// A job claimed by a worker, see [`JobStore::claim`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Claim {
+    /// The claimed job, running.
+    pub job: Job,
+    /// The token of the claim, which [`JobStore::renew`] and [`JobStore::complete`] require.
+    pub token: String,
+}
+
+/// Returns a fresh claim token.
+pub(crate) fn claim_token() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// A store of proof jobs.
+///
+/// Implementations must make [`JobStore::insert`] and [`JobStore::claim`] atomic, so that a store
+/// shared by several services never creates a job twice for the same idempotency key, nor hands
+/// a job out to two workers at once.
+#[async_trait]
+pub trait JobStore: Send + Sync {
+    /// Stores a new pending job.
+    ///
+    /// If a job of the same client with the same idempotency key already exists, nothing is stored
+    /// and the existing job is returned instead. The keys of different clients never collide.
+    async fn insert(&self, job: Job, input: JobInput) -> Result<Job>;
+
+    /// Gets a job.
+    async fn get(&self, request_id: &str) -> Result<Option<Job>>;
+
+    /// Lists the jobs, oldest first, optionally only the ones of a client.
+    async fn list(&self, client_address: Option<&str>) -> Result<Vec<Job>>;
+
+    /// Lists at most `limit` of the jobs that pass `filter`, ordered by creation time then id,
+    /// starting after the job created at `after.0` with the id `after.1`, if given.
+    ///
+    /// The default implementation filters [`JobStore::list`], the stores backed by a database
+    /// filter and page in their queries.
+    async fn list_page(
+        &self,
+        filter: &JobFilter,
+        after: Option<(i64, &str)>,
+        limit: usize,
+    ) -> Result<Vec<Job>> {
+        let mut jobs = self.list(filter.client_address.as_deref()).await?;
+        jobs.sort_by(|a, b| (a.created_at, &a.request_id).cmp(&(b.created_at, &b.request_id)));
+        Ok(jobs
+            .into_iter()
+            .filter(|job| {
+                after.map_or(true, |after| (job.created_at, job.request_id.as_str()) > after) &&
+                    filter.matches(job)
+            })
+            .take(limit)
+            .collect())
+    }
+
+    /// Gets the input of a job.
+    async fn input(&self, request_id: &str) -> Result<JobInput>;
+
+    /// Gets the proof of a succeeded job.
+    async fn proof(&self, request_id: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Gets the encoded signed receipt of a succeeded job, if the service signed one.
+    async fn receipt(&self, request_id: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Claims the oldest pending job, or a running job whose lease has expired, marks it running
+    /// and leases it for `lease` under a fresh token.
+    async fn claim(&self, lease: Duration) -> Result<Option<Claim>>;
+
+    /// Extends the lease of a running job, and returns whether the job is still running under
+    /// the claim of `token`.
+    async fn renew(&self, request_id: &str, token: &str, lease: Duration) -> Result<bool>;
+
+    /// Records the outcome of a running job, the serialized proof or the error, with the
+    /// resources it consumed and the encoded signed receipt of a succeeded job, and returns
+    /// whether the job was still running under the claim of `token`.
+    async fn complete(
+        &self,
+        request_id: &str,
+        token: &str,
+        outcome: Result<Vec<u8>, String>,
+        usage: JobUsage,
+        receipt: Option<Vec<u8>>,
+    ) -> Result<bool>;
+
+    /// Cancels a pending or running job, and returns whether it was cancelled.
+    ///
+    /// A running job is not interrupted, but its outcome is discarded.
+    async fn cancel(&self, request_id: &str) -> Result<bool>;
+
+    /// Pins or unpins a program for a client.
+    async fn pin(&self, client_address: &str, program_id: &str, pinned: bool) -> Result<()>;
+
+    /// Lists the ids of the programs pinned by a client.
+    async fn pinned(&self, client_address: &str) -> Result<Vec<String>>;
+}
+
+/// Opens the job store at `url`.
+///
+/// The supported URLs are:
+/// - `sqlite://<path>`, with the `sqlite` feature.
+/// - `postgres://...` or `postgresql://...`, with the `postgres` feature.
+/// - `redis://...` or `rediss://...`, with the `redis` feature.
+/// - `file://<directory>`, or a plain directory path.
+pub async fn open(url: &str) -> Result<Arc<dyn JobStore>> {
+    let scheme = url.split_once("://").map(|(scheme, _)| scheme);
+    match scheme {
+        Some("sqlite") => {
+            cfg_if::cfg_if! {
+                if #[cfg(feature = "sqlite")] {
+                    let path = url.trim_start_matches("sqlite://");
+                    Ok(Arc::new(super::sqlite::SqliteJobStore::open(path)?))
+                } else {
+                    bail!("the sqlite job store requires the 'sqlite' feature")
+                }
+            }
+        }
+        Some("postgres" | "postgresql") => {
+            cfg_if::cfg_if! {
+                if #[cfg(feature = "postgres")] {
+                    Ok(Arc::new(super::postgres::PostgresJobStore::connect(url).await?))
+                } else {
+                    bail!("the postgres job store requires the 'postgres' feature")
+                }
+            }
+        }
+        Some("redis" | "rediss") => {
+            cfg_if::cfg_if! {
+                if #[cfg(feature = "redis")] {
+                    Ok(Arc::new(super::redis::RedisJobStore::connect(url).await?))
+                } else {
+                    bail!("the redis job store requires the 'redis' feature")
+                }
+            }
+        }
+        Some("file") => Ok(Arc::new(FileJobStore::open(url.trim_start_matches("file://"))?)),
+        Some(scheme) => bail!("unsupported job store url scheme '{scheme}'"),
+        None => Ok(Arc::new(FileJobStore::open(url)?)),
+    }
+}
+
+/// A job with the expiry of its lease, in unix seconds, and the token of its claim.
+#[derive(Debug, Clone)]
+struct FileEntry {
+    job: Job,
+    lease_expires_at: i64,
+    lease_token: String,
+}
+
+impl FileEntry {
+    fn unleased(job: Job) -> Self {
+        Self { job, lease_expires_at: 0, lease_token: String::new() }
+    }
+}
+
+/// A [`JobStore`] that keeps the jobs in a directory.
+///
+/// Each job is stored in up to four files: the metadata in `<request_id>.job`, the input in
+/// `<request_id>.input`, and the proof in `<request_id>.proof` and the signed receipt in
+/// `<request_id>.receipt` once the job has succeeded. The pinned programs are stored in `pins`.
+///
+/// The jobs are indexed in memory, so the directory must not be shared by several services.
+pub struct FileJobStore {
+    dir: PathBuf,
+    jobs: Mutex<HashMap<String, FileEntry>>,
+    /// The pinned programs, as `(client_address, program_id)` pairs.
+    pins: Mutex<BTreeSet<(String, String)>>,
+}
+
+impl FileJobStore {
+    /// Opens the store in `dir`, creating the directory if needed.
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create data directory {}", dir.display()))?;
+
+        let mut jobs = HashMap::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "job") {
+                let job: Job = bincode::deserialize(&fs::read(&path)?)
+                    .with_context(|| format!("failed to read job {}", path.display()))?;
+                // Leases are not persisted, so a job left running by a previous run is claimed
+                // again right away.
+                jobs.insert(job.request_id.clone(), FileEntry::unleased(job));
+            }
+        }
+
+        let pins = match fs::read(dir.join("pins")) {
+            Ok(bytes) => bincode::deserialize(&bytes).context("failed to read the pins")?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => BTreeSet::new(),
+            Err(err) => return Err(err).context("failed to read the pins"),
+        };
+
+        Ok(Self { dir, jobs: Mutex::new(jobs), pins: Mutex::new(pins) })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, FileEntry>> {
+        self.jobs.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn path(&self, request_id: &str, extension: &str) -> PathBuf {
+        self.dir.join(format!("{request_id}.{extension}"))
+    }
+
+    fn write_job(&self, job: &Job) -> Result<()> {
+        write_atomic(&self.path(&job.request_id, "job"), &bincode::serialize(job)?)
+    }
+
+    /// Updates the status of a job if it has one of the statuses `from`, and returns whether it
+    /// was updated.
+    fn transition(
+        &self,
+        request_id: &str,
+        from: &[JobStatus],
+        to: JobStatus,
+        error_message: &str,
+        usage: JobUsage,
+    ) -> Result<bool> {
+        let mut jobs = self.lock();
+        let Some(entry) = jobs.get_mut(request_id).filter(|entry| entry.job.has_status(from))
+        else {
+            return Ok(false);
+        };
+        entry.job.status = to as i32;
+        entry.job.error_message = error_message.to_string();
+        entry.job.usage = usage;
+        self.write_job(&entry.job)?;
+        Ok(true)
+    }
+}
+
+#[async_trait]
+impl JobStore for FileJobStore {
+    async fn insert(&self, job: Job, input: JobInput) -> Result<Job> {
+        let mut jobs = self.lock();
+        if let Some(key) = &job.idempotency_key {
+            let existing = jobs.values().find(|entry| {
+                entry.job.idempotency_key.as_ref() == Some(key)
+                    && entry.job.client_address == job.client_address
+            });
+            if let Some(entry) = existing {
+                return Ok(entry.job.clone());
+            }
+        }
+
+        write_atomic(&self.path(&job.request_id, "input"), &bincode::serialize(&input)?)?;
+        self.write_job(&job)?;
+        jobs.insert(job.request_id.clone(), FileEntry::unleased(job.clone()));
+        Ok(job)
+    }
+
+    async fn get(&self, request_id: &str) -> Result<Option<Job>> {
+        Ok(self.lock().get(request_id).map(|entry| entry.job.clone()))
+    }
+
+    async fn list(&self, client_address: Option<&str>) -> Result<Vec<Job>> {
+        let mut jobs: Vec<_> = self
+            .lock()
+            .values()
+            .filter(|entry| {
+                client_address.is_none() || entry.job.client_address.as_deref() == client_address
+            })
+            .map(|entry| entry.job.clone())
+            .collect();
+        jobs.sort_by_key(|job| job.created_at);
+        Ok(jobs)
+    }
+
+    async fn input(&self, request_id: &str) -> Result<JobInput> {
+        let bytes = fs::read(self.path(request_id, "input"))
+            .with_context(|| format!("failed to read the input of {request_id}"))?;
+        Ok(bincode::deserialize(&bytes)?)
+    }
+
+    async fn proof(&self, request_id: &str) -> Result<Option<Vec<u8>>> {
+        match fs::read(self.path(request_id, "proof")) {
+            Ok(proof) => Ok(Some(proof)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err).context("failed to read proof"),
+        }
+    }
+
+    async fn receipt(&self, request_id: &str) -> Result<Option<Vec<u8>>> {
+        match fs::read(self.path(request_id, "receipt")) {
+            Ok(receipt) => Ok(Some(receipt)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err).context("failed to read receipt"),
+        }
+    }
+
+    async fn claim(&self, lease: Duration) -> Result<Option<Claim>> {
+        let now = unix_timestamp();
+        let mut jobs = self.lock();
+        let claimable = jobs
+            .values_mut()
+            .filter(|entry| {
+                entry.job.status == JobStatus::Pending as i32 ||
+                    (entry.job.status == JobStatus::Running as i32 &&
+                        entry.lease_expires_at < now)
+            })
+            .min_by_key(|entry| entry.job.created_at);
+        let Some(entry) = claimable else {
+            return Ok(None);
+        };
+
+        entry.job.status = JobStatus::Running as i32;
+        entry.lease_expires_at = now + lease.as_secs() as i64;
+        entry.lease_token = claim_token();
+        self.write_job(&entry.job)?;
+        Ok(Some(Claim { job: entry.job.clone(), token: entry.lease_token.clone() }))
+    }
+
+    async fn renew(&self, request_id: &str, token: &str, lease: Duration) -> Result<bool> {
+        let mut jobs = self.lock();
+        let Some(entry) = jobs.get_mut(request_id).filter(|entry| {
+            entry.job.status == JobStatus::Running as i32 && entry.lease_token == token
+        }) else {
+            return Ok(false);
+        };
+        entry.lease_expires_at = unix_timestamp() + lease.as_secs() as i64;
+        Ok(true)
+    }
+
+    async fn complete(
+        &self,
+        request_id: &str,
+        token: &str,
+        outcome: Result<Vec<u8>, String>,
+        usage: JobUsage,
+        receipt: Option<Vec<u8>>,
+    ) -> Result<bool> {
+        // The files are written under the lock, so that a stale claim never overwrites them.
+        let mut jobs = self.lock();
+        let Some(entry) = jobs.get_mut(request_id).filter(|entry| {
+            entry.job.status == JobStatus::Running as i32 && entry.lease_token == token
+        }) else {
+            return Ok(false);
+        };
+        match outcome {
+            Ok(proof) => {
+                write_atomic(&self.path(request_id, "proof"), &proof)?;
+                if let Some(receipt) = receipt {
+                    write_atomic(&self.path(request_id, "receipt"), &receipt)?;
+                }
+                entry.job.status = JobStatus::Succeeded as i32;
+                entry.job.error_message = String::new();
+            }
+            Err(error) => {
+                entry.job.status = JobStatus::Failed as i32;
+                entry.job.error_message = error;
+            }
+        }
+        entry.job.usage = usage;
+        self.write_job(&entry.job)?;
+        Ok(true)
+    }
+
+    async fn cancel(&self, request_id: &str) -> Result<bool> {
+        self.transition(
+            request_id,
+            &[JobStatus::Pending, JobStatus::Running],
+            JobStatus::Failed,
+            CANCELLED_MESSAGE,
+            JobUsage::default(),
+        )
+    }
+
+    async fn pin(&self, client_address: &str, program_id: &str, pinned: bool) -> Result<()> {
+        let mut pins = self.pins.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        let pin = (client_address.to_string(), program_id.to_string());
+        let changed = if pinned { pins.insert(pin) } else { pins.remove(&pin) };
+        if changed {
+            write_atomic(&self.dir.join("pins"), &bincode::serialize(&*pins)?)?;
+        }
+        Ok(())
+    }
+
+    async fn pinned(&self, client_address: &str) -> Result<Vec<String>> {
+        let pins = self.pins.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        Ok(pins
+            .iter()
+            .filter(|(client, _)| client == client_address)
+            .map(|(_, program_id)| program_id.clone())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn job(request_id: &str, created_at: i64, idempotency_key: Option<&str>) -> Job {
+        Job {
+            request_id: request_id.to_string(),
+            program_id: "0x00".to_string(),
+            proof_mode: crate::network::proto::api::ProofMode::Core as i32,
+            status: JobStatus::Pending as i32,
+            error_message: String::new(),
+            created_at,
+            skip_simulation: false,
+            cycle_limit: None,
+            client_address: None,
+            idempotency_key: idempotency_key.map(str::to_string),
+            labels: BTreeMap::new(),
+            usage: JobUsage::default(),
+        }
+    }
+
+    fn input() -> JobInput {
+        JobInput { elf: b"elf".to_vec(), stdin: b"stdin".to_vec() }
+    }
+
+    /// Checks the semantics shared by every [`JobStore`] on an empty store.
+    pub(crate) async fn check_store(store: &dyn JobStore) {
+        let lease = Duration::from_secs(60);
+
+        store.insert(job("0x02", 2, None), input()).await.unwrap();
+        store.insert(job("0x01", 1, Some("key")), input()).await.unwrap();
+        let retried = store.insert(job("0x03", 3, Some("key")), input()).await.unwrap();
+        assert_eq!(retried.request_id, "0x01");
+        assert!(store.get("0x03").await.unwrap().is_none());
+        assert_eq!(store.input("0x01").await.unwrap(), input());
+
+        let listed = store.list(None).await.unwrap();
+        assert_eq!(
+            listed.iter().map(|job| job.request_id.as_str()).collect::<Vec<_>>(),
+            ["0x01", "0x02"]
+        );
+
+        // The oldest job is claimed first, and a leased job is not claimed again.
+        let first = store.claim(lease).await.unwrap().unwrap();
+        assert_eq!(first.job.request_id, "0x01");
+        assert_eq!(first.job.status, JobStatus::Running as i32);
+        let second = store.claim(lease).await.unwrap().unwrap();
+        assert_eq!(second.job.request_id, "0x02");
+        assert_ne!(first.token, second.token);
+        assert!(store.claim(lease).await.unwrap().is_none());
+        assert!(store.renew("0x01", &first.token, lease).await.unwrap());
+        assert!(!store.renew("0x01", &second.token, lease).await.unwrap());
+
+        let usage = JobUsage { cycles: 1000, proving_time_ms: 2000 };
+        let receipt = Some(b"receipt".to_vec());
+        let outcome = Ok(b"proof".to_vec());
+        assert!(store.complete("0x01", &first.token, outcome, usage, receipt).await.unwrap());
+        let done = store.get("0x01").await.unwrap().unwrap();
+        assert_eq!(done.status, JobStatus::Succeeded as i32);
+        assert_eq!(done.usage, usage);
+        assert_eq!(store.proof("0x01").await.unwrap().unwrap(), b"proof");
+        assert_eq!(store.receipt("0x01").await.unwrap().unwrap(), b"receipt");
+        assert!(!store.renew("0x01", &first.token, lease).await.unwrap());
+
+        // A cancelled job keeps the cancellation over the outcome of its worker.
+        assert!(store.cancel("0x02").await.unwrap());
+        let error = Err("error".to_string());
+        let failed = store.complete("0x02", &second.token, error, JobUsage::default(), None);
+        assert!(!failed.await.unwrap());
+        assert!(store.receipt("0x02").await.unwrap().is_none());
+        let cancelled = store.get("0x02").await.unwrap().unwrap();
+        assert_eq!(cancelled.status, JobStatus::Failed as i32);
+        assert_eq!(cancelled.error_message, CANCELLED_MESSAGE);
+        assert!(!store.cancel("0x02").await.unwrap());
+
+        // An expired lease makes the job claimable again, and fences off its previous claim.
+        store.insert(job("0x04", 4, None), input()).await.unwrap();
+        let stale = store.claim(Duration::ZERO).await.unwrap().unwrap();
+        assert_eq!(stale.job.request_id, "0x04");
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        let current = store.claim(lease).await.unwrap().unwrap();
+        assert_eq!(current.job.request_id, "0x04");
+        assert!(!store.renew("0x04", &stale.token, lease).await.unwrap());
+        let outcome = Ok(b"stale".to_vec());
+        let completed = store.complete("0x04", &stale.token, outcome, usage, None);
+        assert!(!completed.await.unwrap());
+        assert!(store.proof("0x04").await.unwrap().is_none());
+        assert!(store.renew("0x04", &current.token, lease).await.unwrap());
+
+        // The idempotency keys are scoped by client.
+        let other = Job {
+            client_address: Some("alice".to_string()),
+            ..job("0x05", 5, Some("key"))
+        };
+        assert_eq!(store.insert(other.clone(), input()).await.unwrap().request_id, "0x05");
+        let retried = Job { request_id: "0x06".to_string(), ..other };
+        assert_eq!(store.insert(retried, input()).await.unwrap().request_id, "0x05");
+
+        // The pages are filtered and ordered by creation time, and resume after the cursor.
+        let page = |filter: JobFilter, after: Option<(i64, &'static str)>, limit| async move {
+            let jobs = store.list_page(&filter, after, limit).await.unwrap();
+            jobs.into_iter().map(|job| job.request_id).collect::<Vec<_>>()
+        };
+        assert_eq!(page(JobFilter::default(), None, 2).await, ["0x01", "0x02"]);
+        assert_eq!(page(JobFilter::default(), Some((2, "0x02")), 10).await, ["0x04", "0x05"]);
+        let alice = JobFilter { client_address: Some("alice".to_string()), ..Default::default() };
+        assert_eq!(page(alice, None, 10).await, ["0x05"]);
+        let statuses = vec![JobStatus::Succeeded as i32, JobStatus::Running as i32];
+        let done = JobFilter { statuses, ..Default::default() };
+        assert_eq!(page(done, None, 10).await, ["0x01", "0x04"]);
+        let window =
+            JobFilter { created_after: Some(2), created_before: Some(5), ..Default::default() };
+        assert_eq!(page(window, None, 10).await, ["0x02", "0x04"]);
+        let program = JobFilter { program_id: Some("0xaa".to_string()), ..Default::default() };
+        assert!(page(program, None, 10).await.is_empty());
+        let labels = BTreeMap::from([("env".to_string(), "prod".to_string())]);
+        assert!(page(JobFilter { labels, ..Default::default() }, None, 10).await.is_empty());
+
+        // Pins are kept per client, and pinning twice is harmless.
+        store.pin("alice", "0xaa", true).await.unwrap();
+        store.pin("alice", "0xaa", true).await.unwrap();
+        store.pin("alice", "0xbb", true).await.unwrap();
+        store.pin("bob", "0xaa", true).await.unwrap();
+        store.pin("alice", "0xbb", false).await.unwrap();
+        assert_eq!(store.pinned("alice").await.unwrap(), ["0xaa"]);
+        assert_eq!(store.pinned("bob").await.unwrap(), ["0xaa"]);
+        assert!(store.pinned("carol").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_file_job_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileJobStore::open(dir.path()).unwrap();
+        check_store(&store).await;
+
+        // A reopened store keeps the jobs, and hands out the running ones again.
+        drop(store);
+        let store = FileJobStore::open(dir.path()).unwrap();
+        assert_eq!(store.list(None).await.unwrap().len(), 4);
+        let claim = store.claim(Duration::from_secs(60)).await.unwrap().unwrap();
+        assert_eq!(claim.job.request_id, "0x04");
+        assert_eq!(store.proof("0x01").await.unwrap().unwrap(), b"proof");
+        assert_eq!(store.pinned("alice").await.unwrap(), ["0xaa"]);
+    }
+}
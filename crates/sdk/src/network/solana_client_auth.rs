@@ -2,11 +2,28 @@
 //!
 //! This module provides helpers for signing client authentication messages
 //! using Ed25519 signatures compatible with Solana addresses.
+//!
+//! A message is only accepted within an expiry window around its timestamp, and a service keeps
+//! the nonces it has seen within that window in a [`NonceStore`], so that an intercepted message
+//! cannot be replayed. The nonces are random by default, and can instead come from a
+//! [`NonceCounter`] persisted across runs, or be challenges issued by the service, see
+//! [`NonceSource`].
 
-use anyhow::{Context, Result};
-use ed25519_dalek::{SigningKey, Signer};
+use anyhow::{anyhow, ensure, Context, Result};
+use ed25519_dalek::{Signature, SigningKey, Signer, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
 use sha2::{Sha256, Digest};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::{
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    network::Error,
+    utils::{unix_timestamp, write_atomic},
+};
 
 /// Derive Solana address from private key
 pub fn derive_solana_address(private_key: &[u8]) -> Result<String> {
@@ -23,6 +40,26 @@ pub fn derive_solana_address(private_key: &[u8]) -> Result<String> {
     Ok(address)
 }
 
+/// The maximum age of a client authentication message, in seconds.
+pub const MAX_CLIENT_AUTH_AGE_SECS: i64 = 3600;
+
+/// The default maximum number of outstanding challenges of a client, see
+/// [`NonceStore::max_challenges`].
+pub const DEFAULT_MAX_CHALLENGES_PER_CLIENT: usize = 16;
+
+/// The default maximum number of outstanding challenges of all the clients, see
+/// [`NonceStore::max_challenges`].
+pub const DEFAULT_MAX_CHALLENGES: usize = 100_000;
+
+/// Hash of the client authentication message: sha256(job_id || nonce || timestamp_le_bytes)
+fn client_auth_digest(job_id: &str, nonce: &str, timestamp: i64) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(job_id.as_bytes());
+    hasher.update(nonce.as_bytes());
+    hasher.update(timestamp.to_le_bytes());
+    hasher.finalize().into()
+}
+
 /// Sign client authentication message
 /// 
 /// The message format is: sha256(job_id || nonce || timestamp_le_bytes)
@@ -40,11 +77,7 @@ pub fn sign_client_auth(
     );
     
     // Create message: sha256(job_id || nonce || timestamp_le_bytes)
-    let mut hasher = Sha256::new();
-    hasher.update(job_id.as_bytes());
-    hasher.update(nonce.as_bytes());
-    hasher.update(timestamp.to_le_bytes());
-    let digest = hasher.finalize();
+    let digest = client_auth_digest(job_id, nonce, timestamp);
     
     // Sign the digest
     let signature = signing_key.sign(&digest);
@@ -54,13 +87,17 @@ pub fn sign_client_auth(
 /// Create client authentication data
 pub fn create_client_auth(
     private_key: &[u8],
+) -> Result<(String, String, i64, Vec<u8>, String)> {
+    create_client_auth_with_nonce(private_key, uuid::Uuid::new_v4().to_string())
+}
+
+/// Create client authentication data with the given nonce, see [`NonceSource`].
+pub fn create_client_auth_with_nonce(
+    private_key: &[u8],
+    nonce: String,
 ) -> Result<(String, String, i64, Vec<u8>, String)> {
     let job_id = uuid::Uuid::new_v4().to_string();
-    let nonce = uuid::Uuid::new_v4().to_string();
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .context("failed to get timestamp")?
-        .as_secs() as i64;
+    let timestamp = unix_timestamp();
     
     let signature = sign_client_auth(
         private_key,
@@ -74,3 +111,367 @@ pub fn create_client_auth(
     Ok((job_id, nonce, timestamp, signature, client_address))
 }
 
+/// Verify a client authentication message signed by [`sign_client_auth`]
+///
+/// Fails if the signature is not from the given Solana address, or if the message is older than
+/// [`MAX_CLIENT_AUTH_AGE_SECS`].
+pub fn verify_client_auth(
+    client_address: &str,
+    job_id: &str,
+    nonce: &str,
+    timestamp: i64,
+    signature: &[u8],
+) -> Result<()> {
+    verify_client_auth_within(
+        client_address,
+        job_id,
+        nonce,
+        timestamp,
+        signature,
+        MAX_CLIENT_AUTH_AGE_SECS,
+    )
+}
+
+/// Verify a client authentication message signed by [`sign_client_auth`], accepting messages
+/// whose timestamp is at most `max_age_secs` away from the current time.
+pub fn verify_client_auth_within(
+    client_address: &str,
+    job_id: &str,
+    nonce: &str,
+    timestamp: i64,
+    signature: &[u8],
+    max_age_secs: i64,
+) -> Result<()> {
+    let now = unix_timestamp();
+    ensure!((now - timestamp).abs() <= max_age_secs, "client authentication is stale");
+
+    let public_key: [u8; 32] = bs58::decode(client_address)
+        .into_vec()
+        .context("client address is not base58")?
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("client address must be 32 bytes"))?;
+    let verifying_key =
+        VerifyingKey::from_bytes(&public_key).context("client address is not a valid key")?;
+    let signature = Signature::from_slice(signature).context("invalid signature")?;
+
+    verifying_key
+        .verify(&client_auth_digest(job_id, nonce, timestamp), &signature)
+        .context("invalid client signature")
+}
+
+/// Where the nonces of the client authentication messages come from.
+#[derive(Debug, Clone, Default)]
+pub enum NonceSource {
+    /// A random UUID for each message.
+    #[default]
+    Random,
+    /// The next value of a counter persisted in a file, so that nonces never repeat across runs.
+    Counter(Arc<NonceCounter>),
+    /// A single-use challenge issued by the service for each message, which the service may
+    /// require. Fetching the challenges requires the gRPC transport.
+    Challenge,
+}
+
+impl NonceSource {
+    /// Draws the nonces from the counter persisted in `path`, see [`NonceCounter::open`].
+    pub fn counter(path: impl Into<PathBuf>) -> Result<Self> {
+        Ok(Self::Counter(Arc::new(NonceCounter::open(path)?)))
+    }
+}
+
+/// A monotonic counter persisted in a file, to draw nonces from.
+///
+/// The counter is written back before each nonce is returned, so a nonce is never returned twice,
+/// even if the process is killed. A counter file must not be shared by concurrent processes.
+#[derive(Debug)]
+pub struct NonceCounter {
+    path: PathBuf,
+    next: Mutex<u64>,
+}
+
+impl NonceCounter {
+    /// Opens the counter persisted in `path`, starting from zero if the file does not exist.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let next = match fs::read_to_string(&path) {
+            Ok(value) => value
+                .trim()
+                .parse()
+                .with_context(|| format!("invalid nonce counter {}", path.display()))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("failed to read nonce counter {}", path.display()))
+            }
+        };
+        Ok(Self { path, next: Mutex::new(next) })
+    }
+
+    /// Returns the next nonce, after persisting the counter.
+    pub fn next(&self) -> Result<String> {
+        let mut next = self.next.lock().unwrap();
+        let nonce = *next;
+        write_atomic(&self.path, (nonce + 1).to_string().as_bytes())
+            .with_context(|| format!("failed to write nonce counter {}", self.path.display()))?;
+        *next = nonce + 1;
+        Ok(nonce.to_string())
+    }
+}
+
+/// The nonces a service has seen and the challenges it has issued, over the expiry window of the
+/// client authentication messages.
+///
+/// A message older than the window is rejected as stale, so the nonces are only kept for the
+/// window, and a nonce seen again within it is rejected as replayed. The outstanding challenges
+/// are bounded, per client and in total, so that issuing them cannot exhaust the memory of the
+/// service.
+///
+/// The store is kept in memory, so a restarted service would accept the messages seen before it
+/// restarted until they expire, unless the store is [persisted](Self::persist).
+#[derive(Debug)]
+pub struct NonceStore {
+    max_age_secs: i64,
+    require_challenge: bool,
+    max_challenges_per_client: usize,
+    max_challenges: usize,
+    path: Option<PathBuf>,
+    state: Mutex<NonceState>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct NonceState {
+    /// The timestamps of the messages seen, by client address and nonce.
+    seen: HashMap<(String, String), i64>,
+    /// The client address and the issue time of the outstanding challenges, by challenge.
+    challenges: HashMap<String, (String, i64)>,
+}
+
+impl Default for NonceStore {
+    fn default() -> Self {
+        Self::new(MAX_CLIENT_AUTH_AGE_SECS)
+    }
+}
+
+impl NonceStore {
+    /// Creates a store accepting messages at most `max_age_secs` away from the current time.
+    #[must_use]
+    pub fn new(max_age_secs: i64) -> Self {
+        Self {
+            max_age_secs,
+            require_challenge: false,
+            max_challenges_per_client: DEFAULT_MAX_CHALLENGES_PER_CLIENT,
+            max_challenges: DEFAULT_MAX_CHALLENGES,
+            path: None,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Sets whether the nonces must be challenges issued with [`Self::issue_challenge`].
+    #[must_use]
+    pub fn require_challenge(mut self, require_challenge: bool) -> Self {
+        self.require_challenge = require_challenge;
+        self
+    }
+
+    /// Sets the maximum numbers of outstanding challenges, of each client and of all of them,
+    /// beyond which [`Self::issue_challenge`] fails with [`Error::TooManyChallenges`] until some
+    /// are used or expire.
+    #[must_use]
+    pub fn max_challenges(mut self, per_client: usize, total: usize) -> Self {
+        self.max_challenges_per_client = per_client;
+        self.max_challenges = total;
+        self
+    }
+
+    /// Persists the store in a file, so that the nonces and the challenges survive a restart of
+    /// the service. The store is loaded from the file if it exists, and written back on every
+    /// change.
+    ///
+    /// A store file must not be shared by concurrent services.
+    pub fn persist(mut self, path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let state = match fs::read(&path) {
+            Ok(bytes) => bincode::deserialize(&bytes)
+                .with_context(|| format!("invalid nonce store {}", path.display()))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => NonceState::default(),
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("failed to read nonce store {}", path.display()))
+            }
+        };
+        self.state = Mutex::new(state);
+        self.path = Some(path);
+        Ok(self)
+    }
+
+    /// The expiry window of the messages, in seconds.
+    #[must_use]
+    pub fn max_age_secs(&self) -> i64 {
+        self.max_age_secs
+    }
+
+    /// Issues a single-use challenge to a client, and returns it with its expiry time, in unix
+    /// seconds.
+    ///
+    /// Fails with [`Error::TooManyChallenges`] if the client or all the clients already have the
+    /// maximum number of outstanding challenges.
+    pub fn issue_challenge(&self, client_address: &str) -> Result<(String, i64)> {
+        let now = unix_timestamp();
+        let mut state = self.state.lock().unwrap();
+        state.prune(now - self.max_age_secs);
+        let issued =
+            state.challenges.values().filter(|(issued_to, _)| issued_to == client_address).count();
+        if issued >= self.max_challenges_per_client || state.challenges.len() >= self.max_challenges
+        {
+            return Err(Error::TooManyChallenges.into());
+        }
+        let challenge = uuid::Uuid::new_v4().to_string();
+        state.challenges.insert(challenge.clone(), (client_address.to_string(), now));
+        self.save(&state)?;
+        Ok((challenge, now + self.max_age_secs))
+    }
+
+    /// Verifies a client authentication message with [`verify_client_auth_within`], and records
+    /// its nonce.
+    ///
+    /// Fails if the nonce was already seen within the window, or if challenges are required and
+    /// the nonce is not a challenge issued to the client.
+    pub fn verify(
+        &self,
+        client_address: &str,
+        job_id: &str,
+        nonce: &str,
+        timestamp: i64,
+        signature: &[u8],
+    ) -> Result<()> {
+        verify_client_auth_within(
+            client_address,
+            job_id,
+            nonce,
+            timestamp,
+            signature,
+            self.max_age_secs,
+        )?;
+
+        let mut state = self.state.lock().unwrap();
+        state.prune(unix_timestamp() - self.max_age_secs);
+        let challenged = match state.challenges.get(nonce) {
+            Some((issued_to, _)) => issued_to == client_address,
+            None => false,
+        };
+        if challenged {
+            state.challenges.remove(nonce);
+        } else if self.require_challenge {
+            return Err(anyhow!("client authentication must use a challenge issued by the service"));
+        }
+
+        let key = (client_address.to_string(), nonce.to_string());
+        ensure!(!state.seen.contains_key(&key), "client authentication was replayed");
+        state.seen.insert(key, timestamp);
+        self.save(&state)
+    }
+
+    /// Writes the state back to the file of the store, if it is persisted.
+    fn save(&self, state: &NonceState) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        write_atomic(path, &bincode::serialize(state)?)
+            .with_context(|| format!("failed to write nonce store {}", path.display()))
+    }
+}
+
+impl NonceState {
+    /// Forgets the nonces and the challenges older than `oldest`, which are expired.
+    fn prune(&mut self, oldest: i64) {
+        self.seen.retain(|_, timestamp| *timestamp >= oldest);
+        self.challenges.retain(|_, (_, issued_at)| *issued_at >= oldest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nonce_store() {
+        let key = [7u8; 32];
+        let store = NonceStore::default();
+        let (job_id, nonce, timestamp, signature, address) = create_client_auth(&key).unwrap();
+        store.verify(&address, &job_id, &nonce, timestamp, &signature).unwrap();
+        let replayed = store.verify(&address, &job_id, &nonce, timestamp, &signature);
+        assert!(replayed.unwrap_err().to_string().contains("replayed"));
+
+        let stale = NonceStore::new(-1);
+        let (job_id, nonce, timestamp, signature, address) = create_client_auth(&key).unwrap();
+        assert!(stale.verify(&address, &job_id, &nonce, timestamp, &signature).is_err());
+    }
+
+    #[test]
+    fn test_nonce_challenge() {
+        let key = [7u8; 32];
+        let address = derive_solana_address(&key).unwrap();
+        let store = NonceStore::default().require_challenge(true);
+
+        let (job_id, nonce, timestamp, signature, _) = create_client_auth(&key).unwrap();
+        assert!(store.verify(&address, &job_id, &nonce, timestamp, &signature).is_err());
+
+        let (challenge, _) = store.issue_challenge(&address).unwrap();
+        let (job_id, nonce, timestamp, signature, _) =
+            create_client_auth_with_nonce(&key, challenge).unwrap();
+        store.verify(&address, &job_id, &nonce, timestamp, &signature).unwrap();
+        // A challenge is only valid once.
+        let (job_id, _, timestamp, signature, _) =
+            create_client_auth_with_nonce(&key, nonce.clone()).unwrap();
+        assert!(store.verify(&address, &job_id, &nonce, timestamp, &signature).is_err());
+
+        // Nor can another client use it.
+        let (challenge, _) = store.issue_challenge("someone else").unwrap();
+        let (job_id, nonce, timestamp, signature, _) =
+            create_client_auth_with_nonce(&key, challenge).unwrap();
+        assert!(store.verify(&address, &job_id, &nonce, timestamp, &signature).is_err());
+    }
+
+    #[test]
+    fn test_challenge_limits() {
+        let store = NonceStore::default().max_challenges(2, 3);
+        store.issue_challenge("a").unwrap();
+        store.issue_challenge("a").unwrap();
+        let err = store.issue_challenge("a").unwrap_err();
+        assert!(matches!(err.downcast_ref::<Error>(), Some(Error::TooManyChallenges)));
+        store.issue_challenge("b").unwrap();
+        assert!(store.issue_challenge("c").is_err());
+    }
+
+    #[test]
+    fn test_persisted_nonce_store() {
+        let key = [7u8; 32];
+        let address = derive_solana_address(&key).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nonces");
+
+        let store = NonceStore::default().persist(&path).unwrap();
+        let (job_id, nonce, timestamp, signature, _) = create_client_auth(&key).unwrap();
+        store.verify(&address, &job_id, &nonce, timestamp, &signature).unwrap();
+        let (challenge, _) = store.issue_challenge(&address).unwrap();
+
+        // A restarted service still rejects the replayed message, and accepts the challenge.
+        let store = NonceStore::default().persist(&path).unwrap();
+        let replayed = store.verify(&address, &job_id, &nonce, timestamp, &signature);
+        assert!(replayed.unwrap_err().to_string().contains("replayed"));
+        let (job_id, nonce, timestamp, signature, _) =
+            create_client_auth_with_nonce(&key, challenge).unwrap();
+        store.verify(&address, &job_id, &nonce, timestamp, &signature).unwrap();
+        assert!(store.state.lock().unwrap().challenges.is_empty());
+    }
+
+    #[test]
+    fn test_nonce_counter() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nonce");
+        let counter = NonceCounter::open(&path).unwrap();
+        assert_eq!(counter.next().unwrap(), "0");
+        assert_eq!(counter.next().unwrap(), "1");
+        assert_eq!(NonceCounter::open(&path).unwrap().next().unwrap(), "2");
+    }
+}
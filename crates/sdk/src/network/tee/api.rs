@@ -91,6 +91,50 @@ pub struct GetAddressResponse {
     pub address: Address,
 }
 
+/// The response payload from the TEE server for the `get_signed_signers` endpoint.
+///
+/// The signer set is signed by a signer of the previous set, so that a client can follow signer
+/// rotations without trusting the server that reports them.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignedSignerSet {
+    /// The current TEE signers.
+    pub signers: Vec<Address>,
+    /// The signature over the signer set, computed as
+    /// keccak256([`keccack256(version)` || `signers`]).
+    pub signature: Signature,
+    /// The recovery id of the signature, offset by 27.
+    pub recovery_id: u8,
+}
+
+impl SignedSignerSet {
+    /// The digest signed by the endorsing signer.
+    #[must_use]
+    pub fn digest(&self) -> alloy_primitives::B256 {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(
+            alloy_primitives::keccak256(super::MONEROCHAN_TEE_VERSION.to_le_bytes()).as_ref(),
+        );
+        for signer in &self.signers {
+            bytes.extend_from_slice(signer.as_ref());
+        }
+        alloy_primitives::keccak256(&bytes)
+    }
+
+    /// Recovers the address of the signer that endorsed the signer set.
+    #[must_use]
+    pub fn endorser(&self) -> Option<Address> {
+        let recovery_id =
+            k256::ecdsa::RecoveryId::from_byte(self.recovery_id.checked_sub(27)?)?;
+        let key = k256::ecdsa::VerifyingKey::recover_from_prehash(
+            self.digest().as_ref(),
+            &self.signature,
+            recovery_id,
+        )
+        .ok()?;
+        Some(Address::from_public_key(&key))
+    }
+}
+
 /// The underlying payload for the SSE event sent from the TEE server.
 ///
 /// This is an implementation detail, and should not be used directly.
@@ -1,4 +1,4 @@
-use super::api::{EventPayload, GetAddressResponse, TEERequest, TEEResponse};
+use super::api::{EventPayload, GetAddressResponse, SignedSignerSet, TEERequest, TEEResponse};
 
 use super::MONEROCHAN_TEE_VERSION;
 use alloy_primitives::Address;
@@ -138,4 +138,24 @@ impl Client {
 
         bincode::deserialize(&response).map_err(ClientError::Parse)
     }
+
+    /// Get the current signer set for the TEE server, endorsed by a signer of the previous set.
+    ///
+    /// This function will send a request to the TEE server, and await a response.
+    ///
+    /// # Errors
+    /// - [`ClientError::Http`] - If the request fails to send.
+    /// - [`ClientError::Parse`] - If the response is not valid.
+    pub async fn get_signed_signers(&self) -> Result<SignedSignerSet, ClientError> {
+        let response = self
+            .client
+            .get(format!("{}/signers/signed", self.url))
+            .header("X-MONEROCHAN-Tee-Version", MONEROCHAN_TEE_VERSION)
+            .send()
+            .await?
+            .bytes()
+            .await?;
+
+        bincode::deserialize(&response).map_err(ClientError::Parse)
+    }
 }
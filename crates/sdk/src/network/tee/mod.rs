@@ -4,6 +4,9 @@
 //! in a trusted execution environment (TEE).
 //!
 //! This acts a "2-factor authentication" for the MONEROCHAN proving system.
+//!
+//! Integrity proofs are verified by [`NetworkProver`](crate::NetworkProver), or
+//! with [`verify_tee_proof`] from the `monerochan-verifier` crate, which does not need a prover.
 
 /// The API for the TEE server.
 pub mod api;
@@ -11,11 +14,17 @@ pub mod api;
 /// The client for the TEE server.
 pub mod client;
 
+/// The registry of trusted TEE signers.
+pub mod registry;
+
 /// The MONEROCHAN TEE backend version to use.
 ///
-/// Since this doesn't necessarily correspond to new versions of MONEROCHAN,
-/// we opt to keep track of it manually here.
-pub const MONEROCHAN_TEE_VERSION: u32 = 1;
+/// Since this doesn't necessarily correspond to new versions of MONEROCHAN, it is tracked manually
+/// in the verifier crate, alongside the verification of the integrity proofs.
+pub use monerochan_verifier::tee::MONEROCHAN_TEE_VERSION;
+
+/// Verification of integrity proofs, without a [`crate::NetworkProver`].
+pub use monerochan_verifier::tee::{recover_tee_signer, verify_tee_proof, TeeError};
 
 /// This method will get the list of signers for the TEE server, trusting the server to honestly
 /// report the list of signers.
@@ -0,0 +1,298 @@
+use std::{
+    sync::{
+        mpsc::{self, RecvTimeoutError},
+        Arc, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+use alloy_primitives::Address;
+
+use super::{
+    api::SignedSignerSet,
+    client::{Client, ClientError},
+};
+
+/// The minimum time between two refreshes triggered by a proof signed by an unknown signer.
+const MIN_ON_DEMAND_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A callback invoked with the previous and the new signer set when the trusted signers change.
+pub type TeeSignerChangeCallback = Box<dyn Fn(&[Address], &[Address]) + Send + Sync>;
+
+/// Errors that can occur when refreshing the TEE signers.
+#[derive(Debug, thiserror::Error)]
+pub enum TeeSignerError {
+    /// The signer set could not be fetched from the TEE server.
+    #[error("failed to fetch the TEE signers: {0}")]
+    Client(#[from] ClientError),
+
+    /// The signature over the new signer set is invalid.
+    #[error("invalid signature over the TEE signer set")]
+    InvalidSignature,
+
+    /// The new signer set is not signed by a currently trusted signer.
+    #[error("TEE signer set is signed by {0}, which is not a trusted signer")]
+    UntrustedEndorser(Address),
+}
+
+/// How the trusted TEE signers are kept up to date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TeeSignerPolicy {
+    /// Only the configured signers are trusted, and they are never refreshed.
+    Pinned,
+    /// The signers are refreshed from the TEE server when they are older than the interval, or
+    /// when a proof is signed by an unknown signer.
+    ///
+    /// A new signer set is only accepted if it is signed by a currently trusted signer.
+    Refresh {
+        /// The maximum age of the signer set.
+        interval: Duration,
+    },
+}
+
+/// A thread that refreshes the signers of a [`TeeSignerRegistry`] every refresh interval, see
+/// [`TeeSignerRegistry::spawn_refresh_task`].
+///
+/// The thread stops when the task is dropped.
+pub struct TeeSignerRefreshTask {
+    _stop: mpsc::Sender<()>,
+}
+
+struct SignerState {
+    signers: Vec<Address>,
+    refreshed_at: Instant,
+}
+
+/// The set of TEE signers trusted to sign integrity proofs.
+pub struct TeeSignerRegistry {
+    policy: TeeSignerPolicy,
+    client: Client,
+    state: RwLock<SignerState>,
+    on_change: Option<TeeSignerChangeCallback>,
+}
+
+impl TeeSignerRegistry {
+    /// Creates a registry that only trusts the given signers.
+    #[must_use]
+    pub fn pinned(signers: Vec<Address>) -> Self {
+        Self::new(TeeSignerPolicy::Pinned, signers)
+    }
+
+    /// Creates a registry that starts from the given signers, and follows the signer rotations
+    /// reported by the TEE server.
+    #[must_use]
+    pub fn refreshing(signers: Vec<Address>, interval: Duration) -> Self {
+        Self::new(TeeSignerPolicy::Refresh { interval }, signers)
+    }
+
+    fn new(policy: TeeSignerPolicy, signers: Vec<Address>) -> Self {
+        Self {
+            policy,
+            client: Client::default(),
+            state: RwLock::new(SignerState { signers, refreshed_at: Instant::now() }),
+            on_change: None,
+        }
+    }
+
+    /// Sets the TEE server the signers are refreshed from.
+    #[must_use]
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Sets a callback invoked with the previous and the new signer set when the signers change.
+    #[must_use]
+    pub fn on_change(mut self, callback: TeeSignerChangeCallback) -> Self {
+        self.on_change = Some(callback);
+        self
+    }
+
+    /// The policy of the registry.
+    #[must_use]
+    pub fn policy(&self) -> TeeSignerPolicy {
+        self.policy
+    }
+
+    /// The currently trusted signers.
+    #[must_use]
+    pub fn signers(&self) -> Vec<Address> {
+        self.state.read().unwrap().signers.clone()
+    }
+
+    /// Fetches the signer set from the TEE server, and trusts it if it is signed by a currently
+    /// trusted signer. Returns whether the signers changed.
+    ///
+    /// Pinned registries are never refreshed.
+    ///
+    /// # Errors
+    /// - [`TeeSignerError::Client`] - If the signer set cannot be fetched.
+    /// - [`TeeSignerError::InvalidSignature`] - If the signature over the signer set is invalid.
+    /// - [`TeeSignerError::UntrustedEndorser`] - If the signer set is not signed by a trusted
+    ///   signer.
+    pub async fn refresh(&self) -> Result<bool, TeeSignerError> {
+        if self.policy == TeeSignerPolicy::Pinned {
+            return Ok(false);
+        }
+
+        let signed = self.client.get_signed_signers().await?;
+        let endorser = signed.endorser().ok_or(TeeSignerError::InvalidSignature)?;
+
+        let mut state = self.state.write().unwrap();
+        state.refreshed_at = Instant::now();
+        if !state.signers.contains(&endorser) {
+            return Err(TeeSignerError::UntrustedEndorser(endorser));
+        }
+        if state.signers == signed.signers {
+            return Ok(false);
+        }
+
+        let previous = std::mem::replace(&mut state.signers, signed.signers);
+        tracing::info!("TEE signers changed from {:?} to {:?}", previous, state.signers);
+        if let Some(on_change) = &self.on_change {
+            on_change(&previous, &state.signers);
+        }
+        Ok(true)
+    }
+
+    /// Spawns a thread that refreshes the signers every refresh interval, until the returned task
+    /// is dropped, so that the signers are kept up to date even when no proof is verified.
+    ///
+    /// Returns `None` for pinned registries. The thread only holds a weak reference to the
+    /// registry, and stops once the registry is dropped.
+    #[must_use]
+    pub fn spawn_refresh_task(self: &Arc<Self>) -> Option<TeeSignerRefreshTask> {
+        let TeeSignerPolicy::Refresh { interval } = self.policy else {
+            return None;
+        };
+        // Do not hammer the TEE server if the interval is very short.
+        let interval = interval.max(MIN_ON_DEMAND_REFRESH_INTERVAL);
+        let registry = Arc::downgrade(self);
+        let (stop, stopped) = mpsc::channel();
+        std::thread::spawn(move || {
+            let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                Ok(runtime) => runtime,
+                Err(err) => {
+                    tracing::warn!("failed to start the TEE signer refresh task: {err}");
+                    return;
+                }
+            };
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                let Some(registry) = registry.upgrade() else {
+                    return;
+                };
+                if let Err(err) = runtime.block_on(registry.refresh()) {
+                    tracing::warn!("failed to refresh the TEE signers: {err}");
+                }
+            }
+        });
+        Some(TeeSignerRefreshTask { _stop: stop })
+    }
+
+    /// Whether the signers should be refreshed before checking the address: they are older than
+    /// the refresh interval, or the address is unknown and they were not refreshed recently.
+    ///
+    /// Pinned registries are never stale.
+    #[must_use]
+    pub fn is_stale(&self, address: &Address) -> bool {
+        let TeeSignerPolicy::Refresh { interval } = self.policy else {
+            return false;
+        };
+        let state = self.state.read().unwrap();
+        let age = state.refreshed_at.elapsed();
+        // A proof signed by an unknown signer may follow a rotation, so refresh early, but not on
+        // every invalid proof.
+        let unknown = !state.signers.contains(address);
+        age >= interval || (unknown && age >= MIN_ON_DEMAND_REFRESH_INTERVAL)
+    }
+
+    /// Refreshes the signers if they are stale for the address, see [`Self::is_stale`].
+    ///
+    /// Failures are logged, and the current signers are kept.
+    pub async fn refresh_if_stale(&self, address: &Address) {
+        if self.is_stale(address) {
+            if let Err(err) = self.refresh().await {
+                tracing::warn!("failed to refresh the TEE signers: {err}");
+            }
+        }
+    }
+
+    /// Whether the address is one of the current signers.
+    ///
+    /// This never blocks on the TEE server: the signers are refreshed ahead of time with
+    /// [`Self::refresh_if_stale`] and [`Self::spawn_refresh_task`].
+    pub(crate) fn is_trusted(&self, address: &Address) -> bool {
+        self.state.read().unwrap().signers.contains(address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use k256::ecdsa::SigningKey;
+
+    use super::*;
+
+    fn sign(key: &SigningKey, signers: Vec<Address>) -> SignedSignerSet {
+        let mut set = SignedSignerSet {
+            signers,
+            signature: k256::ecdsa::Signature::from_scalars([1u8; 32], [1u8; 32]).unwrap(),
+            recovery_id: 27,
+        };
+        let (signature, recovery_id) = key.sign_prehash_recoverable(set.digest().as_ref()).unwrap();
+        set.signature = signature;
+        set.recovery_id = recovery_id.to_byte() + 27;
+        set
+    }
+
+    #[test]
+    fn test_signer_set_endorser() {
+        let old = SigningKey::from_bytes(&[1u8; 32].into()).unwrap();
+        let new = SigningKey::from_bytes(&[2u8; 32].into()).unwrap();
+        let old_address = Address::from_public_key(old.verifying_key());
+        let new_address = Address::from_public_key(new.verifying_key());
+
+        let set = sign(&old, vec![new_address]);
+        assert_eq!(set.endorser(), Some(old_address));
+
+        // Tampering with the signer set changes the recovered endorser.
+        let mut tampered = set.clone();
+        tampered.signers.push(old_address);
+        assert_ne!(tampered.endorser(), Some(old_address));
+    }
+
+    #[test]
+    fn test_pinned_registry() {
+        let address = Address::repeat_byte(1);
+        let registry = TeeSignerRegistry::pinned(vec![address]);
+        assert!(registry.is_trusted(&address));
+        assert!(!registry.is_trusted(&Address::repeat_byte(2)));
+        assert!(!crate::utils::block_on(registry.refresh()).unwrap());
+        assert!(!registry.is_stale(&Address::repeat_byte(2)));
+    }
+
+    #[test]
+    fn test_refreshing_registry_staleness() {
+        let address = Address::repeat_byte(1);
+        let registry = TeeSignerRegistry::refreshing(vec![address], Duration::from_secs(3600));
+        assert!(!registry.is_stale(&address));
+        // Unknown signers only trigger a refresh once the signers are old enough.
+        assert!(!registry.is_stale(&Address::repeat_byte(2)));
+
+        let registry = TeeSignerRegistry::refreshing(vec![address], Duration::ZERO);
+        assert!(registry.is_stale(&address));
+        // The cached signers are still used while they are stale.
+        assert!(registry.is_trusted(&address));
+    }
+
+    #[test]
+    fn test_refresh_task() {
+        let pinned = Arc::new(TeeSignerRegistry::pinned(vec![Address::repeat_byte(1)]));
+        assert!(pinned.spawn_refresh_task().is_none());
+
+        let registry = Arc::new(TeeSignerRegistry::refreshing(vec![], Duration::from_secs(3600)));
+        let task = registry.spawn_refresh_task();
+        assert!(task.is_some());
+        // The thread only holds a weak reference to the registry.
+        assert_eq!(Arc::strong_count(&registry), 1);
+    }
+}
@@ -77,6 +77,9 @@ pub fn get_explorer_url_for_mode(network_mode: super::NetworkMode) -> &'static s
 }
 
 /// Get the default cycle limit for the given network mode.
+///
+/// This is also the hard cap of the mode: the [`NetworkProver`](crate::NetworkProver) lowers
+/// larger cycle limits to it, and refuses to submit requests whose simulation exceeds it.
 #[must_use]
 pub fn get_default_cycle_limit_for_mode(network_mode: super::NetworkMode) -> u64 {
     match network_mode {
@@ -2,6 +2,10 @@
 //!
 //! This module provides validation functions for the network sdk.
 
+use std::time::Duration;
+
+use alloy_primitives::Address;
+
 use super::{FulfillmentStrategy, NetworkMode};
 
 /// Errors that can occur during network validation.
@@ -15,6 +19,37 @@ pub enum ValidationError {
         /// The network mode that was specified.
         mode: NetworkMode,
     },
+
+    /// An auction parameter is set for a request that is not fulfilled by an auction.
+    #[error(
+        "{parameter} is only used with FulfillmentStrategy::Auction, not \
+         FulfillmentStrategy::{strategy:?}"
+    )]
+    AuctionParameterWithoutAuction {
+        /// The name of the auction parameter.
+        parameter: &'static str,
+        /// The fulfillment strategy of the request.
+        strategy: FulfillmentStrategy,
+    },
+
+    /// The auction times out before its minimum period has passed, so it can never settle.
+    #[error(
+        "the auction timeout of {auction_timeout}s is shorter than the minimum auction period of \
+         {min_auction_period}s"
+    )]
+    AuctionTimeoutTooShort {
+        /// The auction timeout, in seconds.
+        auction_timeout: u64,
+        /// The minimum auction period, in seconds.
+        min_auction_period: u64,
+    },
+
+    /// A parameter is set that the network API does not support.
+    #[error("{parameter} is not supported by the network API")]
+    UnsupportedParameter {
+        /// The name of the parameter.
+        parameter: &'static str,
+    },
 }
 
 /// Validates that the given fulfillment strategy is compatible with the specified network mode.
@@ -62,6 +97,89 @@ pub fn validate_strategy_compatibility(
     }
 }
 
+/// Validates the auction parameters of a proof request.
+///
+/// The parameters are only used by [`FulfillmentStrategy::Auction`], so setting them with another
+/// strategy is an error rather than being silently ignored. An empty whitelist counts as unset.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use monerochan::network::{validation::validate_auction_parameters, FulfillmentStrategy};
+///
+/// assert!(validate_auction_parameters(
+///     FulfillmentStrategy::Auction,
+///     10,
+///     None,
+///     None,
+///     Some(1_000),
+///     Some(Duration::from_secs(60)),
+/// )
+/// .is_ok());
+/// assert!(validate_auction_parameters(
+///     FulfillmentStrategy::Hosted,
+///     0,
+///     None,
+///     None,
+///     Some(1_000),
+///     None,
+/// )
+/// .is_err());
+/// ```
+pub fn validate_auction_parameters(
+    strategy: FulfillmentStrategy,
+    min_auction_period: u64,
+    whitelist: Option<&[Address]>,
+    auctioneer: Option<Address>,
+    max_price_per_pgu: Option<u64>,
+    auction_timeout: Option<Duration>,
+) -> Result<(), ValidationError> {
+    if strategy != FulfillmentStrategy::Auction {
+        let parameters = [
+            ("min_auction_period", min_auction_period != 0),
+            ("whitelist", whitelist.is_some_and(|whitelist| !whitelist.is_empty())),
+            ("auctioneer", auctioneer.is_some()),
+            ("max_price_per_pgu", max_price_per_pgu.is_some()),
+            ("auction_timeout", auction_timeout.is_some()),
+        ];
+        if let Some((parameter, _)) = parameters.into_iter().find(|(_, set)| *set) {
+            return Err(ValidationError::AuctionParameterWithoutAuction { parameter, strategy });
+        }
+    }
+
+    if let Some(auction_timeout) = auction_timeout.map(|timeout| timeout.as_secs()) {
+        if auction_timeout < min_auction_period {
+            return Err(ValidationError::AuctionTimeoutTooShort {
+                auction_timeout,
+                min_auction_period,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Validates the settlement parameters of a proof request.
+///
+/// The network API settles every request with its own executor, verifier and treasury, so
+/// requests that set them are rejected rather than settled differently than asked.
+pub fn validate_settlement_parameters(
+    executor: Option<Address>,
+    verifier: Option<Address>,
+    treasury: Option<Address>,
+) -> Result<(), ValidationError> {
+    let parameters = [
+        ("executor", executor.is_some()),
+        ("verifier", verifier.is_some()),
+        ("treasury", treasury.is_some()),
+    ];
+    match parameters.into_iter().find(|(_, set)| *set) {
+        Some((parameter, _)) => Err(ValidationError::UnsupportedParameter { parameter }),
+        None => Ok(()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,4 +223,37 @@ mod tests {
         )
         .is_err());
     }
+
+    #[test]
+    fn test_auction_parameters() {
+        let timeout = Some(Duration::from_secs(30));
+        let auction = FulfillmentStrategy::Auction;
+        assert!(validate_auction_parameters(auction, 10, None, None, None, timeout).is_ok());
+        let hosted = FulfillmentStrategy::Hosted;
+        assert!(validate_auction_parameters(hosted, 0, Some(&[]), None, None, None).is_ok());
+
+        let err = validate_auction_parameters(
+            FulfillmentStrategy::Reserved,
+            0,
+            Some(&[Address::ZERO]),
+            None,
+            None,
+            None,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ValidationError::AuctionParameterWithoutAuction { parameter: "whitelist", .. }
+        ));
+
+        let err = validate_auction_parameters(auction, 60, None, None, None, timeout).unwrap_err();
+        assert!(matches!(err, ValidationError::AuctionTimeoutTooShort { auction_timeout: 30, .. }));
+    }
+
+    #[test]
+    fn test_settlement_parameters() {
+        assert!(validate_settlement_parameters(None, None, None).is_ok());
+        let err = validate_settlement_parameters(None, Some(Address::ZERO), None).unwrap_err();
+        assert!(matches!(err, ValidationError::UnsupportedParameter { parameter: "verifier" }));
+    }
 }
@@ -3,23 +3,38 @@
 //! A library of types and functions for MONEROCHAN proofs.
 #![allow(missing_docs)]
 
-use std::{fmt::Debug, fs::File, path::Path};
+use std::{
+    fmt::Debug,
+    fs::File,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use anyhow::{Context, Result};
 use hashbrown::HashMap;
 use p3_baby_bear::BabyBear;
-use p3_field::{extension::BinomialExtensionField, AbstractField, PrimeField};
+use p3_field::{extension::BinomialExtensionField, AbstractField, PrimeField, PrimeField32};
 use p3_fri::{FriProof, TwoAdicFriPcsProof};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
 use monerochan_primitives::io::MONEROCHANPublicValues;
-use monerochan_prover::{Groth16Bn254Proof, HashableKey, PlonkBn254Proof, MONEROCHANProvingKey};
+use monerochan_prover::{
+    verify::proof_fri_parameters, Groth16Bn254Proof, HashableKey, PlonkBn254Proof,
+    MONEROCHANProvingKey, MONEROCHANVerifyingKey, MONEROCHAN_CIRCUIT_VERSION,
+};
 use monerochan_stark::{
-    septic_digest::SepticDigest, MONEROCHANReduceProof, ShardCommitment, ShardOpenedValues, ShardProof,
-    StarkVerifyingKey,
+    septic_digest::SepticDigest, CoreSC, FriParameters, MONEROCHANReduceProof, ShardCommitment,
+    ShardOpenedValues, ShardProof, StarkVerifyingKey,
 };
 
 pub use monerochan_stark::{MONEROCHANProof, MONEROCHANProofMode};
 
+#[cfg(feature = "proof-compression")]
+mod compression;
+#[cfg(feature = "proof-compression")]
+pub use compression::COMPRESSED_PROOF_MAGIC;
+
 /// A proof generated by the MONEROCHAN RISC-V zkVM bundled together with the public values and the
 /// version.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,6 +60,82 @@ pub struct ProofFromNetwork {
     pub monerochan_version: String,
 }
 
+/// The prefix of proof files written by [`MONEROCHANProofWithPublicValues::save_with_metadata`].
+const PROOF_FILE_MAGIC: &[u8; 8] = b"MCPROOF1";
+
+/// The metadata stored alongside a proof by
+/// [`MONEROCHANProofWithPublicValues::save_with_metadata`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofMetadata {
+    /// The version of the circuit the proof was generated with.
+    pub circuit_version: String,
+    /// The version of the SDK that saved the proof.
+    pub sdk_version: String,
+    /// The hash of the verifying key of the program, as returned by [`HashableKey::bytes32`].
+    pub vk_hash: String,
+    /// The time the proof was saved at, in seconds since the Unix epoch.
+    pub created_at: u64,
+    /// The SHA-256 checksum of the serialized proof.
+    pub checksum: [u8; 32],
+}
+
+/// An error that occurs when loading a proof saved with metadata.
+#[derive(Debug, Error)]
+pub enum ProofMetadataError {
+    /// The proof was generated with another circuit version than the one of this SDK.
+    #[error(
+        "proof was generated with circuit version {found}, but this SDK uses circuit version \
+         {expected}"
+    )]
+    CircuitVersionMismatch {
+        /// The circuit version of this SDK.
+        expected: String,
+        /// The circuit version recorded in the proof file.
+        found: String,
+    },
+    /// The proof does not match the checksum recorded in the file, so the file is corrupted.
+    #[error("proof checksum mismatch, the proof file is corrupted")]
+    ChecksumMismatch,
+    /// The proof was generated for another program than the one of the given verifying key.
+    #[error("proof was generated for the program with vk hash {found}, expected {expected}")]
+    VkHashMismatch {
+        /// The hash of the given verifying key.
+        expected: String,
+        /// The vk hash recorded in the proof file.
+        found: String,
+    },
+    /// The proof does not match the recorded circuit version.
+    #[error("proof has version {proof}, but its metadata records circuit version {metadata}")]
+    InconsistentVersion {
+        /// The version recorded in the proof itself.
+        proof: String,
+        /// The circuit version recorded in the metadata.
+        metadata: String,
+    },
+}
+
+impl ProofMetadata {
+    /// Checks that the proof was generated for the program of the given verifying key.
+    pub fn check_vk(&self, vk: &MONEROCHANVerifyingKey) -> Result<(), ProofMetadataError> {
+        let expected = vk.bytes32();
+        if self.vk_hash != expected {
+            return Err(ProofMetadataError::VkHashMismatch {
+                expected,
+                found: self.vk_hash.clone(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// The layout of a proof file written by [`MONEROCHANProofWithPublicValues::save_with_metadata`],
+/// after [`PROOF_FILE_MAGIC`].
+#[derive(Serialize, Deserialize)]
+struct ProofFile {
+    metadata: ProofMetadata,
+    proof: Vec<u8>,
+}
+
 impl From<ProofFromNetwork> for MONEROCHANProofWithPublicValues {
     fn from(value: ProofFromNetwork) -> Self {
         Self {
@@ -79,8 +170,84 @@ impl MONEROCHANProofWithPublicValues {
         .map_err(Into::into)
     }
 
+    /// Saves the proof to a path, together with a [`ProofMetadata`] recording the circuit and SDK
+    /// versions, the vk hash of the program, the creation time and a checksum of the proof.
+    ///
+    /// Proofs saved this way are checked by [`Self::load`], which refuses proofs generated with
+    /// another circuit version.
+    pub fn save_with_metadata(
+        &self,
+        path: impl AsRef<Path>,
+        vk: &MONEROCHANVerifyingKey,
+    ) -> Result<ProofMetadata> {
+        let proof = bincode::serialize(self)?;
+        let metadata = ProofMetadata {
+            circuit_version: self.monerochan_version.clone(),
+            sdk_version: env!("CARGO_PKG_VERSION").to_string(),
+            vk_hash: vk.bytes32(),
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            checksum: Sha256::digest(&proof).into(),
+        };
+
+        let mut bytes = PROOF_FILE_MAGIC.to_vec();
+        bincode::serialize_into(&mut bytes, &ProofFile { metadata: metadata.clone(), proof })?;
+        std::fs::write(path.as_ref(), bytes)
+            .with_context(|| format!("failed to write proof to {}", path.as_ref().display()))?;
+
+        Ok(metadata)
+    }
+
+    /// Loads a proof saved by [`Self::save_with_metadata`], returning it with its metadata.
+    ///
+    /// # Errors
+    /// Fails with a [`ProofMetadataError`] if the proof was generated with another circuit version
+    /// than the one of this SDK, or if the file is corrupted.
+    pub fn load_with_metadata(path: impl AsRef<Path>) -> Result<(Self, ProofMetadata)> {
+        let bytes = std::fs::read(path.as_ref()).with_context(|| {
+            format!("failed to open file for loading proof: {}", path.as_ref().display())
+        })?;
+        let Some(file) = bytes.strip_prefix(PROOF_FILE_MAGIC.as_slice()) else {
+            anyhow::bail!(
+                "{} was not saved with `save_with_metadata`, use `load` instead",
+                path.as_ref().display()
+            );
+        };
+        let ProofFile { metadata, proof } = bincode::deserialize(file)?;
+
+        if metadata.circuit_version != MONEROCHAN_CIRCUIT_VERSION {
+            return Err(ProofMetadataError::CircuitVersionMismatch {
+                expected: MONEROCHAN_CIRCUIT_VERSION.to_string(),
+                found: metadata.circuit_version,
+            }
+            .into());
+        }
+        if <[u8; 32]>::from(Sha256::digest(&proof)) != metadata.checksum {
+            return Err(ProofMetadataError::ChecksumMismatch.into());
+        }
+
+        let this: Self = bincode::deserialize(&proof)?;
+        if this.monerochan_version != metadata.circuit_version {
+            return Err(ProofMetadataError::InconsistentVersion {
+                proof: this.monerochan_version,
+                metadata: metadata.circuit_version,
+            }
+            .into());
+        }
+
+        Ok((this, metadata))
+    }
+
     /// Loads a proof from a path.
+    ///
+    /// Proofs saved by [`Self::save_with_metadata`] are checked as in [`Self::load_with_metadata`].
     pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        if Self::has_metadata(path.as_ref())? {
+            return Self::load_with_metadata(path).map(|(this, _)| this);
+        }
+
         // Try to load a [`Self`] from the file.
         let maybe_this: Result<Self> =
             bincode::deserialize_from(File::open(path.as_ref()).with_context(|| {
@@ -114,6 +281,17 @@ impl MONEROCHANProofWithPublicValues {
         }
     }
 
+    /// Whether the file at the path was written by [`Self::save_with_metadata`].
+    fn has_metadata(path: &Path) -> Result<bool> {
+        use std::io::Read;
+
+        let mut magic = [0u8; PROOF_FILE_MAGIC.len()];
+        let mut file = File::open(path).with_context(|| {
+            format!("failed to open file for loading proof: {}", path.display())
+        })?;
+        Ok(file.read_exact(&mut magic).is_ok() && &magic == PROOF_FILE_MAGIC)
+    }
+
     /// The proof in the byte encoding the onchain verifiers accepts for [`MONEROCHANProofMode::Groth16`]
     /// and [`MONEROCHANProofMode::Plonk`] proofs.
     ///
@@ -172,6 +350,26 @@ impl MONEROCHANProofWithPublicValues {
         }
     }
 
+    /// The FRI parameters of a [`MONEROCHANProofMode::Core`] or [`MONEROCHANProofMode::Compressed`]
+    /// proof, whose [`FriParameters::conjectured_security_bits`] is the security level of the
+    /// proof.
+    ///
+    /// Returns `None` for the other modes, whose proofs are not FRI proofs, and for mock proofs.
+    #[must_use]
+    pub fn fri_parameters(&self) -> Option<FriParameters> {
+        let proof_of_work_bits = CoreSC::default().fri_parameters().proof_of_work_bits;
+        match &self.proof {
+            MONEROCHANProof::Core(shard_proofs) => {
+                proof_fri_parameters(shard_proofs, proof_of_work_bits)
+            }
+            MONEROCHANProof::Compressed(reduce_proof) => proof_fri_parameters(
+                std::slice::from_ref(&reduce_proof.proof),
+                proof_of_work_bits,
+            ),
+            _ => None,
+        }
+    }
+
     /// Creates a mock proof for the specified proof mode from the public values.
     ///
     /// # Example
@@ -201,36 +399,37 @@ impl MONEROCHANProofWithPublicValues {
         public_values: MONEROCHANPublicValues,
         mode: MONEROCHANProofMode,
         monerochan_version: &str,
+    ) -> Self {
+        Self::create_mock_proof_with_seed(pk, public_values, mode, monerochan_version, 0)
+    }
+
+    /// Creates a mock proof for the specified proof mode from the public values, which only a mock
+    /// prover created with the same seed accepts.
+    ///
+    /// # Details
+    /// The proof carries a deterministic tag derived from the seed, the verifying key, the public
+    /// values and the proof mode, so the same inputs always produce the same proof. See
+    /// [`crate::cpu::builder::CpuProverBuilder::mock_seed`].
+    #[must_use]
+    pub fn create_mock_proof_with_seed(
+        pk: &MONEROCHANProvingKey,
+        public_values: MONEROCHANPublicValues,
+        mode: MONEROCHANProofMode,
+        monerochan_version: &str,
+        seed: u64,
     ) -> Self {
         let monerochan_version = monerochan_version.to_string();
+        let tag = mock_proof_tag(seed, &pk.vk.bytes32(), &public_values, mode);
         match mode {
             MONEROCHANProofMode::Core => MONEROCHANProofWithPublicValues {
-                proof: MONEROCHANProof::Core(vec![]),
+                proof: MONEROCHANProof::Core(vec![mock_shard_proof(&tag)]),
                 public_values,
                 monerochan_version,
 
                 tee_proof: None,
             },
             MONEROCHANProofMode::Compressed => {
-                let shard_proof = ShardProof {
-                    commitment: ShardCommitment {
-                        main_commit: [BabyBear::zero(); 8].into(),
-                        permutation_commit: [BabyBear::zero(); 8].into(),
-                        quotient_commit: [BabyBear::zero(); 8].into(),
-                    },
-                    opened_values: ShardOpenedValues { chips: vec![] },
-                    opening_proof: TwoAdicFriPcsProof {
-                        fri_proof: FriProof {
-                            commit_phase_commits: vec![],
-                            query_proofs: vec![],
-                            final_poly: BinomialExtensionField::default(),
-                            pow_witness: BabyBear::zero(),
-                        },
-                        query_openings: vec![],
-                    },
-                    chip_ordering: HashMap::new(),
-                    public_values: vec![],
-                };
+                let shard_proof = mock_shard_proof(&tag);
 
                 let reduce_vk = StarkVerifyingKey {
                     commit: [BabyBear::zero(); 8].into(),
@@ -254,7 +453,7 @@ impl MONEROCHANProofWithPublicValues {
                         public_values.hash_bn254().to_string(),
                     ],
                     encoded_proof: String::new(),
-                    raw_proof: String::new(),
+                    raw_proof: hex::encode(tag),
                     plonk_vkey_hash: [0; 32],
                 }),
                 public_values,
@@ -269,7 +468,7 @@ impl MONEROCHANProofWithPublicValues {
                         public_values.hash_bn254().to_string(),
                     ],
                     encoded_proof: String::new(),
-                    raw_proof: String::new(),
+                    raw_proof: hex::encode(tag),
                     groth16_vkey_hash: [0; 32],
                 }),
                 public_values,
@@ -279,6 +478,67 @@ impl MONEROCHANProofWithPublicValues {
             },
         }
     }
+
+    /// Returns the tag of a mock proof, or `None` if the proof is not a mock proof.
+    pub(crate) fn mock_proof_tag(&self) -> Option<[u8; 32]> {
+        let tag: Vec<u8> = match &self.proof {
+            MONEROCHANProof::Core(shards) => match shards.as_slice() {
+                [shard] => shard.public_values.iter().map(|v| v.as_canonical_u32() as u8).collect(),
+                _ => return None,
+            },
+            MONEROCHANProof::Compressed(proof) => {
+                proof.proof.public_values.iter().map(|v| v.as_canonical_u32() as u8).collect()
+            }
+            MONEROCHANProof::Plonk(PlonkBn254Proof { encoded_proof, raw_proof, .. }) |
+            MONEROCHANProof::Groth16(Groth16Bn254Proof { encoded_proof, raw_proof, .. }) => {
+                if !encoded_proof.is_empty() {
+                    return None;
+                }
+                hex::decode(raw_proof).ok()?
+            }
+        };
+        tag.try_into().ok()
+    }
+}
+
+/// Computes the tag that binds a mock proof to the seed of the mock prover, the program, the public
+/// values and the proof mode.
+pub(crate) fn mock_proof_tag(
+    seed: u64,
+    vkey_hash: &str,
+    public_values: &MONEROCHANPublicValues,
+    mode: MONEROCHANProofMode,
+) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"monerochan-mock-proof");
+    hasher.update(seed.to_le_bytes());
+    hasher.update(vkey_hash.as_bytes());
+    hasher.update([mode as u8]);
+    hasher.update(public_values.as_slice());
+    hasher.finalize().into()
+}
+
+/// Creates an empty shard proof whose public values hold the tag of a mock proof.
+fn mock_shard_proof(tag: &[u8; 32]) -> ShardProof<CoreSC> {
+    ShardProof {
+        commitment: ShardCommitment {
+            main_commit: [BabyBear::zero(); 8].into(),
+            permutation_commit: [BabyBear::zero(); 8].into(),
+            quotient_commit: [BabyBear::zero(); 8].into(),
+        },
+        opened_values: ShardOpenedValues { chips: vec![] },
+        opening_proof: TwoAdicFriPcsProof {
+            fri_proof: FriProof {
+                commit_phase_commits: vec![],
+                query_proofs: vec![],
+                final_poly: BinomialExtensionField::default(),
+                pow_witness: BabyBear::zero(),
+            },
+            query_openings: vec![],
+        },
+        chip_ordering: HashMap::new(),
+        public_values: tag.iter().map(|&b| BabyBear::from_canonical_u8(b)).collect(),
+    }
 }
 
 #[cfg(test)]
@@ -410,4 +670,49 @@ mod tests {
         // Verify the loaded proof
         prover.verify(&proof_loaded, &pk.vk).unwrap();
     }
+
+    #[test]
+    fn test_proof_save_load_with_metadata() {
+        let proof = MONEROCHANProofWithPublicValues {
+            proof: MONEROCHANProof::Core(vec![]),
+            public_values: MONEROCHANPublicValues::from(&[1, 2, 3]),
+            monerochan_version: MONEROCHAN_CIRCUIT_VERSION.to_string(),
+            tee_proof: None,
+        };
+        let prover = crate::CpuProver::mock();
+        let (_, vk) = crate::Prover::setup(&prover, test_artifacts::FIBONACCI_ELF);
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("proof.bin");
+        let saved = proof.save_with_metadata(&path, &vk).unwrap();
+        assert_eq!(saved.circuit_version, MONEROCHAN_CIRCUIT_VERSION);
+
+        let (loaded, metadata) =
+            MONEROCHANProofWithPublicValues::load_with_metadata(&path).unwrap();
+        assert_eq!(metadata, saved);
+        assert_eq!(loaded.public_values.as_slice(), proof.public_values.as_slice());
+        metadata.check_vk(&vk).unwrap();
+        MONEROCHANProofWithPublicValues::load(&path).unwrap();
+
+        // A proof from another circuit version is refused.
+        let mut old = proof.clone();
+        old.monerochan_version = "v0.0.0".to_string();
+        old.save_with_metadata(&path, &vk).unwrap();
+        let err = MONEROCHANProofWithPublicValues::load(&path).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ProofMetadataError>(),
+            Some(ProofMetadataError::CircuitVersionMismatch { .. })
+        ));
+
+        // So is a corrupted proof.
+        proof.save_with_metadata(&path, &vk).unwrap();
+        let mut bytes = std::fs::read(&path).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        std::fs::write(&path, bytes).unwrap();
+        let err = MONEROCHANProofWithPublicValues::load(&path).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ProofMetadataError>(),
+            Some(ProofMetadataError::ChecksumMismatch)
+        ));
+    }
 }
@@ -0,0 +1,117 @@
+//! # Proof Compression
+//!
+//! Core proofs hold a shard proof for every shard of the execution, and are retained for audits
+//! long after they are verified. [`MONEROCHANProofWithPublicValues::compress_bytes`] encodes a proof
+//! with zstd, using a dictionary of the names of the RISC-V chips, which every shard proof repeats
+//! in its chip ordering, and a window large enough to find the matches between shards.
+//!
+//! Compressed proofs start with [`COMPRESSED_PROOF_MAGIC`] and the id of the dictionary, so that a
+//! proof compressed with the dictionary of another SDK version is refused rather than decoded into
+//! garbage.
+
+use std::sync::OnceLock;
+
+use anyhow::{ensure, Context, Result};
+use monerochan_core_executor::RiscvAirId;
+use sha2::{Digest, Sha256};
+use strum::IntoEnumIterator;
+
+use super::MONEROCHANProofWithPublicValues;
+
+/// The prefix of proofs encoded by [`MONEROCHANProofWithPublicValues::compress_bytes`].
+pub const COMPRESSED_PROOF_MAGIC: &[u8; 8] = b"MCPROOFZ";
+
+/// The zstd level. Proofs are compressed once and stored for long, so the ratio matters more than
+/// the speed.
+const LEVEL: i32 = 19;
+
+/// The log of the window of the compressor, 128 MiB, which is the largest window decoders accept
+/// by default.
+const WINDOW_LOG: u32 = 27;
+
+/// A raw zstd dictionary, and the prefix of its sha256 digest identifying it.
+struct Dictionary {
+    bytes: Vec<u8>,
+    id: [u8; 4],
+}
+
+/// The dictionary of the compressor: the chip names, encoded as bincode encodes the keys of the
+/// chip ordering of a shard proof.
+fn dictionary() -> &'static Dictionary {
+    static DICTIONARY: OnceLock<Dictionary> = OnceLock::new();
+    DICTIONARY.get_or_init(|| {
+        let mut bytes = Vec::new();
+        for air in RiscvAirId::iter() {
+            bincode::serialize_into(&mut bytes, air.as_str()).expect("failed to encode chip name");
+        }
+        let id = Sha256::digest(&bytes)[..4].try_into().unwrap();
+        Dictionary { bytes, id }
+    })
+}
+
+impl MONEROCHANProofWithPublicValues {
+    /// Encodes the proof into compressed bytes, to be decoded with [`Self::decompress`].
+    ///
+    /// The compression is tuned for [`crate::MONEROCHANProofMode::Core`] proofs, but any proof
+    /// can be compressed.
+    pub fn compress_bytes(&self) -> Result<Vec<u8>> {
+        let dictionary = dictionary();
+        let mut bytes = COMPRESSED_PROOF_MAGIC.to_vec();
+        bytes.extend_from_slice(&dictionary.id);
+
+        let mut encoder = zstd::Encoder::with_dictionary(bytes, LEVEL, &dictionary.bytes)?;
+        encoder.long_distance_matching(true)?;
+        encoder.window_log(WINDOW_LOG)?;
+        encoder.include_checksum(true)?;
+        bincode::serialize_into(&mut encoder, self).context("failed to compress proof")?;
+        Ok(encoder.finish()?)
+    }
+
+    /// Decodes a proof encoded by [`Self::compress_bytes`].
+    ///
+    /// # Errors
+    /// Fails if the bytes are not a compressed proof, were compressed with another dictionary, or
+    /// are corrupted.
+    pub fn decompress(bytes: &[u8]) -> Result<Self> {
+        let dictionary = dictionary();
+        let bytes = bytes
+            .strip_prefix(COMPRESSED_PROOF_MAGIC.as_slice())
+            .context("the bytes are not a compressed proof")?;
+        ensure!(
+            bytes.get(..dictionary.id.len()) == Some(dictionary.id.as_slice()),
+            "the proof was compressed with another dictionary"
+        );
+
+        let mut decoder =
+            zstd::Decoder::with_dictionary(&bytes[dictionary.id.len()..], &dictionary.bytes)?;
+        decoder.window_log_max(WINDOW_LOG)?;
+        bincode::deserialize_from(decoder).context("failed to decompress proof")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use monerochan_primitives::io::MONEROCHANPublicValues;
+
+    use super::*;
+    use crate::MONEROCHANProof;
+
+    #[test]
+    fn test_compress_round_trip() {
+        let proof = MONEROCHANProofWithPublicValues {
+            proof: MONEROCHANProof::Core(vec![]),
+            public_values: MONEROCHANPublicValues::from(&[1, 2, 3]),
+            monerochan_version: "v1.0.0".to_string(),
+            tee_proof: None,
+        };
+
+        let compressed = proof.compress_bytes().unwrap();
+        let decompressed = MONEROCHANProofWithPublicValues::decompress(&compressed).unwrap();
+        assert_eq!(bincode::serialize(&decompressed).unwrap(), bincode::serialize(&proof).unwrap());
+
+        let mut other_dictionary = compressed.clone();
+        other_dictionary[COMPRESSED_PROOF_MAGIC.len()] ^= 1;
+        assert!(MONEROCHANProofWithPublicValues::decompress(&other_dictionary).is_err());
+        assert!(MONEROCHANProofWithPublicValues::decompress(&compressed[1..]).is_err());
+    }
+}
@@ -0,0 +1,241 @@
+//! # Proof Stores
+//!
+//! A [`ProofStore`] keeps proofs under a content address, the [`ProofKey`]: the vk hash of the
+//! program, the digest of the public values and the proof mode. A proof of a program for some
+//! public values is the same artifact whichever service generated it, so services sharing a store
+//! find the proofs of each other, and never need to agree on a layout of their own.
+//!
+//! Stored proofs carry a checksum, and are checked against it and against their key when read, so
+//! that a corrupted or misplaced object is an error rather than a wrong proof.
+
+#[cfg(feature = "s3")]
+mod s3;
+#[cfg(feature = "s3")]
+pub use s3::S3ProofStore;
+
+use std::{
+    fs,
+    path::PathBuf,
+};
+
+use anyhow::{bail, ensure, Context, Result};
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    utils::write_atomic, HashableKey, MONEROCHANProofMode, MONEROCHANProofWithPublicValues,
+    MONEROCHANVerifyingKey,
+};
+
+/// The prefix of the objects written by the stores.
+const STORED_PROOF_MAGIC: &[u8; 8] = b"MCSTORE1";
+
+/// The address of a proof in a [`ProofStore`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProofKey {
+    /// The vk hash of the program, as returned by [`HashableKey::bytes32`].
+    pub vk_hash: String,
+    /// The sha256 digest of the public values.
+    pub public_values_digest: [u8; 32],
+    /// The mode of the proof.
+    pub mode: MONEROCHANProofMode,
+}
+
+impl ProofKey {
+    /// The key of a proof of the program of `vk`.
+    #[must_use]
+    pub fn new(vk: &MONEROCHANVerifyingKey, proof: &MONEROCHANProofWithPublicValues) -> Self {
+        Self::with_vk_hash(vk.bytes32(), proof)
+    }
+
+    /// The key of a proof of the program with the given vk hash.
+    fn with_vk_hash(vk_hash: String, proof: &MONEROCHANProofWithPublicValues) -> Self {
+        Self {
+            vk_hash,
+            public_values_digest: Sha256::digest(proof.public_values.as_slice()).into(),
+            mode: MONEROCHANProofMode::from(&proof.proof),
+        }
+    }
+
+    /// Checks that the vk hash is `0x` followed by 64 hex digits, as returned by
+    /// [`HashableKey::bytes32`], so that the key cannot address objects outside of its store.
+    pub fn validate(&self) -> Result<()> {
+        let digits = self.vk_hash.strip_prefix("0x").unwrap_or_default();
+        ensure!(
+            digits.len() == 64 && digits.bytes().all(|b| b.is_ascii_hexdigit()),
+            "invalid vk hash {:?}, expected 0x followed by 64 hex digits",
+            self.vk_hash
+        );
+        Ok(())
+    }
+
+    /// The relative path of the proof in a store, `<vk_hash>/<mode>/<public_values_digest>`.
+    #[must_use]
+    pub fn path(&self) -> String {
+        let mode = format!("{:?}", self.mode).to_lowercase();
+        format!("{}/{mode}/{}", self.vk_hash, hex::encode(self.public_values_digest))
+    }
+}
+
+/// A content-addressed store of proofs.
+#[async_trait]
+pub trait ProofStore: Send + Sync {
+    /// Stores a proof, replacing the proof stored under the same key if there is one.
+    ///
+    /// # Errors
+    /// Fails if the key is invalid, or is not the key of the proof.
+    async fn put(&self, key: &ProofKey, proof: &MONEROCHANProofWithPublicValues) -> Result<()>;
+
+    /// Gets a proof, checking its integrity.
+    ///
+    /// # Errors
+    /// Fails if the key is invalid, or if the stored proof is corrupted or does not match its
+    /// key.
+    async fn get(&self, key: &ProofKey) -> Result<Option<MONEROCHANProofWithPublicValues>>;
+
+    /// Whether a proof is stored under the key.
+    async fn exists(&self, key: &ProofKey) -> Result<bool>;
+}
+
+/// Encodes a proof as stored: the magic, the sha256 checksum of the encoded proof, and the proof
+/// encoded with [`bincode`].
+///
+/// Fails if the key is invalid, or is not the key of the proof, as the proof would be refused
+/// when read.
+pub(crate) fn encode(key: &ProofKey, proof: &MONEROCHANProofWithPublicValues) -> Result<Vec<u8>> {
+    key.validate()?;
+    let expected = ProofKey::with_vk_hash(key.vk_hash.clone(), proof);
+    ensure!(expected == *key, "the proof does not match the key {}", key.path());
+
+    let proof = bincode::serialize(proof).context("failed to encode proof")?;
+    let mut bytes = STORED_PROOF_MAGIC.to_vec();
+    bytes.extend_from_slice(&Sha256::digest(&proof));
+    bytes.extend_from_slice(&proof);
+    Ok(bytes)
+}
+
+/// Decodes a stored proof, checking its checksum and that it matches its key.
+pub(crate) fn decode(key: &ProofKey, bytes: &[u8]) -> Result<MONEROCHANProofWithPublicValues> {
+    key.validate()?;
+    let Some(bytes) = bytes.strip_prefix(STORED_PROOF_MAGIC.as_slice()) else {
+        bail!("the object at {} is not a stored proof", key.path());
+    };
+    ensure!(bytes.len() >= 32, "the proof at {} is truncated", key.path());
+    let (checksum, proof) = bytes.split_at(32);
+    ensure!(
+        Sha256::digest(proof).as_slice() == checksum,
+        "the proof at {} does not match its checksum",
+        key.path()
+    );
+
+    let proof: MONEROCHANProofWithPublicValues =
+        bincode::deserialize(proof).with_context(|| format!("failed to decode {}", key.path()))?;
+    // The vk hash cannot be recovered from the proof, only the rest of the key is checked.
+    let found = ProofKey::with_vk_hash(key.vk_hash.clone(), &proof);
+    ensure!(found == *key, "the proof at {} is stored under the wrong key", key.path());
+    Ok(proof)
+}
+
+/// A [`ProofStore`] that keeps the proofs in a directory, each in the file `<key path>.proof`.
+pub struct FileProofStore {
+    dir: PathBuf,
+}
+
+impl FileProofStore {
+    /// Opens the store in `dir`, creating the directory if needed.
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create proof directory {}", dir.display()))?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, key: &ProofKey) -> Result<PathBuf> {
+        key.validate()?;
+        Ok(self.dir.join(format!("{}.proof", key.path())))
+    }
+}
+
+#[async_trait]
+impl ProofStore for FileProofStore {
+    async fn put(&self, key: &ProofKey, proof: &MONEROCHANProofWithPublicValues) -> Result<()> {
+        let path = self.path(key)?;
+        let bytes = encode(key, proof)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_atomic(&path, &bytes)
+            .with_context(|| format!("failed to write proof to {}", path.display()))
+    }
+
+    async fn get(&self, key: &ProofKey) -> Result<Option<MONEROCHANProofWithPublicValues>> {
+        let path = self.path(key)?;
+        match fs::read(&path) {
+            Ok(bytes) => decode(key, &bytes).map(Some),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err).with_context(|| format!("failed to read {}", path.display())),
+        }
+    }
+
+    async fn exists(&self, key: &ProofKey) -> Result<bool> {
+        Ok(self.path(key)?.try_exists()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+    use crate::{MONEROCHANProof, MONEROCHANPublicValues};
+
+    fn proof(public_values: &[u8]) -> MONEROCHANProofWithPublicValues {
+        MONEROCHANProofWithPublicValues {
+            proof: MONEROCHANProof::Core(vec![]),
+            public_values: MONEROCHANPublicValues::from(public_values),
+            monerochan_version: String::new(),
+            tee_proof: None,
+        }
+    }
+
+    fn key(proof: &MONEROCHANProofWithPublicValues) -> ProofKey {
+        ProofKey::with_vk_hash(format!("0x{}", "01".repeat(32)), proof)
+    }
+
+    #[test]
+    fn test_file_proof_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileProofStore::open(dir.path()).unwrap();
+        let (proof, other) = (proof(b"public values"), proof(b"other values"));
+
+        assert!(!block_on(store.exists(&key(&proof))).unwrap());
+        assert!(block_on(store.get(&key(&proof))).unwrap().is_none());
+
+        block_on(store.put(&key(&proof), &proof)).unwrap();
+        assert!(block_on(store.exists(&key(&proof))).unwrap());
+        let stored = block_on(store.get(&key(&proof))).unwrap().unwrap();
+        assert_eq!(stored.public_values.as_slice(), proof.public_values.as_slice());
+
+        // A proof is not stored under the key of another one, and is refused if found there.
+        assert!(block_on(store.put(&key(&other), &proof)).is_err());
+        let misplaced = store.path(&key(&other)).unwrap();
+        fs::create_dir_all(misplaced.parent().unwrap()).unwrap();
+        fs::copy(store.path(&key(&proof)).unwrap(), &misplaced).unwrap();
+        assert!(block_on(store.get(&key(&other))).is_err());
+
+        // Keys that are not vk hashes are refused rather than turned into paths.
+        for vk_hash in ["0x01", "../../etc", &format!("0x{}", "zz".repeat(32))] {
+            let invalid = ProofKey { vk_hash: vk_hash.to_string(), ..key(&proof) };
+            assert!(block_on(store.put(&invalid, &proof)).is_err());
+            assert!(block_on(store.get(&invalid)).is_err());
+            assert!(block_on(store.exists(&invalid)).is_err());
+        }
+
+        // So is a corrupted proof.
+        let path = store.path(&key(&proof)).unwrap();
+        let mut bytes = fs::read(&path).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        fs::write(&path, bytes).unwrap();
+        assert!(block_on(store.get(&key(&proof))).is_err());
+    }
+}
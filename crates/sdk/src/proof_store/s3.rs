@@ -0,0 +1,83 @@
+//! A [`ProofStore`] backed by S3.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use aws_sdk_s3::{error::DisplayErrorContext, primitives::ByteStream, Client};
+
+use super::{decode, encode, ProofKey, ProofStore};
+use crate::MONEROCHANProofWithPublicValues;
+
+/// A [`ProofStore`] that keeps the proofs in an S3 bucket, each in the object
+/// `<prefix>/<key path>.proof`.
+pub struct S3ProofStore {
+    client: Client,
+    bucket: String,
+    prefix: String,
+}
+
+impl S3ProofStore {
+    /// Creates a store in `bucket`, under `prefix`, with a client.
+    pub fn new(client: Client, bucket: impl Into<String>, prefix: impl Into<String>) -> Self {
+        Self { client, bucket: bucket.into(), prefix: prefix.into() }
+    }
+
+    /// Creates a store in `bucket`, under `prefix`, with a client configured from the environment.
+    pub async fn from_env(bucket: impl Into<String>, prefix: impl Into<String>) -> Self {
+        let config = aws_config::defaults(aws_config::BehaviorVersion::latest()).load().await;
+        Self::new(Client::new(&config), bucket, prefix)
+    }
+
+    fn object_key(&self, key: &ProofKey) -> Result<String> {
+        key.validate()?;
+        let prefix = self.prefix.trim_end_matches('/');
+        if prefix.is_empty() {
+            Ok(format!("{}.proof", key.path()))
+        } else {
+            Ok(format!("{prefix}/{}.proof", key.path()))
+        }
+    }
+}
+
+#[async_trait]
+impl ProofStore for S3ProofStore {
+    async fn put(&self, key: &ProofKey, proof: &MONEROCHANProofWithPublicValues) -> Result<()> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(self.object_key(key)?)
+            .body(ByteStream::from(encode(key, proof)?))
+            .send()
+            .await
+            .map_err(|e| anyhow!("failed to put {}: {}", key.path(), DisplayErrorContext(e)))?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &ProofKey) -> Result<Option<MONEROCHANProofWithPublicValues>> {
+        let result =
+            self.client.get_object().bucket(&self.bucket).key(self.object_key(key)?).send().await;
+        let object = match result {
+            Ok(object) => object,
+            Err(e) if e.as_service_error().is_some_and(|e| e.is_no_such_key()) => return Ok(None),
+            Err(e) => {
+                return Err(anyhow!("failed to get {}: {}", key.path(), DisplayErrorContext(e)))
+            }
+        };
+        let bytes = object
+            .body
+            .collect()
+            .await
+            .map_err(|e| anyhow!("failed to read {}: {e}", key.path()))?
+            .into_bytes();
+        decode(key, &bytes).map(Some)
+    }
+
+    async fn exists(&self, key: &ProofKey) -> Result<bool> {
+        let result =
+            self.client.head_object().bucket(&self.bucket).key(self.object_key(key)?).send().await;
+        match result {
+            Ok(_) => Ok(true),
+            Err(e) if e.as_service_error().is_some_and(|e| e.is_not_found()) => Ok(false),
+            Err(e) => Err(anyhow!("failed to check {}: {}", key.path(), DisplayErrorContext(e))),
+        }
+    }
+}
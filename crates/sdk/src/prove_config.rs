@@ -0,0 +1,168 @@
+//! # Prove Configs
+//!
+//! A [`ProveConfig`] captures a proving policy, such as the proof mode, the fulfillment strategy,
+//! the timeouts and the limits of the requests, so that it can be kept in a config file per
+//! environment rather than in code, and applied to any prove builder with `with_config`.
+//!
+//! Configs are read from JSON files, where every field is optional:
+//!
+//! ```json
+//! {
+//!     "mode": "groth16",
+//!     "strategy": "Hosted",
+//!     "timeout_secs": 600,
+//!     "cycle_limit": 100000000,
+//!     "labels": { "env": "staging" }
+//! }
+//! ```
+//!
+//! A builder only applies the fields it supports: the CPU, CUDA and env provers ignore the fields
+//! specific to the network. The methods called on a builder after `with_config` override the
+//! config.
+
+use std::{collections::HashMap, fs, path::Path, time::Duration};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "network")]
+use crate::network::FulfillmentStrategy;
+use crate::MONEROCHANProofMode;
+
+/// A reusable configuration of proof requests.
+///
+/// See the [module documentation](self) for the file format.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProveConfig {
+    /// The proof mode, `core`, `compressed`, `plonk` or `groth16`.
+    #[serde(with = "proof_mode")]
+    pub mode: Option<MONEROCHANProofMode>,
+    /// The fulfillment strategy of network requests.
+    pub strategy: Option<NetworkStrategy>,
+    /// The time to wait for a network proof, in seconds.
+    pub timeout_secs: Option<u64>,
+    /// The time to wait for a prover to bid on a network request, in seconds.
+    pub auction_timeout_secs: Option<u64>,
+    /// The maximum number of cycles the program may execute for.
+    pub cycle_limit: Option<u64>,
+    /// The maximum gas a network request may use.
+    pub gas_limit: Option<u64>,
+    /// The maximum price per prover gas unit of a network request.
+    pub max_price_per_pgu: Option<u64>,
+    /// Whether to skip the local simulation of network requests.
+    pub skip_simulation: Option<bool>,
+    /// The labels of network requests.
+    pub labels: HashMap<String, String>,
+}
+
+impl ProveConfig {
+    /// Reads a config from a JSON file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("failed to read prove config {}", path.display()))?;
+        serde_json::from_str(&json)
+            .with_context(|| format!("invalid prove config {}", path.display()))
+    }
+
+    /// Writes the config to a JSON file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("failed to write prove config {}", path.display()))
+    }
+
+    /// The time to wait for a network proof.
+    #[must_use]
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout_secs.map(Duration::from_secs)
+    }
+
+    /// The time to wait for a prover to bid on a network request.
+    #[must_use]
+    pub fn auction_timeout(&self) -> Option<Duration> {
+        self.auction_timeout_secs.map(Duration::from_secs)
+    }
+}
+
+/// The fulfillment strategy of network requests in a [`ProveConfig`].
+///
+/// The network `FulfillmentStrategy` is only built with the `network` feature, while configs are
+/// read the same way by every build.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NetworkStrategy {
+    /// The on-demand prover of the network.
+    Hosted,
+    /// An existing agreement with a fulfiller.
+    Reserved,
+    /// A proof contest between the provers of the network.
+    Auction,
+}
+
+#[cfg(feature = "network")]
+impl From<NetworkStrategy> for FulfillmentStrategy {
+    fn from(strategy: NetworkStrategy) -> Self {
+        match strategy {
+            NetworkStrategy::Hosted => Self::Hosted,
+            NetworkStrategy::Reserved => Self::Reserved,
+            NetworkStrategy::Auction => Self::Auction,
+        }
+    }
+}
+
+/// (De)serializes a proof mode by its lowercase name.
+mod proof_mode {
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::MONEROCHANProofMode;
+
+    pub(super) fn serialize<S: Serializer>(
+        mode: &Option<MONEROCHANProofMode>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        mode.map(|mode| format!("{mode:?}").to_lowercase()).serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<MONEROCHANProofMode>, D::Error> {
+        let Some(name) = Option::<String>::deserialize(deserializer)? else {
+            return Ok(None);
+        };
+        let mode = match name.as_str() {
+            "core" => MONEROCHANProofMode::Core,
+            "compressed" => MONEROCHANProofMode::Compressed,
+            "plonk" => MONEROCHANProofMode::Plonk,
+            "groth16" => MONEROCHANProofMode::Groth16,
+            _ => return Err(D::Error::custom(format!("unknown proof mode {name}"))),
+        };
+        Ok(Some(mode))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prove_config() {
+        let config: ProveConfig = serde_json::from_str(
+            r#"{"mode": "groth16", "strategy": "Hosted", "timeout_secs": 600,
+                "labels": {"env": "staging"}}"#,
+        )
+        .unwrap();
+        assert_eq!(config.mode, Some(MONEROCHANProofMode::Groth16));
+        assert_eq!(config.strategy, Some(NetworkStrategy::Hosted));
+        assert_eq!(config.timeout(), Some(Duration::from_secs(600)));
+        assert_eq!(config.cycle_limit, None);
+        assert_eq!(config.labels["env"], "staging");
+
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(serde_json::from_str::<ProveConfig>(&json).unwrap(), config);
+
+        assert!(serde_json::from_str::<ProveConfig>(r#"{"mode": "stark"}"#).is_err());
+        assert!(serde_json::from_str::<ProveConfig>(r#"{"strategy": "Fastest"}"#).is_err());
+        assert!(serde_json::from_str::<ProveConfig>(r#"{"timeout": 600}"#).is_err());
+    }
+}
@@ -0,0 +1,65 @@
+//! # Build Provenance
+//!
+//! Binds the verification key of a program to the provenance of its reproducible build, written
+//! by `monerochan-build` in Docker mode. An auditor can rebuild the program from the sources
+//! hashed in the provenance, check that the ELF is the same, and compare the verification key hash
+//! to the one deployed on-chain.
+
+use anyhow::{bail, Result};
+use monerochan_prover::{
+    components::MONEROCHANProverComponents, HashableKey, MONEROCHANVerifyingKey,
+};
+
+pub use monerochan_build::{BuildProvenance, PROVENANCE_EXTENSION};
+
+use crate::Prover;
+
+/// Checks that an ELF is the one described by a provenance, and returns its verification key.
+///
+/// # Details
+/// If the provenance records a verification key hash, the verification key of the ELF must match
+/// it.
+///
+/// # Example
+/// ```rust,no_run
+/// use monerochan::{provenance::verify_provenance, BuildProvenance, ProverClient};
+///
+/// let elf = std::fs::read("program.elf").unwrap();
+/// let provenance = BuildProvenance::load("program.elf.provenance.json").unwrap();
+///
+/// let client = ProverClient::builder().cpu().build();
+/// let vk = verify_provenance(&client, &elf, &provenance).unwrap();
+/// ```
+pub fn verify_provenance<C: MONEROCHANProverComponents>(
+    prover: &impl Prover<C>,
+    elf: &[u8],
+    provenance: &BuildProvenance,
+) -> Result<MONEROCHANVerifyingKey> {
+    provenance.verify_elf(elf)?;
+    let (_, vk) = prover.try_setup(elf)?;
+    if let Some(expected) = &provenance.vk_hash {
+        let actual = vk.bytes32();
+        if actual != *expected {
+            bail!(
+                "verification key hash mismatch for target `{}`: expected {expected}, got {actual}",
+                provenance.target
+            );
+        }
+    }
+    Ok(vk)
+}
+
+/// Checks that an ELF is the one described by a provenance, and returns the provenance with the
+/// verification key hash of the ELF recorded in it.
+///
+/// # Details
+/// The returned provenance can be saved with [`BuildProvenance::save`] and published alongside the
+/// deployed verification key.
+pub fn attest_provenance<C: MONEROCHANProverComponents>(
+    prover: &impl Prover<C>,
+    elf: &[u8],
+    provenance: &BuildProvenance,
+) -> Result<BuildProvenance> {
+    let vk = verify_provenance(prover, elf, provenance)?;
+    Ok(BuildProvenance { vk_hash: Some(vk.bytes32()), ..provenance.clone() })
+}
@@ -5,16 +5,18 @@
 use std::borrow::Borrow;
 
 use anyhow::Result;
+use async_trait::async_trait;
 use itertools::Itertools;
-use p3_field::PrimeField32;
+use p3_baby_bear::BabyBear;
+use p3_field::{PrimeField, PrimeField32};
 use monerochan_core_executor::{ExecutionReport, MONEROCHANContext};
 use monerochan_core_machine::io::MONEROCHANStdin;
-use monerochan_primitives::io::MONEROCHANPublicValues;
+use monerochan_primitives::io::{blake3_hash, MONEROCHANPublicValues};
 use monerochan_prover::{
-    components::MONEROCHANProverComponents, CoreSC, InnerSC, MONEROCHANCoreProofData, MONEROCHANProver, MONEROCHANProvingKey,
+    components::MONEROCHANProverComponents, CoreSC, HashableKey, InnerSC, MONEROCHANCoreProofData, MONEROCHANProver, MONEROCHANProvingKey,
     MONEROCHANVerifyingKey, MONEROCHAN_CIRCUIT_VERSION,
 };
-use monerochan_stark::{air::PublicValues, MachineVerificationError, Word};
+use monerochan_stark::{air::PublicValues, FriParameters, MachineVerificationError, Word};
 use thiserror::Error;
 
 use crate::{
@@ -32,8 +34,17 @@ pub trait Prover<C: MONEROCHANProverComponents>: Send + Sync {
     }
 
     /// Generate the proving and verifying keys for the given program.
+    ///
+    /// # Panics
+    /// Panics if the program cannot be set up, see [`Prover::try_setup`].
     fn setup(&self, elf: &[u8]) -> (MONEROCHANProvingKey, MONEROCHANVerifyingKey);
 
+    /// Generate the proving and verifying keys for the given program, failing if the ELF is
+    /// invalid or if the program does not fit the prover, e.g. if it is too large.
+    fn try_setup(&self, elf: &[u8]) -> Result<(MONEROCHANProvingKey, MONEROCHANVerifyingKey)> {
+        Ok(self.setup(elf))
+    }
+
     /// Executes the program on the given input.
     fn execute(&self, elf: &[u8], stdin: &MONEROCHANStdin) -> Result<(MONEROCHANPublicValues, ExecutionReport)> {
         let (pv, _, report) = self.inner().execute(elf, stdin, MONEROCHANContext::default())?;
@@ -48,6 +59,20 @@ pub trait Prover<C: MONEROCHANProverComponents>: Send + Sync {
         mode: MONEROCHANProofMode,
     ) -> Result<MONEROCHANProofWithPublicValues>;
 
+    /// Proves a Wasm module on the given input in the given proof mode.
+    ///
+    /// The proving key must be the one of a guest built from `monerochan-wasm-guest`. Decode the
+    /// public values of the proof with [`crate::wasm::WasmPublicValues`].
+    fn prove_wasm(
+        &self,
+        pk: &MONEROCHANProvingKey,
+        wasm_bytes: &[u8],
+        input: &[u8],
+        mode: MONEROCHANProofMode,
+    ) -> Result<MONEROCHANProofWithPublicValues> {
+        self.prove(pk, &crate::wasm::wasm_stdin(wasm_bytes, input), mode)
+    }
+
     /// Verify that an MONEROCHAN proof is valid given its vkey and metadata.
     /// For Plonk proofs, verifies that the public inputs of the `PlonkBn254` proof match
     /// the hash of the VK and the committed public values of the `MONEROCHANProofWithPublicValues`.
@@ -56,19 +81,142 @@ pub trait Prover<C: MONEROCHANProverComponents>: Send + Sync {
         bundle: &MONEROCHANProofWithPublicValues,
         vkey: &MONEROCHANVerifyingKey,
     ) -> Result<(), MONEROCHANVerificationError> {
-        verify_proof(self.inner(), self.version(), bundle, vkey)
+        verify_proof(self.inner(), self.version(), bundle, vkey, None)
+    }
+
+    /// Verify an MONEROCHAN proof generated with the given FRI parameters.
+    ///
+    /// [`Prover::verify`] rejects the [`MONEROCHANProofMode::Core`] and
+    /// [`MONEROCHANProofMode::Compressed`] proofs whose FRI parameters are not the default ones,
+    /// e.g. the proofs with a number of queries set with `fri_queries`. The parameters are not
+    /// used for the other modes.
+    fn verify_with_fri_parameters(
+        &self,
+        bundle: &MONEROCHANProofWithPublicValues,
+        vkey: &MONEROCHANVerifyingKey,
+        fri_parameters: FriParameters,
+    ) -> Result<(), MONEROCHANVerificationError> {
+        verify_proof(self.inner(), self.version(), bundle, vkey, Some(fri_parameters))
+    }
+
+    /// Verify an MONEROCHAN proof generated with the given circuit version.
+    ///
+    /// Proofs of the current circuit version are verified with [`Prover::verify`]. Plonk and
+    /// Groth16 proofs of one of the [`PREVIOUS_CIRCUIT_VERSIONS`] are verified with the verifying
+    /// keys of their version, which are kept in the crate, so nothing is downloaded. The verifiers
+    /// of older core and compressed proofs are not kept, so they are rejected.
+    #[cfg(feature = "legacy-verifiers")]
+    fn verify_with_version(
+        &self,
+        bundle: &MONEROCHANProofWithPublicValues,
+        vkey: &MONEROCHANVerifyingKey,
+        version: &str,
+    ) -> Result<(), MONEROCHANVerificationError> {
+        if version == self.version() {
+            return self.verify(bundle, vkey);
+        }
+        verify_legacy_proof(bundle, vkey, version)
     }
 }
 
+/// The previous circuit versions whose Plonk and Groth16 proofs can still be verified with
+/// [`Prover::verify_with_version`], from the most recent.
+///
+/// Only released circuit versions are listed, each with its verifying keys in
+/// [`LEGACY_VERIFYING_KEYS`]. `v5.0.0` is the first circuit version, so there is none yet.
+#[cfg(feature = "legacy-verifiers")]
+pub const PREVIOUS_CIRCUIT_VERSIONS: &[&str] = &[];
+
+/// The verifying keys of the Plonk and Groth16 circuits of the [`PREVIOUS_CIRCUIT_VERSIONS`], as
+/// `(version, plonk_vk, groth16_vk)`.
+///
+/// When `MONEROCHAN_CIRCUIT_VERSION` is bumped, the keys of the released version, in
+/// `crates/verifier/bn254-vk`, are copied to `crates/sdk/legacy-vk/<version>` and included here
+/// with `include_bytes!`, so that the proofs of the version are verified offline.
+#[cfg(feature = "legacy-verifiers")]
+const LEGACY_VERIFYING_KEYS: &[(&str, &[u8], &[u8])] = &[];
+
+/// The async counterpart of [`Prover`], for proving from inside an async runtime.
+///
+/// [`Prover::prove`] blocks on the network requests of the network prover, which panics inside a
+/// single-threaded tokio runtime and ties up a worker thread otherwise. Only provers that talk to
+/// a remote service implement [`AsyncProver::prove_async`], natively. Local provers are CPU or
+/// GPU bound and do not implement it: [`crate::EnvProver`] proves on a dedicated thread when
+/// awaited, for any prover.
+///
+/// # Example
+/// ```rust,no_run
+/// use monerochan::{AsyncProver, Prover, ProverClient, MONEROCHANProofMode, MONEROCHANStdin};
+///
+/// tokio_test::block_on(async {
+///     let elf = &[1, 2, 3];
+///     let stdin = MONEROCHANStdin::new();
+///
+///     let client = ProverClient::builder().network().build();
+///     let (pk, vk) = client.setup(elf);
+///     let proof = client.prove_async(&pk, &stdin, MONEROCHANProofMode::Compressed).await.unwrap();
+/// });
+/// ```
+#[async_trait]
+pub trait AsyncProver<C: MONEROCHANProverComponents>: Prover<C> {
+    /// Proves the given program on the given input in the given proof mode.
+    async fn prove_async(
+        &self,
+        pk: &MONEROCHANProvingKey,
+        stdin: &MONEROCHANStdin,
+        mode: MONEROCHANProofMode,
+    ) -> Result<MONEROCHANProofWithPublicValues>;
+}
+
 /// An error that occurs when calling [`Prover::verify`].
+///
+/// The variants tell apart a proof for another program ([`Self::VkeyMismatch`]), tampered public
+/// values ([`Self::PublicValuesDigestMismatch`]) and a corrupted proof (the other variants, see
+/// [`Self::shard`] for core proofs).
 #[derive(Error, Debug)]
 pub enum MONEROCHANVerificationError {
     /// An error that occurs when the public values are invalid.
     #[error("Invalid public values")]
     InvalidPublicValues,
+    /// The digest of the public values committed in the proof does not match the public values
+    /// of the bundle, so the public values were tampered with.
+    #[error(
+        "Public values digest mismatch: the proof commits to {committed}, but the public values \
+         hash to {sha256} (SHA-256) and {blake3} (Blake3)"
+    )]
+    PublicValuesDigestMismatch {
+        /// The digest committed in the proof.
+        committed: String,
+        /// The SHA-256 digest of the public values.
+        sha256: String,
+        /// The Blake3 digest of the public values.
+        blake3: String,
+    },
+    /// The proof was generated for another program than the one of the verifying key.
+    ///
+    /// The hashes are in the encoding of the proof mode: hex for compressed proofs, and decimal
+    /// BN254 field elements for Plonk and Groth16 proofs.
+    #[error(
+        "Verifying key mismatch: expected a proof for {expected}, but the proof is for {}",
+        .actual.as_deref().unwrap_or("another program")
+    )]
+    VkeyMismatch {
+        /// The hash of the verifying key passed to [`Prover::verify`].
+        expected: String,
+        /// The hash of the verifying key the proof was generated for, if it can be recovered.
+        actual: Option<String>,
+    },
     /// An error that occurs when the MONEROCHAN version does not match the version of the circuit.
     #[error("Version mismatch")]
     VersionMismatch(String),
+    /// The proof was generated with a circuit version whose verifier is not available.
+    #[error("Unsupported circuit version {version}: {reason}")]
+    UnsupportedVersion {
+        /// The circuit version of the proof.
+        version: String,
+        /// Why the proof cannot be verified.
+        reason: &'static str,
+    },
     /// An error that occurs when the core machine verification fails.
     #[error("Core machine verification error: {0}")]
     Core(MachineVerificationError<CoreSC>),
@@ -86,6 +234,79 @@ pub enum MONEROCHANVerificationError {
     Other(anyhow::Error),
 }
 
+impl MONEROCHANVerificationError {
+    /// The index of the shard whose proof is invalid, for core proofs.
+    #[must_use]
+    pub fn shard(&self) -> Option<usize> {
+        match self {
+            Self::Core(MachineVerificationError::InvalidShardProof(shard, _)) => Some(*shard),
+            _ => None,
+        }
+    }
+}
+
+/// Checks the committed value digest of a core or compressed proof against the public values.
+fn check_committed_value_digest(
+    committed_value_digest: &[Word<BabyBear>],
+    public_values: &MONEROCHANPublicValues,
+) -> Result<(), MONEROCHANVerificationError> {
+    // Get the committed value digest bytes.
+    let committed_value_digest_bytes = committed_value_digest
+        .iter()
+        .flat_map(|w| w.0.iter().map(|x| x.as_canonical_u32() as u8))
+        .collect_vec();
+
+    // Make sure the committed value digest matches the public values hash.
+    // It is computationally infeasible to find two distinct inputs, one processed with
+    // SHA256 and the other with Blake3, that yield the same hash value.
+    let sha256 = public_values.hash();
+    let blake3 = public_values.blake3_hash();
+    if committed_value_digest_bytes != sha256 && committed_value_digest_bytes != blake3 {
+        return Err(MONEROCHANVerificationError::PublicValuesDigestMismatch {
+            committed: hex::encode(committed_value_digest_bytes),
+            sha256: hex::encode(sha256),
+            blake3: hex::encode(blake3),
+        });
+    }
+
+    Ok(())
+}
+
+/// Explains why the public inputs of a Plonk or Groth16 proof were rejected, if it is because of
+/// the verifying key or the public values rather than the proof itself.
+///
+/// Returns `None` if the public inputs are malformed, so that the caller reports its own error.
+pub(crate) fn diagnose_bn254_public_inputs(
+    vkey: &MONEROCHANVerifyingKey,
+    public_values: &MONEROCHANPublicValues,
+    public_inputs: &[String],
+) -> Option<MONEROCHANVerificationError> {
+    let (Some(vkey_input), Some(digest_input)) = (public_inputs.first(), public_inputs.get(1))
+    else {
+        return None;
+    };
+
+    let vk_hash = vkey.hash_bn254().as_canonical_biguint().to_string();
+    if *vkey_input != vk_hash {
+        return Some(MONEROCHANVerificationError::VkeyMismatch {
+            expected: vk_hash,
+            actual: Some(vkey_input.clone()),
+        });
+    }
+
+    let sha256 = public_values.hash_bn254().to_string();
+    let blake3 = public_values.hash_bn254_with_fn(blake3_hash).to_string();
+    if *digest_input != sha256 && *digest_input != blake3 {
+        return Some(MONEROCHANVerificationError::PublicValuesDigestMismatch {
+            committed: digest_input.clone(),
+            sha256,
+            blake3,
+        });
+    }
+
+    None
+}
+
 /// In MONEROCHAN, a proof's public values can either be hashed with SHA2 or Blake3. In MONEROCHAN V4, there is no
 /// metadata attached to the proof about which hasher function was used for public values hashing.
 /// Instead, when verifying the proof, the public values are hashed with SHA2 and Blake3, and
@@ -100,6 +321,7 @@ pub(crate) fn verify_proof<C: MONEROCHANProverComponents>(
     version: &str,
     bundle: &MONEROCHANProofWithPublicValues,
     vkey: &MONEROCHANVerifyingKey,
+    fri_parameters: Option<FriParameters>,
 ) -> Result<(), MONEROCHANVerificationError> {
     // Check that the MONEROCHAN version matches the version of the currentcircuit.
     if bundle.monerochan_version != version {
@@ -110,73 +332,128 @@ pub(crate) fn verify_proof<C: MONEROCHANProverComponents>(
         MONEROCHANProof::Core(proof) => {
             let public_values: &PublicValues<Word<_>, _> =
                 proof.last().unwrap().public_values.as_slice().borrow();
-
-            // Get the committed value digest bytes.
-            let committed_value_digest_bytes = public_values
-                .committed_value_digest
-                .iter()
-                .flat_map(|w| w.0.iter().map(|x| x.as_canonical_u32() as u8))
-                .collect_vec();
-
-            // Make sure the committed value digest matches the public values hash.
-            // It is computationally infeasible to find two distinct inputs, one processed with
-            // SHA256 and the other with Blake3, that yield the same hash value.
-            if committed_value_digest_bytes != bundle.public_values.hash() &&
-                committed_value_digest_bytes != bundle.public_values.blake3_hash()
-            {
-                return Err(MONEROCHANVerificationError::InvalidPublicValues);
-            }
+            check_committed_value_digest(
+                &public_values.committed_value_digest,
+                &bundle.public_values,
+            )?;
 
             // Verify the core proof.
-            prover
-                .verify(&MONEROCHANCoreProofData(proof.clone()), vkey)
-                .map_err(MONEROCHANVerificationError::Core)
+            let proof = MONEROCHANCoreProofData(proof.clone());
+            match fri_parameters {
+                Some(fri_parameters) => {
+                    prover.verify_with_fri_parameters(&proof, vkey, fri_parameters)
+                }
+                None => prover.verify(&proof, vkey),
+            }
+            .map_err(MONEROCHANVerificationError::Core)
         }
         MONEROCHANProof::Compressed(proof) => {
             let public_values: &PublicValues<Word<_>, _> =
                 proof.proof.public_values.as_slice().borrow();
+            check_committed_value_digest(
+                &public_values.committed_value_digest,
+                &bundle.public_values,
+            )?;
 
-            // Get the committed value digest bytes.
-            let committed_value_digest_bytes = public_values
-                .committed_value_digest
-                .iter()
-                .flat_map(|w| w.0.iter().map(|x| x.as_canonical_u32() as u8))
-                .collect_vec();
-
-            // Make sure the committed value digest matches the public values hash.
-            // It is computationally infeasible to find two distinct inputs, one processed with
-            // SHA256 and the other with Blake3, that yield the same hash value.
-            if committed_value_digest_bytes != bundle.public_values.hash() &&
-                committed_value_digest_bytes != bundle.public_values.blake3_hash()
-            {
-                return Err(MONEROCHANVerificationError::InvalidPublicValues);
+            match fri_parameters {
+                Some(fri_parameters) => {
+                    prover.verify_compressed_with_fri_parameters(proof, vkey, fri_parameters)
+                }
+                None => prover.verify_compressed(proof, vkey),
             }
-
-            prover.verify_compressed(proof, vkey).map_err(MONEROCHANVerificationError::Recursion)
+            .map_err(|err| match err {
+                MachineVerificationError::ProgramVkeyMismatch => {
+                    MONEROCHANVerificationError::VkeyMismatch {
+                        expected: vkey.bytes32(),
+                        actual: None,
+                    }
+                }
+                err => MONEROCHANVerificationError::Recursion(err),
+            })
         }
         MONEROCHANProof::Plonk(proof) => prover
             .verify_plonk_bn254(
                 proof,
                 vkey,
                 &bundle.public_values,
-                &if monerochan_prover::build::monerochan_dev_mode() {
+                &if prover.dev_mode() {
                     monerochan_prover::build::plonk_bn254_artifacts_dev_dir()
                 } else {
                     try_install_circuit_artifacts("plonk")
                 },
             )
-            .map_err(MONEROCHANVerificationError::Plonk),
+            .map_err(|err| {
+                diagnose_bn254_public_inputs(vkey, &bundle.public_values, &proof.public_inputs)
+                    .unwrap_or(MONEROCHANVerificationError::Plonk(err))
+            }),
         MONEROCHANProof::Groth16(proof) => prover
             .verify_groth16_bn254(
                 proof,
                 vkey,
                 &bundle.public_values,
-                &if monerochan_prover::build::monerochan_dev_mode() {
+                &if prover.dev_mode() {
                     monerochan_prover::build::groth16_bn254_artifacts_dev_dir()
                 } else {
                     try_install_circuit_artifacts("groth16")
                 },
             )
-            .map_err(MONEROCHANVerificationError::Groth16),
+            .map_err(|err| {
+                diagnose_bn254_public_inputs(vkey, &bundle.public_values, &proof.public_inputs)
+                    .unwrap_or(MONEROCHANVerificationError::Groth16(err))
+            }),
+    }
+}
+
+/// Verifies a Plonk or Groth16 proof generated with one of the [`PREVIOUS_CIRCUIT_VERSIONS`].
+#[cfg(feature = "legacy-verifiers")]
+fn verify_legacy_proof(
+    bundle: &MONEROCHANProofWithPublicValues,
+    vkey: &MONEROCHANVerifyingKey,
+    version: &str,
+) -> Result<(), MONEROCHANVerificationError> {
+    use monerochan_verifier::{Groth16Verifier, PlonkVerifier};
+
+    if bundle.monerochan_version != version {
+        return Err(MONEROCHANVerificationError::VersionMismatch(bundle.monerochan_version.clone()));
+    }
+    let Some(&(_, plonk_vk, groth16_vk)) =
+        LEGACY_VERIFYING_KEYS.iter().find(|(legacy_version, _, _)| *legacy_version == version)
+    else {
+        return Err(MONEROCHANVerificationError::UnsupportedVersion {
+            version: version.to_string(),
+            reason: "the verifier parameters of this version are not available",
+        });
+    };
+
+    let public_values = bundle.public_values.as_slice();
+    let vkey_hash = vkey.bytes32();
+    match &bundle.proof {
+        MONEROCHANProof::Plonk(_) => {
+            PlonkVerifier::verify(&bundle.bytes(), public_values, &vkey_hash, plonk_vk)
+                .map_err(|err| MONEROCHANVerificationError::Plonk(err.into()))
+        }
+        MONEROCHANProof::Groth16(_) => {
+            Groth16Verifier::verify(&bundle.bytes(), public_values, &vkey_hash, groth16_vk)
+                .map_err(|err| MONEROCHANVerificationError::Groth16(err.into()))
+        }
+        MONEROCHANProof::Core(_) | MONEROCHANProof::Compressed(_) => {
+            Err(MONEROCHANVerificationError::UnsupportedVersion {
+                version: version.to_string(),
+                reason: "only Plonk and Groth16 proofs of previous versions can be verified",
+            })
+        }
+    }
+}
+
+#[cfg(all(test, feature = "legacy-verifiers"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_verifying_keys() {
+        let versions: Vec<_> =
+            LEGACY_VERIFYING_KEYS.iter().map(|(version, _, _)| *version).collect();
+        assert_eq!(versions, PREVIOUS_CIRCUIT_VERSIONS);
+        assert!(!PREVIOUS_CIRCUIT_VERSIONS.contains(&crate::MONEROCHAN_CIRCUIT_VERSION));
     }
 }
@@ -3,6 +3,8 @@
 //! A collection of utilities for the MONEROCHAN SDK.
 
 use std::{
+    io::Write,
+    path::Path,
     sync::Once,
     thread::{sleep, spawn},
     time::Duration,
@@ -43,6 +45,66 @@ pub fn setup_memory_usage_monitoring() {
     });
 }
 
+/// Flushes and shuts down the OpenTelemetry exporter configured by [`init_telemetry`] when dropped.
+#[cfg(feature = "telemetry")]
+pub struct TelemetryGuard {
+    provider: opentelemetry_sdk::trace::TracerProvider,
+}
+
+#[cfg(feature = "telemetry")]
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        if let Err(err) = self.provider.shutdown() {
+            eprintln!("failed to shut down the OpenTelemetry exporter: {err}");
+        }
+    }
+}
+
+/// Configures `tracing` to export spans to an OpenTelemetry collector over OTLP, in addition to
+/// logging them to stderr.
+///
+/// The SDK spans (`setup`, `execute`, `prove`, `prove_core`, `compress`, `shrink`, `wrap`,
+/// `network_prove`, `network_submit` and `network_wait`) carry the request id, the vk hash, the
+/// proof mode and the cycle count, so that they can be correlated with the traces of the calling
+/// service.
+///
+/// The exporter is configured with the standard `OTEL_EXPORTER_OTLP_*` environment variables, and
+/// the spans are filtered with `RUST_LOG` (defaults to `info`). This function must be called from
+/// within a tokio runtime, and the returned guard must be kept alive for spans to be exported.
+///
+/// # Example
+/// ```rust,no_run
+/// #[tokio::main]
+/// async fn main() {
+///     let _telemetry = monerochan::utils::init_telemetry("my-service").unwrap();
+/// }
+/// ```
+#[cfg(feature = "telemetry")]
+pub fn init_telemetry(service_name: &str) -> anyhow::Result<TelemetryGuard> {
+    use opentelemetry::trace::TracerProvider as _;
+    use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder().with_tonic().build()?;
+    let provider = opentelemetry_sdk::trace::TracerProvider::builder()
+        .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
+        .with_resource(opentelemetry_sdk::Resource::new([opentelemetry::KeyValue::new(
+            "service.name",
+            service_name.to_string(),
+        )]))
+        .build();
+    let tracer = provider.tracer("monerochan");
+    opentelemetry::global::set_tracer_provider(provider.clone());
+
+    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    tracing_subscriber::registry()
+        .with(env_filter)
+        .with(tracing_opentelemetry::layer().with_tracer(tracer))
+        .with(tracing_subscriber::fmt::layer().compact().with_target(false))
+        .try_init()?;
+
+    Ok(TelemetryGuard { provider })
+}
+
 /// Dump the program and stdin to files for debugging if `MONEROCHAN_DUMP` is set.
 pub(crate) fn monerochan_dump(elf: &[u8], stdin: &MONEROCHANStdin) {
     if std::env::var("MONEROCHAN_DUMP").map(|v| v == "1" || v.to_lowercase() == "true").unwrap_or(false) {
@@ -74,6 +136,47 @@ pub(crate) fn block_on<T>(fut: impl std::future::Future<Output = T>) -> T {
     }
 }
 
+/// Runs the blocking `f` on a dedicated thread and awaits its result, so that proving never ties
+/// up the threads of the async runtime it is awaited from.
+///
+/// Unlike `tokio::task::spawn_blocking`, this works from any async runtime. A panic in `f` is
+/// resumed in the awaiting task.
+pub(crate) async fn spawn_blocking<T, F>(f: F) -> T
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (tx, rx) = futures::channel::oneshot::channel();
+    spawn(move || {
+        let _ = tx.send(std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)));
+    });
+    match rx.await.expect("the blocking thread always sends its result") {
+        Ok(value) => value,
+        Err(panic) => std::panic::resume_unwind(panic),
+    }
+}
+
+/// Writes a file through a temporary file, so that readers never see it partially written, even
+/// if the process is killed.
+///
+/// Each write has its own temporary file, so that concurrent writes of the same file do not
+/// interleave.
+pub(crate) fn write_atomic(path: &Path, bytes: &[u8]) -> anyhow::Result<()> {
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let mut tmp = tempfile::NamedTempFile::new_in(dir)?;
+    tmp.write_all(bytes)?;
+    tmp.persist(path)?;
+    Ok(())
+}
+
+/// The current time, in unix seconds.
+#[cfg(feature = "network")]
+pub(crate) fn unix_timestamp() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
 /// Check that MONEROCHAN SDK was built in release mode. Ensures that the prover and executor
 /// will be performant, which is important for benchmarking.
 pub(crate) fn check_release_build() {
@@ -0,0 +1,86 @@
+//! # Wasm Programs
+//!
+//! Helpers to prove WebAssembly programs with a guest built from `monerochan-wasm-guest`.
+//!
+//! The verifying key of the proof is the one of the interpreter guest, so the same key is used for
+//! every Wasm module. The digest of the module is committed to the public values, and verifiers
+//! must check it against [`module_hash`] of the module they expect.
+
+use anyhow::Result;
+use monerochan_core_machine::io::MONEROCHANStdin;
+use monerochan_primitives::io::MONEROCHANPublicValues;
+use sha2::{Digest, Sha256};
+
+/// The public values committed by the Wasm guest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WasmPublicValues {
+    /// The SHA-256 digest of the Wasm module that was run.
+    pub module_hash: [u8; 32],
+    /// The bytes committed by the Wasm module.
+    pub output: Vec<u8>,
+}
+
+impl WasmPublicValues {
+    /// Decodes the public values committed by the Wasm guest.
+    ///
+    /// # Panics
+    /// Panics if the public values were not committed by the Wasm guest.
+    #[must_use]
+    pub fn from_public_values(public_values: &MONEROCHANPublicValues) -> Self {
+        let mut public_values = public_values.clone();
+        let mut module_hash = [0u8; 32];
+        public_values.read_slice(&mut module_hash);
+        let output = public_values.read_framed();
+        Self { module_hash, output }
+    }
+}
+
+/// The SHA-256 digest of a Wasm module, as committed by the Wasm guest.
+#[must_use]
+pub fn module_hash(wasm_bytes: &[u8]) -> [u8; 32] {
+    Sha256::digest(wasm_bytes).into()
+}
+
+/// Builds the stdin of the Wasm guest to run a Wasm module on the given input.
+#[must_use]
+pub fn wasm_stdin(wasm_bytes: &[u8], input: &[u8]) -> MONEROCHANStdin {
+    let mut stdin = MONEROCHANStdin::new();
+    stdin.write_vec(wasm_bytes.to_vec());
+    stdin.write_vec(input.to_vec());
+    stdin
+}
+
+/// Checks that the public values were committed by the Wasm guest for the given module, and
+/// returns the output of the module.
+pub fn verify_module(public_values: &MONEROCHANPublicValues, wasm_bytes: &[u8]) -> Result<Vec<u8>> {
+    let WasmPublicValues { module_hash: committed, output } =
+        WasmPublicValues::from_public_values(public_values);
+    let expected = module_hash(wasm_bytes);
+    anyhow::ensure!(
+        committed == expected,
+        "the proof is for the wasm module 0x{}, expected 0x{}",
+        hex::encode(committed),
+        hex::encode(expected)
+    );
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wasm_public_values() {
+        let wasm = b"\0asm\x01\0\0\0";
+        let mut public_values = MONEROCHANPublicValues::new();
+        public_values.write_slice(&module_hash(wasm));
+        public_values.write_framed(b"output");
+
+        let decoded = WasmPublicValues::from_public_values(&public_values);
+        assert_eq!(decoded.module_hash, module_hash(wasm));
+        assert_eq!(decoded.output, b"output");
+
+        assert_eq!(verify_module(&public_values, wasm).unwrap(), b"output");
+        assert!(verify_module(&public_values, b"other").is_err());
+    }
+}
@@ -0,0 +1,226 @@
+//! A pool of reusable buffers for traces.
+//!
+//! The prover generates and commits to the traces of hundreds of shards of similar sizes. Giving
+//! each of them a fresh allocation pays for the page faults of every shard again, and fragments
+//! the heap so that the peak memory grows beyond the working set. Trace buffers can instead be
+//! taken from this pool and given back to it once the shard they belong to is proven.
+//!
+//! The pool is off by default, as its benefit has not been measured yet: setting
+//! `TRACE_ARENA_SIZE` enables it, and it then keeps at most that many bytes of buffers, capped to
+//! an eighth of the [available memory](crate::resources::available_memory), which accounts for
+//! the cgroup limit of the process. Only vectors of 32-bit field elements are pooled.
+//!
+//! Buffers are only kept while a proof holds a [`session`], and the pool is freed once the last
+//! of the concurrent sessions ends, so that a finished proof never frees the buffers of another
+//! one that is still running.
+
+use std::{
+    env,
+    mem::{align_of, size_of, ManuallyDrop},
+    sync::{Mutex, OnceLock},
+};
+
+use p3_field::Field;
+
+use crate::resources;
+
+/// The default maximum number of bytes of the buffers kept in the pool, which disables it.
+pub const DEFAULT_TRACE_ARENA_SIZE: usize = 0;
+
+/// The buffers of the pool, their total size in bytes, and the number of open sessions.
+struct Pool {
+    buffers: Vec<Vec<u32>>,
+    size: usize,
+    max_size: usize,
+    sessions: usize,
+}
+
+impl Pool {
+    const fn new(max_size: usize) -> Self {
+        Self { buffers: Vec::new(), size: 0, max_size, sessions: 0 }
+    }
+
+    /// Takes an empty buffer with room for `len` elements, if there is one.
+    ///
+    /// Only buffers at most twice as large as needed are taken, so that small traces do not hold
+    /// on to the buffers of the largest chips.
+    fn take(&mut self, len: usize) -> Option<Vec<u32>> {
+        let best = self
+            .buffers
+            .iter()
+            .enumerate()
+            .filter(|(_, buffer)| buffer.capacity() >= len && buffer.capacity() <= 2 * len)
+            .min_by_key(|(_, buffer)| buffer.capacity())
+            .map(|(i, _)| i);
+        let mut buffer = self.buffers.swap_remove(best?);
+        self.size -= buffer.capacity() * size_of::<u32>();
+        buffer.clear();
+        Some(buffer)
+    }
+
+    /// Keeps a buffer if it fits within the maximum size of the pool, and drops it otherwise.
+    fn put(&mut self, buffer: Vec<u32>) {
+        let bytes = buffer.capacity() * size_of::<u32>();
+        if self.size + bytes <= self.max_size {
+            self.size += bytes;
+            self.buffers.push(buffer);
+        }
+    }
+
+    /// Drops all the buffers.
+    fn clear(&mut self) {
+        self.buffers = Vec::new();
+        self.size = 0;
+    }
+}
+
+fn pool() -> &'static Mutex<Pool> {
+    static POOL: OnceLock<Mutex<Pool>> = OnceLock::new();
+    POOL.get_or_init(|| {
+        let max_size = env::var("TRACE_ARENA_SIZE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_TRACE_ARENA_SIZE);
+        let cap = usize::try_from(resources::available_memory() / 8).unwrap_or(usize::MAX);
+        Mutex::new(Pool::new(max_size.min(cap)))
+    })
+}
+
+/// Whether vectors of `F` can be stored in the pool.
+const fn is_pooled<F>() -> bool {
+    size_of::<F>() == size_of::<u32>() && align_of::<F>() == align_of::<u32>()
+}
+
+/// Reinterprets a vector as a vector of another type of the same size and alignment.
+///
+/// # Safety
+///
+/// Every bit pattern of the elements of `values` must be a valid `U`.
+unsafe fn cast_vec<T: Copy, U: Copy>(values: Vec<T>) -> Vec<U> {
+    debug_assert!(size_of::<T>() == size_of::<U>() && align_of::<T>() == align_of::<U>());
+    let mut values = ManuallyDrop::new(values);
+    Vec::from_raw_parts(values.as_mut_ptr().cast(), values.len(), values.capacity())
+}
+
+/// Returns a vector of `len` zeroes, in a buffer of the pool if one fits.
+///
+/// The zero of `F` must be represented by zero bits, as it is for `BabyBear`.
+#[must_use]
+pub fn zeroed_vec<F: Field>(len: usize) -> Vec<F> {
+    debug_assert!(is_pooled::<F>());
+    let buffer = match pool().lock().unwrap().take(len) {
+        Some(mut buffer) => {
+            buffer.resize(len, 0);
+            buffer
+        }
+        // Fresh zeroed allocations are cheaper than writing the zeroes.
+        None => vec![0; len],
+    };
+    // SAFETY: `F` is 32 bits wide and zero bits are its zero.
+    unsafe { cast_vec(buffer) }
+}
+
+/// Gives a vector back to the pool, to be reused by the next traces.
+///
+/// The vector is dropped instead if it is not made of 32-bit field elements, if the pool is
+/// disabled or full, or if no [`session`] is open.
+pub fn recycle<F: Field>(values: Vec<F>) {
+    if !is_pooled::<F>() || values.capacity() == 0 {
+        return;
+    }
+    let mut pool = pool().lock().unwrap();
+    if pool.max_size == 0 || pool.sessions == 0 {
+        return;
+    }
+    // SAFETY: `F` is 32 bits wide, and every bit pattern is a valid `u32`.
+    pool.put(unsafe { cast_vec(values) });
+}
+
+/// Opens a session of the pool for the duration of a proof.
+///
+/// The pool only keeps buffers while a session is open, and frees them when the last open session
+/// is dropped.
+#[must_use = "the session ends when it is dropped"]
+pub fn session() -> Session {
+    pool().lock().unwrap().sessions += 1;
+    Session { _private: () }
+}
+
+/// A session of the pool, see [`session`].
+#[derive(Debug)]
+pub struct Session {
+    _private: (),
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        let mut pool = pool().lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        pool.sessions -= 1;
+        if pool.sessions == 0 {
+            pool.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::AbstractField;
+
+    use super::*;
+
+    #[test]
+    fn test_reused_buffers_are_zeroed() {
+        // The pool is disabled unless `TRACE_ARENA_SIZE` is set.
+        pool().lock().unwrap().max_size = 1 << 20;
+        let len = 12_345;
+
+        let first = session();
+        let values = vec![BabyBear::one(); len];
+        let ptr = values.as_ptr();
+        recycle(values);
+        let reused = zeroed_vec::<BabyBear>(len);
+        assert_eq!(reused.as_ptr(), ptr);
+        assert!(reused.iter().all(|x| *x == BabyBear::zero()));
+
+        // The buffers are kept until the last session ends.
+        let other = session();
+        recycle(reused);
+        drop(first);
+        assert_eq!(pool().lock().unwrap().size, len * size_of::<u32>());
+        drop(other);
+        assert_eq!(pool().lock().unwrap().size, 0);
+
+        // Without a session, recycled buffers are dropped.
+        recycle(vec![BabyBear::one(); len]);
+        assert_eq!(pool().lock().unwrap().size, 0);
+    }
+
+    #[test]
+    fn test_pool() {
+        let mut pool = Pool::new(1500);
+        pool.put(vec![1; 100]);
+        pool.put(vec![1; 200]);
+        // Beyond the maximum size of the pool.
+        pool.put(vec![1; 100]);
+        assert_eq!(pool.size, 1200);
+        assert_eq!(pool.buffers.len(), 2);
+
+        // Buffers more than twice as large as needed are left in the pool.
+        assert_eq!(pool.take(90).unwrap().capacity(), 100);
+        assert!(pool.take(300).is_none());
+        assert!(pool.take(50).is_none());
+        let buffer = pool.take(150).unwrap();
+        assert!(buffer.is_empty() && buffer.capacity() == 200);
+        assert_eq!(pool.size, 0);
+
+        pool.put(buffer);
+        pool.clear();
+        assert!(pool.buffers.is_empty());
+        assert_eq!(pool.size, 0);
+
+        let mut disabled = Pool::new(0);
+        disabled.put(vec![1; 10]);
+        assert!(disabled.buffers.is_empty());
+    }
+}
@@ -164,7 +164,7 @@ pub mod baby_bear_poseidon2 {
     use serde::{Deserialize, Serialize};
     use monerochan_primitives::RC_16_30;
 
-    use crate::{Com, StarkGenericConfig, ZeroCommitment, DIGEST_SIZE};
+    use crate::{Com, FriParameters, StarkGenericConfig, ZeroCommitment, DIGEST_SIZE};
 
     pub type Val = BabyBear;
     pub type Challenge = BinomialExtensionField<Val, 4>;
@@ -209,15 +209,30 @@ pub mod baby_bear_poseidon2 {
 
     #[must_use]
     pub fn default_fri_config() -> FriConfig<ChallengeMmcs> {
-        let perm = my_perm();
-        let hash = MyHash::new(perm.clone());
-        let compress = MyCompress::new(perm.clone());
-        let challenge_mmcs = ChallengeMmcs::new(ValMmcs::new(hash, compress));
         let num_queries = match std::env::var("FRI_QUERIES") {
             Ok(value) => value.parse().unwrap(),
             Err(_) => 100,
         };
-        FriConfig { log_blowup: 1, num_queries, proof_of_work_bits: 16, mmcs: challenge_mmcs }
+        fri_config_with_parameters(FriParameters {
+            log_blowup: 1,
+            num_queries,
+            proof_of_work_bits: 16,
+        })
+    }
+
+    /// A FRI config with the given parameters.
+    #[must_use]
+    pub fn fri_config_with_parameters(parameters: FriParameters) -> FriConfig<ChallengeMmcs> {
+        let perm = my_perm();
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm.clone());
+        let challenge_mmcs = ChallengeMmcs::new(ValMmcs::new(hash, compress));
+        FriConfig {
+            log_blowup: parameters.log_blowup,
+            num_queries: parameters.num_queries,
+            proof_of_work_bits: parameters.proof_of_work_bits,
+            mmcs: challenge_mmcs,
+        }
     }
 
     #[must_use]
@@ -246,9 +261,18 @@ pub mod baby_bear_poseidon2 {
         FriConfig { log_blowup: 3, num_queries, proof_of_work_bits: 16, mmcs: challenge_mmcs }
     }
 
+    fn fri_parameters(fri_config: &FriConfig<ChallengeMmcs>) -> FriParameters {
+        FriParameters {
+            log_blowup: fri_config.log_blowup,
+            num_queries: fri_config.num_queries,
+            proof_of_work_bits: fri_config.proof_of_work_bits,
+        }
+    }
+
     enum BabyBearPoseidon2Type {
         Default,
         Compressed,
+        WithParameters(FriParameters),
     }
 
     #[derive(Deserialize)]
@@ -257,6 +281,7 @@ pub mod baby_bear_poseidon2 {
         pub perm: Perm,
         pcs: Pcs,
         config_type: BabyBearPoseidon2Type,
+        fri_parameters: FriParameters,
     }
 
     impl BabyBearPoseidon2 {
@@ -268,8 +293,33 @@ pub mod baby_bear_poseidon2 {
             let val_mmcs = ValMmcs::new(hash, compress);
             let dft = Dft {};
             let fri_config = default_fri_config();
+            let fri_parameters = fri_parameters(&fri_config);
+            let pcs = Pcs::new(27, dft, val_mmcs, fri_config);
+            Self { pcs, perm, config_type: BabyBearPoseidon2Type::Default, fri_parameters }
+        }
+
+        /// The default config, with the given FRI parameters instead of the default ones.
+        #[must_use]
+        pub fn new_with_fri_parameters(fri_parameters: FriParameters) -> Self {
+            let perm = my_perm();
+            let hash = MyHash::new(perm.clone());
+            let compress = MyCompress::new(perm.clone());
+            let val_mmcs = ValMmcs::new(hash, compress);
+            let dft = Dft {};
+            let fri_config = fri_config_with_parameters(fri_parameters);
             let pcs = Pcs::new(27, dft, val_mmcs, fri_config);
-            Self { pcs, perm, config_type: BabyBearPoseidon2Type::Default }
+            Self {
+                pcs,
+                perm,
+                config_type: BabyBearPoseidon2Type::WithParameters(fri_parameters),
+                fri_parameters,
+            }
+        }
+
+        /// The parameters of the FRI config.
+        #[must_use]
+        pub fn fri_parameters(&self) -> FriParameters {
+            self.fri_parameters
         }
 
         #[must_use]
@@ -280,8 +330,9 @@ pub mod baby_bear_poseidon2 {
             let val_mmcs = ValMmcs::new(hash, compress);
             let dft = Dft {};
             let fri_config = compressed_fri_config();
+            let fri_parameters = fri_parameters(&fri_config);
             let pcs = Pcs::new(27, dft, val_mmcs, fri_config);
-            Self { pcs, perm, config_type: BabyBearPoseidon2Type::Compressed }
+            Self { pcs, perm, config_type: BabyBearPoseidon2Type::Compressed, fri_parameters }
         }
 
         #[must_use]
@@ -292,8 +343,9 @@ pub mod baby_bear_poseidon2 {
             let val_mmcs = ValMmcs::new(hash, compress);
             let dft = Dft {};
             let fri_config = ultra_compressed_fri_config();
+            let fri_parameters = fri_parameters(&fri_config);
             let pcs = Pcs::new(27, dft, val_mmcs, fri_config);
-            Self { pcs, perm, config_type: BabyBearPoseidon2Type::Compressed }
+            Self { pcs, perm, config_type: BabyBearPoseidon2Type::Compressed, fri_parameters }
         }
     }
 
@@ -302,6 +354,9 @@ pub mod baby_bear_poseidon2 {
             match self.config_type {
                 BabyBearPoseidon2Type::Default => Self::new(),
                 BabyBearPoseidon2Type::Compressed => Self::compressed(),
+                BabyBearPoseidon2Type::WithParameters(fri_parameters) => {
+                    Self::new_with_fri_parameters(fri_parameters)
+                }
             }
         }
     }
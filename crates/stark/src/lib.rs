@@ -21,6 +21,7 @@
 #![warn(missing_docs)]
 
 pub mod air;
+pub mod arena;
 mod bb31_poseidon2;
 mod chip;
 mod config;
@@ -33,6 +34,7 @@ mod permutation;
 mod prover;
 mod quotient;
 mod record;
+pub mod resources;
 pub mod septic_curve;
 pub mod septic_digest;
 pub mod septic_extension;
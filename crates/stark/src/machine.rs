@@ -21,7 +21,7 @@ use crate::{
     count_permutation_constraints,
     lookup::{debug_interactions_with_all_chips, InteractionKind},
     record::MachineRecord,
-    DebugConstraintBuilder, ShardProof, VerifierConstraintFolder,
+    DebugConstraintBuilder, FriParametersError, ShardProof, VerifierConstraintFolder,
 };
 
 use super::{
@@ -531,7 +531,7 @@ impl<SC: StarkGenericConfig, A: MachineAir<Val<SC>> + Air<SymbolicAirBuilder<Val
                         &mut shard_challenger,
                         shard_proof,
                     )
-                    .map_err(MachineVerificationError::InvalidShardProof)
+                    .map_err(|e| MachineVerificationError::InvalidShardProof(i, e))
                 })?;
             }
 
@@ -561,8 +561,8 @@ impl<SC: StarkGenericConfig, A: MachineAir<Val<SC>> + Air<SymbolicAirBuilder<Val
 
 /// Errors that can occur during machine verification.
 pub enum MachineVerificationError<SC: StarkGenericConfig> {
-    /// An error occurred during the verification of a shard proof.
-    InvalidShardProof(VerificationError<SC>),
+    /// An error occurred during the verification of the shard proof at the given index.
+    InvalidShardProof(usize, VerificationError<SC>),
     /// An error occurred during the verification of a global proof.
     InvalidGlobalProof(VerificationError<SC>),
     /// The cumulative sum is non-zero.
@@ -585,14 +585,18 @@ pub enum MachineVerificationError<SC: StarkGenericConfig> {
     CpuLogDegreeTooLarge(usize),
     /// The verification key is not allowed.
     InvalidVerificationKey,
+    /// The FRI parameters of the proof are not allowed.
+    InvalidFriParameters(FriParametersError),
+    /// The proof was generated for another program than the one of the verifying key.
+    ProgramVkeyMismatch,
 }
 
 impl<SC: StarkGenericConfig> Debug for MachineVerificationError<SC> {
     #[allow(clippy::uninlined_format_args)]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            MachineVerificationError::InvalidShardProof(e) => {
-                write!(f, "Invalid shard proof: {:?}", e)
+            MachineVerificationError::InvalidShardProof(shard, e) => {
+                write!(f, "Invalid shard proof at shard {}: {:?}", shard, e)
             }
             MachineVerificationError::InvalidGlobalProof(e) => {
                 write!(f, "Invalid global proof: {:?}", e)
@@ -627,6 +631,12 @@ impl<SC: StarkGenericConfig> Debug for MachineVerificationError<SC> {
             MachineVerificationError::InvalidVerificationKey => {
                 write!(f, "Invalid verification key")
             }
+            MachineVerificationError::InvalidFriParameters(e) => {
+                write!(f, "Invalid FRI parameters: {}", e)
+            }
+            MachineVerificationError::ProgramVkeyMismatch => {
+                write!(f, "Invalid public values: monerochan vk hash mismatch")
+            }
         }
     }
 }
@@ -643,6 +653,7 @@ impl<SC: StarkGenericConfig> MachineVerificationError<SC> {
     /// This function will check if the verification error is from constraints failing.
     pub fn is_constraints_failing(&self, expected_chip_name: &str) -> bool {
         if let MachineVerificationError::InvalidShardProof(
+            _,
             VerificationError::OodEvaluationMismatch(chip_name),
         ) = self
         {
@@ -656,9 +667,10 @@ impl<SC: StarkGenericConfig> MachineVerificationError<SC> {
     pub fn is_local_cumulative_sum_failing(&self) -> bool {
         matches!(
             self,
-            MachineVerificationError::InvalidShardProof(VerificationError::CumulativeSumsError(
-                "local cumulative sum is not zero"
-            ))
+            MachineVerificationError::InvalidShardProof(
+                _,
+                VerificationError::CumulativeSumsError("local cumulative sum is not zero")
+            )
         )
     }
 }
@@ -1,7 +1,7 @@
 use std::env;
 
 use serde::{Deserialize, Serialize};
-use sysinfo::System;
+use crate::resources;
 
 const MAX_SHARD_SIZE: usize = 1 << 21;
 const RECURSION_MAX_SHARD_SIZE: usize = 1 << 22;
@@ -11,6 +11,10 @@ const DEFAULT_CHECKPOINTS_CHANNEL_CAPACITY: usize = 128;
 const DEFAULT_RECORDS_AND_TRACES_CHANNEL_CAPACITY: usize = 1;
 const MAX_DEFERRED_SPLIT_THRESHOLD: usize = 1 << 15;
 
+/// The minimum conjectured security, in bits, of the FRI parameters accepted by
+/// [`FriParameters::validate`].
+pub const MIN_CONJECTURED_SECURITY_BITS: usize = 100;
+
 /// Options to configure the MONEROCHAN prover for core and recursive proofs.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct MONEROCHANProverOpts {
@@ -21,11 +25,11 @@ pub struct MONEROCHANProverOpts {
 }
 
 impl MONEROCHANProverOpts {
-    /// Get the default prover options.
+    /// Get the default prover options, sized from the memory available to the process, which is
+    /// capped by the limit of its cgroup.
     #[must_use]
     pub fn auto() -> Self {
-        let cpu_ram_gb = System::new_all().total_memory() / (1024 * 1024 * 1024);
-        MONEROCHANProverOpts::cpu(cpu_ram_gb as usize)
+        MONEROCHANProverOpts::cpu(resources::available_memory_gb())
     }
 
     /// Get the memory options (shard size, shard batch size, and divisor) for a prover on CPU based
@@ -114,8 +118,31 @@ pub struct MONEROCHANCoreOpts {
     pub trace_gen_workers: usize,
     /// The capacity of the channel for checkpoints.
     pub checkpoints_channel_capacity: usize,
-    /// The capacity of the channel for records and traces.
+    /// The capacity of the channel for records and traces, in batches of shards.
     pub records_and_traces_channel_capacity: usize,
+    /// The number of FRI queries of the proofs, or `None` for the default of the prover.
+    ///
+    /// Fewer queries give smaller proofs that are faster to verify, at the cost of security; see
+    /// [`FriParameters`]. Only the number of queries can be set per proof, since the verifying keys
+    /// commit to the traces extended with the blowup of the prover: the blowup is set when the
+    /// prover is created, before the setup of the programs. The verifier must be given the same
+    /// parameters, since it rejects the proofs with other parameters than its own.
+    #[serde(default)]
+    pub fri_queries: Option<usize>,
+    /// The seed to derive the randomness of the prover from, for reproducible proofs.
+    ///
+    /// If set, proving the same inputs with the same seed gives byte-identical proofs. The STARK
+    /// provers draw no randomness, but the opening of a shard, which searches the FRI proof of
+    /// work, then runs on a single thread so that it finds the same witness. The shards of a
+    /// batch are still opened concurrently, so this mostly slows down the proofs with fewer
+    /// shards than threads. The seed itself derives the blinding of the Plonk and Groth16 proofs.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// The NUMA node to pin the prover threads to, or `None` to let them run on any CPU.
+    ///
+    /// See [`resources::numa_thread_pool`]. Defaults to the `NUMA_NODE` environment variable.
+    #[serde(default)]
+    pub numa_node: Option<usize>,
 }
 
 impl Default for MONEROCHANProverOpts {
@@ -126,9 +153,8 @@ impl Default for MONEROCHANProverOpts {
 
 impl Default for MONEROCHANCoreOpts {
     fn default() -> Self {
-        let cpu_ram_gb = System::new_all().total_memory() / (1024 * 1024 * 1024);
         let (default_log2_shard_size, default_shard_batch_size, default_log2_divisor) =
-            MONEROCHANProverOpts::get_memory_opts(cpu_ram_gb as usize);
+            MONEROCHANProverOpts::get_memory_opts(resources::available_memory_gb());
 
         let mut opts = Self {
             shard_size: env::var("SHARD_SIZE").map_or_else(
@@ -153,6 +179,9 @@ impl Default for MONEROCHANCoreOpts {
                     |_| DEFAULT_RECORDS_AND_TRACES_CHANNEL_CAPACITY,
                     |s| s.parse::<usize>().unwrap_or(DEFAULT_RECORDS_AND_TRACES_CHANNEL_CAPACITY),
                 ),
+            fri_queries: None,
+            seed: None,
+            numa_node: env::var("NUMA_NODE").ok().and_then(|s| s.parse().ok()),
         };
 
         let divisor = 1 << default_log2_divisor;
@@ -207,10 +236,89 @@ impl MONEROCHANCoreOpts {
                     |_| DEFAULT_RECORDS_AND_TRACES_CHANNEL_CAPACITY,
                     |s| s.parse::<usize>().unwrap_or(DEFAULT_RECORDS_AND_TRACES_CHANNEL_CAPACITY),
                 ),
+            fri_queries: None,
+            seed: None,
+            numa_node: env::var("NUMA_NODE").ok().and_then(|s| s.parse().ok()),
+        }
+    }
+}
+
+/// The parameters of the FRI low-degree test of a proof, which trade its size and verification
+/// time for its security.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FriParameters {
+    /// The log of the blowup factor of the traces.
+    pub log_blowup: usize,
+    /// The number of queries.
+    pub num_queries: usize,
+    /// The number of bits of the proof of work grinding.
+    pub proof_of_work_bits: usize,
+}
+
+impl FriParameters {
+    /// The conjectured security of a proof with these parameters, in bits: every query adds
+    /// `log_blowup` bits, and the proof of work adds `proof_of_work_bits` bits.
+    #[must_use]
+    pub const fn conjectured_security_bits(&self) -> usize {
+        self.log_blowup * self.num_queries + self.proof_of_work_bits
+    }
+
+    /// Checks that the parameters give at least [`MIN_CONJECTURED_SECURITY_BITS`] bits of
+    /// conjectured security.
+    pub fn validate(&self) -> Result<(), FriParametersError> {
+        if self.log_blowup == 0 {
+            return Err(FriParametersError::ZeroBlowup);
         }
+        let security_bits = self.conjectured_security_bits();
+        if security_bits < MIN_CONJECTURED_SECURITY_BITS {
+            return Err(FriParametersError::InsufficientSecurity(security_bits));
+        }
+        Ok(())
     }
 }
 
+/// An error returned by [`FriParameters::validate`], or by a verifier given a proof with other
+/// FRI parameters than its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FriParametersError {
+    /// The blowup factor is one, which cannot hold the quotients of the constraints.
+    ZeroBlowup,
+    /// The conjectured security, in bits, is lower than [`MIN_CONJECTURED_SECURITY_BITS`].
+    InsufficientSecurity(usize),
+    /// The proof was generated with other FRI parameters than the ones of the verifier, or with
+    /// different parameters across its shards.
+    Mismatch {
+        /// The parameters of the verifier.
+        expected: FriParameters,
+        /// The parameters of the proof, if all its shards have the same.
+        actual: Option<FriParameters>,
+    },
+}
+
+impl std::fmt::Display for FriParametersError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ZeroBlowup => write!(f, "the log blowup must be at least 1"),
+            Self::InsufficientSecurity(bits) => write!(
+                f,
+                "the FRI parameters give {bits} bits of conjectured security, but at least \
+                 {MIN_CONJECTURED_SECURITY_BITS} are required"
+            ),
+            Self::Mismatch { expected, actual: Some(actual) } => write!(
+                f,
+                "the proof has a log blowup of {} and {} queries, but the verifier expects a log \
+                 blowup of {} and {} queries",
+                actual.log_blowup, actual.num_queries, expected.log_blowup, expected.num_queries
+            ),
+            Self::Mismatch { .. } => {
+                write!(f, "the shards of the proof have different FRI parameters")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FriParametersError {}
+
 /// Options for splitting deferred events.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SplitOpts {
@@ -285,4 +393,20 @@ mod tests {
         let opts = MONEROCHANProverOpts::auto();
         println!("auto: {:?}", opts.core_opts);
     }
+
+    #[test]
+    fn test_fri_parameters_validate() {
+        let params = FriParameters { log_blowup: 1, num_queries: 100, proof_of_work_bits: 16 };
+        assert_eq!(params.conjectured_security_bits(), 116);
+        assert_eq!(params.validate(), Ok(()));
+
+        let params = FriParameters { num_queries: 84, ..params };
+        assert_eq!(params.validate(), Ok(()));
+
+        let params = FriParameters { num_queries: 83, ..params };
+        assert_eq!(params.validate(), Err(FriParametersError::InsufficientSecurity(99)));
+
+        let params = FriParameters { log_blowup: 0, ..params };
+        assert_eq!(params.validate(), Err(FriParametersError::ZeroBlowup));
+    }
 }
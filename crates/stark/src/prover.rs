@@ -1,5 +1,5 @@
 use crate::{
-    air::InteractionScope, septic_curve::SepticCurve, septic_digest::SepticDigest,
+    air::InteractionScope, arena, septic_curve::SepticCurve, septic_digest::SepticDigest,
     septic_extension::SepticExtension, AirOpenedValues, ChipOpenedValues, ShardOpenedValues,
 };
 use core::fmt::Display;
@@ -118,6 +118,20 @@ pub trait MachineProver<SC: StarkGenericConfig, A: MachineAir<SC::Val>>:
         challenger: &mut SC::Challenger,
     ) -> Result<ShardProof<SC>, Self::Error>;
 
+    /// Compute the openings of the traces like [`MachineProver::open`], but with a proof that does
+    /// not depend on the scheduling of the prover threads, for reproducible proofs.
+    ///
+    /// The default implementation calls [`MachineProver::open`], which is enough for provers whose
+    /// openings are already deterministic.
+    fn open_deterministic(
+        &self,
+        pk: &Self::DeviceProvingKey,
+        data: ShardMainData<SC, Self::DeviceMatrix, Self::DeviceProverData>,
+        challenger: &mut SC::Challenger,
+    ) -> Result<ShardProof<SC>, Self::Error> {
+        self.open(pk, data, challenger)
+    }
+
     /// Generate a proof for the given records.
     fn prove(
         &self,
@@ -180,6 +194,17 @@ pub trait MachineProvingKey<SC: StarkGenericConfig>: Send + Sync {
     fn observe_into(&self, challenger: &mut Challenger<SC>);
 }
 
+thread_local! {
+    /// The single-threaded pool the deterministic openings of a thread run in.
+    ///
+    /// Every thread that opens shards has its own pool, so that the shards of a batch, which are
+    /// opened on the threads of the global pool, are still opened concurrently.
+    static DETERMINISTIC_OPENING_POOL: rayon::ThreadPool = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .expect("failed to build the opening thread pool");
+}
+
 /// A prover implementation based on x86 and ARM CPUs.
 pub struct CpuProver<SC: StarkGenericConfig, A> {
     machine: StarkMachine<SC, A>,
@@ -202,7 +227,7 @@ where
     Com<SC>: Send + Sync,
     PcsProverData<SC>: Send + Sync + Serialize + DeserializeOwned,
     OpeningProof<SC>: Send + Sync,
-    SC::Challenger: Clone,
+    SC::Challenger: Clone + Send,
 {
     type DeviceMatrix = RowMajorMatrix<Val<SC>>;
     type DeviceProverData = PcsProverData<SC>;
@@ -273,16 +298,93 @@ where
         }
     }
 
+    fn open(
+        &self,
+        pk: &StarkProvingKey<SC>,
+        data: ShardMainData<SC, Self::DeviceMatrix, Self::DeviceProverData>,
+        challenger: &mut <SC as StarkGenericConfig>::Challenger,
+    ) -> Result<ShardProof<SC>, Self::Error> {
+        self.open_shard(pk, data, challenger, false)
+    }
+
+    fn open_deterministic(
+        &self,
+        pk: &StarkProvingKey<SC>,
+        data: ShardMainData<SC, Self::DeviceMatrix, Self::DeviceProverData>,
+        challenger: &mut <SC as StarkGenericConfig>::Challenger,
+    ) -> Result<ShardProof<SC>, Self::Error> {
+        self.open_shard(pk, data, challenger, true)
+    }
+
+    /// Prove the execution record is valid.
+    ///
+    /// Given a proving key `pk` and a matching execution record `record`, this function generates
+    /// a STARK proof that the execution record is valid.
+    #[allow(clippy::needless_for_each)]
+    fn prove(
+        &self,
+        pk: &StarkProvingKey<SC>,
+        mut records: Vec<A::Record>,
+        challenger: &mut SC::Challenger,
+        opts: <A::Record as MachineRecord>::Config,
+    ) -> Result<MachineProof<SC>, Self::Error>
+    where
+        A: for<'a> Air<DebugConstraintBuilder<'a, Val<SC>, SC::Challenge>>,
+    {
+        // Generate dependencies.
+        self.machine().generate_dependencies(&mut records, &opts, None);
+
+        // Observe the preprocessed commitment.
+        pk.observe_into(challenger);
+
+        let shard_proofs = tracing::info_span!("prove_shards").in_scope(|| {
+            records
+                .into_par_iter()
+                .map(|record| {
+                    let named_traces = self.generate_traces(&record);
+                    let shard_data = self.commit(&record, named_traces);
+                    if opts.seed.is_some() {
+                        self.open_deterministic(pk, shard_data, &mut challenger.clone())
+                    } else {
+                        self.open(pk, shard_data, &mut challenger.clone())
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })?;
+
+        Ok(MachineProof { shard_proofs })
+    }
+}
+
+impl<SC, A> CpuProver<SC, A>
+where
+    SC: 'static + StarkGenericConfig + Send + Sync,
+    A: MachineAir<SC::Val>
+        + for<'a> Air<ProverConstraintFolder<'a, SC>>
+        + Air<InteractionBuilder<Val<SC>>>
+        + for<'a> Air<VerifierConstraintFolder<'a, SC>>
+        + for<'a> Air<SymbolicAirBuilder<Val<SC>>>,
+    A::Record: MachineRecord<Config = MONEROCHANCoreOpts>,
+    SC::Val: PrimeField32,
+    Com<SC>: Send + Sync,
+    PcsProverData<SC>: Send + Sync + Serialize + DeserializeOwned,
+    OpeningProof<SC>: Send + Sync,
+    SC::Challenger: Clone + Send,
+{
     /// Prove the program for the given shard and given a commitment to the main data.
+    ///
+    /// If `deterministic` is set, the FRI proof of work is searched on a single thread, so that
+    /// the proof does not depend on which thread finds a witness first.
     #[allow(clippy::too_many_lines)]
     #[allow(clippy::redundant_closure_for_method_calls)]
     #[allow(clippy::map_unwrap_or)]
-    fn open(
+    fn open_shard(
         &self,
         pk: &StarkProvingKey<SC>,
-        data: ShardMainData<SC, Self::DeviceMatrix, Self::DeviceProverData>,
+        data: ShardMainData<SC, RowMajorMatrix<Val<SC>>, PcsProverData<SC>>,
         challenger: &mut <SC as StarkGenericConfig>::Challenger,
-    ) -> Result<ShardProof<SC>, Self::Error> {
+        deterministic: bool,
+    ) -> Result<ShardProof<SC>, CpuProverError> {
         let chips = self.machine().shard_chips_ordered(&data.chip_ordering).collect::<Vec<_>>();
         let traces = data.traces;
 
@@ -366,15 +468,17 @@ where
             );
         }
 
+        // The main traces were committed to, so their buffers can be reused by the next shard.
+        for trace in traces {
+            arena::recycle(trace.values);
+        }
+
         let domains_and_perm_traces =
             tracing::debug_span!("flatten permutation traces and collect domains").in_scope(|| {
                 permutation_traces
                     .into_iter()
                     .zip(trace_domains.iter())
-                    .map(|(perm_trace, domain)| {
-                        let trace = perm_trace.flatten_to_base();
-                        (*domain, trace.clone())
-                    })
+                    .map(|(perm_trace, domain)| (*domain, perm_trace.flatten_to_base()))
                     .collect::<Vec<_>>()
             });
 
@@ -526,7 +630,7 @@ where
         let quotient_opening_points =
             (0..num_quotient_chunks).map(|_| vec![zeta]).collect::<Vec<_>>();
 
-        let (openings, opening_proof) = tracing::debug_span!("open multi batches").in_scope(|| {
+        let open = || {
             pcs.open(
                 vec![
                     (&pk.data, preprocessed_opening_points),
@@ -536,6 +640,15 @@ where
                 ],
                 challenger,
             )
+        };
+        let (openings, opening_proof) = tracing::debug_span!("open multi batches").in_scope(|| {
+            if deterministic {
+                // The proof of work is the first witness found by the threads of the pool, so
+                // only the search of a single thread is reproducible.
+                DETERMINISTIC_OPENING_POOL.with(|pool| pool.install(open))
+            } else {
+                open()
+            }
         });
 
         // Collect the opened values for each chip.
@@ -634,41 +747,6 @@ where
             public_values: data.public_values,
         })
     }
-
-    /// Prove the execution record is valid.
-    ///
-    /// Given a proving key `pk` and a matching execution record `record`, this function generates
-    /// a STARK proof that the execution record is valid.
-    #[allow(clippy::needless_for_each)]
-    fn prove(
-        &self,
-        pk: &StarkProvingKey<SC>,
-        mut records: Vec<A::Record>,
-        challenger: &mut SC::Challenger,
-        opts: <A::Record as MachineRecord>::Config,
-    ) -> Result<MachineProof<SC>, Self::Error>
-    where
-        A: for<'a> Air<DebugConstraintBuilder<'a, Val<SC>, SC::Challenge>>,
-    {
-        // Generate dependencies.
-        self.machine().generate_dependencies(&mut records, &opts, None);
-
-        // Observe the preprocessed commitment.
-        pk.observe_into(challenger);
-
-        let shard_proofs = tracing::info_span!("prove_shards").in_scope(|| {
-            records
-                .into_par_iter()
-                .map(|record| {
-                    let named_traces = self.generate_traces(&record);
-                    let shard_data = self.commit(&record, named_traces);
-                    self.open(pk, shard_data, &mut challenger.clone())
-                })
-                .collect::<Result<Vec<_>, _>>()
-        })?;
-
-        Ok(MachineProof { shard_proofs })
-    }
 }
 
 impl<SC> MachineProvingKey<SC> for StarkProvingKey<SC>
@@ -0,0 +1,124 @@
+//! The CPU and memory resources available to the prover.
+//!
+//! Provers often run in containers whose cgroup limits their CPU time and memory far below what
+//! the host has, and on multi-socket machines where a thread reading memory of another NUMA node
+//! runs at half the throughput. The default options are sized from the resources found here
+//! rather than from the host, and [`numa_thread_pool`] keeps the prover threads, and so the memory
+//! they first touch, on a single node.
+
+use std::{fs, io, path::Path, thread};
+
+use sysinfo::System;
+
+/// The memory limit of the cgroup of the process, in bytes, if it has one.
+///
+/// Both the unified hierarchy of cgroup v2 and the memory controller of cgroup v1 are read.
+#[must_use]
+pub fn cgroup_memory_limit() -> Option<u64> {
+    let read = |path: &str| fs::read_to_string(path).ok()?.trim().parse::<u64>().ok();
+    // cgroup v2 writes `max` when there is no limit, and cgroup v1 a huge page-aligned number.
+    read("/sys/fs/cgroup/memory.max")
+        .or_else(|| read("/sys/fs/cgroup/memory/memory.limit_in_bytes"))
+        .filter(|&limit| limit < 1 << 60)
+}
+
+/// The memory available to the prover, in bytes: the memory of the host, capped by the limit of
+/// its cgroup.
+#[must_use]
+pub fn available_memory() -> u64 {
+    let total = System::new_all().total_memory();
+    cgroup_memory_limit().map_or(total, |limit| total.min(limit))
+}
+
+/// The memory available to the prover, in GiB.
+#[must_use]
+pub fn available_memory_gb() -> usize {
+    (available_memory() / (1024 * 1024 * 1024)) as usize
+}
+
+/// The number of CPUs the prover can use.
+///
+/// This accounts for the CPU quota of the cgroup of the process and for its CPU affinity, so it
+/// is the size to give to thread pools.
+#[must_use]
+pub fn available_cpus() -> usize {
+    thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+}
+
+/// Parses a list of CPUs in the format of the kernel, such as `0-3,8,10-11`.
+fn parse_cpu_list(list: &str) -> io::Result<Vec<usize>> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("invalid CPU list {list}"));
+    let mut cpus = Vec::new();
+    for range in list.trim().split(',').filter(|range| !range.is_empty()) {
+        let (start, end) = range.split_once('-').unwrap_or((range, range));
+        let start = start.parse::<usize>().map_err(|_| invalid())?;
+        let end = end.parse::<usize>().map_err(|_| invalid())?;
+        cpus.extend(start..=end);
+    }
+    Ok(cpus)
+}
+
+/// The CPUs of a NUMA node.
+pub fn numa_node_cpus(node: usize) -> io::Result<Vec<usize>> {
+    let path = Path::new("/sys/devices/system/node").join(format!("node{node}")).join("cpulist");
+    parse_cpu_list(&fs::read_to_string(path)?)
+}
+
+/// Restricts the calling thread to a set of CPUs.
+#[cfg(target_os = "linux")]
+fn pin_current_thread(cpus: &[usize]) -> io::Result<()> {
+    // SAFETY: the set is zeroed before use, and only CPUs below `CPU_SETSIZE` are added to it.
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_ZERO(&mut set);
+        for &cpu in cpus.iter().filter(|&&cpu| cpu < libc::CPU_SETSIZE as usize) {
+            libc::CPU_SET(cpu, &mut set);
+        }
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn pin_current_thread(_: &[usize]) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "pinning threads is only supported on Linux"))
+}
+
+/// Builds a pool of threads pinned to the CPUs of a NUMA node, for the threads of a prover.
+///
+/// Since the kernel allocates memory on the node of the thread that first touches it, the traces
+/// generated in the pool stay local to the node. Only the threads of the pool are pinned: the
+/// calling thread, the global rayon pool and the other threads of the process keep running on any
+/// CPU. The pool has a thread per CPU of the node, and at most [`available_cpus`] threads.
+pub fn numa_thread_pool(node: usize) -> io::Result<rayon::ThreadPool> {
+    let cpus = numa_node_cpus(node)?;
+    if cpus.is_empty() {
+        let message = format!("NUMA node {node} has no CPUs");
+        return Err(io::Error::new(io::ErrorKind::NotFound, message));
+    }
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(cpus.len().min(available_cpus()))
+        .thread_name(move |i| format!("prover-numa{node}-{i}"))
+        .start_handler(move |_| {
+            if let Err(err) = pin_current_thread(&cpus) {
+                tracing::warn!("failed to pin a prover thread to NUMA node {node}: {err}");
+            }
+        })
+        .build()
+        .map_err(io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpu_list() {
+        assert_eq!(parse_cpu_list("0-3,8,10-11\n").unwrap(), vec![0, 1, 2, 3, 8, 10, 11]);
+        assert_eq!(parse_cpu_list("5").unwrap(), vec![5]);
+        assert_eq!(parse_cpu_list("").unwrap(), Vec::<usize>::new());
+        assert!(parse_cpu_list("0-x").is_err());
+    }
+}
@@ -0,0 +1,41 @@
+#![no_main]
+monerochan_runtime::entrypoint!(main);
+
+use monerochan_runtime::syscalls::syscall_aes_encrypt_round;
+
+/// Converts a block in hex to words, one column of the state per word.
+fn words(hex: &str) -> [u32; 4] {
+    core::array::from_fn(|c| {
+        let column = &hex[8 * c..8 * (c + 1)];
+        u32::from_str_radix(column, 16).unwrap().swap_bytes()
+    })
+}
+
+pub fn main() {
+    // Rounds 1 to 3 of the AES-128 example of FIPS-197, appendix B.
+    let rounds = [
+        (
+            "193de3bea0f4e22b9ac68d2ae9f84808",
+            "a0fafe1788542cb123a339392a6c7605",
+            "a49c7ff2689f352b6b5bea43026a5049",
+        ),
+        (
+            "a49c7ff2689f352b6b5bea43026a5049",
+            "f2c295f27a96b9435935807a7359f67f",
+            "aa8f5f0361dde3ef82d24ad26832469a",
+        ),
+        (
+            "aa8f5f0361dde3ef82d24ad26832469a",
+            "3d80477d4716fe3e1e237e446d7a883b",
+            "486c4eee671d9d0d4de3b138d65f58e7",
+        ),
+    ];
+
+    for (state, round_key, expected) in rounds {
+        let mut state = words(state);
+        syscall_aes_encrypt_round(&mut state, &words(round_key));
+        assert_eq!(state, words(expected));
+    }
+
+    println!("done");
+}
@@ -0,0 +1,51 @@
+#![no_main]
+monerochan_runtime::entrypoint!(main);
+
+use monerochan_runtime::syscalls::{syscall_f64_add, syscall_f64_div, syscall_f64_mul};
+
+/// Runs an f64 syscall on the given operands, and returns the bits of its result.
+fn run(syscall: extern "C" fn(*mut [u32; 2], *const [u32; 2]), x: f64, y: f64) -> u64 {
+    let (x, y) = (x.to_bits(), y.to_bits());
+    let mut result = [x as u32, (x >> 32) as u32];
+    syscall(&mut result, &[y as u32, (y >> 32) as u32]);
+    u64::from(result[0]) | (u64::from(result[1]) << 32)
+}
+
+pub fn main() {
+    // Normal operands and results, which are accelerated. The software results of compiler-rt
+    // are the reference.
+    let normal = [
+        (1.5, 2.25),
+        (0.1, 0.2),
+        (3.0, 3.0),
+        (1.0, -0.9999999999999999),
+        (-7.25, 1e-3),
+        (1e150, 1e-150),
+        (1e-100, 1e100),
+        (-123456.789, -0.000321),
+        // Ties of the addition, the second one carrying into the next binade.
+        (1.0, f64::EPSILON / 2.0),
+        (1.0 - f64::EPSILON / 2.0, f64::EPSILON / 4.0),
+        (1.0 + f64::EPSILON, 1.0 - f64::EPSILON / 2.0),
+        (f64::MAX / 2.0, 1.5),
+        (f64::MIN_POSITIVE * 4.0, 0.75),
+    ];
+    for (x, y) in normal {
+        assert_eq!(run(syscall_f64_add, x, y), (x + y).to_bits());
+        assert_eq!(run(syscall_f64_add, x, -y), (x - y).to_bits());
+        assert_eq!(run(syscall_f64_mul, x, y), (x * y).to_bits());
+        assert_eq!(run(syscall_f64_div, x, y), (x / y).to_bits());
+    }
+
+    // Operands or results which are not normal write the all-zero sentinel.
+    let special =
+        [(f64::MIN_POSITIVE / 2.0, 1.0), (0.0, 1.0), (f64::INFINITY, 1.0), (f64::NAN, 1.0)];
+    for (x, y) in special {
+        assert_eq!(run(syscall_f64_add, x, -y), 0);
+    }
+    assert_eq!(run(syscall_f64_mul, f64::MAX, 2.0), 0);
+    assert_eq!(run(syscall_f64_mul, f64::MIN_POSITIVE, 0.5), 0);
+    assert_eq!(run(syscall_f64_div, 1.0, 0.0), 0);
+
+    println!("done");
+}
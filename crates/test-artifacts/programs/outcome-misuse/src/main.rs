@@ -0,0 +1,8 @@
+#![no_main]
+monerochan_runtime::entrypoint!(main);
+
+/// Commits a value before recording the outcome, which must panic.
+pub fn main() {
+    monerochan_runtime::io::commit(&1u32);
+    monerochan_runtime::io::record_outcome();
+}
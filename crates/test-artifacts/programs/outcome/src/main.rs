@@ -0,0 +1,13 @@
+#![no_main]
+monerochan_runtime::entrypoint!(main);
+
+/// Commits the input, and rejects it with its value as the error code if it is odd.
+pub fn main() {
+    monerochan_runtime::io::record_outcome();
+    let n = monerochan_runtime::io::read::<u32>();
+    monerochan_runtime::io::commit(&n);
+    if n % 2 == 1 {
+        monerochan_runtime::io::reject(n);
+    }
+    monerochan_runtime::io::commit(&(n / 2));
+}
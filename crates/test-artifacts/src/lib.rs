@@ -22,6 +22,10 @@ pub const ED_ADD_ELF: &[u8] = include_elf!("ed-add-test");
 
 pub const ED_DECOMPRESS_ELF: &[u8] = include_elf!("ed-decompress-test");
 
+pub const AES_ROUND_ELF: &[u8] = include_elf!("aes-round-test");
+
+pub const F64_OP_ELF: &[u8] = include_elf!("f64-op-test");
+
 pub const KECCAK_PERMUTE_ELF: &[u8] = include_elf!("keccak-permute-test");
 
 pub const KECCAK256_ELF: &[u8] = include_elf!("keccak256-test");
@@ -68,6 +72,9 @@ pub const VERIFY_PROOF_ELF: &[u8] = include_elf!("verify-proof");
 
 pub const PANIC_ELF: &[u8] = include_elf!("panic-test");
 
+pub const OUTCOME_ELF: &[u8] = include_elf!("outcome-test");
+pub const OUTCOME_MISUSE_ELF: &[u8] = include_elf!("outcome-misuse-test");
+
 pub const BLS12381_FP_ELF: &[u8] = include_elf!("bls12381-fp-test");
 
 pub const BLS12381_FP2_MUL_ELF: &[u8] = include_elf!("bls12381-fp2-mul-test");
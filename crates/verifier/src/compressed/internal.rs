@@ -128,7 +128,7 @@ pub fn verify_monerochan_reduce_proof(
 
     // Verify that the proof is for the monerochan vkey we are expecting.
     if public_values.monerochan_vk_digest != *vkey_hash {
-        return Err(MachineVerificationError::InvalidPublicValues("monerochan vk hash mismatch").into());
+        return Err(MachineVerificationError::ProgramVkeyMismatch.into());
     }
 
     Ok(())
@@ -1,5 +1,7 @@
 use alloc::vec::Vec;
 
+use bn::AffineG1;
+
 use crate::{
     constants::{COMPRESSED_GROTH16_PROOF_LENGTH, GROTH16_PROOF_LENGTH},
     converter::{
@@ -90,13 +92,18 @@ pub(crate) fn load_compressed_groth16_proof_from_bytes(
     Ok(Groth16Proof { ar, bs, krs })
 }
 
-/// Load the Groth16 verification key from the given byte slice.
-///
-/// The gnark verification key includes a lot of extraneous information. We only extract the
-/// necessary elements to verify a proof.
-pub(crate) fn load_groth16_verifying_key_from_bytes(
+/// The offset of the number of `k` points in the gnark verification key, which are stored after it.
+const GROTH16_VK_NUM_K_OFFSET: usize = 288;
+
+/// Load the elements of the Groth16 verification key that come before the `k` points, and the
+/// number of `k` points.
+fn load_groth16_verifying_key_header(
     buffer: &[u8],
-) -> Result<Groth16VerifyingKey, Groth16Error> {
+) -> Result<(AffineG1, Groth16G2, usize), Groth16Error> {
+    if buffer.len() < GROTH16_VK_NUM_K_OFFSET + 4 {
+        return Err(Groth16Error::GeneralError(Error::InvalidData));
+    }
+
     // We don't need to check each compressed point because the Groth16 vkey is a public constant
     // that doesn't usually change. The party using the Groth16 vkey will usually clearly know
     // how the vkey was generated.
@@ -106,16 +113,54 @@ pub(crate) fn load_groth16_verifying_key_from_bytes(
     let g2_delta = unchecked_compressed_x_to_g2_point(&buffer[224..288])?;
 
     let num_k = u32::from_be_bytes([buffer[288], buffer[289], buffer[290], buffer[291]]);
-    let mut k = Vec::new();
-    let mut offset = 292;
-    for _ in 0..num_k {
-        let point = unchecked_compressed_x_to_g1_point(&buffer[offset..offset + 32])?;
-        k.push(point);
-        offset += 32;
+
+    Ok((
+        g1_alpha,
+        Groth16G2 { beta: -g2_beta, gamma: g2_gamma, delta: g2_delta },
+        num_k as usize,
+    ))
+}
+
+/// Load the `i`-th `k` point of the Groth16 verification key.
+fn load_groth16_verifying_key_k(buffer: &[u8], i: usize) -> Result<AffineG1, Groth16Error> {
+    let offset = GROTH16_VK_NUM_K_OFFSET + 4 + 32 * i;
+    let bytes =
+        buffer.get(offset..offset + 32).ok_or(Groth16Error::GeneralError(Error::InvalidData))?;
+    Ok(unchecked_compressed_x_to_g1_point(bytes)?)
+}
+
+/// Load the Groth16 verification key from the given byte slice.
+///
+/// The gnark verification key includes a lot of extraneous information. We only extract the
+/// necessary elements to verify a proof.
+pub(crate) fn load_groth16_verifying_key_from_bytes(
+    buffer: &[u8],
+) -> Result<Groth16VerifyingKey, Groth16Error> {
+    let (alpha, g2, num_k) = load_groth16_verifying_key_header(buffer)?;
+    let k = (0..num_k)
+        .map(|i| load_groth16_verifying_key_k(buffer, i))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Groth16VerifyingKey { g1: Groth16G1 { alpha, k }, g2 })
+}
+
+/// Load a Groth16 verification key with `N` points `k`, that is for `N - 1` public inputs, from
+/// the given byte slice.
+///
+/// Unlike [`load_groth16_verifying_key_from_bytes`], this does not allocate.
+pub(crate) fn load_fixed_groth16_verifying_key_from_bytes<const N: usize>(
+    buffer: &[u8],
+) -> Result<Groth16VerifyingKey<[AffineG1; N]>, Groth16Error> {
+    let (alpha, g2, num_k) = load_groth16_verifying_key_header(buffer)?;
+    if num_k != N {
+        return Err(Groth16Error::PrepareInputsFailed);
+    }
+
+    // Every point is overwritten below, `alpha` only fills the array.
+    let mut k = [alpha; N];
+    for (i, point) in k.iter_mut().enumerate() {
+        *point = load_groth16_verifying_key_k(buffer, i)?;
     }
 
-    Ok(Groth16VerifyingKey {
-        g1: Groth16G1 { alpha: g1_alpha, k },
-        g2: Groth16G2 { beta: -g2_beta, gamma: g2_gamma, delta: g2_delta },
-    })
+    Ok(Groth16VerifyingKey { g1: Groth16G1 { alpha, k }, g2 })
 }
@@ -4,8 +4,8 @@ mod verify;
 
 use bn::Fr;
 pub(crate) use converter::{
-    load_compressed_groth16_proof_from_bytes, load_groth16_proof_from_bytes,
-    load_groth16_verifying_key_from_bytes,
+    load_compressed_groth16_proof_from_bytes, load_fixed_groth16_verifying_key_from_bytes,
+    load_groth16_proof_from_bytes, load_groth16_verifying_key_from_bytes,
 };
 pub(crate) use verify::*;
 
@@ -47,49 +47,93 @@ impl Groth16Verifier {
     /// # Returns
     ///
     /// A success [`Result`] if verification succeeds, or a [`Groth16Error`] if verification fails.
+    ///
+    /// # Allocation and timing
+    ///
+    /// This does not allocate, and apart from rejecting malformed proofs, it does the same work
+    /// whatever the proof: the vkey hash prefix is compared in constant time, and the proof is
+    /// checked against both the Sha256 and the Blake3 digests of the public inputs rather than
+    /// stopping at the first that matches.
     pub fn verify(
         proof: &[u8],
         monerochan_public_inputs: &[u8],
         monerochan_vkey_hash: &str,
         groth16_vk: &[u8],
     ) -> Result<(), Groth16Error> {
-        if proof.len() < VK_HASH_PREFIX_LENGTH {
-            return Err(Groth16Error::GeneralError(Error::InvalidData));
-        }
-
-        // Hash the vk and get the first 4 bytes.
-        let groth16_vk_hash: [u8; 4] = Sha256::digest(groth16_vk)[..VK_HASH_PREFIX_LENGTH]
-            .try_into()
-            .map_err(|_| Groth16Error::GeneralError(Error::InvalidData))?;
+        let proof = load_groth16_proof_from_bytes(strip_groth16_vk_hash(proof, groth16_vk)?)?;
+        Self::verify_monerochan_proof(
+            &proof,
+            monerochan_public_inputs,
+            monerochan_vkey_hash,
+            groth16_vk,
+        )
+    }
 
-        // Check to make sure that this proof was generated by the groth16 proving key corresponding
-        // to the given groth16_vk.
-        //
-        // MONEROCHAN prepends the raw Groth16 proof with the first 4 bytes of the groth16 vkey to
-        // facilitate this check.
-        if groth16_vk_hash != proof[..VK_HASH_PREFIX_LENGTH] {
-            return Err(Groth16Error::Groth16VkeyHashMismatch);
-        }
+    /// Verifies an MONEROCHAN Groth16 proof against the hash of its public inputs.
+    ///
+    /// This is [`Groth16Verifier::verify`] for callers which already hold the digest of the
+    /// public inputs, such as when the public inputs are too large to be kept around, and like it,
+    /// it does not allocate.
+    ///
+    /// # Arguments
+    ///
+    /// * `proof` - The proof bytes.
+    /// * `public_inputs_hash` - The digest of the MONEROCHAN public inputs, as returned by
+    ///   [`crate::hash_public_inputs`] or [`crate::hash_public_inputs_with_fn`] for programs which
+    ///   commit to their public values with Blake3.
+    /// * `monerochan_vkey_hash` - The MONEROCHAN vkey hash, as returned by `vk.bytes32()`.
+    /// * `groth16_vk` - The Groth16 verifying key bytes.
+    ///
+    /// # Returns
+    ///
+    /// A success [`Result`] if verification succeeds, or a [`Groth16Error`] if verification fails.
+    pub fn verify_prehashed(
+        proof: &[u8],
+        public_inputs_hash: &[u8; 32],
+        monerochan_vkey_hash: &str,
+        groth16_vk: &[u8],
+    ) -> Result<(), Groth16Error> {
+        let proof = load_groth16_proof_from_bytes(strip_groth16_vk_hash(proof, groth16_vk)?)?;
+        let groth16_vk =
+            load_fixed_groth16_verifying_key_from_bytes::<MONEROCHAN_GROTH16_VK_NUM_K>(groth16_vk)?;
 
         let monerochan_vkey_hash = decode_monerochan_vkey_hash(monerochan_vkey_hash)?;
+        let public_inputs =
+            [fr_from_bytes(&monerochan_vkey_hash)?, fr_from_bytes(public_inputs_hash)?];
+        verify_groth16_algebraic(&groth16_vk, &proof, &public_inputs)
+    }
+
+    /// Verifies a decoded MONEROCHAN Groth16 proof against the Sha256 and Blake3 digests of the
+    /// public inputs.
+    fn verify_monerochan_proof(
+        proof: &Groth16Proof,
+        monerochan_public_inputs: &[u8],
+        monerochan_vkey_hash: &str,
+        groth16_vk: &[u8],
+    ) -> Result<(), Groth16Error> {
+        let groth16_vk =
+            load_fixed_groth16_verifying_key_from_bytes::<MONEROCHAN_GROTH16_VK_NUM_K>(groth16_vk)?;
+
+        let monerochan_vkey_hash =
+            fr_from_bytes(&decode_monerochan_vkey_hash(monerochan_vkey_hash)?)?;
+        let sha256_inputs =
+            [monerochan_vkey_hash, fr_from_bytes(&hash_public_inputs(monerochan_public_inputs))?];
+        let blake3_inputs = [
+            monerochan_vkey_hash,
+            fr_from_bytes(&hash_public_inputs_with_fn(monerochan_public_inputs, blake3_hash))?,
+        ];
 
         // It is computationally infeasible to find two distinct inputs, one processed with
         // SHA256 and the other with Blake3, that yield the same hash value.
-        if Self::verify_gnark_proof(
-            &proof[VK_HASH_PREFIX_LENGTH..],
-            &[monerochan_vkey_hash, hash_public_inputs(monerochan_public_inputs)],
-            groth16_vk,
-        )
-        .is_ok()
-        {
-            return Ok(());
+        //
+        // Both are checked, so that the time taken does not tell which one the proof commits to.
+        let sha256_valid = check_groth16_pairing(&groth16_vk, proof, &sha256_inputs)?;
+        let blake3_valid = check_groth16_pairing(&groth16_vk, proof, &blake3_inputs)?;
+        if sha256_valid | blake3_valid {
+            Ok(())
+        } else {
+            Err(Groth16Error::ProofVerificationFailed)
         }
-
-        Self::verify_gnark_proof(
-            &proof[VK_HASH_PREFIX_LENGTH..],
-            &[monerochan_vkey_hash, hash_public_inputs_with_fn(monerochan_public_inputs, blake3_hash)],
-            groth16_vk,
-        )
     }
 
     /// Verifies a Gnark Groth16 proof using raw byte inputs.
@@ -153,41 +197,12 @@ impl Groth16Verifier {
         monerochan_vkey_hash: &str,
         groth16_vk: &[u8],
     ) -> Result<(), Groth16Error> {
-        if proof.len() < VK_HASH_PREFIX_LENGTH {
-            return Err(Groth16Error::GeneralError(Error::InvalidData));
-        }
-
-        // Hash the vk and get the first 4 bytes.
-        let groth16_vk_hash: [u8; 4] = Sha256::digest(groth16_vk)[..VK_HASH_PREFIX_LENGTH]
-            .try_into()
-            .map_err(|_| Groth16Error::GeneralError(Error::InvalidData))?;
-
-        // Check to make sure that this proof was generated by the groth16 proving key corresponding
-        // to the given groth16_vk.
-        //
-        // MONEROCHAN prepends the raw Groth16 proof with the first 4 bytes of the groth16 vkey to
-        // facilitate this check.
-        if groth16_vk_hash != proof[..VK_HASH_PREFIX_LENGTH] {
-            return Err(Groth16Error::Groth16VkeyHashMismatch);
-        }
-
-        let monerochan_vkey_hash = decode_monerochan_vkey_hash(monerochan_vkey_hash)?;
-
-        // It is computationally infeasible to find two distinct inputs, one processed with
-        // SHA256 and the other with Blake3, that yield the same hash value.
-        if Self::verify_compressed_gnark_proof(
-            &proof[VK_HASH_PREFIX_LENGTH..],
-            &[monerochan_vkey_hash, hash_public_inputs(monerochan_public_inputs)],
-            groth16_vk,
-        )
-        .is_ok()
-        {
-            return Ok(());
-        }
-
-        Self::verify_compressed_gnark_proof(
-            &proof[VK_HASH_PREFIX_LENGTH..],
-            &[monerochan_vkey_hash, hash_public_inputs_with_fn(monerochan_public_inputs, blake3_hash)],
+        let proof =
+            load_compressed_groth16_proof_from_bytes(strip_groth16_vk_hash(proof, groth16_vk)?)?;
+        Self::verify_monerochan_proof(
+            &proof,
+            monerochan_public_inputs,
+            monerochan_vkey_hash,
             groth16_vk,
         )
     }
@@ -226,3 +241,35 @@ impl Groth16Verifier {
         verify_groth16_algebraic(&groth16_vk, &proof, &public_inputs)
     }
 }
+
+/// The number of `k` points of the MONEROCHAN Groth16 verifying key: one for the constant term and
+/// one for each of the vkey hash and the committed values digest.
+const MONEROCHAN_GROTH16_VK_NUM_K: usize = 3;
+
+/// Checks that the proof was generated by the Groth16 proving key corresponding to `groth16_vk`,
+/// and returns the raw Groth16 proof.
+///
+/// MONEROCHAN prepends the raw Groth16 proof with the first 4 bytes of the sha256 hash of the
+/// groth16 vkey to facilitate this check.
+fn strip_groth16_vk_hash<'a>(proof: &'a [u8], groth16_vk: &[u8]) -> Result<&'a [u8], Groth16Error> {
+    if proof.len() < VK_HASH_PREFIX_LENGTH {
+        return Err(Groth16Error::GeneralError(Error::InvalidData));
+    }
+
+    let groth16_vk_hash = Sha256::digest(groth16_vk);
+    let (prefix, proof) = proof.split_at(VK_HASH_PREFIX_LENGTH);
+    if !constant_time_eq(&groth16_vk_hash[..VK_HASH_PREFIX_LENGTH], prefix) {
+        return Err(Groth16Error::Groth16VkeyHashMismatch);
+    }
+    Ok(proof)
+}
+
+/// Compares two byte slices of the same length without stopping at the first difference.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Converts a big-endian 32 byte digest to a scalar, failing if it is not reduced.
+fn fr_from_bytes(bytes: &[u8; 32]) -> Result<Fr, Groth16Error> {
+    Fr::from_slice(bytes).map_err(|e| Groth16Error::GeneralError(Error::Field(e)))
+}
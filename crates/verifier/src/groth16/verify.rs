@@ -4,10 +4,13 @@ use bn::{pairing_batch, AffineG1, AffineG2, Fr, Gt, G1, G2};
 use super::error::Groth16Error;
 
 /// G1 elements of the verification key.
+///
+/// The points `k` are held in a vector, or in an array when the number of public inputs is known
+/// ahead of time, so that the key can be loaded without allocating.
 #[derive(Clone, PartialEq)]
-pub(crate) struct Groth16G1 {
+pub(crate) struct Groth16G1<K = Vec<AffineG1>> {
     pub(crate) alpha: AffineG1,
-    pub(crate) k: Vec<AffineG1>,
+    pub(crate) k: K,
 }
 
 /// G2 elements of the verification key.
@@ -20,8 +23,8 @@ pub(crate) struct Groth16G2 {
 
 /// Verification key for the Groth16 proof.
 #[derive(Clone, PartialEq)]
-pub(crate) struct Groth16VerifyingKey {
-    pub(crate) g1: Groth16G1,
+pub(crate) struct Groth16VerifyingKey<K = Vec<AffineG1>> {
+    pub(crate) g1: Groth16G1<K>,
     pub(crate) g2: Groth16G2,
 }
 
@@ -34,36 +37,43 @@ pub(crate) struct Groth16Proof {
 
 /// Prepare the inputs for the Groth16 verification by combining the public inputs with the
 /// corresponding elements of the verification key.
-fn prepare_inputs(vk: Groth16VerifyingKey, public_inputs: &[Fr]) -> Result<G1, Groth16Error> {
-    if (public_inputs.len() + 1) != vk.g1.k.len() {
+fn prepare_inputs(k: &[AffineG1], public_inputs: &[Fr]) -> Result<G1, Groth16Error> {
+    if (public_inputs.len() + 1) != k.len() {
         return Err(Groth16Error::PrepareInputsFailed);
     }
 
-    Ok(public_inputs
-        .iter()
-        .zip(vk.g1.k.iter().skip(1))
-        .fold(vk.g1.k[0], |acc, (i, b)| acc + (*b * *i))
-        .into())
+    Ok(public_inputs.iter().zip(k.iter().skip(1)).fold(k[0], |acc, (i, b)| acc + (*b * *i)).into())
 }
 
-/// Verify the Groth16 proof using algebraic inputs.
+/// Check the pairing equation of the Groth16 proof, returning whether it holds.
 ///
-/// First, prepare the public inputs by folding them with the verification key.
-/// Then, verify the proof by checking the pairing equation.
-pub(crate) fn verify_groth16_algebraic(
-    vk: &Groth16VerifyingKey,
+/// The result is returned rather than turned into an error so that callers checking several
+/// candidate public inputs can combine the results without branching on each of them.
+pub(crate) fn check_groth16_pairing<K: AsRef<[AffineG1]>>(
+    vk: &Groth16VerifyingKey<K>,
     proof: &Groth16Proof,
     public_inputs: &[Fr],
-) -> Result<(), Groth16Error> {
-    let prepared_inputs = prepare_inputs(vk.clone(), public_inputs)?;
+) -> Result<bool, Groth16Error> {
+    let prepared_inputs = prepare_inputs(vk.g1.k.as_ref(), public_inputs)?;
 
-    if pairing_batch(&[
+    Ok(pairing_batch(&[
         (-Into::<G1>::into(proof.ar), proof.bs.into()),
         (prepared_inputs, vk.g2.gamma.into()),
         (proof.krs.into(), vk.g2.delta.into()),
         (vk.g1.alpha.into(), -Into::<G2>::into(vk.g2.beta)),
-    ]) == Gt::one()
-    {
+    ]) == Gt::one())
+}
+
+/// Verify the Groth16 proof using algebraic inputs.
+///
+/// First, prepare the public inputs by folding them with the verification key.
+/// Then, verify the proof by checking the pairing equation.
+pub(crate) fn verify_groth16_algebraic<K: AsRef<[AffineG1]>>(
+    vk: &Groth16VerifyingKey<K>,
+    proof: &Groth16Proof,
+    public_inputs: &[Fr],
+) -> Result<(), Groth16Error> {
+    if check_groth16_pairing(vk, proof, public_inputs)? {
         Ok(())
     } else {
         Err(Groth16Error::ProofVerificationFailed)
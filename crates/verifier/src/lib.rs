@@ -21,6 +21,11 @@ pub mod compressed;
 #[cfg(feature = "compressed")]
 pub use compressed::{CompressedError, CompressedVerifier};
 
+#[cfg(feature = "tee")]
+pub mod tee;
+#[cfg(feature = "tee")]
+pub use tee::{verify_tee_proof, TeeError};
+
 mod constants;
 pub mod converter;
 mod error;
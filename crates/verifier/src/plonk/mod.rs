@@ -57,31 +57,14 @@ impl PlonkVerifier {
         monerochan_vkey_hash: &str,
         plonk_vk: &[u8],
     ) -> Result<(), PlonkError> {
-        if proof.len() < VK_HASH_PREFIX_LENGTH {
-            return Err(PlonkError::GeneralError(Error::InvalidData));
-        }
-
-        // Hash the vk and get the first 4 bytes.
-        let plonk_vk_hash: [u8; 4] = Sha256::digest(plonk_vk)[..VK_HASH_PREFIX_LENGTH]
-            .try_into()
-            .map_err(|_| PlonkError::GeneralError(Error::InvalidData))?;
-
-        // Check to make sure that this proof was generated by the plonk proving key corresponding
-        // to the given plonk vk.
-        //
-        // MONEROCHAN prepends the raw Plonk proof with the first 4 bytes of the plonk vkey to
-        // facilitate this check.
-        if plonk_vk_hash != proof[..VK_HASH_PREFIX_LENGTH] {
-            return Err(PlonkError::PlonkVkeyHashMismatch);
-        }
-
+        let proof = strip_plonk_vk_hash(proof, plonk_vk)?;
         let monerochan_vkey_hash = decode_monerochan_vkey_hash(monerochan_vkey_hash)?;
 
         // First, check if the public values hashed with SHA2 match the expected public values.
         // If not, try hashing with Blake3. If both fail, return an error. We perform the checks
         // sequentially to avoid calculating both hashes unless necessary.
         if Self::verify_gnark_proof(
-            &proof[VK_HASH_PREFIX_LENGTH..],
+            proof,
             &[monerochan_vkey_hash, hash_public_inputs(monerochan_public_inputs)],
             plonk_vk,
         )
@@ -91,12 +74,45 @@ impl PlonkVerifier {
         }
 
         Self::verify_gnark_proof(
-            &proof[VK_HASH_PREFIX_LENGTH..],
+            proof,
             &[monerochan_vkey_hash, hash_public_inputs_with_fn(monerochan_public_inputs, blake3_hash)],
             plonk_vk,
         )
     }
 
+    /// Verifies a PLONK proof, prefixed like the MONEROCHAN proofs, against an arbitrary vector of
+    /// public inputs.
+    ///
+    /// [`PlonkVerifier::verify`] assumes the public inputs of the circuit are the MONEROCHAN vkey
+    /// hash followed by the digest of the committed values. Circuits which embed the MONEROCHAN
+    /// wrap proof in a larger gnark circuit have their own public inputs, and are verified with
+    /// this method instead. Like [`PlonkVerifier::verify`], it expects the proof to be prefixed
+    /// with the first 4 bytes of the hash of the verifying key, and checks it.
+    ///
+    /// # Arguments
+    ///
+    /// * `proof` - The proof bytes, including the 4-byte vkey hash prefix.
+    /// * `public_inputs` - The public inputs of the circuit, as big-endian encoded scalars, in the
+    ///   order the circuit declares them. Their number must be
+    ///   [`PlonkVerifier::num_public_inputs`] of the verifying key.
+    /// * `plonk_vk` - The PLONK verifying key bytes of the circuit.
+    ///
+    /// # Returns
+    ///
+    /// A success [`Result`] if verification succeeds, or a [`PlonkError`] if verification fails.
+    pub fn verify_with_public_inputs(
+        proof: &[u8],
+        public_inputs: &[[u8; 32]],
+        plonk_vk: &[u8],
+    ) -> Result<(), PlonkError> {
+        Self::verify_gnark_proof(strip_plonk_vk_hash(proof, plonk_vk)?, public_inputs, plonk_vk)
+    }
+
+    /// Returns the number of public inputs of the circuit of a PLONK verifying key.
+    pub fn num_public_inputs(plonk_vk: &[u8]) -> Result<usize, PlonkError> {
+        Ok(load_plonk_verifying_key_from_bytes(plonk_vk)?.nb_public_variables)
+    }
+
     /// Verifies a Gnark PLONK proof using raw byte inputs.
     ///
     /// WARNING: if you're verifying an MONEROCHAN proof, you should use [`verify`] instead.
@@ -112,17 +128,38 @@ impl PlonkVerifier {
     /// # Returns
     ///
     /// A [`Result`] containing unit `()` if the proof is valid,
-    /// or a [`PlonkError`] if verification fails.
+    /// or a [`PlonkError`] if verification fails. Public inputs which are not reduced modulo the
+    /// scalar field are rejected with [`PlonkError::BeyondTheModulus`].
     pub fn verify_gnark_proof(
         proof: &[u8],
         public_inputs: &[[u8; 32]],
         plonk_vk: &[u8],
     ) -> Result<(), PlonkError> {
-        let plonk_vk = load_plonk_verifying_key_from_bytes(plonk_vk).unwrap();
-        let proof = load_plonk_proof_from_bytes(proof, plonk_vk.qcp.len()).unwrap();
+        let plonk_vk = load_plonk_verifying_key_from_bytes(plonk_vk)?;
+        let proof = load_plonk_proof_from_bytes(proof, plonk_vk.qcp.len())?;
 
-        let public_inputs =
-            public_inputs.iter().map(|input| Fr::from_slice(input).unwrap()).collect::<Vec<_>>();
+        let public_inputs = public_inputs
+            .iter()
+            .map(|input| Fr::from_slice(input).map_err(|_| PlonkError::BeyondTheModulus))
+            .collect::<Result<Vec<_>, _>>()?;
         verify_plonk_algebraic(&plonk_vk, &proof, &public_inputs)
     }
 }
+
+/// Checks that the proof was generated by the plonk proving key corresponding to `plonk_vk`, and
+/// returns the raw Plonk proof.
+///
+/// MONEROCHAN prepends the raw Plonk proof with the first 4 bytes of the sha256 hash of the plonk
+/// vkey to facilitate this check.
+fn strip_plonk_vk_hash<'a>(proof: &'a [u8], plonk_vk: &[u8]) -> Result<&'a [u8], PlonkError> {
+    if proof.len() < VK_HASH_PREFIX_LENGTH {
+        return Err(PlonkError::GeneralError(Error::InvalidData));
+    }
+
+    let plonk_vk_hash = Sha256::digest(plonk_vk);
+    let (prefix, proof) = proof.split_at(VK_HASH_PREFIX_LENGTH);
+    if plonk_vk_hash[..VK_HASH_PREFIX_LENGTH] != *prefix {
+        return Err(PlonkError::PlonkVkeyHashMismatch);
+    }
+    Ok(proof)
+}
@@ -0,0 +1,172 @@
+//! Verification of TEE integrity proofs.
+//!
+//! An integrity proof is an ECDSA signature over secp256k1, by a signer running in a trusted
+//! execution environment (TEE), over the verifying key of a program and the public values of one
+//! of its executions. It is laid out as four bytes identifying the TEE verifier, which are not
+//! checked here, followed by the recoverable signature `v || r || s`, with `v` in `{27, 28}`.
+//!
+//! The signed message is the keccak256 digest of the concatenation of the keccak256 digest of the
+//! TEE version, the verifying key hash, and the keccak256 digest of the public values.
+
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use sha3::{Digest, Keccak256};
+use thiserror::Error;
+
+/// The MONEROCHAN TEE backend version whose integrity proofs are verified.
+pub const MONEROCHAN_TEE_VERSION: u32 = 1;
+
+/// The length of an integrity proof.
+pub const TEE_PROOF_LEN: usize = 69;
+
+/// An Ethereum style address of a TEE signer, the last 20 bytes of the keccak256 digest of its
+/// uncompressed public key.
+pub type TeeSigner = [u8; 20];
+
+/// A reason why an integrity proof is rejected.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TeeError {
+    #[error("no trusted TEE signers are provided")]
+    NoSigners,
+    #[error("invalid TEE proof length: expected at least {TEE_PROOF_LEN} bytes, got {0}")]
+    InvalidLength(usize),
+    #[error("invalid TEE proof recovery id {0}")]
+    InvalidRecoveryId(u8),
+    #[error("invalid TEE proof signature")]
+    InvalidSignature,
+    #[error("TEE proof signed by an unknown signer 0x{}", hex::encode(.0))]
+    UnknownSigner(TeeSigner),
+}
+
+/// Returns the digest signed by a TEE for the given verifying key hash and public values.
+///
+/// The verifying key hash is the one returned by `HashableKey::bytes32_raw`.
+pub fn tee_message_digest(vkey_hash: &[u8; 32], public_values: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(Keccak256::digest(MONEROCHAN_TEE_VERSION.to_le_bytes()));
+    hasher.update(vkey_hash);
+    hasher.update(Keccak256::digest(public_values));
+    hasher.finalize().into()
+}
+
+/// Recovers the signer of an integrity proof, without checking that it is trusted.
+pub fn recover_tee_signer(
+    tee_proof: &[u8],
+    vkey_hash: &[u8; 32],
+    public_values: &[u8],
+) -> Result<TeeSigner, TeeError> {
+    if tee_proof.len() < TEE_PROOF_LEN {
+        return Err(TeeError::InvalidLength(tee_proof.len()));
+    }
+
+    let recovery_id = tee_proof[4]
+        .checked_sub(27)
+        .and_then(RecoveryId::from_byte)
+        .ok_or(TeeError::InvalidRecoveryId(tee_proof[4]))?;
+    let signature = Signature::from_slice(&tee_proof[5..TEE_PROOF_LEN])
+        .map_err(|_| TeeError::InvalidSignature)?;
+
+    let digest = tee_message_digest(vkey_hash, public_values);
+    let signer = VerifyingKey::recover_from_prehash(&digest, &signature, recovery_id)
+        .map_err(|_| TeeError::InvalidSignature)?;
+
+    // The address is derived from the uncompressed public key, without its `0x04` tag.
+    let public_key = signer.to_encoded_point(false);
+    let digest = Keccak256::digest(&public_key.as_bytes()[1..]);
+    let mut address = [0; 20];
+    address.copy_from_slice(&digest[12..]);
+    Ok(address)
+}
+
+/// Verifies that an integrity proof is signed by one of the trusted signers, and returns the
+/// signer.
+///
+/// This only checks the TEE attestation of the execution. The proof itself must be verified
+/// separately, for example with [`crate::Groth16Verifier`] or [`crate::PlonkVerifier`].
+///
+/// # Arguments
+///
+/// * `tee_proof` - The integrity proof, as found in the `tee_proof` field of a proof from the
+///   prover network.
+/// * `vkey_hash` - The verifying key hash of the program, as returned by
+///   `HashableKey::bytes32_raw`.
+/// * `public_values` - The public values of the execution.
+/// * `signers` - The trusted TEE signers.
+pub fn verify_tee_proof(
+    tee_proof: &[u8],
+    vkey_hash: &[u8; 32],
+    public_values: &[u8],
+    signers: &[TeeSigner],
+) -> Result<TeeSigner, TeeError> {
+    if signers.is_empty() {
+        return Err(TeeError::NoSigners);
+    }
+
+    let signer = recover_tee_signer(tee_proof, vkey_hash, public_values)?;
+    if signers.contains(&signer) {
+        Ok(signer)
+    } else {
+        Err(TeeError::UnknownSigner(signer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use k256::ecdsa::SigningKey;
+
+    use super::*;
+
+    /// Signs an integrity proof, and returns it with the address of its signer.
+    fn sign(key: &SigningKey, vkey_hash: &[u8; 32], public_values: &[u8]) -> ([u8; 69], TeeSigner) {
+        let digest = tee_message_digest(vkey_hash, public_values);
+        let (signature, recovery_id) = key.sign_prehash_recoverable(&digest).unwrap();
+
+        let mut proof = [0; TEE_PROOF_LEN];
+        proof[4] = recovery_id.to_byte() + 27;
+        proof[5..].copy_from_slice(&signature.to_bytes());
+
+        let public_key = key.verifying_key().to_encoded_point(false);
+        let mut address = [0; 20];
+        address.copy_from_slice(&Keccak256::digest(&public_key.as_bytes()[1..])[12..]);
+        (proof, address)
+    }
+
+    #[test]
+    fn test_verify_tee_proof() {
+        let key = SigningKey::from_slice(&[7; 32]).unwrap();
+        let vkey_hash = [1; 32];
+        let public_values = b"public values";
+        let (proof, signer) = sign(&key, &vkey_hash, public_values);
+
+        assert_eq!(verify_tee_proof(&proof, &vkey_hash, public_values, &[signer]), Ok(signer));
+
+        // A proof of other public values recovers another signer.
+        assert!(matches!(
+            verify_tee_proof(&proof, &vkey_hash, b"other values", &[signer]),
+            Err(TeeError::UnknownSigner(_))
+        ));
+        assert_eq!(
+            verify_tee_proof(&proof, &vkey_hash, public_values, &[[2; 20]]),
+            Err(TeeError::UnknownSigner(signer))
+        );
+        assert_eq!(
+            verify_tee_proof(&proof, &vkey_hash, public_values, &[]),
+            Err(TeeError::NoSigners)
+        );
+    }
+
+    #[test]
+    fn test_malformed_tee_proof() {
+        let key = SigningKey::from_slice(&[7; 32]).unwrap();
+        let (mut proof, _) = sign(&key, &[1; 32], b"");
+
+        assert_eq!(
+            recover_tee_signer(&proof[..68], &[1; 32], b""),
+            Err(TeeError::InvalidLength(68))
+        );
+        proof[4] = 3;
+        assert_eq!(recover_tee_signer(&proof, &[1; 32], b""), Err(TeeError::InvalidRecoveryId(3)));
+        proof[4] = 27;
+        proof[5..].fill(0);
+        assert_eq!(recover_tee_signer(&proof, &[1; 32], b""), Err(TeeError::InvalidSignature));
+    }
+}
@@ -194,6 +194,25 @@ fn test_groth16_verifier(#[case] elf: &[u8], #[case] groth16_elf: &[u8]) {
     crate::Groth16Verifier::verify(&proof, &public_inputs, &vkey_hash, &crate::GROTH16_VK_BYTES)
         .expect("Groth16 proof is invalid");
 
+    // Verify it against the digest of the public inputs, with whichever hash the program uses.
+    let sha256_hash = crate::hash_public_inputs(&public_inputs);
+    let blake3_hash = crate::hash_public_inputs_with_fn(&public_inputs, crate::blake3_hash);
+    crate::Groth16Verifier::verify_prehashed(
+        &proof,
+        &sha256_hash,
+        &vkey_hash,
+        &crate::GROTH16_VK_BYTES,
+    )
+    .or_else(|_| {
+        crate::Groth16Verifier::verify_prehashed(
+            &proof,
+            &blake3_hash,
+            &vkey_hash,
+            &crate::GROTH16_VK_BYTES,
+        )
+    })
+    .expect("Prehashed Groth16 proof is invalid");
+
     // Now we should do the verifaction in the VM.
     let mut stdin = MONEROCHANStdin::new();
     stdin.write_slice(&proof);
@@ -273,6 +292,31 @@ fn test_plonk_verifier(#[case] elf: &[u8], #[case] plonk_elf: &[u8]) {
     crate::PlonkVerifier::verify(&proof, &public_inputs, &vkey_hash, &crate::PLONK_VK_BYTES)
         .expect("Plonk proof is invalid");
 
+    // Verify it again with the public inputs laid out explicitly.
+    let vkey_hash_bytes = crate::decode_monerochan_vkey_hash(&vkey_hash).unwrap();
+    let sha256_inputs = [vkey_hash_bytes, crate::hash_public_inputs(&public_inputs)];
+    let blake3_inputs = [
+        vkey_hash_bytes,
+        crate::hash_public_inputs_with_fn(&public_inputs, crate::blake3_hash),
+    ];
+    crate::PlonkVerifier::verify_with_public_inputs(&proof, &sha256_inputs, &crate::PLONK_VK_BYTES)
+        .or_else(|_| {
+            crate::PlonkVerifier::verify_with_public_inputs(
+                &proof,
+                &blake3_inputs,
+                &crate::PLONK_VK_BYTES,
+            )
+        })
+        .expect("Plonk proof is invalid");
+
+    // A public input is missing.
+    let result = crate::PlonkVerifier::verify_with_public_inputs(
+        &proof,
+        &sha256_inputs[..1],
+        &crate::PLONK_VK_BYTES,
+    );
+    assert!(matches!(result, Err(PlonkError::InvalidWitness)));
+
     // Now we should do the verifaction in the VM.
     let mut stdin = MONEROCHANStdin::new();
     stdin.write_slice(&proof);
@@ -282,6 +326,28 @@ fn test_plonk_verifier(#[case] elf: &[u8], #[case] plonk_elf: &[u8]) {
     let _ = client.execute(plonk_elf, &stdin).run().unwrap();
 }
 
+#[test]
+fn test_public_inputs() {
+    let vkey_hash = format!("0x00{}", "11".repeat(31));
+    let (vkey_input, public_values_input) =
+        crate::public_inputs(&vkey_hash, b"public values").unwrap();
+    let vkey_hash_bytes = crate::decode_monerochan_vkey_hash(&vkey_hash).unwrap();
+    assert_eq!(
+        [vkey_input, public_values_input],
+        crate::bn254_public_values(&vkey_hash_bytes, b"public values")
+    );
+
+    // The vkey hash is not in the Bn254 field.
+    let result = crate::public_inputs(&format!("0x{}", "ff".repeat(32)), b"public values");
+    assert!(matches!(result, Err(Error::InvalidProgramVkeyHash)));
+}
+
+#[test]
+fn test_plonk_num_public_inputs() {
+    // The vkey hash and the committed values digest.
+    assert_eq!(crate::PlonkVerifier::num_public_inputs(&crate::PLONK_VK_BYTES).unwrap(), 2);
+}
+
 #[rstest]
 #[case(FIBONACCI_ELF)]
 #[case(FIBONACCI_BLAKE3_ELF)]
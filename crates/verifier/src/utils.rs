@@ -42,8 +42,39 @@ pub fn bn254_public_values(monerochan_vkey_hash: &[u8; 32], monerochan_public_in
     [vkey_hash, committed_values_digest]
 }
 
+/// Recomputes the pair of public inputs the Plonk and Groth16 verifiers, and the onchain gateway
+/// contracts, check a proof against: the monerochan vkey hash, and the digest of the public values.
+///
+/// This mirrors `MONEROCHANPublicInputs.publicInputs` of the exported Solidity verifier, so
+/// integrators can check what their contract will see before submitting a transaction. The digest
+/// is the Sha256 one the contracts use; programs committing with Blake3 are hashed with
+/// [`hash_public_inputs_with_fn`] instead.
+///
+/// # Arguments
+///
+/// * `monerochan_vkey_hash` - The monerochan vkey hash, as returned by `vk.bytes32()`.
+/// * `monerochan_public_inputs` - The public values committed by the program.
+///
+/// # Errors
+///
+/// [`Error::InvalidProgramVkeyHash`] if the vkey hash is malformed or not in the Bn254 field, in
+/// which case the contracts would reject the proof as well.
+pub fn public_inputs(
+    monerochan_vkey_hash: &str,
+    monerochan_public_inputs: &[u8],
+) -> Result<(Fr, Fr), Error> {
+    let vkey_hash = decode_monerochan_vkey_hash(monerochan_vkey_hash)?;
+    let vkey_hash = Fr::from_slice(&vkey_hash).map_err(|_| Error::InvalidProgramVkeyHash)?;
+    // The digest is truncated to 253 bits, so it is always in the field.
+    let committed_values_digest =
+        Fr::from_slice(&hash_public_inputs(monerochan_public_inputs)).map_err(Error::Field)?;
+    Ok((vkey_hash, committed_values_digest))
+}
+
 /// Decodes the monerochan vkey hash from the string from a call to `vk.bytes32`.
 pub fn decode_monerochan_vkey_hash(monerochan_vkey_hash: &str) -> Result<[u8; 32], Error> {
-    let bytes = hex::decode(&monerochan_vkey_hash[2..]).map_err(|_| Error::InvalidProgramVkeyHash)?;
-    bytes.try_into().map_err(|_| Error::InvalidProgramVkeyHash)
+    let hex = monerochan_vkey_hash.get(2..).ok_or(Error::InvalidProgramVkeyHash)?;
+    let mut bytes = [0u8; 32];
+    hex::decode_to_slice(hex, &mut bytes).map_err(|_| Error::InvalidProgramVkeyHash)?;
+    Ok(bytes)
 }
@@ -1,18 +1,23 @@
-use crate::syscalls::sys_alloc_aligned;
+use crate::syscalls::bump_alloc_aligned;
 use core::alloc::{GlobalAlloc, Layout};
 
 /// A simple heap allocator.
 ///
 /// Allocates memory from left to right, without any deallocation.
-struct SimpleAlloc;
+pub struct BumpAlloc;
 
-unsafe impl GlobalAlloc for SimpleAlloc {
+unsafe impl GlobalAlloc for BumpAlloc {
     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
-        sys_alloc_aligned(layout.size(), layout.align())
+        bump_alloc_aligned(layout.size(), layout.align())
     }
 
     unsafe fn dealloc(&self, _: *mut u8, _: Layout) {}
 }
 
-#[global_allocator]
-static HEAP: SimpleAlloc = SimpleAlloc;
+/// The allocator installed by `entrypoint!(main, allocator = bump)`.
+#[doc(hidden)]
+pub type Allocator = BumpAlloc;
+
+/// Initializes the allocator. The bump allocator needs no initialization.
+#[doc(hidden)]
+pub fn init() {}
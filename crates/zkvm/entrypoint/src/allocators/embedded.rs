@@ -1,15 +1,32 @@
 use crate::{
-    syscalls::MAX_MEMORY, EMBEDDED_RESERVED_INPUT_REGION_SIZE, EMBEDDED_RESERVED_INPUT_START,
+    syscalls::{heap_bounds, MAX_MEMORY},
+    EMBEDDED_RESERVED_INPUT_REGION_SIZE, EMBEDDED_RESERVED_INPUT_START,
 };
 use alloc::alloc::{GlobalAlloc, Layout};
+use core::{fmt, ptr::NonNull};
 use critical_section::RawRestoreState;
 use embedded_alloc::TlsfHeap as Heap;
 
-#[global_allocator]
-static HEAP: EmbeddedAlloc = EmbeddedAlloc;
-
 pub static INNER_HEAP: Heap = Heap::empty();
 
+/// The maximum number of heap regions that can be added with [`add_heap_region`].
+pub const MAX_HEAP_REGIONS: usize = 8;
+
+/// The heaps of the regions added with [`add_heap_region`].
+static REGION_HEAPS: [Heap; MAX_HEAP_REGIONS] = [const { Heap::empty() }; MAX_HEAP_REGIONS];
+
+/// The bounds of the regions added with [`add_heap_region`], in the order they were added.
+static mut REGIONS: [(usize, usize); MAX_HEAP_REGIONS] = [(0, 0); MAX_HEAP_REGIONS];
+
+/// The number of regions added with [`add_heap_region`].
+static mut NUM_REGIONS: usize = 0;
+
+/// The bounds of the default heap, set by [`init`].
+static mut DEFAULT_HEAP: (usize, usize) = (0, 0);
+
+/// Whether [`init`] was called, that is whether the program uses this allocator.
+static mut ACTIVE: bool = false;
+
 struct CriticalSection;
 critical_section::set_impl!(CriticalSection);
 
@@ -19,27 +36,194 @@ unsafe impl critical_section::Impl for CriticalSection {
     unsafe fn release(_token: RawRestoreState) {}
 }
 
+/// Initializes the allocator, before the first allocation of the program.
+///
+/// This is called by the entrypoint of programs using this allocator, and does nothing when called
+/// again.
 pub fn init() {
-    extern "C" {
-        // https://lld.llvm.org/ELF/linker_script.html#sections-command
-        static _end: u8;
+    // SAFETY: The VM is single threaded.
+    if unsafe { ACTIVE } {
+        return;
     }
-    let heap_pos: usize = unsafe { (&_end) as *const u8 as usize };
+    unsafe { ACTIVE = true };
+
+    let (heap_pos, heap_end) = heap_bounds();
     assert!(heap_pos <= EMBEDDED_RESERVED_INPUT_START);
-    // The heap size that is available for the program is the total memory minus the reserved input
-    // region and the heap position.
-    let heap_size: usize = EMBEDDED_RESERVED_INPUT_START - heap_pos;
+    // The heap size that is available for the program is the configured heap, minus the reserved
+    // input region.
+    let heap_size: usize = heap_end.min(EMBEDDED_RESERVED_INPUT_START) - heap_pos;
+    // SAFETY: The VM is single threaded.
+    unsafe { DEFAULT_HEAP = (heap_pos, heap_pos + heap_size) };
     unsafe { INNER_HEAP.init(heap_pos, heap_size) };
 }
 
-struct EmbeddedAlloc;
+/// Whether the program uses this allocator.
+///
+/// Inputs are then read into the reserved input region rather than allocated on the heap.
+pub fn is_active() -> bool {
+    // SAFETY: The VM is single threaded.
+    unsafe { ACTIVE }
+}
+
+/// The allocator installed by `entrypoint!(main, allocator = embedded)`.
+#[doc(hidden)]
+pub type Allocator = EmbeddedAlloc;
+
+/// An error returned by [`add_heap_region`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeapRegionError {
+    /// [`MAX_HEAP_REGIONS`] regions were already added.
+    TooManyRegions,
+    /// The region is empty, or extends past [`MAX_MEMORY`].
+    InvalidBounds,
+    /// The region overlaps the default heap, the reserved input region, or another region.
+    Overlap,
+}
+
+impl fmt::Display for HeapRegionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooManyRegions => {
+                write!(f, "at most {MAX_HEAP_REGIONS} heap regions can be added")
+            }
+            Self::InvalidBounds => write!(f, "the heap region is empty or exceeds the memory"),
+            Self::Overlap => write!(f, "the heap region overlaps the memory of another region"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for HeapRegionError {}
+
+/// An error returned by [`try_alloc`] when no heap region can fit an allocation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocError {
+    /// The layout of the allocation that failed.
+    pub layout: Layout,
+}
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "out of memory allocating {} bytes aligned to {}",
+            self.layout.size(),
+            self.layout.align()
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AllocError {}
+
+/// Adds the memory from `start` to `start + size` to the heap.
+///
+/// Allocations are served from the default heap first, then from the added regions in the order
+/// they were added. The regions must not overlap the default heap, the reserved input region, or
+/// each other.
+///
+/// # Safety
+///
+/// The region must not be used by anything else for the rest of the execution, such as the
+/// program, its stack, or memory written by a syscall.
+pub unsafe fn add_heap_region(start: usize, size: usize) -> Result<(), HeapRegionError> {
+    let end = match start.checked_add(size) {
+        Some(end) if size > 0 && end <= MAX_MEMORY => end,
+        _ => return Err(HeapRegionError::InvalidBounds),
+    };
+
+    // SAFETY: The VM is single threaded.
+    let (num_regions, default_heap) = unsafe { (NUM_REGIONS, DEFAULT_HEAP) };
+    if num_regions == MAX_HEAP_REGIONS {
+        return Err(HeapRegionError::TooManyRegions);
+    }
+    let overlaps =
+        |(other_start, other_end): (usize, usize)| start < other_end && other_start < end;
+    let input_region = (
+        EMBEDDED_RESERVED_INPUT_START,
+        EMBEDDED_RESERVED_INPUT_START + EMBEDDED_RESERVED_INPUT_REGION_SIZE,
+    );
+    // SAFETY: The VM is single threaded.
+    let regions = unsafe { &*core::ptr::addr_of!(REGIONS) };
+    if overlaps(default_heap) ||
+        overlaps(input_region) ||
+        regions[..num_regions].iter().any(|region| overlaps(*region))
+    {
+        return Err(HeapRegionError::Overlap);
+    }
+
+    // SAFETY: The VM is single threaded, and the caller guarantees the region is unused.
+    unsafe {
+        REGION_HEAPS[num_regions].init(start, size);
+        REGIONS[num_regions] = (start, end);
+        NUM_REGIONS = num_regions + 1;
+    }
+    Ok(())
+}
+
+/// Allocates memory for `layout`, returning an error instead of aborting the execution when no
+/// heap region can fit it.
+///
+/// The memory can be freed with [`alloc::alloc::dealloc`]. Collections can recover from exhaustion
+/// the same way, through `try_reserve`.
+///
+/// # Panics
+///
+/// Panics if `layout` has a size of zero.
+pub fn try_alloc(layout: Layout) -> Result<NonNull<u8>, AllocError> {
+    assert!(layout.size() != 0, "zero-sized allocation");
+    // SAFETY: `alloc` returns null instead of aborting on exhaustion.
+    NonNull::new(unsafe { EmbeddedAlloc.alloc(layout) }).ok_or(AllocError { layout })
+}
+
+/// Allocates zeroed memory for `layout`, like [`try_alloc`].
+///
+/// # Panics
+///
+/// Panics if `layout` has a size of zero.
+pub fn try_alloc_zeroed(layout: Layout) -> Result<NonNull<u8>, AllocError> {
+    assert!(layout.size() != 0, "zero-sized allocation");
+    // SAFETY: `alloc_zeroed` returns null instead of aborting on exhaustion.
+    NonNull::new(unsafe { EmbeddedAlloc.alloc_zeroed(layout) }).ok_or(AllocError { layout })
+}
+
+/// Returns the heap of the added region that contains `ptr`, if any.
+fn region_heap(ptr: *mut u8) -> Option<&'static Heap> {
+    let addr = ptr as usize;
+    // SAFETY: The VM is single threaded.
+    let (regions, num_regions) = unsafe { (&*core::ptr::addr_of!(REGIONS), NUM_REGIONS) };
+    regions[..num_regions]
+        .iter()
+        .position(|(start, end)| (*start..*end).contains(&addr))
+        .map(|index| &REGION_HEAPS[index])
+}
+
+/// An allocator backed by a TLSF heap, which reuses the memory it frees.
+///
+/// Only programs using this allocator may call [`try_alloc`], [`try_alloc_zeroed`] and
+/// [`add_heap_region`].
+pub struct EmbeddedAlloc;
 
 unsafe impl GlobalAlloc for EmbeddedAlloc {
     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
-        INNER_HEAP.alloc(layout)
+        let ptr = INNER_HEAP.alloc(layout);
+        if !ptr.is_null() {
+            return ptr;
+        }
+        // SAFETY: The VM is single threaded.
+        let num_regions = unsafe { NUM_REGIONS };
+        REGION_HEAPS[..num_regions]
+            .iter()
+            .map(|heap| heap.alloc(layout))
+            .find(|ptr| !ptr.is_null())
+            .unwrap_or(core::ptr::null_mut())
     }
 
     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if let Some(heap) = region_heap(ptr) {
+            heap.dealloc(ptr, layout);
+            return;
+        }
         // Deallocating reserved input region memory is not allowed.
         if (ptr as usize) >= EMBEDDED_RESERVED_INPUT_START {
             return;
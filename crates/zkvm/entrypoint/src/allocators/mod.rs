@@ -1,12 +1,22 @@
 //! Allocators for the MONEROCHAN zkVM.
 //!
-//! The `embedded` allocator takes precedence if enabled.
-
-#[cfg(not(feature = "embedded"))]
-mod bump;
+//! The program selects its allocator with the `entrypoint!` macro, as
+//! `entrypoint!(main, allocator = bump)` or `entrypoint!(main, allocator = embedded)`. Without
+//! the argument, the `embedded` allocator is used if the `embedded` feature is enabled, and the
+//! `bump` allocator otherwise.
+//!
+//! The `embedded` allocator can serve allocations from several disjoint heap regions, see
+//! [`embedded::add_heap_region`], and report exhaustion to the program instead of aborting the
+//! execution, see [`embedded::try_alloc`].
 
-#[cfg(feature = "embedded")]
+pub mod bump;
 pub mod embedded;
 
+/// The allocator of programs which do not select one.
+#[doc(hidden)]
 #[cfg(feature = "embedded")]
-pub use embedded::init;
+pub use embedded as default;
+/// The allocator of programs which do not select one.
+#[doc(hidden)]
+#[cfg(not(feature = "embedded"))]
+pub use bump as default;
@@ -0,0 +1,157 @@
+//! The C ABI of the runtime, for programs written in C or C++.
+//!
+//! Such a program is a Rust crate declaring its [`entrypoint!`](crate::entrypoint), which calls
+//! into the C or C++ sources compiled by its build script with the `cc` crate, as set up by
+//! `cargo monerochan new --language c`. The functions are declared by the `monerochan.h` header of
+//! that template.
+//!
+//! The C library is not linked into the program. In the zkVM, the runtime provides `malloc`,
+//! `calloc`, `realloc` and `free`, served by the allocator of the program, and the Rust toolchain
+//! provides `memcpy`, `memmove`, `memset` and `memcmp`.
+
+use crate::{
+    read_vec_raw,
+    syscalls::{syscall_halt, syscall_record_outcome, syscall_reject, syscall_write},
+    ReadVecResult,
+};
+use monerochan_primitives::consts::fd::{FD_HINT, FD_PUBLIC_VALUES};
+
+/// Reads the next buffer of the input stream, and writes its length to `len`.
+///
+/// Returns null if the input stream is exhausted. The buffer is owned by the runtime, and must not
+/// be freed.
+///
+/// # Safety
+///
+/// `len` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_read(len: *mut usize) -> *mut u8 {
+    let ReadVecResult { ptr, len: read, .. } = read_vec_raw();
+    *len = read;
+    ptr
+}
+
+/// Commits `len` bytes to the public values stream.
+///
+/// # Safety
+///
+/// `buf` must be valid for reads of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_commit(buf: *const u8, len: usize) {
+    syscall_write(FD_PUBLIC_VALUES, buf, len);
+}
+
+/// Writes `len` bytes to the hint stream.
+///
+/// # Safety
+///
+/// `buf` must be valid for reads of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_hint(buf: *const u8, len: usize) {
+    syscall_write(FD_HINT, buf, len);
+}
+
+/// Writes `len` bytes to a file descriptor, 1 for stdout and 2 for stderr.
+///
+/// # Safety
+///
+/// `buf` must be valid for reads of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn monerochan_write(fd: u32, buf: *const u8, len: usize) {
+    syscall_write(fd, buf, len);
+}
+
+/// Halts the program with the given exit code, committing the public values written so far.
+#[no_mangle]
+pub extern "C" fn monerochan_exit(exit_code: u8) -> ! {
+    syscall_halt(exit_code)
+}
+
+/// Records the outcome of the program in a header starting its public values, which must be done
+/// before anything is committed. See `monerochan_lib::io::record_outcome`.
+#[no_mangle]
+pub extern "C" fn monerochan_record_outcome() {
+    syscall_record_outcome();
+}
+
+/// Rejects the input of the program with an error code, and halts it successfully, recording the
+/// rejection in the header of its public values. See `monerochan_lib::io::reject`.
+#[no_mangle]
+pub extern "C" fn monerochan_reject(code: u32) -> ! {
+    syscall_reject(code)
+}
+
+/// The allocation functions of the C library, served by the allocator of the program.
+///
+/// Each block is prefixed by a header holding its size, so that `free` and `realloc` can recover
+/// the layout of the allocation.
+#[cfg(target_os = "zkvm")]
+mod malloc {
+    use alloc::alloc::{alloc, alloc_zeroed, dealloc, realloc as grow, Layout};
+    use core::ptr;
+
+    /// The alignment of the blocks, that of `max_align_t`, which is also the size of the header.
+    const ALIGN: usize = 16;
+
+    /// The layout of the allocation of a block of `size` bytes.
+    fn layout(size: usize) -> Option<Layout> {
+        Layout::from_size_align(size.checked_add(ALIGN)?, ALIGN).ok()
+    }
+
+    /// Writes the header of an allocation, and returns the block following it.
+    unsafe fn block(allocation: *mut u8, size: usize) -> *mut u8 {
+        if allocation.is_null() {
+            return allocation;
+        }
+        allocation.cast::<usize>().write(size);
+        allocation.add(ALIGN)
+    }
+
+    /// Returns the allocation of a block, and the size of the block.
+    unsafe fn allocation(block: *mut u8) -> (*mut u8, usize) {
+        let allocation = block.sub(ALIGN);
+        (allocation, allocation.cast::<usize>().read())
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn malloc(size: usize) -> *mut u8 {
+        match layout(size) {
+            Some(layout) => block(alloc(layout), size),
+            None => ptr::null_mut(),
+        }
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn calloc(count: usize, size: usize) -> *mut u8 {
+        match count.checked_mul(size).and_then(|size| Some((size, layout(size)?))) {
+            Some((size, layout)) => block(alloc_zeroed(layout), size),
+            None => ptr::null_mut(),
+        }
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn realloc(block_ptr: *mut u8, size: usize) -> *mut u8 {
+        if block_ptr.is_null() {
+            return malloc(size);
+        }
+        if size == 0 {
+            free(block_ptr);
+            return ptr::null_mut();
+        }
+        let Some(new_layout) = layout(size) else {
+            return ptr::null_mut();
+        };
+        let (allocation, old_size) = allocation(block_ptr);
+        let old_layout = layout(old_size).expect("the block was allocated with this layout");
+        block(grow(allocation, old_layout, new_layout.size()), size)
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn free(block_ptr: *mut u8) {
+        if block_ptr.is_null() {
+            return;
+        }
+        let (allocation, size) = allocation(block_ptr);
+        dealloc(allocation, layout(size).expect("the block was allocated with this layout"));
+    }
+}
@@ -1,11 +1,13 @@
-#[cfg(all(target_os = "zkvm", feature = "embedded"))]
-use syscalls::MAX_MEMORY;
+//! The runtime of programs running in the MONEROCHAN zkVM.
+//!
+//! Programs are `no_std` compatible, with the `alloc` crate, when the `std` feature is disabled.
+//! The runtime then provides the panic handler of the program, which writes the panic message to
+//! stderr and halts with exit code 1, so no dependency of the program may link `std`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
 
 #[cfg(target_os = "zkvm")]
-use {
-    cfg_if::cfg_if,
-    syscalls::{syscall_hint_len, syscall_hint_read},
-};
+use syscalls::{syscall_hint_len, syscall_hint_read, MAX_MEMORY};
 
 extern crate alloc;
 
@@ -14,6 +16,9 @@ pub mod allocators;
 
 pub mod syscalls;
 
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
 #[cfg(feature = "lib")]
 pub mod io {
     pub use monerochan_lib::io::*;
@@ -27,21 +32,26 @@ pub mod lib {
 #[cfg(all(target_os = "zkvm", feature = "libm"))]
 mod libm;
 
+#[cfg(all(target_os = "zkvm", feature = "softfloat"))]
+mod softfloat;
+
 /// The number of 32 bit words that the public values digest is composed of.
 pub const PV_DIGEST_NUM_WORDS: usize = 8;
 pub const POSEIDON_NUM_WORDS: usize = 8;
 
+pub use monerochan_primitives::consts::STACK_GUARD_SIZE;
+
 /// Size of the reserved region for input values with the embedded allocator.
-#[cfg(all(target_os = "zkvm", feature = "embedded"))]
+#[cfg(target_os = "zkvm")]
 pub(crate) const EMBEDDED_RESERVED_INPUT_REGION_SIZE: usize = 1024 * 1024 * 1024;
 
 /// Start of the reserved region for inputs with the embedded allocator.
-#[cfg(all(target_os = "zkvm", feature = "embedded"))]
+#[cfg(target_os = "zkvm")]
 pub(crate) const EMBEDDED_RESERVED_INPUT_START: usize =
     MAX_MEMORY - EMBEDDED_RESERVED_INPUT_REGION_SIZE;
 
 /// Pointer to the current position in the reserved region for inputs with the embedded allocator.
-#[cfg(all(target_os = "zkvm", feature = "embedded"))]
+#[cfg(target_os = "zkvm")]
 static mut EMBEDDED_RESERVED_INPUT_PTR: usize = EMBEDDED_RESERVED_INPUT_START;
 
 #[repr(C)]
@@ -55,12 +65,9 @@ pub struct ReadVecResult {
 ///
 /// The buffer is read into uninitialized memory.
 ///
-/// When the `bump` feature is enabled, the buffer is read into a new buffer allocated by the
-/// program.
-///
-/// When the `embedded` feature is enabled, the buffer is read into the reserved input region.
+/// With the `bump` allocator, the buffer is read into a new buffer allocated by the program.
 ///
-/// When there is no allocator selected, the program will fail to compile.
+/// With the `embedded` allocator, the buffer is read into the reserved input region.
 ///
 /// If the input stream is exhausted, the failed flag will be returned as true. In this case, the
 /// other outputs from the function are likely incorrect, which is fine as `monerochan-lib` always panics
@@ -77,54 +84,45 @@ pub extern "C" fn read_vec_raw() -> ReadVecResult {
 
         // If the length is u32::MAX, then the input stream is exhausted.
         if len == usize::MAX {
-            return ReadVecResult { ptr: std::ptr::null_mut(), len: 0, capacity: 0 };
+            return ReadVecResult { ptr: core::ptr::null_mut(), len: 0, capacity: 0 };
         }
 
         // Round up to multiple of 4 for whole-word alignment.
         let capacity = (len + 3) / 4 * 4;
 
-        cfg_if! {
-            if #[cfg(feature = "embedded")] {
-                // Get the existing pointer in the reserved region which is the start of the vec.
-                // Increment the pointer by the capacity to set the new pointer to the end of the vec.
-                let ptr = unsafe { EMBEDDED_RESERVED_INPUT_PTR };
-                if ptr.saturating_add(capacity) > MAX_MEMORY {
-                    panic!("Input region overflowed.")
-                }
+        if allocators::embedded::is_active() {
+            // Get the existing pointer in the reserved region which is the start of the vec.
+            // Increment the pointer by the capacity to set the new pointer to the end of the vec.
+            let ptr = unsafe { EMBEDDED_RESERVED_INPUT_PTR };
+            if ptr.saturating_add(capacity) > MAX_MEMORY {
+                panic!("Input region overflowed.")
+            }
 
-                // SAFETY: The VM is single threaded.
-                unsafe { EMBEDDED_RESERVED_INPUT_PTR += capacity };
+            // SAFETY: The VM is single threaded.
+            unsafe { EMBEDDED_RESERVED_INPUT_PTR += capacity };
 
-                // Read the vec into uninitialized memory. The syscall assumes the memory is
-                // uninitialized, which is true because the input ptr is incremented manually on each
-                // read.
-                syscall_hint_read(ptr as *mut u8, len);
+            // Read the vec into uninitialized memory. The syscall assumes the memory is
+            // uninitialized, which is true because the input ptr is incremented manually on each
+            // read.
+            syscall_hint_read(ptr as *mut u8, len);
 
-                // Return the result.
-                ReadVecResult {
-                    ptr: ptr as *mut u8,
-                    len,
-                    capacity,
-                }
-            } else {
-                // Allocate a buffer of the required length that is 4 byte aligned.
-                let layout = std::alloc::Layout::from_size_align(capacity, 4).expect("vec is too large");
-
-                // SAFETY: The layout was made through the checked constructor.
-                let ptr = unsafe { std::alloc::alloc(layout) };
-
-                // Read the vec into uninitialized memory. The syscall assumes the memory is
-                // uninitialized, which is true because the bump allocator does not dealloc, so a new
-                // alloc is always fresh.
-                syscall_hint_read(ptr as *mut u8, len);
-
-                // Return the result.
-                ReadVecResult {
-                    ptr: ptr as *mut u8,
-                    len,
-                    capacity,
-                }
-            }
+            // Return the result.
+            ReadVecResult { ptr: ptr as *mut u8, len, capacity }
+        } else {
+            // Allocate a buffer of the required length that is 4 byte aligned.
+            let layout =
+                core::alloc::Layout::from_size_align(capacity, 4).expect("vec is too large");
+
+            // SAFETY: The layout was made through the checked constructor.
+            let ptr = unsafe { alloc::alloc::alloc(layout) };
+
+            // Read the vec into uninitialized memory. The syscall assumes the memory is
+            // uninitialized, which is true because the bump allocator does not dealloc, so a new
+            // alloc is always fresh.
+            syscall_hint_read(ptr as *mut u8, len);
+
+            // Return the result.
+            ReadVecResult { ptr: ptr as *mut u8, len, capacity }
         }
     }
 }
@@ -154,12 +152,20 @@ mod zkvm {
         }
     }
 
+    /// The public values committed since the program started recording its outcome, written when
+    /// it halts.
+    pub static mut PUBLIC_VALUES_BUFFER: Option<alloc::vec::Vec<u8>> = None;
+
+    /// Whether the program committed public values before recording its outcome, which would
+    /// put the header recording it in the middle of the public values.
+    pub static mut PUBLIC_VALUES_WRITTEN: bool = false;
+
+    /// The error code the program rejected its input with, if it did.
+    pub static mut REJECTION: Option<u32> = None;
+
     #[no_mangle]
     unsafe extern "C" fn __start() {
         {
-            #[cfg(all(target_os = "zkvm", feature = "embedded"))]
-            crate::allocators::init();
-
             cfg_if::cfg_if! {
                 if #[cfg(feature = "blake3")] {
                     PUBLIC_VALUES_HASHER = Some(blake3::Hasher::new());
@@ -183,11 +189,24 @@ mod zkvm {
         syscall_halt(0);
     }
 
-    static STACK_TOP: u32 = 0x0020_0400;
-
     core::arch::global_asm!(include_str!("memset.s"));
     core::arch::global_asm!(include_str!("memcpy.s"));
 
+    // The default memory layout. `monerochan-build` overrides these symbols with `--defsym` when a
+    // custom layout is configured, and `entrypoint!` overrides the stack top when the program
+    // declares a stack size. A heap start or end of zero means the end of the program and
+    // `MAX_MEMORY` respectively.
+    core::arch::global_asm!(
+        r#"
+    .weak _monerochan_stack_top;
+    .set _monerochan_stack_top, 0x00200400;
+    .weak _monerochan_heap_start;
+    .set _monerochan_heap_start, 0;
+    .weak _monerochan_heap_end;
+    .set _monerochan_heap_end, 0;
+    "#
+    );
+
     core::arch::global_asm!(
         r#"
     .section .text._start;
@@ -197,11 +216,9 @@ mod zkvm {
         .option norelax;
         la gp, __global_pointer$;
         .option pop;
-        la sp, {0}
-        lw sp, 0(sp)
+        la sp, _monerochan_stack_top;
         call __start;
-    "#,
-        sym STACK_TOP
+    "#
     );
 
     pub fn zkvm_getrandom_v2(s: &mut [u8]) -> Result<(), getrandom_v2::Error> {
@@ -225,17 +242,202 @@ mod zkvm {
 
         Ok(())
     }
+
+    /// Writes to stderr.
+    pub(crate) struct Stderr;
+
+    impl core::fmt::Write for Stderr {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            crate::syscalls::sys_write(2, s.as_ptr(), s.len());
+            Ok(())
+        }
+    }
+
+    /// The panic handler of `no_std` programs, which writes the panic message to stderr and halts
+    /// with exit code 1, as `std` does in the zkVM.
+    #[cfg(not(feature = "std"))]
+    #[panic_handler]
+    fn panic(info: &core::panic::PanicInfo) -> ! {
+        use core::fmt::Write;
+
+        let _ = writeln!(Stderr, "{info}");
+        syscall_halt(monerochan_primitives::consts::PANIC_EXIT_CODE)
+    }
+}
+
+/// Embeds the version and the git commit of the program in the `.monerochan_metadata` section of
+/// its ELF, to be read on the host with `monerochan::ProgramMetadata::from_elf`.
+///
+/// The version defaults to the version of the crate invoking the macro. Both values must expand to
+/// string literals, such as `env!("GIT_COMMIT")` set by a build script:
+///
+/// ```ignore
+/// monerochan_runtime::program_metadata!(commit = env!("GIT_COMMIT"));
+/// monerochan_runtime::program_metadata!(version = "1.2.0", commit = "4f2a9c1");
+/// ```
+///
+/// The section is not loaded into the memory of the program, so it does not change its
+/// verification key.
+#[macro_export]
+macro_rules! program_metadata {
+    () => {
+        $crate::program_metadata!(version = env!("CARGO_PKG_VERSION"), commit = "");
+    };
+    (commit = $commit:expr $(,)?) => {
+        $crate::program_metadata!(version = env!("CARGO_PKG_VERSION"), commit = $commit);
+    };
+    (version = $version:expr, commit = $commit:expr $(,)?) => {
+        // The format version, then the version and the commit as NUL terminated strings.
+        #[cfg(target_os = "zkvm")]
+        ::core::arch::global_asm!(
+            ".pushsection .monerochan_metadata,\"\",@progbits",
+            ".byte 1",
+            concat!(".asciz \"", $version, "\""),
+            concat!(".asciz \"", $commit, "\""),
+            ".popsection",
+        );
+    };
+}
+
+/// Halts the program with an exit code, which tells the host why the program failed, see
+/// [`ensure!`].
+///
+/// The exit code 0 is that of a successful execution, and 1 that of a panic, so the exit code must
+/// be at least 2. Outside of the zkVM, this panics instead.
+#[track_caller]
+pub fn abort(exit_code: u8) -> ! {
+    abort_with(exit_code, None)
+}
+
+/// Halts the program with an exit code, after writing a message to stderr, see [`ensure!`].
+#[doc(hidden)]
+#[track_caller]
+pub fn abort_with(exit_code: u8, message: Option<core::fmt::Arguments<'_>>) -> ! {
+    assert!(
+        exit_code > monerochan_primitives::consts::PANIC_EXIT_CODE,
+        "the exit code {exit_code} is reserved, the exit codes of the program start at 2"
+    );
+
+    #[cfg(target_os = "zkvm")]
+    {
+        if let Some(message) = message {
+            use core::fmt::Write;
+            let _ = writeln!(zkvm::Stderr, "{message}");
+        }
+        syscalls::syscall_halt(exit_code)
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    match message {
+        Some(message) => panic!("the program aborted with exit code {exit_code}: {message}"),
+        None => panic!("the program aborted with exit code {exit_code}"),
+    }
 }
 
+/// Aborts the program with an exit code if a condition does not hold.
+///
+/// Unlike a panic, which halts the program with the exit code 1, the exit code tells the host why
+/// the program failed: the execution fails with `ExecutionError::HaltWithNonZeroExitCode`, or the
+/// code is in `ExecutionReport::exit_code` if it is reported. A relayer can then tell an invalid
+/// input from a bug of the program without parsing its output. The code is any value convertible
+/// into a `u8` of at least 2, and may be followed by a message written to stderr. To prove that
+/// the input is invalid rather than failing the execution, see `io::reject`.
+///
+/// ```ignore
+/// const INVALID_SIGNATURE: u8 = 2;
+/// const EXPIRED: u8 = 3;
+///
+/// monerochan_runtime::ensure!(signature.verify(&message), INVALID_SIGNATURE);
+/// monerochan_runtime::ensure!(now < deadline, EXPIRED, "expired at {deadline}");
+/// ```
+#[macro_export]
+macro_rules! ensure {
+    ($cond:expr, $code:expr $(,)?) => {
+        if !$cond {
+            $crate::abort(::core::convert::Into::<u8>::into($code));
+        }
+    };
+    ($cond:expr, $code:expr, $($message:tt)+) => {
+        if !$cond {
+            $crate::abort_with(
+                ::core::convert::Into::<u8>::into($code),
+                ::core::option::Option::Some(::core::format_args!($($message)+)),
+            );
+        }
+    };
+}
+
+/// Called by the `main` generated by [`entrypoint!`] when the program is not compiled for the
+/// zkVM.
+#[doc(hidden)]
+pub fn skip_entrypoint() {
+    #[cfg(feature = "std")]
+    eprintln!("Not running in zkVM, skipping entrypoint");
+}
+
+/// Declares the entrypoint of the program.
+///
+/// By default, the stack grows down from `0x00200400`, below the program. With `stack_size`, the
+/// program reserves a stack of that many bytes after its data instead, above a guard region of
+/// [`STACK_GUARD_SIZE`] bytes. A stack overflow then traps in the executor instead of silently
+/// overwriting the data or the heap of the program:
+///
+/// ```ignore
+/// monerochan_runtime::entrypoint!(main, stack_size = 4 * 1024 * 1024);
+/// ```
+///
+/// The stack size must be a non-zero multiple of 16. The `--stack-size` option of
+/// `monerochan-build` takes precedence over it.
+///
+/// With `allocator`, the program selects its allocator, `bump` or `embedded`, rather than through
+/// the `embedded` feature, so that variants of a program with either allocator can be built from
+/// the same crate:
+///
+/// ```ignore
+/// monerochan_runtime::entrypoint!(main, allocator = embedded);
+/// monerochan_runtime::entrypoint!(main, allocator = bump, stack_size = 4 * 1024 * 1024);
+/// ```
 #[macro_export]
 macro_rules! entrypoint {
-    ($path:path) => {
+    (@stack $size:expr) => {
+        const _: () = assert!(
+            ($size) > 0 && ($size) % 16 == 0,
+            "the stack size must be a non-zero multiple of 16"
+        );
+
+        // The stack is reserved in `.bss`, so it takes no space in the ELF. The symbols override
+        // the default layout defined by the runtime.
+        #[cfg(target_os = "zkvm")]
+        ::core::arch::global_asm!(
+            ".pushsection .bss._monerochan_stack,\"aw\",@nobits",
+            ".balign 16",
+            ".globl _monerochan_stack_guard",
+            "_monerochan_stack_guard:",
+            ".space {guard}",
+            ".space {size}",
+            ".globl _monerochan_stack_top",
+            "_monerochan_stack_top:",
+            ".popsection",
+            guard = const $crate::STACK_GUARD_SIZE,
+            size = const $size,
+        );
+    };
+    (@main $path:path, $allocator:ident) => {
         const ZKVM_ENTRY: fn() = $path;
 
+        #[cfg(target_os = "zkvm")]
+        #[global_allocator]
+        static ZKVM_ALLOCATOR: $crate::allocators::$allocator::Allocator =
+            $crate::allocators::$allocator::Allocator {};
+
         mod zkvm_generated_main {
 
             #[no_mangle]
             fn main() {
+                // The allocator is initialized before the program allocates anything.
+                #[cfg(target_os = "zkvm")]
+                $crate::allocators::$allocator::init();
+
                 // Link to the actual entrypoint only when compiling for zkVM, otherwise run a
                 // simple noop. Doing this avoids compilation errors when building for the host
                 // target.
@@ -247,9 +449,27 @@ macro_rules! entrypoint {
                 if cfg!(target_os = "zkvm") {
                     super::ZKVM_ENTRY()
                 } else {
-                    eprintln!("Not running in zkVM, skipping entrypoint");
+                    $crate::skip_entrypoint();
                 }
             }
         }
     };
+    ($path:path, allocator = $allocator:ident, stack_size = $size:expr $(,)?) => {
+        $crate::entrypoint!(@main $path, $allocator);
+        $crate::entrypoint!(@stack $size);
+    };
+    ($path:path, stack_size = $size:expr, allocator = $allocator:ident $(,)?) => {
+        $crate::entrypoint!(@main $path, $allocator);
+        $crate::entrypoint!(@stack $size);
+    };
+    ($path:path, allocator = $allocator:ident $(,)?) => {
+        $crate::entrypoint!(@main $path, $allocator);
+    };
+    ($path:path, stack_size = $size:expr $(,)?) => {
+        $crate::entrypoint!(@main $path, default);
+        $crate::entrypoint!(@stack $size);
+    };
+    ($path:path) => {
+        $crate::entrypoint!(@main $path, default);
+    };
 }
@@ -0,0 +1,25 @@
+//! Overrides of the f64 soft-float intrinsics of compiler-rt.
+//!
+//! The zkVM has no floating point instructions, so f64 arithmetic is lowered to calls to these
+//! intrinsics. Defining them here takes precedence over the ones of `compiler_builtins`, which are
+//! only linked in for the symbols that are still undefined, and routes them to the f64 syscalls.
+
+#[no_mangle]
+pub extern "C" fn __adddf3(a: f64, b: f64) -> f64 {
+    monerochan_lib::float::add(a, b)
+}
+
+#[no_mangle]
+pub extern "C" fn __subdf3(a: f64, b: f64) -> f64 {
+    monerochan_lib::float::sub(a, b)
+}
+
+#[no_mangle]
+pub extern "C" fn __muldf3(a: f64, b: f64) -> f64 {
+    monerochan_lib::float::mul(a, b)
+}
+
+#[no_mangle]
+pub extern "C" fn __divdf3(a: f64, b: f64) -> f64 {
+    monerochan_lib::float::div(a, b)
+}
@@ -0,0 +1,29 @@
+#[cfg(target_os = "zkvm")]
+use core::arch::asm;
+
+/// AES encryption round operation.
+///
+/// Computes `AddRoundKey(MixColumns(ShiftRows(SubBytes(state))), round_key)`, and writes the
+/// result over the state. The state is stored column by column, with each word holding one column
+/// in little endian, which is the byte order of an AES block.
+///
+/// ### Safety
+///
+/// The caller must ensure that `state` and `round_key` are valid pointers to data that is aligned
+/// along a four byte boundary.
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn syscall_aes_encrypt_round(state: *mut [u32; 4], round_key: *const [u32; 4]) {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        asm!(
+            "ecall",
+            in("t0") crate::syscalls::AES_ENCRYPT_ROUND,
+            in("a0") state,
+            in("a1") round_key,
+        );
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
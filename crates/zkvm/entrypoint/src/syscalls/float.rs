@@ -0,0 +1,81 @@
+#[cfg(target_os = "zkvm")]
+use core::arch::asm;
+
+/// F64 addition operation.
+///
+/// Computes `x + y` rounded to nearest with ties to even, and writes the result over `x`. The
+/// result is only computed when both operands and the result are normal, and is the all-zero
+/// sentinel otherwise, in which case the caller must compute it in software.
+///
+/// ### Safety
+///
+/// The caller must ensure that `x` and `y` are valid pointers to data that is aligned along a four
+/// byte boundary.
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn syscall_f64_add(x: *mut [u32; 2], y: *const [u32; 2]) {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        asm!(
+            "ecall",
+            in("t0") crate::syscalls::F64_ADD,
+            in("a0") x,
+            in("a1") y,
+        );
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
+
+/// F64 multiplication operation.
+///
+/// Computes `x * y` with the same rounding and sentinel as [`syscall_f64_add`], and writes the
+/// result over `x`.
+///
+/// ### Safety
+///
+/// The caller must ensure that `x` and `y` are valid pointers to data that is aligned along a four
+/// byte boundary.
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn syscall_f64_mul(x: *mut [u32; 2], y: *const [u32; 2]) {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        asm!(
+            "ecall",
+            in("t0") crate::syscalls::F64_MUL,
+            in("a0") x,
+            in("a1") y,
+        );
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
+
+/// F64 division operation.
+///
+/// Computes `x / y` with the same rounding and sentinel as [`syscall_f64_add`], and writes the
+/// result over `x`.
+///
+/// ### Safety
+///
+/// The caller must ensure that `x` and `y` are valid pointers to data that is aligned along a four
+/// byte boundary.
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn syscall_f64_div(x: *mut [u32; 2], y: *const [u32; 2]) {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        asm!(
+            "ecall",
+            in("t0") crate::syscalls::F64_DIV,
+            in("a0") x,
+            in("a1") y,
+        );
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
@@ -4,6 +4,8 @@ cfg_if::cfg_if! {
         use sha2::Digest;
         use crate::zkvm;
         use crate::{PV_DIGEST_NUM_WORDS, POSEIDON_NUM_WORDS};
+        use crate::syscalls::syscall_write;
+        use monerochan_primitives::consts::{fd::FD_PUBLIC_VALUES, OUTCOME_HEADER};
     }
 }
 
@@ -20,6 +22,20 @@ cfg_if::cfg_if! {
 pub extern "C" fn syscall_halt(exit_code: u8) -> ! {
     #[cfg(target_os = "zkvm")]
     unsafe {
+        // The public values of a program recording its outcome are written after the header
+        // recording it.
+        let buffer = core::mem::take(&mut *core::ptr::addr_of_mut!(zkvm::PUBLIC_VALUES_BUFFER));
+        if let Some(buffer) = buffer {
+            let (tag, code) = match *core::ptr::addr_of!(zkvm::REJECTION) {
+                Some(code) => (1u8, code),
+                None => (0, 0),
+            };
+            syscall_write(FD_PUBLIC_VALUES, OUTCOME_HEADER.as_ptr(), OUTCOME_HEADER.len());
+            syscall_write(FD_PUBLIC_VALUES, &tag, 1);
+            syscall_write(FD_PUBLIC_VALUES, code.to_le_bytes().as_ptr(), 4);
+            syscall_write(FD_PUBLIC_VALUES, buffer.as_ptr(), buffer.len());
+        }
+
         // When we halt, we retrieve the public values finalized digest.  This is the hash of all
         // the bytes written to the public values fd.
         let pv_digest_bytes =
@@ -65,3 +81,25 @@ pub extern "C" fn syscall_halt(exit_code: u8) -> ! {
     #[cfg(not(target_os = "zkvm"))]
     unreachable!()
 }
+
+/// Rejects the input of the program with an error code, and halts it successfully, see
+/// `monerochan_lib::io::reject`.
+///
+/// The program must have called [`syscall_record_outcome`](crate::syscalls::syscall_record_outcome)
+/// first, or it panics, since the rejection could not be told from its public values otherwise.
+#[allow(unused_variables)]
+#[no_mangle]
+pub extern "C" fn syscall_reject(code: u32) -> ! {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        assert!(
+            (*core::ptr::addr_of!(zkvm::PUBLIC_VALUES_BUFFER)).is_some(),
+            "rejecting the input requires recording the outcome first"
+        );
+        *core::ptr::addr_of_mut!(zkvm::REJECTION) = Some(code);
+        syscall_halt(0)
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
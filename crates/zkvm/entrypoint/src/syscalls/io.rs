@@ -13,6 +13,26 @@ cfg_if::cfg_if! {
 pub extern "C" fn syscall_write(fd: u32, write_buf: *const u8, nbytes: usize) {
     cfg_if::cfg_if! {
         if #[cfg(target_os = "zkvm")] {
+            if fd == FD_PUBLIC_VALUES {
+                let pi_slice: &[u8] = unsafe { core::slice::from_raw_parts(write_buf, nbytes) };
+
+                // The public values of a program recording its outcome are buffered, and written
+                // after the header recording the outcome when the program halts.
+                let buffer = unsafe { &mut *core::ptr::addr_of_mut!(zkvm::PUBLIC_VALUES_BUFFER) };
+                if let Some(buffer) = buffer {
+                    buffer.extend_from_slice(pi_slice);
+                    return;
+                }
+                if nbytes > 0 {
+                    unsafe { *core::ptr::addr_of_mut!(zkvm::PUBLIC_VALUES_WRITTEN) = true };
+                }
+
+                // For writes to the public values fd, we update a global program hasher with the
+                // bytes being written. At the end of the program, we call the COMMIT ecall with
+                // the finalized version of this hash.
+                unsafe { zkvm::PUBLIC_VALUES_HASHER.as_mut().unwrap().update(pi_slice) };
+            }
+
             unsafe {
                 asm!(
                     "ecall",
@@ -22,20 +42,36 @@ pub extern "C" fn syscall_write(fd: u32, write_buf: *const u8, nbytes: usize) {
                     in("a2") nbytes,
                 );
             }
-
-            // For writes to the public values fd, we update a global program hasher with the bytes
-            // being written. At the end of the program, we call the COMMIT ecall with the finalized
-            // version of this hash.
-            if fd == FD_PUBLIC_VALUES {
-                let pi_slice: &[u8] = unsafe { core::slice::from_raw_parts(write_buf, nbytes) };
-                unsafe { zkvm::PUBLIC_VALUES_HASHER.as_mut().unwrap().update(pi_slice) };
-            }
         } else {
             unreachable!()
         }
     }
 }
 
+/// Records the outcome of the program in a header starting its public values, see
+/// `monerochan_lib::io::record_outcome`.
+///
+/// The values committed from then on are buffered until the program halts, to be written after
+/// the header. The program panics if it already committed values, as the header would not start
+/// its public values.
+#[no_mangle]
+pub extern "C" fn syscall_record_outcome() {
+    #[cfg(target_os = "zkvm")]
+    unsafe {
+        let buffer = &mut *core::ptr::addr_of_mut!(zkvm::PUBLIC_VALUES_BUFFER);
+        if buffer.is_none() {
+            assert!(
+                !*core::ptr::addr_of!(zkvm::PUBLIC_VALUES_WRITTEN),
+                "the outcome must be recorded before anything is committed"
+            );
+            *buffer = Some(alloc::vec::Vec::new());
+        }
+    }
+
+    #[cfg(not(target_os = "zkvm"))]
+    unreachable!()
+}
+
 /// Returns the length of the next element in the hint stream.
 #[allow(unused_variables)]
 #[no_mangle]
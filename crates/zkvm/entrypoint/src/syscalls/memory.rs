@@ -15,28 +15,50 @@
 // Memory addresses must be lower than BabyBear prime.
 pub const MAX_MEMORY: usize = 0x78000000;
 
-// Pointer to next heap address to use, or 0 if the heap has not yet been
-// initialized.
-#[cfg(not(feature = "embedded"))]
-static mut HEAP_POS: usize = 0;
-
-/// Allocate memory aligned to the given alignment.
+/// Returns the start and end of the heap.
 ///
-/// Only available when the `bump` feature is enabled.
-#[allow(clippy::missing_safety_doc)]
-#[no_mangle]
-#[cfg(all(target_os = "zkvm", not(feature = "embedded")))]
-pub unsafe extern "C" fn sys_alloc_aligned(bytes: usize, align: usize) -> *mut u8 {
+/// The bounds are read from the `_monerochan_heap_start` and `_monerochan_heap_end` linker symbols,
+/// which `monerochan-build` sets for a custom memory layout. By default the heap spans from the end
+/// of the program to [`MAX_MEMORY`].
+#[cfg(target_os = "zkvm")]
+pub(crate) fn heap_bounds() -> (usize, usize) {
     extern "C" {
         // https://lld.llvm.org/ELF/linker_script.html#sections-command
         static _end: u8;
     }
 
+    // The symbols are absolute, so their addresses are the configured values. They are read with
+    // `la` because the compiler may assume that the address of a static is never zero.
+    let (start, end): (usize, usize);
+    unsafe {
+        core::arch::asm!(
+            "la {0}, _monerochan_heap_start",
+            "la {1}, _monerochan_heap_end",
+            out(reg) start,
+            out(reg) end,
+            options(pure, nomem, nostack),
+        );
+    }
+
+    let start = if start == 0 { unsafe { (&_end) as *const u8 as usize } } else { start };
+    let end = if end == 0 { MAX_MEMORY } else { end };
+    (start, end)
+}
+
+// Pointer to next heap address to use, or 0 if the heap has not yet been
+// initialized.
+static mut HEAP_POS: usize = 0;
+
+/// Allocate memory aligned to the given alignment, from the heap of the bump allocator.
+#[cfg(target_os = "zkvm")]
+pub(crate) unsafe fn bump_alloc_aligned(bytes: usize, align: usize) -> *mut u8 {
+    let (heap_start, heap_end) = heap_bounds();
+
     // SAFETY: Single threaded, so nothing else can touch this while we're working.
     let mut heap_pos = unsafe { HEAP_POS };
 
     if heap_pos == 0 {
-        heap_pos = unsafe { (&_end) as *const u8 as usize };
+        heap_pos = heap_start;
     }
 
     let offset = heap_pos & (align - 1);
@@ -47,28 +69,29 @@ pub unsafe extern "C" fn sys_alloc_aligned(bytes: usize, align: usize) -> *mut u
     let ptr = heap_pos as *mut u8;
     let (heap_pos, overflowed) = heap_pos.overflowing_add(bytes);
 
-    if overflowed || MAX_MEMORY < heap_pos {
-        panic!("Memory limit exceeded (0x78000000)");
+    if overflowed || heap_end < heap_pos {
+        panic!("Memory limit exceeded ({heap_end:#x})");
     }
 
     unsafe { HEAP_POS = heap_pos };
     ptr
 }
 
-/// Allocate memory aligned to the given alignment.
-///
-/// Only available when the `embedded` feature is enabled.
+/// Allocate memory aligned to the given alignment, with the allocator selected by the program.
 #[allow(clippy::missing_safety_doc)]
 #[no_mangle]
-#[cfg(all(target_os = "zkvm", feature = "embedded"))]
+#[cfg(target_os = "zkvm")]
 pub unsafe extern "C" fn sys_alloc_aligned(bytes: usize, align: usize) -> *mut u8 {
-    use std::alloc::GlobalAlloc;
-    crate::allocators::embedded::INNER_HEAP
-        .alloc(std::alloc::Layout::from_size_align(bytes, align).unwrap())
+    if crate::allocators::embedded::is_active() {
+        use core::alloc::GlobalAlloc;
+        crate::allocators::embedded::INNER_HEAP
+            .alloc(core::alloc::Layout::from_size_align(bytes, align).unwrap())
+    } else {
+        bump_alloc_aligned(bytes, align)
+    }
 }
 
-/// Used memory in bytes.
-#[cfg(not(feature = "embedded"))]
+/// Used memory in bytes, with the bump allocator.
 pub fn used_memory() -> usize {
     unsafe { HEAP_POS }
 }
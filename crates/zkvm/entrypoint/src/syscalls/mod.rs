@@ -1,7 +1,9 @@
+mod aes;
 mod bigint;
 mod bls12381;
 mod bn254;
 mod ed25519;
+mod float;
 mod fptower;
 mod halt;
 mod io;
@@ -18,10 +20,12 @@ mod unconstrained;
 #[cfg(feature = "verify")]
 mod verify;
 
+pub use aes::*;
 pub use bigint::*;
 pub use bls12381::*;
 pub use bn254::*;
 pub use ed25519::*;
+pub use float::*;
 pub use fptower::*;
 pub use halt::*;
 pub use io::*;
@@ -157,3 +161,15 @@ pub const BN254_FP2_SUB: u32 = 0x00_01_01_2A;
 
 /// Executes the `BN254_FP2_MUL` precompile.
 pub const BN254_FP2_MUL: u32 = 0x00_01_01_2B;
+
+/// Executes the `AES_ENCRYPT_ROUND` precompile.
+pub const AES_ENCRYPT_ROUND: u32 = 0x00_01_01_30;
+
+/// Executes the `F64_ADD` precompile.
+pub const F64_ADD: u32 = 0x00_01_01_31;
+
+/// Executes the `F64_MUL` precompile.
+pub const F64_MUL: u32 = 0x00_01_01_32;
+
+/// Executes the `F64_DIV` precompile.
+pub const F64_DIV: u32 = 0x00_01_01_33;
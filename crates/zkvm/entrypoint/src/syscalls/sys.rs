@@ -1,6 +1,3 @@
-use std::sync::Mutex;
-
-use lazy_static::lazy_static;
 use rand::{rngs::StdRng, Rng, SeedableRng};
 
 use crate::syscalls::{syscall_halt, syscall_write};
@@ -10,13 +7,11 @@ use crate::syscalls::{syscall_halt, syscall_write};
 /// In the future, we can pass in this seed from the host or have the verifier generate it.
 const PRNG_SEED: u64 = 0x123456789abcdef0;
 
-lazy_static! {
-    /// A lazy static to generate a global random number generator.
-    static ref RNG: Mutex<StdRng> = Mutex::new(StdRng::seed_from_u64(PRNG_SEED));
-}
+/// The global random number generator, created on the first use of the `sys_rand` system call.
+static mut RNG: Option<StdRng> = None;
 
-/// A lazy static to print a warning once for using the `sys_rand` system call.
-static SYS_RAND_WARNING: std::sync::Once = std::sync::Once::new();
+/// Whether the warning for using the `sys_rand` system call was printed.
+static mut SYS_RAND_WARNED: bool = false;
 
 /// Generates random bytes.
 ///
@@ -25,10 +20,14 @@ static SYS_RAND_WARNING: std::sync::Once = std::sync::Once::new();
 /// Make sure that `buf` has at least `nwords` words.
 #[no_mangle]
 pub unsafe extern "C" fn sys_rand(recv_buf: *mut u8, words: usize) {
-    SYS_RAND_WARNING.call_once(|| {
-        eprintln!("WARNING: Using insecure random number generator.");
-    });
-    let mut rng = RNG.lock().unwrap();
+    // SAFETY: The VM is single threaded.
+    if !SYS_RAND_WARNED {
+        SYS_RAND_WARNED = true;
+        let warning = b"WARNING: Using insecure random number generator.\n";
+        sys_write(2, warning.as_ptr(), warning.len());
+    }
+    let rng =
+        (*core::ptr::addr_of_mut!(RNG)).get_or_insert_with(|| StdRng::seed_from_u64(PRNG_SEED));
     for i in 0..words {
         let element = recv_buf.add(i);
         *element = rng.gen();
@@ -16,6 +16,7 @@ pub fn syscall_enter_unconstrained() -> bool {
 
     #[cfg(not(target_os = "zkvm"))]
     {
+        #[cfg(feature = "std")]
         eprintln!("Entering unconstrained execution block");
         continue_unconstrained = 1;
     }
@@ -34,6 +35,6 @@ pub fn syscall_exit_unconstrained() {
         unreachable!()
     }
 
-    #[cfg(not(target_os = "zkvm"))]
+    #[cfg(all(not(target_os = "zkvm"), feature = "std"))]
     eprintln!("Exiting unconstrained execution block");
 }
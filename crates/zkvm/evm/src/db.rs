@@ -0,0 +1,341 @@
+//! The state database of the EVM, backed by the witness.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use revm::{
+    primitives::{
+        keccak256, Account, AccountInfo, Address, Bytecode, Bytes, HashMap as StateChanges,
+        KECCAK_EMPTY, B256, U256,
+    },
+    Database, DatabaseCommit,
+};
+
+use crate::{
+    input::AccountWitness,
+    mpt::{self, EMPTY_ROOT},
+    rlp::Item,
+    EvmError,
+};
+
+/// An account of the database.
+#[derive(Debug, Clone, Default)]
+struct DbAccount {
+    /// The account, or `None` if it does not exist.
+    info: Option<AccountInfo>,
+    /// The known storage slots.
+    storage: HashMap<U256, U256>,
+    /// Whether the whole storage is known, so that the other slots are zero.
+    storage_complete: bool,
+}
+
+/// The accounts, storage slots, bytecode and block hashes accessed by an execution but missing
+/// from the witness.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MissingWitness {
+    /// The missing accounts, and the missing storage slots of each account.
+    pub accounts: BTreeMap<Address, BTreeSet<U256>>,
+    /// The accounts whose bytecode is missing.
+    pub codes: BTreeSet<Address>,
+    /// The numbers of the missing block hashes.
+    pub block_hashes: BTreeSet<u64>,
+}
+
+impl MissingWitness {
+    /// Whether nothing is missing.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty() && self.codes.is_empty() && self.block_hashes.is_empty()
+    }
+}
+
+/// A state database holding the accounts of a witness, checked against a state root.
+///
+/// By default, accessing state missing from the witness fails the execution. A recording database
+/// instead records the missing state and reads it as empty, which lets the host discover the
+/// witness of a block.
+#[derive(Debug, Clone, Default)]
+pub struct WitnessDb {
+    accounts: HashMap<Address, DbAccount>,
+    codes: HashMap<B256, Bytecode>,
+    block_hashes: HashMap<u64, B256>,
+    missing: Option<MissingWitness>,
+}
+
+impl WitnessDb {
+    /// Builds the database from the account proofs, the bytecode and the block hashes of the
+    /// witness, checking the proofs against the state root.
+    pub fn new(
+        state_root: B256,
+        accounts: &[AccountWitness],
+        codes: &[Bytes],
+        block_hashes: HashMap<u64, B256>,
+    ) -> Result<Self, EvmError> {
+        let codes: HashMap<_, _> =
+            codes.iter().map(|code| (keccak256(code), Bytecode::new_raw(code.clone()))).collect();
+
+        let mut db_accounts = HashMap::with_capacity(accounts.len());
+        for witness in accounts {
+            let key = keccak256(witness.address);
+            let leaf = mpt::verify_proof(state_root, key.as_slice(), &witness.proof)?;
+            let (info, storage_root) = match leaf {
+                Some(leaf) => {
+                    let fields = Item::decode_exact(&leaf)?.list()?;
+                    let [nonce, balance, storage_root, code_hash] = fields.as_slice() else {
+                        return Err(EvmError::InvalidProof("invalid account"));
+                    };
+                    let code_hash = code_hash.b256()?;
+                    let code = if code_hash == KECCAK_EMPTY {
+                        Some(Bytecode::new())
+                    } else {
+                        codes.get(&code_hash).cloned()
+                    };
+                    let balance = balance.u256()?;
+                    let info = AccountInfo { balance, nonce: nonce.u64()?, code_hash, code };
+                    (Some(info), storage_root.b256()?)
+                }
+                None => (None, EMPTY_ROOT),
+            };
+
+            let mut storage = HashMap::with_capacity(witness.storage.len());
+            for slot in &witness.storage {
+                let key = keccak256(slot.slot.to_be_bytes::<32>());
+                let value = match mpt::verify_proof(storage_root, key.as_slice(), &slot.proof)? {
+                    Some(leaf) => Item::decode_exact(&leaf)?.u256()?,
+                    None => U256::ZERO,
+                };
+                storage.insert(slot.slot, value);
+            }
+
+            let account = DbAccount { info, storage, storage_complete: storage_root == EMPTY_ROOT };
+            if db_accounts.insert(witness.address, account).is_some() {
+                return Err(EvmError::InvalidWitness("duplicate account"));
+            }
+        }
+
+        Ok(Self { accounts: db_accounts, codes, block_hashes, missing: None })
+    }
+
+    /// Makes the database record the state missing from the witness instead of failing.
+    #[must_use]
+    pub fn recording(mut self) -> Self {
+        self.missing = Some(MissingWitness::default());
+        self
+    }
+
+    /// The state missing from the witness, for a recording database.
+    #[must_use]
+    pub fn missing(&self) -> Option<&MissingWitness> {
+        self.missing.as_ref()
+    }
+
+    /// Writes a storage slot outside of a transaction.
+    pub(crate) fn set_storage(&mut self, address: Address, slot: U256, value: U256) {
+        self.accounts.entry(address).or_default().storage.insert(slot, value);
+    }
+
+    /// Fails with the error, or records the missing state in a recording database.
+    fn miss(
+        &mut self,
+        record: impl FnOnce(&mut MissingWitness),
+        error: EvmError,
+    ) -> Result<(), EvmError> {
+        match &mut self.missing {
+            Some(missing) => {
+                record(missing);
+                Ok(())
+            }
+            None => Err(error),
+        }
+    }
+}
+
+impl Database for WitnessDb {
+    type Error = EvmError;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, EvmError> {
+        if let Some(account) = self.accounts.get(&address) {
+            return Ok(account.info.clone());
+        }
+        self.miss(
+            |missing| {
+                missing.accounts.entry(address).or_default();
+            },
+            EvmError::MissingAccount(address),
+        )?;
+        Ok(None)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, EvmError> {
+        if let Some(code) = self.codes.get(&code_hash) {
+            return Ok(code.clone());
+        }
+        // Bytecode is fetched by address, so record the accounts with this code.
+        let addresses: Vec<_> = self
+            .accounts
+            .iter()
+            .filter(|(_, account)| account.info.as_ref().is_some_and(|i| i.code_hash == code_hash))
+            .map(|(address, _)| *address)
+            .collect();
+        self.miss(|missing| missing.codes.extend(addresses), EvmError::MissingCode(code_hash))?;
+        Ok(Bytecode::new())
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, EvmError> {
+        if let Some(account) = self.accounts.get(&address) {
+            if let Some(value) = account.storage.get(&index) {
+                return Ok(*value);
+            }
+            if account.storage_complete {
+                return Ok(U256::ZERO);
+            }
+        }
+        self.miss(
+            |missing| {
+                missing.accounts.entry(address).or_default().insert(index);
+            },
+            EvmError::MissingStorage(address, index),
+        )?;
+        Ok(U256::ZERO)
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, EvmError> {
+        if let Some(hash) = self.block_hashes.get(&number) {
+            return Ok(*hash);
+        }
+        self.miss(
+            |missing| {
+                missing.block_hashes.insert(number);
+            },
+            EvmError::MissingBlockHash(number),
+        )?;
+        Ok(B256::ZERO)
+    }
+}
+
+impl DatabaseCommit for WitnessDb {
+    fn commit(&mut self, changes: StateChanges<Address, Account>) {
+        for (address, account) in changes {
+            if !account.is_touched() {
+                continue;
+            }
+            let entry = self.accounts.entry(address).or_default();
+            if account.is_selfdestructed() {
+                *entry = DbAccount { info: None, storage: HashMap::new(), storage_complete: true };
+                continue;
+            }
+            if account.is_created() {
+                entry.storage.clear();
+                entry.storage_complete = true;
+            }
+            if let Some(code) = &account.info.code {
+                if !code.is_empty() {
+                    self.codes.insert(account.info.code_hash, code.clone());
+                }
+            }
+            entry.info = Some(account.info);
+            let storage = account.storage.into_iter();
+            entry.storage.extend(storage.map(|(slot, value)| (slot, value.present_value)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use revm::primitives::{address, b256, hex};
+
+    use super::*;
+    use crate::input::StorageWitness;
+
+    // The nodes of the proofs `eth_getProof` returns for the accounts of a state trie holding
+    // three accounts, and for the slots of the storage trie of one of them, built with an
+    // independent implementation of the trie.
+    const STATE_NODES: [&str; 3] = [
+        concat!(
+            "f87180a0ab8cdb808c8303bb61fb48e276217be9770fa83ecf3f90f2234d558885f5abf1808080808080",
+            "80a00c76940e36ac805130e6b43bbf66b1b77b237a4609b9dc9f22aa713fc19997c4808080a0979b6099",
+            "cdcf735a24fe74920cd140f688eb613af5f8a969a4273c5bea0dd967808080",
+        ),
+        concat!(
+            "f871a037b57340ce9e8b67ec924a29f2310201901547a5aa1a7de69b1b112758e9f860b84ef84c01880d",
+            "e0b6b3a7640000a0da82393dd94b0dbf6102de4f3267acf41071ebbeec89dae2345eea102bb8d9f4a098",
+            "e3a357b0a9519e7773d42cf7912a620a18c8f53cd8e1525ce5344917d07e76",
+        ),
+        concat!(
+            "f869a03468288056310c82aa4c01a7e12a10f8111a0560e72b700555479031b86c357db846f8448001a0",
+            "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421a0c5d2460186f7233c92",
+            "7e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+        ),
+    ];
+
+    const STORAGE_NODES: [&str; 3] = [
+        concat!(
+            "f8518080a0f73cea67884580eec8c3f6d0746360906cf897bf812183520e51b89a12166cfe8080808080",
+            "808080a0ccef1a76b91b414bf9a187b76fd22b9c8a2c9852bdc10f324442f25d2edd5f2d8080808080",
+        ),
+        "e2a0390decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e5632a",
+        concat!(
+            "f3a0310e2d527612073b26eecdfd717e6a320cf44b4afac2b0732d9fcbe2b7fa0cf691901234567890ab",
+            "cdef1234567890abcdef",
+        ),
+    ];
+
+    /// Decodes the hex-encoded nodes of a proof.
+    fn decode_nodes(nodes: &[&str]) -> Vec<Bytes> {
+        nodes.iter().map(|node| Bytes::from(hex::decode(node).unwrap())).collect()
+    }
+
+    #[test]
+    fn test_witness_proofs() {
+        let state_root = b256!("d19eb47d3eb6200f0b5f38b5a2dab3f663a078779380408303f7150674441517");
+        let contract = address!("9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f");
+        let (account, missing) = (Address::with_last_byte(1), Address::with_last_byte(3));
+        let code = Bytes::from(hex::decode("602a60005260206000f3").unwrap());
+
+        let state = decode_nodes(&STATE_NODES);
+        let storage = decode_nodes(&STORAGE_NODES);
+        let slot = |slot: u64, proof: Vec<Bytes>| StorageWitness { slot: U256::from(slot), proof };
+        let accounts = vec![
+            AccountWitness {
+                address: contract,
+                proof: state[..2].to_vec(),
+                storage: vec![
+                    slot(0, storage[..2].to_vec()),
+                    slot(1, vec![storage[0].clone(), storage[2].clone()]),
+                    slot(5, storage[..1].to_vec()),
+                ],
+            },
+            AccountWitness {
+                address: account,
+                proof: vec![state[0].clone(), state[2].clone()],
+                storage: vec![],
+            },
+            AccountWitness { address: missing, proof: state[..1].to_vec(), storage: vec![] },
+        ];
+        let mut db =
+            WitnessDb::new(state_root, &accounts, &[code.clone()], HashMap::new()).unwrap();
+
+        let info = db.basic(contract).unwrap().unwrap();
+        assert_eq!(info.nonce, 1);
+        assert_eq!(info.balance, U256::from(10u64.pow(18)));
+        assert_eq!(info.code_hash, keccak256(&code));
+        assert_eq!(db.storage(contract, U256::from(0)).unwrap(), U256::from(42));
+        let value = U256::from(0x1234567890abcdef1234567890abcdef_u128);
+        assert_eq!(db.storage(contract, U256::from(1)).unwrap(), value);
+        // Slot 5 is proven empty, while the other slots are unknown.
+        assert_eq!(db.storage(contract, U256::from(5)).unwrap(), U256::ZERO);
+        assert!(matches!(db.storage(contract, U256::from(2)), Err(EvmError::MissingStorage(..))));
+
+        // The storage of an account with an empty storage root is known to be empty.
+        let info = db.basic(account).unwrap().unwrap();
+        assert_eq!(info.balance, U256::from(1));
+        assert_eq!(info.code_hash, KECCAK_EMPTY);
+        assert_eq!(db.storage(account, U256::from(7)).unwrap(), U256::ZERO);
+        assert_eq!(db.basic(missing).unwrap(), None);
+
+        // The proofs are checked against the state root, and the slot they are given for.
+        assert!(WitnessDb::new(B256::ZERO, &accounts, &[], HashMap::new()).is_err());
+        let mut swapped = accounts.clone();
+        swapped[0].storage[0].slot = U256::from(1);
+        assert!(WitnessDb::new(state_root, &swapped, &[], HashMap::new()).is_err());
+    }
+}
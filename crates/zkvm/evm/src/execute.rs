@@ -0,0 +1,257 @@
+//! The execution of a block.
+
+use std::collections::HashMap;
+
+use revm::{
+    primitives::{address, Address, EVMError, SpecId, B256, KECCAK_EMPTY, U256},
+    Database, Evm,
+};
+
+use crate::{
+    db::WitnessDb,
+    header::{Ancestor, Header},
+    input::{EvmInput, EvmLog, EvmOutput, TransactionOutcome},
+    mpt,
+    transaction::Transaction,
+    EvmError,
+};
+
+/// The EIP-4788 contract holding the parent beacon block roots.
+const BEACON_ROOTS_ADDRESS: Address = address!("000f3df6d732807ef1319fb7b8bb8522d0beac02");
+
+/// The number of roots held by the EIP-4788 contract.
+const BEACON_ROOTS_HISTORY: u64 = 8191;
+
+/// A block whose headers and transactions were checked against its hash.
+#[derive(Debug, Clone)]
+pub struct VerifiedBlock {
+    /// The header of the block.
+    pub header: Header,
+    /// The header of the parent block.
+    pub parent: Ancestor,
+    /// The transactions of the block.
+    pub transactions: Vec<Transaction>,
+    /// The hashes of the ancestors, by number.
+    pub block_hashes: HashMap<u64, B256>,
+}
+
+impl VerifiedBlock {
+    /// Decodes the block of the input, and checks its parent and ancestor headers and its
+    /// transactions.
+    pub fn decode(input: &EvmInput) -> Result<Self, EvmError> {
+        let header = Header::decode(&input.header)?;
+        let parent = Ancestor::decode(&input.parent_header)?;
+        if parent.hash != header.parent_hash || parent.number + 1 != header.number {
+            return Err(EvmError::InvalidWitness("parent header mismatch"));
+        }
+
+        let mut block_hashes = HashMap::from([(parent.number, parent.hash)]);
+        let (mut parent_hash, mut number) = (parent.parent_hash, parent.number);
+        for encoded in &input.ancestor_headers {
+            let ancestor = Ancestor::decode(encoded)?;
+            if ancestor.hash != parent_hash || ancestor.number + 1 != number {
+                return Err(EvmError::InvalidWitness("ancestor header mismatch"));
+            }
+            block_hashes.insert(ancestor.number, ancestor.hash);
+            (parent_hash, number) = (ancestor.parent_hash, ancestor.number);
+        }
+
+        if mpt::ordered_trie_root(&input.transactions) != header.transactions_root {
+            return Err(EvmError::TransactionsRootMismatch);
+        }
+        let transactions =
+            input.transactions.iter().map(|tx| Transaction::decode(tx)).collect::<Result<_, _>>()?;
+
+        Ok(Self { header, parent, transactions, block_hashes })
+    }
+
+    /// Executes the block on the database.
+    pub fn execute(&self, chain_id: u64, db: &mut WitnessDb) -> Result<EvmOutput, EvmError> {
+        let header = &self.header;
+        if let Some(root) = header.parent_beacon_block_root {
+            // The EIP-4788 system call only writes the timestamp and the root to the contract, so
+            // it is applied to the state directly.
+            let contract = db.basic(BEACON_ROOTS_ADDRESS)?;
+            if contract.is_some_and(|info| info.code_hash != KECCAK_EMPTY) {
+                let slot = U256::from(header.timestamp % BEACON_ROOTS_HISTORY);
+                let root_slot = slot + U256::from(BEACON_ROOTS_HISTORY);
+                db.set_storage(BEACON_ROOTS_ADDRESS, slot, U256::from(header.timestamp));
+                db.set_storage(BEACON_ROOTS_ADDRESS, root_slot, U256::from_be_bytes(root.0));
+            }
+        }
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_spec_id(header.spec_id)
+            .modify_cfg_env(|cfg| cfg.chain_id = chain_id)
+            .modify_block_env(|env| {
+                env.number = U256::from(header.number);
+                env.coinbase = header.beneficiary;
+                env.timestamp = U256::from(header.timestamp);
+                env.gas_limit = U256::from(header.gas_limit);
+                env.basefee = U256::from(header.base_fee_per_gas);
+                env.difficulty = header.difficulty;
+                env.prevrandao = (header.spec_id >= SpecId::MERGE).then_some(header.mix_hash);
+                if let Some(excess_blob_gas) = header.excess_blob_gas {
+                    env.set_blob_excess_gas_and_price(excess_blob_gas);
+                }
+            })
+            .build();
+
+        let mut gas_used = 0;
+        let mut outcomes = Vec::with_capacity(self.transactions.len());
+        for (index, tx) in self.transactions.iter().enumerate() {
+            tx.fill_tx_env(evm.tx_mut());
+            let result = evm.transact_commit().map_err(|error| match error {
+                EVMError::Database(error) => error,
+                error => EvmError::Execution { index, message: error.to_string() },
+            })?;
+            gas_used += result.gas_used();
+            let logs = result.logs().iter().map(|log| EvmLog {
+                address: log.address,
+                topics: log.topics().to_vec(),
+                data: log.data.data.clone(),
+            });
+            outcomes.push(TransactionOutcome {
+                hash: tx.hash,
+                sender: tx.sender,
+                success: result.is_success(),
+                gas_used: result.gas_used(),
+                logs: logs.collect(),
+            });
+        }
+
+        if gas_used != header.gas_used {
+            return Err(EvmError::GasUsedMismatch { expected: header.gas_used, actual: gas_used });
+        }
+        Ok(EvmOutput {
+            chain_id,
+            block_hash: header.hash,
+            block_number: header.number,
+            parent_hash: header.parent_hash,
+            parent_state_root: self.parent.state_root,
+            gas_used,
+            transactions: outcomes,
+        })
+    }
+}
+
+/// Executes the block of the input, checking the whole witness against the hash of the block.
+pub fn execute(input: &EvmInput) -> Result<EvmOutput, EvmError> {
+    let block = VerifiedBlock::decode(input)?;
+    let mut db = WitnessDb::new(
+        block.parent.state_root,
+        &input.accounts,
+        &input.codes,
+        block.block_hashes.clone(),
+    )?;
+    block.execute(input.chain_id, &mut db)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use revm::primitives::{hex, keccak256, Bytes};
+
+    use super::*;
+    use crate::{header, input::AccountWitness, mpt::EMPTY_ROOT, rlp, transaction};
+
+    const SENDER: Address = address!("9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f");
+    const RECIPIENT: Address = address!("3535353535353535353535353535353535353535");
+    const BENEFICIARY: Address = Address::repeat_byte(0xc0);
+
+    /// A block transferring one ether from the sender of the EIP-155 example.
+    pub(crate) fn transfer_block() -> EvmInput {
+        let mut account = Vec::new();
+        rlp::encode_u64(9, &mut account);
+        rlp::encode_bytes(&U256::from(2 * 10u64.pow(18)).to_be_bytes_trimmed_vec(), &mut account);
+        rlp::encode_bytes(EMPTY_ROOT.as_slice(), &mut account);
+        rlp::encode_bytes(KECCAK_EMPTY.as_slice(), &mut account);
+        let mut leaf = Vec::new();
+        rlp::encode_list(&account, &mut leaf);
+        let state = vec![(keccak256(SENDER).to_vec(), leaf)];
+        let state_root = mpt::trie_root(state.iter().map(|(key, value)| (key.clone(), &value[..])));
+
+        let transaction = Bytes::from(hex::decode(transaction::tests::EIP155_TX).unwrap());
+        let transactions_root = mpt::ordered_trie_root(&[&transaction]);
+
+        let parent_header =
+            header::tests::encode_header(B256::ZERO, state_root, EMPTY_ROOT, 99, 0, 20);
+        let header = header::tests::encode_header(
+            keccak256(&parent_header),
+            B256::ZERO,
+            transactions_root,
+            100,
+            21_000,
+            20,
+        );
+
+        let accounts = [SENDER, RECIPIENT, BENEFICIARY, BEACON_ROOTS_ADDRESS]
+            .into_iter()
+            .map(|address| AccountWitness {
+                address,
+                proof: mpt::tests::proof(&state, keccak256(address).as_slice()),
+                storage: Vec::new(),
+            })
+            .collect();
+
+        EvmInput {
+            chain_id: 1,
+            header: header.into(),
+            parent_header: parent_header.into(),
+            ancestor_headers: Vec::new(),
+            transactions: vec![transaction],
+            accounts,
+            codes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_execute_transfer() {
+        let input = transfer_block();
+        let output = execute(&input).unwrap();
+        assert_eq!(output.chain_id, 1);
+        assert_eq!(output.block_hash, keccak256(&input.header));
+        assert_eq!(output.block_number, 100);
+        assert_eq!(output.gas_used, 21_000);
+        assert_eq!(output.transactions.len(), 1);
+        assert_eq!(output.transactions[0].sender, SENDER);
+        assert!(output.transactions[0].success);
+        assert!(output.transactions[0].logs.is_empty());
+    }
+
+    #[test]
+    fn test_execute_invalid_witness() {
+        // The transactions must match the transactions root.
+        let mut input = transfer_block();
+        input.transactions.clear();
+        assert!(matches!(execute(&input), Err(EvmError::TransactionsRootMismatch)));
+
+        // The parent header must match the parent hash.
+        let mut input = transfer_block();
+        input.parent_header = input.header.clone();
+        assert!(matches!(execute(&input), Err(EvmError::InvalidWitness(_))));
+
+        // The transactions are signed for another chain.
+        let mut input = transfer_block();
+        input.chain_id = 5;
+        assert!(matches!(execute(&input), Err(EvmError::Execution { index: 0, .. })));
+
+        // A missing account fails the execution, unless the database records it.
+        let mut input = transfer_block();
+        input.accounts.retain(|account| account.address != BENEFICIARY);
+        assert!(matches!(execute(&input), Err(EvmError::MissingAccount(BENEFICIARY))));
+
+        let block = VerifiedBlock::decode(&input).unwrap();
+        let mut db = WitnessDb::new(
+            block.parent.state_root,
+            &input.accounts,
+            &input.codes,
+            block.block_hashes.clone(),
+        )
+        .unwrap()
+        .recording();
+        block.execute(input.chain_id, &mut db).unwrap();
+        let missing = db.missing().unwrap();
+        assert_eq!(missing.accounts.keys().collect::<Vec<_>>(), [&BENEFICIARY]);
+    }
+}
@@ -0,0 +1,252 @@
+//! Block headers.
+
+use revm::primitives::{keccak256, Address, SpecId, B256, U256};
+
+use crate::{rlp::Item, EvmError};
+
+/// The fields of a block header used to execute the block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    /// The hash of the header.
+    pub hash: B256,
+    /// The hash of the parent block.
+    pub parent_hash: B256,
+    /// The address receiving the priority fees.
+    pub beneficiary: Address,
+    /// The root of the state trie after the block.
+    pub state_root: B256,
+    /// The root of the transactions trie.
+    pub transactions_root: B256,
+    /// The difficulty, zero after the merge.
+    pub difficulty: U256,
+    /// The block number.
+    pub number: u64,
+    /// The maximum gas the transactions of the block may use.
+    pub gas_limit: u64,
+    /// The gas used by the transactions of the block.
+    pub gas_used: u64,
+    /// The block timestamp, in seconds.
+    pub timestamp: u64,
+    /// The mix hash, the `PREVRANDAO` value after the merge.
+    pub mix_hash: B256,
+    /// The base fee per gas.
+    pub base_fee_per_gas: u64,
+    /// The excess blob gas, from Cancun on.
+    pub excess_blob_gas: Option<u64>,
+    /// The root of the parent beacon block, from Cancun on.
+    pub parent_beacon_block_root: Option<B256>,
+    /// The fork the block is executed with.
+    pub spec_id: SpecId,
+}
+
+impl Header {
+    /// Decodes an RLP-encoded header.
+    ///
+    /// Only headers from London to Cancun are supported: earlier blocks have no base fee, and
+    /// later blocks have system calls and transaction types that are not implemented.
+    pub fn decode(encoded: &[u8]) -> Result<Self, EvmError> {
+        let fields = Item::decode_exact(encoded)?.list()?;
+        let field = |index: usize| fields.get(index).copied();
+        let required =
+            |index: usize| field(index).ok_or(EvmError::InvalidHeader("missing field"));
+
+        let Some(base_fee_per_gas) = field(15) else {
+            return Err(EvmError::UnsupportedFork("blocks before London"));
+        };
+        if fields.len() > 20 {
+            return Err(EvmError::UnsupportedFork("blocks after Cancun"));
+        }
+        let difficulty = required(7)?.u256()?;
+        let withdrawals_root = field(16).map(|item| item.b256()).transpose()?;
+        let blob_gas_used = field(17).map(|item| item.u64()).transpose()?;
+        let excess_blob_gas = field(18).map(|item| item.u64()).transpose()?;
+        let parent_beacon_block_root = field(19).map(|item| item.b256()).transpose()?;
+
+        let spec_id = match (withdrawals_root, blob_gas_used, parent_beacon_block_root) {
+            (_, Some(_), Some(_)) => SpecId::CANCUN,
+            (Some(_), None, None) => SpecId::SHANGHAI,
+            (None, None, None) if difficulty.is_zero() => SpecId::MERGE,
+            (None, None, None) => SpecId::LONDON,
+            _ => return Err(EvmError::InvalidHeader("inconsistent fork fields")),
+        };
+
+        Ok(Self {
+            hash: keccak256(encoded),
+            parent_hash: required(0)?.b256()?,
+            beneficiary: required(2)?.address()?,
+            state_root: required(3)?.b256()?,
+            transactions_root: required(4)?.b256()?,
+            difficulty,
+            number: required(8)?.u64()?,
+            gas_limit: required(9)?.u64()?,
+            gas_used: required(10)?.u64()?,
+            timestamp: required(11)?.u64()?,
+            mix_hash: required(13)?.b256()?,
+            base_fee_per_gas: base_fee_per_gas.u64()?,
+            excess_blob_gas,
+            parent_beacon_block_root,
+            spec_id,
+        })
+    }
+}
+
+/// The fields of an ancestor header used to link it to the block, for any fork.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ancestor {
+    /// The hash of the header.
+    pub hash: B256,
+    /// The hash of the parent block.
+    pub parent_hash: B256,
+    /// The root of the state trie after the block.
+    pub state_root: B256,
+    /// The block number.
+    pub number: u64,
+}
+
+impl Ancestor {
+    /// Decodes an RLP-encoded header.
+    pub fn decode(encoded: &[u8]) -> Result<Self, EvmError> {
+        let fields = Item::decode_exact(encoded)?.list()?;
+        if fields.len() < 15 {
+            return Err(EvmError::InvalidHeader("missing field"));
+        }
+        Ok(Self {
+            hash: keccak256(encoded),
+            parent_hash: fields[0].b256()?,
+            state_root: fields[3].b256()?,
+            number: fields[8].u64()?,
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use revm::primitives::{b256, hex};
+
+    use super::*;
+    use crate::rlp;
+
+    // The headers of the genesis block and of block 1 of Ethereum mainnet.
+    const GENESIS_HEADER: &str = concat!(
+        "f90214a00000000000000000000000000000000000000000000000000000000000000000a01dcc4de8dec75d",
+        "7aab85b567b6ccd41ad312451b948a7413f0a142fd40d4934794000000000000000000000000000000000000",
+        "0000a0d7f8974fb5ac78d9ac099b9ad5018bedc2ce0a72dad1827a1709da30580f0544a056e81f171bcc55a6",
+        "ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421a056e81f171bcc55a6ff8345e692c0f86e5b48e0",
+        "1b996cadc001622fb5e363b421b9010000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000850400000000808213888080a011bbe8db4e347b4e8c937c1c8370e4b5ed33adb3db69cb",
+        "db7a38e1e50b1b82faa000000000000000000000000000000000000000000000000000000000000000008800",
+        "00000000000042",
+    );
+
+    const BLOCK_1_HEADER: &str = concat!(
+        "f90211a0d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3a01dcc4de8dec75d",
+        "7aab85b567b6ccd41ad312451b948a7413f0a142fd40d493479405a56e2d52c817161883f50c441c3228cfe5",
+        "4d9fa0d67e4d450343046425ae4271474353857ab860dbc0a1dde64b41b5cd3a532bf3a056e81f171bcc55a6",
+        "ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421a056e81f171bcc55a6ff8345e692c0f86e5b48e0",
+        "1b996cadc001622fb5e363b421b9010000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+        "00000000000000008503ff80000001821388808455ba422499476574682f76312e302e302f6c696e75782f67",
+        "6f312e342e32a0969b900de27b6ac6a67742365dd65f55a0526c41fd18e1b16f1a1215c2e66f5988539bd497",
+        "9fef1ec4",
+    );
+
+    /// Encodes a header with the given parent, state root, transactions root and number, and the
+    /// optional fields up to the given field count.
+    pub(crate) fn encode_header(
+        parent_hash: B256,
+        state_root: B256,
+        transactions_root: B256,
+        number: u64,
+        gas_used: u64,
+        len: usize,
+    ) -> Vec<u8> {
+        let mut payload = Vec::new();
+        rlp::encode_bytes(parent_hash.as_slice(), &mut payload);
+        rlp::encode_bytes(&[0; 32], &mut payload);
+        rlp::encode_bytes(&[0xc0; 20], &mut payload);
+        rlp::encode_bytes(state_root.as_slice(), &mut payload);
+        rlp::encode_bytes(transactions_root.as_slice(), &mut payload);
+        rlp::encode_bytes(&[0; 32], &mut payload);
+        rlp::encode_bytes(&[0; 256], &mut payload);
+        rlp::encode_u64(0, &mut payload);
+        rlp::encode_u64(number, &mut payload);
+        rlp::encode_u64(30_000_000, &mut payload);
+        rlp::encode_u64(gas_used, &mut payload);
+        rlp::encode_u64(1_700_000_000 + number * 12, &mut payload);
+        rlp::encode_bytes(&[], &mut payload);
+        rlp::encode_bytes(&[0x11; 32], &mut payload);
+        rlp::encode_bytes(&[0; 8], &mut payload);
+        for index in 15..len {
+            match index {
+                15 => rlp::encode_u64(10_000_000_000, &mut payload),
+                17 | 18 => rlp::encode_u64(0, &mut payload),
+                _ => rlp::encode_bytes(&[0x22; 32], &mut payload),
+            }
+        }
+        let mut encoded = Vec::new();
+        rlp::encode_list(&payload, &mut encoded);
+        encoded
+    }
+
+    #[test]
+    fn test_decode_header() {
+        let parent_hash = B256::repeat_byte(1);
+        let encoded = encode_header(parent_hash, B256::ZERO, B256::ZERO, 42, 21_000, 20);
+        let header = Header::decode(&encoded).unwrap();
+        assert_eq!(header.hash, keccak256(&encoded));
+        assert_eq!(header.parent_hash, parent_hash);
+        assert_eq!(header.number, 42);
+        assert_eq!(header.gas_used, 21_000);
+        assert_eq!(header.base_fee_per_gas, 10_000_000_000);
+        assert_eq!(header.excess_blob_gas, Some(0));
+        assert_eq!(header.spec_id, SpecId::CANCUN);
+
+        let shanghai = encode_header(parent_hash, B256::ZERO, B256::ZERO, 42, 0, 17);
+        assert_eq!(Header::decode(&shanghai).unwrap().spec_id, SpecId::SHANGHAI);
+        let merge = encode_header(parent_hash, B256::ZERO, B256::ZERO, 42, 0, 16);
+        assert_eq!(Header::decode(&merge).unwrap().spec_id, SpecId::MERGE);
+
+        let berlin = encode_header(parent_hash, B256::ZERO, B256::ZERO, 42, 0, 15);
+        assert!(matches!(Header::decode(&berlin), Err(EvmError::UnsupportedFork(_))));
+        let prague = encode_header(parent_hash, B256::ZERO, B256::ZERO, 42, 0, 21);
+        assert!(matches!(Header::decode(&prague), Err(EvmError::UnsupportedFork(_))));
+        let partial = encode_header(parent_hash, B256::ZERO, B256::ZERO, 42, 0, 18);
+        assert!(matches!(Header::decode(&partial), Err(EvmError::InvalidHeader(_))));
+        assert!(Header::decode(&encoded[..encoded.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_mainnet_headers() {
+        let genesis = hex::decode(GENESIS_HEADER).unwrap();
+        let block_1 = hex::decode(BLOCK_1_HEADER).unwrap();
+
+        // Blocks before London are executed by no supported fork, but link the chain as ancestors.
+        assert!(matches!(Header::decode(&block_1), Err(EvmError::UnsupportedFork(_))));
+        let genesis = Ancestor::decode(&genesis).unwrap();
+        let block_1 = Ancestor::decode(&block_1).unwrap();
+        assert_eq!(
+            genesis.hash,
+            b256!("d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3")
+        );
+        assert_eq!(
+            genesis.state_root,
+            b256!("d7f8974fb5ac78d9ac099b9ad5018bedc2ce0a72dad1827a1709da30580f0544")
+        );
+        assert_eq!(genesis.number, 0);
+        assert_eq!(
+            block_1.hash,
+            b256!("88e96d4537bea4d9c05d12549907b32561d3bf31f45aae734cdc119f13406cb6")
+        );
+        assert_eq!(block_1.parent_hash, genesis.hash);
+        assert_eq!(block_1.number, 1);
+    }
+}
@@ -0,0 +1,318 @@
+//! The builder of the input of a block from an Ethereum JSON-RPC endpoint.
+
+use revm::primitives::{keccak256, Address, Bytes, B256, U256};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::{
+    db::{MissingWitness, WitnessDb},
+    execute::VerifiedBlock,
+    input::{AccountWitness, EvmInput, StorageWitness},
+    rlp, EvmError,
+};
+
+/// The maximum number of times the block is executed to discover its witness.
+///
+/// Each execution reads the state fetched by the previous one, so it only misses the state
+/// accessed behind branches that depend on it.
+const MAX_ROUNDS: usize = 16;
+
+/// Errors that can occur when building the input of a block.
+#[derive(Debug, Error)]
+pub enum HostError {
+    /// The request to the endpoint failed.
+    #[error("rpc request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// The endpoint returned an error.
+    #[error("rpc error: {0}")]
+    Rpc(String),
+
+    /// The endpoint returned an unexpected response.
+    #[error("unexpected rpc response: {0}")]
+    Response(String),
+
+    /// The block could not be executed with the fetched witness.
+    #[error(transparent)]
+    Evm(#[from] EvmError),
+
+    /// The witness was still incomplete after the maximum number of executions.
+    #[error("the witness is still incomplete after {MAX_ROUNDS} executions")]
+    Incomplete,
+}
+
+/// Builds the [`EvmInput`] of a block from an Ethereum JSON-RPC endpoint.
+///
+/// The endpoint must serve `eth_getProof` and `eth_getRawTransactionByHash` for the parent of the
+/// block, which usually requires an archive node for old blocks.
+#[derive(Debug, Clone)]
+pub struct EvmInputBuilder {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl EvmInputBuilder {
+    /// Creates a builder fetching from the endpoint at the URL.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { client: reqwest::Client::new(), url: url.into() }
+    }
+
+    /// Builds the input of the block with the given number.
+    ///
+    /// The block is executed on the host until its witness is complete, so the guest is given
+    /// exactly the state it accesses.
+    pub async fn build(&self, number: u64) -> Result<EvmInput, HostError> {
+        if number == 0 {
+            return Err(HostError::Response("the genesis block has no parent".to_string()));
+        }
+        let chain_id = self.request::<U256>("eth_chainId", json!([])).await?;
+        let header = self.header(number).await?;
+        let mut input = EvmInput {
+            chain_id: chain_id.to(),
+            header: header.encode()?,
+            parent_header: self.header(number - 1).await?.encode()?,
+            ..EvmInput::default()
+        };
+        for hash in &header.transactions {
+            let transaction = self.request("eth_getRawTransactionByHash", json!([hash])).await?;
+            input.transactions.push(transaction);
+        }
+
+        for _ in 0..MAX_ROUNDS {
+            let block = VerifiedBlock::decode(&input)?;
+            let mut db = WitnessDb::new(
+                block.parent.state_root,
+                &input.accounts,
+                &input.codes,
+                block.block_hashes.clone(),
+            )?
+            .recording();
+            let result = block.execute(input.chain_id, &mut db);
+            let missing = db.missing().cloned().unwrap_or_default();
+            if missing.is_empty() {
+                // The witness is complete, so the execution fails the same way in the guest.
+                result?;
+                return Ok(input);
+            }
+            self.fetch(&mut input, &missing, number - 1).await?;
+        }
+        Err(HostError::Incomplete)
+    }
+
+    /// Fetches the missing witness from the state of the parent block.
+    async fn fetch(
+        &self,
+        input: &mut EvmInput,
+        missing: &MissingWitness,
+        parent: u64,
+    ) -> Result<(), HostError> {
+        let block = format!("{parent:#x}");
+        for (address, slots) in &missing.accounts {
+            let slots: Vec<B256> = slots.iter().map(|slot| B256::from(*slot)).collect();
+            let proof: RpcProof =
+                self.request("eth_getProof", json!([address, slots, block])).await?;
+            let storage = proof
+                .storage_proof
+                .into_iter()
+                .map(|slot| StorageWitness { slot: slot.key, proof: slot.proof });
+            match input.accounts.iter_mut().find(|account| account.address == *address) {
+                Some(account) => account.storage.extend(storage),
+                None => input.accounts.push(AccountWitness {
+                    address: *address,
+                    proof: proof.account_proof,
+                    storage: storage.collect(),
+                }),
+            }
+        }
+
+        for address in &missing.codes {
+            input.codes.push(self.request("eth_getCode", json!([address, block])).await?);
+        }
+
+        // The ancestors are linked to the parent one by one, so fetch all the headers down to
+        // the oldest missing block.
+        if let Some(&oldest) = missing.block_hashes.first() {
+            let known = parent - input.ancestor_headers.len() as u64;
+            for number in (oldest..known).rev() {
+                input.ancestor_headers.push(self.header(number).await?.encode()?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Fetches the header of a block.
+    async fn header(&self, number: u64) -> Result<RpcHeader, HostError> {
+        self.request("eth_getBlockByNumber", json!([format!("{number:#x}"), false])).await
+    }
+
+    /// Calls a JSON-RPC method.
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<T, HostError> {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response: RpcResponse<T> = self
+            .client
+            .post(&self.url)
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        match response {
+            RpcResponse { result: Some(result), .. } => Ok(result),
+            RpcResponse { error: Some(error), .. } => Err(HostError::Rpc(error.message)),
+            _ => Err(HostError::Response(format!("{method} returned no result"))),
+        }
+    }
+}
+
+/// A JSON-RPC response.
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+/// A JSON-RPC error.
+#[derive(Deserialize)]
+struct RpcError {
+    message: String,
+}
+
+/// A block returned by `eth_getBlockByNumber`, without the transaction bodies.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcHeader {
+    hash: B256,
+    parent_hash: B256,
+    sha3_uncles: B256,
+    miner: Address,
+    state_root: B256,
+    transactions_root: B256,
+    receipts_root: B256,
+    logs_bloom: Bytes,
+    difficulty: U256,
+    number: U256,
+    gas_limit: U256,
+    gas_used: U256,
+    timestamp: U256,
+    extra_data: Bytes,
+    mix_hash: B256,
+    nonce: Bytes,
+    base_fee_per_gas: Option<U256>,
+    withdrawals_root: Option<B256>,
+    blob_gas_used: Option<U256>,
+    excess_blob_gas: Option<U256>,
+    parent_beacon_block_root: Option<B256>,
+    requests_hash: Option<B256>,
+    #[serde(default)]
+    transactions: Vec<B256>,
+}
+
+impl RpcHeader {
+    /// RLP-encodes the header, and checks that it matches the block hash.
+    fn encode(&self) -> Result<Bytes, HostError> {
+        let mut payload = Vec::new();
+        let int = |value: U256, out: &mut Vec<u8>| {
+            rlp::encode_bytes(&value.to_be_bytes_trimmed_vec(), out);
+        };
+        rlp::encode_bytes(self.parent_hash.as_slice(), &mut payload);
+        rlp::encode_bytes(self.sha3_uncles.as_slice(), &mut payload);
+        rlp::encode_bytes(self.miner.as_slice(), &mut payload);
+        rlp::encode_bytes(self.state_root.as_slice(), &mut payload);
+        rlp::encode_bytes(self.transactions_root.as_slice(), &mut payload);
+        rlp::encode_bytes(self.receipts_root.as_slice(), &mut payload);
+        rlp::encode_bytes(&self.logs_bloom, &mut payload);
+        int(self.difficulty, &mut payload);
+        int(self.number, &mut payload);
+        int(self.gas_limit, &mut payload);
+        int(self.gas_used, &mut payload);
+        int(self.timestamp, &mut payload);
+        rlp::encode_bytes(&self.extra_data, &mut payload);
+        rlp::encode_bytes(self.mix_hash.as_slice(), &mut payload);
+        rlp::encode_bytes(&self.nonce, &mut payload);
+
+        // The fields added by later forks are present from their fork on.
+        let optional = [
+            self.base_fee_per_gas.map(|fee| fee.to_be_bytes_trimmed_vec()),
+            self.withdrawals_root.map(|root| root.to_vec()),
+            self.blob_gas_used.map(|gas| gas.to_be_bytes_trimmed_vec()),
+            self.excess_blob_gas.map(|gas| gas.to_be_bytes_trimmed_vec()),
+            self.parent_beacon_block_root.map(|root| root.to_vec()),
+            self.requests_hash.map(|hash| hash.to_vec()),
+        ];
+        for field in optional.iter().map_while(Option::as_ref) {
+            rlp::encode_bytes(field, &mut payload);
+        }
+
+        let mut encoded = Vec::new();
+        rlp::encode_list(&payload, &mut encoded);
+        if keccak256(&encoded) != self.hash {
+            return Err(HostError::Response(format!("unexpected header of block {}", self.number)));
+        }
+        Ok(encoded.into())
+    }
+}
+
+/// The proof returned by `eth_getProof`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcProof {
+    account_proof: Vec<Bytes>,
+    storage_proof: Vec<RpcStorageProof>,
+}
+
+/// The proof of a storage slot returned by `eth_getProof`.
+#[derive(Debug, Clone, Deserialize)]
+struct RpcStorageProof {
+    key: U256,
+    proof: Vec<Bytes>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::tests::encode_header;
+
+    #[test]
+    fn test_encode_rpc_header() {
+        let encoded =
+            encode_header(B256::repeat_byte(1), B256::repeat_byte(2), B256::ZERO, 42, 0, 20);
+        let hash = keccak256(&encoded);
+        let zero = format!("{:#x}", B256::ZERO);
+        let header: RpcHeader = serde_json::from_value(json!({
+            "hash": hash,
+            "parentHash": B256::repeat_byte(1),
+            "sha3Uncles": zero,
+            "miner": Address::repeat_byte(0xc0),
+            "stateRoot": B256::repeat_byte(2),
+            "transactionsRoot": zero,
+            "receiptsRoot": zero,
+            "logsBloom": Bytes::from(vec![0; 256]),
+            "difficulty": "0x0",
+            "number": "0x2a",
+            "gasLimit": "0x1c9c380",
+            "gasUsed": "0x0",
+            "timestamp": format!("{:#x}", 1_700_000_000 + 42 * 12),
+            "extraData": "0x",
+            "mixHash": B256::repeat_byte(0x11),
+            "nonce": "0x0000000000000000",
+            "baseFeePerGas": "0x2540be400",
+            "withdrawalsRoot": B256::repeat_byte(0x22),
+            "blobGasUsed": "0x0",
+            "excessBlobGas": "0x0",
+            "parentBeaconBlockRoot": B256::repeat_byte(0x22),
+            "transactions": [],
+        }))
+        .unwrap();
+        assert_eq!(header.encode().unwrap().to_vec(), encoded);
+
+        let mut tampered = header;
+        tampered.gas_used = U256::from(1);
+        assert!(tampered.encode().is_err());
+    }
+}
@@ -0,0 +1,94 @@
+//! The input and the public values of the guest.
+
+use revm::primitives::{Address, Bytes, B256, U256};
+use serde::{Deserialize, Serialize};
+
+/// The witness of a block execution, read by the guest from the stdin.
+///
+/// Every value is checked by the guest against the hash of the block, so a dishonest host can only
+/// make the execution fail.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvmInput {
+    /// The chain id the block is executed with. It is not part of the block, so it is committed
+    /// with its hash.
+    pub chain_id: u64,
+    /// The RLP-encoded header of the block.
+    pub header: Bytes,
+    /// The RLP-encoded header of the parent block, holding the state root the block is executed
+    /// on.
+    pub parent_header: Bytes,
+    /// The RLP-encoded headers of the ancestors of the parent block, newest first, for the
+    /// `BLOCKHASH` opcode.
+    pub ancestor_headers: Vec<Bytes>,
+    /// The EIP-2718 encoded transactions of the block.
+    pub transactions: Vec<Bytes>,
+    /// The accounts accessed by the block, with proofs against the parent state root.
+    pub accounts: Vec<AccountWitness>,
+    /// The bytecode of the contracts called by the block.
+    pub codes: Vec<Bytes>,
+}
+
+/// The proof of an account and of some of its storage slots, as returned by `eth_getProof`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountWitness {
+    /// The address of the account.
+    pub address: Address,
+    /// The nodes of the state trie on the path to the account.
+    pub proof: Vec<Bytes>,
+    /// The proofs of the storage slots.
+    pub storage: Vec<StorageWitness>,
+}
+
+/// The proof of a storage slot, as returned by `eth_getProof`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageWitness {
+    /// The storage slot.
+    pub slot: U256,
+    /// The nodes of the storage trie on the path to the slot.
+    pub proof: Vec<Bytes>,
+}
+
+/// The public values committed by the guest.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvmOutput {
+    /// The chain id the block was executed with.
+    pub chain_id: u64,
+    /// The hash of the executed block.
+    pub block_hash: B256,
+    /// The number of the executed block.
+    pub block_number: u64,
+    /// The hash of the parent block.
+    pub parent_hash: B256,
+    /// The state root the block was executed on.
+    pub parent_state_root: B256,
+    /// The gas used by the block.
+    pub gas_used: u64,
+    /// The outcome of each transaction of the block.
+    pub transactions: Vec<TransactionOutcome>,
+}
+
+/// The outcome of a transaction.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionOutcome {
+    /// The hash of the transaction.
+    pub hash: B256,
+    /// The address that sent the transaction.
+    pub sender: Address,
+    /// Whether the transaction succeeded, rather than reverting or halting.
+    pub success: bool,
+    /// The gas used by the transaction.
+    pub gas_used: u64,
+    /// The logs emitted by the transaction.
+    pub logs: Vec<EvmLog>,
+}
+
+/// A log emitted by a transaction.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvmLog {
+    /// The contract that emitted the log.
+    pub address: Address,
+    /// The topics of the log.
+    pub topics: Vec<B256>,
+    /// The data of the log.
+    pub data: Bytes,
+}
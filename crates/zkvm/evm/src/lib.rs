@@ -0,0 +1,140 @@
+//! A guest library for proving the execution of Ethereum blocks with MONEROCHAN.
+//!
+//! The guest executes every transaction of a block with [revm], on the state of its parent block,
+//! and commits the outcome of each transaction:
+//!
+//! ```ignore
+//! #![no_main]
+//! monerochan_runtime::entrypoint!(main);
+//!
+//! pub fn main() {
+//!     monerochan_evm::run();
+//! }
+//! ```
+//!
+//! The guest reads an [`EvmInput`] from the stdin, and commits an [`EvmOutput`] with
+//! `monerochan_lib::io::commit`. The input holds the headers and the transactions of the block,
+//! and a witness of the state it accesses: the accounts and storage slots with their Merkle
+//! Patricia Trie proofs, the bytecode of the called contracts and the headers of the ancestors
+//! read by `BLOCKHASH`. The guest checks all of it against the block hash, which it commits along
+//! with the chain id: a verifier checking these two values knows that the committed outcomes are
+//! the ones of that block.
+//!
+//! With the `host` feature, [`EvmInputBuilder`] assembles the input of a block from an Ethereum
+//! JSON-RPC endpoint supporting `eth_getProof`.
+//!
+//! The blocks from London to Cancun are supported. The post-state root of the block is not
+//! recomputed, so the output does not prove the state after the block.
+//!
+//! # Precompiles
+//!
+//! Hashing and signature recovery dominate the cost of executing blocks. The program proving them
+//! should patch `tiny-keccak` and `k256` to run them on the Keccak and secp256k1 precompiles:
+//!
+//! ```toml
+//! [patch.crates-io]
+//! tiny-keccak = { git = "https://github.com/sp1-patches/tiny-keccak", tag = "patch-2.0.2-monerochan-4.0.0" }
+//! k256 = { git = "https://github.com/sp1-patches/elliptic-curves", tag = "patch-k256-13.4-monerochan-5.0.0" }
+//! ```
+
+pub mod db;
+pub mod execute;
+pub mod header;
+#[cfg(feature = "host")]
+pub mod host;
+pub mod input;
+pub mod mpt;
+pub mod rlp;
+pub mod transaction;
+
+pub use db::{MissingWitness, WitnessDb};
+pub use execute::{execute, VerifiedBlock};
+#[cfg(feature = "host")]
+pub use host::{EvmInputBuilder, HostError};
+pub use input::{AccountWitness, EvmInput, EvmLog, EvmOutput, StorageWitness, TransactionOutcome};
+pub use revm;
+
+use monerochan_lib::io;
+use revm::primitives::{Address, B256, U256};
+use thiserror::Error;
+
+/// Errors that can occur when checking the input of a block or executing it.
+#[derive(Debug, Error)]
+pub enum EvmError {
+    /// The RLP encoding of a header, transaction or trie node is invalid.
+    #[error("invalid rlp: {0}")]
+    Rlp(&'static str),
+
+    /// A Merkle Patricia Trie proof is invalid.
+    #[error("invalid trie proof: {0}")]
+    InvalidProof(&'static str),
+
+    /// A header is invalid.
+    #[error("invalid header: {0}")]
+    InvalidHeader(&'static str),
+
+    /// The block uses a fork that is not supported.
+    #[error("unsupported fork: {0}")]
+    UnsupportedFork(&'static str),
+
+    /// A transaction is invalid.
+    #[error("invalid transaction: {0}")]
+    InvalidTransaction(&'static str),
+
+    /// A transaction has a type that is not supported.
+    #[error("unsupported transaction type {0}")]
+    UnsupportedTransactionType(u8),
+
+    /// The witness does not match the block.
+    #[error("invalid witness: {0}")]
+    InvalidWitness(&'static str),
+
+    /// The transactions do not match the transactions root of the block.
+    #[error("the transactions do not match the transactions root")]
+    TransactionsRootMismatch,
+
+    /// An account accessed by the block is missing from the witness.
+    #[error("account {0} is missing from the witness")]
+    MissingAccount(Address),
+
+    /// A storage slot accessed by the block is missing from the witness.
+    #[error("storage slot {1} of account {0} is missing from the witness")]
+    MissingStorage(Address, U256),
+
+    /// The bytecode of a contract called by the block is missing from the witness.
+    #[error("bytecode {0} is missing from the witness")]
+    MissingCode(B256),
+
+    /// The hash of a block read by the block is missing from the witness.
+    #[error("the hash of block {0} is missing from the witness")]
+    MissingBlockHash(u64),
+
+    /// A transaction could not be executed.
+    #[error("failed to execute transaction {index}: {message}")]
+    Execution {
+        /// The index of the transaction in the block.
+        index: usize,
+        /// The error of the EVM.
+        message: String,
+    },
+
+    /// The transactions used a different amount of gas than the block header.
+    #[error("the block used {actual} gas, but its header says {expected}")]
+    GasUsedMismatch {
+        /// The gas used in the header.
+        expected: u64,
+        /// The gas used by the transactions.
+        actual: u64,
+    },
+}
+
+/// Reads an [`EvmInput`] from the stdin, executes its block and commits the [`EvmOutput`].
+///
+/// # Panics
+/// Panics if the input is invalid or the block fails to execute, so no proof can be generated for
+/// it.
+pub fn run() {
+    let input = io::read::<EvmInput>();
+    let output = execute(&input).unwrap_or_else(|e| panic!("{e}"));
+    io::commit(&output);
+}
@@ -0,0 +1,385 @@
+//! Merkle Patricia Trie proofs and roots.
+//!
+//! The state and storage tries are checked with the proofs returned by `eth_getProof`, and the
+//! transactions root of a block is recomputed from its transactions.
+
+use revm::primitives::{keccak256, Bytes, B256};
+
+use crate::{
+    rlp::{self, Item},
+    EvmError,
+};
+
+/// The root of an empty trie, the Keccak-256 digest of the empty string encoding.
+pub const EMPTY_ROOT: B256 = B256::new([
+    0x56, 0xe8, 0x1f, 0x17, 0x1b, 0xcc, 0x55, 0xa6, 0xff, 0x83, 0x45, 0xe6, 0x92, 0xc0, 0xf8, 0x6e,
+    0x5b, 0x48, 0xe0, 0x1b, 0x99, 0x6c, 0xad, 0xc0, 0x01, 0x62, 0x2f, 0xb5, 0xe3, 0x63, 0xb4, 0x21,
+]);
+
+/// The nibbles of a key, most significant first.
+fn nibbles(key: &[u8]) -> Vec<u8> {
+    key.iter().flat_map(|byte| [byte >> 4, byte & 0x0f]).collect()
+}
+
+/// Decodes a hex-prefix encoded path, and returns its nibbles and whether it ends in a leaf.
+fn decode_path(encoded: &[u8]) -> Result<(Vec<u8>, bool), EvmError> {
+    let nibbles = nibbles(encoded);
+    let (is_leaf, odd) = match nibbles.first() {
+        Some(0) => (false, false),
+        Some(1) => (false, true),
+        Some(2) => (true, false),
+        Some(3) => (true, true),
+        _ => return Err(EvmError::InvalidProof("invalid path prefix")),
+    };
+    if odd {
+        Ok((nibbles[1..].to_vec(), is_leaf))
+    } else if nibbles[1] == 0 {
+        Ok((nibbles[2..].to_vec(), is_leaf))
+    } else {
+        Err(EvmError::InvalidProof("invalid path padding"))
+    }
+}
+
+/// Hex-prefix encodes a path.
+fn encode_path(nibbles: &[u8], is_leaf: bool) -> Vec<u8> {
+    let flag = if is_leaf { 2 } else { 0 };
+    let mut encoded = Vec::with_capacity(nibbles.len() / 2 + 1);
+    let rest = if nibbles.len() % 2 == 1 {
+        encoded.push(((flag + 1) << 4) | nibbles[0]);
+        &nibbles[1..]
+    } else {
+        encoded.push(flag << 4);
+        nibbles
+    };
+    encoded.extend(rest.chunks_exact(2).map(|pair| (pair[0] << 4) | pair[1]));
+    encoded
+}
+
+/// Verifies a proof of the value at the key of the trie with the given root.
+///
+/// Returns the value at the key, or `None` if the proof shows that the trie has no such key. An
+/// empty trie holds no key, whatever the proof.
+pub fn verify_proof(root: B256, key: &[u8], proof: &[Bytes]) -> Result<Option<Vec<u8>>, EvmError> {
+    if root == EMPTY_ROOT {
+        return Ok(None);
+    }
+    let key = nibbles(key);
+    let mut proof = proof.iter();
+    let mut pos = 0;
+
+    // The encoding of the next node, and the hash it is referenced by, if it is not inlined.
+    let mut next: (Option<B256>, &[u8]) = (Some(root), &[]);
+    loop {
+        let encoded: &[u8] = match next {
+            (Some(hash), _) => {
+                let node = proof.next().ok_or(EvmError::InvalidProof("missing node"))?;
+                if keccak256(node) != hash {
+                    return Err(EvmError::InvalidProof("node hash mismatch"));
+                }
+                node
+            }
+            (None, inline) => inline,
+        };
+
+        let items = Item::decode_exact(encoded)?.list()?;
+        let child = match items.as_slice() {
+            [branch @ .., value] if branch.len() == 16 => {
+                let Some(&nibble) = key.get(pos) else {
+                    let value = value.bytes()?;
+                    return Ok((!value.is_empty()).then(|| value.to_vec()));
+                };
+                pos += 1;
+                branch[nibble as usize]
+            }
+            [path, child] => {
+                let (path, is_leaf) = decode_path(path.bytes()?)?;
+                let matches = key[pos..].starts_with(&path);
+                if is_leaf {
+                    let found = matches && pos + path.len() == key.len();
+                    return found.then(|| child.bytes().map(<[u8]>::to_vec)).transpose();
+                }
+                if !matches {
+                    return Ok(None);
+                }
+                pos += path.len();
+                *child
+            }
+            _ => return Err(EvmError::InvalidProof("invalid node")),
+        };
+
+        next = match child {
+            Item { is_list: true, raw, .. } => (None, raw),
+            Item { payload: [], .. } => return Ok(None),
+            _ => (Some(child.b256()?), &[]),
+        };
+    }
+}
+
+/// The encoding of a trie node over entries sorted by key, all sharing the first `depth` nibbles.
+pub(crate) fn encode_node(entries: &[(Vec<u8>, &[u8])], depth: usize) -> Vec<u8> {
+    let mut payload = Vec::new();
+    match entries {
+        [] => return vec![rlp::EMPTY_STRING],
+        [(key, value)] => {
+            rlp::encode_bytes(&encode_path(&key[depth..], true), &mut payload);
+            rlp::encode_bytes(value, &mut payload);
+        }
+        [(first, _), .., (last, _)] => {
+            let prefix_len =
+                first[depth..].iter().zip(&last[depth..]).take_while(|(a, b)| a == b).count();
+            if prefix_len > 0 {
+                let path = &first[depth..depth + prefix_len];
+                rlp::encode_bytes(&encode_path(path, false), &mut payload);
+                encode_child(&encode_node(entries, depth + prefix_len), &mut payload);
+            } else {
+                // The keys ending at this node sort first, and hold the value of the branch.
+                let ended = entries.iter().take_while(|(key, _)| key.len() == depth).count();
+                let (ended, mut rest) = entries.split_at(ended);
+                for nibble in 0..16 {
+                    let len = rest.iter().take_while(|(key, _)| key[depth] == nibble).count();
+                    let (group, next) = rest.split_at(len);
+                    if group.is_empty() {
+                        payload.push(rlp::EMPTY_STRING);
+                    } else {
+                        encode_child(&encode_node(group, depth + 1), &mut payload);
+                    }
+                    rest = next;
+                }
+                rlp::encode_bytes(ended.first().map_or(&[][..], |(_, value)| value), &mut payload);
+            }
+        }
+    }
+    let mut encoded = Vec::new();
+    rlp::encode_list(&payload, &mut encoded);
+    encoded
+}
+
+/// Appends the reference to a child node: the node itself if it is short, its hash otherwise.
+fn encode_child(node: &[u8], out: &mut Vec<u8>) {
+    if node.len() < 32 {
+        out.extend_from_slice(node);
+    } else {
+        rlp::encode_bytes(keccak256(node).as_slice(), out);
+    }
+}
+
+/// Computes the root of the trie holding the given keys and values.
+pub fn trie_root<'a>(entries: impl IntoIterator<Item = (Vec<u8>, &'a [u8])>) -> B256 {
+    let mut entries: Vec<_> =
+        entries.into_iter().map(|(key, value)| (nibbles(&key), value)).collect();
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    entries.dedup_by(|(a, _), (b, _)| a == b);
+    keccak256(encode_node(&entries, 0))
+}
+
+/// Computes the root of the trie holding the values at their RLP-encoded indices, such as the
+/// transactions root of a block.
+pub fn ordered_trie_root<T: AsRef<[u8]>>(values: &[T]) -> B256 {
+    trie_root(values.iter().enumerate().map(|(index, value)| {
+        let mut key = Vec::new();
+        rlp::encode_u64(index as u64, &mut key);
+        (key, value.as_ref())
+    }))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use revm::primitives::{b256, hex};
+
+    use super::*;
+
+    // The nodes of the proofs of the `puppy` and `smallValues` tries of the Ethereum tests, built
+    // with an independent implementation of the trie.
+    const PUPPY_NODES: [&str; 4] = [
+        "e216a0bd3ee507e6c67cfefca98f84be47c1bbc009315fabc4405db4ba32190374572a",
+        concat!(
+            "f84080808080a094a9f95bd89698e4da1812e0518053813b4d5b87caaf6b3c6fa57e9e50c0ff68808080",
+            "cf85206f727365887374616c6c696f6e8080808080808080",
+        ),
+        "e482006fa0d43b87fdcd4217013ccc92d04662e12d36e4cc25dc690077cd821a1956fc3e36",
+        concat!(
+            "f3808080808080de17dc808080808080c63584636f696e80808080808080808085707570707980808080",
+            "80808080808476657262",
+        ),
+    ];
+
+    const SMALL_VALUES_NODES: [&str; 2] = [
+        "e216a0dfa248cf59bfe3ba749d4aeb7c927f8dab8d5681ef81adef25d3634c30d6d35d",
+        concat!(
+            "f28080d7820065d3808080808080c234648080808080808080806580ca83206f67857075707079808080",
+            "808080808080808080",
+        ),
+    ];
+
+    /// Decodes the hex-encoded nodes of a proof.
+    fn decode_nodes(nodes: &[&str]) -> Vec<Bytes> {
+        nodes.iter().map(|node| Bytes::from(hex::decode(node).unwrap())).collect()
+    }
+
+    /// Builds the proof of a key of the trie holding the given entries.
+    pub(crate) fn proof(entries: &[(Vec<u8>, Vec<u8>)], key: &[u8]) -> Vec<Bytes> {
+        let mut sorted: Vec<_> =
+            entries.iter().map(|(key, value)| (nibbles(key), value.as_slice())).collect();
+        sorted.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let key = nibbles(key);
+
+        let mut proof = Vec::new();
+        let mut group = &sorted[..];
+        let mut depth = 0;
+        loop {
+            let node = encode_node(group, depth);
+            if proof.is_empty() || node.len() >= 32 {
+                proof.push(Bytes::from(node));
+            }
+            let [(first, _), .., (last, _)] = group else {
+                return proof;
+            };
+            let prefix_len =
+                first[depth..].iter().zip(&last[depth..]).take_while(|(a, b)| a == b).count();
+            if prefix_len > 0 {
+                if !key[depth..].starts_with(&first[depth..depth + prefix_len]) {
+                    return proof;
+                }
+                depth += prefix_len;
+                continue;
+            }
+            let Some(&nibble) = key.get(depth) else {
+                return proof;
+            };
+            let start = group.iter().position(|(key, _)| key.get(depth) == Some(&nibble));
+            let len = group.iter().filter(|(key, _)| key.get(depth) == Some(&nibble)).count();
+            let Some(start) = start else {
+                return proof;
+            };
+            group = &group[start..start + len];
+            depth += 1;
+        }
+    }
+
+    #[test]
+    fn test_trie_proofs() {
+        assert_eq!(keccak256([0x80]), EMPTY_ROOT);
+        assert_eq!(trie_root([]), EMPTY_ROOT);
+        assert_eq!(verify_proof(EMPTY_ROOT, &[1], &[]).unwrap(), None);
+
+        // Keys sharing a prefix, diverging at a branch, with short inlined and long hashed leaves.
+        let entries: Vec<(Vec<u8>, Vec<u8>)> = vec![
+            (vec![0x12, 0x34, 0x56], vec![1]),
+            (vec![0x12, 0x34, 0x78], vec![2; 40]),
+            (vec![0x12, 0x35, 0x00], vec![3; 40]),
+            (vec![0xab, 0xcd, 0xef], vec![4]),
+        ];
+        let root = trie_root(entries.iter().map(|(key, value)| (key.clone(), value.as_slice())));
+        for (key, value) in &entries {
+            let proof = proof(&entries, key);
+            assert_eq!(verify_proof(root, key, &proof).unwrap().as_ref(), Some(value));
+        }
+
+        // Keys missing from the trie, ending at an empty branch slot, a diverging extension or a
+        // diverging leaf.
+        for key in [[0x50, 0x00, 0x00], [0x12, 0x00, 0x00], [0x12, 0x34, 0x57]] {
+            let proof = proof(&entries, &key);
+            assert_eq!(verify_proof(root, &key, &proof).unwrap(), None);
+        }
+
+        // Proofs with a tampered, missing or malformed node.
+        let key = &entries[1].0;
+        let mut tampered = proof(&entries, key);
+        let mut node = tampered[1].to_vec();
+        *node.last_mut().unwrap() ^= 1;
+        tampered[1] = node.into();
+        assert!(verify_proof(root, key, &tampered).is_err());
+        let truncated = &proof(&entries, key)[..1];
+        assert!(verify_proof(root, key, truncated).is_err());
+        let garbage = [Bytes::from_static(&[0xc2, 0x01])];
+        assert!(verify_proof(keccak256(&garbage[0]), key, &garbage).is_err());
+        assert!(verify_proof(B256::ZERO, key, &proof(&entries, key)).is_err());
+    }
+
+    #[test]
+    fn test_ordered_trie_root() {
+        assert_eq!(ordered_trie_root::<&[u8]>(&[]), EMPTY_ROOT);
+
+        // A single value is stored in a leaf at the path of `rlp(0) = 0x80`.
+        let value = [0xaa; 40];
+        let mut payload = Vec::new();
+        rlp::encode_bytes(&[0x20, 0x80], &mut payload);
+        rlp::encode_bytes(&value, &mut payload);
+        let mut leaf = Vec::new();
+        rlp::encode_list(&payload, &mut leaf);
+        assert_eq!(ordered_trie_root(&[value]), keccak256(&leaf));
+    }
+
+    #[test]
+    fn test_reference_tries() {
+        // The tries of the `trieanyorder` tests of the Ethereum tests, with their published roots.
+        let tries: [(&[(&str, &str)], B256); 5] = [
+            (
+                &[("do", "verb"), ("horse", "stallion"), ("doge", "coin"), ("dog", "puppy")],
+                b256!("5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84"),
+            ),
+            (
+                &[("doe", "reindeer"), ("dog", "puppy"), ("dogglesworth", "cat")],
+                b256!("8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3"),
+            ),
+            (
+                &[("foo", "bar"), ("food", "bass")],
+                b256!("17beaa1648bafa633cda809c90c04af50fc8aed3cb40d16efbddee6fdf63c4c3"),
+            ),
+            (
+                &[("be", "e"), ("dog", "puppy"), ("bed", "d")],
+                b256!("3f67c7a47520f79faa29255d2d3c084a7a6df0453116ed7232ff10277a8be68b"),
+            ),
+            (
+                &[("test", "test"), ("te", "testy")],
+                b256!("8452568af70d8d140f58d941338542f645fcca50094b20f3c3d8c3df49337928"),
+            ),
+        ];
+        for (entries, root) in tries {
+            let entries =
+                entries.iter().map(|(key, value)| (key.as_bytes().to_vec(), value.as_bytes()));
+            assert_eq!(trie_root(entries), root);
+        }
+    }
+
+    #[test]
+    fn test_reference_proofs() {
+        // `do` and `dog` are values of branch nodes, and the subtrie holding `dog` and `doge` is
+        // inlined in the branch of `do`, as is the leaf of `horse`. The missing keys end at an
+        // extension, an empty branch slot, an inlined extension and an inlined leaf.
+        let root = b256!("5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84");
+        let nodes = decode_nodes(&PUPPY_NODES);
+        let cases = [
+            ("do", Some("verb"), 4),
+            ("dog", Some("puppy"), 4),
+            ("doge", Some("coin"), 4),
+            ("horse", Some("stallion"), 2),
+            ("d", None, 3),
+            ("dogs", None, 4),
+            ("cat", None, 2),
+            ("doe", None, 4),
+            ("horses", None, 2),
+        ];
+        for (key, value, len) in cases {
+            let found = verify_proof(root, key.as_bytes(), &nodes[..len]).unwrap();
+            assert_eq!(found.as_deref(), value.map(str::as_bytes), "{key}");
+        }
+        // A proof missing a hashed node is rejected.
+        assert!(verify_proof(root, b"doge", &nodes[..3]).is_err());
+
+        // All the nodes below the root are inlined in its child.
+        let root = b256!("3f67c7a47520f79faa29255d2d3c084a7a6df0453116ed7232ff10277a8be68b");
+        let nodes = decode_nodes(&SMALL_VALUES_NODES);
+        let cases = [
+            ("be", Some("e")),
+            ("bed", Some("d")),
+            ("dog", Some("puppy")),
+            ("b", None),
+            ("beds", None),
+        ];
+        for (key, value) in cases {
+            let found = verify_proof(root, key.as_bytes(), &nodes).unwrap();
+            assert_eq!(found.as_deref(), value.map(str::as_bytes), "{key}");
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! A minimal RLP decoder and encoder, covering what is needed to check headers, transactions and
+//! Merkle Patricia Trie proofs.
+//!
+//! The decoder only accepts canonical encodings, so an item has a single valid encoding and its
+//! hash identifies it.
+
+use revm::primitives::{Address, B256, U256};
+
+use crate::EvmError;
+
+/// The encoding of the empty string.
+pub const EMPTY_STRING: u8 = 0x80;
+
+/// A decoded RLP item, borrowing the encoded data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Item<'a> {
+    /// The full encoding of the item, including its header.
+    pub raw: &'a [u8],
+    /// The payload of the item: the bytes of a string, or the encoded items of a list.
+    pub payload: &'a [u8],
+    /// Whether the item is a list.
+    pub is_list: bool,
+}
+
+impl<'a> Item<'a> {
+    /// Decodes the item at the start of the data, and returns it with the remaining data.
+    pub fn decode(data: &'a [u8]) -> Result<(Self, &'a [u8]), EvmError> {
+        let (&prefix, rest) = data.split_first().ok_or(EvmError::Rlp("unexpected end of data"))?;
+        let (is_list, header_len, payload_len) = match prefix {
+            0x00..=0x7f => {
+                let item = Self { raw: &data[..1], payload: &data[..1], is_list: false };
+                return Ok((item, rest));
+            }
+            0x80..=0xb7 => (false, 1, (prefix - 0x80) as usize),
+            0xb8..=0xbf => (false, 1 + (prefix - 0xb7) as usize, long_len(rest, prefix - 0xb7)?),
+            0xc0..=0xf7 => (true, 1, (prefix - 0xc0) as usize),
+            0xf8..=0xff => (true, 1 + (prefix - 0xf7) as usize, long_len(rest, prefix - 0xf7)?),
+        };
+        let len = header_len
+            .checked_add(payload_len)
+            .filter(|&len| len <= data.len())
+            .ok_or(EvmError::Rlp("item longer than the data"))?;
+        let item = Self { raw: &data[..len], payload: &data[header_len..len], is_list };
+        if !is_list && payload_len == 1 && item.payload[0] < 0x80 {
+            return Err(EvmError::Rlp("non-canonical single byte"));
+        }
+        Ok((item, &data[len..]))
+    }
+
+    /// Decodes data holding exactly one item.
+    pub fn decode_exact(data: &'a [u8]) -> Result<Self, EvmError> {
+        match Self::decode(data)? {
+            (item, []) => Ok(item),
+            _ => Err(EvmError::Rlp("trailing data")),
+        }
+    }
+
+    /// The items of a list.
+    pub fn list(&self) -> Result<Vec<Item<'a>>, EvmError> {
+        if !self.is_list {
+            return Err(EvmError::Rlp("expected a list"));
+        }
+        let mut items = Vec::new();
+        let mut rest = self.payload;
+        while !rest.is_empty() {
+            let (item, next) = Self::decode(rest)?;
+            items.push(item);
+            rest = next;
+        }
+        Ok(items)
+    }
+
+    /// The bytes of a string.
+    pub fn bytes(&self) -> Result<&'a [u8], EvmError> {
+        if self.is_list {
+            return Err(EvmError::Rlp("expected a string"));
+        }
+        Ok(self.payload)
+    }
+
+    /// A big-endian integer of at most 32 bytes, without leading zeros.
+    pub fn u256(&self) -> Result<U256, EvmError> {
+        let bytes = self.bytes()?;
+        if bytes.len() > 32 {
+            return Err(EvmError::Rlp("integer overflow"));
+        }
+        if bytes.first() == Some(&0) {
+            return Err(EvmError::Rlp("integer with leading zeros"));
+        }
+        Ok(U256::from_be_slice(bytes))
+    }
+
+    /// A big-endian integer of at most 8 bytes, without leading zeros.
+    pub fn u64(&self) -> Result<u64, EvmError> {
+        self.u256()?.try_into().map_err(|_| EvmError::Rlp("integer overflow"))
+    }
+
+    /// A 32-byte hash.
+    pub fn b256(&self) -> Result<B256, EvmError> {
+        B256::try_from(self.bytes()?).map_err(|_| EvmError::Rlp("expected 32 bytes"))
+    }
+
+    /// A 20-byte address.
+    pub fn address(&self) -> Result<Address, EvmError> {
+        Address::try_from(self.bytes()?).map_err(|_| EvmError::Rlp("expected 20 bytes"))
+    }
+}
+
+/// Reads the big-endian length of a long item.
+fn long_len(data: &[u8], len_of_len: u8) -> Result<usize, EvmError> {
+    let bytes = data.get(..len_of_len as usize).ok_or(EvmError::Rlp("unexpected end of data"))?;
+    if bytes[0] == 0 {
+        return Err(EvmError::Rlp("length with leading zeros"));
+    }
+    if bytes.len() > std::mem::size_of::<usize>() {
+        return Err(EvmError::Rlp("length overflow"));
+    }
+    let len = bytes.iter().fold(0usize, |len, &byte| (len << 8) | byte as usize);
+    if len < 56 {
+        return Err(EvmError::Rlp("non-canonical length"));
+    }
+    Ok(len)
+}
+
+/// Appends the header of an item with the given payload length.
+fn encode_header(len: usize, offset: u8, out: &mut Vec<u8>) {
+    if len < 56 {
+        out.push(offset + len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let start = bytes.iter().position(|&byte| byte != 0).unwrap();
+        out.push(offset + 55 + (bytes.len() - start) as u8);
+        out.extend_from_slice(&bytes[start..]);
+    }
+}
+
+/// Appends the encoding of a string.
+pub fn encode_bytes(bytes: &[u8], out: &mut Vec<u8>) {
+    if let [byte @ 0x00..=0x7f] = bytes {
+        out.push(*byte);
+    } else {
+        encode_header(bytes.len(), 0x80, out);
+        out.extend_from_slice(bytes);
+    }
+}
+
+/// Appends the encoding of an integer.
+pub fn encode_u64(value: u64, out: &mut Vec<u8>) {
+    let bytes = value.to_be_bytes();
+    let start = bytes.iter().position(|&byte| byte != 0).unwrap_or(bytes.len());
+    encode_bytes(&bytes[start..], out);
+}
+
+/// Appends the encoding of a list, given the concatenated encodings of its items.
+pub fn encode_list(payload: &[u8], out: &mut Vec<u8>) {
+    encode_header(payload.len(), 0xc0, out);
+    out.extend_from_slice(payload);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rlp_roundtrip() {
+        for value in [0, 1, 0x7f, 0x80, 0x400, u64::MAX] {
+            let mut out = Vec::new();
+            encode_u64(value, &mut out);
+            assert_eq!(Item::decode_exact(&out).unwrap().u64().unwrap(), value);
+        }
+
+        let long = vec![0xaa; 60];
+        let mut payload = Vec::new();
+        encode_bytes(b"dog", &mut payload);
+        encode_bytes(&long, &mut payload);
+        let mut out = Vec::new();
+        encode_list(&payload, &mut out);
+        assert_eq!(&out[..2], &[0xf8, 4 + 62]);
+
+        let items = Item::decode_exact(&out).unwrap().list().unwrap();
+        assert_eq!(items[0].bytes().unwrap(), b"dog");
+        assert_eq!(items[0].raw, &[0x83, b'd', b'o', b'g']);
+        assert_eq!(items[1].bytes().unwrap(), &long[..]);
+    }
+
+    #[test]
+    fn test_rlp_malformed() {
+        // Truncated items.
+        assert!(Item::decode_exact(&[]).is_err());
+        assert!(Item::decode_exact(&[0x83, b'd', b'o']).is_err());
+        assert!(Item::decode_exact(&[0xb9, 0x01]).is_err());
+        // Trailing data.
+        assert!(Item::decode_exact(&[0x01, 0x02]).is_err());
+        // Non-canonical encodings.
+        assert!(Item::decode_exact(&[0x81, 0x05]).is_err());
+        assert!(Item::decode_exact(&[0xb8, 0x01, 0xaa]).is_err());
+        assert!(Item::decode_exact(&[0xb9, 0x00, 0x38]).is_err());
+        assert!(Item::decode_exact(&[0x82, 0x00, 0x01]).unwrap().u64().is_err());
+        // Huge lengths.
+        let huge = [0xbf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+        assert!(Item::decode_exact(&huge).is_err());
+        // Type mismatches.
+        assert!(Item::decode_exact(&[0xc0]).unwrap().bytes().is_err());
+        assert!(Item::decode_exact(&[0x80]).unwrap().list().is_err());
+        assert!(Item::decode_exact(&[0x80]).unwrap().b256().is_err());
+    }
+}
@@ -0,0 +1,346 @@
+//! Signed transactions.
+//!
+//! Transactions are decoded from their EIP-2718 encoding, the form they are hashed with in the
+//! transactions trie, and their senders are recovered from their signatures.
+
+use k256::{
+    ecdsa::{RecoveryId, Signature, VerifyingKey},
+    FieldBytes,
+};
+use revm::primitives::{keccak256, AccessListItem, Address, Bytes, TxEnv, TxKind, B256, U256};
+
+use crate::{
+    rlp::{self, Item},
+    EvmError,
+};
+
+/// A decoded transaction, with its recovered sender.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    /// The hash of the transaction.
+    pub hash: B256,
+    /// The EIP-2718 type of the transaction, 0 for legacy transactions.
+    pub tx_type: u8,
+    /// The address that signed the transaction.
+    pub sender: Address,
+    /// The chain id the transaction is signed for, `None` for legacy transactions without replay
+    /// protection.
+    pub chain_id: Option<u64>,
+    /// The nonce of the sender.
+    pub nonce: u64,
+    /// The gas price of legacy and access list transactions, the maximum fee per gas of the
+    /// others.
+    pub gas_price: U256,
+    /// The maximum priority fee per gas, for dynamic fee transactions.
+    pub max_priority_fee_per_gas: Option<U256>,
+    /// The maximum gas the transaction may use.
+    pub gas_limit: u64,
+    /// The called address, or `Create` for contract creations.
+    pub to: TxKind,
+    /// The value transferred.
+    pub value: U256,
+    /// The call data, or the init code of contract creations.
+    pub input: Bytes,
+    /// The addresses and storage keys accessed by the transaction.
+    pub access_list: Vec<AccessListItem>,
+    /// The maximum fee per blob gas, for blob transactions.
+    pub max_fee_per_blob_gas: Option<U256>,
+    /// The versioned hashes of the blobs, for blob transactions.
+    pub blob_versioned_hashes: Vec<B256>,
+}
+
+impl Transaction {
+    /// Decodes an EIP-2718 encoded transaction and recovers its sender.
+    pub fn decode(encoded: &[u8]) -> Result<Self, EvmError> {
+        let tx_type = match encoded.first() {
+            Some(&byte) if byte <= 0x7f => byte,
+            _ => 0,
+        };
+        let body = if tx_type == 0 { encoded } else { &encoded[1..] };
+        let fields = Item::decode_exact(body)?.list()?;
+
+        // The number of signed fields, which precede the signature.
+        let signed = match (tx_type, fields.len()) {
+            (0, 9) => 6,
+            (1, 11) => 8,
+            (2, 12) => 9,
+            (3, 14) => 11,
+            (0..=3, _) => return Err(EvmError::InvalidTransaction("invalid number of fields")),
+            _ => return Err(EvmError::UnsupportedTransactionType(tx_type)),
+        };
+        let (unsigned, signature) = fields.split_at(signed);
+        let field = |index: usize| unsigned[index - usize::from(tx_type == 0)];
+
+        let (chain_id, recovery_id, signing_hash) = if tx_type == 0 {
+            // EIP-155 transactions sign their chain id and encode it in `v`.
+            let v = signature[0].u64()?;
+            let (chain_id, parity) = match v {
+                27 | 28 => (None, v - 27),
+                35.. => (Some((v - 35) / 2), (v - 35) % 2),
+                _ => return Err(EvmError::InvalidTransaction("invalid signature v")),
+            };
+            let mut payload: Vec<u8> = unsigned.iter().flat_map(|item| item.raw).copied().collect();
+            if let Some(chain_id) = chain_id {
+                rlp::encode_u64(chain_id, &mut payload);
+                rlp::encode_u64(0, &mut payload);
+                rlp::encode_u64(0, &mut payload);
+            }
+            let mut message = Vec::new();
+            rlp::encode_list(&payload, &mut message);
+            (chain_id, parity, keccak256(message))
+        } else {
+            let payload: Vec<u8> = unsigned.iter().flat_map(|item| item.raw).copied().collect();
+            let mut message = vec![tx_type];
+            rlp::encode_list(&payload, &mut message);
+            (Some(unsigned[0].u64()?), signature[0].u64()?, keccak256(message))
+        };
+        let sender = recover_sender(signing_hash, recovery_id, signature[1], signature[2])?;
+
+        let (gas_price, max_priority_fee_per_gas, fees_end) = match tx_type {
+            0 | 1 => (field(2).u256()?, None, 3),
+            _ => (field(3).u256()?, Some(field(2).u256()?), 4),
+        };
+        let to = match field(fees_end + 1) {
+            item if item.bytes()?.is_empty() && tx_type != 3 => TxKind::Create,
+            item => TxKind::Call(item.address()?),
+        };
+        let access_list = match tx_type {
+            0 => Vec::new(),
+            _ => decode_access_list(field(fees_end + 4))?,
+        };
+        let (max_fee_per_blob_gas, blob_versioned_hashes) = match tx_type {
+            3 => (
+                Some(field(9).u256()?),
+                field(10).list()?.iter().map(Item::b256).collect::<Result<_, _>>()?,
+            ),
+            _ => (None, Vec::new()),
+        };
+
+        Ok(Self {
+            hash: keccak256(encoded),
+            tx_type,
+            sender,
+            chain_id,
+            nonce: field(1).u64()?,
+            gas_price,
+            max_priority_fee_per_gas,
+            gas_limit: field(fees_end).u64()?,
+            to,
+            value: field(fees_end + 2).u256()?,
+            input: Bytes::copy_from_slice(field(fees_end + 3).bytes()?),
+            access_list,
+            max_fee_per_blob_gas,
+            blob_versioned_hashes,
+        })
+    }
+
+    /// Fills the transaction environment of the EVM with the transaction.
+    pub fn fill_tx_env(&self, env: &mut TxEnv) {
+        env.caller = self.sender;
+        env.gas_limit = self.gas_limit;
+        env.gas_price = self.gas_price;
+        env.gas_priority_fee = self.max_priority_fee_per_gas;
+        env.transact_to = self.to;
+        env.value = self.value;
+        env.data = self.input.clone();
+        env.nonce = Some(self.nonce);
+        env.chain_id = self.chain_id;
+        env.access_list = self.access_list.clone();
+        env.max_fee_per_blob_gas = self.max_fee_per_blob_gas;
+        env.blob_hashes = self.blob_versioned_hashes.clone();
+    }
+}
+
+/// Decodes an EIP-2930 access list.
+fn decode_access_list(item: Item<'_>) -> Result<Vec<AccessListItem>, EvmError> {
+    item.list()?
+        .iter()
+        .map(|entry| match entry.list()?.as_slice() {
+            [address, keys] => Ok(AccessListItem {
+                address: address.address()?,
+                storage_keys: keys.list()?.iter().map(Item::b256).collect::<Result<_, _>>()?,
+            }),
+            _ => Err(EvmError::InvalidTransaction("invalid access list")),
+        })
+        .collect()
+}
+
+/// Recovers the address that signed the hash.
+///
+/// In the zkVM, this runs on the secp256k1 precompiles when `k256` is patched.
+fn recover_sender(
+    hash: B256,
+    parity: u64,
+    r: Item<'_>,
+    s: Item<'_>,
+) -> Result<Address, EvmError> {
+    let invalid = || EvmError::InvalidTransaction("invalid signature");
+    let r = FieldBytes::from(r.u256()?.to_be_bytes::<32>());
+    let s = FieldBytes::from(s.u256()?.to_be_bytes::<32>());
+    let signature = Signature::from_scalars(r, s).map_err(|_| invalid())?;
+    // EIP-2 forbids signatures with a high `s`, which have a malleable twin.
+    if signature.normalize_s().is_some() {
+        return Err(invalid());
+    }
+    let recovery_id =
+        u8::try_from(parity).ok().and_then(RecoveryId::from_byte).ok_or_else(invalid)?;
+    let key = VerifyingKey::recover_from_prehash(hash.as_slice(), &signature, recovery_id)
+        .map_err(|_| invalid())?;
+    let point = key.to_encoded_point(false);
+    Ok(Address::from_slice(&keccak256(&point.as_bytes()[1..])[12..]))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use revm::primitives::{address, b256, hex};
+
+    use super::*;
+
+    /// The signed transaction of the EIP-155 example.
+    pub(crate) const EIP155_TX: &str = "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+
+    /// A dynamic fee transaction with an access list, signed by the key of the EIP-155 example.
+    const EIP1559_TX: &str = "02f8a30180843b9aca0084773594008262d49435353535353535353535353535353535353535350180f838f7943535353535353535353535353535353535353535e1a0000000000000000000000000000000000000000000000000000000000000000101a0d47644539acec3da5e3ecf5fe8863c628a9c97e8b71e9ea9167a6f4f83c03c32a0598e120cfa7d599a20c008d15a5b913b9088c44d1de815c1ca65b224ccde1d98";
+
+    // The transactions below were signed with an independent implementation of secp256k1, so
+    // that they check the recovery of their senders.
+
+    /// An access list transaction, signed by the key of the EIP-155 example.
+    const ACCESS_LIST_TX: &str = concat!(
+        "01f8a101038504a817c8008275309435353535353535353535353535353535353535350582abcdf838f7",
+        "943535353535353535353535353535353535353535e1a000000000000000000000000000000000000000",
+        "0000000000000000000000000101a07592aab5d43618dda13fba71e3993cd7517a712d3da49664c06ee1",
+        "bd3d1f70afa072f9933eee4015869c67bd5bd21d477795a1ba9806bbc906a195023bb2cc1c20",
+    );
+
+    /// A blob transaction, signed by the key of the EIP-155 example.
+    const BLOB_TX: &str = concat!(
+        "03f88d0104843b9aca0084773594008252089435353535353535353535353535353535353535358080c0",
+        "03e1a0012222222222222222222222222222222222222222222222222222222222222280a0e5740e63ba",
+        "d28081ed7cf654dd6c19029ca03382fc05ab5f5dda81f2c55b845ba06c613eb9623f03b3edb9cebbc6d3",
+        "5124a46547055c9b879d227632858a282231",
+    );
+
+    /// A dynamic fee contract creation, signed by the key of the EIP-155 example.
+    const CREATE_TX: &str = concat!(
+        "02f8610105843b9aca008477359400830186a080808a602a60005260206000f3c001a0ec6d499aefd540",
+        "e90357f1004a136049d1f7df5ad99c44c46e3ed4169e40acb6a0284cdcf7effd9536de38a0e1a5fca75c",
+        "33290a3bb0cea7f16dc434ad3e4afaf0",
+    );
+
+    /// A legacy transaction without replay protection, signed by the key of the EIP-155
+    /// example.
+    const PRE_EIP155_TX: &str = concat!(
+        "f864078504a817c80082520894353535353535353535353535353535353535353501801ba071550e6c83",
+        "a9381f35c568d1a80e11fa3e0efc97dfd0e0f17492a2edb64c37a9a01f3fb34a70fab9835bbbe3eaa2b3",
+        "dd850ffc5fbe73d4a7e43c7c06a55107c7e7",
+    );
+
+    const SENDER: Address = address!("9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f");
+    const RECIPIENT: Address = address!("3535353535353535353535353535353535353535");
+
+    #[test]
+    fn test_decode_legacy_transaction() {
+        let tx = Transaction::decode(&hex::decode(EIP155_TX).unwrap()).unwrap();
+        assert_eq!(
+            tx.hash,
+            b256!("33469b22e9f636356c4160a87eb19df52b7412e8eac32a4a55ffe88ea8350788")
+        );
+        assert_eq!(tx.sender, SENDER);
+        assert_eq!(tx.chain_id, Some(1));
+        assert_eq!(tx.nonce, 9);
+        assert_eq!(tx.gas_price, U256::from(20_000_000_000u64));
+        assert_eq!(tx.gas_limit, 21_000);
+        assert_eq!(tx.to, TxKind::Call(RECIPIENT));
+        assert_eq!(tx.value, U256::from(10u64.pow(18)));
+        assert!(tx.input.is_empty());
+    }
+
+    #[test]
+    fn test_decode_dynamic_fee_transaction() {
+        let tx = Transaction::decode(&hex::decode(EIP1559_TX).unwrap()).unwrap();
+        assert_eq!(tx.tx_type, 2);
+        assert_eq!(tx.sender, SENDER);
+        assert_eq!(tx.chain_id, Some(1));
+        assert_eq!(tx.max_priority_fee_per_gas, Some(U256::from(1_000_000_000u64)));
+        assert_eq!(tx.gas_price, U256::from(2_000_000_000u64));
+        assert_eq!(tx.gas_limit, 25_300);
+        assert_eq!(tx.value, U256::from(1));
+        assert_eq!(tx.access_list.len(), 1);
+        assert_eq!(tx.access_list[0].address, RECIPIENT);
+        assert_eq!(tx.access_list[0].storage_keys, vec![B256::with_last_byte(1)]);
+    }
+
+    #[test]
+    fn test_decode_invalid_transaction() {
+        // A tampered value changes the recovered sender.
+        let mut tampered = hex::decode(EIP155_TX).unwrap();
+        tampered[30] ^= 1;
+        assert!(Transaction::decode(&tampered).map_or(true, |tx| tx.sender != SENDER));
+
+        // A malleated signature with a high `s`.
+        let mut encoded = hex::decode(EIP155_TX).unwrap();
+        let s = U256::from_be_slice(&encoded[encoded.len() - 32..]);
+        let order = hex::decode("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141");
+        let order = U256::from_be_slice(&order.unwrap());
+        let len = encoded.len();
+        encoded[len - 32..].copy_from_slice(&(order - s).to_be_bytes::<32>());
+        assert!(Transaction::decode(&encoded).is_err());
+
+        let encoded = hex::decode(EIP1559_TX).unwrap();
+        assert!(Transaction::decode(&encoded[..encoded.len() - 1]).is_err());
+        let mut set_code = encoded.clone();
+        set_code[0] = 4;
+        assert!(matches!(
+            Transaction::decode(&set_code),
+            Err(EvmError::UnsupportedTransactionType(4))
+        ));
+    }
+
+    #[test]
+    fn test_decode_reference_transactions() {
+        let tx = Transaction::decode(&hex::decode(ACCESS_LIST_TX).unwrap()).unwrap();
+        assert_eq!(
+            tx.hash,
+            b256!("0ad176562e335e308a650f9f38184caedb79734dc139752246ea4af800b677fc")
+        );
+        assert_eq!((tx.tx_type, tx.sender, tx.chain_id, tx.nonce), (1, SENDER, Some(1), 3));
+        assert_eq!(tx.gas_price, U256::from(20_000_000_000u64));
+        assert_eq!(tx.max_priority_fee_per_gas, None);
+        assert_eq!(tx.gas_limit, 30_000);
+        assert_eq!(tx.to, TxKind::Call(RECIPIENT));
+        assert_eq!(tx.value, U256::from(5));
+        assert_eq!(tx.input, Bytes::from_static(&[0xab, 0xcd]));
+        assert_eq!(tx.access_list[0].storage_keys, vec![B256::with_last_byte(1)]);
+
+        let tx = Transaction::decode(&hex::decode(BLOB_TX).unwrap()).unwrap();
+        assert_eq!(
+            tx.hash,
+            b256!("458dffa4863f7992d0936707f7f323c395b39ec81ab18f343875c61b42d45e1d")
+        );
+        assert_eq!((tx.tx_type, tx.sender, tx.chain_id, tx.nonce), (3, SENDER, Some(1), 4));
+        assert_eq!(tx.to, TxKind::Call(RECIPIENT));
+        assert!(tx.access_list.is_empty());
+        assert_eq!(tx.max_fee_per_blob_gas, Some(U256::from(3)));
+        let mut versioned_hash = B256::repeat_byte(0x22);
+        versioned_hash[0] = 1;
+        assert_eq!(tx.blob_versioned_hashes, vec![versioned_hash]);
+
+        let tx = Transaction::decode(&hex::decode(CREATE_TX).unwrap()).unwrap();
+        assert_eq!(
+            tx.hash,
+            b256!("4f339584f21b509465487297b34b8f9757fad4b968051d1b9c5a7d85f786a23c")
+        );
+        assert_eq!((tx.tx_type, tx.sender, tx.chain_id, tx.nonce), (2, SENDER, Some(1), 5));
+        assert_eq!(tx.to, TxKind::Create);
+        assert_eq!(tx.input, Bytes::from(hex::decode("602a60005260206000f3").unwrap()));
+
+        let tx = Transaction::decode(&hex::decode(PRE_EIP155_TX).unwrap()).unwrap();
+        assert_eq!(
+            tx.hash,
+            b256!("863f6895e91dd75d203cf3fcec3dc2343326385d0fb27f622414c29b317a5d6f")
+        );
+        assert_eq!((tx.tx_type, tx.sender, tx.chain_id, tx.nonce), (0, SENDER, None, 7));
+        assert_eq!(tx.to, TxKind::Call(RECIPIENT));
+    }
+}
@@ -0,0 +1,83 @@
+//! A JSON push parser for MONEROCHAN guests.
+//!
+//! Deserializing JSON with `serde_json` in a guest spends most of its cycles allocating and
+//! copying: every string and every nested value is built on the heap, even when only a few fields
+//! of a document are used, as when verifying an API response. This crate parses a document in a
+//! single pass and pushes its tokens to a [`Handler`], without allocating:
+//!
+//! - Strings and numbers are borrowed from the input, and are only unescaped or converted when the
+//!   handler reads them.
+//! - The nesting of objects and arrays is tracked with a bit per level, so the parser has no stack
+//!   and no recursion.
+//! - The handler can stop the parse as soon as it has read what it needs.
+//!
+//! The whole document is still validated up to the point where the handler stops, so a guest
+//! rejects malformed input. For the common case of reading a single value, [`get`] runs a handler
+//! which follows a path of keys and indices:
+//!
+//! ```
+//! use monerochan_json::{get, Scalar, Segment};
+//!
+//! let body = br#"{"data": {"prices": [{"symbol": "XMR", "usd": 162.5}]}}"#;
+//! let path =
+//!     [Segment::Key("data"), Segment::Key("prices"), Segment::Index(0), Segment::Key("usd")];
+//! let Some(Scalar::Number(price)) = get(body, &path).unwrap() else { panic!() };
+//! assert_eq!(price.as_f64(), Some(162.5));
+//! ```
+
+#![no_std]
+
+mod parser;
+mod path;
+mod scalar;
+
+pub use parser::*;
+pub use path::*;
+pub use scalar::*;
+
+use thiserror::Error;
+
+/// The maximum nesting depth of objects and arrays.
+pub const MAX_DEPTH: usize = 128;
+
+/// An error found when parsing a document, at a byte offset of the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("{kind} at offset {offset}")]
+pub struct Error {
+    /// The kind of the error.
+    pub kind: ErrorKind,
+    /// The offset of the byte at which the error was found.
+    pub offset: usize,
+}
+
+/// The kinds of parse errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum ErrorKind {
+    /// The input ended in the middle of the document.
+    #[error("unexpected end of input")]
+    UnexpectedEnd,
+
+    /// A byte which cannot start or continue the document at this position.
+    #[error("unexpected character")]
+    UnexpectedCharacter,
+
+    /// An invalid escape sequence in a string.
+    #[error("invalid escape")]
+    InvalidEscape,
+
+    /// An unescaped control character in a string.
+    #[error("control character in string")]
+    ControlCharacter,
+
+    /// A malformed number.
+    #[error("invalid number")]
+    InvalidNumber,
+
+    /// The document is nested deeper than [`MAX_DEPTH`].
+    #[error("nested too deep")]
+    TooDeep,
+
+    /// Non-whitespace characters after the document.
+    #[error("trailing characters")]
+    TrailingCharacters,
+}
@@ -0,0 +1,268 @@
+use core::ops::ControlFlow;
+
+use crate::{Error, ErrorKind, Number, Scalar, Str, MAX_DEPTH};
+
+/// A token of a document, pushed to a [`Handler`] in document order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event<'a> {
+    /// The start of an object.
+    ObjectStart,
+    /// The end of an object.
+    ObjectEnd,
+    /// The start of an array.
+    ArrayStart,
+    /// The end of an array.
+    ArrayEnd,
+    /// The key of the next member of an object.
+    Key(Str<'a>),
+    /// A string, number, boolean or null value.
+    Scalar(Scalar<'a>),
+}
+
+/// A receiver of the events of a document.
+pub trait Handler<'a> {
+    /// Handles an event, and returns whether to continue the parse.
+    fn event(&mut self, event: Event<'a>) -> ControlFlow<()>;
+}
+
+impl<'a, F: FnMut(Event<'a>) -> ControlFlow<()>> Handler<'a> for F {
+    fn event(&mut self, event: Event<'a>) -> ControlFlow<()> {
+        self(event)
+    }
+}
+
+/// Parses a document, pushing its events to a handler.
+///
+/// Returns once the document is parsed, or as soon as the handler breaks, in which case the rest
+/// of the input is not validated.
+pub fn parse<'a>(input: &'a [u8], handler: &mut impl Handler<'a>) -> Result<(), Error> {
+    Parser { input, pos: 0 }.parse(handler)
+}
+
+/// The state of a parse.
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+/// Pushes an event, and returns from the parse if the handler breaks.
+macro_rules! emit {
+    ($handler:expr, $event:expr) => {
+        if $handler.event($event).is_break() {
+            return Ok(());
+        }
+    };
+}
+
+impl<'a> Parser<'a> {
+    fn parse(&mut self, handler: &mut impl Handler<'a>) -> Result<(), Error> {
+        // Bit `i` of the stack is set if the container at depth `i + 1` is an array.
+        let mut stack = 0u128;
+        let mut depth = 0;
+
+        'value: loop {
+            self.skip_whitespace();
+            match self.next_byte()? {
+                b'{' => {
+                    if depth == MAX_DEPTH {
+                        return Err(self.error(ErrorKind::TooDeep));
+                    }
+                    emit!(handler, Event::ObjectStart);
+                    self.skip_whitespace();
+                    if self.peek() == Some(b'}') {
+                        self.pos += 1;
+                        emit!(handler, Event::ObjectEnd);
+                    } else {
+                        stack &= !(1 << depth);
+                        depth += 1;
+                        emit!(handler, Event::Key(self.key()?));
+                        continue 'value;
+                    }
+                }
+                b'[' => {
+                    if depth == MAX_DEPTH {
+                        return Err(self.error(ErrorKind::TooDeep));
+                    }
+                    emit!(handler, Event::ArrayStart);
+                    self.skip_whitespace();
+                    if self.peek() == Some(b']') {
+                        self.pos += 1;
+                        emit!(handler, Event::ArrayEnd);
+                    } else {
+                        stack |= 1 << depth;
+                        depth += 1;
+                        continue 'value;
+                    }
+                }
+                b'"' => emit!(handler, Event::Scalar(Scalar::String(self.string()?))),
+                b'-' | b'0'..=b'9' => {
+                    self.pos -= 1;
+                    emit!(handler, Event::Scalar(Scalar::Number(self.number()?)));
+                }
+                b't' => {
+                    self.literal(b"rue")?;
+                    emit!(handler, Event::Scalar(Scalar::Bool(true)));
+                }
+                b'f' => {
+                    self.literal(b"alse")?;
+                    emit!(handler, Event::Scalar(Scalar::Bool(false)));
+                }
+                b'n' => {
+                    self.literal(b"ull")?;
+                    emit!(handler, Event::Scalar(Scalar::Null));
+                }
+                _ => return Err(self.error_before(ErrorKind::UnexpectedCharacter)),
+            }
+
+            // After a value, close containers until the next member or element.
+            while depth > 0 {
+                let in_array = stack & (1 << (depth - 1)) != 0;
+                self.skip_whitespace();
+                match self.next_byte()? {
+                    b',' if in_array => continue 'value,
+                    b',' => {
+                        self.skip_whitespace();
+                        emit!(handler, Event::Key(self.key()?));
+                        continue 'value;
+                    }
+                    b']' if in_array => {
+                        depth -= 1;
+                        emit!(handler, Event::ArrayEnd);
+                    }
+                    b'}' if !in_array => {
+                        depth -= 1;
+                        emit!(handler, Event::ObjectEnd);
+                    }
+                    _ => return Err(self.error_before(ErrorKind::UnexpectedCharacter)),
+                }
+            }
+            break;
+        }
+
+        self.skip_whitespace();
+        if self.pos != self.input.len() {
+            return Err(self.error(ErrorKind::TrailingCharacters));
+        }
+        Ok(())
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn next_byte(&mut self) -> Result<u8, Error> {
+        let byte = self.peek().ok_or_else(|| self.error(ErrorKind::UnexpectedEnd))?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\n' | b'\r' | b'\t') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    /// Returns an error at the current position.
+    fn error(&self, kind: ErrorKind) -> Error {
+        Error { kind, offset: self.pos }
+    }
+
+    /// Returns an error at the byte which was just read.
+    fn error_before(&self, kind: ErrorKind) -> Error {
+        Error { kind, offset: self.pos - 1 }
+    }
+
+    /// Parses the key of a member and its colon.
+    fn key(&mut self) -> Result<Str<'a>, Error> {
+        if self.next_byte()? != b'"' {
+            return Err(self.error_before(ErrorKind::UnexpectedCharacter));
+        }
+        let key = self.string()?;
+        self.skip_whitespace();
+        if self.next_byte()? != b':' {
+            return Err(self.error_before(ErrorKind::UnexpectedCharacter));
+        }
+        Ok(key)
+    }
+
+    /// Parses a string after its opening quote.
+    fn string(&mut self) -> Result<Str<'a>, Error> {
+        let start = self.pos;
+        let mut escaped = false;
+        loop {
+            match self.next_byte()? {
+                b'"' => break,
+                b'\\' => {
+                    escaped = true;
+                    match self.next_byte()? {
+                        b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't' => {}
+                        b'u' => {
+                            for _ in 0..4 {
+                                if !self.next_byte()?.is_ascii_hexdigit() {
+                                    return Err(self.error_before(ErrorKind::InvalidEscape));
+                                }
+                            }
+                        }
+                        _ => return Err(self.error_before(ErrorKind::InvalidEscape)),
+                    }
+                }
+                0..=0x1f => return Err(self.error_before(ErrorKind::ControlCharacter)),
+                _ => {}
+            }
+        }
+        Ok(Str::new(&self.input[start..self.pos - 1], escaped))
+    }
+
+    /// Parses a number.
+    fn number(&mut self) -> Result<Number<'a>, Error> {
+        let start = self.pos;
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        match self.next_byte()? {
+            b'0' => {}
+            b'1'..=b'9' => self.skip_digits(),
+            _ => return Err(self.error_before(ErrorKind::InvalidNumber)),
+        }
+        let mut integer = true;
+        if self.peek() == Some(b'.') {
+            integer = false;
+            self.pos += 1;
+            self.digits()?;
+        }
+        if let Some(b'e' | b'E') = self.peek() {
+            integer = false;
+            self.pos += 1;
+            if let Some(b'+' | b'-') = self.peek() {
+                self.pos += 1;
+            }
+            self.digits()?;
+        }
+        Ok(Number::new(&self.input[start..self.pos], integer))
+    }
+
+    /// Skips one or more digits.
+    fn digits(&mut self) -> Result<(), Error> {
+        if !self.next_byte()?.is_ascii_digit() {
+            return Err(self.error_before(ErrorKind::InvalidNumber));
+        }
+        self.skip_digits();
+        Ok(())
+    }
+
+    fn skip_digits(&mut self) {
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    /// Parses the rest of `true`, `false` or `null`.
+    fn literal(&mut self, rest: &[u8]) -> Result<(), Error> {
+        for &expected in rest {
+            if self.next_byte()? != expected {
+                return Err(self.error_before(ErrorKind::UnexpectedCharacter));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+use core::ops::ControlFlow;
+
+use crate::{parse, Error, Event, Handler, Scalar};
+
+/// A step of a path into a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Segment<'p> {
+    /// The member of an object with this key.
+    Key(&'p str),
+    /// The element of an array at this index.
+    Index(usize),
+}
+
+/// Returns the scalar at a path of a document, or `None` if there is no value at the path or the
+/// value is an object or an array.
+///
+/// The parse stops at the value, so the rest of the document is not validated.
+pub fn get<'a>(input: &'a [u8], path: &[Segment<'_>]) -> Result<Option<Scalar<'a>>, Error> {
+    let mut getter = Getter {
+        path,
+        depth: 0,
+        matched: 0,
+        in_array: false,
+        index: 0,
+        key_matches: false,
+        found: None,
+    };
+    parse(input, &mut getter)?;
+    Ok(getter.found)
+}
+
+/// A handler which follows a path.
+///
+/// The value at the end of the matched segments of the path is selected, and its children are at
+/// depth `matched + 1`. A child is selected if it matches the next segment.
+struct Getter<'a, 'p> {
+    path: &'p [Segment<'p>],
+    /// The number of open objects and arrays.
+    depth: usize,
+    /// The number of matched segments.
+    matched: usize,
+    /// Whether the selected container is an array.
+    in_array: bool,
+    /// The index of the next element of the selected array.
+    index: usize,
+    /// Whether the last key of the selected object matches the next segment.
+    key_matches: bool,
+    found: Option<Scalar<'a>>,
+}
+
+impl<'a> Getter<'a, '_> {
+    /// Handles the start of a value, which is a scalar or the start of a container.
+    fn value(&mut self, scalar: Option<Scalar<'a>>, is_array: bool) -> ControlFlow<()> {
+        let selected = if self.depth == 0 {
+            true
+        } else if self.depth == self.matched + 1 {
+            let matches = if self.in_array {
+                self.index += 1;
+                self.path[self.matched] == Segment::Index(self.index - 1)
+            } else {
+                self.key_matches
+            };
+            self.matched += usize::from(matches);
+            matches
+        } else {
+            false
+        };
+
+        if scalar.is_none() {
+            self.depth += 1;
+        }
+        if !selected {
+            return ControlFlow::Continue(());
+        }
+        if self.matched == self.path.len() {
+            self.found = scalar;
+            return ControlFlow::Break(());
+        }
+        if scalar.is_some() {
+            // The path continues into a scalar.
+            return ControlFlow::Break(());
+        }
+        self.in_array = is_array;
+        self.index = 0;
+        self.key_matches = false;
+        ControlFlow::Continue(())
+    }
+}
+
+impl<'a> Handler<'a> for Getter<'a, '_> {
+    fn event(&mut self, event: Event<'a>) -> ControlFlow<()> {
+        match event {
+            Event::ObjectStart => self.value(None, false),
+            Event::ArrayStart => self.value(None, true),
+            Event::Scalar(scalar) => self.value(Some(scalar), false),
+            Event::Key(key) => {
+                if self.depth == self.matched + 1 {
+                    self.key_matches = match self.path[self.matched] {
+                        Segment::Key(segment) => key.eq_str(segment),
+                        Segment::Index(_) => false,
+                    };
+                }
+                ControlFlow::Continue(())
+            }
+            Event::ObjectEnd | Event::ArrayEnd => {
+                self.depth -= 1;
+                if self.depth == self.matched {
+                    // The selected container has no value at the next segment.
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            }
+        }
+    }
+}
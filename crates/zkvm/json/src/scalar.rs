@@ -0,0 +1,203 @@
+/// A string, number, boolean or null value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scalar<'a> {
+    /// A string.
+    String(Str<'a>),
+    /// A number.
+    Number(Number<'a>),
+    /// A boolean.
+    Bool(bool),
+    /// The null value.
+    Null,
+}
+
+/// A string, borrowed from the input with its escape sequences.
+///
+/// The escape sequences are checked by the parser, but the string is only unescaped, and checked
+/// to be UTF-8, when it is read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Str<'a> {
+    raw: &'a [u8],
+    escaped: bool,
+}
+
+impl<'a> Str<'a> {
+    pub(crate) const fn new(raw: &'a [u8], escaped: bool) -> Self {
+        Self { raw, escaped }
+    }
+
+    /// Returns the bytes between the quotes of the string, with its escape sequences.
+    #[must_use]
+    pub const fn raw(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    /// Returns whether the string has escape sequences.
+    #[must_use]
+    pub const fn is_escaped(&self) -> bool {
+        self.escaped
+    }
+
+    /// Returns the string without copying it, if it has no escape sequences and is UTF-8.
+    #[must_use]
+    pub fn as_str(&self) -> Option<&'a str> {
+        if self.escaped {
+            return None;
+        }
+        core::str::from_utf8(self.raw).ok()
+    }
+
+    /// Returns the UTF-8 bytes of the unescaped string.
+    ///
+    /// Escaped surrogates which are not part of a pair are replaced by `U+FFFD`.
+    #[must_use]
+    pub fn unescaped(&self) -> Unescaped<'a> {
+        Unescaped { raw: self.raw, pending: [0; 4], pending_len: 0 }
+    }
+
+    /// Returns whether the unescaped string is equal to `other`.
+    #[must_use]
+    pub fn eq_str(&self, other: &str) -> bool {
+        if self.escaped {
+            self.unescaped().eq(other.bytes())
+        } else {
+            self.raw == other.as_bytes()
+        }
+    }
+}
+
+/// An iterator over the bytes of an unescaped [`Str`].
+#[derive(Debug, Clone)]
+pub struct Unescaped<'a> {
+    raw: &'a [u8],
+    /// The bytes of an escaped character which are yet to be returned, in reverse order.
+    pending: [u8; 4],
+    pending_len: usize,
+}
+
+impl Unescaped<'_> {
+    /// Decodes the four hex digits of a `\u` escape at the start of the remaining bytes.
+    fn hex4(&mut self) -> Option<u32> {
+        let (digits, rest) = self.raw.split_first_chunk::<4>()?;
+        let mut value = 0;
+        for digit in digits {
+            value = value * 16 + char::from(*digit).to_digit(16)?;
+        }
+        self.raw = rest;
+        Some(value)
+    }
+
+    /// Decodes a `\u` escape, with the low surrogate of a pair.
+    fn unicode(&mut self) -> char {
+        let Some(high) = self.hex4() else {
+            return char::REPLACEMENT_CHARACTER;
+        };
+        if (0xd800..0xdc00).contains(&high) && self.raw.starts_with(b"\\u") {
+            let mut low_escape = self.clone();
+            low_escape.raw = &self.raw[2..];
+            if let Some(low @ 0xdc00..0xe000) = low_escape.hex4() {
+                self.raw = low_escape.raw;
+                let code = 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00);
+                return char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER);
+            }
+        }
+        char::from_u32(high).unwrap_or(char::REPLACEMENT_CHARACTER)
+    }
+}
+
+impl Iterator for Unescaped<'_> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.pending_len > 0 {
+            self.pending_len -= 1;
+            return Some(self.pending[self.pending_len]);
+        }
+
+        let (&byte, rest) = self.raw.split_first()?;
+        self.raw = rest;
+        if byte != b'\\' {
+            return Some(byte);
+        }
+
+        let (&escape, rest) = self.raw.split_first()?;
+        self.raw = rest;
+        let character = match escape {
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => self.unicode(),
+            // `"`, `\` and `/` escape themselves.
+            _ => char::from(escape),
+        };
+
+        let mut utf8 = [0; 4];
+        let encoded = character.encode_utf8(&mut utf8).as_bytes();
+        for (i, &byte) in encoded[1..].iter().rev().enumerate() {
+            self.pending[i] = byte;
+        }
+        self.pending_len = encoded.len() - 1;
+        Some(encoded[0])
+    }
+}
+
+/// A number, borrowed from the input.
+///
+/// The number is only converted when it is read, so a number which is not read costs nothing
+/// beyond checking its syntax.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Number<'a> {
+    raw: &'a [u8],
+    integer: bool,
+}
+
+impl<'a> Number<'a> {
+    pub(crate) const fn new(raw: &'a [u8], integer: bool) -> Self {
+        Self { raw, integer }
+    }
+
+    /// Returns the text of the number.
+    #[must_use]
+    pub const fn raw(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    /// Returns whether the number has no fraction and no exponent.
+    #[must_use]
+    pub const fn is_integer(&self) -> bool {
+        self.integer
+    }
+
+    /// Returns the number as a `u64`, if it is an integer in range.
+    #[must_use]
+    pub fn as_u64(&self) -> Option<u64> {
+        if !self.integer {
+            return None;
+        }
+        self.raw.iter().try_fold(0u64, |value, &digit| {
+            value
+                .checked_mul(10)?
+                .checked_add(u64::from(digit.checked_sub(b'0').filter(|d| *d < 10)?))
+        })
+    }
+
+    /// Returns the number as an `i64`, if it is an integer in range.
+    #[must_use]
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.raw.split_first() {
+            Some((b'-', digits)) => {
+                let magnitude = Number::new(digits, self.integer).as_u64()?;
+                0i64.checked_sub_unsigned(magnitude)
+            }
+            _ => self.as_u64()?.try_into().ok(),
+        }
+    }
+
+    /// Returns the number as the nearest `f64`.
+    #[must_use]
+    pub fn as_f64(&self) -> Option<f64> {
+        core::str::from_utf8(self.raw).ok()?.parse().ok()
+    }
+}
@@ -0,0 +1,261 @@
+//! AES over the AES encryption round syscall.
+//!
+//! All rounds of a block encryption but the last are a single `AES_ENCRYPT_ROUND` syscall, instead
+//! of the many table lookups of a software implementation. The key schedule, which is computed
+//! once per key, and the last round, which skips `MixColumns`, are computed in software.
+//!
+//! On top of the block cipher, this module implements the counter mode and the GHASH authenticator
+//! of AES-GCM, so that guests can decrypt and authenticate TLS records.
+//!
+//! This module is used by the `aes` patch, and can only be called inside the zkVM. The syscall is
+//! only supported by the provers built with the `aes-precompile` feature of the SDK.
+
+use crate::syscall_aes_encrypt_round;
+
+/// The size of an AES block and of an AES-GCM tag, in bytes.
+pub const BLOCK_SIZE: usize = 16;
+
+/// The size of an AES-GCM nonce, in bytes.
+pub const NONCE_SIZE: usize = 12;
+
+/// The number of rounds of AES-256, the largest supported key size.
+const MAX_ROUNDS: usize = 14;
+
+/// The round constants of the key schedule.
+const RCON: [u8; 10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+/// The reduction constant of GHASH, for the polynomial x^128 + x^7 + x^2 + x + 1.
+const GHASH_R: u128 = 0xe1 << 120;
+
+/// The AES S-box, as defined in FIPS-197.
+const SBOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+/// An AES-128 or AES-256 block cipher, with its expanded key.
+#[derive(Clone)]
+pub struct Aes {
+    /// The round keys, one column of the state per word, as read by the syscall.
+    round_keys: [[u32; 4]; MAX_ROUNDS + 1],
+    /// The number of rounds, 10 for AES-128 and 14 for AES-256.
+    rounds: usize,
+}
+
+impl Aes {
+    /// Creates a new AES-128 cipher from a key.
+    pub fn new_128(key: &[u8; 16]) -> Self {
+        Self::expand_key(key)
+    }
+
+    /// Creates a new AES-256 cipher from a key.
+    pub fn new_256(key: &[u8; 32]) -> Self {
+        Self::expand_key(key)
+    }
+
+    /// Computes the key schedule of FIPS-197, section 5.2.
+    fn expand_key(key: &[u8]) -> Self {
+        let nk = key.len() / 4;
+        let rounds = nk + 6;
+
+        let mut w = [[0u8; 4]; 4 * (MAX_ROUNDS + 1)];
+        for (word, bytes) in w.iter_mut().zip(key.chunks_exact(4)) {
+            word.copy_from_slice(bytes);
+        }
+        for i in nk..4 * (rounds + 1) {
+            let mut temp = w[i - 1];
+            if i % nk == 0 {
+                temp.rotate_left(1);
+                temp = temp.map(|b| SBOX[b as usize]);
+                temp[0] ^= RCON[i / nk - 1];
+            } else if nk > 6 && i % nk == 4 {
+                temp = temp.map(|b| SBOX[b as usize]);
+            }
+            w[i] = core::array::from_fn(|j| w[i - nk][j] ^ temp[j]);
+        }
+
+        let round_keys = core::array::from_fn(|round| {
+            core::array::from_fn(|c| u32::from_le_bytes(w[4 * round + c]))
+        });
+        Self { round_keys, rounds }
+    }
+
+    /// Encrypts a block in place.
+    pub fn encrypt_block(&self, block: &mut [u8; BLOCK_SIZE]) {
+        let mut state: [u32; 4] = core::array::from_fn(|c| {
+            u32::from_le_bytes(block[4 * c..4 * (c + 1)].try_into().unwrap()) ^
+                self.round_keys[0][c]
+        });
+
+        for round_key in &self.round_keys[1..self.rounds] {
+            unsafe {
+                syscall_aes_encrypt_round(&mut state, round_key);
+            }
+        }
+
+        // The last round skips MixColumns. Row `r` of the state is rotated left by `r` columns.
+        let state = state.map(u32::to_le_bytes);
+        let round_key = self.round_keys[self.rounds].map(u32::to_le_bytes);
+        for c in 0..4 {
+            for r in 0..4 {
+                block[4 * c + r] = SBOX[state[(c + r) % 4][r] as usize] ^ round_key[c][r];
+            }
+        }
+    }
+
+    /// Xors the data with the counter mode keystream starting at the given counter block.
+    ///
+    /// The last four bytes of the counter block are incremented as a big endian integer, as in
+    /// AES-GCM and `ctr::Ctr32BE`.
+    pub fn apply_keystream_ctr32(&self, counter: &[u8; BLOCK_SIZE], data: &mut [u8]) {
+        let mut counter = *counter;
+        for chunk in data.chunks_mut(BLOCK_SIZE) {
+            let mut keystream = counter;
+            self.encrypt_block(&mut keystream);
+            for (byte, key) in chunk.iter_mut().zip(keystream) {
+                *byte ^= key;
+            }
+            inc32(&mut counter);
+        }
+    }
+
+    /// Encrypts the buffer in place with AES-GCM, and returns the tag.
+    pub fn gcm_encrypt(
+        &self,
+        nonce: &[u8; NONCE_SIZE],
+        aad: &[u8],
+        buffer: &mut [u8],
+    ) -> [u8; BLOCK_SIZE] {
+        let mut counter = initial_counter(nonce);
+        inc32(&mut counter);
+        self.apply_keystream_ctr32(&counter, buffer);
+        self.gcm_tag(nonce, aad, buffer)
+    }
+
+    /// Verifies the AES-GCM tag of the buffer and, only if it is valid, decrypts the buffer in
+    /// place.
+    ///
+    /// Returns whether the tag is valid.
+    #[must_use]
+    pub fn gcm_decrypt(
+        &self,
+        nonce: &[u8; NONCE_SIZE],
+        aad: &[u8],
+        buffer: &mut [u8],
+        tag: &[u8; BLOCK_SIZE],
+    ) -> bool {
+        if !self.gcm_verify_tag(nonce, aad, buffer, tag) {
+            return false;
+        }
+        let mut counter = initial_counter(nonce);
+        inc32(&mut counter);
+        self.apply_keystream_ctr32(&counter, buffer);
+        true
+    }
+
+    /// Returns whether the tag is the AES-GCM tag of the ciphertext and associated data.
+    #[must_use]
+    pub fn gcm_verify_tag(
+        &self,
+        nonce: &[u8; NONCE_SIZE],
+        aad: &[u8],
+        ciphertext: &[u8],
+        tag: &[u8; BLOCK_SIZE],
+    ) -> bool {
+        let expected = self.gcm_tag(nonce, aad, ciphertext);
+        expected.iter().zip(tag).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+    }
+
+    /// Computes the AES-GCM tag of the ciphertext and associated data.
+    pub fn gcm_tag(&self, nonce: &[u8; NONCE_SIZE], aad: &[u8], ciphertext: &[u8]) -> [u8; 16] {
+        let mut h = [0u8; BLOCK_SIZE];
+        self.encrypt_block(&mut h);
+
+        let mut lengths = [0u8; BLOCK_SIZE];
+        lengths[..8].copy_from_slice(&(aad.len() as u64 * 8).to_be_bytes());
+        lengths[8..].copy_from_slice(&(ciphertext.len() as u64 * 8).to_be_bytes());
+
+        let mut ghash = GHash::new(&h);
+        ghash.update_padded(aad);
+        ghash.update_padded(ciphertext);
+        ghash.update_padded(&lengths);
+
+        let mut tag = initial_counter(nonce);
+        self.encrypt_block(&mut tag);
+        for (byte, mask) in tag.iter_mut().zip(ghash.finalize()) {
+            *byte ^= mask;
+        }
+        tag
+    }
+}
+
+/// The GHASH universal hash of AES-GCM, as defined in NIST SP 800-38D.
+#[derive(Clone)]
+pub struct GHash {
+    /// The hash key.
+    h: u128,
+    /// The accumulator.
+    acc: u128,
+}
+
+impl GHash {
+    /// Creates a new GHASH instance from a hash key.
+    pub fn new(h: &[u8; BLOCK_SIZE]) -> Self {
+        Self { h: u128::from_be_bytes(*h), acc: 0 }
+    }
+
+    /// Absorbs the given data, zero padding the last block to a full block.
+    pub fn update_padded(&mut self, data: &[u8]) {
+        for chunk in data.chunks(BLOCK_SIZE) {
+            let mut block = [0u8; BLOCK_SIZE];
+            block[..chunk.len()].copy_from_slice(chunk);
+            self.acc = gf128_mul(self.acc ^ u128::from_be_bytes(block), self.h);
+        }
+    }
+
+    /// Returns the hash of the absorbed data.
+    pub fn finalize(self) -> [u8; BLOCK_SIZE] {
+        self.acc.to_be_bytes()
+    }
+}
+
+/// Multiplies two elements of GF(2^128), in the bit order of GHASH.
+fn gf128_mul(x: u128, y: u128) -> u128 {
+    let mut z = 0;
+    let mut v = y;
+    for i in (0..128).rev() {
+        if (x >> i) & 1 == 1 {
+            z ^= v;
+        }
+        v = if v & 1 == 1 { (v >> 1) ^ GHASH_R } else { v >> 1 };
+    }
+    z
+}
+
+/// Returns the initial counter block of AES-GCM for a 96-bit nonce.
+fn initial_counter(nonce: &[u8; NONCE_SIZE]) -> [u8; BLOCK_SIZE] {
+    let mut counter = [0u8; BLOCK_SIZE];
+    counter[..NONCE_SIZE].copy_from_slice(nonce);
+    counter[BLOCK_SIZE - 1] = 1;
+    counter
+}
+
+/// Increments the last four bytes of a counter block as a big endian integer.
+fn inc32(counter: &mut [u8; BLOCK_SIZE]) {
+    let value = u32::from_be_bytes(counter[12..].try_into().unwrap()).wrapping_add(1);
+    counter[12..].copy_from_slice(&value.to_be_bytes());
+}
@@ -0,0 +1,196 @@
+//! Division and modular reduction of 256-bit integers over the uint256 multiplication syscall.
+//!
+//! Software long division and Montgomery reduction cost thousands of 32-bit instructions per call,
+//! and dominate RSA and Paillier style workloads. Here the remainder is a single `UINT256_MUL`
+//! syscall, and the quotient and the Montgomery reduction are hinted by the prover and checked with
+//! a few more syscalls.
+//!
+//! All integers are little endian words. This module is used by the `crypto-bigint` patch, and can
+//! only be called inside the zkVM.
+
+use crate::{
+    io, syscall_uint256_mulmod, unconstrained,
+    utils::{bytes_to_words_le, words_to_bytes_le},
+};
+
+/// The number of words in a 256-bit integer.
+const N: usize = 8;
+
+/// The integer one.
+const ONE: [u32; N] = [1, 0, 0, 0, 0, 0, 0, 0];
+
+/// The modulus 2^256 - 1, used together with 2^256 to check products that may overflow 256 bits.
+const MAX: [u32; N] = [u32::MAX; N];
+
+/// Returns `x mod modulus`.
+///
+/// # Panics
+///
+/// Panics if `modulus` is zero.
+pub fn rem(x: &[u32; N], modulus: &[u32; N]) -> [u32; N] {
+    assert!(*modulus != [0; N], "attempt to calculate the remainder with a divisor of zero");
+    mulmod(x, &ONE, modulus)
+}
+
+/// Returns the quotient and the remainder of `x` divided by `divisor`.
+///
+/// # Panics
+///
+/// Panics if `divisor` is zero.
+pub fn div_rem(x: &[u32; N], divisor: &[u32; N]) -> ([u32; N], [u32; N]) {
+    let remainder = rem(x, divisor);
+
+    unconstrained! {
+        io::hint_slice(&words_to_bytes_le(&long_division(x, divisor)));
+    }
+    let quotient = read_hint();
+
+    // `x - remainder` is a multiple of the divisor, and `quotient * divisor` must equal it as an
+    // integer. Checking the product modulo both 2^256 and 2^256 - 1 checks it modulo their product,
+    // which is larger than any product of two 256-bit integers.
+    let (diff, _) = sub(x, &remainder);
+    assert_eq!(mulmod(&quotient, divisor, &[0; N]), diff, "invalid quotient hint");
+    let diff = if diff == MAX { [0; N] } else { diff };
+    assert_eq!(mulmod(&quotient, divisor, &MAX), diff, "invalid quotient hint");
+
+    (quotient, remainder)
+}
+
+/// Returns the Montgomery reduction `(lo + hi * 2^256) * 2^-256 mod modulus`.
+///
+/// # Panics
+///
+/// Panics if `modulus` is even, including zero.
+pub fn montgomery_reduce(lo: &[u32; N], hi: &[u32; N], modulus: &[u32; N]) -> [u32; N] {
+    // 2^256 is only invertible modulo an odd modulus. Otherwise, the check below holds for several
+    // values, and the hint would not be unique.
+    assert!(modulus[0] & 1 == 1, "the modulus of a Montgomery reduction must be odd");
+
+    // 2^256 mod modulus, computed as (2^256 - modulus) mod modulus.
+    let (neg_modulus, _) = sub(&[0; N], modulus);
+    let r = rem(&neg_modulus, modulus);
+
+    unconstrained! {
+        io::hint_slice(&words_to_bytes_le(&bitwise_redc(lo, &rem(hi, modulus), modulus)));
+    }
+    let reduced = read_hint();
+
+    // Check that `reduced * 2^256 = lo + hi * 2^256` modulo the modulus.
+    assert!(lt(&reduced, modulus), "invalid Montgomery reduction hint");
+    let expected = addmod(&mulmod(hi, &r, modulus), &rem(lo, modulus), modulus);
+    assert_eq!(mulmod(&reduced, &r, modulus), expected, "invalid Montgomery reduction hint");
+
+    reduced
+}
+
+/// Reads a 256-bit integer hinted in unconstrained mode.
+fn read_hint() -> [u32; N] {
+    bytes_to_words_le(&io::read_vec()).try_into().expect("hint should be 32 bytes")
+}
+
+/// Returns `x * y mod modulus`, where a zero modulus stands for 2^256.
+fn mulmod(x: &[u32; N], y: &[u32; N], modulus: &[u32; N]) -> [u32; N] {
+    let mut result = *x;
+    let mut y_modulus = [0u32; 2 * N];
+    y_modulus[..N].copy_from_slice(y);
+    y_modulus[N..].copy_from_slice(modulus);
+    unsafe {
+        syscall_uint256_mulmod(&mut result, y_modulus.as_ptr() as *const [u32; N]);
+    }
+    result
+}
+
+/// Returns `x + y mod modulus`, for `x` and `y` below the modulus.
+fn addmod(x: &[u32; N], y: &[u32; N], modulus: &[u32; N]) -> [u32; N] {
+    let (sum, carry) = add(x, y);
+    if carry || !lt(&sum, modulus) {
+        sub(&sum, modulus).0
+    } else {
+        sum
+    }
+}
+
+/// Computes `x / divisor` by binary long division, outside of the constrained execution.
+fn long_division(x: &[u32; N], divisor: &[u32; N]) -> [u32; N] {
+    let mut quotient = [0u32; N];
+    let mut remainder = [0u32; N];
+    for i in (0..32 * N).rev() {
+        let overflow = shl1(&mut remainder, (x[i / 32] >> (i % 32)) & 1);
+        if overflow || !lt(&remainder, divisor) {
+            remainder = sub(&remainder, divisor).0;
+            quotient[i / 32] |= 1 << (i % 32);
+        }
+    }
+    quotient
+}
+
+/// Computes `(lo + hi * 2^256) * 2^-256 mod modulus` one bit at a time, outside of the constrained
+/// execution. Requires `hi` to be below the modulus, so that the result is below twice the modulus.
+fn bitwise_redc(lo: &[u32; N], hi: &[u32; N], modulus: &[u32; N]) -> [u32; N] {
+    // The accumulator needs one word more than the 512-bit input for the added multiples of the
+    // modulus.
+    let mut acc = [0u32; 2 * N + 1];
+    acc[..N].copy_from_slice(lo);
+    acc[N..2 * N].copy_from_slice(hi);
+    for _ in 0..32 * N {
+        if acc[0] & 1 == 1 {
+            let mut carry = 0u64;
+            for (i, word) in acc.iter_mut().enumerate() {
+                let sum = *word as u64 + modulus.get(i).copied().unwrap_or(0) as u64 + carry;
+                *word = sum as u32;
+                carry = sum >> 32;
+            }
+        }
+        for i in 0..2 * N {
+            acc[i] = (acc[i] >> 1) | (acc[i + 1] << 31);
+        }
+        acc[2 * N] >>= 1;
+    }
+
+    let mut reduced: [u32; N] = acc[..N].try_into().unwrap();
+    if acc[N] != 0 || !lt(&reduced, modulus) {
+        reduced = sub(&reduced, modulus).0;
+    }
+    reduced
+}
+
+/// Returns whether `a < b`.
+fn lt(a: &[u32; N], b: &[u32; N]) -> bool {
+    a.iter().rev().cmp(b.iter().rev()).is_lt()
+}
+
+/// Returns `a + b mod 2^256` and the carry.
+fn add(a: &[u32; N], b: &[u32; N]) -> ([u32; N], bool) {
+    let mut res = [0u32; N];
+    let mut carry = false;
+    for ((res, a), b) in res.iter_mut().zip(a).zip(b) {
+        let (sum, c1) = a.overflowing_add(*b);
+        let (sum, c2) = sum.overflowing_add(u32::from(carry));
+        *res = sum;
+        carry = c1 || c2;
+    }
+    (res, carry)
+}
+
+/// Returns `a - b mod 2^256` and the borrow.
+fn sub(a: &[u32; N], b: &[u32; N]) -> ([u32; N], bool) {
+    let mut res = [0u32; N];
+    let mut borrow = false;
+    for ((res, a), b) in res.iter_mut().zip(a).zip(b) {
+        let (diff, b1) = a.overflowing_sub(*b);
+        let (diff, b2) = diff.overflowing_sub(u32::from(borrow));
+        *res = diff;
+        borrow = b1 || b2;
+    }
+    (res, borrow)
+}
+
+/// Shifts `a` left by one bit, shifting in `bit`, and returns whether a bit was shifted out.
+fn shl1(a: &mut [u32; N], bit: u32) -> bool {
+    let overflow = a[N - 1] >> 31 == 1;
+    for i in (1..N).rev() {
+        a[i] = (a[i] << 1) | (a[i - 1] >> 31);
+    }
+    a[0] = (a[0] << 1) | bit;
+    overflow
+}
@@ -1,5 +1,3 @@
-use std::io::ErrorKind;
-
 use crate::{
     syscall_bls12381_add, syscall_bls12381_decompress, syscall_bls12381_double,
     utils::{AffinePoint, WeierstrassAffinePoint, WeierstrassPoint},
@@ -84,7 +82,8 @@ impl AffinePoint<N> for Bls12381Point {
 }
 
 /// Decompresses a compressed public key using bls12381_decompress precompile.
-pub fn decompress_pubkey(compressed_key: &[u8; 48]) -> Result<[u8; 96], ErrorKind> {
+#[cfg(feature = "std")]
+pub fn decompress_pubkey(compressed_key: &[u8; 48]) -> Result<[u8; 96], std::io::ErrorKind> {
     let mut decompressed_key = [0u8; 96];
     decompressed_key[..48].copy_from_slice(compressed_key);
 
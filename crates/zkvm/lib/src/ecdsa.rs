@@ -16,7 +16,7 @@ use crate::utils::AffinePoint as MONEROCHANAffinePointTrait;
 use elliptic_curve::{
     ff, generic_array::typenum::consts::U32, subtle::CtOption, CurveArithmetic, FieldBytes,
 };
-use std::{fmt::Debug, ops::Neg};
+use core::{fmt::Debug, ops::Neg};
 
 /// The affine point type for MONEROCHAN.
 pub mod affine;
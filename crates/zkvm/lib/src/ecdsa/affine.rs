@@ -19,7 +19,7 @@ use elliptic_curve::{
     zeroize::DefaultIsZeroes,
     FieldBytes, PrimeField,
 };
-use std::ops::Neg;
+use core::ops::Neg;
 
 #[derive(Clone, Copy, Debug)]
 pub struct AffinePoint<C: ECDSACurve> {
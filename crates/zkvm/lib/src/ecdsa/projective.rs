@@ -24,12 +24,12 @@ use elliptic_curve::{
     zeroize::DefaultIsZeroes,
 };
 
-use std::{
+use core::{
     iter::Sum,
     ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign},
 };
 
-use std::borrow::Borrow;
+use core::borrow::Borrow;
 
 /// The MONEROCHAN accelerated projective point.
 #[derive(Clone, Copy, Debug)]
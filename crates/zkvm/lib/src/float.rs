@@ -0,0 +1,254 @@
+//! F64 arithmetic over the f64 syscalls.
+//!
+//! The `F64_ADD`, `F64_MUL` and `F64_DIV` syscalls compute the correctly rounded IEEE 754 result,
+//! rounded to nearest with ties to even, when both operands and the result are normal. The result
+//! is then deterministic, and identical to the one of any IEEE 754 compliant host. Otherwise, the
+//! syscalls write the all-zero sentinel, which is never the result of an accelerated operation,
+//! and the operation is computed by the integer soft-float fallback of this module, which follows
+//! the same rounding and the NaN, infinity and signed zero rules of compiler-rt.
+//!
+//! This module is used by the `softfloat` feature of the runtime, which routes the f64 soft-float
+//! intrinsics to it, and can only be called inside the zkVM. The syscalls are only supported by
+//! the provers built with the `f64-precompile` feature of the SDK.
+
+use crate::{syscall_f64_add, syscall_f64_div, syscall_f64_mul};
+
+/// The sign bit of an f64.
+const SIGN: u64 = 1 << 63;
+
+/// The biased exponent bits of an f64.
+const EXPONENT: u64 = 0x7ff << 52;
+
+/// The fraction bits of an f64.
+const FRACTION: u64 = (1 << 52) - 1;
+
+/// The implicit leading bit of the significand of a normal f64.
+const IMPLICIT_BIT: u64 = 1 << 52;
+
+/// The quiet bit of a NaN.
+const QUIET_BIT: u64 = 1 << 51;
+
+/// The canonical quiet NaN.
+const QUIET_NAN: u64 = EXPONENT | QUIET_BIT;
+
+/// The exponent of the least significant bit of a significand is its biased exponent minus this.
+const SIGNIFICAND_BIAS: i32 = 1075;
+
+/// The type of the f64 syscalls.
+type F64Syscall = unsafe extern "C" fn(*mut [u32; 2], *const [u32; 2]);
+
+/// Adds two f64s.
+pub fn add(x: f64, y: f64) -> f64 {
+    let (x, y) = (x.to_bits(), y.to_bits());
+    f64::from_bits(accelerated(syscall_f64_add, x, y).unwrap_or_else(|| soft_add(x, y)))
+}
+
+/// Subtracts two f64s.
+pub fn sub(x: f64, y: f64) -> f64 {
+    add(x, f64::from_bits(y.to_bits() ^ SIGN))
+}
+
+/// Multiplies two f64s.
+pub fn mul(x: f64, y: f64) -> f64 {
+    let (x, y) = (x.to_bits(), y.to_bits());
+    f64::from_bits(accelerated(syscall_f64_mul, x, y).unwrap_or_else(|| soft_mul(x, y)))
+}
+
+/// Divides two f64s.
+pub fn div(x: f64, y: f64) -> f64 {
+    let (x, y) = (x.to_bits(), y.to_bits());
+    f64::from_bits(accelerated(syscall_f64_div, x, y).unwrap_or_else(|| soft_div(x, y)))
+}
+
+/// Runs an f64 syscall, and returns its result unless it is the sentinel.
+fn accelerated(syscall: F64Syscall, x: u64, y: u64) -> Option<u64> {
+    let mut result = [x as u32, (x >> 32) as u32];
+    let y = [y as u32, (y >> 32) as u32];
+    unsafe {
+        syscall(&mut result, &y);
+    }
+    let result = u64::from(result[0]) | (u64::from(result[1]) << 32);
+    (result != 0).then_some(result)
+}
+
+/// Returns whether an f64 is a NaN.
+fn is_nan(x: u64) -> bool {
+    x & !SIGN > EXPONENT
+}
+
+/// Returns whether an f64 is an infinity.
+fn is_infinite(x: u64) -> bool {
+    x & !SIGN == EXPONENT
+}
+
+/// Returns whether an f64 is a zero.
+fn is_zero(x: u64) -> bool {
+    x & !SIGN == 0
+}
+
+/// Returns the quieted NaN operand, the first one if both are NaNs.
+fn propagate_nan(x: u64, y: u64) -> u64 {
+    if is_nan(x) {
+        x | QUIET_BIT
+    } else {
+        y | QUIET_BIT
+    }
+}
+
+/// Returns the biased exponent and the significand of a finite f64, such that its magnitude is
+/// `significand * 2^(exponent - SIGNIFICAND_BIAS)`.
+fn unpack(x: u64) -> (i32, u64) {
+    let exponent = ((x & EXPONENT) >> 52) as i32;
+    if exponent == 0 {
+        // A subnormal has the exponent of the smallest normal, without the implicit bit.
+        (1, x & FRACTION)
+    } else {
+        (exponent, (x & FRACTION) | IMPLICIT_BIT)
+    }
+}
+
+/// Returns the biased exponent and the significand of a finite nonzero f64, with the significand
+/// shifted so that its leading bit is the implicit bit.
+fn unpack_normalized(x: u64) -> (i32, u64) {
+    let (exponent, significand) = unpack(x);
+    let shift = significand.leading_zeros() as i32 - 11;
+    (exponent - shift, significand << shift)
+}
+
+/// Rounds `significand * 2^(exponent - SIGNIFICAND_BIAS)` to nearest with ties to even, and packs
+/// it with a sign.
+///
+/// The significand must be less than `2^127`. It is either exact, or has at least two bits below
+/// the rounding position, with its least significant bit set to mark the inexact low bits.
+fn round_pack(sign: u64, exponent: i32, significand: u128) -> u64 {
+    if significand == 0 {
+        return sign;
+    }
+
+    // Shift the significand to 53 bits, or less for a subnormal result.
+    let bits = 128 - significand.leading_zeros() as i32;
+    let mut shift = bits - 53;
+    let mut exponent = exponent + shift;
+    if exponent < 1 {
+        shift += 1 - exponent;
+        exponent = 1;
+    }
+    if exponent > 2046 {
+        return sign | EXPONENT;
+    }
+
+    let rounded = if shift <= 0 {
+        (significand << -shift) as u64
+    } else if shift > bits {
+        // The magnitude is below half of the smallest subnormal.
+        0
+    } else {
+        let truncated = (significand >> shift) as u64;
+        let remainder = significand & ((1 << shift) - 1);
+        let half = 1 << (shift - 1);
+        if remainder > half || (remainder == half && truncated & 1 == 1) {
+            truncated + 1
+        } else {
+            truncated
+        }
+    };
+
+    // The implicit bit carries into the exponent, which also covers a rounding carry into the
+    // next binade, a subnormal rounded to the smallest normal, and an overflow to infinity.
+    let magnitude = (((exponent - 1) as u64) << 52) + rounded;
+    sign | magnitude.min(EXPONENT)
+}
+
+/// Adds two f64s in software.
+pub fn soft_add(x: u64, y: u64) -> u64 {
+    if is_nan(x) || is_nan(y) {
+        return propagate_nan(x, y);
+    }
+    if is_infinite(x) {
+        return if is_infinite(y) && (x ^ y) & SIGN != 0 { QUIET_NAN } else { x };
+    }
+    if is_infinite(y) {
+        return y;
+    }
+
+    // Sort the operands by exponent.
+    let (large, small) = if x & EXPONENT >= y & EXPONENT { (x, y) } else { (y, x) };
+    let (large_exponent, large_significand) = unpack(large);
+    let (small_exponent, small_significand) = unpack(small);
+    let gap = (large_exponent - small_exponent) as u32;
+
+    // Align the significands, keeping the bits of the smaller one which are too far below the
+    // larger one as a sticky bit.
+    let (exponent, large_term, small_term) = if gap <= 64 {
+        (small_exponent, u128::from(large_significand) << gap, u128::from(small_significand))
+    } else {
+        let shift = gap - 64;
+        let shifted = small_significand.checked_shr(shift).unwrap_or(0);
+        let lost_bits = 1u64.checked_shl(shift).map_or(u64::MAX, |bit| bit - 1);
+        let sticky = u128::from(small_significand & lost_bits != 0);
+        (
+            large_exponent - 65,
+            u128::from(large_significand) << 65,
+            (u128::from(shifted) << 1) | sticky,
+        )
+    };
+
+    if (x ^ y) & SIGN == 0 {
+        round_pack(x & SIGN, exponent, large_term + small_term)
+    } else if large_term > small_term {
+        round_pack(large & SIGN, exponent, large_term - small_term)
+    } else if large_term < small_term {
+        round_pack(small & SIGN, exponent, small_term - large_term)
+    } else {
+        // An exact zero sum is positive when rounding to nearest.
+        0
+    }
+}
+
+/// Multiplies two f64s in software.
+pub fn soft_mul(x: u64, y: u64) -> u64 {
+    let sign = (x ^ y) & SIGN;
+    if is_nan(x) || is_nan(y) {
+        return propagate_nan(x, y);
+    }
+    if is_infinite(x) || is_infinite(y) {
+        return if is_zero(x) || is_zero(y) { QUIET_NAN } else { sign | EXPONENT };
+    }
+
+    let (x_exponent, x_significand) = unpack(x);
+    let (y_exponent, y_significand) = unpack(y);
+    round_pack(
+        sign,
+        x_exponent + y_exponent - SIGNIFICAND_BIAS,
+        u128::from(x_significand) * u128::from(y_significand),
+    )
+}
+
+/// Divides two f64s in software.
+pub fn soft_div(x: u64, y: u64) -> u64 {
+    let sign = (x ^ y) & SIGN;
+    if is_nan(x) || is_nan(y) {
+        return propagate_nan(x, y);
+    }
+    if is_infinite(x) {
+        return if is_infinite(y) { QUIET_NAN } else { sign | EXPONENT };
+    }
+    if is_infinite(y) {
+        return sign;
+    }
+    if is_zero(y) {
+        return if is_zero(x) { QUIET_NAN } else { sign | EXPONENT };
+    }
+    if is_zero(x) {
+        return sign;
+    }
+
+    // With normalized significands, the quotient has 64 or 65 bits, and the remainder is kept as
+    // a sticky bit.
+    let (x_exponent, x_significand) = unpack_normalized(x);
+    let (y_exponent, y_significand) = unpack_normalized(y);
+    let dividend = u128::from(x_significand) << 64;
+    let quotient = dividend / u128::from(y_significand);
+    let sticky = u128::from(quotient * u128::from(y_significand) != dividend);
+    round_pack(sign, x_exponent - y_exponent + SIGNIFICAND_BIAS - 64, quotient | sticky)
+}
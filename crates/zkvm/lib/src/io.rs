@@ -1,38 +1,52 @@
+//! Reading the inputs of the program and writing its outputs.
+//!
+//! The functions (de)serializing values, and the [`MerkleTree`] of committed values, require the
+//! `std` feature. The others can be used by `no_std` programs.
+
 #![allow(unused_unsafe)]
-use crate::{read_vec_raw, syscall_write, ReadVecResult};
-use serde::{de::DeserializeOwned, Serialize};
-use std::io::{Result, Write};
+
+use crate::{read_vec_raw, syscall_record_outcome, syscall_reject, syscall_write, ReadVecResult};
+use alloc::{string::String, vec::Vec};
+use core::panic::Location;
 
 pub use monerochan_primitives::consts::fd::*;
+use monerochan_primitives::consts::NAMED_INPUT_TAG;
+#[cfg(feature = "std")]
+pub use monerochan_primitives::merkle::MerkleTree;
+#[cfg(feature = "std")]
+use serde::{de::DeserializeOwned, Serialize};
 
 /// A writer that writes to a file descriptor inside the zkVM.
 struct SyscallWriter {
     fd: u32,
 }
 
-impl Write for SyscallWriter {
-    fn write(&mut self, buf: &[u8]) -> Result<usize> {
-        let nbytes = buf.len();
-        let write_buf = buf.as_ptr();
+impl SyscallWriter {
+    /// Writes all of `buf` to the file descriptor.
+    fn write_slice(&mut self, buf: &[u8]) {
         unsafe {
-            syscall_write(self.fd, write_buf, nbytes);
+            syscall_write(self.fd, buf.as_ptr(), buf.len());
         }
-        Ok(nbytes)
     }
+}
 
-    fn flush(&mut self) -> Result<()> {
+#[cfg(feature = "std")]
+impl std::io::Write for SyscallWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.write_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
         Ok(())
     }
 }
 
 /// Read a buffer from the input stream. The buffer is read into uninitialized memory.
 ///
-/// When the `bump` feature is enabled, the buffer is read into a new buffer allocated by the
-/// program.
+/// With the `bump` allocator, the buffer is read into a new buffer allocated by the program.
 ///
-/// When the `embedded` feature is enabled, the buffer is read into the reserved input region.
-///
-/// When there is no allocator selected, the program will fail to compile.
+/// With the `embedded` allocator, the buffer is read into the reserved input region.
 ///
 /// ### Examples
 /// ```ignore
@@ -46,7 +60,7 @@ pub fn read_vec() -> Vec<u8> {
         panic!(
             "Tried to read from the input stream, but it was empty @ {} \n
             Was the correct data written into MONEROCHANStdin?",
-            std::panic::Location::caller()
+            Location::caller()
         )
     }
 
@@ -67,6 +81,7 @@ pub fn read_vec() -> Vec<u8> {
 ///
 /// let data: MyStruct = monerochan_runtime::io::read();
 /// ```
+#[cfg(feature = "std")]
 #[track_caller]
 pub fn read<T: DeserializeOwned>() -> T {
     let ReadVecResult { ptr, len, capacity } = unsafe { read_vec_raw() };
@@ -75,7 +90,7 @@ pub fn read<T: DeserializeOwned>() -> T {
         panic!(
             "Tried to read from the input stream, but it was empty @ {} \n
             Was the correct data written into MONEROCHANStdin?",
-            std::panic::Location::caller()
+            Location::caller()
         )
     }
 
@@ -87,6 +102,58 @@ pub fn read<T: DeserializeOwned>() -> T {
     bincode::deserialize(&vec).expect("deserialization failed")
 }
 
+/// Read the tag of the named input `name` from the input stream, panicking if the next input is
+/// another input.
+#[track_caller]
+fn read_named_tag(name: &str) {
+    let tag = read_vec();
+    match tag.strip_prefix(NAMED_INPUT_TAG) {
+        Some(found) if found == name.as_bytes() => {}
+        Some(found) => panic!(
+            "Expected input `{name}`, but the next input is `{}` @ {} \n
+            Was the data written into MONEROCHANStdin in the order it is read?",
+            String::from_utf8_lossy(found),
+            Location::caller()
+        ),
+        None => panic!(
+            "Expected input `{name}`, but the next input is unnamed @ {} \n
+            Was the data written into MONEROCHANStdin with `MONEROCHANStdin::insert`?",
+            Location::caller()
+        ),
+    }
+}
+
+/// Read the named buffer `name` from the input stream, written with
+/// `MONEROCHANStdin::insert_slice`.
+///
+/// ### Examples
+/// ```ignore
+/// let payload: Vec<u8> = monerochan_runtime::io::read_named_vec("payload");
+/// ```
+#[track_caller]
+pub fn read_named_vec(name: &str) -> Vec<u8> {
+    read_named_tag(name);
+    read_vec()
+}
+
+/// Read the named deserializable object `name` from the input stream, written with
+/// `MONEROCHANStdin::insert`.
+///
+/// Unlike [`read`], the program panics if the host wrote the inputs in another order than they
+/// are read, instead of deserializing one input as another.
+///
+/// ### Examples
+/// ```ignore
+/// let header: BlockHeader = monerochan_runtime::io::read_named("block_header");
+/// let height: u64 = monerochan_runtime::io::read_named("height");
+/// ```
+#[cfg(feature = "std")]
+#[track_caller]
+pub fn read_named<T: DeserializeOwned>(name: &str) -> T {
+    read_named_tag(name);
+    read()
+}
+
 /// Commit a serializable object to the public values stream.
 ///
 /// ### Examples
@@ -105,6 +172,7 @@ pub fn read<T: DeserializeOwned>() -> T {
 /// };
 /// monerochan_runtime::io::commit(&data);
 /// ```
+#[cfg(feature = "std")]
 pub fn commit<T: Serialize>(value: &T) {
     let writer = SyscallWriter { fd: FD_PUBLIC_VALUES };
     bincode::serialize_into(writer, value).expect("serialization failed");
@@ -119,7 +187,83 @@ pub fn commit<T: Serialize>(value: &T) {
 /// ```
 pub fn commit_slice(buf: &[u8]) {
     let mut my_writer = SyscallWriter { fd: FD_PUBLIC_VALUES };
-    my_writer.write_all(buf).unwrap();
+    my_writer.write_slice(buf);
+}
+
+/// Commit bytes to the public values stream, prefixed with their length as a little-endian u32.
+///
+/// Unlike [`commit_slice`], the committed bytes can be read back without knowing their length with
+/// `MONEROCHANPublicValues::read_framed`.
+///
+/// ### Examples
+/// ```ignore
+/// monerochan_runtime::io::commit_framed(b"first");
+/// monerochan_runtime::io::commit_framed(&[1, 2, 3]);
+/// ```
+pub fn commit_framed(buf: &[u8]) {
+    let len = u32::try_from(buf.len()).expect("framed public value is too long");
+    let mut my_writer = SyscallWriter { fd: FD_PUBLIC_VALUES };
+    my_writer.write_slice(&len.to_le_bytes());
+    my_writer.write_slice(buf);
+}
+
+/// Commit the root of a [`MerkleTree`] of values to the public values stream.
+///
+/// The 32 byte root is committed in place of the values themselves, and the host can prove any
+/// single value against it with `MerkleTree::prove`.
+///
+/// ### Examples
+/// ```ignore
+/// let mut tree = monerochan_runtime::io::MerkleTree::new();
+/// tree.push(&balance);
+/// tree.push(&nonce);
+/// monerochan_runtime::io::commit_merkle_root(&tree);
+/// ```
+#[cfg(feature = "std")]
+pub fn commit_merkle_root(tree: &MerkleTree) {
+    commit_slice(&tree.root());
+}
+
+/// Record the outcome of the program in a header starting its public values, so that it can
+/// reject its input with [`reject`].
+///
+/// The values committed after it are kept in memory until the program halts, and then committed
+/// after the header, which records whether the program succeeded or rejected its input. The host
+/// reads the outcome with `MONEROCHANPublicValues::outcome`, and the values committed by the
+/// program with `MONEROCHANPublicValues::committed`.
+///
+/// # Panics
+/// Panics if the program already committed values, since the header must start its public values.
+///
+/// ### Examples
+/// ```ignore
+/// monerochan_runtime::io::record_outcome();
+/// ```
+pub fn record_outcome() {
+    unsafe { syscall_record_outcome() }
+}
+
+/// Reject the input of the program with an error code, and halt it successfully.
+///
+/// Unlike a panic or a failed `ensure!`, which fail the execution so that nothing can be proven,
+/// a rejection is a provable outcome: the error code is recorded in the header of the public
+/// values, before the values committed so far, which are kept.
+///
+/// # Panics
+/// Panics if the program has not called [`record_outcome`], as the rejection could not be told
+/// from the values it committed otherwise.
+///
+/// ### Examples
+/// ```ignore
+/// const INVALID_SIGNATURE: u32 = 1;
+///
+/// monerochan_runtime::io::record_outcome();
+/// if !signature.verify(&message) {
+///     monerochan_runtime::io::reject(INVALID_SIGNATURE);
+/// }
+/// ```
+pub fn reject(code: u32) -> ! {
+    unsafe { syscall_reject(code) }
 }
 
 /// Hint a serializable object to the hint stream.
@@ -140,6 +284,7 @@ pub fn commit_slice(buf: &[u8]) {
 /// };
 /// monerochan_runtime::io::hint(&data);
 /// ```
+#[cfg(feature = "std")]
 pub fn hint<T: Serialize>(value: &T) {
     let writer = SyscallWriter { fd: FD_HINT };
     bincode::serialize_into(writer, value).expect("serialization failed");
@@ -154,7 +299,31 @@ pub fn hint<T: Serialize>(value: &T) {
 /// ```
 pub fn hint_slice(buf: &[u8]) {
     let mut my_reader = SyscallWriter { fd: FD_HINT };
-    my_reader.write_all(buf).unwrap();
+    my_reader.write_slice(buf);
+}
+
+/// Read the number of cycles executed so far.
+///
+/// The count is provided by the prover and is not constrained by the proof, so it must only be
+/// used for budgeting, e.g. to stop processing untrusted input before the cycle limit is hit, and
+/// never for the correctness of the program's output.
+///
+/// ### Examples
+/// ```ignore
+/// let start = monerochan_runtime::io::cycle_count();
+/// for item in items {
+///     if monerochan_runtime::io::cycle_count() - start > BUDGET {
+///         break;
+///     }
+///     process(item);
+/// }
+/// ```
+pub fn cycle_count() -> u64 {
+    unsafe {
+        syscall_write(FD_CYCLE_COUNT, core::ptr::null(), 0);
+    }
+    let bytes = read_vec();
+    u64::from_le_bytes(bytes.try_into().expect("the cycle count should be 8 bytes"))
 }
 
 /// Write the data `buf` to the file descriptor `fd`.
@@ -165,5 +334,5 @@ pub fn hint_slice(buf: &[u8]) {
 /// monerochan_runtime::io::write(3, &data);
 /// ```
 pub fn write(fd: u32, buf: &[u8]) {
-    SyscallWriter { fd }.write_all(buf).unwrap();
+    SyscallWriter { fd }.write_slice(buf);
 }
@@ -2,7 +2,17 @@
 //!
 //! Documentation for these syscalls can be found in the zkVM entrypoint
 //! `monerochan_runtime::syscalls` module.
+//!
+//! The crate supports `no_std` programs, with the `alloc` crate, when the `std` feature is
+//! disabled. The io functions (de)serializing values with `bincode` are then unavailable, and the
+//! program reads and commits bytes instead.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
+pub mod aes;
+pub mod bigint;
 pub mod bls12381;
 pub mod bn254;
 
@@ -10,7 +20,9 @@ pub mod bn254;
 pub mod ecdsa;
 
 pub mod ed25519;
+pub mod float;
 pub mod io;
+pub mod poly1305;
 pub mod secp256k1;
 pub mod secp256r1;
 pub mod unconstrained;
@@ -26,6 +38,12 @@ extern "C" {
     /// Writes the bytes in the given buffer to the given file descriptor.
     pub fn syscall_write(fd: u32, write_buf: *const u8, nbytes: usize);
 
+    /// Records the outcome of the program in a header starting its public values.
+    pub fn syscall_record_outcome();
+
+    /// Rejects the input of the program with an error code, and halts it successfully.
+    pub fn syscall_reject(code: u32) -> !;
+
     /// Reads the bytes from the given file descriptor into the given buffer.
     pub fn syscall_read(fd: u32, read_buf: *mut u8, nbytes: usize);
 
@@ -84,6 +102,19 @@ extern "C" {
         lo: *mut [u32; 64],
         hi: *mut [u32; 8],
     );
+
+    /// Executes an AES encryption round on the given state with the given round key.
+    pub fn syscall_aes_encrypt_round(state: *mut [u32; 4], round_key: *const [u32; 4]);
+
+    /// Executes an f64 addition on the given inputs.
+    pub fn syscall_f64_add(x: *mut [u32; 2], y: *const [u32; 2]);
+
+    /// Executes an f64 multiplication on the given inputs.
+    pub fn syscall_f64_mul(x: *mut [u32; 2], y: *const [u32; 2]);
+
+    /// Executes an f64 division on the given inputs.
+    pub fn syscall_f64_div(x: *mut [u32; 2], y: *const [u32; 2]);
+
     /// Enters unconstrained mode.
     pub fn syscall_enter_unconstrained() -> bool;
 
@@ -0,0 +1,103 @@
+//! Poly1305 over the uint256 multiplication syscall.
+//!
+//! Each 16 byte block of a Poly1305 MAC costs one multiplication modulo 2^130 - 5, which is a
+//! single `UINT256_MUL` syscall instead of the many 32-bit multiplications of a software
+//! implementation. This is the hot path of ChaCha20-Poly1305 decryption in the zkVM, the ChaCha20
+//! keystream itself only uses additions, rotations and xors, which are cheap RISC-V instructions.
+//!
+//! This module is used by the `poly1305` patch, and can only be called inside the zkVM.
+
+use crate::syscall_uint256_mulmod;
+
+/// The size of a Poly1305 block and tag, in bytes.
+pub const BLOCK_SIZE: usize = 16;
+
+/// The Poly1305 prime 2^130 - 5, in little endian words.
+const MODULUS: [u32; 8] = [0xffff_fffb, 0xffff_ffff, 0xffff_ffff, 0xffff_ffff, 3, 0, 0, 0];
+
+/// A Poly1305 one-time authenticator.
+#[derive(Clone)]
+pub struct Poly1305 {
+    /// The clamped `r` half of the key, followed by the modulus, as read by the syscall.
+    r: [u32; 16],
+    /// The `s` half of the key.
+    s: [u32; 4],
+    /// The accumulator, reduced modulo 2^130 - 5.
+    acc: [u32; 8],
+}
+
+impl Poly1305 {
+    /// Creates a new authenticator from a one-time key.
+    pub fn new(key: &[u8; 32]) -> Self {
+        let mut r = [0u32; 16];
+        r[..4].copy_from_slice(&words(&key[..16]));
+        r[0] &= 0x0fff_ffff;
+        r[1] &= 0x0fff_fffc;
+        r[2] &= 0x0fff_fffc;
+        r[3] &= 0x0fff_fffc;
+        r[8..].copy_from_slice(&MODULUS);
+
+        Self { r, s: words(&key[16..]), acc: [0; 8] }
+    }
+
+    /// Absorbs the given data, zero padding the last block to a full block, as in the AEAD
+    /// construction of RFC 8439.
+    pub fn update_padded(&mut self, data: &[u8]) {
+        for chunk in data.chunks(BLOCK_SIZE) {
+            let mut block = [0u8; BLOCK_SIZE];
+            block[..chunk.len()].copy_from_slice(chunk);
+            self.absorb(&block, true);
+        }
+    }
+
+    /// Computes the tag of the given message, whose last block may be partial.
+    pub fn compute_unpadded(mut self, data: &[u8]) -> [u8; BLOCK_SIZE] {
+        for chunk in data.chunks(BLOCK_SIZE) {
+            let mut block = [0u8; BLOCK_SIZE];
+            block[..chunk.len()].copy_from_slice(chunk);
+            if chunk.len() == BLOCK_SIZE {
+                self.absorb(&block, true);
+            } else {
+                // A partial block is terminated by a one byte instead of the 2^128 bit.
+                block[chunk.len()] = 1;
+                self.absorb(&block, false);
+            }
+        }
+        self.finalize()
+    }
+
+    /// Returns the tag of the absorbed data.
+    pub fn finalize(self) -> [u8; BLOCK_SIZE] {
+        let mut tag = [0u8; BLOCK_SIZE];
+        let mut carry = 0u64;
+        for (bytes, (acc, s)) in tag.chunks_exact_mut(4).zip(self.acc.iter().zip(self.s)) {
+            let sum = *acc as u64 + s as u64 + carry;
+            bytes.copy_from_slice(&(sum as u32).to_le_bytes());
+            carry = sum >> 32;
+        }
+        tag
+    }
+
+    /// Adds a block to the accumulator and multiplies it by `r`.
+    fn absorb(&mut self, block: &[u8; BLOCK_SIZE], full: bool) {
+        let block = words(block);
+
+        // The accumulator is below 2^130 and the block below 2^129, so the sum fits in five words.
+        let mut carry = 0u64;
+        for (acc, word) in self.acc.iter_mut().zip(block) {
+            let sum = *acc as u64 + word as u64 + carry;
+            *acc = sum as u32;
+            carry = sum >> 32;
+        }
+        self.acc[4] += carry as u32 + u32::from(full);
+
+        unsafe {
+            syscall_uint256_mulmod(&mut self.acc, self.r.as_ptr() as *const [u32; 8]);
+        }
+    }
+}
+
+/// Converts 16 bytes in little endian to words.
+fn words(bytes: &[u8]) -> [u32; 4] {
+    core::array::from_fn(|i| u32::from_le_bytes(bytes[i * 4..(i + 1) * 4].try_into().unwrap()))
+}
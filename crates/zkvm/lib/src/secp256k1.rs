@@ -1,11 +1,23 @@
+use alloc::vec::Vec;
+
 use crate::{
-    syscall_secp256k1_add, syscall_secp256k1_double,
-    utils::{AffinePoint, WeierstrassAffinePoint, WeierstrassPoint},
+    syscall_secp256k1_add, syscall_secp256k1_decompress, syscall_secp256k1_double,
+    utils::{bytes_to_words_le, AffinePoint, WeierstrassAffinePoint, WeierstrassPoint},
 };
 
 /// The number of limbs in [Secp256k1Point].
 pub const N: usize = 16;
 
+/// The base field modulus `p` of the Secp256k1 curve, as little endian words.
+const FIELD_MODULUS: [u32; 8] = [
+    0xFFFFFC2F, 0xFFFFFFFE, 0xFFFFFFFF, 0xFFFFFFFF, 0xFFFFFFFF, 0xFFFFFFFF, 0xFFFFFFFF, 0xFFFFFFFF,
+];
+
+/// The order `n` of the Secp256k1 group, as little endian words.
+const GROUP_ORDER: [u32; 8] = [
+    0xD0364141, 0xBFD25E8C, 0xAF48A03B, 0xBAAEDCE6, 0xFFFFFFFE, 0xFFFFFFFF, 0xFFFFFFFF, 0xFFFFFFFF,
+];
+
 /// An affine point on the Secp256k1 curve.
 #[derive(Copy, Clone, Debug)]
 #[repr(align(4))]
@@ -79,3 +91,87 @@ impl AffinePoint<N> for Secp256k1Point {
         }
     }
 }
+
+/// Verifies a BIP-340 Schnorr signature using the Secp256k1 precompiles.
+///
+/// `pubkey` is the x-only public key and `sig` is the `r || s` signature, both big endian as
+/// serialized by BIP-340. `challenge` is the big endian tagged hash
+/// `hash_BIP0340/challenge(r || pubkey || msg)`, which is left to the caller so that any message
+/// length and hash implementation can be used.
+///
+/// Checks that `R = s * G - e * P` is not the identity, has an even y coordinate and has `r` as its
+/// x coordinate, where `P` is the point with even y coordinate and x coordinate `pubkey`.
+///
+/// # Panics
+///
+/// Panics if `pubkey` is not the x coordinate of a point on the curve. Callers are expected to have
+/// validated the public key when parsing it.
+pub fn verify_schnorr(pubkey: &[u8; 32], sig: &[u8; 64], challenge: &[u8; 32]) -> bool {
+    let r = be_bytes_to_words(&sig[..32]);
+    let s = be_bytes_to_words(&sig[32..]);
+    if !lt(&r, &FIELD_MODULUS) || !lt(&s, &GROUP_ORDER) {
+        return false;
+    }
+
+    // Lift the public key to the point with an even y coordinate.
+    let mut decompressed = [0u8; 64];
+    decompressed[..32].copy_from_slice(pubkey);
+    unsafe {
+        syscall_secp256k1_decompress(&mut decompressed, false);
+    }
+    let mut limbs = [0u32; N];
+    limbs[..N / 2].copy_from_slice(&be_bytes_to_words(&decompressed[..32]));
+    limbs[N / 2..].copy_from_slice(&be_bytes_to_words(&decompressed[32..]));
+    let pubkey = Secp256k1Point::new(limbs);
+
+    // Reduce the challenge modulo `n`, a single subtraction suffices since `2^256 < 2n`, and negate
+    // it so that `R = s * G + (n - e) * P`.
+    let mut e = be_bytes_to_words(challenge);
+    if !lt(&e, &GROUP_ORDER) {
+        e = sub(&e, &GROUP_ORDER);
+    }
+    let neg_e = if e == [0; 8] { e } else { sub(&GROUP_ORDER, &e) };
+
+    let res = Secp256k1Point::multi_scalar_multiplication(
+        &words_to_bits_le(&s),
+        Secp256k1Point::GENERATOR_T,
+        &words_to_bits_le(&neg_e),
+        pubkey,
+    );
+    if res.is_infinity() {
+        return false;
+    }
+    let res = res.limbs_ref();
+    res[N / 2] & 1 == 0 && res[..N / 2] == r
+}
+
+/// Converts 32 big endian bytes to 8 little endian words.
+fn be_bytes_to_words(bytes: &[u8]) -> [u32; 8] {
+    let mut le_bytes = [0u8; 32];
+    le_bytes.copy_from_slice(bytes);
+    le_bytes.reverse();
+    bytes_to_words_le(&le_bytes).try_into().unwrap()
+}
+
+/// Converts little endian words to little endian bits.
+fn words_to_bits_le(words: &[u32; 8]) -> Vec<bool> {
+    (0..256).map(|i| (words[i / 32] >> (i % 32)) & 1 == 1).collect()
+}
+
+/// Returns whether `a < b` for little endian words.
+fn lt(a: &[u32; 8], b: &[u32; 8]) -> bool {
+    a.iter().rev().cmp(b.iter().rev()).is_lt()
+}
+
+/// Returns `a - b` for little endian words, assuming `a >= b`.
+fn sub(a: &[u32; 8], b: &[u32; 8]) -> [u32; 8] {
+    let mut res = [0u32; 8];
+    let mut borrow = false;
+    for ((res, a), b) in res.iter_mut().zip(a).zip(b) {
+        let (diff, b1) = a.overflowing_sub(*b);
+        let (diff, b2) = diff.overflowing_sub(u32::from(borrow));
+        *res = diff;
+        borrow = b1 || b2;
+    }
+    res
+}
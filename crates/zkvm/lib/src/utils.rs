@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 pub trait AffinePoint<const N: usize>: Clone + Sized {
     /// The generator.
     #[deprecated = "This const will have the `Self` type in the next major version."]
@@ -0,0 +1,279 @@
+//! Quantized kernels tuned for the zkVM cost model.
+//!
+//! Every load and store of the zkVM is a memory access of the proof, which costs much more than
+//! an ALU instruction, so the kernels minimize memory traffic:
+//!
+//! - Weights and activations are packed four `i8` to a word, so a multiply-accumulate of four
+//!   values reads two words instead of eight bytes. The values are unpacked with shifts, which are
+//!   cheap ALU instructions.
+//! - Images are stored in `[height, width, channels]` order, so the input channels of a convolution
+//!   window are contiguous, and each row of the window is a single packed dot product.
+//! - Each output is accumulated in a register, and written once.
+
+use crate::model::{Conv2d, Dense, MaxPool2d};
+
+/// Returns the number of words of `len` packed values.
+#[must_use]
+pub const fn packed_len(len: usize) -> usize {
+    len.div_ceil(4)
+}
+
+/// Packs values four to a word, in little endian order, padding the last word with zeros.
+#[must_use]
+pub fn pack(values: &[i8]) -> Vec<u32> {
+    values
+        .chunks(4)
+        .map(|chunk| {
+            let mut bytes = [0u8; 4];
+            for (byte, &value) in bytes.iter_mut().zip(chunk) {
+                *byte = value as u8;
+            }
+            u32::from_le_bytes(bytes)
+        })
+        .collect()
+}
+
+/// Returns the dot product of two packed vectors.
+#[inline]
+#[must_use]
+pub fn dot(x: &[u32], y: &[u32]) -> i32 {
+    let mut acc = 0i32;
+    for (&x, &y) in x.iter().zip(y) {
+        acc = acc.wrapping_add(dot4(x, y));
+    }
+    acc
+}
+
+/// Returns the dot product of two words of four packed values.
+#[inline(always)]
+fn dot4(x: u32, y: u32) -> i32 {
+    let lane = |word: u32, i: u32| ((word << (24 - 8 * i)) as i32) >> 24;
+    lane(x, 0) * lane(y, 0) +
+        lane(x, 1) * lane(y, 1) +
+        lane(x, 2) * lane(y, 2) +
+        lane(x, 3) * lane(y, 3)
+}
+
+/// Computes a fully connected layer.
+#[must_use]
+pub fn dense(layer: &Dense, input: &[i8]) -> Vec<i8> {
+    let input = pack(input);
+    let row_len = packed_len(layer.inputs);
+    layer
+        .weights
+        .chunks_exact(row_len)
+        .zip(&layer.bias)
+        .map(|(row, &bias)| layer.requantize.apply(bias.wrapping_add(dot(row, &input))))
+        .collect()
+}
+
+/// Computes a two dimensional convolution of an image of the given height and width.
+#[must_use]
+pub fn conv2d(layer: &Conv2d, input: &[i8], height: usize, width: usize) -> Vec<i8> {
+    let [kernel_height, kernel_width] = layer.kernel;
+    let [stride_y, stride_x] = layer.stride;
+    let [padding_y, padding_x] = layer.padding;
+    let out_height = (height + 2 * padding_y - kernel_height) / stride_y + 1;
+    let out_width = (width + 2 * padding_x - kernel_width) / stride_x + 1;
+
+    // Pack the channels of each pixel, so that a row of a window is contiguous.
+    let pixel_len = packed_len(layer.in_channels);
+    let pixels = input.chunks_exact(layer.in_channels).flat_map(pack).collect::<Vec<_>>();
+    let filter_len = kernel_height * kernel_width * pixel_len;
+
+    let mut output = Vec::with_capacity(out_height * out_width * layer.out_channels);
+    for out_y in 0..out_height {
+        // The rows of the window inside the image, as zero padding adds nothing.
+        let top = (out_y * stride_y) as isize - padding_y as isize;
+        let rows = (top.max(0) as usize)..
+            ((top + kernel_height as isize).clamp(0, height as isize) as usize);
+        for out_x in 0..out_width {
+            let left = (out_x * stride_x) as isize - padding_x as isize;
+            let columns = (left.max(0) as usize)..
+                ((left + kernel_width as isize).clamp(0, width as isize) as usize);
+            let kernel_x = (columns.start as isize - left) as usize;
+
+            for (filter, &bias) in layer.weights.chunks_exact(filter_len).zip(&layer.bias) {
+                let mut acc = bias;
+                for y in rows.clone() {
+                    let kernel_y = (y as isize - top) as usize;
+                    let image_row = &pixels[(y * width + columns.start) * pixel_len..]
+                        [..columns.len() * pixel_len];
+                    let filter_row = &filter[(kernel_y * kernel_width + kernel_x) * pixel_len..]
+                        [..columns.len() * pixel_len];
+                    acc = acc.wrapping_add(dot(filter_row, image_row));
+                }
+                output.push(layer.requantize.apply(acc));
+            }
+        }
+    }
+    output
+}
+
+/// Computes a two dimensional max pooling of an image of the given height, width and channels.
+#[must_use]
+pub fn max_pool2d(
+    layer: &MaxPool2d,
+    input: &[i8],
+    height: usize,
+    width: usize,
+    channels: usize,
+) -> Vec<i8> {
+    let [kernel_height, kernel_width] = layer.kernel;
+    let [stride_y, stride_x] = layer.stride;
+    let out_height = (height - kernel_height) / stride_y + 1;
+    let out_width = (width - kernel_width) / stride_x + 1;
+
+    let mut output = vec![i8::MIN; out_height * out_width * channels];
+    for out_y in 0..out_height {
+        for out_x in 0..out_width {
+            let pooled = &mut output[(out_y * out_width + out_x) * channels..][..channels];
+            for y in out_y * stride_y..out_y * stride_y + kernel_height {
+                for x in out_x * stride_x..out_x * stride_x + kernel_width {
+                    let pixel = &input[(y * width + x) * channels..][..channels];
+                    for (max, &value) in pooled.iter_mut().zip(pixel) {
+                        *max = (*max).max(value);
+                    }
+                }
+            }
+        }
+    }
+    output
+}
+
+/// Computes the rectified linear unit in place.
+pub fn relu(values: &mut [i8]) {
+    for value in values {
+        *value = (*value).max(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Requantize;
+
+    /// Deterministic values covering the whole range of an `i8`.
+    fn values(len: usize, seed: u32) -> Vec<i8> {
+        (0..len as u32)
+            .map(|i| (i.wrapping_add(seed << 16).wrapping_mul(2_654_435_761) >> 24) as i8)
+            .collect()
+    }
+
+    #[test]
+    fn test_pack_and_dot() {
+        assert_eq!(pack(&[1, -1, 2]), vec![0x0002_ff01]);
+        assert_eq!(packed_len(0), 0);
+        assert_eq!(packed_len(5), 2);
+
+        for len in [0, 1, 3, 4, 7, 64] {
+            let x = values(len, 1);
+            let y = values(len, 2);
+            let expected = x.iter().zip(&y).map(|(&x, &y)| i32::from(x) * i32::from(y)).sum();
+            assert_eq!(dot(&pack(&x), &pack(&y)), expected, "len {len}");
+        }
+        assert_eq!(dot(&pack(&[i8::MIN; 4]), &pack(&[i8::MIN; 4])), 4 * 128 * 128);
+    }
+
+    #[test]
+    fn test_dense() {
+        let (inputs, outputs) = (5, 3);
+        let weights = values(inputs * outputs, 3);
+        let input = values(inputs, 4);
+        let layer = Dense {
+            inputs,
+            outputs,
+            weights: weights.chunks_exact(inputs).flat_map(pack).collect(),
+            bias: vec![100, -100, 0],
+            requantize: Requantize::from_ratio(1.0 / 64.0),
+        };
+
+        let expected = weights
+            .chunks_exact(inputs)
+            .zip(&layer.bias)
+            .map(|(row, &bias)| {
+                let acc = row.iter().zip(&input).map(|(&w, &x)| i32::from(w) * i32::from(x));
+                layer.requantize.apply(bias + acc.sum::<i32>())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(dense(&layer, &input), expected);
+    }
+
+    #[test]
+    fn test_conv2d() {
+        for (stride, padding) in [([1, 1], [0, 0]), ([1, 1], [1, 1]), ([2, 1], [1, 0])] {
+            let (height, width, in_channels, out_channels) = (5, 4, 3, 2);
+            let kernel = [3, 2];
+            let weights = values(out_channels * kernel[0] * kernel[1] * in_channels, 5);
+            let input = values(height * width * in_channels, 6);
+            let layer = Conv2d {
+                in_channels,
+                out_channels,
+                kernel,
+                stride,
+                padding,
+                weights: weights.chunks_exact(in_channels).flat_map(pack).collect(),
+                bias: vec![7, -7],
+                requantize: Requantize::from_ratio(1.0 / 128.0),
+            };
+
+            // A direct convolution, reading the unpacked values.
+            let out_height = (height + 2 * padding[0] - kernel[0]) / stride[0] + 1;
+            let out_width = (width + 2 * padding[1] - kernel[1]) / stride[1] + 1;
+            let mut expected = Vec::new();
+            for out_y in 0..out_height {
+                for out_x in 0..out_width {
+                    for o in 0..out_channels {
+                        let mut acc = layer.bias[o];
+                        for ky in 0..kernel[0] {
+                            for kx in 0..kernel[1] {
+                                let y = (out_y * stride[0] + ky) as isize - padding[0] as isize;
+                                let x = (out_x * stride[1] + kx) as isize - padding[1] as isize;
+                                if y < 0 || x < 0 || y >= height as isize || x >= width as isize {
+                                    continue;
+                                }
+                                for c in 0..in_channels {
+                                    let w = weights
+                                        [((o * kernel[0] + ky) * kernel[1] + kx) * in_channels + c];
+                                    let v = input
+                                        [(y as usize * width + x as usize) * in_channels + c];
+                                    acc += i32::from(w) * i32::from(v);
+                                }
+                            }
+                        }
+                        expected.push(layer.requantize.apply(acc));
+                    }
+                }
+            }
+            assert_eq!(
+                conv2d(&layer, &input, height, width),
+                expected,
+                "stride {stride:?}, padding {padding:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_max_pool2d() {
+        let layer = MaxPool2d { kernel: [2, 2], stride: [2, 2] };
+        #[rustfmt::skip]
+        let input = [
+            1, -1,   2, -2,   3, -3,
+            4, -4,   5, -5,   6, -6,
+            -7, 7,   -8, 8,   -9, 9,
+        ];
+        // A 3x3 image of two channels is pooled to a 1x1 image, as the last row and column are
+        // dropped.
+        assert_eq!(max_pool2d(&layer, &input, 3, 3, 2), vec![5, -1]);
+
+        let layer = MaxPool2d { kernel: [2, 1], stride: [1, 1] };
+        assert_eq!(max_pool2d(&layer, &[1, 5, -3, 2], 2, 2, 1), vec![1, 5]);
+    }
+
+    #[test]
+    fn test_relu() {
+        let mut values = [i8::MIN, -1, 0, 1, i8::MAX];
+        relu(&mut values);
+        assert_eq!(values, [0, 0, 0, 1, i8::MAX]);
+    }
+}
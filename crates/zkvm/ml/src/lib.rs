@@ -0,0 +1,153 @@
+//! A guest library for proving the inference of quantized neural networks with MONEROCHAN.
+//!
+//! Models are run with 8-bit integer weights and activations and 32-bit accumulators, which only
+//! use integer instructions, so the result is deterministic and identical on the host and in the
+//! zkVM. A single guest program can prove the inference of any model:
+//!
+//! ```ignore
+//! #![no_main]
+//! monerochan_runtime::entrypoint!(main);
+//!
+//! pub fn main() {
+//!     monerochan_ml::run();
+//! }
+//! ```
+//!
+//! The guest reads the model, serialized with bincode, and its quantized input from the stdin,
+//! runs the model, then commits the SHA-256 digest of the serialized model followed by the
+//! quantized output, framed with `monerochan_lib::io::commit_framed`. The guest should patch
+//! `sha2` to hash the model on the SHA-256 precompile.
+//!
+//! On the host, models are converted from ONNX with the [`onnx`] module, inputs are quantized
+//! with [`Model::quantize_input`], and outputs are dequantized with [`Model::dequantize_output`].
+//!
+//! # Cost model
+//!
+//! The kernels of the [`kernels`] module are tuned for the zkVM, where memory accesses dominate
+//! the cost of a proof. Weights are stored packed four to a word, so that the weights are read
+//! with a quarter of the loads, and the model is read with the hint syscalls in one copy.
+
+pub mod kernels;
+pub mod model;
+#[cfg(not(target_os = "zkvm"))]
+pub mod onnx;
+
+pub use model::*;
+
+use monerochan_lib::io;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// Errors that can occur when running a model.
+#[derive(Debug, Error)]
+pub enum MlError {
+    /// The model could not be deserialized.
+    #[error("invalid model: {0}")]
+    Model(bincode::Error),
+
+    /// The layers of the model do not fit the shapes of their inputs.
+    #[error("the layers of the model do not fit the shape {0:?} of the input")]
+    Shape(Vec<usize>),
+
+    /// The input does not have the length of the input shape of the model.
+    #[error("the input has {0} values, expected {1}")]
+    InputLength(usize, usize),
+}
+
+/// Reads a model and its input from the stdin, runs the model and commits its digest and output.
+///
+/// # Panics
+/// Panics if the model fails to run, so no proof can be generated for it.
+pub fn run() {
+    let model = io::read_vec();
+    let input = io::read_vec().into_iter().map(|x| x as i8).collect::<Vec<_>>();
+
+    let output = bincode::deserialize(&model)
+        .map_err(MlError::Model)
+        .and_then(|model: Model| infer(&model, input))
+        .unwrap_or_else(|e| panic!("{e}"));
+
+    io::commit_slice(&model_hash(&model));
+    io::commit_framed(&output.into_iter().map(|x| x as u8).collect::<Vec<_>>());
+}
+
+/// Runs a model on a quantized input, and returns its quantized output.
+pub fn infer(model: &Model, mut values: Vec<i8>) -> Result<Vec<i8>, MlError> {
+    let shapes = model.shapes().ok_or_else(|| MlError::Shape(model.input_shape.clone()))?;
+    let input_len = model.input_shape.iter().product();
+    if values.len() != input_len {
+        return Err(MlError::InputLength(values.len(), input_len));
+    }
+
+    let mut shape = &model.input_shape;
+    for (layer, output_shape) in model.layers.iter().zip(&shapes) {
+        values = match (layer, shape[..]) {
+            (Layer::Dense(dense), _) => kernels::dense(dense, &values),
+            (Layer::Conv2d(conv), [height, width, _]) => {
+                kernels::conv2d(conv, &values, height, width)
+            }
+            (Layer::MaxPool2d(pool), [height, width, channels]) => {
+                kernels::max_pool2d(pool, &values, height, width, channels)
+            }
+            (Layer::Relu, _) => {
+                kernels::relu(&mut values);
+                values
+            }
+            (Layer::Flatten, _) => values,
+            _ => unreachable!("the shapes are checked"),
+        };
+        shape = output_shape;
+    }
+    Ok(values)
+}
+
+/// The SHA-256 digest of a serialized model, as committed by the guest.
+#[must_use]
+pub fn model_hash(model_bytes: &[u8]) -> [u8; 32] {
+    Sha256::digest(model_bytes).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A model of two features, computing `relu(x0 - x1)` and `relu(x1 - x0)`.
+    fn model() -> Model {
+        Model {
+            input_shape: vec![2],
+            input_scale: 1.0,
+            output_scale: 1.0,
+            layers: vec![
+                Layer::Dense(Dense {
+                    inputs: 2,
+                    outputs: 2,
+                    weights: [kernels::pack(&[1, -1]), kernels::pack(&[-1, 1])].concat(),
+                    bias: vec![0, 0],
+                    requantize: Requantize::from_ratio(1.0),
+                }),
+                Layer::Relu,
+            ],
+        }
+    }
+
+    #[test]
+    fn test_infer() {
+        assert_eq!(infer(&model(), vec![5, 2]).unwrap(), vec![3, 0]);
+        assert_eq!(infer(&model(), vec![-100, 100]).unwrap(), vec![0, i8::MAX]);
+    }
+
+    #[test]
+    fn test_infer_malformed() {
+        assert!(matches!(infer(&model(), vec![1, 2, 3]), Err(MlError::InputLength(3, 2))));
+
+        let mut unfit = model();
+        unfit.input_shape = vec![3];
+        assert!(matches!(infer(&unfit, vec![1, 2, 3]), Err(MlError::Shape(shape)) if shape == [3]));
+
+        // Truncated and garbage serializations are rejected rather than run.
+        let bytes = bincode::serialize(&model()).unwrap();
+        assert_eq!(bincode::deserialize::<Model>(&bytes).unwrap(), model());
+        assert!(bincode::deserialize::<Model>(&bytes[..bytes.len() - 1]).is_err());
+        assert!(bincode::deserialize::<Model>(&[0xff; 64]).is_err());
+    }
+}
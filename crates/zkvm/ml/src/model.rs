@@ -0,0 +1,312 @@
+//! The quantized model format run by the guest.
+//!
+//! Activations and weights are signed 8-bit integers with a per-tensor scale and a zero point of
+//! zero, and accumulators are 32-bit integers. Weights are stored packed four to a word, which is
+//! the layout read by the kernels.
+
+use serde::{Deserialize, Serialize};
+
+use crate::kernels;
+
+/// A quantized model, which is a sequence of layers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Model {
+    /// The shape of the input, as `[height, width, channels]` for an image or `[features]`.
+    pub input_shape: Vec<usize>,
+    /// The scale of the quantized input, such that a real value is `scale * quantized`.
+    pub input_scale: f32,
+    /// The scale of the quantized output.
+    pub output_scale: f32,
+    /// The layers, applied in order.
+    pub layers: Vec<Layer>,
+}
+
+/// A layer of a [`Model`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Layer {
+    /// A fully connected layer.
+    Dense(Dense),
+    /// A two dimensional convolution.
+    Conv2d(Conv2d),
+    /// The rectified linear unit, `max(x, 0)`.
+    Relu,
+    /// A two dimensional max pooling.
+    MaxPool2d(MaxPool2d),
+    /// Flattens an image to features, in `[height, width, channels]` order.
+    Flatten,
+}
+
+/// The rescaling of 32-bit accumulators to 8-bit activations.
+///
+/// An accumulator `acc` is rescaled to `round(acc * multiplier / 2^shift)`, clamped to the range
+/// of an `i8`, which is the fixed-point form of the ratio of the scales of the accumulator and of
+/// the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Requantize {
+    /// The multiplier, normalized to `[2^30, 2^31)` unless it is zero.
+    pub multiplier: i32,
+    /// The right shift applied after the multiplication, at most `62`.
+    pub shift: u8,
+}
+
+/// A fully connected layer, computing `requantize(weights * input + bias)`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Dense {
+    /// The number of input features.
+    pub inputs: usize,
+    /// The number of output features.
+    pub outputs: usize,
+    /// The weights, one row per output, each row packed with [`kernels::pack`].
+    pub weights: Vec<u32>,
+    /// The bias of each output, in the scale of the accumulators.
+    pub bias: Vec<i32>,
+    /// The rescaling of the accumulators.
+    pub requantize: Requantize,
+}
+
+/// A two dimensional convolution over an image in `[height, width, channels]` order, with zero
+/// padding.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Conv2d {
+    /// The number of input channels.
+    pub in_channels: usize,
+    /// The number of output channels.
+    pub out_channels: usize,
+    /// The height and width of the kernel.
+    pub kernel: [usize; 2],
+    /// The vertical and horizontal strides.
+    pub stride: [usize; 2],
+    /// The zero padding before the first row and column, the same padding is applied after the
+    /// last ones.
+    pub padding: [usize; 2],
+    /// The weights in `[out_channels, kernel height, kernel width, in_channels]` order, with each
+    /// group of input channels packed with [`kernels::pack`].
+    pub weights: Vec<u32>,
+    /// The bias of each output channel, in the scale of the accumulators.
+    pub bias: Vec<i32>,
+    /// The rescaling of the accumulators.
+    pub requantize: Requantize,
+}
+
+/// A two dimensional max pooling over an image in `[height, width, channels]` order, without
+/// padding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaxPool2d {
+    /// The height and width of the window.
+    pub kernel: [usize; 2],
+    /// The vertical and horizontal strides.
+    pub stride: [usize; 2],
+}
+
+impl Requantize {
+    /// Returns the rescaling by a real ratio, which must be nonnegative.
+    #[must_use]
+    pub fn from_ratio(ratio: f64) -> Self {
+        if ratio.is_nan() || ratio <= 0.0 {
+            return Self { multiplier: 0, shift: 0 };
+        }
+
+        // Normalize the ratio to `[2^30, 2^31)`, rounding the multiplier.
+        let mut shift = 0i32;
+        let mut scaled = ratio;
+        while scaled < f64::from(1u32 << 30) && shift < 62 {
+            scaled *= 2.0;
+            shift += 1;
+        }
+        while scaled >= f64::from(1u32 << 31) && shift > 0 {
+            scaled /= 2.0;
+            shift -= 1;
+        }
+        let multiplier = scaled.round().min(f64::from(i32::MAX)) as i32;
+        Self { multiplier, shift: shift as u8 }
+    }
+
+    /// Rescales an accumulator.
+    #[must_use]
+    pub fn apply(self, acc: i32) -> i8 {
+        let product = i64::from(acc) * i64::from(self.multiplier);
+        let rounding = if self.shift == 0 { 0 } else { 1 << (self.shift - 1) };
+        ((product + rounding) >> self.shift).clamp(i8::MIN.into(), i8::MAX.into()) as i8
+    }
+}
+
+impl Model {
+    /// Returns the shape of the output of each layer, or `None` if the layers do not fit the
+    /// shapes of their inputs.
+    #[must_use]
+    pub fn shapes(&self) -> Option<Vec<Vec<usize>>> {
+        let mut shape = self.input_shape.clone();
+        let mut shapes = Vec::with_capacity(self.layers.len());
+        for layer in &self.layers {
+            shape = layer.output_shape(&shape)?;
+            shapes.push(shape.clone());
+        }
+        Some(shapes)
+    }
+
+    /// Quantizes a real input, given in `[channels, height, width]` order for an image as in
+    /// ONNX, to the input of the model.
+    #[must_use]
+    pub fn quantize_input(&self, input: &[f32]) -> Vec<i8> {
+        let quantize =
+            |x: f32| (x / self.input_scale).round().clamp(i8::MIN.into(), i8::MAX.into()) as i8;
+        match self.input_shape[..] {
+            [height, width, channels] => {
+                let mut quantized = vec![0; input.len()];
+                for c in 0..channels {
+                    for y in 0..height {
+                        for x in 0..width {
+                            quantized[(y * width + x) * channels + c] =
+                                quantize(input[(c * height + y) * width + x]);
+                        }
+                    }
+                }
+                quantized
+            }
+            _ => input.iter().map(|&x| quantize(x)).collect(),
+        }
+    }
+
+    /// Dequantizes an output of the model to real values.
+    #[must_use]
+    pub fn dequantize_output(&self, output: &[i8]) -> Vec<f32> {
+        output.iter().map(|&x| f32::from(x) * self.output_scale).collect()
+    }
+}
+
+impl Layer {
+    /// Returns the shape of the output of the layer, or `None` if the layer does not fit the
+    /// shape of its input.
+    #[must_use]
+    pub fn output_shape(&self, input: &[usize]) -> Option<Vec<usize>> {
+        match (self, input) {
+            (Layer::Dense(dense), [features]) if *features == dense.inputs => {
+                (dense.weights.len() == dense.outputs * kernels::packed_len(dense.inputs) &&
+                    dense.bias.len() == dense.outputs)
+                    .then(|| vec![dense.outputs])
+            }
+            (Layer::Conv2d(conv), [height, width, channels]) if *channels == conv.in_channels => {
+                let weights = conv.out_channels *
+                    conv.kernel[0] *
+                    conv.kernel[1] *
+                    kernels::packed_len(conv.in_channels);
+                if conv.weights.len() != weights || conv.bias.len() != conv.out_channels {
+                    return None;
+                }
+                let out_height =
+                    window_count(*height, conv.kernel[0], conv.stride[0], conv.padding[0])?;
+                let out_width =
+                    window_count(*width, conv.kernel[1], conv.stride[1], conv.padding[1])?;
+                Some(vec![out_height, out_width, conv.out_channels])
+            }
+            (Layer::MaxPool2d(pool), [height, width, channels]) => Some(vec![
+                window_count(*height, pool.kernel[0], pool.stride[0], 0)?,
+                window_count(*width, pool.kernel[1], pool.stride[1], 0)?,
+                *channels,
+            ]),
+            (Layer::Relu, shape) => Some(shape.to_vec()),
+            (Layer::Flatten, shape) => Some(vec![shape.iter().product()]),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the number of windows of a pooling or convolution along one dimension.
+fn window_count(size: usize, kernel: usize, stride: usize, padding: usize) -> Option<usize> {
+    let padded = size + 2 * padding;
+    (kernel > 0 && stride > 0 && padded >= kernel).then(|| (padded - kernel) / stride + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requantize() {
+        for ratio in [1.0, 0.5, 1.0 / 3.0, 1e-6, 3.5] {
+            let requantize = Requantize::from_ratio(ratio);
+            assert!(requantize.multiplier >= 1 << 30, "ratio {ratio}");
+            for acc in [-1000, -7, 0, 7, 30] {
+                let expected = (f64::from(acc) * ratio).round().clamp(-128.0, 127.0) as i8;
+                assert!(
+                    (i16::from(requantize.apply(acc)) - i16::from(expected)).abs() <= 1,
+                    "ratio {ratio}, acc {acc}"
+                );
+            }
+        }
+        assert_eq!(Requantize::from_ratio(1.0).apply(1000), i8::MAX);
+        assert_eq!(Requantize::from_ratio(1.0).apply(-1000), i8::MIN);
+
+        for ratio in [0.0, -1.0, f64::NAN] {
+            let requantize = Requantize::from_ratio(ratio);
+            assert_eq!(requantize, Requantize { multiplier: 0, shift: 0 });
+            assert_eq!(requantize.apply(i32::MAX), 0);
+        }
+    }
+
+    #[test]
+    fn test_shapes() {
+        let conv = Conv2d {
+            in_channels: 1,
+            out_channels: 2,
+            kernel: [3, 3],
+            stride: [1, 1],
+            padding: [1, 1],
+            weights: vec![0; 2 * 3 * 3],
+            bias: vec![0; 2],
+            requantize: Requantize::from_ratio(1.0),
+        };
+        let dense = Dense {
+            inputs: 8,
+            outputs: 3,
+            weights: vec![0; 3 * 2],
+            bias: vec![0; 3],
+            requantize: Requantize::from_ratio(1.0),
+        };
+        let mut model = Model {
+            input_shape: vec![4, 4, 1],
+            input_scale: 1.0,
+            output_scale: 1.0,
+            layers: vec![
+                Layer::Conv2d(conv.clone()),
+                Layer::Relu,
+                Layer::MaxPool2d(MaxPool2d { kernel: [2, 2], stride: [2, 2] }),
+                Layer::Flatten,
+                Layer::Dense(dense.clone()),
+            ],
+        };
+        assert_eq!(
+            model.shapes(),
+            Some(vec![vec![4, 4, 2], vec![4, 4, 2], vec![2, 2, 2], vec![8], vec![3]])
+        );
+
+        // A dense layer on an image, before flattening.
+        model.layers.swap(3, 4);
+        assert_eq!(model.shapes(), None);
+
+        // Weights which do not fit the layer.
+        let short = Layer::Dense(Dense { weights: vec![0; 5], ..dense });
+        assert_eq!(short.output_shape(&[8]), None);
+        let short = Layer::Conv2d(Conv2d { bias: vec![0], ..conv.clone() });
+        assert_eq!(short.output_shape(&[4, 4, 1]), None);
+
+        // Windows larger than the padded image, and zero strides.
+        assert_eq!(Layer::Conv2d(conv.clone()).output_shape(&[0, 4, 1]), None);
+        let pool = MaxPool2d { kernel: [2, 2], stride: [0, 1] };
+        assert_eq!(Layer::MaxPool2d(pool).output_shape(&[4, 4, 1]), None);
+        assert_eq!(Layer::Conv2d(conv).output_shape(&[16]), None);
+    }
+
+    #[test]
+    fn test_quantize_input() {
+        let model = Model {
+            input_shape: vec![1, 2, 2],
+            input_scale: 0.5,
+            output_scale: 0.25,
+            layers: Vec::new(),
+        };
+        // The input is given in `[channels, height, width]` order.
+        assert_eq!(model.quantize_input(&[1.0, 2.0, 100.0, -100.0]), vec![2, 127, 4, -128]);
+        assert_eq!(model.dequantize_output(&[4, -2]), vec![1.0, -0.5]);
+    }
+}
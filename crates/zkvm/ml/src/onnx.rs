@@ -0,0 +1,980 @@
+//! Conversion of small ONNX models to quantized [`Model`]s, on the host.
+//!
+//! The converter supports sequential graphs, where each node consumes the output of the previous
+//! one, built from the following operators:
+//!
+//! - `Gemm`, and `MatMul` optionally followed by an `Add` of a constant bias.
+//! - `Conv` without groups or dilations, and with the same padding on both sides.
+//! - `Relu`, `MaxPool` without padding, `Flatten` and `Reshape` to features.
+//! - `Dropout` and `Identity`, which are skipped.
+//!
+//! Weights are quantized per tensor from their range. Activations are quantized per tensor from
+//! their range over calibration inputs, which should be representative of the inputs the model
+//! will be run on.
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::{
+    kernels,
+    model::{Conv2d, Dense, Layer, MaxPool2d, Model, Requantize},
+};
+
+/// Errors that can occur when converting an ONNX model.
+#[derive(Debug, Error)]
+pub enum OnnxError {
+    /// The model is not a valid ONNX protobuf.
+    #[error("invalid onnx protobuf: {0}")]
+    Protobuf(&'static str),
+
+    /// The graph has no input, or its input has an unknown dimension.
+    #[error("the graph input must have a known shape")]
+    Input,
+
+    /// A node is not supported by the converter.
+    #[error("unsupported node {name} ({op_type}): {reason}")]
+    Unsupported {
+        /// The name of the node.
+        name: String,
+        /// The operator of the node.
+        op_type: String,
+        /// Why the node is not supported.
+        reason: String,
+    },
+
+    /// No calibration input was given, or one has the wrong length.
+    #[error("the calibration inputs must be nonempty and have {0} values")]
+    Calibration(usize),
+}
+
+/// Converts an ONNX model to a quantized model.
+///
+/// The calibration inputs are given in the order of the ONNX input, which is
+/// `[channels, height, width]` for an image.
+pub fn convert(onnx: &[u8], calibration: &[Vec<f32>]) -> Result<Model, OnnxError> {
+    let graph = Graph::parse(onnx)?;
+    let (input_shape, layers) = graph.layers()?;
+
+    let input_len = input_shape.iter().product::<usize>();
+    if calibration.is_empty() || calibration.iter().any(|input| input.len() != input_len) {
+        return Err(OnnxError::Calibration(input_len));
+    }
+    let inputs =
+        calibration.iter().map(|input| to_image_order(&input_shape, input)).collect::<Vec<_>>();
+
+    quantize(input_shape, &layers, inputs)
+}
+
+/// A layer with real weights, in the layout of the quantized model.
+#[derive(Debug, Clone)]
+enum FloatLayer {
+    Dense { inputs: usize, outputs: usize, weights: Vec<f32>, bias: Vec<f32> },
+    Conv2d { conv: Conv2d, weights: Vec<f32>, bias: Vec<f32> },
+    Relu,
+    MaxPool2d(MaxPool2d),
+    Flatten,
+}
+
+/// Quantizes real layers, calibrating the scales of the activations on the given inputs.
+fn quantize(
+    input_shape: Vec<usize>,
+    layers: &[FloatLayer],
+    mut activations: Vec<Vec<f32>>,
+) -> Result<Model, OnnxError> {
+    let input_scale = scale(activations.iter().flatten());
+    let mut scale_in = input_scale;
+    let mut shape = input_shape.clone();
+    let mut quantized = Vec::with_capacity(layers.len());
+
+    for layer in layers {
+        for values in &mut activations {
+            *values = layer.forward(&shape, values);
+        }
+        let scale_out = match layer {
+            FloatLayer::Dense { .. } | FloatLayer::Conv2d { .. } => {
+                scale(activations.iter().flatten())
+            }
+            _ => scale_in,
+        };
+
+        let layer = match layer {
+            FloatLayer::Dense { inputs, outputs, weights, bias } => {
+                let (weights, bias, requantize) =
+                    quantize_weights(weights, bias, *inputs, scale_in, scale_out);
+                Layer::Dense(Dense {
+                    inputs: *inputs,
+                    outputs: *outputs,
+                    weights,
+                    bias,
+                    requantize,
+                })
+            }
+            FloatLayer::Conv2d { conv, weights, bias } => {
+                let (weights, bias, requantize) =
+                    quantize_weights(weights, bias, conv.in_channels, scale_in, scale_out);
+                Layer::Conv2d(Conv2d { weights, bias, requantize, ..conv.clone() })
+            }
+            FloatLayer::Relu => Layer::Relu,
+            FloatLayer::MaxPool2d(pool) => Layer::MaxPool2d(*pool),
+            FloatLayer::Flatten => Layer::Flatten,
+        };
+        shape = layer.output_shape(&shape).expect("the shapes are checked when parsing");
+        quantized.push(layer);
+        scale_in = scale_out;
+    }
+
+    Ok(Model { input_shape, input_scale, output_scale: scale_in, layers: quantized })
+}
+
+/// Returns the scale mapping the largest magnitude of the values to `127`.
+fn scale<'a>(values: impl Iterator<Item = &'a f32>) -> f32 {
+    let max = values.fold(0f32, |max, value| max.max(value.abs()));
+    if max > 0.0 {
+        max / 127.0
+    } else {
+        1.0
+    }
+}
+
+/// Quantizes weights, whose rows of `row_len` values are packed, and the bias in the scale of
+/// the accumulators.
+fn quantize_weights(
+    weights: &[f32],
+    bias: &[f32],
+    row_len: usize,
+    scale_in: f32,
+    scale_out: f32,
+) -> (Vec<u32>, Vec<i32>, Requantize) {
+    let scale_weights = scale(weights.iter());
+    let quantized = weights
+        .iter()
+        .map(|&w| (w / scale_weights).round().clamp(-127.0, 127.0) as i8)
+        .collect::<Vec<_>>();
+    let packed = quantized.chunks_exact(row_len).flat_map(kernels::pack).collect();
+
+    let scale_acc = f64::from(scale_in) * f64::from(scale_weights);
+    let bias = bias.iter().map(|&b| (f64::from(b) / scale_acc).round() as i32).collect();
+    (packed, bias, Requantize::from_ratio(scale_acc / f64::from(scale_out)))
+}
+
+/// Reorders an input from `[channels, height, width]` to `[height, width, channels]` order.
+fn to_image_order(shape: &[usize], input: &[f32]) -> Vec<f32> {
+    match shape[..] {
+        [height, width, channels] => {
+            let mut image = vec![0.0; input.len()];
+            for c in 0..channels {
+                for y in 0..height {
+                    for x in 0..width {
+                        image[(y * width + x) * channels + c] = input[(c * height + y) * width + x];
+                    }
+                }
+            }
+            image
+        }
+        _ => input.to_vec(),
+    }
+}
+
+impl FloatLayer {
+    /// Runs the layer on real values, with the same layout as the kernels.
+    fn forward(&self, shape: &[usize], input: &[f32]) -> Vec<f32> {
+        match (self, shape[..]) {
+            (FloatLayer::Dense { inputs, weights, bias, .. }, _) => weights
+                .chunks_exact(*inputs)
+                .zip(bias)
+                .map(|(row, b)| b + row.iter().zip(input).map(|(w, x)| w * x).sum::<f32>())
+                .collect(),
+            (FloatLayer::Conv2d { conv, weights, bias }, [height, width, _]) => {
+                let [kernel_height, kernel_width] = conv.kernel;
+                let out_height =
+                    (height + 2 * conv.padding[0] - kernel_height) / conv.stride[0] + 1;
+                let out_width = (width + 2 * conv.padding[1] - kernel_width) / conv.stride[1] + 1;
+                let filter_len = kernel_height * kernel_width * conv.in_channels;
+                let mut output = Vec::with_capacity(out_height * out_width * conv.out_channels);
+                for out_y in 0..out_height {
+                    for out_x in 0..out_width {
+                        for (filter, b) in weights.chunks_exact(filter_len).zip(bias) {
+                            let mut acc = *b;
+                            for ky in 0..kernel_height {
+                                for kx in 0..kernel_width {
+                                    let y = (out_y * conv.stride[0] + ky) as isize -
+                                        conv.padding[0] as isize;
+                                    let x = (out_x * conv.stride[1] + kx) as isize -
+                                        conv.padding[1] as isize;
+                                    if y < 0 || x < 0 || y >= height as isize || x >= width as isize
+                                    {
+                                        continue;
+                                    }
+                                    let pixel =
+                                        (y as usize * width + x as usize) * conv.in_channels;
+                                    let taps = (ky * kernel_width + kx) * conv.in_channels;
+                                    for c in 0..conv.in_channels {
+                                        acc += filter[taps + c] * input[pixel + c];
+                                    }
+                                }
+                            }
+                            output.push(acc);
+                        }
+                    }
+                }
+                output
+            }
+            (FloatLayer::MaxPool2d(pool), [height, width, channels]) => {
+                let out_height = (height - pool.kernel[0]) / pool.stride[0] + 1;
+                let out_width = (width - pool.kernel[1]) / pool.stride[1] + 1;
+                let mut output = vec![f32::NEG_INFINITY; out_height * out_width * channels];
+                for out_y in 0..out_height {
+                    for out_x in 0..out_width {
+                        for ky in 0..pool.kernel[0] {
+                            for kx in 0..pool.kernel[1] {
+                                let y = out_y * pool.stride[0] + ky;
+                                let x = out_x * pool.stride[1] + kx;
+                                for c in 0..channels {
+                                    let max =
+                                        &mut output[(out_y * out_width + out_x) * channels + c];
+                                    *max = max.max(input[(y * width + x) * channels + c]);
+                                }
+                            }
+                        }
+                    }
+                }
+                output
+            }
+            (FloatLayer::Relu, _) => input.iter().map(|x| x.max(0.0)).collect(),
+            (FloatLayer::Flatten, _) => input.to_vec(),
+            _ => unreachable!("the shapes are checked when parsing"),
+        }
+    }
+
+    /// Returns the shape of the output of the layer, with placeholder weights.
+    fn output_shape(&self, shape: &[usize]) -> Option<Vec<usize>> {
+        let layer = match self {
+            FloatLayer::Dense { inputs, outputs, .. } => Layer::Dense(Dense {
+                inputs: *inputs,
+                outputs: *outputs,
+                weights: vec![0; outputs * kernels::packed_len(*inputs)],
+                bias: vec![0; *outputs],
+                requantize: Requantize::from_ratio(0.0),
+            }),
+            FloatLayer::Conv2d { conv, .. } => Layer::Conv2d(Conv2d {
+                weights: vec![
+                    0;
+                    conv.out_channels *
+                        conv.kernel[0] *
+                        conv.kernel[1] *
+                        kernels::packed_len(conv.in_channels)
+                ],
+                bias: vec![0; conv.out_channels],
+                ..conv.clone()
+            }),
+            FloatLayer::Relu => Layer::Relu,
+            FloatLayer::MaxPool2d(pool) => Layer::MaxPool2d(*pool),
+            FloatLayer::Flatten => Layer::Flatten,
+        };
+        layer.output_shape(shape)
+    }
+}
+
+/// A tensor of real values, from an initializer of the graph.
+#[derive(Debug, Clone, Default)]
+struct Tensor {
+    dims: Vec<usize>,
+    data: Vec<f32>,
+}
+
+/// An attribute of a node.
+#[derive(Debug, Clone, Default)]
+struct Attribute {
+    float: Option<f32>,
+    int: Option<i64>,
+    ints: Vec<i64>,
+}
+
+/// A node of the graph.
+#[derive(Debug, Clone, Default)]
+struct Node {
+    name: String,
+    op_type: String,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+    attributes: HashMap<String, Attribute>,
+}
+
+/// The parts of an ONNX graph used by the converter.
+#[derive(Debug, Default)]
+struct Graph {
+    nodes: Vec<Node>,
+    initializers: HashMap<String, Tensor>,
+    inputs: Vec<(String, Vec<Option<usize>>)>,
+}
+
+impl Graph {
+    /// Parses the graph of a serialized `ModelProto`.
+    fn parse(model: &[u8]) -> Result<Self, OnnxError> {
+        let mut graph = Graph::default();
+        for field in Fields::new(model) {
+            if let (7, Value::Bytes(bytes)) = field? {
+                graph.parse_graph(bytes)?;
+            }
+        }
+        Ok(graph)
+    }
+
+    /// Parses a `GraphProto`.
+    fn parse_graph(&mut self, bytes: &[u8]) -> Result<(), OnnxError> {
+        for field in Fields::new(bytes) {
+            match field? {
+                (1, Value::Bytes(bytes)) => self.nodes.push(parse_node(bytes)?),
+                (5, Value::Bytes(bytes)) => {
+                    let (name, tensor) = parse_tensor(bytes)?;
+                    self.initializers.insert(name, tensor);
+                }
+                (11, Value::Bytes(bytes)) => self.inputs.push(parse_value_info(bytes)?),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the shape of the input and the layers of a sequential graph.
+    fn layers(&self) -> Result<(Vec<usize>, Vec<FloatLayer>), OnnxError> {
+        // The input of the graph is the one which is not an initializer, without the batch.
+        let (input, dims) = self
+            .inputs
+            .iter()
+            .find(|(name, _)| !self.initializers.contains_key(name))
+            .ok_or(OnnxError::Input)?;
+        let mut current = input;
+        let dims =
+            dims.iter().skip(1).copied().collect::<Option<Vec<_>>>().ok_or(OnnxError::Input)?;
+        let input_shape = match dims[..] {
+            [channels, height, width] => vec![height, width, channels],
+            [features] => vec![features],
+            _ => return Err(OnnxError::Input),
+        };
+
+        let mut shape = input_shape.clone();
+        // The `[channels, height, width]` shape of a flattened image, whose features are in
+        // `[height, width, channels]` order in the quantized model.
+        let mut flattened_image = None;
+        let mut layers: Vec<FloatLayer> = Vec::new();
+        for node in &self.nodes {
+            let unsupported = |reason: &str| OnnxError::Unsupported {
+                name: node.name.clone(),
+                op_type: node.op_type.clone(),
+                reason: reason.to_string(),
+            };
+            if node.inputs.first() != Some(current) || node.outputs.len() != 1 {
+                return Err(unsupported("the graph is not sequential"));
+            }
+
+            let layer = match node.op_type.as_str() {
+                "Gemm" | "MatMul" => {
+                    let [features] = shape[..] else {
+                        return Err(unsupported("the input is not flat"));
+                    };
+                    let weights =
+                        self.initializer(node, 1).ok_or_else(|| unsupported("no weights"))?;
+                    let [rows, columns] = weights.dims[..] else {
+                        return Err(unsupported("the weights are not a matrix"));
+                    };
+                    let transposed = node.op_type == "Gemm" && node.int("transB", 0) == 1;
+                    if node.int("transA", 0) != 0 {
+                        return Err(unsupported("transA is not supported"));
+                    }
+                    let (inputs, outputs) =
+                        if transposed { (columns, rows) } else { (rows, columns) };
+                    if inputs != features {
+                        return Err(unsupported("the weights do not fit the input"));
+                    }
+
+                    // The weights are stored one row per output.
+                    let alpha = node.float("alpha", 1.0);
+                    let mut matrix = vec![0.0; inputs * outputs];
+                    for o in 0..outputs {
+                        for i in 0..inputs {
+                            let w = if transposed {
+                                weights.data[o * inputs + i]
+                            } else {
+                                weights.data[i * outputs + o]
+                            };
+                            matrix[o * inputs + flattened_index(flattened_image, i)] = alpha * w;
+                        }
+                    }
+                    let bias = match self.initializer(node, 2) {
+                        Some(bias) => broadcast(&bias.data, outputs, node.float("beta", 1.0))
+                            .ok_or_else(|| unsupported("the bias does not fit the output"))?,
+                        None => vec![0.0; outputs],
+                    };
+                    FloatLayer::Dense { inputs, outputs, weights: matrix, bias }
+                }
+                "Add" => {
+                    let Some(FloatLayer::Dense { outputs, bias, .. }) = layers.last_mut() else {
+                        return Err(unsupported("only a bias after a matrix product is supported"));
+                    };
+                    let addend = self
+                        .initializer(node, 1)
+                        .and_then(|addend| broadcast(&addend.data, *outputs, 1.0))
+                        .ok_or_else(|| unsupported("the addend is not a bias"))?;
+                    for (b, a) in bias.iter_mut().zip(addend) {
+                        *b += a;
+                    }
+                    current = &node.outputs[0];
+                    continue;
+                }
+                "Conv" => {
+                    let [_, _, in_channels] = shape[..] else {
+                        return Err(unsupported("the input is not an image"));
+                    };
+                    let weights =
+                        self.initializer(node, 1).ok_or_else(|| unsupported("no weights"))?;
+                    let [out_channels, weight_channels, kernel_height, kernel_width] =
+                        weights.dims[..]
+                    else {
+                        return Err(unsupported("the weights are not a 2d kernel"));
+                    };
+                    if weight_channels != in_channels || node.int("group", 1) != 1 {
+                        return Err(unsupported("groups are not supported"));
+                    }
+                    if node.ints("dilations").iter().any(|&d| d != 1) {
+                        return Err(unsupported("dilations are not supported"));
+                    }
+                    let padding = match node.ints("pads")[..] {
+                        [] => [0, 0],
+                        [top, left, bottom, right] if top == bottom && left == right => {
+                            [top as usize, left as usize]
+                        }
+                        _ => return Err(unsupported("the padding is not symmetric")),
+                    };
+
+                    // Reorder the weights to `[out, kernel height, kernel width, in]`.
+                    let mut reordered = vec![0.0; weights.data.len()];
+                    for o in 0..out_channels {
+                        for c in 0..in_channels {
+                            for ky in 0..kernel_height {
+                                for kx in 0..kernel_width {
+                                    let tap = (o * kernel_height + ky) * kernel_width + kx;
+                                    let onnx_tap =
+                                        ((o * in_channels + c) * kernel_height + ky) * kernel_width;
+                                    reordered[tap * in_channels + c] = weights.data[onnx_tap + kx];
+                                }
+                            }
+                        }
+                    }
+                    let bias = match self.initializer(node, 2) {
+                        Some(bias) if bias.data.len() == out_channels => bias.data.clone(),
+                        Some(_) => return Err(unsupported("the bias does not fit the output")),
+                        None => vec![0.0; out_channels],
+                    };
+                    let conv = Conv2d {
+                        in_channels,
+                        out_channels,
+                        kernel: [kernel_height, kernel_width],
+                        stride: strides(node),
+                        padding,
+                        weights: Vec::new(),
+                        bias: Vec::new(),
+                        requantize: Requantize::from_ratio(0.0),
+                    };
+                    FloatLayer::Conv2d { conv, weights: reordered, bias }
+                }
+                "Relu" => FloatLayer::Relu,
+                "MaxPool" => {
+                    if node.ints("pads").iter().any(|&p| p != 0) {
+                        return Err(unsupported("padding is not supported"));
+                    }
+                    let [kernel_height, kernel_width] = node.ints("kernel_shape")[..] else {
+                        return Err(unsupported("the kernel is not 2d"));
+                    };
+                    FloatLayer::MaxPool2d(MaxPool2d {
+                        kernel: [kernel_height as usize, kernel_width as usize],
+                        stride: strides(node),
+                    })
+                }
+                "Flatten" | "Reshape" => {
+                    if let [height, width, channels] = shape[..] {
+                        flattened_image = Some([channels, height, width]);
+                    }
+                    FloatLayer::Flatten
+                }
+                "Dropout" | "Identity" => {
+                    current = &node.outputs[0];
+                    continue;
+                }
+                _ => return Err(unsupported("the operator is not supported")),
+            };
+
+            shape = layer
+                .output_shape(&shape)
+                .ok_or_else(|| unsupported("the layer does not fit the shape of its input"))?;
+            if !matches!(layer, FloatLayer::Flatten | FloatLayer::Relu) {
+                flattened_image = flattened_image.filter(|_| matches!(layer, FloatLayer::Flatten));
+            }
+            layers.push(layer);
+            current = &node.outputs[0];
+        }
+
+        Ok((input_shape, layers))
+    }
+
+    /// Returns the initializer of an input of a node.
+    fn initializer(&self, node: &Node, index: usize) -> Option<&Tensor> {
+        node.inputs.get(index).and_then(|name| self.initializers.get(name))
+    }
+}
+
+impl Node {
+    fn int(&self, name: &str, default: i64) -> i64 {
+        self.attributes.get(name).and_then(|a| a.int).unwrap_or(default)
+    }
+
+    fn float(&self, name: &str, default: f32) -> f32 {
+        self.attributes.get(name).and_then(|a| a.float).unwrap_or(default)
+    }
+
+    fn ints(&self, name: &str) -> Vec<i64> {
+        self.attributes.get(name).map(|a| a.ints.clone()).unwrap_or_default()
+    }
+}
+
+/// Returns the strides of a node, which default to one.
+fn strides(node: &Node) -> [usize; 2] {
+    match node.ints("strides")[..] {
+        [stride_y, stride_x] => [stride_y as usize, stride_x as usize],
+        _ => [1, 1],
+    }
+}
+
+/// Maps the index of a feature of a flattened `[channels, height, width]` image to its index in
+/// `[height, width, channels]` order.
+fn flattened_index(image: Option<[usize; 3]>, index: usize) -> usize {
+    match image {
+        Some([channels, height, width]) => {
+            let (c, y, x) = (index / (height * width), index / width % height, index % width);
+            (y * width + x) * channels + c
+        }
+        None => index,
+    }
+}
+
+/// Broadcasts a bias of one or `len` values to `len` values, scaled by `scale`.
+fn broadcast(bias: &[f32], len: usize, scale: f32) -> Option<Vec<f32>> {
+    match bias.len() {
+        1 => Some(vec![bias[0] * scale; len]),
+        n if n == len => Some(bias.iter().map(|b| b * scale).collect()),
+        _ => None,
+    }
+}
+
+/// Parses a `NodeProto`.
+fn parse_node(bytes: &[u8]) -> Result<Node, OnnxError> {
+    let mut node = Node::default();
+    for field in Fields::new(bytes) {
+        match field? {
+            (1, Value::Bytes(input)) => node.inputs.push(string(input)?),
+            (2, Value::Bytes(output)) => node.outputs.push(string(output)?),
+            (3, Value::Bytes(name)) => node.name = string(name)?,
+            (4, Value::Bytes(op_type)) => node.op_type = string(op_type)?,
+            (5, Value::Bytes(attribute)) => {
+                let (name, attribute) = parse_attribute(attribute)?;
+                node.attributes.insert(name, attribute);
+            }
+            _ => {}
+        }
+    }
+    Ok(node)
+}
+
+/// Parses an `AttributeProto`.
+fn parse_attribute(bytes: &[u8]) -> Result<(String, Attribute), OnnxError> {
+    let mut name = String::new();
+    let mut attribute = Attribute::default();
+    for field in Fields::new(bytes) {
+        match field? {
+            (1, Value::Bytes(bytes)) => name = string(bytes)?,
+            (2, Value::Fixed32(bits)) => attribute.float = Some(f32::from_bits(bits)),
+            (3, Value::Varint(int)) => attribute.int = Some(int as i64),
+            (8, Value::Varint(int)) => attribute.ints.push(int as i64),
+            (8, Value::Bytes(packed)) => attribute.ints.extend(varints(packed)?),
+            _ => {}
+        }
+    }
+    Ok((name, attribute))
+}
+
+/// Parses a `TensorProto` of floats or integers, which are converted to floats.
+fn parse_tensor(bytes: &[u8]) -> Result<(String, Tensor), OnnxError> {
+    const FLOAT: u64 = 1;
+    const INT64: u64 = 7;
+
+    let mut name = String::new();
+    let mut tensor = Tensor::default();
+    let mut data_type = FLOAT;
+    let mut raw = None;
+    for field in Fields::new(bytes) {
+        match field? {
+            (1, Value::Varint(dim)) => tensor.dims.push(dim as usize),
+            (1, Value::Bytes(packed)) => {
+                tensor.dims.extend(varints(packed)?.into_iter().map(|dim| dim as usize));
+            }
+            (2, Value::Varint(value)) => data_type = value,
+            (4, Value::Fixed32(bits)) => tensor.data.push(f32::from_bits(bits)),
+            (4, Value::Bytes(packed)) => tensor
+                .data
+                .extend(packed.chunks_exact(4).map(|b| f32::from_le_bytes(b.try_into().unwrap()))),
+            (7, Value::Varint(int)) => tensor.data.push(int as i64 as f32),
+            (7, Value::Bytes(packed)) => {
+                tensor.data.extend(varints(packed)?.into_iter().map(|int| int as f32));
+            }
+            (8, Value::Bytes(bytes)) => name = string(bytes)?,
+            (9, Value::Bytes(bytes)) => raw = Some(bytes),
+            _ => {}
+        }
+    }
+
+    if let Some(raw) = raw {
+        tensor.data = match data_type {
+            FLOAT => {
+                raw.chunks_exact(4).map(|b| f32::from_le_bytes(b.try_into().unwrap())).collect()
+            }
+            INT64 => raw
+                .chunks_exact(8)
+                .map(|b| i64::from_le_bytes(b.try_into().unwrap()) as f32)
+                .collect(),
+            _ => return Err(OnnxError::Protobuf("unsupported tensor data type")),
+        };
+    }
+    Ok((name, tensor))
+}
+
+/// Parses a `ValueInfoProto` of a tensor, returning its name and dimensions.
+fn parse_value_info(bytes: &[u8]) -> Result<(String, Vec<Option<usize>>), OnnxError> {
+    let mut name = String::new();
+    let mut dims = Vec::new();
+    for field in Fields::new(bytes) {
+        match field? {
+            (1, Value::Bytes(bytes)) => name = string(bytes)?,
+            // TypeProto.tensor_type.shape.dim.dim_value
+            (2, Value::Bytes(type_proto)) => {
+                for tensor_type in nested(type_proto, 1)? {
+                    for shape in nested(tensor_type, 2)? {
+                        for dim in nested(shape, 1)? {
+                            let mut value = None;
+                            for field in Fields::new(dim) {
+                                if let (1, Value::Varint(dim)) = field? {
+                                    value = Some(dim as usize);
+                                }
+                            }
+                            dims.push(value);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok((name, dims))
+}
+
+/// Returns the embedded messages of a field.
+fn nested(bytes: &[u8], number: u32) -> Result<Vec<&[u8]>, OnnxError> {
+    let mut messages = Vec::new();
+    for field in Fields::new(bytes) {
+        if let (n, Value::Bytes(message)) = field? {
+            if n == number {
+                messages.push(message);
+            }
+        }
+    }
+    Ok(messages)
+}
+
+fn string(bytes: &[u8]) -> Result<String, OnnxError> {
+    String::from_utf8(bytes.to_vec()).map_err(|_| OnnxError::Protobuf("invalid string"))
+}
+
+/// Decodes packed varints.
+fn varints(mut bytes: &[u8]) -> Result<Vec<i64>, OnnxError> {
+    let mut values = Vec::new();
+    while !bytes.is_empty() {
+        values.push(varint(&mut bytes)? as i64);
+    }
+    Ok(values)
+}
+
+fn varint(bytes: &mut &[u8]) -> Result<u64, OnnxError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = bytes.split_first().ok_or(OnnxError::Protobuf("truncated varint"))?;
+        *bytes = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(OnnxError::Protobuf("varint too long"))
+}
+
+/// A value of a protobuf field.
+enum Value<'a> {
+    Varint(u64),
+    Fixed64,
+    Bytes(&'a [u8]),
+    Fixed32(u32),
+}
+
+/// An iterator over the fields of a protobuf message.
+struct Fields<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Fields<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn next_field(&mut self) -> Result<(u32, Value<'a>), OnnxError> {
+        let key = varint(&mut self.bytes)?;
+        let value = match key & 7 {
+            0 => Value::Varint(varint(&mut self.bytes)?),
+            1 => {
+                take(&mut self.bytes, 8)?;
+                Value::Fixed64
+            }
+            2 => {
+                let len = varint(&mut self.bytes)? as usize;
+                Value::Bytes(take(&mut self.bytes, len)?)
+            }
+            5 => Value::Fixed32(u32::from_le_bytes(take(&mut self.bytes, 4)?.try_into().unwrap())),
+            _ => return Err(OnnxError::Protobuf("unsupported wire type")),
+        };
+        Ok(((key >> 3) as u32, value))
+    }
+}
+
+/// Splits the first `len` bytes off a buffer.
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], OnnxError> {
+    if bytes.len() < len {
+        return Err(OnnxError::Protobuf("truncated field"));
+    }
+    let (value, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Ok(value)
+}
+
+impl<'a> Iterator for Fields<'a> {
+    type Item = Result<(u32, Value<'a>), OnnxError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.bytes.is_empty() {
+            return None;
+        }
+        let field = self.next_field();
+        if field.is_err() {
+            self.bytes = &[];
+        }
+        Some(field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infer;
+
+    /// Encodes the fields of a protobuf message.
+    #[derive(Default)]
+    struct Message(Vec<u8>);
+
+    impl Message {
+        fn varint(mut self, number: u64, value: u64) -> Self {
+            encode_varint(&mut self.0, number << 3);
+            encode_varint(&mut self.0, value);
+            self
+        }
+
+        fn bytes(mut self, number: u64, value: &[u8]) -> Self {
+            encode_varint(&mut self.0, number << 3 | 2);
+            encode_varint(&mut self.0, value.len() as u64);
+            self.0.extend(value);
+            self
+        }
+
+        fn message(self, number: u64, value: Message) -> Self {
+            self.bytes(number, &value.0)
+        }
+    }
+
+    fn encode_varint(bytes: &mut Vec<u8>, mut value: u64) {
+        while value >= 0x80 {
+            bytes.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        bytes.push(value as u8);
+    }
+
+    fn node(op_type: &str, inputs: &[&str], output: &str) -> Message {
+        let node =
+            inputs.iter().fold(Message::default(), |node, input| node.bytes(1, input.as_bytes()));
+        node.bytes(2, output.as_bytes()).bytes(3, output.as_bytes()).bytes(4, op_type.as_bytes())
+    }
+
+    fn ints(node: Message, name: &str, values: &[u64]) -> Message {
+        let attribute = Message::default().bytes(1, name.as_bytes());
+        let attribute = values.iter().fold(attribute, |attribute, &v| attribute.varint(8, v));
+        node.message(5, attribute)
+    }
+
+    fn tensor(name: &str, dims: &[u64], data: &[f32]) -> Message {
+        let packed = data.iter().flat_map(|x| x.to_le_bytes()).collect::<Vec<_>>();
+        dims.iter()
+            .fold(Message::default(), |tensor, &dim| tensor.varint(1, dim))
+            .bytes(4, &packed)
+            .bytes(8, name.as_bytes())
+    }
+
+    fn input(name: &str, dims: &[u64]) -> Message {
+        let shape = dims.iter().fold(Message::default(), |shape, &dim| {
+            shape.message(1, Message::default().varint(1, dim))
+        });
+        let tensor_type = Message::default().message(2, shape);
+        Message::default()
+            .bytes(1, name.as_bytes())
+            .message(2, Message::default().message(1, tensor_type))
+    }
+
+    fn model(nodes: Vec<Message>, initializers: Vec<Message>, input_dims: &[u64]) -> Vec<u8> {
+        let mut graph = Message::default();
+        for node in nodes {
+            graph = graph.message(1, node);
+        }
+        for initializer in initializers {
+            graph = graph.message(5, initializer);
+        }
+        graph = graph.message(11, input("x", input_dims));
+        Message::default().message(7, graph).0
+    }
+
+    /// A `Gemm` computing `x0 - x1` and `x1 - x0`, followed by a `Relu`.
+    fn gemm_relu() -> Vec<u8> {
+        let gemm = node("Gemm", &["x", "w", "b"], "h");
+        let gemm = gemm.message(5, Message::default().bytes(1, b"transB").varint(3, 1));
+        model(
+            vec![gemm, node("Relu", &["h"], "y")],
+            vec![tensor("w", &[2, 2], &[1.0, -1.0, -1.0, 1.0]), tensor("b", &[2], &[0.0, 0.0])],
+            &[1, 2],
+        )
+    }
+
+    fn assert_close(output: &[f32], expected: &[f32], tolerance: f32) {
+        assert_eq!(output.len(), expected.len());
+        for (x, y) in output.iter().zip(expected) {
+            assert!((x - y).abs() <= tolerance, "{output:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn test_convert_gemm() {
+        let model = convert(&gemm_relu(), &[vec![1.0, -1.0], vec![0.5, 0.25]]).unwrap();
+        assert_eq!(model.input_shape, vec![2]);
+        assert!(matches!(model.layers[..], [Layer::Dense(_), Layer::Relu]));
+
+        for (input, expected) in [([1.0, -1.0], [2.0, 0.0]), ([0.25, 0.5], [0.0, 0.25])] {
+            let output = infer(&model, model.quantize_input(&input)).unwrap();
+            assert_close(&model.dequantize_output(&output), &expected, 0.02);
+        }
+    }
+
+    #[test]
+    fn test_convert_conv() {
+        // A 3x3 image of one channel, convolved to two channels with a 2x2 kernel, flattened in
+        // `[channels, height, width]` order by ONNX and reduced to one output.
+        let conv_weights = [1.0, 0.0, 0.0, -1.0, 0.5, 0.5, 0.5, 0.5];
+        let dense_weights = [1.0, -1.0, 0.5, 0.0, 0.0, 0.0, 0.0, 0.25];
+        let onnx = model(
+            vec![
+                node("Conv", &["x", "k"], "c"),
+                node("Flatten", &["c"], "f"),
+                node("MatMul", &["f", "w"], "y"),
+            ],
+            vec![tensor("k", &[2, 1, 2, 2], &conv_weights), tensor("w", &[8, 1], &dense_weights)],
+            &[1, 1, 3, 3],
+        );
+
+        // The same graph on an image in `[channels, height, width]` order.
+        let reference = |image: &[f32]| {
+            let mut features = Vec::new();
+            for filter in conv_weights.chunks_exact(4) {
+                for y in 0..2 {
+                    for x in 0..2 {
+                        let taps = [(0, 0), (0, 1), (1, 0), (1, 1)];
+                        features.push(
+                            taps.iter()
+                                .zip(filter)
+                                .map(|(&(ky, kx), w)| w * image[(y + ky) * 3 + x + kx])
+                                .sum::<f32>(),
+                        );
+                    }
+                }
+            }
+            features.iter().zip(dense_weights).map(|(f, w)| f * w).sum::<f32>()
+        };
+
+        let images = [
+            [0.0, 0.5, 1.0, -1.0, 0.25, 0.75, 0.5, -0.5, 0.0],
+            [1.0, -1.0, 1.0, -1.0, 1.0, -1.0, 1.0, -1.0, 1.0],
+            [0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9],
+        ];
+        let model = convert(&onnx, &images.map(|image| image.to_vec())).unwrap();
+        assert_eq!(model.input_shape, vec![3, 3, 1]);
+        for image in images {
+            let output = infer(&model, model.quantize_input(&image)).unwrap();
+            assert_close(&model.dequantize_output(&output), &[reference(&image)], 0.05);
+        }
+    }
+
+    #[test]
+    fn test_convert_malformed() {
+        let onnx = gemm_relu();
+        let calibration = [vec![1.0, -1.0]];
+
+        // Every truncation of the model cuts the graph short.
+        for len in 1..onnx.len() {
+            assert!(
+                matches!(convert(&onnx[..len], &calibration), Err(OnnxError::Protobuf(_))),
+                "len {len}"
+            );
+        }
+        assert!(matches!(convert(&[0xff; 16], &calibration), Err(OnnxError::Protobuf(_))));
+        assert!(matches!(convert(&[0x0b], &calibration), Err(OnnxError::Protobuf(_))));
+
+        assert!(matches!(convert(&[], &calibration), Err(OnnxError::Input)));
+        assert!(matches!(convert(&onnx, &[]), Err(OnnxError::Calibration(2))));
+        assert!(matches!(convert(&onnx, &[vec![1.0]]), Err(OnnxError::Calibration(2))));
+    }
+
+    #[test]
+    fn test_convert_unsupported() {
+        let unsupported = |nodes, initializers, dims: &[u64]| {
+            match convert(&model(nodes, initializers, dims), &[vec![0.0; 4]]) {
+                Err(OnnxError::Unsupported { reason, .. }) => reason,
+                result => panic!("expected an unsupported node, got {result:?}"),
+            }
+        };
+
+        let reason = unsupported(vec![node("Softmax", &["x"], "y")], vec![], &[1, 4]);
+        assert_eq!(reason, "the operator is not supported");
+
+        let nodes = vec![node("Relu", &["x"], "h"), node("Relu", &["x"], "y")];
+        assert_eq!(unsupported(nodes, vec![], &[1, 4]), "the graph is not sequential");
+
+        let gemm = node("Gemm", &["x", "w"], "y");
+        let weights = vec![tensor("w", &[3, 2], &[0.0; 6])];
+        assert_eq!(unsupported(vec![gemm], weights, &[1, 4]), "the weights do not fit the input");
+
+        let pool = ints(node("MaxPool", &["x"], "y"), "kernel_shape", &[2, 2]);
+        let pool = ints(pool, "pads", &[1, 1, 1, 1]);
+        assert_eq!(unsupported(vec![pool], vec![], &[1, 1, 2, 2]), "padding is not supported");
+    }
+}
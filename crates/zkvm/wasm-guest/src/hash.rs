@@ -0,0 +1,90 @@
+//! The hash functions exposed to Wasm programs.
+//!
+//! Inside the zkVM they run on the SHA-256 and Keccak precompiles, elsewhere on the software
+//! implementations.
+
+/// Computes the SHA-256 digest of the data.
+#[cfg(target_os = "zkvm")]
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    use monerochan_lib::{syscall_sha256_compress, syscall_sha256_extend};
+
+    let mut state: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+
+    let mut padded = data.to_vec();
+    padded.push(0x80);
+    while padded.len() % 64 != 56 {
+        padded.push(0);
+    }
+    padded.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+
+    for block in padded.chunks_exact(64) {
+        let mut w = [0u32; 64];
+        for (word, bytes) in w.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_be_bytes(bytes.try_into().unwrap());
+        }
+        unsafe {
+            syscall_sha256_extend(&mut w);
+            syscall_sha256_compress(&mut w, &mut state);
+        }
+    }
+
+    let mut digest = [0u8; 32];
+    for (bytes, word) in digest.chunks_exact_mut(4).zip(state) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+/// Computes the Keccak-256 digest of the data.
+#[cfg(target_os = "zkvm")]
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    use monerochan_lib::syscall_keccak_permute;
+
+    const RATE: usize = 136;
+
+    let mut padded = data.to_vec();
+    padded.push(0x01);
+    while padded.len() % RATE != 0 {
+        padded.push(0);
+    }
+    *padded.last_mut().unwrap() |= 0x80;
+
+    let mut state = [0u64; 25];
+    for block in padded.chunks_exact(RATE) {
+        for (lane, bytes) in state.iter_mut().zip(block.chunks_exact(8)) {
+            *lane ^= u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+        unsafe {
+            syscall_keccak_permute(&mut state);
+        }
+    }
+
+    let mut digest = [0u8; 32];
+    for (bytes, lane) in digest.chunks_exact_mut(8).zip(state) {
+        bytes.copy_from_slice(&lane.to_le_bytes());
+    }
+    digest
+}
+
+/// Computes the SHA-256 digest of the data.
+#[cfg(not(target_os = "zkvm"))]
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    use sha2::{Digest, Sha256};
+
+    Sha256::digest(data).into()
+}
+
+/// Computes the Keccak-256 digest of the data.
+#[cfg(not(target_os = "zkvm"))]
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    use tiny_keccak::{Hasher, Keccak};
+
+    let mut keccak = Keccak::v256();
+    keccak.update(data);
+    let mut digest = [0u8; 32];
+    keccak.finalize(&mut digest);
+    digest
+}
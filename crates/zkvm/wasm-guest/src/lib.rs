@@ -0,0 +1,166 @@
+//! A guest library for proving WebAssembly programs with MONEROCHAN.
+//!
+//! The library embeds a Wasm interpreter, so a single guest program can prove any Wasm module:
+//!
+//! ```ignore
+//! #![no_main]
+//! monerochan_runtime::entrypoint!(main);
+//!
+//! pub fn main() {
+//!     monerochan_wasm_guest::run();
+//! }
+//! ```
+//!
+//! The guest reads the Wasm module and its input from the stdin, calls the `main` function exported
+//! by the module, then commits the SHA-256 digest of the module followed by the output of the
+//! module, framed with `monerochan_lib::io::commit_framed`. The host builds the stdin and decodes
+//! the public values with the `monerochan::wasm` module.
+//!
+//! # Wasm ABI
+//!
+//! The module must export its linear memory as `memory` and a `main` function without parameters
+//! and results. It may import the following functions from the `monerochan` module:
+//!
+//! - `input_len() -> i32`: the length of the input.
+//! - `read_input(ptr: i32)`: copies the input to the memory at `ptr`.
+//! - `commit(ptr: i32, len: i32)`: appends `len` bytes at `ptr` to the output.
+//! - `sha256(ptr: i32, len: i32, out: i32)`: writes the SHA-256 digest of `len` bytes at `ptr` to
+//!   the 32 bytes at `out`.
+//! - `keccak256(ptr: i32, len: i32, out: i32)`: writes the Keccak-256 digest of `len` bytes at
+//!   `ptr` to the 32 bytes at `out`.
+//!
+//! The hash functions run on the MONEROCHAN precompiles, so they are much cheaper than hashing in
+//! Wasm.
+
+pub mod hash;
+
+use monerochan_lib::io;
+use thiserror::Error;
+use wasmi::{core::Trap, Caller, Engine, Extern, Linker, Memory, Module, Store};
+
+/// The module the host functions are imported from.
+pub const HOST_MODULE: &str = "monerochan";
+
+/// The function called by the guest.
+pub const ENTRYPOINT: &str = "main";
+
+/// Errors that can occur when running a Wasm module.
+#[derive(Debug, Error)]
+pub enum WasmError {
+    /// The module is not valid Wasm.
+    #[error("invalid wasm module: {0}")]
+    Module(wasmi::Error),
+
+    /// The module could not be instantiated, e.g. because it imports an unknown function.
+    #[error("failed to instantiate the wasm module: {0}")]
+    Instantiate(wasmi::Error),
+
+    /// The module does not export the entrypoint.
+    #[error("the wasm module does not export a `{ENTRYPOINT}` function without parameters: {0}")]
+    Entrypoint(wasmi::Error),
+
+    /// The module trapped while running.
+    #[error("the wasm module trapped: {0}")]
+    Trap(wasmi::Error),
+}
+
+/// The state shared with the host functions.
+struct HostState {
+    input: Vec<u8>,
+    output: Vec<u8>,
+}
+
+/// Reads a Wasm module and its input from the stdin, runs the module and commits its digest and
+/// output.
+///
+/// # Panics
+/// Panics if the module fails to run, so no proof can be generated for it.
+pub fn run() {
+    let wasm = io::read_vec();
+    let input = io::read_vec();
+
+    let output = execute(&wasm, input).unwrap_or_else(|e| panic!("{e}"));
+
+    io::commit_slice(&hash::sha256(&wasm));
+    io::commit_framed(&output);
+}
+
+/// Runs the `main` function of a Wasm module on the given input, and returns its output.
+pub fn execute(wasm: &[u8], input: Vec<u8>) -> Result<Vec<u8>, WasmError> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, wasm).map_err(WasmError::Module)?;
+    let mut store = Store::new(&engine, HostState { input, output: Vec::new() });
+
+    let mut linker = <Linker<HostState>>::new(&engine);
+    let instance = register_host_functions(&mut linker)
+        .and_then(|()| linker.instantiate(&mut store, &module))
+        .and_then(|instance| instance.start(&mut store))
+        .map_err(WasmError::Instantiate)?;
+    let main =
+        instance.get_typed_func::<(), ()>(&store, ENTRYPOINT).map_err(WasmError::Entrypoint)?;
+    main.call(&mut store, ()).map_err(WasmError::Trap)?;
+
+    Ok(store.into_data().output)
+}
+
+fn register_host_functions(linker: &mut Linker<HostState>) -> Result<(), wasmi::Error> {
+    linker.func_wrap(HOST_MODULE, "input_len", |caller: Caller<'_, HostState>| -> i32 {
+        caller.data().input.len() as i32
+    })?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "read_input",
+        |mut caller: Caller<'_, HostState>, ptr: i32| -> Result<(), Trap> {
+            let input = std::mem::take(&mut caller.data_mut().input);
+            let result = write_bytes(&mut caller, ptr, &input);
+            caller.data_mut().input = input;
+            result
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "commit",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> Result<(), Trap> {
+            let bytes = read_bytes(&caller, ptr, len)?;
+            caller.data_mut().output.extend_from_slice(&bytes);
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "sha256",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32, out: i32| -> Result<(), Trap> {
+            let digest = hash::sha256(&read_bytes(&caller, ptr, len)?);
+            write_bytes(&mut caller, out, &digest)
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "keccak256",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32, out: i32| -> Result<(), Trap> {
+            let digest = hash::keccak256(&read_bytes(&caller, ptr, len)?);
+            write_bytes(&mut caller, out, &digest)
+        },
+    )?;
+    Ok(())
+}
+
+/// The linear memory exported by the module.
+fn memory(caller: &Caller<'_, HostState>) -> Result<Memory, Trap> {
+    caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| Trap::new("the wasm module does not export its memory"))
+}
+
+fn read_bytes(caller: &Caller<'_, HostState>, ptr: i32, len: i32) -> Result<Vec<u8>, Trap> {
+    let mut bytes = vec![0; len as u32 as usize];
+    memory(caller)?
+        .read(caller, ptr as u32 as usize, &mut bytes)
+        .map_err(|e| Trap::new(e.to_string()))?;
+    Ok(bytes)
+}
+
+fn write_bytes(caller: &mut Caller<'_, HostState>, ptr: i32, bytes: &[u8]) -> Result<(), Trap> {
+    memory(caller)?.write(caller, ptr as u32 as usize, bytes).map_err(|e| Trap::new(e.to_string()))
+}
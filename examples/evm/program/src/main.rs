@@ -0,0 +1,8 @@
+//! A program that executes an Ethereum block and commits the outcome of its transactions.
+
+#![no_main]
+monerochan_runtime::entrypoint!(main);
+
+pub fn main() {
+    monerochan_evm::run();
+}
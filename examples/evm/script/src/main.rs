@@ -0,0 +1,46 @@
+//! Executes an Ethereum block in the zkVM.
+//!
+//! The witness of the block is fetched from the JSON-RPC endpoint at `RPC_URL`, which must serve
+//! `eth_getProof` for the parent of the block:
+//!
+//! ```sh
+//! RPC_URL=https://... cargo run --release -- 20000000
+//! ```
+
+use monerochan::{include_elf, utils, ProverClient, MONEROCHANStdin};
+use monerochan_evm::{EvmInputBuilder, EvmOutput};
+
+/// The ELF we want to execute inside the zkVM.
+const ELF: &[u8] = include_elf!("evm-program");
+
+#[tokio::main]
+async fn main() {
+    // Setup logging.
+    utils::setup_logger();
+
+    let rpc_url = std::env::var("RPC_URL").expect("RPC_URL must be set");
+    let number = std::env::args()
+        .nth(1)
+        .expect("usage: evm-script <block number>")
+        .parse::<u64>()
+        .expect("invalid block number");
+
+    // Fetch the block and the state it accesses.
+    let builder = EvmInputBuilder::new(rpc_url);
+    let input = builder.build(number).await.expect("failed to build the input");
+    println!("fetched {} accounts and {} contracts", input.accounts.len(), input.codes.len());
+
+    let mut stdin = MONEROCHANStdin::new();
+    stdin.write(&input);
+
+    // Execute the block in the zkVM, without generating a proof.
+    let client = ProverClient::from_env();
+    let (mut public_values, report) = client.execute(ELF, &stdin).run().unwrap();
+    println!("executed block {number} with {} cycles", report.total_instruction_count());
+
+    let output = public_values.read::<EvmOutput>();
+    println!("block hash: {}", output.block_hash);
+    for tx in &output.transactions {
+        println!("{}: success={} gas_used={}", tx.hash, tx.success, tx.gas_used);
+    }
+}
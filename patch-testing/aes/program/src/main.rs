@@ -0,0 +1,45 @@
+#![no_main]
+monerochan_runtime::entrypoint!(main);
+
+use aes::cipher::{KeyIvInit, StreamCipher};
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm,
+};
+use monerochan_lib::aes::Aes;
+
+type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+/// Emits AES_ENCRYPT_ROUND syscalls.
+pub fn main() {
+    let times = monerochan_runtime::io::read::<usize>();
+
+    for _ in 0..times {
+        // Decrypt a TLS-like AES-256-GCM record with the patched `aes` crate.
+        let key = monerochan_runtime::io::read::<[u8; 32]>();
+        let nonce = monerochan_runtime::io::read::<[u8; 12]>();
+        let aad = monerochan_runtime::io::read_vec();
+        let ciphertext = monerochan_runtime::io::read_vec();
+
+        let cipher = Aes256Gcm::new(&key.into());
+        let plaintext = cipher.decrypt(&nonce.into(), Payload { msg: &ciphertext, aad: &aad }).ok();
+
+        // The GCM helpers must agree with the `aes-gcm` crate.
+        let (body, tag) = ciphertext.split_at(ciphertext.len() - 16);
+        let mut buffer = body.to_vec();
+        let valid =
+            Aes::new_256(&key).gcm_decrypt(&nonce, &aad, &mut buffer, tag.try_into().unwrap());
+        assert_eq!(plaintext, valid.then_some(buffer));
+
+        monerochan_runtime::io::commit(&plaintext);
+
+        // Encrypt with AES-128 in counter mode.
+        let key = monerochan_runtime::io::read::<[u8; 16]>();
+        let iv = monerochan_runtime::io::read::<[u8; 16]>();
+        let mut data = monerochan_runtime::io::read_vec();
+
+        Aes128Ctr::new(&key.into(), &iv.into()).apply_keystream(&mut data);
+
+        monerochan_runtime::io::commit(&data);
+    }
+}
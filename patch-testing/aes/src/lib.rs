@@ -0,0 +1,64 @@
+#[monerochan_test::monerochan_test("aes_patch_test", syscalls = [AES_ENCRYPT_ROUND], gpu, prove)]
+fn test_aes_gcm_and_ctr_random_messages_100(
+    stdin: &mut monerochan::MONEROCHANStdin,
+) -> impl FnOnce(monerochan::MONEROCHANPublicValues) {
+    use aes::cipher::{KeyIvInit, StreamCipher};
+    use aes_gcm::{
+        aead::{Aead, KeyInit, Payload},
+        Aes256Gcm,
+    };
+    use monerochan_test::{random_preimages_with_bounded_len, DEFAULT_CORPUS_COUNT};
+
+    type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+    let mut messages = random_preimages_with_bounded_len(DEFAULT_CORPUS_COUNT, 1000);
+    monerochan_test::add_hash_fn_edge_cases(&mut messages);
+
+    stdin.write(&messages.len());
+
+    let mut expected = Vec::with_capacity(messages.len());
+    for (i, message) in messages.into_iter().enumerate() {
+        let key = rand::random::<[u8; 32]>();
+        let nonce = rand::random::<[u8; 12]>();
+        let aad = rand::random::<[u8; 13]>()[..i % 14].to_vec();
+
+        let cipher = Aes256Gcm::new(&key.into());
+        let mut ciphertext =
+            cipher.encrypt(&nonce.into(), Payload { msg: &message, aad: &aad }).unwrap();
+
+        // Tamper with every other ciphertext, whose decryption must then fail.
+        let plaintext = if i % 2 == 0 {
+            Some(message.clone())
+        } else {
+            let last = ciphertext.len() - 1;
+            ciphertext[last] ^= 1;
+            None
+        };
+
+        stdin.write(&key);
+        stdin.write(&nonce);
+        stdin.write_vec(aad);
+        stdin.write_vec(ciphertext);
+
+        let key = rand::random::<[u8; 16]>();
+        let iv = rand::random::<[u8; 16]>();
+        let mut encrypted = message.clone();
+        Aes128Ctr::new(&key.into(), &iv.into()).apply_keystream(&mut encrypted);
+
+        stdin.write(&key);
+        stdin.write(&iv);
+        stdin.write_vec(message);
+
+        expected.push((plaintext, encrypted));
+    }
+
+    move |mut public| {
+        for (plaintext, encrypted) in expected {
+            let committed = public.read::<Option<Vec<u8>>>();
+            assert_eq!(plaintext, committed);
+
+            let committed = public.read::<Vec<u8>>();
+            assert_eq!(encrypted, committed);
+        }
+    }
+}
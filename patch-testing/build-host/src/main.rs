@@ -1,8 +1,10 @@
+pub use aes_patched;
 pub use bls12_381_patched;
 pub use crypto_bigint_patched;
 pub use curve25519_dalek_ng_patched;
 pub use curve25519_dalek_patched;
 pub use ecdsa_core_patched;
+pub use poly1305_patched;
 pub use rsa_patched;
 pub use secp256k1_patched;
 pub use sha2_v0_10_6_patched;
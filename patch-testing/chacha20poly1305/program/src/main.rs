@@ -0,0 +1,25 @@
+#![no_main]
+monerochan_runtime::entrypoint!(main);
+
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305,
+};
+
+/// Emits UINT256_MUL syscalls.
+pub fn main() {
+    let times = monerochan_runtime::io::read::<usize>();
+
+    for _ in 0..times {
+        let key = monerochan_runtime::io::read::<[u8; 32]>();
+        let nonce = monerochan_runtime::io::read::<[u8; 12]>();
+        let aad = monerochan_runtime::io::read_vec();
+        let ciphertext = monerochan_runtime::io::read_vec();
+
+        let cipher = ChaCha20Poly1305::new(&key.into());
+        let plaintext =
+            cipher.decrypt(&nonce.into(), Payload { msg: &ciphertext, aad: &aad }).ok();
+
+        monerochan_runtime::io::commit(&plaintext);
+    }
+}
@@ -0,0 +1,49 @@
+#[monerochan_test::monerochan_test("chacha20poly1305_patch_test", syscalls = [UINT256_MUL], gpu, prove)]
+fn test_decrypt_random_messages_100(
+    stdin: &mut monerochan::MONEROCHANStdin,
+) -> impl FnOnce(monerochan::MONEROCHANPublicValues) {
+    use chacha20poly1305::{
+        aead::{Aead, KeyInit, Payload},
+        ChaCha20Poly1305,
+    };
+    use monerochan_test::{random_preimages_with_bounded_len, DEFAULT_CORPUS_COUNT};
+
+    let mut messages = random_preimages_with_bounded_len(DEFAULT_CORPUS_COUNT, 1000);
+    monerochan_test::add_hash_fn_edge_cases(&mut messages);
+
+    stdin.write(&messages.len());
+
+    let mut expected = Vec::with_capacity(messages.len());
+    for (i, message) in messages.into_iter().enumerate() {
+        let key = rand::random::<[u8; 32]>();
+        let nonce = rand::random::<[u8; 12]>();
+        let aad = rand::random::<[u8; 20]>()[..i % 21].to_vec();
+
+        let cipher = ChaCha20Poly1305::new(&key.into());
+        let mut ciphertext =
+            cipher.encrypt(&nonce.into(), Payload { msg: &message, aad: &aad }).unwrap();
+
+        // Tamper with every other ciphertext, whose decryption must then fail.
+        let plaintext = if i % 2 == 0 {
+            Some(message)
+        } else {
+            let last = ciphertext.len() - 1;
+            ciphertext[last] ^= 1;
+            None
+        };
+
+        stdin.write(&key);
+        stdin.write(&nonce);
+        stdin.write_vec(aad);
+        stdin.write_vec(ciphertext);
+        expected.push(plaintext);
+    }
+
+    move |mut public| {
+        for plaintext in expected {
+            let committed = public.read::<Option<Vec<u8>>>();
+
+            assert_eq!(plaintext, committed);
+        }
+    }
+}
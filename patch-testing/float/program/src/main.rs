@@ -0,0 +1,17 @@
+#![no_main]
+monerochan_runtime::entrypoint!(main);
+
+/// Emits F64_ADD, F64_MUL and F64_DIV syscalls, through the soft-float intrinsics.
+pub fn main() {
+    let operands = monerochan_runtime::io::read::<Vec<(u64, u64)>>();
+
+    let results = operands
+        .into_iter()
+        .map(|(x, y)| {
+            let (x, y) = (f64::from_bits(x), f64::from_bits(y));
+            [(x + y).to_bits(), (x - y).to_bits(), (x * y).to_bits(), (x / y).to_bits()]
+        })
+        .collect::<Vec<_>>();
+
+    monerochan_runtime::io::commit(&results);
+}
@@ -0,0 +1,82 @@
+/// Returns random f64 operands, mostly normal with close exponents, along with the edge cases of
+/// the IEEE 754 arithmetic.
+#[cfg(test)]
+fn operands() -> Vec<(u64, u64)> {
+    use rand::Rng;
+
+    let mut rng = rand::thread_rng();
+    let mut operands = Vec::new();
+
+    // Normal operands whose exponents are close enough for the operations to be accelerated.
+    for _ in 0..monerochan_test::DEFAULT_CORPUS_COUNT {
+        let mut operand = || {
+            let exponent: u64 = rng.gen_range(1023 - 60..1023 + 60);
+            (rng.gen::<u64>() & !(0x7ff << 52)) | (exponent << 52)
+        };
+        operands.push((operand(), operand()));
+    }
+
+    // Arbitrary bit patterns, which cover subnormals, overflows and underflows.
+    for _ in 0..monerochan_test::DEFAULT_CORPUS_COUNT {
+        operands.push((rng.gen(), rng.gen()));
+    }
+
+    let edge_cases = [
+        0.0,
+        -0.0,
+        1.0,
+        -1.0,
+        1.0 + f64::EPSILON,
+        1.0 - f64::EPSILON / 2.0,
+        f64::EPSILON / 2.0,
+        f64::EPSILON / 4.0,
+        f64::MAX,
+        f64::MIN,
+        f64::MIN_POSITIVE,
+        f64::MIN_POSITIVE / 2.0,
+        f64::MIN_POSITIVE * (1.0 - f64::EPSILON),
+        f64::from_bits(1),
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::NAN,
+    ];
+    for x in edge_cases {
+        for y in edge_cases {
+            operands.push((x.to_bits(), y.to_bits()));
+        }
+    }
+
+    operands
+}
+
+#[monerochan_test::monerochan_test("float_patch_test", syscalls = [F64_ADD, F64_MUL, F64_DIV], gpu, prove)]
+fn test_f64_ops_against_host(
+    stdin: &mut monerochan::MONEROCHANStdin,
+) -> impl FnOnce(monerochan::MONEROCHANPublicValues) {
+    let operands = operands();
+    stdin.write(&operands);
+
+    let expected = operands
+        .iter()
+        .map(|&(x, y)| {
+            let (x, y) = (f64::from_bits(x), f64::from_bits(y));
+            [x + y, x - y, x * y, x / y]
+        })
+        .collect::<Vec<_>>();
+
+    move |mut public| {
+        let results = public.read::<Vec<[u64; 4]>>();
+        assert_eq!(results.len(), expected.len());
+
+        for ((result, expected), (x, y)) in results.into_iter().zip(expected).zip(operands) {
+            for (result, expected) in result.into_iter().zip(expected) {
+                // The payload of a NaN is not specified, only its NaN-ness is compared.
+                if expected.is_nan() {
+                    assert!(f64::from_bits(result).is_nan(), "{x:#x} {y:#x}");
+                } else {
+                    assert_eq!(result, expected.to_bits(), "{x:#x} {y:#x}");
+                }
+            }
+        }
+    }
+}
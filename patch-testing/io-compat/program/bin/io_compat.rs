@@ -0,0 +1,43 @@
+#![no_main]
+monerochan_runtime::entrypoint!(main);
+
+use monerochan_runtime::io;
+use serde::{Deserialize, Serialize};
+
+/// A value covering the serde data model, defined independently of the host so that a change to
+/// the encoding of the inputs or the public values breaks the round trip.
+#[derive(Serialize, Deserialize)]
+pub struct Record {
+    pub id: u64,
+    pub flag: bool,
+    pub name: String,
+    pub bytes: Vec<u8>,
+    pub pair: (u32, i64),
+    pub digest: [u8; 32],
+    pub parent: Option<u128>,
+    pub kind: Kind,
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum Kind {
+    Unit,
+    Tuple(u16, u8),
+    Struct { value: i32 },
+}
+
+/// Decodes the inputs written by the host with every stdin API, and commits them back with every
+/// public values API.
+pub fn main() {
+    let count = io::read::<u32>();
+    for _ in 0..count {
+        let record = io::read::<Record>();
+        let raw = io::read_vec();
+        io::commit(&record);
+        io::commit_framed(&raw);
+    }
+
+    let header = io::read_named::<Record>("header");
+    let payload = io::read_named_vec("payload");
+    io::commit(&header);
+    io::commit_slice(&payload);
+}
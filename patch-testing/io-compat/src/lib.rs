@@ -0,0 +1,192 @@
+//! Compatibility tests of the encoding of [`monerochan::MONEROCHANStdin`] and
+//! [`monerochan::MONEROCHANPublicValues`].
+//!
+//! The guest program decodes inputs written with every stdin API and commits them back with every
+//! public values API. To check that an SDK upgrade does not invalidate deployed guests, set
+//! `MONEROCHAN_IO_COMPAT_ELF` to the program built with an older version of the runtime crates:
+//! the inputs are written and the public values are read with the current SDK.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// The environment variable holding the path of a prebuilt guest program to test against.
+pub const IO_COMPAT_ELF_ENV: &str = "MONEROCHAN_IO_COMPAT_ELF";
+
+/// The value decoded and committed by the guest program, matching its own definition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Record {
+    pub id: u64,
+    pub flag: bool,
+    pub name: String,
+    pub bytes: Vec<u8>,
+    pub pair: (u32, i64),
+    pub digest: [u8; 32],
+    pub parent: Option<u128>,
+    pub kind: Kind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Kind {
+    Unit,
+    Tuple(u16, u8),
+    Struct { value: i32 },
+}
+
+impl Record {
+    /// Generates a random record, with strings and byte vectors of length at most `max_len`.
+    pub fn random(rng: &mut impl Rng, max_len: usize) -> Self {
+        let name_len = rng.gen_range(0..=max_len);
+        let bytes_len = rng.gen_range(0..=max_len);
+        Self {
+            id: rng.gen(),
+            flag: rng.gen(),
+            name: (0..name_len).map(|_| rng.gen::<char>()).collect(),
+            bytes: (0..bytes_len).map(|_| rng.gen()).collect(),
+            pair: rng.gen(),
+            digest: rng.gen(),
+            parent: rng.gen::<bool>().then(|| rng.gen()),
+            kind: match rng.gen_range(0..3) {
+                0 => Kind::Unit,
+                1 => Kind::Tuple(rng.gen(), rng.gen()),
+                _ => Kind::Struct { value: rng.gen() },
+            },
+        }
+    }
+}
+
+/// The inputs of the guest program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Corpus {
+    pub records: Vec<(Record, Vec<u8>)>,
+    pub header: Record,
+    pub payload: Vec<u8>,
+}
+
+impl Corpus {
+    /// Generates a random corpus of `count` records.
+    pub fn random(rng: &mut impl Rng, count: u8, max_len: usize) -> Self {
+        let records = (0..count)
+            .map(|_| {
+                let raw_len = rng.gen_range(0..=max_len);
+                (Record::random(rng, max_len), (0..raw_len).map(|_| rng.gen()).collect())
+            })
+            .collect();
+        let payload_len = rng.gen_range(0..=max_len);
+        Self {
+            records,
+            header: Record::random(rng, max_len),
+            payload: (0..payload_len).map(|_| rng.gen()).collect(),
+        }
+    }
+
+    /// Writes the corpus in the order the guest program reads it.
+    pub fn write(&self, stdin: &mut monerochan::MONEROCHANStdin) {
+        stdin.write(&u32::try_from(self.records.len()).unwrap());
+        for (record, raw) in &self.records {
+            stdin.write(record);
+            stdin.write_vec(raw.clone());
+        }
+        stdin.insert("header", &self.header);
+        stdin.insert_slice("payload", &self.payload);
+    }
+
+    /// Reads the public values committed by the guest program, and checks they match the corpus.
+    pub fn check(&self, public_values: &mut monerochan::MONEROCHANPublicValues) {
+        for (record, raw) in &self.records {
+            assert_eq!(public_values.read::<Record>(), *record);
+            assert_eq!(public_values.read_framed(), *raw);
+        }
+        assert_eq!(public_values.read::<Record>(), self.header);
+        let mut payload = vec![0; self.payload.len()];
+        public_values.read_slice(&mut payload);
+        assert_eq!(payload, self.payload);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use monerochan::{MONEROCHANPublicValues, MONEROCHANStdin};
+    use monerochan_test::{DEFAULT_CORPUS_COUNT, DEFAULT_CORPUS_MAX_LEN};
+
+    /// Loads the guest program from [`IO_COMPAT_ELF_ENV`] if set, or the one built from this tree.
+    fn elf() -> Vec<u8> {
+        match std::env::var(IO_COMPAT_ELF_ENV) {
+            Ok(path) => std::fs::read(&path)
+                .unwrap_or_else(|err| panic!("failed to read the guest program {path}: {err}")),
+            Err(_) => monerochan::include_elf!("io_compat").to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_stdin_encoding_is_stable() {
+        let mut stdin = MONEROCHANStdin::new();
+        stdin.write(&42u32);
+        stdin.write_slice(&[1, 2, 3]);
+        let expected = concat!(
+            "0200000000000000",
+            "0400000000000000",
+            "2a000000",
+            "0300000000000000",
+            "010203",
+            "0000000000000000",
+            "0000000000000000",
+        );
+        assert_eq!(encode_hex(&bincode::serialize(&stdin).unwrap()), expected);
+    }
+
+    #[test]
+    fn test_public_values_encoding_is_stable() {
+        let mut public_values = MONEROCHANPublicValues::new();
+        public_values.write(&42u32);
+        public_values.write_framed(b"ab");
+        let expected = concat!("0a00000000000000", "2a000000", "02000000", "6162");
+        assert_eq!(encode_hex(&bincode::serialize(&public_values).unwrap()), expected);
+    }
+
+    #[test]
+    fn test_round_trip_fuzz() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..DEFAULT_CORPUS_COUNT {
+            let corpus = Corpus::random(&mut rng, 4, DEFAULT_CORPUS_MAX_LEN);
+
+            // The stdin survives the encoding used to send it to the network.
+            let mut stdin = MONEROCHANStdin::new();
+            corpus.write(&mut stdin);
+            let decoded: MONEROCHANStdin =
+                bincode::deserialize(&bincode::serialize(&stdin).unwrap()).unwrap();
+            assert_eq!(decoded.buffer, stdin.buffer);
+
+            // The public values committed the way the guest commits them decode to the corpus.
+            let mut public_values = MONEROCHANPublicValues::new();
+            for (record, raw) in &corpus.records {
+                public_values.write(record);
+                public_values.write_framed(raw);
+            }
+            public_values.write(&corpus.header);
+            public_values.write_slice(&corpus.payload);
+            let mut decoded: MONEROCHANPublicValues =
+                bincode::deserialize(&bincode::serialize(&public_values).unwrap()).unwrap();
+            corpus.check(&mut decoded);
+        }
+    }
+
+    #[test]
+    fn test_guest_decodes_inputs() {
+        let _lock = monerochan_test::lock_serial();
+        let elf = elf();
+        let client = &*monerochan_test::MONEROCHAN_CPU_PROVER;
+
+        let mut rng = rand::thread_rng();
+        let corpus = Corpus::random(&mut rng, DEFAULT_CORPUS_COUNT, DEFAULT_CORPUS_MAX_LEN);
+        let mut stdin = MONEROCHANStdin::new();
+        corpus.write(&mut stdin);
+
+        let (mut public_values, _) = client.execute(&elf, &stdin).run().unwrap();
+        corpus.check(&mut public_values);
+    }
+
+    fn encode_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+}
@@ -0,0 +1,112 @@
+#![no_main]
+monerochan_runtime::entrypoint!(main);
+
+use core::ops::ControlFlow;
+
+use monerochan_json::{Event, Handler, Scalar};
+use monerochan_runtime::io;
+
+/// The fields of the response read by the guest.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Status,
+    Timestamp,
+    Data,
+    Trades,
+    Amount,
+    Side,
+    Other,
+}
+
+/// Summarizes the response in a single pass, without allocating.
+struct Summary {
+    depth: usize,
+    field: Field,
+    in_data: bool,
+    in_trades: bool,
+    status_ok: bool,
+    timestamp: u64,
+    amount: u64,
+    buy: bool,
+    trades: u64,
+    buy_volume: u64,
+}
+
+impl<'a> Handler<'a> for Summary {
+    fn event(&mut self, event: Event<'a>) -> ControlFlow<()> {
+        match event {
+            Event::ObjectStart | Event::ArrayStart => {
+                match (self.depth, self.field, event) {
+                    (1, Field::Data, Event::ObjectStart) => self.in_data = true,
+                    (2, Field::Trades, Event::ArrayStart) => self.in_trades = self.in_data,
+                    (3, _, Event::ObjectStart) if self.in_trades => {
+                        self.amount = 0;
+                        self.buy = false;
+                    }
+                    _ => {}
+                }
+                self.depth += 1;
+            }
+            Event::ObjectEnd | Event::ArrayEnd => {
+                self.depth -= 1;
+                match self.depth {
+                    3 if self.in_trades => {
+                        self.trades += 1;
+                        if self.buy {
+                            self.buy_volume += self.amount;
+                        }
+                    }
+                    2 => self.in_trades = false,
+                    1 => self.in_data = false,
+                    _ => {}
+                }
+            }
+            Event::Key(key) => {
+                let field = |name| key.eq_str(name);
+                self.field = match self.depth {
+                    1 if field("status") => Field::Status,
+                    1 if field("timestamp") => Field::Timestamp,
+                    1 if field("data") => Field::Data,
+                    2 if self.in_data && field("trades") => Field::Trades,
+                    4 if self.in_trades && field("amount") => Field::Amount,
+                    4 if self.in_trades && field("side") => Field::Side,
+                    _ => Field::Other,
+                };
+            }
+            Event::Scalar(scalar) => match (self.field, scalar) {
+                (Field::Status, Scalar::String(status)) => self.status_ok = status.eq_str("ok"),
+                (Field::Timestamp, Scalar::Number(timestamp)) => {
+                    self.timestamp = timestamp.as_u64().expect("invalid timestamp");
+                }
+                (Field::Amount, Scalar::Number(amount)) => {
+                    self.amount = amount.as_u64().expect("invalid amount");
+                }
+                (Field::Side, Scalar::String(side)) => self.buy = side.eq_str("buy"),
+                _ => {}
+            },
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+/// Verifies an API response with the `monerochan-json` push parser, and commits its summary.
+pub fn main() {
+    let body = io::read_vec();
+
+    let mut summary = Summary {
+        depth: 0,
+        field: Field::Other,
+        in_data: false,
+        in_trades: false,
+        status_ok: false,
+        timestamp: 0,
+        amount: 0,
+        buy: false,
+        trades: 0,
+        buy_volume: 0,
+    };
+    monerochan_json::parse(&body, &mut summary).expect("invalid response");
+    assert!(summary.status_ok, "the response is not ok");
+
+    io::commit(&(summary.timestamp, summary.trades, summary.buy_volume));
+}
@@ -0,0 +1,42 @@
+#![no_main]
+monerochan_runtime::entrypoint!(main);
+
+use monerochan_runtime::io;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Response {
+    status: String,
+    timestamp: u64,
+    data: Data,
+}
+
+#[derive(Deserialize)]
+struct Data {
+    trades: Vec<Trade>,
+}
+
+#[derive(Deserialize)]
+struct Trade {
+    amount: u64,
+    side: String,
+}
+
+/// Verifies an API response with `serde_json`, and commits its summary.
+pub fn main() {
+    let body = io::read_vec();
+
+    let response: Response = serde_json::from_slice(&body).expect("invalid response");
+    assert_eq!(response.status, "ok", "the response is not ok");
+
+    let trades = response.data.trades.len() as u64;
+    let buy_volume = response
+        .data
+        .trades
+        .iter()
+        .filter(|trade| trade.side == "buy")
+        .map(|trade| trade.amount)
+        .sum::<u64>();
+
+    io::commit(&(response.timestamp, trades, buy_volume));
+}
@@ -0,0 +1,99 @@
+/// The minimum ratio of the cycles of the `serde_json` guest to the ones of the push parser guest.
+#[cfg(all(test, not(any(feature = "prove", feature = "gpu"))))]
+const MIN_CYCLE_REDUCTION: u64 = 5;
+
+/// Returns a random API response, serialized, along with the summary committed by the guests:
+/// the timestamp, the number of trades and the total amount of the buy trades.
+#[cfg(test)]
+fn response(trades: usize) -> (Vec<u8>, (u64, u64, u64)) {
+    use rand::Rng;
+    use serde_json::json;
+
+    let mut rng = rand::thread_rng();
+    let timestamp = rng.gen_range(1_600_000_000..1_800_000_000u64);
+
+    let mut buy_volume = 0;
+    let trades = (0..trades)
+        .map(|id| {
+            let amount = rng.gen_range(1..1_000_000u64);
+            let buy = rng.gen_bool(0.5);
+            if buy {
+                buy_volume += amount;
+            }
+            json!({
+                "id": id,
+                "price": format!("{}.{:02}", rng.gen_range(100..200), rng.gen_range(0..100)),
+                "amount": amount,
+                "side": if buy { "buy" } else { "sell" },
+                "fee": { "rate": rng.gen::<f64>() / 100.0, "currency": "USD" },
+                "tags": ["spot", "maker"],
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let response = json!({
+        "status": "ok",
+        "timestamp": timestamp,
+        "data": {
+            "symbol": "XMR",
+            "venue": "Café \"Spot\"\n\u{1F980}",
+            "trades": trades,
+            "next": null,
+        },
+        "error": null,
+        "cached": false,
+    });
+
+    let count = trades.len() as u64;
+    (serde_json::to_vec_pretty(&response).unwrap(), (timestamp, count, buy_volume))
+}
+
+#[monerochan_test::monerochan_test("json_push", gpu, prove)]
+fn test_push_parser_summary(
+    stdin: &mut monerochan::MONEROCHANStdin,
+) -> impl FnOnce(monerochan::MONEROCHANPublicValues) {
+    let (body, expected) = response(monerochan_test::DEFAULT_CORPUS_COUNT.into());
+    stdin.write_vec(body);
+
+    move |mut public| {
+        assert_eq!(public.read::<(u64, u64, u64)>(), expected);
+    }
+}
+
+#[monerochan_test::monerochan_test("json_serde", gpu, prove)]
+fn test_serde_json_summary(
+    stdin: &mut monerochan::MONEROCHANStdin,
+) -> impl FnOnce(monerochan::MONEROCHANPublicValues) {
+    let (body, expected) = response(monerochan_test::DEFAULT_CORPUS_COUNT.into());
+    stdin.write_vec(body);
+
+    move |mut public| {
+        assert_eq!(public.read::<(u64, u64, u64)>(), expected);
+    }
+}
+
+#[cfg(not(any(feature = "prove", feature = "gpu")))]
+#[test]
+fn test_push_parser_cycle_reduction() {
+    let _lock = monerochan_test::lock_serial();
+    let client = &*monerochan_test::MONEROCHAN_CPU_PROVER;
+
+    let (body, expected) = response(monerochan_test::DEFAULT_CORPUS_COUNT.into());
+    let mut stdin = monerochan::MONEROCHANStdin::new();
+    stdin.write_vec(body);
+
+    let mut cycles = Vec::new();
+    for elf in [monerochan::include_elf!("json_push"), monerochan::include_elf!("json_serde")] {
+        let (mut public, report) = client.execute(elf, &stdin).run().unwrap();
+        assert_eq!(public.read::<(u64, u64, u64)>(), expected);
+        cycles.push(report.total_instruction_count());
+    }
+
+    let (push, serde) = (cycles[0], cycles[1]);
+    println!("push parser: {push} cycles, serde_json: {serde} cycles");
+    assert!(
+        serde >= MIN_CYCLE_REDUCTION * push,
+        "the push parser uses {push} cycles, more than 1/{MIN_CYCLE_REDUCTION} of the {serde} \
+         cycles of serde_json"
+    );
+}
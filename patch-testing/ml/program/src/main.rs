@@ -0,0 +1,7 @@
+#![no_main]
+monerochan_runtime::entrypoint!(main);
+
+/// Runs the model read from the stdin, and commits its digest and output.
+pub fn main() {
+    monerochan_ml::run();
+}
@@ -0,0 +1,69 @@
+/// Returns a small convolutional network with random weights, serialized, and a random input.
+#[cfg(test)]
+fn model() -> (monerochan_ml::Model, Vec<u8>, Vec<i8>) {
+    use monerochan_ml::{kernels, Conv2d, Dense, Layer, MaxPool2d, Model, Requantize};
+    use rand::Rng;
+
+    let mut rng = rand::thread_rng();
+    let mut weights = |len: usize, row_len: usize| {
+        let values = (0..len).map(|_| rng.gen::<i8>()).collect::<Vec<_>>();
+        values.chunks_exact(row_len).flat_map(kernels::pack).collect::<Vec<_>>()
+    };
+
+    // An 8x8 image of 3 channels, convolved to 4 channels, pooled to 4x4 and reduced to 10
+    // outputs.
+    let conv = Conv2d {
+        in_channels: 3,
+        out_channels: 4,
+        kernel: [3, 3],
+        stride: [1, 1],
+        padding: [1, 1],
+        weights: weights(4 * 3 * 3 * 3, 3),
+        bias: vec![64, -64, 0, 128],
+        requantize: Requantize::from_ratio(1.0 / 512.0),
+    };
+    let dense = Dense {
+        inputs: 64,
+        outputs: 10,
+        weights: weights(10 * 64, 64),
+        bias: vec![0; 10],
+        requantize: Requantize::from_ratio(1.0 / 1024.0),
+    };
+    let model = Model {
+        input_shape: vec![8, 8, 3],
+        input_scale: 1.0 / 127.0,
+        output_scale: 1.0 / 127.0,
+        layers: vec![
+            Layer::Conv2d(conv),
+            Layer::Relu,
+            Layer::MaxPool2d(MaxPool2d { kernel: [2, 2], stride: [2, 2] }),
+            Layer::Flatten,
+            Layer::Dense(dense),
+        ],
+    };
+
+    let input = (0..8 * 8 * 3).map(|_| rng.gen::<i8>()).collect();
+    let bytes = bincode::serialize(&model).unwrap();
+    (model, bytes, input)
+}
+
+#[monerochan_test::monerochan_test("ml_program", syscalls = [SHA_COMPRESS, SHA_EXTEND], gpu, prove)]
+fn test_infer_model(
+    stdin: &mut monerochan::MONEROCHANStdin,
+) -> impl FnOnce(monerochan::MONEROCHANPublicValues) {
+    let (model, bytes, input) = model();
+    let expected = monerochan_ml::infer(&model, input.clone()).unwrap();
+    let hash = monerochan_ml::model_hash(&bytes);
+
+    stdin.write_vec(bytes);
+    stdin.write_vec(input.into_iter().map(|x| x as u8).collect());
+
+    move |mut public| {
+        let mut committed = [0u8; 32];
+        public.read_slice(&mut committed);
+        assert_eq!(committed, hash);
+
+        let output = public.read_framed().into_iter().map(|x| x as i8).collect::<Vec<_>>();
+        assert_eq!(output, expected);
+    }
+}
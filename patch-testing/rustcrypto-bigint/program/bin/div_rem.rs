@@ -0,0 +1,20 @@
+#![no_main]
+monerochan_runtime::entrypoint!(main);
+
+use crypto_bigint::{Encoding, NonZero, U256};
+
+pub fn main() {
+    let times = monerochan_lib::io::read::<u8>();
+
+    for _ in 0..times {
+        let x = U256::from_be_slice(&monerochan_lib::io::read_vec());
+        let divisor = U256::from_be_slice(&monerochan_lib::io::read_vec());
+        let divisor = NonZero::new(divisor).unwrap();
+
+        let (quotient, remainder) = x.div_rem(&divisor);
+        assert_eq!(x.rem(&divisor), remainder);
+
+        monerochan_lib::io::commit(&quotient.to_be_bytes().to_vec());
+        monerochan_lib::io::commit(&remainder.to_be_bytes().to_vec());
+    }
+}
@@ -0,0 +1,20 @@
+#![no_main]
+monerochan_runtime::entrypoint!(main);
+
+use crypto_bigint::modular::runtime_mod::{DynResidue, DynResidueParams};
+use crypto_bigint::{Encoding, U256};
+
+pub fn main() {
+    let times = monerochan_lib::io::read::<u8>();
+
+    for _ in 0..times {
+        let base = U256::from_be_slice(&monerochan_lib::io::read_vec());
+        let exponent = U256::from_be_slice(&monerochan_lib::io::read_vec());
+        let modulus = U256::from_be_slice(&monerochan_lib::io::read_vec());
+        let params = DynResidueParams::new(&modulus);
+
+        let result = DynResidue::new(&base, params).pow(&exponent);
+
+        monerochan_lib::io::commit(&result.retrieve().to_be_bytes().to_vec());
+    }
+}
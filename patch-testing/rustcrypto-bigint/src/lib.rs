@@ -77,3 +77,74 @@ pub fn test_bigint_mul_add_residue(
         }
     }
 }
+
+#[monerochan_test::monerochan_test("bigint_test_div_rem", syscalls = [UINT256_MUL], gpu, prove)]
+pub fn test_bigint_div_rem(
+    stdin: &mut monerochan::MONEROCHANStdin,
+) -> impl FnOnce(monerochan::MONEROCHANPublicValues) {
+    use crypto_bigint::{Encoding, NonZero, U256};
+
+    let times: u8 = 255;
+    stdin.write(&times);
+
+    let mut unpatched_results: Vec<(Vec<u8>, Vec<u8>)> = Vec::new();
+
+    while unpatched_results.len() < times as usize {
+        let x = U256::from_be_bytes(rand::random::<[u8; 32]>());
+        // Vary the size of the divisor so that both small and large quotients are covered.
+        let shift = rand::random::<usize>() % 256;
+        let divisor = U256::from_be_bytes(rand::random::<[u8; 32]>()) >> shift;
+        let Some(divisor) = Option::<NonZero<U256>>::from(NonZero::new(divisor)) else {
+            continue;
+        };
+
+        stdin.write_vec(x.to_be_bytes().to_vec());
+        stdin.write_vec(divisor.to_be_bytes().to_vec());
+
+        let (quotient, remainder) = x.div_rem(&divisor);
+        unpatched_results.push((quotient.to_be_bytes().to_vec(), remainder.to_be_bytes().to_vec()));
+    }
+
+    |mut public| {
+        for (quotient, remainder) in unpatched_results {
+            assert_eq!(quotient, public.read::<Vec<u8>>());
+            assert_eq!(remainder, public.read::<Vec<u8>>());
+        }
+    }
+}
+
+#[monerochan_test::monerochan_test("bigint_test_dyn_residue_pow", syscalls = [UINT256_MUL], gpu, prove)]
+pub fn test_bigint_dyn_residue_pow(
+    stdin: &mut monerochan::MONEROCHANStdin,
+) -> impl FnOnce(monerochan::MONEROCHANPublicValues) {
+    use crypto_bigint::modular::runtime_mod::{DynResidue, DynResidueParams};
+    use crypto_bigint::{Encoding, U256};
+
+    let times: u8 = 16;
+    stdin.write(&times);
+
+    let mut unpatched_results: Vec<Vec<u8>> = Vec::new();
+
+    while unpatched_results.len() < times as usize {
+        let base = U256::from_be_bytes(rand::random::<[u8; 32]>());
+        let exponent = U256::from_be_bytes(rand::random::<[u8; 32]>());
+        // Montgomery reduction requires an odd modulus.
+        let modulus = U256::from_be_bytes(rand::random::<[u8; 32]>()) | U256::ONE;
+
+        stdin.write_vec(base.to_be_bytes().to_vec());
+        stdin.write_vec(exponent.to_be_bytes().to_vec());
+        stdin.write_vec(modulus.to_be_bytes().to_vec());
+
+        let params = DynResidueParams::new(&modulus);
+        let result = DynResidue::new(&base, params).pow(&exponent);
+
+        unpatched_results.push(result.retrieve().to_be_bytes().to_vec());
+    }
+
+    |mut public| {
+        for res in unpatched_results {
+            let zk_res = public.read::<Vec<u8>>();
+            assert_eq!(res, zk_res);
+        }
+    }
+}
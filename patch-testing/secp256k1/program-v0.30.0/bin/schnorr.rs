@@ -0,0 +1,23 @@
+#![no_main]
+monerochan_runtime::entrypoint!(main);
+
+use secp256k1::{schnorr::Signature, Secp256k1, XOnlyPublicKey};
+
+pub fn main() {
+    let times = monerochan_runtime::io::read::<u8>();
+
+    for _ in 0..times {
+        monerochan_runtime::io::commit(&inner_verify_schnorr());
+    }
+}
+
+fn inner_verify_schnorr() -> bool {
+    let msg = monerochan_runtime::io::read_vec();
+    let signature = monerochan_runtime::io::read_vec();
+    let pubkey = monerochan_runtime::io::read_vec();
+    let signature = Signature::from_slice(&signature).unwrap();
+    let pubkey = XOnlyPublicKey::from_slice(&pubkey).unwrap();
+    let secp = Secp256k1::verification_only();
+
+    secp.verify_schnorr(&signature, &msg, &pubkey).is_ok()
+}
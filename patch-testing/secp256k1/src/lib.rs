@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod tests {
-    use secp256k1::{Message, PublicKey, Secp256k1};
+    use secp256k1::{Keypair, Message, PublicKey, Secp256k1};
 
     #[monerochan_test::monerochan_test("secp256k1_recover_v0-29-1", syscalls = [SECP256K1_DOUBLE, SECP256K1_ADD], prove)]
     fn test_recover_rand_lte_100(
@@ -103,5 +103,59 @@ mod tests {
             }
         }
     }
+
+    #[monerochan_test::monerochan_test("secp256k1_schnorr_v0-29-1", syscalls = [SECP256K1_DOUBLE, SECP256K1_ADD, SECP256K1_DECOMPRESS], prove)]
+    fn test_schnorr_verify_rand_lte_100(
+        stdin: &mut monerochan::MONEROCHANStdin,
+    ) -> impl FnOnce(monerochan::MONEROCHANPublicValues) {
+        schnorr_verify_rand_lte_100(stdin)
+    }
+
+    #[monerochan_test::monerochan_test("secp256k1_schnorr_v0-30-0", syscalls = [SECP256K1_DOUBLE, SECP256K1_ADD, SECP256K1_DECOMPRESS], prove)]
+    fn test_schnorr_verify_v0_30_0_rand_lte_100(
+        stdin: &mut monerochan::MONEROCHANStdin,
+    ) -> impl FnOnce(monerochan::MONEROCHANPublicValues) {
+        schnorr_verify_rand_lte_100(stdin)
+    }
+
+    fn schnorr_verify_rand_lte_100(
+        stdin: &mut monerochan::MONEROCHANStdin,
+    ) -> impl FnOnce(monerochan::MONEROCHANPublicValues) {
+        let times = 100_u8;
+        stdin.write(&times);
+
+        let secp = Secp256k1::new();
+
+        let mut expected = Vec::with_capacity(times.into());
+        for i in 0..times {
+            let mut rng = rand::thread_rng();
+            let keypair = Keypair::new(&secp, &mut rng);
+            let (public, _) = keypair.x_only_public_key();
+
+            let mut msg = rand::random::<[u8; 32]>();
+
+            let signature = secp.sign_schnorr_with_rng(&Message::from_digest(msg), &keypair, &mut rng);
+
+            // Tamper with every other message so that both outcomes are exercised.
+            if i % 2 == 1 {
+                msg[0] ^= 1;
+            }
+
+            // Verify that the unpatched version of this function verifies as expected.
+            let valid = secp.verify_schnorr(&signature, &Message::from_digest(msg), &public).is_ok();
+            assert_eq!(valid, i % 2 == 0);
+            expected.push(valid);
+
+            stdin.write_vec(msg.to_vec());
+            stdin.write_vec(signature.serialize().to_vec());
+            stdin.write_vec(public.serialize().to_vec());
+        }
+
+        move |mut public| {
+            for valid in expected {
+                assert_eq!(public.read::<bool>(), valid);
+            }
+        }
+    }
 }
 // add cases for fail verify, although its not patched